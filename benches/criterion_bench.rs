@@ -2,6 +2,7 @@ use criterion::{criterion_group, criterion_main, Criterion};
 
 mod allocator;
 mod hash_trie;
+mod hazard_ptr;
 mod mpmc;
 mod mpsc;
 mod spsc;
@@ -27,6 +28,7 @@ criterion_group!(
     mpmc::ncq_enqueue_dequeue,
     mpmc::scq_enqueue_dequeue,
     mpmc::unbounded_enqueue_dequeue,
+    mpmc::unbounded_concurrent_enqueue,
 );
 
 criterion_group!(
@@ -37,10 +39,12 @@ criterion_group!(
     thread_data::storage::trie::gets,
 );
 
+criterion_group!(reclamation, hazard_ptr::reclaim, hazard_ptr::protect_drop);
+
 criterion_group! {
     name = allocator;
     config = Criterion::default().with_profiler(profiler::FlamegraphProfiler::new(100));
     targets = allocator::lrmalloc::allocate_deallocate, allocator::lrmalloc::allocate, allocator::lrmalloc::deallocate, allocator::system_alloc::allocate_deallocate, allocator::system_alloc::allocate, allocator::system_alloc::deallocate,
 }
 
-criterion_main!(queues, maps, thread_data_storage, allocator);
+criterion_main!(queues, maps, thread_data_storage, reclamation, allocator);
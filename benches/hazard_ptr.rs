@@ -0,0 +1,69 @@
+use std::sync::atomic;
+
+use criterion::{BatchSize, Criterion, Throughput};
+use nolock::hazard_ptr;
+
+const RETIRED: usize = 64;
+
+pub fn reclaim(ctx: &mut Criterion) {
+    let mut group = ctx.benchmark_group("hazard_ptr::reclaim");
+
+    group.throughput(Throughput::Elements(RETIRED as u64));
+
+    for records in [1, 16, 64, 256] {
+        group.bench_function(format!("{:03}-records", records), |b| {
+            // Never reclaim automatically, so only the explicit Reclaim is
+            // measured
+            let domain = hazard_ptr::Domain::new(usize::MAX);
+
+            let protected: Vec<_> = (0..records)
+                .map(|i| atomic::AtomicPtr::new(Box::into_raw(Box::new(i))))
+                .collect();
+            let guards: Vec<_> = protected
+                .iter()
+                .map(|ptr| domain.protect(ptr, atomic::Ordering::SeqCst))
+                .collect();
+
+            b.iter_batched(
+                || {
+                    for i in 0..RETIRED {
+                        let ptr = Box::into_raw(Box::new(i));
+                        unsafe {
+                            domain.retire(ptr, |p| {
+                                drop(Box::from_raw(p));
+                            });
+                        }
+                    }
+                },
+                |_| domain.reclaim(),
+                BatchSize::SmallInput,
+            );
+
+            drop(guards);
+            for ptr in protected {
+                drop(unsafe { Box::from_raw(ptr.into_inner()) });
+            }
+        });
+    }
+}
+
+pub fn protect_drop(ctx: &mut Criterion) {
+    let mut group = ctx.benchmark_group("hazard_ptr::protect_drop");
+
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("cycle", |b| {
+        let domain = hazard_ptr::Domain::new(64);
+        let ptr = atomic::AtomicPtr::new(Box::into_raw(Box::new(13usize)));
+
+        // Acquire the first Record outside of the measurement
+        drop(domain.protect(&ptr, atomic::Ordering::SeqCst));
+
+        b.iter(|| {
+            let guard = domain.protect(&ptr, atomic::Ordering::SeqCst);
+            criterion::black_box(&*guard);
+        });
+
+        drop(unsafe { Box::from_raw(ptr.into_inner()) });
+    });
+}
@@ -0,0 +1,139 @@
+//! Exports the Metrics of a Queue and a Hazard-Ptr Domain in the
+//! Prometheus Text-Format, using a minimal Recorder for the `metrics` Facade.
+//!
+//! In a real Service you would install an existing Exporter instead, like
+//! `metrics-exporter-prometheus`, which works the same Way.
+//!
+//! Run using `cargo run --features metrics-facade --example metrics_exporter`
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{atomic, Arc, Mutex},
+};
+
+use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+use nolock::{
+    facade::{MetricNames, QueueRecorder, ReclamationRecorder},
+    hazard_ptr,
+    queues::mpsc::jiffy,
+};
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Counter,
+    Gauge,
+}
+
+/// All the registered Metrics, identified by their Name and rendered Labels
+type Metrics = BTreeMap<(String, String), (Kind, Arc<atomic::AtomicU64>)>;
+
+/// Keeps all the registered Metrics, so they can be rendered later on
+#[derive(Default)]
+struct TextRecorder {
+    metrics: Mutex<Metrics>,
+}
+
+impl TextRecorder {
+    fn register(&self, key: &Key, kind: Kind) -> Arc<atomic::AtomicU64> {
+        let labels: Vec<_> = key
+            .labels()
+            .map(|l| format!("{}=\"{}\"", l.key(), l.value()))
+            .collect();
+
+        let mut metrics = self.metrics.lock().unwrap();
+        let (_, value) = metrics
+            .entry((key.name().to_owned(), labels.join(",")))
+            .or_insert_with(|| (kind, Arc::new(atomic::AtomicU64::new(0))));
+        value.clone()
+    }
+
+    /// Renders all the Metrics in the Prometheus Text-Format
+    fn render(&self) -> String {
+        let mut result = String::new();
+
+        let mut last_name = "";
+        let metrics = self.metrics.lock().unwrap();
+        for ((name, labels), (kind, value)) in metrics.iter() {
+            let value = value.load(atomic::Ordering::Relaxed);
+
+            if name != last_name {
+                let kind = match kind {
+                    Kind::Counter => "counter",
+                    Kind::Gauge => "gauge",
+                };
+                writeln!(result, "# TYPE {} {}", name, kind).unwrap();
+                last_name = name;
+            }
+
+            let labels = if labels.is_empty() {
+                String::new()
+            } else {
+                format!("{{{}}}", labels)
+            };
+            match kind {
+                Kind::Counter => writeln!(result, "{}{} {}", name, labels, value),
+                Kind::Gauge => writeln!(result, "{}{} {}", name, labels, f64::from_bits(value)),
+            }
+            .unwrap();
+        }
+
+        result
+    }
+}
+
+impl Recorder for TextRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.register(key, Kind::Counter))
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.register(key, Kind::Gauge))
+    }
+
+    fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+fn main() {
+    let recorder: &'static TextRecorder = Box::leak(Box::default());
+    metrics::set_global_recorder(recorder).expect("No other Recorder is installed");
+
+    let queue_metrics =
+        QueueRecorder::new(MetricNames::new("nolock_jobs").with_label("queue", "jobs"));
+    let (mut rx, tx) =
+        jiffy::queue_with_metrics::<usize>(jiffy::OrderingMode::Relaxed, Arc::new(queue_metrics));
+    let tx = Arc::new(tx);
+
+    let producers: Vec<_> = (0..4)
+        .map(|_| {
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                for job in 0..1000 {
+                    tx.enqueue(job).unwrap();
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+
+    for _ in 0..2500 {
+        rx.try_dequeue().unwrap();
+    }
+
+    let domain = hazard_ptr::Domain::new(10);
+    let guards: Vec<_> = (0..3).map(|_| domain.empty_guard::<usize>()).collect();
+
+    let reclamation = ReclamationRecorder::new(MetricNames::new("nolock_hazard"));
+    reclamation.record_hazard_domain(&domain);
+    drop(guards);
+
+    print!("{}", recorder.render());
+}
@@ -0,0 +1,363 @@
+//! A Benchmark-Harness that compares the Throughput and Latency of the Queues
+//! in this Crate with some commonly used Baselines and writes the Results as
+//! CSV to stdout.
+//!
+//! Every Run spawns the given Number of Producer-Threads, which all enqueue
+//! their Share of the Elements as fast as possible, while the main Thread
+//! dequeues all of them. The Elements are the Timestamps at which they were
+//! enqueued, which are used to measure the End-to-End Latency of every single
+//! Element.
+//!
+//! # Usage
+//! ```text
+//! cargo run --release --example queue_bench -- [OPTIONS] > results.csv
+//!
+//! Options:
+//!   --producers <LIST>  Comma separated Producer-Counts   [default: 1,2,4,8]
+//!   --ops <N>           Elements per Run                  [default: 1000000]
+//!   --runs <N>          Runs per Configuration            [default: 5]
+//!   --capacity <N>      Capacity for the bounded Queues   [default: 1024]
+//!   --queues <LIST>     Comma separated Queues to run     [default: all]
+//! ```
+//!
+//! The Single-Producer Queues are only measured with a single Producer and
+//! the unbounded MPMC-Queue is only included with the `hyaline` Feature.
+
+use std::{
+    hint::spin_loop,
+    sync::{Arc, Barrier},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "hyaline")]
+use nolock::queues::DequeueError;
+use nolock::queues::{mpmc, mpsc, spsc};
+
+struct Config {
+    producers: Vec<usize>,
+    ops: usize,
+    runs: usize,
+    capacity: usize,
+    queues: Option<Vec<String>>,
+}
+
+impl Config {
+    fn parse() -> Self {
+        let mut config = Self {
+            producers: vec![1, 2, 4, 8],
+            ops: 1_000_000,
+            runs: 5,
+            capacity: 1024,
+            queues: None,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .unwrap_or_else(|| panic!("Missing Value for {:?}", arg));
+
+            match arg.as_str() {
+                "--producers" => {
+                    config.producers = value
+                        .split(',')
+                        .map(|p| p.parse().expect("Invalid Producer-Count"))
+                        .collect();
+                }
+                "--ops" => config.ops = value.parse().expect("Invalid Number of Elements"),
+                "--runs" => config.runs = value.parse().expect("Invalid Number of Runs"),
+                "--capacity" => config.capacity = value.parse().expect("Invalid Capacity"),
+                "--queues" => {
+                    config.queues = Some(value.split(',').map(String::from).collect());
+                }
+                other => panic!("Unknown Option {:?}", other),
+            };
+        }
+
+        config
+    }
+
+    fn enabled(&self, queue: &str) -> bool {
+        match &self.queues {
+            Some(queues) => queues.iter().any(|q| q == queue),
+            None => true,
+        }
+    }
+}
+
+/// The Results of a single Run
+struct Measurement {
+    elapsed: Duration,
+    latencies: Vec<u64>,
+}
+
+impl Measurement {
+    fn percentile(&self, percentile: usize) -> u64 {
+        let index = (self.latencies.len() * percentile / 100).min(self.latencies.len() - 1);
+        self.latencies[index]
+    }
+}
+
+/// Performs a single Run, where `create` is responsible for creating the Queue
+/// and returning one Producer-Handle for every Producer-Thread as well as the
+/// Consumer
+fn measure<P, C, F>(
+    producers: usize,
+    ops: usize,
+    create: &F,
+    send: fn(&mut P, Instant),
+    recv: fn(&mut C) -> Option<Instant>,
+) -> Measurement
+where
+    P: Send + 'static,
+    F: Fn(usize) -> (Vec<P>, C),
+{
+    let per_producer = ops / producers;
+    let total = per_producer * producers;
+
+    let (handles, mut consumer) = create(producers);
+    let barrier = Arc::new(Barrier::new(producers + 1));
+
+    let threads: Vec<_> = handles
+        .into_iter()
+        .map(|mut handle| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                for _ in 0..per_producer {
+                    send(&mut handle, Instant::now());
+                }
+                handle
+            })
+        })
+        .collect();
+
+    let mut latencies = Vec::with_capacity(total);
+
+    barrier.wait();
+    let start = Instant::now();
+    while latencies.len() < total {
+        match recv(&mut consumer) {
+            Some(enqueued) => latencies.push(enqueued.elapsed().as_nanos() as u64),
+            None => spin_loop(),
+        };
+    }
+    let elapsed = start.elapsed();
+
+    // The Producers are only dropped after all the Elements were dequeued,
+    // so that closing the Queue does not influence the Measurement
+    for th in threads {
+        drop(th.join().unwrap());
+    }
+
+    latencies.sort_unstable();
+    Measurement { elapsed, latencies }
+}
+
+/// Runs all the configured Runs for a single Queue and prints a CSV-Row for
+/// every Run
+fn bench<P, C, F>(
+    config: &Config,
+    queue: &str,
+    multi_producer: bool,
+    create: F,
+    send: fn(&mut P, Instant),
+    recv: fn(&mut C) -> Option<Instant>,
+) where
+    P: Send + 'static,
+    F: Fn(usize) -> (Vec<P>, C),
+{
+    if !config.enabled(queue) {
+        return;
+    }
+
+    for &producers in config.producers.iter() {
+        if producers == 0 || (producers > 1 && !multi_producer) {
+            continue;
+        }
+
+        for run in 0..config.runs {
+            let result = measure(producers, config.ops, &create, send, recv);
+
+            let count = result.latencies.len();
+            println!(
+                "{},{},{},{},{},{:.0},{},{},{}",
+                queue,
+                producers,
+                run,
+                count,
+                result.elapsed.as_nanos(),
+                count as f64 / result.elapsed.as_secs_f64(),
+                result.percentile(50),
+                result.percentile(99),
+                result.latencies[count - 1],
+            );
+        }
+    }
+}
+
+/// Attempts to enqueue the Data into a bounded Queue until it succeeds
+fn spin_enqueue<T, E>(mut data: T, mut try_enqueue: impl FnMut(T) -> Result<(), (T, E)>) {
+    loop {
+        match try_enqueue(data) {
+            Ok(_) => return,
+            Err((d, _)) => {
+                data = d;
+                spin_loop();
+            }
+        };
+    }
+}
+
+fn shared<T>(producers: usize, value: T) -> Vec<Arc<T>> {
+    let value = Arc::new(value);
+    (0..producers).map(|_| value.clone()).collect()
+}
+
+fn main() {
+    let config = Config::parse();
+    let capacity = config.capacity;
+
+    println!("queue,producers,run,elements,elapsed_ns,throughput_per_sec,latency_p50_ns,latency_p99_ns,latency_max_ns");
+
+    bench(
+        &config,
+        "spsc-unbounded",
+        false,
+        |_| {
+            let (rx, tx) = spsc::unbounded::queue();
+            (vec![tx], rx)
+        },
+        |tx, data| tx.enqueue(data).unwrap(),
+        |rx| rx.try_dequeue().ok(),
+    );
+    bench(
+        &config,
+        "spsc-bounded",
+        false,
+        |_| {
+            let (rx, tx) = spsc::bounded::queue(capacity);
+            (vec![tx], rx)
+        },
+        |tx, data| spin_enqueue(data, |d| tx.try_enqueue(d)),
+        |rx| rx.try_dequeue().ok(),
+    );
+    bench(
+        &config,
+        "mpsc-jiffy",
+        true,
+        |producers| {
+            let (rx, tx) = mpsc::jiffy::queue();
+            (shared(producers, tx), rx)
+        },
+        |tx, data| tx.enqueue(data).unwrap(),
+        |rx| rx.try_dequeue().ok(),
+    );
+    bench(
+        &config,
+        "mpmc-ncq",
+        true,
+        |producers| {
+            let (rx, tx) = mpmc::bounded::ncq::queue(capacity);
+            (shared(producers, tx), rx)
+        },
+        |tx, data| spin_enqueue(data, |d| tx.try_enqueue(d).map_err(|(e, d)| (d, e))),
+        |rx| rx.try_dequeue().ok(),
+    );
+    bench(
+        &config,
+        "mpmc-scq",
+        true,
+        |producers| {
+            let (rx, tx) = mpmc::bounded::scq::queue(capacity);
+            (shared(producers, tx), rx)
+        },
+        |tx, data| spin_enqueue(data, |d| tx.try_enqueue(d).map_err(|(e, d)| (d, e))),
+        |rx| rx.try_dequeue().ok(),
+    );
+    #[cfg(feature = "hyaline")]
+    bench(
+        &config,
+        "mpmc-unbounded",
+        true,
+        |producers| {
+            let (rx, tx) = mpmc::unbounded::queue();
+            (shared(producers, tx), rx)
+        },
+        |tx, data| tx.enqueue(data).unwrap(),
+        |rx| match rx.try_dequeue() {
+            Ok(d) => Some(d),
+            Err(DequeueError::Empty) => None,
+            Err(e) => panic!("{:?}", e),
+        },
+    );
+
+    // The Baselines
+    bench(
+        &config,
+        "std-mpsc",
+        true,
+        |producers| {
+            let (tx, rx) = std::sync::mpsc::channel();
+            ((0..producers).map(|_| tx.clone()).collect(), rx)
+        },
+        |tx, data| tx.send(data).unwrap(),
+        |rx| rx.try_recv().ok(),
+    );
+    bench(
+        &config,
+        "std-sync-channel",
+        true,
+        |producers| {
+            let (tx, rx) = std::sync::mpsc::sync_channel(capacity);
+            ((0..producers).map(|_| tx.clone()).collect(), rx)
+        },
+        |tx, data| tx.send(data).unwrap(),
+        |rx| rx.try_recv().ok(),
+    );
+    bench(
+        &config,
+        "crossbeam-channel-unbounded",
+        true,
+        |producers| {
+            let (tx, rx) = crossbeam_channel::unbounded();
+            ((0..producers).map(|_| tx.clone()).collect(), rx)
+        },
+        |tx, data| tx.send(data).unwrap(),
+        |rx| rx.try_recv().ok(),
+    );
+    bench(
+        &config,
+        "crossbeam-channel-bounded",
+        true,
+        |producers| {
+            let (tx, rx) = crossbeam_channel::bounded(capacity);
+            ((0..producers).map(|_| tx.clone()).collect(), rx)
+        },
+        |tx, data| tx.send(data).unwrap(),
+        |rx| rx.try_recv().ok(),
+    );
+    bench(
+        &config,
+        "crossbeam-segqueue",
+        true,
+        |producers| {
+            let queue = Arc::new(crossbeam_queue::SegQueue::new());
+            ((0..producers).map(|_| queue.clone()).collect(), queue)
+        },
+        |queue, data| queue.push(data),
+        |queue| queue.pop(),
+    );
+    bench(
+        &config,
+        "crossbeam-arrayqueue",
+        true,
+        |producers| {
+            let queue = Arc::new(crossbeam_queue::ArrayQueue::new(capacity));
+            ((0..producers).map(|_| queue.clone()).collect(), queue)
+        },
+        |queue, data| spin_enqueue(data, |d| queue.push(d).map_err(|d| (d, ()))),
+        |queue| queue.pop(),
+    );
+}
@@ -0,0 +1,241 @@
+//! A Benchmark-Harness that measures how fairly the bounded MPMC-Queues
+//! distribute the Enqueues between competing Producers and writes the Results
+//! as CSV to stdout.
+//!
+//! Every Run spawns the given Number of Producer-Threads, which all enqueue as
+//! many Elements as possible for a fixed Duration, while the main Thread
+//! dequeues them. Afterwards a CSV-Row is printed for every Producer, with the
+//! Number of Elements it enqueued, its Share of all the Enqueues and the
+//! Jain-Fairness-Index of the entire Run, which is 1.0 if all the Producers
+//! enqueued the same Number of Elements and approaches `1 / producers` if a
+//! single Producer enqueued everything.
+//!
+//! # Usage
+//! ```text
+//! cargo run --release --example queue_fairness -- [OPTIONS] > fairness.csv
+//!
+//! Options:
+//!   --producers <LIST>  Comma separated Producer-Counts   [default: 2,4,8,16]
+//!   --duration <MS>     Duration of every Run in ms       [default: 500]
+//!   --runs <N>          Runs per Configuration            [default: 3]
+//!   --capacity <N>      Capacity of the Queues            [default: 64]
+//!   --queues <LIST>     Comma separated Queues to run     [default: all]
+//! ```
+
+use std::{
+    hint::spin_loop,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Barrier,
+    },
+    thread,
+    time::Duration,
+};
+
+use nolock::queues::mpmc;
+
+struct Config {
+    producers: Vec<usize>,
+    duration: Duration,
+    runs: usize,
+    capacity: usize,
+    queues: Option<Vec<String>>,
+}
+
+impl Config {
+    fn parse() -> Self {
+        let mut config = Self {
+            producers: vec![2, 4, 8, 16],
+            duration: Duration::from_millis(500),
+            runs: 3,
+            capacity: 64,
+            queues: None,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .unwrap_or_else(|| panic!("Missing Value for {:?}", arg));
+
+            match arg.as_str() {
+                "--producers" => {
+                    config.producers = value
+                        .split(',')
+                        .map(|p| p.parse().expect("Invalid Producer-Count"))
+                        .collect();
+                }
+                "--duration" => {
+                    config.duration =
+                        Duration::from_millis(value.parse().expect("Invalid Duration"));
+                }
+                "--runs" => config.runs = value.parse().expect("Invalid Number of Runs"),
+                "--capacity" => config.capacity = value.parse().expect("Invalid Capacity"),
+                "--queues" => {
+                    config.queues = Some(value.split(',').map(String::from).collect());
+                }
+                other => panic!("Unknown Option {:?}", other),
+            };
+        }
+
+        config
+    }
+
+    fn enabled(&self, queue: &str) -> bool {
+        match &self.queues {
+            Some(queues) => queues.iter().any(|q| q == queue),
+            None => true,
+        }
+    }
+}
+
+/// Performs a single Run and returns the Number of Elements every Producer
+/// enqueued
+fn measure<P, C, F>(
+    producers: usize,
+    duration: Duration,
+    create: &F,
+    try_send: fn(&P) -> bool,
+    try_recv: fn(&C) -> bool,
+) -> Vec<u64>
+where
+    P: Send + 'static,
+    F: Fn(usize) -> (Vec<P>, C),
+{
+    let (handles, consumer) = create(producers);
+    let barrier = Arc::new(Barrier::new(producers + 1));
+    let running = Arc::new(AtomicBool::new(true));
+
+    let threads: Vec<_> = handles
+        .into_iter()
+        .map(|handle| {
+            let barrier = barrier.clone();
+            let running = running.clone();
+            thread::spawn(move || {
+                barrier.wait();
+
+                let mut enqueued = 0;
+                while running.load(Ordering::Relaxed) {
+                    if try_send(&handle) {
+                        enqueued += 1;
+                    } else {
+                        spin_loop();
+                    }
+                }
+                (handle, enqueued)
+            })
+        })
+        .collect();
+
+    barrier.wait();
+    let stop = std::time::Instant::now() + duration;
+    while std::time::Instant::now() < stop {
+        if !try_recv(&consumer) {
+            spin_loop();
+        }
+    }
+    running.store(false, Ordering::Relaxed);
+
+    // The Producers are only dropped after all of them stopped, so that
+    // closing the Queue does not influence the Measurement
+    let results: Vec<_> = threads.into_iter().map(|th| th.join().unwrap()).collect();
+    results.into_iter().map(|(_, enqueued)| enqueued).collect()
+}
+
+/// Calculates the Jain-Fairness-Index for the given Counts
+fn jain_index(counts: &[u64]) -> f64 {
+    let sum: f64 = counts.iter().map(|c| *c as f64).sum();
+    let squares: f64 = counts.iter().map(|c| (*c as f64) * (*c as f64)).sum();
+    if squares == 0.0 {
+        return 1.0;
+    }
+
+    (sum * sum) / (counts.len() as f64 * squares)
+}
+
+/// Runs all the configured Runs for a single Queue and prints a CSV-Row for
+/// every Producer in every Run
+fn bench<P, C, F>(
+    config: &Config,
+    queue: &str,
+    create: F,
+    try_send: fn(&P) -> bool,
+    try_recv: fn(&C) -> bool,
+) where
+    P: Send + 'static,
+    F: Fn(usize) -> (Vec<P>, C),
+{
+    if !config.enabled(queue) {
+        return;
+    }
+
+    for &producers in config.producers.iter() {
+        if producers == 0 {
+            continue;
+        }
+
+        for run in 0..config.runs {
+            let counts = measure(producers, config.duration, &create, try_send, try_recv);
+
+            let total: u64 = counts.iter().sum();
+            let fairness = jain_index(&counts);
+            for (producer, count) in counts.iter().enumerate() {
+                println!(
+                    "{},{},{},{},{},{:.4},{:.4}",
+                    queue,
+                    producers,
+                    run,
+                    producer,
+                    count,
+                    *count as f64 / total.max(1) as f64,
+                    fairness,
+                );
+            }
+        }
+    }
+}
+
+fn shared<T>(producers: usize, value: T) -> Vec<Arc<T>> {
+    let value = Arc::new(value);
+    (0..producers).map(|_| value.clone()).collect()
+}
+
+fn main() {
+    let config = Config::parse();
+    let capacity = config.capacity;
+
+    println!("queue,producers,run,producer,enqueued,share,jain_index");
+
+    bench(
+        &config,
+        "mpmc-scq",
+        |producers| {
+            let (rx, tx) = mpmc::bounded::scq::queue::<u64>(capacity);
+            (shared(producers, tx), rx)
+        },
+        |tx| tx.try_enqueue(13).is_ok(),
+        |rx| rx.try_dequeue().is_ok(),
+    );
+    bench(
+        &config,
+        "mpmc-ncq",
+        |producers| {
+            let (rx, tx) = mpmc::bounded::ncq::queue::<u64>(capacity);
+            (shared(producers, tx), rx)
+        },
+        |tx| tx.try_enqueue(13).is_ok(),
+        |rx| rx.try_dequeue().is_ok(),
+    );
+
+    // The Baseline
+    bench(
+        &config,
+        "crossbeam-arrayqueue",
+        |producers| {
+            let queue = Arc::new(crossbeam_queue::ArrayQueue::<u64>::new(capacity));
+            ((0..producers).map(|_| queue.clone()).collect(), queue)
+        },
+        |queue| queue.push(13).is_ok(),
+        |queue| queue.pop().is_some(),
+    );
+}
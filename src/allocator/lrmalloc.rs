@@ -9,14 +9,57 @@
 //! The Heap is the central shared entity, which actually manages the underlying allocations
 //! as well as the needed synchronization between different threads.
 //!
+//! ## Remote Frees
+//! Every Superblock is owned by the Thread whose Cache last received Blocks
+//! from it. Blocks freed by any other Thread are not put into that Thread's
+//! Cache, but collected in a Stack on the Descriptor of their Superblock and
+//! only returned to the Superblock once a whole Batch has been freed. This
+//! way Workloads, where one Thread allocates and another one frees, like
+//! Producers and Consumers, only need to update the shared State of a
+//! Superblock once per Batch. The Blocks of incomplete Batches are returned
+//! when the Allocator is [trimmed](#trimming).
+//!
+//! ## Large Allocations
+//! Allocations that are larger than the biggest Size-Class, or that need a
+//! larger Alignment than the 256 Bytes guaranteed for every Block (like
+//! SIMD-Buffers or Page-aligned Memory), bypass the Thread-Caches and
+//! Size-Classes entirely and instead get their own dedicated Superblock from
+//! the System-Allocator. The Descriptor of that Superblock records the exact
+//! Layout it was allocated with, which is then used to free it again.
+//!
+//! ## Superblocks
+//! All the Superblocks are allocated using a
+//! [`SegmentAlloc`](crate::utils::SegmentAlloc), which is the
+//! System-Allocator by default, but can be replaced using
+//! [`Allocator::with_segment_alloc`] to control where they are placed, for
+//! example on a specific NUMA-Node.
+//!
+//! ## Trimming
+//! The Blocks held by the Thread-Caches keep their Superblocks alive, even
+//! if the Program itself no longer uses any Memory from them. Using
+//! [`Allocator::trim`] every Thread returns the Blocks in its Cache to the
+//! Heap, which frees every Superblock that becomes completely empty. The
+//! calling Thread does this immediately, all the other Threads once they
+//! next allocate or free any Memory, because their Caches can only be
+//! accessed safely by themselves.
+//!
+//! ## Usable Size
+//! Every Allocation is rounded up to the Block-Size of its Size-Class, so
+//! there is usually some Slack after the requested Size. Using
+//! [`Allocator::usable_size`] and [`Allocator::allocated_size`] Callers can
+//! find out how much Memory they actually got and make use of the Slack.
+//!
 //! # References
 //! * [Paper - 'LRMalloc: a Modern and Competitive Lock-Free Dynamic Memory Allocator'](https://vecpar2018.ncc.unesp.br/wp-content/uploads/2018/09/VECPAR_2018_paper_27.pdf)
 
 use std::{
     alloc::{handle_alloc_error, GlobalAlloc},
     cell::RefCell,
+    sync::atomic,
 };
 
+use crate::utils::SegmentAlloc;
+
 mod util;
 
 mod cache;
@@ -30,6 +73,9 @@ use pagemap::PageMap;
 mod descriptor;
 
 static PAGEMAP: PageMap = PageMap::new();
+/// Incremented for every requested Trim, every Thread flushes its Cache once
+/// it observes a new Epoch
+static TRIM_EPOCH: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
 
 thread_local! {
     static CACHE: RefCell<Cache> = RefCell::new(Cache::new());
@@ -49,7 +95,29 @@ impl Allocator {
     /// independant of each other.
     /// You should only create a single Instance for use as the Global-Allocator of your program
     pub const fn new() -> Self {
-        Self { heap: Heap::new() }
+        Self::with_segment_alloc(&std::alloc::System)
+    }
+
+    /// Creates a new Instance of the Allocator, like [`new`](Self::new), that
+    /// allocates all its Superblocks using the given [`SegmentAlloc`] instead
+    /// of the System-Allocator, for example to place them on a specific
+    /// NUMA-Node
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::allocator::lrmalloc::Allocator;
+    /// # use std::alloc::{GlobalAlloc, Layout};
+    /// static ALLOCATOR: Allocator = Allocator::with_segment_alloc(&std::alloc::System);
+    ///
+    /// let layout = Layout::new::<u64>();
+    /// let ptr = unsafe { ALLOCATOR.alloc(layout) };
+    /// assert!(!ptr.is_null());
+    /// unsafe { ALLOCATOR.dealloc(ptr, layout) };
+    /// ```
+    pub const fn with_segment_alloc(segments: &'static dyn SegmentAlloc) -> Self {
+        Self {
+            heap: Heap::new(segments),
+        }
     }
 
     /// Allocates Memory for the given Layout using this allocator
@@ -57,6 +125,10 @@ impl Allocator {
     /// # Safety
     /// The caller needs to ensure that the given Memory Layout is valid
     pub unsafe fn allocate(&self, layout: std::alloc::Layout) -> *mut u8 {
+        if layout.align() > size_classes::BLOCK_ALIGN {
+            return self.heap.allocate_large(layout, &PAGEMAP);
+        }
+
         let size_class = match size_classes::get_size_class_index(layout.size()) {
             Some(s) => s,
             None => {
@@ -71,6 +143,7 @@ impl Allocator {
                     handle_alloc_error(layout);
                 }
             };
+            self.apply_trim(&mut cache);
 
             if let Some(ptr) = cache.try_alloc(size_class) {
                 return ptr;
@@ -86,7 +159,7 @@ impl Allocator {
     /// # Safety
     /// The Caller needs to ensure that the given Ptr was given out by this allocator and the given
     /// Layout matches the layout that was used when obtaining the Ptr
-    pub unsafe fn deallocate(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+    pub unsafe fn deallocate(&self, ptr: *mut u8, _layout: std::alloc::Layout) {
         let desc_ptr = match PAGEMAP.load_descriptor(ptr) {
             Some(ptr) => ptr,
             None => {
@@ -98,13 +171,19 @@ impl Allocator {
         let size_class = match desc.size_class() {
             Some(s) => s,
             None => {
-                self.heap.free_large(ptr, layout, &PAGEMAP);
+                self.heap.free_large(ptr, &PAGEMAP);
                 return;
             }
         };
 
         CACHE.with(|raw| {
             let mut cache = raw.borrow_mut();
+            self.apply_trim(&mut cache);
+
+            if !desc.is_owner(cache.id()) {
+                self.heap.free_remote(desc_ptr, ptr, &PAGEMAP);
+                return;
+            }
 
             if cache.add_block(size_class, ptr).is_err() {
                 self.heap.flush_cache(&mut cache, size_class, &PAGEMAP);
@@ -112,6 +191,123 @@ impl Allocator {
             };
         });
     }
+
+    /// Returns the Number of Bytes that can actually be used at the given
+    /// Ptr, similar to `malloc_usable_size`.
+    ///
+    /// Every Allocation is rounded up to the Block-Size of its Size-Class, so
+    /// this is usually larger than the Size that was requested and the
+    /// Caller may use all of these Bytes, without having to reallocate.
+    ///
+    /// # Safety
+    /// The Ptr needs to have been returned by this Allocator and must not
+    /// have been deallocated yet
+    ///
+    /// # Panics
+    /// If the Ptr was not allocated with this Allocator
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::allocator::lrmalloc::Allocator;
+    /// # use std::alloc::{GlobalAlloc, Layout};
+    /// let allocator = Allocator::new();
+    ///
+    /// let layout = Layout::from_size_align(100, 8).unwrap();
+    /// let ptr = unsafe { allocator.alloc(layout) };
+    ///
+    /// let usable = unsafe { allocator.usable_size(ptr) };
+    /// assert!(usable >= layout.size());
+    /// assert_eq!(allocator.allocated_size(layout), usable);
+    ///
+    /// // The Slack after the requested Size can be used as well
+    /// unsafe { ptr.write_bytes(0xab, usable) };
+    /// unsafe { allocator.dealloc(ptr, layout) };
+    /// ```
+    pub unsafe fn usable_size(&self, ptr: *mut u8) -> usize {
+        let desc_ptr = match PAGEMAP.load_descriptor(ptr) {
+            Some(ptr) => ptr,
+            None => {
+                panic!("PTR was not allocated with this allocator");
+            }
+        };
+        let desc = unsafe { &*desc_ptr };
+
+        desc.block_size()
+    }
+
+    /// Returns the Number of Bytes that are actually reserved for an
+    /// Allocation with the given Layout, which is the same as the
+    /// [`usable_size`](Self::usable_size) of the resulting Ptr
+    pub fn allocated_size(&self, layout: std::alloc::Layout) -> usize {
+        // Large Allocations get a Superblock of exactly their Size, which
+        // needs to be at least one Byte
+        if layout.align() > size_classes::BLOCK_ALIGN {
+            return layout.size().max(1);
+        }
+
+        match size_classes::get_size_class_index(layout.size()) {
+            Some(size_class) => size_classes::get_block_size(size_class),
+            None => layout.size().max(1),
+        }
+    }
+
+    /// Returns all the Blocks in the Cache of the current Thread to the Heap
+    /// and frees the Superblocks that became empty because of it.
+    ///
+    /// # Returns
+    /// The Number of Bytes that were released back to the System
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::allocator::lrmalloc::Allocator;
+    /// # use std::alloc::{GlobalAlloc, Layout};
+    /// let allocator = Allocator::new();
+    ///
+    /// let layout = Layout::new::<u64>();
+    /// let ptr = unsafe { allocator.alloc(layout) };
+    /// unsafe { allocator.dealloc(ptr, layout) };
+    ///
+    /// // The Block is still cached by the current Thread and keeps its
+    /// // Superblock alive until the Cache is trimmed
+    /// assert!(allocator.trim_thread() > 0);
+    /// assert_eq!(0, allocator.trim_thread());
+    /// ```
+    pub fn trim_thread(&self) -> usize {
+        CACHE.with(|raw| match raw.try_borrow_mut() {
+            Ok(mut cache) => self.heap.flush_all(&mut cache, &PAGEMAP),
+            Err(_) => 0,
+        })
+    }
+
+    /// Trims the Caches of all the Threads, like [`trim_thread`](Self::trim_thread).
+    ///
+    /// Only the Cache of the current Thread is trimmed immediately, every
+    /// other Thread trims its Cache the next Time it allocates or frees any
+    /// Memory, as the Caches can not be accessed from other Threads.
+    ///
+    /// # Returns
+    /// The Number of Bytes that were released back to the System by trimming
+    /// the Cache of the current Thread
+    pub fn trim(&self) -> usize {
+        let epoch = TRIM_EPOCH.fetch_add(1, atomic::Ordering::Relaxed) + 1;
+
+        CACHE.with(|raw| match raw.try_borrow_mut() {
+            Ok(mut cache) => {
+                cache.update_trim_epoch(epoch);
+                self.heap.flush_all(&mut cache, &PAGEMAP)
+            }
+            Err(_) => 0,
+        })
+    }
+
+    /// Flushes the given Cache, if a Trim was requested since it was last
+    /// flushed
+    fn apply_trim(&self, cache: &mut Cache) {
+        let epoch = TRIM_EPOCH.load(atomic::Ordering::Relaxed);
+        if cache.update_trim_epoch(epoch) {
+            self.heap.flush_all(cache, &PAGEMAP);
+        }
+    }
 }
 
 unsafe impl GlobalAlloc for Allocator {
@@ -14,6 +14,8 @@ const STACK_SIZE: usize = 32;
 pub struct Cache {
     /// Holds a Stack for all the SizeClasses used by the Allocator
     stacks: [Stack<u8, STACK_SIZE>; size_classes::size_class_count()],
+    /// The last Trim-Epoch that was applied to this Cache
+    trim_epoch: usize,
 }
 
 impl Cache {
@@ -21,9 +23,24 @@ impl Cache {
     pub const fn new() -> Self {
         Self {
             stacks: [Stack::new(); size_classes::size_class_count()],
+            trim_epoch: 0,
         }
     }
 
+    /// Updates the Trim-Epoch of the Cache and returns `true` if it changed,
+    /// in which case the Cache should be flushed
+    pub fn update_trim_epoch(&mut self, epoch: usize) -> bool {
+        let changed = self.trim_epoch != epoch;
+        self.trim_epoch = epoch;
+        changed
+    }
+
+    /// An ID that is unique for every Cache that currently exists, which is
+    /// used to track the Owner of a Superblock
+    pub fn id(&self) -> usize {
+        self as *const Self as usize
+    }
+
     /// Gets the fixed size of the Stacks used by the Cache
     pub const fn get_stack_size() -> usize {
         STACK_SIZE
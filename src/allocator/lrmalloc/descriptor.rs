@@ -7,6 +7,9 @@ use anchor::AtomicAnchor;
 mod anchor_state;
 pub use anchor_state::AnchorState;
 
+mod remote;
+pub use remote::{RemoteFrees, RemotePush, RemoteTake};
+
 /// A Descriptor stores all the needed information about any single Superblock
 #[derive(Debug)]
 pub struct Descriptor {
@@ -19,11 +22,19 @@ pub struct Descriptor {
     block_size: usize,
     /// The Maximum number of blocks contained in the Superblock
     max_count: usize,
+    /// The Alignment with which the Superblock itself was allocated
+    align: usize,
     /// The Size-Class of this Superblock, this is only set if the superblock
     /// is allocated for a given SizeClass
     size_class: Option<usize>,
     /// The Range of addresses that belong to this Superblock
     ptr_range: RangeInclusive<usize>,
+    /// Identifies the Thread-Cache that last received Blocks from this
+    /// Superblock, all other Threads free their Blocks into the
+    /// [`RemoteFrees`] instead
+    owner: atomic::AtomicUsize,
+    /// The Blocks that were freed by Threads other than the Owner
+    remote: RemoteFrees,
 }
 
 impl Descriptor {
@@ -31,6 +42,7 @@ impl Descriptor {
     pub fn new(
         block_size: usize,
         max_count: usize,
+        align: usize,
         size_class: Option<usize>,
         super_block: *mut u8,
     ) -> Self {
@@ -42,8 +54,11 @@ impl Descriptor {
             super_block,
             block_size,
             max_count,
+            align,
             size_class,
             ptr_range: (lower_bound..=upper_bound),
+            owner: atomic::AtomicUsize::new(0),
+            remote: RemoteFrees::new(),
         }
     }
 
@@ -59,6 +74,26 @@ impl Descriptor {
     pub fn superblock_ptr(&self) -> *mut u8 {
         self.super_block
     }
+    /// The Layout that was used to allocate the Superblock and therefore
+    /// also needs to be used when freeing it again
+    pub fn superblock_layout(&self) -> std::alloc::Layout {
+        std::alloc::Layout::from_size_align(self.block_size * self.max_count, self.align)
+            .expect("The Layout was already valid when allocating the Superblock")
+    }
+    pub fn remote(&self) -> &RemoteFrees {
+        &self.remote
+    }
+
+    /// Checks if the Thread-Cache with the given ID owns the Superblock
+    pub fn is_owner(&self, owner: usize) -> bool {
+        self.owner.load(atomic::Ordering::Relaxed) == owner
+    }
+    /// Marks the Thread-Cache with the given ID as the Owner of the
+    /// Superblock, because it received Blocks from it
+    pub fn set_owner(&self, owner: usize) {
+        self.owner.store(owner, atomic::Ordering::Relaxed);
+    }
+
     pub fn anchor(&self) -> Anchor {
         self.anchor.load(atomic::Ordering::Acquire)
     }
@@ -97,7 +132,7 @@ mod tests {
 
     #[test]
     fn contains() {
-        let descriptor = Descriptor::new(0x8, 2, Some(0), 0xff as *mut u8);
+        let descriptor = Descriptor::new(0x8, 2, 8, Some(0), 0xff as *mut u8);
 
         assert_eq!(false, descriptor.contains(0xf0 as *mut u8));
         assert_eq!(true, descriptor.contains(0xff as *mut u8));
@@ -105,9 +140,18 @@ mod tests {
         assert_eq!(false, descriptor.contains((0xff + 0x8 * 2) as *mut u8));
     }
 
+    #[test]
+    fn superblock_layout() {
+        let descriptor = Descriptor::new(0x8, 2, 64, None, 0x100 as *mut u8);
+
+        let layout = descriptor.superblock_layout();
+        assert_eq!(16, layout.size());
+        assert_eq!(64, layout.align());
+    }
+
     #[test]
     fn calc_index() {
-        let descriptor = Descriptor::new(0x8, 2, Some(0), 0xff as *mut u8);
+        let descriptor = Descriptor::new(0x8, 2, 8, Some(0), 0xff as *mut u8);
 
         assert_eq!(0, descriptor.calc_index(0xff as *mut u8));
         assert_eq!(2, descriptor.calc_index((0xff + 0x8 * 2) as *mut u8));
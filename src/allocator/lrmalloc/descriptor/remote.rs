@@ -0,0 +1,239 @@
+use std::{fmt::Debug, sync::atomic};
+
+/// The Number of remotely freed Blocks that are collected, before they are
+/// returned to the Superblock together
+pub const REMOTE_BATCH: usize = 8;
+
+/// The Bits of the Head that store the Number of Blocks in the Stack, this
+/// works because every Block is aligned to at least 256 Bytes
+const COUNT_MASK: usize = 0b0011_1111;
+/// Set while the Descriptor is in the Heap's List of Descriptors with
+/// pending remote Frees
+const REGISTERED: usize = 0b0100_0000;
+/// Set if the Descriptor should be retired, once it is removed from the List
+/// of pending remote Frees
+const DEAD: usize = 0b1000_0000;
+const TAG_MASK: usize = COUNT_MASK | REGISTERED | DEAD;
+
+/// The Result of pushing a Block onto the [`RemoteFrees`]
+#[derive(Debug, PartialEq)]
+pub enum RemotePush {
+    /// The Block was added to the Stack, if `register` is set the Descriptor
+    /// needs to be added to the List of pending remote Frees
+    Pending { register: bool },
+    /// The Stack reached [`REMOTE_BATCH`] Blocks and was taken by the Caller,
+    /// which now needs to return all the Blocks to the Superblock
+    Batch { head: *mut u8, count: u32 },
+}
+
+/// The Result of taking all the Blocks from the [`RemoteFrees`]
+#[derive(Debug, PartialEq)]
+pub enum RemoteTake {
+    /// The Stack contained the linked List of Blocks starting at `head`
+    Blocks { head: *mut u8, count: u32 },
+    /// The Stack was empty
+    Empty,
+    /// The Descriptor was retired while it was registered and can now be
+    /// recycled
+    Dead,
+}
+
+/// A Stack of Blocks, that were freed by Threads other than the one owning
+/// the Superblock.
+///
+/// The Blocks are linked through their first Word and the Head also stores
+/// the Number of Blocks in the Stack, so that a full Batch can be detached
+/// with the same CAS that would have pushed the last Block. Blocks are only
+/// ever pushed or taken all at once, so the Stack is not affected by ABA.
+pub struct RemoteFrees {
+    head: atomic::AtomicUsize,
+}
+
+impl RemoteFrees {
+    pub const fn new() -> Self {
+        Self {
+            head: atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Pushes the Block onto the Stack or detaches the whole Stack together
+    /// with the Block, if this completes a Batch
+    pub fn push(&self, block: *mut u8) -> RemotePush {
+        debug_assert_eq!(0, block as usize & TAG_MASK);
+
+        let mut current = self.head.load(atomic::Ordering::Acquire);
+        loop {
+            debug_assert_eq!(0, current & DEAD);
+
+            let count = current & COUNT_MASK;
+            let registered = current & REGISTERED;
+            unsafe { (block as *mut usize).write(current & !TAG_MASK) };
+
+            let (new, result) = if count + 1 >= REMOTE_BATCH {
+                (
+                    registered,
+                    RemotePush::Batch {
+                        head: block,
+                        count: (count + 1) as u32,
+                    },
+                )
+            } else {
+                (
+                    block as usize | (count + 1) | REGISTERED,
+                    RemotePush::Pending {
+                        register: registered == 0,
+                    },
+                )
+            };
+
+            match self.head.compare_exchange_weak(
+                current,
+                new,
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+            ) {
+                Ok(_) => return result,
+                Err(n) => current = n,
+            }
+        }
+    }
+
+    /// Takes all the Blocks from the Stack, after the Descriptor was removed
+    /// from the List of pending remote Frees
+    pub fn take(&self) -> RemoteTake {
+        let current = self.head.swap(0, atomic::Ordering::AcqRel);
+        if current & DEAD != 0 {
+            return RemoteTake::Dead;
+        }
+
+        let head = (current & !TAG_MASK) as *mut u8;
+        if head.is_null() {
+            return RemoteTake::Empty;
+        }
+
+        RemoteTake::Blocks {
+            head,
+            count: (current & COUNT_MASK) as u32,
+        }
+    }
+
+    /// Attempts to retire the Descriptor of the empty Superblock, returns
+    /// `false` if it is still in the List of pending remote Frees, in which
+    /// case it is retired once it is removed from there
+    pub fn try_retire(&self) -> bool {
+        let previous = self.head.fetch_or(DEAD, atomic::Ordering::AcqRel);
+        debug_assert_eq!(0, previous & COUNT_MASK);
+
+        if previous & REGISTERED != 0 {
+            return false;
+        }
+
+        self.head.store(0, atomic::Ordering::Release);
+        true
+    }
+}
+
+impl Debug for RemoteFrees {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let head = self.head.load(atomic::Ordering::Acquire);
+        write!(f, "RemoteFrees ( count = {} )", head & COUNT_MASK)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(align(256))]
+    struct Block {
+        _data: [u8; 256],
+    }
+
+    #[test]
+    fn push_batch() {
+        let remote = RemoteFrees::new();
+        let mut blocks: Vec<_> = (0..REMOTE_BATCH)
+            .map(|_| Block { _data: [0; 256] })
+            .collect();
+        let ptrs: Vec<_> = blocks
+            .iter_mut()
+            .map(|b| b as *mut Block as *mut u8)
+            .collect();
+
+        assert_eq!(RemotePush::Pending { register: true }, remote.push(ptrs[0]));
+        for ptr in &ptrs[1..REMOTE_BATCH - 1] {
+            assert_eq!(RemotePush::Pending { register: false }, remote.push(*ptr));
+        }
+
+        let head = ptrs[REMOTE_BATCH - 1];
+        assert_eq!(
+            RemotePush::Batch {
+                head,
+                count: REMOTE_BATCH as u32
+            },
+            remote.push(head)
+        );
+
+        // The Blocks are linked in the reverse Order they were pushed in
+        let mut current = head;
+        for ptr in ptrs.iter().rev() {
+            assert_eq!(*ptr, current);
+            current = unsafe { (current as *mut *mut u8).read() };
+        }
+        assert!(current.is_null());
+
+        // The Descriptor is still registered
+        assert_eq!(
+            RemotePush::Pending { register: false },
+            remote.push(ptrs[0])
+        );
+    }
+
+    #[test]
+    fn take() {
+        let remote = RemoteFrees::new();
+        let mut block = Block { _data: [0; 256] };
+        let ptr = &mut block as *mut Block as *mut u8;
+
+        assert_eq!(RemoteTake::Empty, remote.take());
+
+        remote.push(ptr);
+        assert_eq!(
+            RemoteTake::Blocks {
+                head: ptr,
+                count: 1
+            },
+            remote.take()
+        );
+        assert_eq!(RemoteTake::Empty, remote.take());
+
+        // Taking the Blocks also unregisters the Descriptor
+        assert_eq!(RemotePush::Pending { register: true }, remote.push(ptr));
+    }
+
+    #[test]
+    fn retire() {
+        let remote = RemoteFrees::new();
+        assert!(remote.try_retire());
+
+        let mut blocks: Vec<_> = (0..REMOTE_BATCH)
+            .map(|_| Block { _data: [0; 256] })
+            .collect();
+        let ptrs: Vec<_> = blocks
+            .iter_mut()
+            .map(|b| b as *mut Block as *mut u8)
+            .collect();
+
+        remote.push(ptrs[0]);
+        let _ = remote.take();
+        // Not registered anymore
+        assert!(remote.try_retire());
+
+        for ptr in &ptrs {
+            remote.push(*ptr);
+        }
+        // The Batch was returned, but the Descriptor is still registered
+        assert!(!remote.try_retire());
+        assert_eq!(RemoteTake::Dead, remote.take());
+    }
+}
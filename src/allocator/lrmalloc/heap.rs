@@ -2,11 +2,11 @@ use crate::allocator::lrmalloc::descriptor::Anchor;
 
 use super::{
     cache::Cache,
-    descriptor::{AnchorState, Descriptor},
-    pagemap::PageMap,
+    descriptor::{AnchorState, Descriptor, RemotePush, RemoteTake},
+    pagemap::{PageMap, PAGE_SIZE},
     size_classes,
-    util::traits::InternalAlloc,
 };
+use crate::utils::SegmentAlloc;
 
 use std::{alloc::GlobalAlloc, fmt::Debug, sync::atomic};
 
@@ -20,6 +20,11 @@ pub struct Heap {
     partial: [stack::DescriptorCollection; size_classes::size_class_count()],
     /// A Collection of old Descriptors that are ready to be used again for a new Superblock
     recycled_desc: descriptors::RecycleList,
+    /// The Descriptors, whose Superblocks have remotely freed Blocks that
+    /// have not been returned yet, because they did not fill a whole Batch
+    remote_pending: stack::DescriptorCollection,
+    /// Used to allocate and free the Superblocks
+    segments: &'static dyn SegmentAlloc,
 }
 
 impl Debug for Heap {
@@ -31,8 +36,9 @@ impl Debug for Heap {
 }
 
 impl Heap {
-    /// Creates a new Instance of the Heap
-    pub const fn new() -> Self {
+    /// Creates a new Instance of the Heap, which allocates its Superblocks
+    /// using the given SegmentAlloc
+    pub const fn new(segments: &'static dyn SegmentAlloc) -> Self {
         let partial: [stack::DescriptorCollection; size_classes::size_class_count()] = [
             stack::DescriptorCollection::new(),
             stack::DescriptorCollection::new(),
@@ -56,32 +62,51 @@ impl Heap {
         Self {
             partial,
             recycled_desc: descriptors::RecycleList::new(),
+            remote_pending: stack::DescriptorCollection::new(),
+            segments,
         }
     }
 
+    /// Allocates a dedicated Superblock for a single large or over-aligned
+    /// Allocation, bypassing the Size-Classes and Thread-Caches completely.
+    ///
+    /// The Superblock is allocated with the exact Alignment of the Layout,
+    /// which is recorded in its Descriptor so that [`free_large`](Self::free_large)
+    /// can release it with the same Layout again.
     pub fn allocate_large(&self, layout: std::alloc::Layout, pagemap: &PageMap) -> *mut u8 {
-        let desc_ptr = self.new_superblock::<_, 1>(layout.size(), None, &std::alloc::System);
+        // The System-Allocator does not support zero-sized Allocations and
+        // the Descriptor needs at least one Byte to be able to find the
+        // Superblock for a given Ptr
+        let size = layout.size().max(1);
+        let desc_ptr = self.new_superblock::<1>(size, layout.align(), None);
 
         pagemap.register_descriptor(desc_ptr);
 
         let desc = unsafe { &*desc_ptr };
         desc.superblock_ptr()
     }
-    pub fn free_large(&self, ptr: *mut u8, layout: std::alloc::Layout, pagemap: &PageMap) {
+    /// Frees an Allocation previously obtained from [`allocate_large`](Self::allocate_large)
+    pub fn free_large(&self, ptr: *mut u8, pagemap: &PageMap) {
         let desc_ptr = pagemap.load_descriptor(ptr).expect("This should exist");
         let desc = unsafe { &*desc_ptr };
 
-        self.free_superblock(layout.size(), 1, desc.superblock_ptr());
+        pagemap.unregister_descriptor(desc_ptr);
+
+        self.free_superblock(desc);
         self.retire_descriptor(desc_ptr);
     }
 
-    pub fn flush_cache(&self, cache: &mut Cache, size_class: usize, pagemap: &PageMap) {
+    /// Returns all the Blocks in the Cache for the given Size-Class to their
+    /// Superblocks and returns the Number of Bytes that were released back
+    /// to the System, because a Superblock became completely empty
+    pub fn flush_cache(&self, cache: &mut Cache, size_class: usize, pagemap: &PageMap) -> usize {
         let mut flush_iter = cache.flush(size_class).peekable();
+        let mut released = 0;
 
         loop {
             let head = match flush_iter.next() {
                 Some(h) => h,
-                None => return,
+                None => return released,
             };
             let mut tail = head;
 
@@ -102,51 +127,144 @@ impl Heap {
                 tail = block;
             }
 
-            let superblock_ptr = head_desc.superblock_ptr();
-            let index = head_desc.calc_index(head);
+            released += self.return_blocks(head_desc_ptr, head, tail, block_count, pagemap);
+        }
+    }
 
-            let mut old_anchor;
-            let mut new_anchor;
-            loop {
-                old_anchor = head_desc.anchor();
-                new_anchor = old_anchor;
+    /// Frees a Block from a Thread that does not own its Superblock.
+    ///
+    /// Instead of going through the Anchor of the Superblock for every
+    /// Block, the Blocks are collected in the Remote-Frees of the Descriptor
+    /// and only returned to the Superblock once a whole Batch has been freed,
+    /// so Threads that mostly free Blocks allocated by other Threads do not
+    /// all contend on the same Anchors. Incomplete Batches are returned by
+    /// [`flush_remote`](Self::flush_remote).
+    pub fn free_remote(&self, desc_ptr: *mut Descriptor, block: *mut u8, pagemap: &PageMap) {
+        let desc = unsafe { &*desc_ptr };
 
-                let old_first_ptr = ((superblock_ptr as usize)
-                    + old_anchor.avail as usize * head_desc.block_size() as usize)
-                    as *mut u8;
-                unsafe { (tail as *mut *mut u8).write(old_first_ptr) };
+        match desc.remote().push(block) {
+            RemotePush::Pending { register: true } => self.remote_pending.push(desc_ptr),
+            RemotePush::Pending { register: false } => {}
+            RemotePush::Batch { head, count } => {
+                let tail = Self::find_tail(head, count);
+                self.return_blocks(desc_ptr, head, tail, count, pagemap);
+            }
+        }
+    }
 
-                new_anchor.state = AnchorState::Partial;
-                new_anchor.avail = index;
-                new_anchor.count += block_count;
+    /// Returns all the remotely freed Blocks, that are still waiting for
+    /// their Batch to fill up, to their Superblocks and returns the Number of
+    /// Bytes that were released back to the System
+    pub fn flush_remote(&self, pagemap: &PageMap) -> usize {
+        let mut released = 0;
 
-                if new_anchor.count == head_desc.max_count() as u32 {
-                    new_anchor.state = AnchorState::Empty;
-                }
+        while let Some(desc_ptr) = self.remote_pending.try_pop() {
+            let desc = unsafe { &*desc_ptr };
 
-                if head_desc.update_anchor(
-                    old_anchor,
-                    new_anchor,
-                    atomic::Ordering::AcqRel,
-                    atomic::Ordering::Relaxed,
-                ) {
-                    break;
+            match desc.remote().take() {
+                RemoteTake::Blocks { head, count } => {
+                    let tail = Self::find_tail(head, count);
+                    released += self.return_blocks(desc_ptr, head, tail, count, pagemap);
                 }
+                RemoteTake::Empty => {}
+                // The Descriptor could not be retired while it was still in
+                // the List, so we are now responsible for it
+                RemoteTake::Dead => self.recycled_desc.add_descriptor(desc_ptr),
+            }
+        }
+
+        released
+    }
+
+    /// Finds the last Block in the linked List of `count` Blocks
+    fn find_tail(head: *mut u8, count: u32) -> *mut u8 {
+        let mut tail = head;
+        for _ in 1..count {
+            tail = unsafe { (tail as *mut *mut u8).read() };
+        }
+        tail
+    }
+
+    /// Returns the linked List of `count` Blocks from `head` to `tail` to
+    /// their Superblock and returns the Number of Bytes that were released
+    /// back to the System, because the Superblock became completely empty
+    fn return_blocks(
+        &self,
+        desc_ptr: *mut Descriptor,
+        head: *mut u8,
+        tail: *mut u8,
+        count: u32,
+        pagemap: &PageMap,
+    ) -> usize {
+        let desc = unsafe { &*desc_ptr };
+        let superblock_ptr = desc.superblock_ptr();
+        let index = desc.calc_index(head);
+
+        let mut old_anchor;
+        let mut new_anchor;
+        loop {
+            old_anchor = desc.anchor();
+            new_anchor = old_anchor;
+
+            let old_first_ptr = ((superblock_ptr as usize)
+                + old_anchor.avail as usize * desc.block_size())
+                as *mut u8;
+            unsafe { (tail as *mut *mut u8).write(old_first_ptr) };
+
+            new_anchor.state = AnchorState::Partial;
+            new_anchor.avail = index;
+            new_anchor.count += count;
+
+            if new_anchor.count == desc.max_count() as u32 {
+                new_anchor.state = AnchorState::Empty;
             }
 
+            if desc.update_anchor(
+                old_anchor,
+                new_anchor,
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Relaxed,
+            ) {
+                break;
+            }
+        }
+
+        if let AnchorState::Empty = new_anchor.state {
+            pagemap.unregister_descriptor(desc_ptr);
+
+            let released = self.free_superblock(desc);
+
+            // A Full Superblock is not in the List of partial
+            // Superblocks, so nothing else references its Descriptor
+            // anymore. Otherwise the Descriptor is retired once it is
+            // popped from the partial List again
             if let AnchorState::Full = old_anchor.state {
-                let partial = self.partial.get(size_class).expect("");
-                partial.push(head_desc_ptr);
-            } else if let AnchorState::Empty = new_anchor.state {
-                pagemap.unregister_descriptor(head_desc_ptr);
-
-                self.free_superblock(
-                    head_desc.block_size(),
-                    head_desc.max_count(),
-                    head_desc.superblock_ptr(),
-                );
+                self.retire_descriptor(desc_ptr);
             }
+
+            return released;
         }
+
+        if let AnchorState::Full = old_anchor.state {
+            let size_class = desc
+                .size_class()
+                .expect("Only Superblocks for a Size-Class contain multiple Blocks");
+            let partial = self.partial.get(size_class).expect("");
+            partial.push(desc_ptr);
+        }
+
+        0
+    }
+
+    /// Flushes the Blocks of all Size-Classes in the Cache, see
+    /// [`flush_cache`](Self::flush_cache), as well as all the pending
+    /// remotely freed Blocks, see [`flush_remote`](Self::flush_remote)
+    pub fn flush_all(&self, cache: &mut Cache, pagemap: &PageMap) -> usize {
+        let released: usize = (0..size_classes::size_class_count())
+            .map(|size_class| self.flush_cache(cache, size_class, pagemap))
+            .sum();
+
+        released + self.flush_remote(pagemap)
     }
 
     pub fn fill_cache(&self, cache: &mut Cache, size_class: usize, pagemap: &PageMap) {
@@ -187,6 +305,7 @@ impl Heap {
                 break;
             }
         }
+        desc.set_owner(cache.id());
 
         let mut current_ptr = (desc.superblock_ptr() as usize
             + old_anchor.avail as usize * desc.block_size())
@@ -205,9 +324,13 @@ impl Heap {
 
         let block_size = size_classes::get_block_size(size_class);
 
-        let descriptor_ptr =
-            self.new_superblock::<_, MAX_COUNT>(block_size, Some(size_class), &std::alloc::System);
+        let descriptor_ptr = self.new_superblock::<MAX_COUNT>(
+            block_size,
+            size_classes::BLOCK_ALIGN,
+            Some(size_class),
+        );
         let descriptor = unsafe { &*descriptor_ptr };
+        descriptor.set_owner(cache.id());
 
         for block_index in 0..MAX_COUNT {
             let offset = descriptor.block_size() * block_index;
@@ -224,32 +347,41 @@ impl Heap {
     /// # Params
     /// * `N`: The Number of blocks in the Superblock
     /// * `block_size`: The Size of each block in the SuperBlock
+    /// * `align`: The Alignment of the Superblock itself, which is raised to at
+    ///   least [`PAGE_SIZE`]
     /// * `size_class`: The Size-Class for the Blocks in the SuperBlock
-    fn new_superblock<A, const N: usize>(
+    fn new_superblock<const N: usize>(
         &self,
         block_size: usize,
+        align: usize,
         size_class: Option<usize>,
-        allocator: &A,
-    ) -> *mut Descriptor
-    where
-        A: InternalAlloc,
-    {
+    ) -> *mut Descriptor {
         let superblock_size = block_size * N;
+        // Every Page can only be mapped to a single Superblock in the PageMap
+        let align = align.max(PAGE_SIZE);
+
+        let superblock_layout =
+            std::alloc::Layout::from_size_align(superblock_size, align).unwrap();
+        let superblock_ptr = unsafe { self.segments.alloc_segment(superblock_layout) };
+        if superblock_ptr.is_null() {
+            std::alloc::handle_alloc_error(superblock_layout);
+        }
 
-        let superblock_layout = std::alloc::Layout::from_size_align(superblock_size, 8).unwrap();
-        let superblock_ptr = allocator.allocate(superblock_layout);
-
-        let descriptor = Descriptor::new(block_size, N, size_class, superblock_ptr);
+        let descriptor = Descriptor::new(block_size, N, align, size_class, superblock_ptr);
         let descriptor_ptr = self.alloc_descriptor();
         unsafe { descriptor_ptr.write(descriptor) };
 
         descriptor_ptr
     }
 
-    fn free_superblock(&self, block_size: usize, block_count: usize, superblock_ptr: *mut u8) {
-        let size = block_size * block_count;
-        let layout = std::alloc::Layout::from_size_align(size, 8).unwrap();
-        unsafe { std::alloc::System.dealloc(superblock_ptr, layout) };
+    /// Frees the Superblock of the Descriptor and returns its Size in Bytes
+    fn free_superblock(&self, descriptor: &Descriptor) -> usize {
+        let layout = descriptor.superblock_layout();
+        unsafe {
+            self.segments
+                .dealloc_segment(descriptor.superblock_ptr(), layout)
+        };
+        layout.size()
     }
 
     // TODO
@@ -265,7 +397,11 @@ impl Heap {
         raw_ptr as *mut Descriptor
     }
     fn retire_descriptor(&self, desc: *mut Descriptor) {
-        self.recycled_desc.add_descriptor(desc);
+        // A Descriptor that is still in the List of pending remote Frees is
+        // recycled once it is removed from there
+        if unsafe { &*desc }.remote().try_retire() {
+            self.recycled_desc.add_descriptor(desc);
+        }
     }
 }
 
@@ -1,37 +1,58 @@
+//! Maps every Page of the Superblocks to the Descriptor of its Superblock,
+//! using a sparse multi-level Radix-Tree, see the `storage` module.
+//!
+//! Superblocks are always aligned to [`PAGE_SIZE`], so that every Page
+//! belongs to at most one Superblock.
+
 use super::descriptor::Descriptor;
 
 mod storage;
+use storage::PAGE_SHIFT;
 
-// TODO
-// This can be implemented by copying and adapting the Trie-Structure
-// like we already have in the ThreadData module.
-//
-// # Adaptations:
-// * Use atomic-Ptrs as Data
-// * Allow Data entries to be overwritten
-// * Make it None-Generic (concrete *mut Descriptor Type as Data)
+/// The Size of the Pages used to map Ptrs to their Descriptors and therefore
+/// also the minimum Alignment of every Superblock
+pub const PAGE_SIZE: usize = 1 << PAGE_SHIFT;
 
 #[derive(Debug)]
 pub struct PageMap {
-    descriptors: storage::Collection,
+    descriptors: storage::Tree,
 }
 
 impl PageMap {
     pub const fn new() -> Self {
         Self {
-            descriptors: storage::Collection::new(),
+            descriptors: storage::Tree::new(),
         }
     }
 
+    /// The Range of Page-Numbers covered by the Superblock of the Descriptor
+    fn pages(descriptor: *mut Descriptor) -> core::ops::Range<usize> {
+        let desc = unsafe { &*descriptor };
+        let start = desc.superblock_ptr() as usize;
+        let size = desc.superblock_layout().size().max(1);
+
+        (start >> PAGE_SHIFT)..(((start + size - 1) >> PAGE_SHIFT) + 1)
+    }
+
     pub fn register_descriptor(&self, descriptor: *mut Descriptor) {
-        self.descriptors.insert(descriptor);
+        self.descriptors
+            .insert(Self::pages(descriptor), descriptor as *mut ());
     }
     pub fn unregister_descriptor(&self, descriptor: *mut Descriptor) {
-        self.descriptors.remove(descriptor);
+        self.descriptors
+            .remove(Self::pages(descriptor), descriptor as *mut ());
     }
 
     pub fn load_descriptor(&self, ptr: *mut u8) -> Option<*mut Descriptor> {
-        self.descriptors.get(ptr)
+        self.descriptors
+            .get(ptr as usize >> PAGE_SHIFT)
+            .map(|desc| desc as *mut Descriptor)
+    }
+
+    /// The Number of Levels of the Radix-Tree that are currently allocated
+    #[cfg(test)]
+    pub fn level_count(&self) -> usize {
+        self.descriptors.level_count()
     }
 }
 
@@ -42,14 +63,31 @@ mod tests {
     #[test]
     fn new() {
         let map = PageMap::new();
+
+        assert_eq!(0, map.level_count());
+        assert_eq!(None, map.load_descriptor(core::ptr::null_mut()));
+        assert_eq!(None, map.load_descriptor(usize::MAX as *mut u8));
+    }
+
+    #[test]
+    fn drop_registered() {
+        let map = PageMap::new();
+
+        let desc = descriptor(1 << 30, 2 * PAGE_SIZE);
+        map.register_descriptor(desc);
+        assert!(map.level_count() > 0);
+
+        // The Levels of the still registered Descriptor are freed with the
+        // Map, which is checked by Miri
         drop(map);
+        drop(unsafe { Box::from_raw(desc) });
     }
 
     #[test]
     fn register_load() {
         let map = PageMap::new();
 
-        let initial_desc = Box::new(Descriptor::new(128, 4, Some(0), 0x0000 as *mut u8));
+        let initial_desc = Box::new(Descriptor::new(128, 4, 8, Some(0), 0x0000 as *mut u8));
         let initial_desc_ptr = Box::into_raw(initial_desc);
 
         map.register_descriptor(initial_desc_ptr);
@@ -59,4 +97,87 @@ mod tests {
 
         assert_eq!(expected, result);
     }
+
+    fn descriptor(address: usize, size: usize) -> *mut Descriptor {
+        Box::into_raw(Box::new(Descriptor::new(
+            size,
+            1,
+            PAGE_SIZE,
+            None,
+            address as *mut u8,
+        )))
+    }
+
+    #[test]
+    fn sparse_superblocks() {
+        let map = PageMap::new();
+
+        // Superblocks scattered across the entire Address-Space
+        let descriptors: Vec<_> = [
+            1usize << 20,
+            1 << 30,
+            usize::MAX / 2,
+            usize::MAX - 4 * PAGE_SIZE,
+        ]
+        .iter()
+        .map(|address| descriptor(address & !(PAGE_SIZE - 1), 3 * PAGE_SIZE / 2))
+        .collect();
+        for desc in descriptors.iter() {
+            map.register_descriptor(*desc);
+        }
+
+        for desc in descriptors.iter() {
+            let start = unsafe { &**desc }.superblock_ptr();
+            assert_eq!(Some(*desc), map.load_descriptor(start));
+            assert_eq!(
+                Some(*desc),
+                map.load_descriptor(unsafe { start.add(3 * PAGE_SIZE / 2 - 1) })
+            );
+        }
+        assert_eq!(None, map.load_descriptor(0x1000 as *mut u8));
+
+        for desc in descriptors {
+            map.unregister_descriptor(desc);
+            assert_eq!(
+                None,
+                map.load_descriptor(unsafe { &*desc }.superblock_ptr())
+            );
+            drop(unsafe { Box::from_raw(desc) });
+        }
+
+        // All the Levels are freed again, once they are empty
+        assert_eq!(0, map.level_count());
+    }
+
+    #[test]
+    fn adjacent_superblocks() {
+        let map = PageMap::new();
+
+        let first = descriptor(0x10000, PAGE_SIZE);
+        let second = descriptor(0x10000 + PAGE_SIZE, 4 * PAGE_SIZE);
+        map.register_descriptor(first);
+        map.register_descriptor(second);
+
+        assert_eq!(
+            Some(first),
+            map.load_descriptor((0x10000 + PAGE_SIZE - 1) as *mut u8)
+        );
+        assert_eq!(
+            Some(second),
+            map.load_descriptor((0x10000 + PAGE_SIZE) as *mut u8)
+        );
+
+        map.unregister_descriptor(first);
+        assert_eq!(None, map.load_descriptor(0x10000 as *mut u8));
+        assert_eq!(
+            Some(second),
+            map.load_descriptor((0x10000 + PAGE_SIZE) as *mut u8)
+        );
+
+        map.unregister_descriptor(second);
+        assert_eq!(0, map.level_count());
+
+        drop(unsafe { Box::from_raw(first) });
+        drop(unsafe { Box::from_raw(second) });
+    }
 }
@@ -1,90 +1,423 @@
-use std::{fmt::Debug, sync::atomic};
+//! A sparse Radix-Tree, that maps the Page-Number of an Address to a Ptr.
+//!
+//! The Levels of the Tree are only allocated once an Entry is stored in
+//! them and are freed again, once their last Entry is removed, so the Memory
+//! needed by the Tree only depends on the Number of distinct Regions that
+//! are currently stored and not on how far apart they are.
+//!
+//! # Freeing Levels
+//! Every Level counts its non-null Entries plus the Number of Writers that
+//! are currently using it. Once this Count drops to 0, the Level is marked
+//! as dead, unlinked from its Parent and retired. The retired Levels are
+//! only freed once no Writer is active, as a Writer could still have loaded
+//! a Ptr to the Level before it was unlinked.
+//!
+//! Readers do not take part in this Protocol, because they only ever look
+//! up Pages that are currently stored, which keeps all the Levels on their
+//! Path alive.
 
-use crate::allocator::lrmalloc::{descriptor::Descriptor, util::list::List};
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    fmt::Debug,
+    ptr,
+    sync::atomic,
+};
 
-pub struct Collection {
-    list: List<atomic::AtomicPtr<Descriptor>>,
+/// The Number of Bits of an Address that are used for the Offset in a Page
+pub const PAGE_SHIFT: usize = 12;
+
+/// The Number of Levels in the Tree, including the Root
+#[cfg(target_pointer_width = "64")]
+const LEVELS: usize = 4;
+#[cfg(not(target_pointer_width = "64"))]
+const LEVELS: usize = 2;
+
+/// The Number of Bits in a Page-Number
+const KEY_BITS: usize = usize::BITS as usize - PAGE_SHIFT;
+/// The Number of Bits of the Page-Number that are used as the Index in
+/// every Level
+const LEVEL_BITS: usize = KEY_BITS / LEVELS;
+/// The Number of Entries in every Level
+const FANOUT: usize = 1 << LEVEL_BITS;
+
+const _: () = assert!(LEVEL_BITS * LEVELS == KEY_BITS);
+
+/// Marks a Level that is being freed and can not be used anymore
+const DEAD: usize = usize::MAX;
+
+struct Level {
+    /// Points to the next Levels or, in the last Level, to the actual Data
+    entries: [atomic::AtomicPtr<()>; FANOUT],
+    /// The Number of non-null Entries plus the Number of Writers that are
+    /// currently using this Level, or [`DEAD`]
+    used: atomic::AtomicUsize,
+    /// Links the retired Levels together
+    next_retired: atomic::AtomicPtr<Level>,
+}
+
+impl Level {
+    const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const EMPTY: atomic::AtomicPtr<()> = atomic::AtomicPtr::new(ptr::null_mut());
+
+        Self {
+            entries: [EMPTY; FANOUT],
+            used: atomic::AtomicUsize::new(0),
+            next_retired: atomic::AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Allocates a new empty Level, which is already reserved by the Caller
+    fn alloc() -> *mut Self {
+        let layout = Layout::new::<Self>();
+        // # Safety:
+        // All the Fields are atomics, for which all zero Bytes are a valid
+        // Value, namely null or 0
+        let ptr = unsafe { std::alloc::System.alloc_zeroed(layout) } as *mut Self;
+        if ptr.is_null() {
+            std::alloc::handle_alloc_error(layout);
+        }
+
+        unsafe { &*ptr }.used.store(1, atomic::Ordering::Relaxed);
+        ptr
+    }
+
+    /// # Safety
+    /// The Level must have been allocated using [`alloc`](Self::alloc) and
+    /// no other Thread can still access it
+    unsafe fn dealloc(ptr: *mut Self) {
+        unsafe { std::alloc::System.dealloc(ptr as *mut u8, Layout::new::<Self>()) };
+    }
+
+    /// Attempts to reserve the Level, which fails if the Level is dead
+    fn reserve(&self) -> bool {
+        let mut current = self.used.load(atomic::Ordering::SeqCst);
+        loop {
+            if current == DEAD {
+                return false;
+            }
+
+            match self.used.compare_exchange_weak(
+                current,
+                current + 1,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(c) => current = c,
+            };
+        }
+    }
 }
 
-impl Collection {
+/// Calculates the Index into the Level at the given Depth for the Page-Number
+const fn index(key: usize, depth: usize) -> usize {
+    (key >> ((LEVELS - 1 - depth) * LEVEL_BITS)) & (FANOUT - 1)
+}
+
+/// The Radix-Tree, see the [module-level documentation](self) for more
+/// Details
+pub struct Tree {
+    root: Level,
+    /// The Number of Writers that are currently modifying the Tree
+    writers: atomic::AtomicUsize,
+    /// The Levels that were unlinked, but may still be used by a Writer
+    retired: atomic::AtomicPtr<Level>,
+    /// The Number of Levels that are currently allocated, excluding the Root
+    levels: atomic::AtomicUsize,
+}
+
+impl Tree {
     pub const fn new() -> Self {
         Self {
-            list: List::new(atomic::AtomicPtr::new(std::ptr::null_mut())),
+            root: Level::new(),
+            writers: atomic::AtomicUsize::new(0),
+            retired: atomic::AtomicPtr::new(ptr::null_mut()),
+            levels: atomic::AtomicUsize::new(0),
         }
     }
 
-    pub fn insert(&self, descriptor: *mut Descriptor) {
-        for a_ptr in self.list.iter() {
-            if !a_ptr.load(atomic::Ordering::Acquire).is_null() {
-                continue;
+    /// Loads the Ptr stored for the given Page-Number.
+    ///
+    /// The Page should currently be stored in the Tree, otherwise the Levels
+    /// on its Path could be freed concurrently
+    pub fn get(&self, key: usize) -> Option<*mut ()> {
+        let mut level = &self.root;
+        for depth in 0..(LEVELS - 1) {
+            let next_ptr = level.entries[index(key, depth)].load(atomic::Ordering::Acquire);
+            if next_ptr.is_null() {
+                return None;
             }
+            level = unsafe { &*(next_ptr as *const Level) };
+        }
+
+        let value = level.entries[index(key, LEVELS - 1)].load(atomic::Ordering::Acquire);
+        if value.is_null() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    /// Stores the Value for all the Page-Numbers in the Range, which must not
+    /// be stored already
+    pub fn insert(&self, keys: core::ops::Range<usize>, value: *mut ()) {
+        self.writers.fetch_add(1, atomic::Ordering::SeqCst);
+        for key in keys {
+            let leaf = self.reserve_leaf(key);
+            // Our Reservation of the Leaf now counts as its new Entry
+            let previous =
+                leaf.entries[index(key, LEVELS - 1)].swap(value, atomic::Ordering::AcqRel);
+            debug_assert!(previous.is_null(), "The Page was already stored");
+        }
+        self.exit();
+    }
+
+    /// Removes the Value from all the Page-Numbers in the Range, if it is
+    /// still stored there, and frees all the Levels that became empty
+    pub fn remove(&self, keys: core::ops::Range<usize>, value: *mut ()) {
+        self.writers.fetch_add(1, atomic::Ordering::SeqCst);
+        for key in keys {
+            self.remove_key(key, value);
+        }
+        self.exit();
+    }
+
+    /// Walks down to the Leaf for the Key, allocating the missing Levels on
+    /// the Way, and returns it reserved
+    fn reserve_leaf(&self, key: usize) -> &Level {
+        let mut path: [*const Level; LEVELS] = [ptr::null(); LEVELS];
+        path[0] = &self.root;
 
-            if a_ptr
-                .compare_exchange(
-                    std::ptr::null_mut(),
-                    descriptor,
+        let mut depth = 0;
+        while depth < LEVELS - 1 {
+            let entry = unsafe { &*path[depth] }.entries[index(key, depth)]
+                .load(atomic::Ordering::Acquire) as *const Level;
+
+            if entry.is_null() {
+                let new_ptr = Level::alloc();
+                match unsafe { &*path[depth] }.entries[index(key, depth)].compare_exchange(
+                    ptr::null_mut(),
+                    new_ptr as *mut (),
                     atomic::Ordering::AcqRel,
-                    atomic::Ordering::Relaxed,
-                )
-                .is_ok()
-            {
-                return;
+                    atomic::Ordering::Acquire,
+                ) {
+                    Ok(_) => {
+                        // Our Reservation of the current Level now counts as
+                        // the Entry for the new Level
+                        self.levels.fetch_add(1, atomic::Ordering::Relaxed);
+                        path[depth + 1] = new_ptr;
+                        depth += 1;
+                    }
+                    Err(_) => {
+                        // # Safety:
+                        // The new Level was never shared
+                        unsafe { Level::dealloc(new_ptr) };
+                    }
+                };
+                continue;
+            }
+
+            if !unsafe { &*entry }.reserve() {
+                // The Level is being unlinked by another Thread, so we start
+                // over once that is done
+                self.release_at(&path, depth, key);
+                depth = 0;
+                continue;
             }
+
+            // The Entry for the reserved Level keeps the current Level alive
+            self.release_at(&path, depth, key);
+            path[depth + 1] = entry;
+            depth += 1;
         }
 
-        self.list.append(atomic::AtomicPtr::new(descriptor));
+        unsafe { &*path[LEVELS - 1] }
     }
 
-    pub fn get(&self, ptr: *mut u8) -> Option<*mut Descriptor> {
-        for a_ptr in self.list.iter() {
-            let desc_ptr = a_ptr.load(atomic::Ordering::Acquire);
-            if desc_ptr.is_null() {
-                continue;
+    fn remove_key(&self, key: usize, value: *mut ()) {
+        // The Levels on the Path, which are needed to unlink the Levels that
+        // become empty
+        let mut path: [*const Level; LEVELS] = [ptr::null(); LEVELS];
+        path[0] = &self.root;
+
+        for depth in 0..(LEVELS - 1) {
+            let level = unsafe { &*path[depth] };
+            let next_ptr =
+                level.entries[index(key, depth)].load(atomic::Ordering::Acquire) as *const Level;
+            if next_ptr.is_null() || !unsafe { &*next_ptr }.reserve() {
+                self.release_at(&path, depth, key);
+                return;
             }
 
-            let desc = unsafe { &*desc_ptr };
+            self.release_at(&path, depth, key);
+            path[depth + 1] = next_ptr;
+        }
+
+        let leaf = unsafe { &*path[LEVELS - 1] };
+        if leaf.entries[index(key, LEVELS - 1)]
+            .compare_exchange(
+                value,
+                ptr::null_mut(),
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Relaxed,
+            )
+            .is_ok()
+        {
+            // Remove the Count of the Entry, our Reservation is released below
+            self.release_at(&path, LEVELS - 1, key);
+        }
+        self.release_at(&path, LEVELS - 1, key);
+    }
 
-            if desc.contains(ptr) {
-                return Some(desc_ptr);
+    /// Releases a Reservation, or Entry, of the Level at the given Depth of
+    /// the Path and unlinks all the Levels that become empty because of it
+    fn release_at(&self, path: &[*const Level; LEVELS], mut depth: usize, key: usize) {
+        while depth > 0 {
+            let level = unsafe { &*path[depth] };
+            if level.used.fetch_sub(1, atomic::Ordering::SeqCst) != 1 {
+                return;
             }
+
+            // Another Writer may have reserved the Level in the mean time, in
+            // which case it is now responsible for it
+            if level
+                .used
+                .compare_exchange(0, DEAD, atomic::Ordering::SeqCst, atomic::Ordering::SeqCst)
+                .is_err()
+            {
+                return;
+            }
+
+            let parent = unsafe { &*path[depth - 1] };
+            parent.entries[index(key, depth - 1)].store(ptr::null_mut(), atomic::Ordering::SeqCst);
+            self.retire(path[depth] as *mut Level);
+
+            // Remove the Entry for the unlinked Level from its Parent
+            depth -= 1;
         }
+    }
+
+    fn retire(&self, level_ptr: *mut Level) {
+        self.levels.fetch_sub(1, atomic::Ordering::Relaxed);
 
-        None
+        let level = unsafe { &*level_ptr };
+        let mut head = self.retired.load(atomic::Ordering::SeqCst);
+        loop {
+            level.next_retired.store(head, atomic::Ordering::Relaxed);
+            match self.retired.compare_exchange_weak(
+                head,
+                level_ptr,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            ) {
+                Ok(_) => return,
+                Err(h) => head = h,
+            };
+        }
     }
 
-    pub fn remove(&self, descriptor: *mut Descriptor) {
-        for a_ptr in self.list.iter() {
-            let desc_ptr = a_ptr.load(atomic::Ordering::Acquire);
-            if desc_ptr == descriptor {
-                let _ = a_ptr.compare_exchange(
-                    desc_ptr,
-                    std::ptr::null_mut(),
-                    atomic::Ordering::AcqRel,
-                    atomic::Ordering::Relaxed,
-                );
+    /// Ends the Operation of a Writer and frees the retired Levels, if it
+    /// was the last active Writer
+    fn exit(&self) {
+        if self.writers.fetch_sub(1, atomic::Ordering::SeqCst) != 1 {
+            return;
+        }
+
+        let mut current = self.retired.swap(ptr::null_mut(), atomic::Ordering::SeqCst);
+        if current.is_null() {
+            return;
+        }
+
+        // Every Writer that could still access one of the Levels, was active
+        // before it was retired and therefore also before we took the List.
+        // If there are no Writers now, all of them have finished
+        if self.writers.load(atomic::Ordering::SeqCst) != 0 {
+            while !current.is_null() {
+                let next = unsafe { &*current }
+                    .next_retired
+                    .load(atomic::Ordering::Relaxed);
+                self.levels.fetch_add(1, atomic::Ordering::Relaxed);
+                self.retire(current);
+                current = next;
             }
+            return;
         }
+
+        while !current.is_null() {
+            let next = unsafe { &*current }
+                .next_retired
+                .load(atomic::Ordering::Relaxed);
+            // # Safety:
+            // The Level was unlinked and no Writer can still access it
+            unsafe { Level::dealloc(current) };
+            current = next;
+        }
+    }
+
+    /// Returns the Number of Levels that are currently allocated, not
+    /// including the Root
+    pub fn level_count(&self) -> usize {
+        self.levels.load(atomic::Ordering::Relaxed)
     }
 }
 
-unsafe impl Sync for Collection {}
+/// Frees the Level and all the Levels below it
+///
+/// # Safety
+/// The Level must be at the given Depth and no other Thread can still
+/// access it or any of the Levels below it
+unsafe fn free_level(level_ptr: *mut Level, depth: usize) {
+    // The Entries of the Leaves point to the stored Values and not to Levels
+    if depth < LEVELS - 1 {
+        for entry in unsafe { &*level_ptr }.entries.iter() {
+            let next_ptr = entry.load(atomic::Ordering::Acquire) as *mut Level;
+            if !next_ptr.is_null() {
+                unsafe { free_level(next_ptr, depth + 1) };
+            }
+        }
+    }
+
+    unsafe { Level::dealloc(level_ptr) };
+}
 
-impl Debug for Collection {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[")?;
-
-        for a_ptr in self.list.iter() {
-            let ptr = a_ptr.load(atomic::Ordering::SeqCst);
-            if ptr.is_null() {
-                write!(f, "{:p},", ptr)?;
-            } else {
-                let desc = unsafe { &*ptr };
-                write!(f, "{:?},", desc)?;
+impl Drop for Tree {
+    fn drop(&mut self) {
+        // The Levels that are still linked into the Tree
+        for entry in self.root.entries.iter() {
+            let next_ptr = entry.load(atomic::Ordering::Acquire) as *mut Level;
+            if !next_ptr.is_null() {
+                // # Safety:
+                // We have exclusive access to the Tree and therefore also to
+                // all of its Levels
+                unsafe { free_level(next_ptr, 1) };
             }
         }
 
-        write!(f, "]")?;
-        Ok(())
+        // The Levels that were already unlinked, but not yet freed
+        let mut current = self
+            .retired
+            .swap(ptr::null_mut(), atomic::Ordering::Acquire);
+        while !current.is_null() {
+            let next = unsafe { &*current }
+                .next_retired
+                .load(atomic::Ordering::Relaxed);
+            // # Safety:
+            // The Level was unlinked before it was retired, so it was not
+            // already freed above
+            unsafe { Level::dealloc(current) };
+            current = next;
+        }
+    }
+}
+
+// Safety:
+// The Tree only stores raw Ptrs and all of its Fields are atomics
+unsafe impl Sync for Tree {}
+
+impl Debug for Tree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Tree ( levels = {} )", self.level_count())
     }
 }
 
@@ -92,27 +425,125 @@ impl Debug for Collection {
 mod tests {
     use super::*;
 
+    fn value(n: usize) -> *mut () {
+        n as *mut ()
+    }
+
     #[test]
-    fn insert_descriptor() {
-        let collection = Collection::new();
+    fn insert_get() {
+        let tree = Tree::new();
 
-        collection.insert(0x123 as *mut Descriptor);
+        tree.insert(10..13, value(0x10));
+        assert_eq!(Some(value(0x10)), tree.get(10));
+        assert_eq!(Some(value(0x10)), tree.get(12));
+        assert_eq!(None, tree.get(13));
+        assert_eq!(None, tree.get(9));
     }
 
     #[test]
-    fn insert_get() {
-        let collection = Collection::new();
+    fn remove_frees_levels() {
+        let tree = Tree::new();
+
+        tree.insert(10..13, value(0x10));
+        assert_eq!(LEVELS - 1, tree.level_count());
+
+        tree.remove(10..13, value(0x10));
+        assert_eq!(None, tree.get(10));
+        assert_eq!(0, tree.level_count());
+    }
+
+    #[test]
+    fn remove_other_value() {
+        let tree = Tree::new();
+
+        tree.insert(10..11, value(0x10));
+        tree.remove(10..11, value(0x20));
+        assert_eq!(Some(value(0x10)), tree.get(10));
+    }
+
+    #[test]
+    fn sparse_keys() {
+        let tree = Tree::new();
+
+        let max_key = usize::MAX >> PAGE_SHIFT;
+        let keys = [0, 1 << LEVEL_BITS, max_key / 3, max_key];
+        for (i, key) in keys.iter().enumerate() {
+            tree.insert(*key..(*key + 1), value(i + 1));
+        }
+
+        // Every Key is in a different Leaf, so only the Levels on their
+        // Paths are allocated
+        assert!(tree.level_count() <= keys.len() * (LEVELS - 1));
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(Some(value(i + 1)), tree.get(*key));
+        }
+
+        for (i, key) in keys.iter().enumerate() {
+            tree.remove(*key..(*key + 1), value(i + 1));
+            assert_eq!(None, tree.get(*key));
+        }
+        assert_eq!(0, tree.level_count());
+    }
+
+    #[test]
+    fn range_across_leaves() {
+        let tree = Tree::new();
+
+        let start = FANOUT - 2;
+        tree.insert(start..(start + 4), value(0x10));
+        assert_eq!(LEVELS, tree.level_count());
+
+        for key in start..(start + 4) {
+            assert_eq!(Some(value(0x10)), tree.get(key));
+        }
+
+        tree.remove(start..(start + 4), value(0x10));
+        assert_eq!(0, tree.level_count());
+    }
+
+    #[test]
+    fn drop_frees_levels() {
+        let tree = Tree::new();
 
-        let desc_ptr = Box::into_raw(Box::new(Descriptor::new(
-            128,
-            4,
-            Some(1),
-            0x1000 as *mut u8,
-        )));
+        // Levels that are still linked into the Tree
+        tree.insert(10..13, value(0x10));
+        tree.insert(FANOUT * FANOUT..(FANOUT * FANOUT + 1), value(0x20));
 
-        collection.insert(desc_ptr);
+        // Levels that are retired, but can not be freed yet, because another
+        // Writer is still active
+        tree.writers.fetch_add(1, atomic::Ordering::SeqCst);
+        tree.insert((FANOUT - 1)..(FANOUT + 1), value(0x30));
+        tree.remove((FANOUT - 1)..(FANOUT + 1), value(0x30));
+        assert!(!tree.retired.load(atomic::Ordering::SeqCst).is_null());
+
+        // Freeing all of them is checked by Miri
+        drop(tree);
+    }
+
+    #[test]
+    fn concurrent_insert_remove() {
+        let tree = std::sync::Arc::new(Tree::new());
+
+        let threads: Vec<_> = (0..4)
+            .map(|t| {
+                let tree = tree.clone();
+                std::thread::spawn(move || {
+                    for i in 0..200 {
+                        // Every Thread uses its own Keys, which share the
+                        // Levels with the other Threads
+                        let key = (i * 4 + t) * 7;
+                        tree.insert(key..(key + 2), value(key + 1));
+                        assert_eq!(Some(value(key + 1)), tree.get(key + 1));
+                        tree.remove(key..(key + 2), value(key + 1));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in threads {
+            handle.join().unwrap();
+        }
 
-        let result = collection.get(0x1000 as *mut u8);
-        assert_eq!(Some(desc_ptr), result);
+        assert_eq!(0, tree.level_count());
     }
 }
@@ -6,6 +6,14 @@ const SIZE_CLASSES: [usize; 17] = [
     14336, 16384,
 ];
 
+/// The Alignment of every Block handed out for any of the Size-Classes.
+///
+/// Every Size in [`SIZE_CLASSES`] is a multiple of this and Superblocks are
+/// allocated with this Alignment, so every Block in them is aligned to it as
+/// well. Layouts that need a larger Alignment have to be served by the
+/// large-allocation Path instead.
+pub const BLOCK_ALIGN: usize = 256;
+
 pub const fn size_class_count() -> usize {
     SIZE_CLASSES.len()
 }
@@ -44,6 +52,13 @@ mod tests {
         assert_eq!(expected, get_size_class_index(size));
     }
 
+    #[test]
+    fn sizes_are_aligned() {
+        for size in SIZE_CLASSES {
+            assert_eq!(0, size % BLOCK_ALIGN, "{} is not aligned", size);
+        }
+    }
+
     #[test]
     fn too_large_size() {
         let size = 20000;
@@ -0,0 +1,364 @@
+//! An atomically replaceable [`Arc`], similar to what the `arc-swap` Crate
+//! provides, but built on top of the [`hyaline`](crate::hyaline) Reclamation
+//! Scheme of this Crate.
+//!
+//! Loading the current Value only enters the Hyaline-Instance of the
+//! [`AtomicArc`] and does not touch the Reference-Count of the Arc, so many
+//! Readers can load the same Value without contending on a single
+//! Cache-Line. A Guard can still be turned into a full [`Arc`] using
+//! [`ArcGuard::to_arc`], if it needs to outlive the Borrow of the AtomicArc.
+//!
+//! # Example
+//! ```
+//! # use std::sync::Arc;
+//! # use nolock::arc::AtomicArc;
+//! let config = AtomicArc::new(Arc::new(String::from("first")));
+//!
+//! let guard = config.load();
+//! config.store(Arc::new(String::from("second")));
+//!
+//! // The Guard still refers to the Value it loaded
+//! assert_eq!("first", guard.as_str());
+//! assert_eq!("second", config.load().as_str());
+//! ```
+
+use alloc::sync::Arc;
+use core::{
+    fmt::Debug,
+    marker::PhantomData,
+    ops::Deref,
+    ptr,
+    sync::atomic::{self, AtomicPtr},
+};
+
+use crate::hyaline;
+
+/// An [`Arc`] that can be loaded and replaced atomically by any Number of
+/// Threads, see the [module-level documentation](self) for more Details
+pub struct AtomicArc<T> {
+    /// Always a Ptr obtained from `Arc::into_raw`, which owns one Strong-Count
+    current: AtomicPtr<T>,
+    instance: hyaline::Hyaline,
+    _marker: PhantomData<Arc<T>>,
+}
+
+/// A protected Reference to the Value that was loaded from an
+/// [`AtomicArc`].
+///
+/// The Value is kept alive for as long as the Guard exists, even if it has
+/// been replaced in the AtomicArc in the mean time.
+pub struct ArcGuard<'a, T> {
+    ptr: *const T,
+    _handle: hyaline::Handle<'a>,
+}
+
+impl<T> AtomicArc<T> {
+    fn free_fn(ptr: *const ()) {
+        drop(unsafe { Arc::from_raw(ptr as *const T) });
+    }
+
+    /// Creates a new AtomicArc storing the given Arc
+    pub fn new(value: Arc<T>) -> Self {
+        Self {
+            current: AtomicPtr::new(Arc::into_raw(value) as *mut T),
+            instance: hyaline::Hyaline::new(Self::free_fn),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the current Value, without modifying its Reference-Count
+    pub fn load(&self) -> ArcGuard<'_, T> {
+        let handle = self.instance.enter();
+        let ptr = self.current.load(atomic::Ordering::Acquire);
+
+        ArcGuard {
+            ptr,
+            _handle: handle,
+        }
+    }
+
+    /// Loads the current Value as a full [`Arc`]
+    pub fn load_full(&self) -> Arc<T> {
+        self.load().to_arc()
+    }
+
+    /// Replaces the current Value with the given one
+    pub fn store(&self, value: Arc<T>) {
+        let mut handle = self.instance.enter();
+        let old_ptr = self
+            .current
+            .swap(Arc::into_raw(value) as *mut T, atomic::Ordering::AcqRel);
+
+        // # Safety:
+        // The old Ptr was swapped out and can therefore not be loaded by any
+        // new Thread
+        unsafe { handle.retire(old_ptr as *const ()) };
+    }
+
+    /// Replaces the current Value with the given one and returns the previous
+    /// Value
+    ///
+    /// # Example
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use nolock::arc::AtomicArc;
+    /// let value = AtomicArc::new(Arc::new(13));
+    ///
+    /// let previous = value.swap(Arc::new(14));
+    /// assert_eq!(13, *previous);
+    /// assert_eq!(14, *value.load());
+    /// ```
+    pub fn swap(&self, value: Arc<T>) -> Arc<T> {
+        let mut handle = self.instance.enter();
+        let old_ptr = self
+            .current
+            .swap(Arc::into_raw(value) as *mut T, atomic::Ordering::AcqRel);
+
+        // # Safety:
+        // The Strong-Count owned by the AtomicArc is only released once the
+        // old Ptr has been reclaimed, because other Threads may still be
+        // accessing the Value through their Guards. So we create a new
+        // Strong-Count for the returned Arc instead
+        let previous = unsafe {
+            Arc::increment_strong_count(old_ptr);
+            Arc::from_raw(old_ptr)
+        };
+        unsafe { handle.retire(old_ptr as *const ()) };
+
+        previous
+    }
+
+    /// Replaces the current Value with `new`, if it is still the `current`
+    /// Value, otherwise `new` is returned again.
+    ///
+    /// The Values are compared by their Address, so `current` is usually
+    /// obtained by dereferencing a previously loaded [`ArcGuard`] or an
+    /// [`Arc`] that was stored before.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use nolock::arc::AtomicArc;
+    /// let value = AtomicArc::new(Arc::new(13));
+    ///
+    /// let guard = value.load();
+    /// assert!(value.compare_and_swap(&*guard, Arc::new(14)).is_ok());
+    ///
+    /// // The Value has changed since the Guard was loaded
+    /// let rejected = value.compare_and_swap(&*guard, Arc::new(15));
+    /// assert_eq!(15, *rejected.unwrap_err());
+    /// assert_eq!(14, *value.load());
+    /// ```
+    pub fn compare_and_swap(&self, current: &T, new: Arc<T>) -> Result<(), Arc<T>> {
+        let mut handle = self.instance.enter();
+        let new_ptr = Arc::into_raw(new) as *mut T;
+
+        match self.current.compare_exchange(
+            current as *const T as *mut T,
+            new_ptr,
+            atomic::Ordering::AcqRel,
+            atomic::Ordering::Acquire,
+        ) {
+            Ok(old_ptr) => {
+                // # Safety:
+                // The old Ptr was swapped out and can therefore not be loaded
+                // by any new Thread
+                unsafe { handle.retire(old_ptr as *const ()) };
+                Ok(())
+            }
+            // # Safety:
+            // The new Ptr was never stored, so we still own its Strong-Count
+            Err(_) => Err(unsafe { Arc::from_raw(new_ptr) }),
+        }
+    }
+
+    /// Consumes the AtomicArc and returns the currently stored Value
+    pub fn into_inner(mut self) -> Arc<T> {
+        let ptr = core::mem::replace(self.current.get_mut(), ptr::null_mut());
+        // # Safety:
+        // We took the Ptr out of the AtomicArc, so its Strong-Count is not
+        // released again when it is dropped
+        unsafe { Arc::from_raw(ptr) }
+    }
+}
+
+impl<T> Debug for AtomicArc<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "AtomicArc ()")
+    }
+}
+
+impl<T> Default for AtomicArc<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(Arc::default())
+    }
+}
+
+impl<T> From<Arc<T>> for AtomicArc<T> {
+    fn from(value: Arc<T>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Drop for AtomicArc<T> {
+    fn drop(&mut self) {
+        // The retired Values are released by the Hyaline-Instance itself
+        let ptr = *self.current.get_mut();
+        if !ptr.is_null() {
+            drop(unsafe { Arc::from_raw(ptr) });
+        }
+    }
+}
+
+// Safety:
+// The AtomicArc shares the Values between Threads just like an Arc does, so
+// it has the same Requirements
+unsafe impl<T> Send for AtomicArc<T> where T: Send + Sync {}
+unsafe impl<T> Sync for AtomicArc<T> where T: Send + Sync {}
+
+impl<'a, T> ArcGuard<'a, T> {
+    /// Creates a new [`Arc`] for the protected Value, which can outlive the
+    /// Guard
+    pub fn to_arc(&self) -> Arc<T> {
+        // # Safety:
+        // The Value is protected by the Handle, so the Strong-Count owned by
+        // the AtomicArc can not have been released yet
+        unsafe {
+            Arc::increment_strong_count(self.ptr);
+            Arc::from_raw(self.ptr)
+        }
+    }
+
+    /// Checks if the Guard and the Arc refer to the same Value
+    pub fn ptr_eq(&self, other: &Arc<T>) -> bool {
+        ptr::eq(self.ptr, Arc::as_ptr(other))
+    }
+}
+
+impl<'a, T> Deref for ArcGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // # Safety:
+        // The Value was loaded while holding the Handle and can therefore not
+        // be released before the Handle is dropped
+        unsafe { &*self.ptr }
+    }
+}
+
+impl<'a, T> Debug for ArcGuard<'a, T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("ArcGuard").field(&**self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+
+    #[test]
+    fn store_load() {
+        let value = AtomicArc::new(Arc::new(13));
+        assert_eq!(13, *value.load());
+
+        value.store(Arc::new(14));
+        assert_eq!(14, *value.load());
+    }
+
+    #[test]
+    fn guard_outlives_store() {
+        let value = AtomicArc::new(Arc::new(13));
+
+        let guard = value.load();
+        for i in 0..64 {
+            value.store(Arc::new(i));
+        }
+
+        assert_eq!(13, *guard);
+        assert_eq!(63, *value.load());
+    }
+
+    #[test]
+    fn to_arc_outlives_atomic() {
+        let value = AtomicArc::new(Arc::new(13));
+        let arc = value.load().to_arc();
+        drop(value);
+
+        assert_eq!(1, Arc::strong_count(&arc));
+        assert_eq!(13, *arc);
+    }
+
+    #[test]
+    fn compare_and_swap() {
+        let initial = Arc::new(13);
+        let value = AtomicArc::new(initial.clone());
+
+        assert!(value.load().ptr_eq(&initial));
+        assert_eq!(Err(Arc::new(0)), value.compare_and_swap(&13, Arc::new(0)));
+        assert_eq!(Ok(()), value.compare_and_swap(&*initial, Arc::new(14)));
+        assert_eq!(14, *value.load());
+    }
+
+    #[test]
+    fn releases_all_values() {
+        let inner = Arc::new(13);
+
+        let value = AtomicArc::new(Arc::new(inner.clone()));
+        for _ in 0..16 {
+            value.store(Arc::new(inner.clone()));
+        }
+        let previous = value.swap(Arc::new(inner.clone()));
+        drop(value);
+
+        assert_eq!(2, Arc::strong_count(&inner));
+        drop(previous);
+        assert_eq!(1, Arc::strong_count(&inner));
+    }
+
+    #[test]
+    fn into_inner() {
+        let value = AtomicArc::new(Arc::new(13));
+        value.store(Arc::new(14));
+
+        let inner = value.into_inner();
+        assert_eq!(1, Arc::strong_count(&inner));
+        assert_eq!(14, *inner);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn concurrent_increments() {
+        let value = Arc::new(AtomicArc::new(Arc::new(0u64)));
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let value = value.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..250 {
+                        loop {
+                            let current = value.load();
+                            let next = Arc::new(*current + 1);
+                            if value.compare_and_swap(&*current, next).is_ok() {
+                                break;
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in threads {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(1000, *value.load());
+    }
+}
@@ -0,0 +1,493 @@
+//! Shareable mutable Containers, that can be accessed without any Locks.
+//!
+//! # AtomicCell
+//! [`AtomicCell`] stores a single `Copy`-Value without any Padding, which
+//! can be loaded and replaced atomically from any Number of Threads, making
+//! it well suited for shared Configuration-Values or Statistics.
+//!
+//! # Example
+//! ```
+//! # use nolock::cell::{AtomicCell, NoUninit};
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+//! #[repr(C)]
+//! struct Config {
+//!     workers: u16,
+//!     verbose: bool,
+//!     level: u8,
+//! }
+//! // Safety: All the Fields are NoUninit and there is no Padding between them
+//! unsafe impl NoUninit for Config {}
+//!
+//! let config = AtomicCell::new(Config { workers: 4, verbose: false, level: 0 });
+//! assert!(AtomicCell::<Config>::is_lock_free());
+//!
+//! config.store(Config { workers: 8, verbose: true, level: 2 });
+//! assert_eq!(Config { workers: 8, verbose: true, level: 2 }, config.load());
+//! ```
+//!
+//! # SnapshotCell
+//! [`SnapshotCell`] stores larger Values, like Routing-Tables, that are
+//! replaced as a whole. Readers get consistent Snapshots without ever
+//! blocking, while the replaced Values are reclaimed using Hyaline.
+
+use core::{
+    cell::UnsafeCell,
+    fmt::Debug,
+    mem::{self, MaybeUninit},
+    num,
+    sync::atomic::{self, AtomicUsize},
+};
+
+use crate::utils::Backoff;
+
+#[cfg(feature = "hyaline")]
+mod snapshot;
+#[cfg(feature = "hyaline")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hyaline")))]
+pub use snapshot::{Snapshot, SnapshotCell};
+
+/// Marks Types, whose Values never contain any uninitialized Bytes, like
+/// Padding, which is needed to store them in an [`AtomicCell`], as the Cell
+/// treats the Values as their raw Bytes.
+///
+/// # Safety
+/// Every Byte of every Value of the Type must be initialized, so the Type
+/// must not contain any Padding or [`MaybeUninit`]-Fields and all of its
+/// Fields must be `NoUninit` themselves. For Structs this usually also means
+/// that they need to be `#[repr(C)]` or `#[repr(transparent)]`, to make sure
+/// that the Compiler does not introduce Padding.
+///
+/// # Example
+/// Tuples may contain Padding, so they can not be stored in an
+/// [`AtomicCell`]
+/// ```compile_fail
+/// # use nolock::cell::AtomicCell;
+/// let cell = AtomicCell::new((1u8, 2u16));
+/// cell.load();
+/// ```
+pub unsafe trait NoUninit: Copy + 'static {}
+
+macro_rules! impl_no_uninit {
+    ($($ty:ty),*) => {
+        $(
+            // Safety: Primitives have no Padding
+            unsafe impl NoUninit for $ty {}
+        )*
+    };
+}
+
+impl_no_uninit!(u8, u16, u32, u64, u128, usize);
+impl_no_uninit!(i8, i16, i32, i64, i128, isize);
+impl_no_uninit!(f32, f64, bool, char);
+impl_no_uninit!(
+    num::NonZeroU8,
+    num::NonZeroU16,
+    num::NonZeroU32,
+    num::NonZeroU64
+);
+impl_no_uninit!(num::NonZeroU128, num::NonZeroUsize);
+
+// Safety: The Size of a Type is always a Multiple of its Alignment, so there
+// is no Padding between the Elements of an Array
+unsafe impl<T, const N: usize> NoUninit for [T; N] where T: NoUninit {}
+
+/// Runs the Body with the native Atomic the Value of the Cell can be stored
+/// in and returns its Result, if there is such an Atomic for the Type
+macro_rules! with_native {
+    ($cell:expr, |$atomic:ident| $body:expr) => {
+        if let Some($atomic) = $cell.native::<atomic::AtomicU8>() {
+            return $body;
+        }
+        if let Some($atomic) = $cell.native::<atomic::AtomicU16>() {
+            return $body;
+        }
+        if let Some($atomic) = $cell.native::<atomic::AtomicU32>() {
+            return $body;
+        }
+        #[cfg(target_has_atomic = "64")]
+        {
+            if let Some($atomic) = $cell.native::<atomic::AtomicU64>() {
+                return $body;
+            }
+        }
+    };
+}
+
+/// A Cell that stores a [`NoUninit`]-Value, which can be loaded and replaced
+/// atomically by any Number of Threads.
+///
+/// # Storage
+/// If the Size of the Type matches one of the native Atomic-Integers (`u8`
+/// to `u64`), the Value is stored directly in such an Atomic and all the
+/// Operations are lock-free. The Cell is always aligned to 8 Bytes, so this
+/// also works for Types with a smaller Alignment. Every other Type,
+/// including 16-Byte Types for which there are no stable Atomics, uses a
+/// Sequence-Lock instead, where Loads never block but only retry if a Store
+/// happened concurrently.
+///
+/// # Comparison
+/// [`compare_exchange`](Self::compare_exchange) compares the Bytes of the
+/// Values, not their [`PartialEq`]-Implementation, so Types with multiple
+/// Representations for equal Values (like `-0.0` and `0.0`) may fail to
+/// compare as equal. This is also why the Values need to be [`NoUninit`].
+///
+/// # Example
+/// ```
+/// # use nolock::cell::AtomicCell;
+/// let cell = AtomicCell::new(13u32);
+///
+/// assert_eq!(13, cell.swap(14));
+/// assert_eq!(Ok(14), cell.compare_exchange(14, 15));
+/// assert_eq!(Err(15), cell.compare_exchange(14, 16));
+/// assert_eq!(15, cell.load());
+/// ```
+#[repr(C, align(8))]
+pub struct AtomicCell<T> {
+    /// The Value is the first Field, so it has the Alignment of the Cell
+    value: UnsafeCell<T>,
+    /// The Sequence-Number of the Sequence-Lock, which is odd while a Store
+    /// is in Progress and only used if the Type has no native Atomic
+    seq: AtomicUsize,
+}
+
+// Safety:
+// All the Accesses to the Value are either performed using Atomics or while
+// holding the Sequence-Lock, so the Cell can be shared as long as the Values
+// can be send between Threads
+unsafe impl<T> Sync for AtomicCell<T> where T: Send {}
+unsafe impl<T> Send for AtomicCell<T> where T: Send {}
+
+impl<T> Debug for AtomicCell<T>
+where
+    T: NoUninit + Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("AtomicCell").field(&self.load()).finish()
+    }
+}
+
+impl<T> Default for AtomicCell<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for AtomicCell<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> AtomicCell<T> {
+    /// Creates a new Cell containing the given Value
+    pub const fn new(value: T) -> Self {
+        Self {
+            value: UnsafeCell::new(value),
+            seq: AtomicUsize::new(0),
+        }
+    }
+
+    /// Consumes the Cell and returns the contained Value
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Returns a mutable Reference to the contained Value, which is safe as
+    /// the mutable Borrow guarantees that no other Thread can access it
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Checks if the Operations on a Cell of this Type are lock-free, which
+    /// is the Case if the Value can be stored in a native Atomic
+    pub fn is_lock_free() -> bool {
+        Self::fits::<atomic::AtomicU8>()
+            || Self::fits::<atomic::AtomicU16>()
+            || Self::fits::<atomic::AtomicU32>()
+            || Self::fits_u64()
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    fn fits_u64() -> bool {
+        Self::fits::<atomic::AtomicU64>()
+    }
+    #[cfg(not(target_has_atomic = "64"))]
+    fn fits_u64() -> bool {
+        false
+    }
+
+    /// Checks if the Value can be stored in the given Atomic, which only
+    /// depends on the Size as the Value is aligned to at least 8 Bytes
+    fn fits<A>() -> bool {
+        mem::size_of::<T>() == mem::size_of::<A>()
+    }
+
+    /// Returns the Value of the Cell as the given Atomic, if it fits into it
+    fn native<A>(&self) -> Option<&A> {
+        if !Self::fits::<A>() {
+            return None;
+        }
+
+        // # Safety:
+        // The Atomic has the same Size as the Value and the Value is aligned
+        // to 8 Bytes, which is enough for all the Atomics, so it can be
+        // accessed through it
+        Some(unsafe { &*(self.value.get() as *const A) })
+    }
+}
+
+impl<T> AtomicCell<T>
+where
+    T: NoUninit,
+{
+    /// Loads the current Value of the Cell
+    pub fn load(&self) -> T {
+        with_native!(self, |a| Self::from_int(a.load(atomic::Ordering::Acquire)));
+
+        let backoff = Backoff::new();
+        loop {
+            let seq = self.seq.load(atomic::Ordering::Acquire);
+            if seq & 1 == 0 {
+                // # Safety:
+                // The Read may race with a concurrent Store, in which case the
+                // Bytes can be torn and may not be a valid T. They are only
+                // read as MaybeUninit and the Sequence-Number changed, so they
+                // are discarded without ever being treated as a T
+                let value = unsafe { (self.value.get() as *const MaybeUninit<T>).read_volatile() };
+
+                atomic::fence(atomic::Ordering::Acquire);
+                if self.seq.load(atomic::Ordering::Relaxed) == seq {
+                    // # Safety:
+                    // The Sequence-Number did not change, so no Store happened
+                    // during the Read and it is a valid Value
+                    return unsafe { value.assume_init() };
+                }
+            }
+
+            backoff.snooze();
+        }
+    }
+
+    /// Stores the given Value in the Cell
+    pub fn store(&self, value: T) {
+        with_native!(self, |a| a
+            .store(Self::to_int(value), atomic::Ordering::Release));
+
+        self.swap(value);
+    }
+
+    /// Stores the given Value in the Cell and returns the previous Value
+    pub fn swap(&self, value: T) -> T {
+        with_native!(self, |a| Self::from_int(
+            a.swap(Self::to_int(value), atomic::Ordering::AcqRel)
+        ));
+
+        // # Safety:
+        // The Ptr is only used while holding the Sequence-Lock
+        self.locked(|ptr| unsafe { ptr.replace(value) })
+    }
+
+    /// Stores the `new` Value in the Cell, if its current Value is the same
+    /// as `current`.
+    ///
+    /// # Returns
+    /// * `Ok(previous)` if the Value was replaced
+    /// * `Err(actual)` with the actual Value of the Cell otherwise
+    pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T> {
+        with_native!(self, |a| a
+            .compare_exchange(
+                Self::to_int(current),
+                Self::to_int(new),
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+            )
+            .map(Self::from_int)
+            .map_err(Self::from_int));
+
+        // # Safety:
+        // The Ptr is only used while holding the Sequence-Lock
+        self.locked(|ptr| unsafe {
+            let actual = ptr.read();
+            if Self::bytes_eq(&actual, &current) {
+                ptr.write(new);
+                Ok(actual)
+            } else {
+                Err(actual)
+            }
+        })
+    }
+
+    /// Runs the given Function with exclusive Write-Access to the Value,
+    /// while holding the Sequence-Lock.
+    ///
+    /// The Function only gets a Ptr to the Value, because Readers may still
+    /// read it concurrently, so there must never be a `&mut T` to it
+    fn locked<F, R>(&self, func: F) -> R
+    where
+        F: FnOnce(*mut T) -> R,
+    {
+        let backoff = Backoff::new();
+        let seq = loop {
+            let seq = self.seq.load(atomic::Ordering::Relaxed);
+            if seq & 1 == 0
+                && self
+                    .seq
+                    .compare_exchange_weak(
+                        seq,
+                        seq.wrapping_add(1),
+                        atomic::Ordering::Acquire,
+                        atomic::Ordering::Relaxed,
+                    )
+                    .is_ok()
+            {
+                break seq;
+            }
+
+            backoff.snooze();
+        };
+        // Pairs with the Fence in `load`, so a Reader that observes any of
+        // our Writes also observes the odd Sequence-Number
+        atomic::fence(atomic::Ordering::Release);
+
+        // We hold the Sequence-Lock, so no other Thread writes to the Value
+        // and all the Readers discard what they read concurrently
+        let result = func(self.value.get());
+
+        self.seq
+            .store(seq.wrapping_add(2), atomic::Ordering::Release);
+        result
+    }
+
+    fn bytes_eq(first: &T, second: &T) -> bool {
+        // # Safety:
+        // Both References point to valid Values of T, which are read as
+        // their Bytes, all of which are initialized as T is NoUninit
+        let first = unsafe {
+            core::slice::from_raw_parts(first as *const T as *const u8, mem::size_of::<T>())
+        };
+        let second = unsafe {
+            core::slice::from_raw_parts(second as *const T as *const u8, mem::size_of::<T>())
+        };
+        first == second
+    }
+
+    fn to_int<I>(value: T) -> I
+    where
+        I: Copy,
+    {
+        // # Safety:
+        // This is only used with Integers that have the same Size as T and
+        // all the Bytes of T are initialized, as it is NoUninit
+        unsafe { mem::transmute_copy(&value) }
+    }
+    fn from_int<I>(value: I) -> T
+    where
+        I: Copy,
+    {
+        // # Safety:
+        // The Integer was obtained from a Value of T, see `to_int`
+        unsafe { mem::transmute_copy(&value) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn native_types() {
+        assert!(AtomicCell::<u8>::is_lock_free());
+        assert!(AtomicCell::<u32>::is_lock_free());
+        assert!(AtomicCell::<[u16; 2]>::is_lock_free());
+        assert!(AtomicCell::<[u8; 8]>::is_lock_free());
+        assert!(!AtomicCell::<[u8; 3]>::is_lock_free());
+        assert!(!AtomicCell::<u128>::is_lock_free());
+    }
+
+    #[test]
+    fn native_unaligned() {
+        let cell = AtomicCell::new([1u8, 2, 3, 4]);
+
+        assert_eq!(
+            Ok([1, 2, 3, 4]),
+            cell.compare_exchange([1, 2, 3, 4], [5, 6, 7, 8])
+        );
+        assert_eq!([5, 6, 7, 8], cell.load());
+        // The Sequence-Lock was never used
+        assert_eq!(0, cell.seq.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn native_operations() {
+        let cell = AtomicCell::new(-1i16);
+
+        assert_eq!(-1, cell.load());
+        cell.store(13);
+        assert_eq!(13, cell.swap(14));
+        assert_eq!(Err(14), cell.compare_exchange(13, 15));
+        assert_eq!(Ok(14), cell.compare_exchange(14, 15));
+        assert_eq!(15, cell.into_inner());
+    }
+
+    #[test]
+    fn native_bool() {
+        let cell = AtomicCell::new(false);
+
+        assert_eq!(Ok(false), cell.compare_exchange(false, true));
+        assert!(cell.load());
+        assert!(cell.swap(false));
+    }
+
+    #[test]
+    fn seqlock_invalid_bit_patterns() {
+        let cell = AtomicCell::new(['a'; 3]);
+
+        assert_eq!(['a'; 3], cell.swap(['b', 'c', 'd']));
+        assert_eq!(
+            Err(['b', 'c', 'd']),
+            cell.compare_exchange(['a'; 3], ['e'; 3])
+        );
+        assert_eq!(['b', 'c', 'd'], cell.load());
+    }
+
+    #[test]
+    fn seqlock_operations() {
+        let cell = AtomicCell::new([1u64, 2, 3]);
+
+        assert_eq!([1, 2, 3], cell.load());
+        cell.store([4, 5, 6]);
+        assert_eq!([4, 5, 6], cell.swap([7, 8, 9]));
+        assert_eq!(Err([7, 8, 9]), cell.compare_exchange([0, 0, 0], [1, 1, 1]));
+        assert_eq!(Ok([7, 8, 9]), cell.compare_exchange([7, 8, 9], [1, 1, 1]));
+        assert_eq!([1, 1, 1], cell.load());
+        // Every Operation, except for the Load, acquired the Lock once
+        assert_eq!(8, cell.seq.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn seqlock_no_torn_reads() {
+        use std::sync::Arc;
+
+        let cell = Arc::new(AtomicCell::new([0u64; 4]));
+
+        let writer = {
+            let cell = cell.clone();
+            std::thread::spawn(move || {
+                for i in 1..10_000u64 {
+                    cell.store([i; 4]);
+                }
+            })
+        };
+
+        for _ in 0..10_000 {
+            let value = cell.load();
+            assert!(value.iter().all(|v| *v == value[0]));
+        }
+
+        writer.join().unwrap();
+    }
+}
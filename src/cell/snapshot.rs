@@ -0,0 +1,337 @@
+use alloc::boxed::Box;
+use core::{
+    fmt::Debug,
+    marker::PhantomData,
+    ops::Deref,
+    sync::atomic::{self, AtomicPtr, AtomicUsize},
+};
+
+use crate::{hyaline, utils::Backoff};
+
+/// A Cell that stores a larger Value, which can be replaced at any Time while
+/// Readers keep working on consistent Snapshots of it.
+///
+/// Readers never block and never see a partially written Value, as every
+/// Write allocates a new Value and atomically swaps it in. The old Value is
+/// then retired using [`Hyaline`](crate::hyaline) and only dropped once no
+/// Reader can still access it. Writers are serialized by a Sequence-Lock, so
+/// [`update`](Self::update) can never lose a concurrent Update.
+///
+/// # Example
+/// ```
+/// # use nolock::cell::SnapshotCell;
+/// let routes = SnapshotCell::new(vec!["a", "b"]);
+///
+/// let snapshot = routes.read();
+/// routes.update(|old| {
+///     let mut new = old.clone();
+///     new.push("c");
+///     new
+/// });
+///
+/// // The old Snapshot stays valid and unchanged
+/// assert_eq!(&["a", "b"], snapshot.as_slice());
+/// assert_eq!(vec!["a", "b", "c"], routes.load());
+/// ```
+pub struct SnapshotCell<T> {
+    current: AtomicPtr<T>,
+    /// The Sequence-Number of the Writers, which is odd while a Writer is
+    /// replacing the Value
+    seq: AtomicUsize,
+    instance: hyaline::Hyaline,
+    _marker: PhantomData<T>,
+}
+
+/// Holds the Write-Lock of a [`SnapshotCell`] and releases it again when it
+/// is dropped without completing the Write, for example because the Function
+/// passed to [`update`](SnapshotCell::update) panicked
+struct WriteGuard<'a> {
+    seq: &'a AtomicUsize,
+}
+
+impl<'a> WriteGuard<'a> {
+    /// Releases the Lock after the Value was replaced, which moves the
+    /// Sequence-Number on to the next even Value
+    fn unlock(self) {
+        self.seq.fetch_add(1, atomic::Ordering::Release);
+        core::mem::forget(self);
+    }
+}
+
+impl<'a> Drop for WriteGuard<'a> {
+    fn drop(&mut self) {
+        // The Value was not replaced, so the Sequence-Number goes back to its
+        // previous even Value and the Version stays the same
+        self.seq.fetch_sub(1, atomic::Ordering::Release);
+    }
+}
+
+/// A consistent Snapshot of the Value in a [`SnapshotCell`].
+///
+/// The Value is protected from being dropped for as long as the Snapshot
+/// exists, even if it has already been replaced in the Cell.
+pub struct Snapshot<'a, T> {
+    ptr: *const T,
+    _handle: hyaline::Handle<'a>,
+}
+
+impl<T> SnapshotCell<T> {
+    fn free_fn(ptr: *const ()) {
+        unsafe { crate::poison::drop_box(ptr as *mut T) };
+    }
+
+    /// Creates a new Cell with the given initial Value
+    pub fn new(value: T) -> Self {
+        Self {
+            current: AtomicPtr::new(Box::into_raw(Box::new(value))),
+            seq: AtomicUsize::new(0),
+            instance: hyaline::Hyaline::new(Self::free_fn),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a Snapshot of the current Value
+    pub fn read(&self) -> Snapshot<'_, T> {
+        let handle = self.instance.enter();
+        let ptr = self.current.load(atomic::Ordering::Acquire);
+
+        Snapshot {
+            ptr,
+            _handle: handle,
+        }
+    }
+
+    /// Returns a Clone of the current Value
+    pub fn load(&self) -> T
+    where
+        T: Clone,
+    {
+        (*self.read()).clone()
+    }
+
+    /// Replaces the current Value with the given one
+    pub fn store(&self, value: T) {
+        self.update(|_| value);
+    }
+
+    /// Replaces the current Value with the Result of the Function, which is
+    /// called with the current Value.
+    ///
+    /// Writers are serialized, so no other Write can happen between reading
+    /// the current Value and storing the new one. The Function should
+    /// therefore be cheap, as other Writers spin while it is running.
+    ///
+    /// If the Function panics, the Value is left unchanged and the Cell can
+    /// still be used by other Writers
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::cell::SnapshotCell;
+    /// let counter = SnapshotCell::new(0);
+    ///
+    /// counter.update(|c| c + 1);
+    /// assert_eq!(1, counter.load());
+    /// ```
+    pub fn update<F>(&self, func: F)
+    where
+        F: FnOnce(&T) -> T,
+    {
+        let mut handle = self.instance.enter();
+
+        let guard = self.lock();
+        // # Safety:
+        // We hold the Write-Lock and therefore no other Thread can retire the
+        // current Value while we are accessing it
+        let new_ptr = Box::into_raw(Box::new(func(unsafe {
+            &*self.current.load(atomic::Ordering::Acquire)
+        })));
+        let old_ptr = self.current.swap(new_ptr, atomic::Ordering::AcqRel);
+        guard.unlock();
+
+        // # Safety:
+        // The old Value was swapped out and can therefore not be loaded by any
+        // new Reader
+        unsafe { handle.retire(old_ptr as *const ()) };
+    }
+
+    /// Returns the Number of Writes that have completed so far
+    pub fn version(&self) -> usize {
+        self.seq.load(atomic::Ordering::Acquire) / 2
+    }
+
+    /// Acquires the Write-Lock, by moving the Sequence-Number from an even
+    /// to an odd Value
+    fn lock(&self) -> WriteGuard<'_> {
+        let backoff = Backoff::new();
+        loop {
+            let seq = self.seq.load(atomic::Ordering::Relaxed);
+            if seq & 1 == 0
+                && self
+                    .seq
+                    .compare_exchange_weak(
+                        seq,
+                        seq + 1,
+                        atomic::Ordering::Acquire,
+                        atomic::Ordering::Relaxed,
+                    )
+                    .is_ok()
+            {
+                return WriteGuard { seq: &self.seq };
+            }
+            backoff.snooze();
+        }
+    }
+}
+
+impl<T> Debug for SnapshotCell<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SnapshotCell ()")
+    }
+}
+
+impl<T> Default for SnapshotCell<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for SnapshotCell<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Drop for SnapshotCell<T> {
+    fn drop(&mut self) {
+        // The retired Values are freed by the Hyaline-Instance itself
+        let ptr = *self.current.get_mut();
+        unsafe { crate::poison::drop_box(ptr) };
+    }
+}
+
+// Safety:
+// Values are created on one Thread and dropped on another one, while
+// References to them can be obtained by multiple Threads at the same Time,
+// so they need to be both Send and Sync
+unsafe impl<T> Send for SnapshotCell<T> where T: Send + Sync {}
+unsafe impl<T> Sync for SnapshotCell<T> where T: Send + Sync {}
+
+impl<'a, T> Deref for Snapshot<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        crate::poison::check(self.ptr);
+        // # Safety:
+        // The Value was loaded while holding the Handle and can therefore not
+        // be freed before the Handle is dropped
+        unsafe { &*self.ptr }
+    }
+}
+
+impl<'a, T> Debug for Snapshot<'a, T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Snapshot").field(&**self).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{sync::Arc, vec, vec::Vec};
+
+    #[test]
+    fn store_load() {
+        let cell = SnapshotCell::new(vec![1, 2, 3]);
+        assert_eq!(0, cell.version());
+
+        cell.store(vec![4, 5]);
+        assert_eq!(vec![4, 5], cell.load());
+        assert_eq!(1, cell.version());
+    }
+
+    #[test]
+    fn snapshot_outlives_store() {
+        let cell = SnapshotCell::new(vec![1, 2, 3]);
+
+        let snapshot = cell.read();
+        for i in 0..64 {
+            cell.store(vec![i]);
+        }
+
+        assert_eq!(&[1, 2, 3], snapshot.as_slice());
+        assert_eq!(vec![63], cell.load());
+    }
+
+    #[test]
+    fn drops_all_values() {
+        let value = Arc::new(13);
+
+        let cell = SnapshotCell::new(value.clone());
+        for _ in 0..16 {
+            cell.store(value.clone());
+        }
+        drop(cell);
+
+        assert_eq!(1, Arc::strong_count(&value));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn panicking_update() {
+        let cell = SnapshotCell::new(vec![1, 2, 3]);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            cell.update(|_| panic!("Update failed"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(vec![1, 2, 3], cell.load());
+        assert_eq!(0, cell.version());
+
+        // The Write-Lock was released again
+        cell.store(vec![4]);
+        assert_eq!(vec![4], cell.load());
+        assert_eq!(1, cell.version());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn concurrent_updates() {
+        let cell = Arc::new(SnapshotCell::new(vec![0u64; 8]));
+
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let cell = cell.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..250 {
+                        cell.update(|old| old.iter().map(|v| v + 1).collect());
+                    }
+                })
+            })
+            .collect();
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let cell = cell.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        let snapshot = cell.read();
+                        assert!(snapshot.iter().all(|v| *v == snapshot[0]));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in writers.into_iter().chain(readers) {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(vec![1000u64; 8], cell.load());
+        assert_eq!(1000, cell.version());
+    }
+}
@@ -0,0 +1,416 @@
+//! Tools for recording Diagnostics from the Hot-Paths of lock-free
+//! Algorithms, like Reclamation-Cycles or contended CAS-Loops.
+//!
+//! # EventRing
+//! [`EventRing`] is a fixed-size Ring of Events, which any Number of Threads
+//! can record into without ever waiting or allocating, while a single
+//! diagnostic Thread periodically reads all the new Events in bulk. Once the
+//! Ring is full, the oldest Events are overwritten, so recording never slows
+//! down the instrumented Code, but Events may be lost if they are not read
+//! fast enough. The Reader is told how many Events it missed.
+//!
+//! # Example
+//! ```
+//! # use nolock::diagnostics::EventRing;
+//! #[derive(Debug, Clone, Copy, PartialEq)]
+//! enum Event {
+//!     Reclaimed(usize),
+//!     CasFailed,
+//! }
+//!
+//! static EVENTS: EventRing<Event, 64> = EventRing::new();
+//!
+//! let workers: Vec<_> = (0..4)
+//!     .map(|_| {
+//!         std::thread::spawn(|| {
+//!             EVENTS.record(Event::CasFailed);
+//!             EVENTS.record(Event::Reclaimed(2));
+//!         })
+//!     })
+//!     .collect();
+//! for worker in workers {
+//!     worker.join().unwrap();
+//! }
+//!
+//! let mut events = Vec::new();
+//! let lost = EVENTS.drain(|_, event| events.push(event));
+//! assert_eq!(0, lost);
+//! assert_eq!(8, events.len());
+//! ```
+
+use core::{cell::UnsafeCell, fmt::Debug, mem::MaybeUninit};
+
+use crate::{sync::native::atomic, utils::CachePadded};
+
+/// Set in the State of a Slot while it is being written or read
+const BUSY: usize = 1;
+
+/// The State of a Slot that contains the Event with the given Sequence-Number.
+///
+/// The State wraps around together with the Sequence-Numbers, so States must
+/// only be compared using [`newer`]
+fn stored(seq: usize) -> usize {
+    seq.wrapping_add(1) << 1
+}
+
+/// Checks if the State `a` belongs to a newer Event than the State `b`, which
+/// still works after the States wrapped around, as long as they are less than
+/// half of their Range apart
+fn newer(a: usize, b: usize) -> bool {
+    (a.wrapping_sub(b) as isize) > 0
+}
+
+/// A single Entry in the Ring
+struct Slot<T> {
+    /// The Sequence-Number of the Event stored in the Slot, see [`stored`],
+    /// together with the [`BUSY`]-Flag, or 0 if nothing has been stored yet
+    state: atomic::AtomicUsize,
+    /// The State of the last Event that a Producer had to drop for this
+    /// Slot, so the Reader can skip it instead of waiting for it
+    dropped: atomic::AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Slot<T> {
+    // This is only used to initialize the Array of Slots, where every Slot
+    // is supposed to be a new Instance
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY: Self = Self {
+        state: atomic::AtomicUsize::new(0),
+        dropped: atomic::AtomicUsize::new(0),
+        value: UnsafeCell::new(MaybeUninit::uninit()),
+    };
+}
+
+/// A wait-free multi-producer Ring of Events, that overwrites the oldest
+/// Events once it is full, see the [`module-level documentation`](self) for
+/// more Details.
+///
+/// # Lost Events
+/// Besides being overwritten before they are read, Events are also dropped
+/// if their Slot is still being written by a Producer that wrapped around
+/// the whole Ring, or is currently being read, as the Producer would
+/// otherwise have to wait.
+pub struct EventRing<T, const N: usize> {
+    /// The Sequence-Number of the next Event that will be recorded
+    head: CachePadded<atomic::AtomicUsize>,
+    /// The Sequence-Number of the next Event that will be read
+    read: CachePadded<atomic::AtomicUsize>,
+    slots: [Slot<T>; N],
+}
+
+// Safety:
+// Every Slot is only ever accessed by the single Thread that set its
+// BUSY-Flag, so the Events are only moved between Threads, which requires
+// them to be Send
+unsafe impl<T, const N: usize> Sync for EventRing<T, N> where T: Send {}
+
+impl<T, const N: usize> EventRing<T, N>
+where
+    T: Copy,
+{
+    /// Creates a new empty Ring, which can store up to `N` Events
+    ///
+    /// # Panics
+    /// If `N` is 0
+    pub const fn new() -> Self {
+        assert!(N > 0, "An EventRing needs at least one Slot");
+
+        Self {
+            head: CachePadded::new(atomic::AtomicUsize::new(0)),
+            read: CachePadded::new(atomic::AtomicUsize::new(0)),
+            slots: [Slot::EMPTY; N],
+        }
+    }
+
+    /// The Number of Events the Ring can hold, before it starts to overwrite
+    /// the oldest ones
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// The total Number of Events that have been recorded into the Ring,
+    /// including the ones that have been lost
+    pub fn recorded(&self) -> usize {
+        self.head.load(atomic::Ordering::Acquire)
+    }
+
+    /// Records a new Event into the Ring, overwriting the oldest Event if
+    /// the Ring is full.
+    ///
+    /// This is wait-free and never allocates, so it can be called from any
+    /// Hot-Path. Returns `false` if the Event had to be dropped, see
+    /// [Lost Events](EventRing#lost-events)
+    pub fn record(&self, event: T) -> bool {
+        let seq = self.head.fetch_add(1, atomic::Ordering::AcqRel);
+        let slot = &self.slots[seq % N];
+
+        let state = slot.state.load(atomic::Ordering::Acquire);
+        // The Slot is in use right now or a newer Event has already been
+        // stored in it, because this Producer was delayed for a whole Round
+        if state & BUSY != 0
+            || !newer(stored(seq), state)
+            || slot
+                .state
+                .compare_exchange(
+                    state,
+                    stored(seq) | BUSY,
+                    atomic::Ordering::Acquire,
+                    atomic::Ordering::Relaxed,
+                )
+                .is_err()
+        {
+            // Tell the Reader that this Event will never be stored
+            slot.dropped.store(stored(seq), atomic::Ordering::Release);
+            return false;
+        }
+
+        // # Safety:
+        // We set the BUSY-Flag, so no other Thread accesses the Slot until
+        // we clear it again
+        unsafe { slot.value.get().write(MaybeUninit::new(event)) };
+        slot.state.store(stored(seq), atomic::Ordering::Release);
+
+        true
+    }
+
+    /// Reads all the Events that were recorded since the last Read, in the
+    /// Order they were recorded in, passing every Event together with its
+    /// Sequence-Number to the given Function.
+    ///
+    /// Returns the Number of Events that were lost since the last Read,
+    /// because they were overwritten or dropped.
+    ///
+    /// # Pending Events
+    /// If an Event is still being recorded, the Read stops before it and it
+    /// will be returned by the next Read instead, so Events are never
+    /// returned out of Order. Events that were dropped by their Producer are
+    /// counted as lost and do not stop the Read.
+    ///
+    /// # Note
+    /// This is meant to be called by a single diagnostic Thread. Concurrent
+    /// Reads are still safe, but may return the same Events multiple Times.
+    pub fn drain<F>(&self, mut func: F) -> usize
+    where
+        F: FnMut(usize, T),
+    {
+        let head = self.head.load(atomic::Ordering::Acquire);
+        let mut seq = self.read.load(atomic::Ordering::Acquire);
+        let mut lost = 0;
+
+        // Everything older than the last Round has definitely been overwritten
+        if head.wrapping_sub(seq) > N {
+            lost += head.wrapping_sub(seq) - N;
+            seq = head.wrapping_sub(N);
+        }
+
+        while seq != head {
+            let slot = &self.slots[seq % N];
+            let expected = stored(seq);
+
+            let state = slot.state.load(atomic::Ordering::Acquire);
+            if newer(state & !BUSY, expected)
+                || (state & !BUSY != expected
+                    && slot.dropped.load(atomic::Ordering::Acquire) == expected)
+            {
+                // The Event was overwritten by a newer one or dropped by its
+                // Producer
+                lost += 1;
+                seq = seq.wrapping_add(1);
+                continue;
+            }
+            if state != expected {
+                // The Event is still pending
+                break;
+            }
+            if slot
+                .state
+                .compare_exchange(
+                    expected,
+                    expected | BUSY,
+                    atomic::Ordering::Acquire,
+                    atomic::Ordering::Relaxed,
+                )
+                .is_err()
+            {
+                // A Producer or another Reader claimed the Slot in the mean
+                // Time, so we check its State again
+                continue;
+            }
+
+            // # Safety:
+            // We set the BUSY-Flag, so no other Thread accesses the Slot and
+            // the State showed that the Event has been completely written
+            let event = unsafe { slot.value.get().read().assume_init() };
+            slot.state.store(expected, atomic::Ordering::Release);
+
+            func(seq, event);
+            seq = seq.wrapping_add(1);
+        }
+
+        self.read.store(seq, atomic::Ordering::Release);
+        lost
+    }
+}
+
+impl<T, const N: usize> Default for EventRing<T, N>
+where
+    T: Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Debug for EventRing<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "EventRing (capacity: {}, recorded: {})",
+            N,
+            self.head.load(atomic::Ordering::Acquire)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::{vec, vec::Vec};
+
+    fn drain_all<const N: usize>(ring: &EventRing<usize, N>) -> (Vec<(usize, usize)>, usize) {
+        let mut events = Vec::new();
+        let lost = ring.drain(|seq, event| events.push((seq, event)));
+        (events, lost)
+    }
+
+    #[test]
+    fn record_drain() {
+        let ring = EventRing::<usize, 4>::new();
+        assert_eq!((Vec::new(), 0), drain_all(&ring));
+
+        assert!(ring.record(13));
+        assert!(ring.record(14));
+        assert_eq!((vec![(0, 13), (1, 14)], 0), drain_all(&ring));
+        assert_eq!((Vec::new(), 0), drain_all(&ring));
+
+        assert!(ring.record(15));
+        assert_eq!((vec![(2, 15)], 0), drain_all(&ring));
+        assert_eq!(3, ring.recorded());
+    }
+
+    #[test]
+    fn overwrites_oldest() {
+        let ring = EventRing::<usize, 4>::new();
+        for event in 0..10 {
+            assert!(ring.record(event));
+        }
+
+        assert_eq!((vec![(6, 6), (7, 7), (8, 8), (9, 9)], 6), drain_all(&ring));
+    }
+
+    #[test]
+    fn pending_event_stops_read() {
+        let ring = EventRing::<usize, 4>::new();
+        ring.record(0);
+
+        // Simulate a Producer that took a Sequence-Number, but has not stored
+        // its Event yet
+        let seq = ring.head.fetch_add(1, atomic::Ordering::AcqRel);
+        ring.record(2);
+        assert_eq!((vec![(0, 0)], 0), drain_all(&ring));
+
+        let slot = &ring.slots[seq % 4];
+        unsafe { slot.value.get().write(MaybeUninit::new(1)) };
+        slot.state.store(stored(seq), atomic::Ordering::Release);
+        assert_eq!((vec![(1, 1), (2, 2)], 0), drain_all(&ring));
+    }
+
+    #[test]
+    fn dropped_event_is_skipped() {
+        let ring = EventRing::<usize, 4>::new();
+        ring.record(0);
+
+        // The Slot of the next Event is still being read
+        let slot = &ring.slots[1];
+        slot.state.store(BUSY, atomic::Ordering::Release);
+        assert!(!ring.record(1));
+        slot.state.store(0, atomic::Ordering::Release);
+
+        assert!(ring.record(2));
+        assert_eq!((vec![(0, 0), (2, 2)], 1), drain_all(&ring));
+
+        assert!(ring.record(3));
+        assert_eq!((vec![(3, 3)], 0), drain_all(&ring));
+    }
+
+    #[test]
+    fn sequence_wraps_around() {
+        let ring = EventRing::<usize, 4>::new();
+
+        // Start right before the States wrap around, with the Events of the
+        // previous Round already read
+        let start = usize::MAX / 2 - 5;
+        for seq in (start - 4)..start {
+            ring.slots[seq % 4]
+                .state
+                .store(stored(seq), atomic::Ordering::Release);
+        }
+        ring.head.store(start, atomic::Ordering::Release);
+        ring.read.store(start, atomic::Ordering::Release);
+
+        for event in 0..6 {
+            assert!(ring.record(event));
+        }
+        let (events, lost) = drain_all(&ring);
+        assert_eq!(2, lost);
+        assert_eq!(
+            vec![2, 3, 4, 5],
+            events.iter().map(|(_, e)| *e).collect::<Vec<_>>()
+        );
+
+        for event in 6..10 {
+            assert!(ring.record(event));
+        }
+        let (events, lost) = drain_all(&ring);
+        assert_eq!(0, lost);
+        assert_eq!(
+            vec![6, 7, 8, 9],
+            events.iter().map(|(_, e)| *e).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[cfg_attr(miri, ignore)]
+    fn concurrent_producers() {
+        static RING: EventRing<usize, 1024> = EventRing::new();
+
+        let producers: Vec<_> = (0..4)
+            .map(|thread| {
+                std::thread::spawn(move || {
+                    for i in 0..100 {
+                        RING.record(thread * 1000 + i);
+                    }
+                })
+            })
+            .collect();
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let (events, lost) = drain_all(&RING);
+        assert_eq!(0, lost);
+        assert_eq!(400, events.len());
+        // The Events of every Producer are in the Order they were recorded
+        for thread in 0..4 {
+            let own: Vec<_> = events
+                .iter()
+                .map(|(_, e)| *e)
+                .filter(|e| e / 1000 == thread)
+                .collect();
+            assert_eq!((0..100).map(|i| thread * 1000 + i).collect::<Vec<_>>(), own);
+        }
+    }
+}
@@ -0,0 +1,442 @@
+//! Integration with the [`metrics`](::metrics) Facade
+//!
+//! This Module provides Recorders, that forward the Instrumentation of the
+//! Datastructures in this crate to the Counters and Gauges of the `metrics`
+//! Facade, so that they can be exported by any `metrics`-Exporter, like the
+//! Prometheus-Exporter, without having to write the Glue-Code yourself.
+//!
+//! All the Metrics of a single Datastructure share a common Prefix and Set
+//! of Labels, configured using [`MetricNames`], so multiple instances of the
+//! same Datastructure can be told apart in the exported Metrics.
+//!
+//! # Metrics
+//! Recorder | Metric | Kind
+//! --- | --- | ---
+//! [`QueueRecorder`] | `{prefix}_enqueued_total` | Counter
+//! [`QueueRecorder`] | `{prefix}_dequeued_total` | Counter
+//! [`QueueRecorder`] | `{prefix}_full_total` | Counter
+//! [`QueueRecorder`] | `{prefix}_segment_allocs_total` | Counter
+//! [`QueueRecorder`] | `{prefix}_length` | Gauge
+//! `MapRecorder` | `{prefix}_cas_failures_total` | Counter, with a `site` Label
+//! `MapRecorder` | `{prefix}_levels_created_total` | Counter
+//! `MapRecorder` | `{prefix}_levels_collapsed_total` | Counter
+//! `MapRecorder` | `{prefix}_stale_lookups_total` | Counter
+//! [`ReclamationRecorder`] | `{prefix}_retired_total` | Counter
+//! [`ReclamationRecorder`] | `{prefix}_freed_total` | Counter
+//! [`ReclamationRecorder`] | `{prefix}_batches_freed_total` | Counter
+//! [`ReclamationRecorder`] | `{prefix}_pending` | Gauge
+//! [`ReclamationRecorder`] | `{prefix}_hazard_records` | Gauge
+//!
+//! # Example
+//! ```
+//! # use nolock::facade::{MetricNames, QueueRecorder};
+//! # use nolock::queues::spsc::bounded;
+//! # use std::sync::Arc;
+//! let recorder = QueueRecorder::new(MetricNames::new("jobs").with_label("worker", "0"));
+//! let (mut rx, mut tx) = bounded::queue_with_metrics::<usize>(16, Arc::new(recorder));
+//!
+//! // Reported as `jobs_enqueued_total{worker="0"}` to the installed Recorder
+//! tx.try_enqueue(13).unwrap();
+//! assert_eq!(Ok(13), rx.try_dequeue());
+//! ```
+
+use std::{fmt::Debug, string::String, vec::Vec};
+
+use ::metrics::{Counter, Gauge, Label};
+
+use crate::queues::metrics::QueueMetrics;
+
+/// The Names and Labels used for all the Metrics of a single Datastructure
+#[derive(Clone)]
+pub struct MetricNames {
+    prefix: String,
+    labels: Vec<Label>,
+}
+
+impl MetricNames {
+    /// Creates a new Set of Names, where every Metric-Name starts with the
+    /// given Prefix, followed by an `_` and the Name of the Metric itself
+    pub fn new<P>(prefix: P) -> Self
+    where
+        P: Into<String>,
+    {
+        Self {
+            prefix: prefix.into(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Adds a Label, that will be attached to all the Metrics
+    pub fn with_label<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.labels.push(Label::new(key.into(), value.into()));
+        self
+    }
+
+    /// The full Name of the given Metric
+    pub fn name(&self, metric: &str) -> String {
+        let mut name = String::with_capacity(self.prefix.len() + 1 + metric.len());
+        name.push_str(&self.prefix);
+        name.push('_');
+        name.push_str(metric);
+        name
+    }
+
+    fn counter(&self, metric: &str) -> Counter {
+        ::metrics::counter!(self.name(metric), self.labels.clone())
+    }
+
+    #[cfg(feature = "hash_trie")]
+    fn counter_with(&self, metric: &str, key: &'static str, value: &'static str) -> Counter {
+        let mut labels = self.labels.clone();
+        labels.push(Label::new(key, value));
+        ::metrics::counter!(self.name(metric), labels)
+    }
+
+    fn gauge(&self, metric: &str) -> Gauge {
+        ::metrics::gauge!(self.name(metric), self.labels.clone())
+    }
+}
+
+impl Debug for MetricNames {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MetricNames ({})", self.prefix)
+    }
+}
+
+/// A [`QueueMetrics`] implementation, that reports all the Operations of a
+/// Queue to the `metrics` Facade.
+///
+/// The Handles for the Metrics are registered once when creating the
+/// Recorder, so the Hooks only need to update them
+pub struct QueueRecorder {
+    enqueued: Counter,
+    dequeued: Counter,
+    full: Counter,
+    segment_allocs: Counter,
+    length: Gauge,
+}
+
+impl QueueRecorder {
+    /// Registers the Metrics for a Queue, using the given Names, with the
+    /// currently installed global Recorder of the `metrics` Facade
+    pub fn new(names: MetricNames) -> Self {
+        Self {
+            enqueued: names.counter("enqueued_total"),
+            dequeued: names.counter("dequeued_total"),
+            full: names.counter("full_total"),
+            segment_allocs: names.counter("segment_allocs_total"),
+            length: names.gauge("length"),
+        }
+    }
+}
+
+impl Debug for QueueRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "QueueRecorder ()")
+    }
+}
+
+impl QueueMetrics for QueueRecorder {
+    fn on_enqueue(&self) {
+        self.enqueued.increment(1);
+        self.length.increment(1.0);
+    }
+
+    fn on_dequeue(&self) {
+        self.dequeued.increment(1);
+        self.length.decrement(1.0);
+    }
+
+    fn on_full(&self) {
+        self.full.increment(1);
+    }
+
+    fn on_segment_alloc(&self) {
+        self.segment_allocs.increment(1);
+    }
+}
+
+#[cfg(feature = "hash_trie")]
+pub use map::MapRecorder;
+
+#[cfg(feature = "hash_trie")]
+mod map {
+    use std::fmt::Debug;
+
+    use ::metrics::Counter;
+
+    use super::MetricNames;
+    use crate::hash_trie::events::{CasFailure, MapEvents};
+
+    /// A [`MapEvents`] implementation, that reports the Contention and
+    /// Restructuring of a [`HashTrieMap`](crate::hash_trie::HashTrieMap) to
+    /// the `metrics` Facade
+    #[cfg_attr(docsrs, doc(cfg(feature = "hash_trie")))]
+    pub struct MapRecorder {
+        chain_append: Counter,
+        level_expansion: Counter,
+        bucket_insert: Counter,
+        levels_created: Counter,
+        levels_collapsed: Counter,
+        stale_lookups: Counter,
+    }
+
+    impl MapRecorder {
+        /// Registers the Metrics for a Map, using the given Names, with the
+        /// currently installed global Recorder of the `metrics` Facade
+        pub fn new(names: MetricNames) -> Self {
+            let cas_failures = "cas_failures_total";
+            Self {
+                chain_append: names.counter_with(cas_failures, "site", "chain_append"),
+                level_expansion: names.counter_with(cas_failures, "site", "level_expansion"),
+                bucket_insert: names.counter_with(cas_failures, "site", "bucket_insert"),
+                levels_created: names.counter("levels_created_total"),
+                levels_collapsed: names.counter("levels_collapsed_total"),
+                stale_lookups: names.counter("stale_lookups_total"),
+            }
+        }
+    }
+
+    impl Debug for MapRecorder {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "MapRecorder ()")
+        }
+    }
+
+    impl MapEvents for MapRecorder {
+        fn on_cas_failure(&self, kind: CasFailure) {
+            match kind {
+                CasFailure::ChainAppend => self.chain_append.increment(1),
+                CasFailure::LevelExpansion => self.level_expansion.increment(1),
+                CasFailure::BucketInsert => self.bucket_insert.increment(1),
+            };
+        }
+
+        fn on_level_created(&self) {
+            self.levels_created.increment(1);
+        }
+
+        fn on_level_collapsed(&self) {
+            self.levels_collapsed.increment(1);
+        }
+
+        fn on_stale_lookup(&self) {
+            self.stale_lookups.increment(1);
+        }
+    }
+}
+
+/// Reports the State of the Memory-Reclamation Schemes to the `metrics`
+/// Facade.
+///
+/// Unlike the other Recorders, the Reclamation-Schemes are not instrumented
+/// using Hooks, but keep their own Statistics instead. These need to be
+/// reported periodically, for example right before the Metrics are scraped
+pub struct ReclamationRecorder {
+    #[cfg(feature = "hyaline")]
+    retired: Counter,
+    #[cfg(feature = "hyaline")]
+    freed: Counter,
+    #[cfg(feature = "hyaline")]
+    batches_freed: Counter,
+    #[cfg(feature = "hyaline")]
+    pending: Gauge,
+    #[cfg(feature = "hazard_ptr")]
+    hazard_records: Gauge,
+}
+
+impl ReclamationRecorder {
+    /// Registers the Metrics for a Reclamation-Scheme, using the given
+    /// Names, with the currently installed global Recorder of the `metrics`
+    /// Facade
+    pub fn new(names: MetricNames) -> Self {
+        let _ = &names;
+        Self {
+            #[cfg(feature = "hyaline")]
+            retired: names.counter("retired_total"),
+            #[cfg(feature = "hyaline")]
+            freed: names.counter("freed_total"),
+            #[cfg(feature = "hyaline")]
+            batches_freed: names.counter("batches_freed_total"),
+            #[cfg(feature = "hyaline")]
+            pending: names.gauge("pending"),
+            #[cfg(feature = "hazard_ptr")]
+            hazard_records: names.gauge("hazard_records"),
+        }
+    }
+
+    /// Reports the current Statistics of a [`Hyaline`](crate::hyaline::Hyaline)
+    /// Instance
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::facade::{MetricNames, ReclamationRecorder};
+    /// # use nolock::hyaline::Hyaline;
+    /// let instance = Hyaline::<4>::new(|_| {});
+    /// let recorder = ReclamationRecorder::new(MetricNames::new("hyaline"));
+    ///
+    /// recorder.record_retire_stats(&instance.stats());
+    /// ```
+    #[cfg(feature = "hyaline")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hyaline")))]
+    pub fn record_retire_stats(&self, stats: &crate::hyaline::RetireStats) {
+        self.retired.absolute(stats.retired);
+        self.freed.absolute(stats.freed);
+        self.batches_freed.absolute(stats.batches_freed);
+        self.pending.set(stats.pending() as f64);
+    }
+
+    /// Reports the Number of Hazard-Records currently allocated by the given
+    /// [`Domain`](crate::hazard_ptr::Domain)
+    #[cfg(feature = "hazard_ptr")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hazard_ptr")))]
+    pub fn record_hazard_domain(&self, domain: &crate::hazard_ptr::Domain) {
+        self.hazard_records.set(domain.record_count() as f64);
+    }
+}
+
+impl Debug for ReclamationRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReclamationRecorder ()")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{
+        collections::BTreeMap,
+        sync::{atomic, Arc, Mutex},
+    };
+
+    use ::metrics::{Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+
+    /// Stores every registered Metric under its Name and Labels
+    #[derive(Default)]
+    struct TestRecorder {
+        metrics: Mutex<BTreeMap<String, Arc<atomic::AtomicU64>>>,
+    }
+
+    impl TestRecorder {
+        fn handle(&self, key: &Key) -> Arc<atomic::AtomicU64> {
+            let mut name = key.name().to_owned();
+            for label in key.labels() {
+                name.push_str(&format!(",{}={}", label.key(), label.value()));
+            }
+            self.metrics
+                .lock()
+                .unwrap()
+                .entry(name)
+                .or_default()
+                .clone()
+        }
+
+        fn counter(&self, name: &str) -> u64 {
+            self.metrics.lock().unwrap()[name].load(atomic::Ordering::SeqCst)
+        }
+
+        fn gauge(&self, name: &str) -> f64 {
+            f64::from_bits(self.counter(name))
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.handle(key))
+        }
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.handle(key))
+        }
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn metric_names() {
+        let names = MetricNames::new("queue").with_label("id", "1");
+
+        assert_eq!("queue_enqueued_total", names.name("enqueued_total"));
+    }
+
+    #[test]
+    fn queue_recorder() {
+        let recorder = TestRecorder::default();
+        let queue = ::metrics::with_local_recorder(&recorder, || {
+            QueueRecorder::new(MetricNames::new("queue").with_label("id", "1"))
+        });
+
+        queue.on_enqueue();
+        queue.on_enqueue();
+        queue.on_dequeue();
+        queue.on_full();
+
+        assert_eq!(2, recorder.counter("queue_enqueued_total,id=1"));
+        assert_eq!(1, recorder.counter("queue_dequeued_total,id=1"));
+        assert_eq!(1, recorder.counter("queue_full_total,id=1"));
+        assert_eq!(0, recorder.counter("queue_segment_allocs_total,id=1"));
+        assert_eq!(1.0, recorder.gauge("queue_length,id=1"));
+    }
+
+    #[test]
+    fn queue_recorder_without_recorder() {
+        // Without a Recorder, the Handles simply discard the Updates
+        let recorder = QueueRecorder::new(MetricNames::new("queue"));
+
+        recorder.on_enqueue();
+        recorder.on_dequeue();
+    }
+
+    #[test]
+    #[cfg(feature = "hash_trie")]
+    fn map_recorder() {
+        use crate::hash_trie::events::{CasFailure, MapEvents};
+
+        let recorder = TestRecorder::default();
+        let map =
+            ::metrics::with_local_recorder(&recorder, || MapRecorder::new(MetricNames::new("map")));
+
+        map.on_cas_failure(CasFailure::BucketInsert);
+        map.on_level_created();
+
+        assert_eq!(
+            1,
+            recorder.counter("map_cas_failures_total,site=bucket_insert")
+        );
+        assert_eq!(
+            0,
+            recorder.counter("map_cas_failures_total,site=chain_append")
+        );
+        assert_eq!(1, recorder.counter("map_levels_created_total"));
+    }
+
+    #[test]
+    #[cfg(feature = "hyaline")]
+    fn reclamation_recorder() {
+        let recorder = TestRecorder::default();
+        let reclamation = ::metrics::with_local_recorder(&recorder, || {
+            ReclamationRecorder::new(MetricNames::new("hyaline"))
+        });
+
+        reclamation.record_retire_stats(&crate::hyaline::RetireStats {
+            retired: 10,
+            freed: 7,
+            batches_freed: 2,
+            #[cfg(feature = "std")]
+            total_residence: core::time::Duration::ZERO,
+        });
+
+        assert_eq!(10, recorder.counter("hyaline_retired_total"));
+        assert_eq!(7, recorder.counter("hyaline_freed_total"));
+        assert_eq!(3.0, recorder.gauge("hyaline_pending"));
+    }
+}
@@ -26,6 +26,13 @@
 //! The Implementation is based on the two Papers listed in the Reference section and uses
 //! [hyaline](crate::hyaline) as its memory Reclaimation scheme
 //!
+//! Sub-Levels are created once a Chain in a Bucket grows too long. Once all
+//! the Entries of a Sub-Level have been removed again, the Sub-Level is
+//! collapsed, meaning that it is removed from its Parent and retired using
+//! hyaline, so the Memory used by the Map shrinks again after Churn.
+//! Sub-Levels that still contain Entries are never folded back into their
+//! Parent.
+//!
 //! # Reference:
 //! * [A Lock-Free Hash Trie Design for Concurrent Tabled Logic Programs](https://link.springer.com/content/pdf/10.1007/s10766-014-0346-1.pdf)
 //! * [Towards a Lock-Free, Fixed Size and Persistent Hash Map Design](https://repositorio.inesctec.pt/bitstream/123456789/6155/1/P-00N-B3Y.pdf)
@@ -35,20 +42,27 @@ use std::collections::hash_map::RandomState;
 
 use core::{
     fmt::Debug,
-    hash::{BuildHasher, Hash, Hasher},
+    hash::{BuildHasher, Hash},
     marker::PhantomData,
 };
 
 use alloc::boxed::Box;
 
 mod entry;
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub mod events;
 mod hashlevel;
+mod instrument;
 mod mptr;
+mod pinned;
 mod refvalue;
 use entry::Entry;
 use hashlevel::HashLevel;
+use instrument::Events;
 
-pub use refvalue::RefValue;
+pub use pinned::PinnedMap;
+pub use refvalue::{MappedRefValue, RefValue};
 
 use crate::hyaline;
 
@@ -66,6 +80,13 @@ impl<K, V> HashTrieMap<K, V, RandomState> {
     pub fn new() -> Self {
         Self::with_build_hasher(std::collections::hash_map::RandomState::new())
     }
+
+    /// Creates a new HashTrieMap, that is already sized to hold roughly
+    /// `capacity` Entries, see
+    /// [`with_capacity_and_build_hasher`](Self::with_capacity_and_build_hasher)
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_build_hasher(capacity, RandomState::new())
+    }
 }
 
 #[cfg(feature = "std")]
@@ -88,15 +109,34 @@ where
     fn free_func(ptr: *const ()) {
         if mptr::is_entry(ptr as *const u8) {
             let ptr = mptr::to_actual_ptr(ptr as *const u8) as *mut Entry<K, V>;
-            let _ = unsafe { Box::from_raw(ptr) };
+            unsafe { crate::poison::drop_box(ptr) };
         } else {
-            // println!("Free Level");
+            // A Sub-Level that was collapsed, after all of its Entries were
+            // removed
+            let ptr = mptr::to_actual_ptr(ptr as *const u8) as *mut HashLevel<K, V, 4>;
+            unsafe { crate::poison::drop_box(ptr) };
         }
     }
 
     /// TODO
     pub fn with_build_hasher(build_hasher: H) -> Self {
-        let start_level = HashLevel::new(core::ptr::null(), 0);
+        Self::with_events(build_hasher, Events::none())
+    }
+
+    /// Creates a new HashTrieMap, that reports Contention and Restructuring
+    /// in the Map to the given [`MapEvents`](events::MapEvents), see the
+    /// [`events`] Module for more Details
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    pub fn with_build_hasher_and_events(
+        build_hasher: H,
+        events: alloc::sync::Arc<dyn events::MapEvents>,
+    ) -> Self {
+        Self::with_events(build_hasher, Events::new(events))
+    }
+
+    fn with_events(build_hasher: H, events: Events) -> Self {
+        let start_level = HashLevel::with_events(core::ptr::null(), 0, events);
 
         Self {
             initial_level: start_level,
@@ -105,46 +145,310 @@ where
             _marker: PhantomData,
         }
     }
+
+    /// Creates a new HashTrieMap, that is already sized to hold roughly
+    /// `capacity` Entries.
+    ///
+    /// Normally a new Sub-Level is only created once a Chain in a Bucket
+    /// grows too long, which requires all the Entries in the Chain to be
+    /// moved over to the new Sub-Level. Creating all the Sub-Levels upfront
+    /// avoids most of these Migrations, which is useful when a large Number
+    /// of Entries is inserted right after creating the Map
+    pub fn with_capacity_and_build_hasher(capacity: usize, build_hasher: H) -> Self {
+        let map = Self::with_build_hasher(build_hasher);
+        map.reserve_levels(capacity);
+        map
+    }
+
+    /// Creates enough Sub-Levels, so that there is roughly one Bucket for
+    /// every one of the `count` Entries
+    fn reserve_levels(&self, count: usize) {
+        let buckets_per_level = 1usize << 4;
+
+        let mut depth = 0;
+        let mut buckets = buckets_per_level;
+        while buckets < count {
+            buckets = buckets.saturating_mul(buckets_per_level);
+            depth += 1;
+        }
+
+        self.initial_level.reserve_levels(depth);
+    }
 }
 
 impl<K, V, H> HashTrieMap<K, V, H>
 where
-    K: Hash + Eq + Debug,
+    K: Hash + Eq,
     H: BuildHasher,
-    V: Clone + Debug,
 {
-    /// Inserts the given Key and Value into the Map
-    pub fn insert(&self, key: K, value: V) {
-        let mut hasher = self.build_hasher.build_hasher();
-        key.hash(&mut hasher);
-        let hash = hasher.finish();
+    fn hash_key(&self, key: &K) -> u64 {
+        self.build_hasher.hash_one(key)
+    }
+
+    /// Creates a [`PinnedMap`] View of the Map, which performs all of its
+    /// Operations using a single Reservation, see [`PinnedMap`] for more
+    /// Details.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::hash_trie::HashTrieMap;
+    /// # use std::collections::hash_map::RandomState;
+    /// let map = HashTrieMap::<u64, u64, RandomState>::new();
+    ///
+    /// let pinned = map.pin();
+    /// pinned.insert(1, 10);
+    /// let value = pinned.get(&1).unwrap();
+    /// pinned.remove(&1);
+    ///
+    /// // The Value is still protected, as long as the PinnedMap exists
+    /// assert_eq!(10, *value);
+    /// assert_eq!(None, pinned.get(&1));
+    /// ```
+    pub fn pin(&self) -> PinnedMap<'_, K, V, H> {
+        PinnedMap::new(self)
+    }
+
+    /// Inserts the given Key and Value into the Map.
+    ///
+    /// If the Map already contained a Value for the Key, it is replaced and
+    /// a Reference to the previous Value is returned. The Replacement happens
+    /// atomically, so other Threads will always find either the previous or
+    /// the new Value for the Key, but never no Value at all.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::hash_trie::HashTrieMap;
+    /// # use std::collections::hash_map::RandomState;
+    /// let map = HashTrieMap::<u64, u64, RandomState>::new();
+    ///
+    /// assert!(map.insert(1, 10).is_none());
+    ///
+    /// let previous = map.insert(1, 20).unwrap();
+    /// assert_eq!(10, *previous);
+    /// assert_eq!(20, *map.get(&1).unwrap());
+    /// ```
+    pub fn insert(&self, key: K, value: V) -> Option<RefValue<'_, K, V>> {
+        let hash = self.hash_key(&key);
 
         let mut handle = self.instance.enter();
-        self.initial_level.insert(hash, key, value, &mut handle);
+        let previous = self.initial_level.insert(hash, key, value, &mut handle)?;
+
+        Some(RefValue {
+            entry_ptr: previous,
+            _handle: handle,
+        })
     }
 
-    /// Clones out a value from the Hash-Trie-Map
+    /// Returns a Reference to the Value stored for the given Key
     pub fn get(&self, key: &K) -> Option<RefValue<'_, K, V>> {
-        let mut hasher = self.build_hasher.build_hasher();
-        key.hash(&mut hasher);
-        let hash = hasher.finish();
+        let hash = self.hash_key(key);
 
         self.initial_level.get(hash, key, self.instance.enter())
     }
 
     /// TODO
     pub fn remove(&self, key: &K) {
-        let mut hasher = self.build_hasher.build_hasher();
-        key.hash(&mut hasher);
-        let hash = hasher.finish();
+        let hash = self.hash_key(key);
 
         let mut handle = self.instance.enter();
         self.initial_level.remove_entry(hash, key, &mut handle);
     }
+
+    /// Inserts all the Key-Value Pairs of the given Iterator into the Map,
+    /// using multiple Threads.
+    ///
+    /// The Map is first sized according to the Number of Entries, similar to
+    /// [`with_capacity_and_build_hasher`](Self::with_capacity_and_build_hasher),
+    /// and the Entries are then split up between as many Threads as there
+    /// are available CPUs. Small Iterators are simply inserted on the
+    /// current Thread.
+    ///
+    /// # Note
+    /// If the Iterator contains the same Key multiple Times, it is not
+    /// specified which of the Values will be stored in the Map afterwards
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::hash_trie::HashTrieMap;
+    /// # use std::collections::hash_map::RandomState;
+    /// let map = HashTrieMap::<u64, u64, RandomState>::new();
+    /// map.bulk_insert((0..10_000).map(|i| (i, i * 2)));
+    ///
+    /// assert_eq!(map.get(&1234).unwrap(), 2468);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn bulk_insert<I>(&self, iter: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Send + Sync,
+        V: Send + Sync,
+        H: Send + Sync,
+    {
+        /// The minimum Number of Entries that a single Thread should insert
+        const MIN_CHUNK_SIZE: usize = 4096;
+
+        let mut entries: alloc::vec::Vec<_> = iter.into_iter().collect();
+        self.reserve_levels(entries.len());
+
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        let chunk_size = (entries.len() / threads).max(MIN_CHUNK_SIZE);
+
+        std::thread::scope(|scope| {
+            while entries.len() > chunk_size {
+                let chunk = entries.split_off(entries.len() - chunk_size);
+                scope.spawn(move || {
+                    for (key, value) in chunk {
+                        self.insert(key, value);
+                    }
+                });
+            }
+
+            for (key, value) in entries {
+                self.insert(key, value);
+            }
+        });
+    }
+}
+
+impl<K, V, H> core::iter::FromIterator<(K, V)> for HashTrieMap<K, V, H>
+where
+    K: Hash + Eq,
+    H: BuildHasher + Default,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let iter = iter.into_iter();
+
+        let map = Self::with_capacity_and_build_hasher(iter.size_hint().0, H::default());
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<K, V, H> HashTrieMap<K, V, H> {
+    /// Calls the given Function for every Key-Value Pair stored in the Map.
+    ///
+    /// # Note
+    /// This is only weakly consistent, Entries that are inserted or removed
+    /// concurrently may or may not be visited
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::hash_trie::HashTrieMap;
+    /// # use std::collections::hash_map::RandomState;
+    /// let map = HashTrieMap::<u64, u64, RandomState>::new();
+    /// map.insert(1, 10);
+    /// map.insert(2, 20);
+    ///
+    /// let mut sum = 0;
+    /// map.for_each(|_, value| sum += value);
+    /// assert_eq!(30, sum);
+    /// ```
+    pub fn for_each<F>(&self, mut func: F)
+    where
+        F: FnMut(&K, &V),
+    {
+        let _handle = self.instance.enter();
+        self.initial_level
+            .for_each_entry(&mut |entry| func(&entry.key, &entry.value));
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V, H> serde::Serialize for HashTrieMap<K, V, H>
+where
+    K: serde::Serialize,
+    V: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        // The Entries are first collected, while holding the Handle, so that
+        // the exact Number of Entries is known upfront
+        let _handle = self.instance.enter();
+        let mut entries = alloc::vec::Vec::new();
+        self.initial_level.for_each_entry(&mut |entry| {
+            entries.push((&entry.key as *const K, &entry.value as *const V))
+        });
+
+        let mut map = serializer.serialize_map(Some(entries.len()))?;
+        for (key, value) in entries {
+            // Safety:
+            // The Entries can not be freed while we still hold the Handle
+            map.serialize_entry(unsafe { &*key }, unsafe { &*value })?;
+        }
+        map.end()
+    }
 }
 
-unsafe impl<K, V, H> Sync for HashTrieMap<K, V, H> {}
-unsafe impl<K, V, H> Send for HashTrieMap<K, V, H> {}
+#[cfg(feature = "serde")]
+impl<'de, K, V, H> serde::Deserialize<'de> for HashTrieMap<K, V, H>
+where
+    K: serde::Deserialize<'de> + Hash + Eq,
+    V: serde::Deserialize<'de>,
+    H: BuildHasher + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct MapVisitor<K, V, H>(PhantomData<(K, V, H)>);
+
+        impl<'de, K, V, H> serde::de::Visitor<'de> for MapVisitor<K, V, H>
+        where
+            K: serde::Deserialize<'de> + Hash + Eq,
+            V: serde::Deserialize<'de>,
+            H: BuildHasher + Default,
+        {
+            type Value = HashTrieMap<K, V, H>;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "a map")
+            }
+
+            fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let map = HashTrieMap::with_build_hasher(H::default());
+                while let Some((key, value)) = access.next_entry()? {
+                    map.insert(key, value);
+                }
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+// Safety:
+// Entries can be inserted on one Thread and accessed or dropped on another
+// one, while References to the Keys and Values can be obtained by multiple
+// Threads at the same Time, so they need to be both Send and Sync
+unsafe impl<K, V, H> Sync for HashTrieMap<K, V, H>
+where
+    K: Send + Sync,
+    V: Send + Sync,
+    H: Send + Sync,
+{
+}
+unsafe impl<K, V, H> Send for HashTrieMap<K, V, H>
+where
+    K: Send + Sync,
+    V: Send + Sync,
+    H: Send,
+{
+}
 
 impl<K, V, H> Drop for HashTrieMap<K, V, H> {
     fn drop(&mut self) {
@@ -194,6 +498,68 @@ mod tests {
         assert_eq!(first_value, 123);
     }
 
+    #[test]
+    fn insert_returns_previous() {
+        let map: HashTrieMap<String, usize, RandomState> = HashTrieMap::new();
+
+        assert!(map.insert("test".to_owned(), 123).is_none());
+
+        let previous = map.insert("test".to_owned(), 234);
+        assert_eq!(Some(123), previous.map(|v| *v));
+        assert_eq!(map.get(&"test".to_owned()).unwrap(), 234);
+
+        let previous = map.insert("test".to_owned(), 345);
+        assert_eq!(Some(234), previous.map(|v| *v));
+        assert_eq!(map.get(&"test".to_owned()).unwrap(), 345);
+    }
+
+    #[test]
+    fn insert_replace_in_chain() {
+        let map: HashTrieMap<u64, u64, RandomState> = HashTrieMap::new();
+
+        for i in 0..256 {
+            assert!(map.insert(i, i).is_none());
+        }
+        for i in 0..256 {
+            assert_eq!(Some(i), map.insert(i, i * 2).map(|v| *v));
+        }
+        for i in 0..256 {
+            assert_eq!(map.get(&i).unwrap(), i * 2);
+        }
+
+        let mut count = 0;
+        map.for_each(|_, _| count += 1);
+        assert_eq!(256, count);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn insert_replace_never_missing() {
+        let map: HashTrieMap<u64, u64, RandomState> = HashTrieMap::new();
+        for i in 0..64 {
+            map.insert(i, 0);
+        }
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for round in 1..100 {
+                    for i in 0..64 {
+                        let previous = map.insert(i, round).unwrap();
+                        assert_eq!(round - 1, *previous);
+                    }
+                }
+            });
+
+            scope.spawn(|| {
+                for _ in 0..100 {
+                    for i in 0..64 {
+                        assert!(map.get(&i).is_some());
+                    }
+                }
+            });
+        });
+    }
+
     #[test]
     fn insert_remove() {
         let map: HashTrieMap<String, usize, RandomState> = HashTrieMap::new();
@@ -211,6 +577,106 @@ mod tests {
         assert_eq!(first_value, 123);
     }
 
+    #[test]
+    fn with_capacity_insert_get() {
+        let map: HashTrieMap<usize, usize, RandomState> = HashTrieMap::with_capacity(1000);
+
+        for i in 0..1000 {
+            map.insert(i, i * 2);
+        }
+        for i in 0..1000 {
+            assert_eq!(map.get(&i).unwrap(), i * 2);
+        }
+    }
+
+    #[test]
+    fn from_iter() {
+        let map: HashTrieMap<usize, usize, RandomState> = (0..500).map(|i| (i, i + 1)).collect();
+
+        for i in 0..500 {
+            assert_eq!(map.get(&i).unwrap(), i + 1);
+        }
+        assert_eq!(None, map.get(&500));
+    }
+
+    #[test]
+    fn bulk_insert() {
+        let map: HashTrieMap<usize, usize, RandomState> = HashTrieMap::new();
+        map.insert(0, 13);
+
+        map.bulk_insert((1..20_000).map(|i| (i, i * 3)));
+
+        assert_eq!(map.get(&0).unwrap(), 13);
+        for i in 1..20_000 {
+            assert_eq!(map.get(&i).unwrap(), i * 3);
+        }
+    }
+
+    #[test]
+    fn for_each_visits_all() {
+        let map: HashTrieMap<usize, usize, RandomState> = HashTrieMap::new();
+
+        for i in 0..200 {
+            map.insert(i, i * 2);
+        }
+        map.insert(13, 0);
+        map.remove(&14);
+
+        let mut entries = alloc::vec::Vec::new();
+        map.for_each(|key, value| entries.push((*key, *value)));
+        entries.sort_unstable();
+
+        let expected: alloc::vec::Vec<_> = (0..200)
+            .filter(|i| *i != 14)
+            .map(|i| (i, if i == 13 { 0 } else { i * 2 }))
+            .collect();
+        assert_eq!(expected, entries);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        let map: HashTrieMap<String, usize, RandomState> = HashTrieMap::new();
+        map.insert("first".to_owned(), 1);
+        map.insert("second".to_owned(), 2);
+
+        let serialized = serde_json::to_string(&map).unwrap();
+        let deserialized: HashTrieMap<String, usize, RandomState> =
+            serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized.get(&"first".to_owned()).unwrap(), 1);
+        assert_eq!(deserialized.get(&"second".to_owned()).unwrap(), 2);
+        assert_eq!(None, deserialized.get(&"third".to_owned()));
+    }
+
+    #[test]
+    fn mapped_value_after_remove() {
+        let map: HashTrieMap<String, (usize, String), RandomState> = HashTrieMap::new();
+
+        map.insert("test".to_owned(), (123, "value".to_owned()));
+        let mapped = map.get(&"test".to_owned()).unwrap().map(|v| &v.1);
+
+        map.remove(&"test".to_owned());
+
+        assert_eq!(mapped, "value".to_owned());
+        assert_eq!("value".to_owned(), mapped.into_owned());
+    }
+
+    #[test]
+    fn no_debug_or_clone() {
+        #[derive(PartialEq, Eq, Hash)]
+        struct Key(usize);
+        struct Value(usize);
+
+        let map: HashTrieMap<Key, Value, RandomState> = HashTrieMap::new();
+
+        map.insert(Key(1), Value(13));
+        assert_eq!(13, map.get(&Key(1)).unwrap().0);
+
+        map.remove(&Key(1));
+        assert!(map.get(&Key(1)).is_none());
+    }
+
     #[test]
     fn remove_nonexisting() {
         let map: HashTrieMap<String, usize, RandomState> = HashTrieMap::new();
@@ -261,4 +727,87 @@ mod loom_tests {
             });
         });
     }
+
+    /// A Reader that observes a concurrently inserted Entry also needs to
+    /// observe its fully initialized Key and Value
+    #[test]
+    fn insert_get_concurrent() {
+        loom::model(|| {
+            let map: Arc<HashTrieMap<u64, u64>> = Arc::new(HashTrieMap::new());
+
+            let w_map = map.clone();
+            let writer = thread::spawn(move || {
+                w_map.insert(13, 123);
+            });
+
+            if let Some(value) = map.get(&13) {
+                assert_eq!(123, *value.value());
+            }
+
+            writer.join().unwrap();
+            assert_eq!(123, *map.get(&13).unwrap().value());
+        });
+    }
+
+    /// A removed Entry must not be freed while a concurrent Reader can still
+    /// access it, which relies on the Fences in the Hyaline Instance
+    #[test]
+    fn remove_get_concurrent() {
+        loom::model(|| {
+            let map: Arc<HashTrieMap<u64, u64>> = Arc::new(HashTrieMap::new());
+            map.insert(13, 123);
+
+            let r_map = map.clone();
+            let reader = thread::spawn(move || {
+                if let Some(value) = r_map.get(&13) {
+                    assert_eq!(123, *value.value());
+                }
+            });
+
+            map.remove(&13);
+
+            reader.join().unwrap();
+            assert!(map.get(&13).is_none());
+        });
+    }
+
+    /// A Reader needs to find either the previous or the new Value while the
+    /// Key is being overwritten concurrently
+    #[test]
+    fn overwrite_get_concurrent() {
+        loom::model(|| {
+            let map: Arc<HashTrieMap<u64, u64>> = Arc::new(HashTrieMap::new());
+            map.insert(13, 123);
+
+            let w_map = map.clone();
+            let writer = thread::spawn(move || {
+                assert_eq!(123, *w_map.insert(13, 234).unwrap().value());
+            });
+
+            let value = *map.get(&13).unwrap().value();
+            assert!(value == 123 || value == 234);
+
+            writer.join().unwrap();
+            assert_eq!(234, *map.get(&13).unwrap().value());
+        });
+    }
+
+    /// Two concurrent Inserts into the same Bucket both need to end up in
+    /// the Chain
+    #[test]
+    fn concurrent_insert_same_bucket() {
+        loom::model(|| {
+            let map: Arc<HashTrieMap<u64, u64>> = Arc::new(HashTrieMap::new());
+
+            let w_map = map.clone();
+            let writer = thread::spawn(move || {
+                w_map.insert(1, 10);
+            });
+            map.insert(2, 20);
+
+            writer.join().unwrap();
+            assert_eq!(10, *map.get(&1).unwrap().value());
+            assert_eq!(20, *map.get(&2).unwrap().value());
+        });
+    }
 }
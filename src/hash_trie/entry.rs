@@ -8,8 +8,8 @@ use crate::{
 };
 
 use super::{
+    instrument::CasSite,
     mptr::{self, boxed_hashlevel, LoadResult, PtrType},
-    RefValue,
 };
 
 /// This contains all the extra needed "Metadata" for a single Entry in the
@@ -49,6 +49,22 @@ impl<K, V> Entry<K, V> {
         self.description.valid.store(false, order);
     }
 
+    pub fn is_valid(&self, order: atomic::Ordering) -> bool {
+        self.description.valid.load(order)
+    }
+
+    /// Marks the Entry as valid again, after a failed Attempt to replace it
+    pub fn restore(&self, order: atomic::Ordering) {
+        self.description.valid.store(true, order);
+    }
+
+    /// Marks the Entry as invalid and returns whether or not it was still
+    /// valid before, which means that the current Thread is now responsible
+    /// for unlinking and retiring it
+    pub fn claim(&self, order: atomic::Ordering) -> bool {
+        self.description.valid.swap(false, order)
+    }
+
     pub fn clean_up<const B: u8>(
         ptr: *mut Self,
         current_level: *mut (),
@@ -56,7 +72,8 @@ impl<K, V> Entry<K, V> {
     ) {
         let current = unsafe { &*ptr };
 
-        match current.other.load_ptr(atomic::Ordering::SeqCst) {
+        // Cleaning up requires exclusive Access to the Trie, so no other Thread can modify it
+        match current.other.load_ptr(atomic::Ordering::Relaxed) {
             PtrType::Entry(next_entry_ptr) => {
                 Self::clean_up::<B>(next_entry_ptr as *mut Self, current_level, handle);
             }
@@ -79,25 +96,50 @@ impl<K, V> Entry<K, V>
 where
     K: Eq,
 {
-    /// Appends the `new_entry` onto the current Chain of Entrys
+    /// Appends the `new_entry` onto the current Chain of Entrys, where
+    /// `previous` is the Ptr through which the current Entry was reached.
+    ///
+    /// If the Chain already contains an Entry for the same Key, that Entry
+    /// is replaced by the `new_entry` and returned. The returned Entry has
+    /// already been retired using the given Handle, so it stays valid for as
+    /// long as the Handle exists
     pub fn insert_key_on_chain<const B: u8>(
         &self,
         k: u64,
         h: &HashLevel<K, V, B>,
+        previous: &mptr::TargetPtr<K, V>,
         mut new_entry: ManuallyDrop<Box<Self>>,
         chain_pos: usize,
         handle: &mut hyaline::Handle<'_>,
-    ) {
+    ) -> Option<*const Self> {
         // If the current Node `r` matches given Key, we have found the Target
-        // Node/Place
-        if self.key == new_entry.key {
-            let entry = ManuallyDrop::into_inner(new_entry);
-
-            // First Remove the record
-            h.remove_entry(k, &entry.key, handle);
-            // Second Insert again
-            h.insert_key_on_hash(k, entry.key, entry.value, handle);
-            return;
+        // Node/Place. Entries that are no longer valid are currently being
+        // removed by another Thread and are therefore skipped
+        if self.key == new_entry.key && self.claim(atomic::Ordering::AcqRel) {
+            let new_entry_ptr = Box::into_raw(ManuallyDrop::into_inner(new_entry));
+
+            // Swap the new Entry into the Place of the current one, so that
+            // there is no Point in Time where the Key is missing from the Map
+            if HashLevel::<K, V, B>::replace_entry_chain(previous, self, new_entry_ptr) {
+                let old_ptr: *const Self = self;
+                // # Safety:
+                // The Entry is no longer reachable from the Map and we are
+                // the only Thread that claimed it
+                unsafe {
+                    handle.retire(old_ptr as *const ());
+                }
+                return Some(old_ptr);
+            }
+
+            // The Chain was modified concurrently, so the current Entry could
+            // not be replaced and we start over with the same Key and Value
+            self.restore(atomic::Ordering::Release);
+            h.events.cas_failure(CasSite::ChainAppend);
+
+            // # Safety:
+            // The new Entry was never published, so we still own it
+            let entry = unsafe { Box::from_raw(new_entry_ptr) };
+            return h.insert_key_on_hash(k, entry.key, entry.value, handle);
         }
 
         match self.other.load() {
@@ -118,10 +160,11 @@ where
                     match self.other.cas_hashlevel::<B>(
                         expected_ptr,
                         new_hash_ptr as *mut (),
-                        atomic::Ordering::SeqCst,
-                        atomic::Ordering::SeqCst,
+                        atomic::Ordering::AcqRel,
+                        atomic::Ordering::Acquire,
                     ) {
                         Ok(_) => {
+                            h.events.level_created();
                             let bucket = h.get_bucket(k).expect(
                                 "The Bucket should exist, as it there are always enough buckets",
                             );
@@ -132,45 +175,50 @@ where
                                     ..
                                 } => {
                                     let new_hash = boxed_hashlevel(new_hash_ptr);
-                                    new_hash.adjust_chain_nodes(bucket_entry);
+                                    new_hash.adjust_chain_nodes(bucket_entry, handle);
                                 }
                                 _ => {
                                     panic!("Expected Bucket to point to an Entry");
                                 }
                             };
 
-                            bucket
-                                .store_hashlevel(new_hash_ptr as *mut (), atomic::Ordering::SeqCst);
+                            bucket.store_hashlevel(
+                                new_hash_ptr as *mut (),
+                                atomic::Ordering::Release,
+                            );
 
                             let new_hash = boxed_hashlevel(new_hash_ptr);
 
                             let new_entry = ManuallyDrop::into_inner(new_entry);
-                            new_hash.insert_key_on_hash(
+                            return new_hash.insert_key_on_hash(
                                 new_entry.hash,
                                 new_entry.key,
                                 new_entry.value,
                                 handle,
                             );
-                            return;
                         }
                         Err(_) => {
-                            // println!("HashLevel CAS failed");
+                            h.events.cas_failure(CasSite::LevelExpansion);
+
+                            // # Safety:
+                            // The CAS failed, so no other Thread has ever
+                            // seen the new HashLevel and it does not contain
+                            // any Entries yet
+                            let _ = unsafe { Box::from_raw(new_hash_ptr) };
                         }
                     }
-
-                    return;
                 } else {
                     let new_entry_ptr = Box::into_raw(ManuallyDrop::into_inner(new_entry));
                     match self.other.cas_entry::<B>(
                         expected_ptr,
                         new_entry_ptr as *mut (),
-                        atomic::Ordering::SeqCst,
-                        atomic::Ordering::SeqCst,
+                        atomic::Ordering::AcqRel,
+                        atomic::Ordering::Acquire,
                     ) {
-                        Ok(_) => return,
+                        Ok(_) => return None,
                         Err(_) => {
                             new_entry = boxed_entry(new_entry_ptr);
-                            // println!("Didnt work");
+                            h.events.cas_failure(CasSite::ChainAppend);
                         }
                     };
                 }
@@ -185,7 +233,7 @@ where
             LoadResult::Entry {
                 entry: other_entry, ..
             } => {
-                other_entry.insert_key_on_chain(k, h, new_entry, chain_pos + 1, handle);
+                other_entry.insert_key_on_chain(k, h, &self.other, new_entry, chain_pos + 1, handle)
             }
             // If the Next-Element is a second HashLevel, try and insert
             // the New Node on the Second-Level HashLevel
@@ -197,42 +245,37 @@ where
                 }
 
                 let inner_entry = ManuallyDrop::into_inner(new_entry);
-                n_h.insert_key_on_hash(k, inner_entry.key, inner_entry.value, handle);
+                n_h.insert_key_on_hash(k, inner_entry.key, inner_entry.value, handle)
             }
-        };
+        }
     }
 
-    pub fn get_chain<'a, const B: u8>(
+    /// Searches the Chain, starting at the current Entry, for the Entry with
+    /// the given Key
+    pub fn get_chain<const B: u8>(
         &self,
         hash: u64,
         current_hash: &HashLevel<K, V, B>,
         key: &K,
         chain_pos: usize,
-        handle: hyaline::Handle<'a>,
-    ) -> Result<RefValue<'a, K, V>, bool> {
+    ) -> Option<*const Self> {
         if &self.key == key {
-            return Ok(RefValue {
-                entry_ptr: self,
-                _handle: handle,
-            });
+            return Some(self);
         }
 
         match self.other.load() {
             LoadResult::HashLevel { ptr: next_ptr, .. } => {
                 if next_ptr == current_hash.own as *mut HashLevel<K, V, B> {
-                    return Err(false);
+                    return None;
                 }
 
-                // TODO
-                // println!("Is new List");
-                Err(false)
+                // The Chain is currently being moved into a new HashLevel
+                current_hash.events.stale_lookup();
+                None
             }
             LoadResult::Entry {
                 entry: other_entry, ..
-            } => match other_entry.get_chain(hash, &current_hash, key, chain_pos + 1, handle) {
-                Ok(v) => Ok(v),
-                _ => Err(false),
-            },
+            } => other_entry.get_chain(hash, &current_hash, key, chain_pos + 1),
         }
     }
 }
@@ -0,0 +1,148 @@
+//! Optional Event-Hooks for the [`HashTrieMap`](super::HashTrieMap)
+//!
+//! The Map can be created with a [`MapEvents`] instance, using
+//! [`with_build_hasher_and_events`](super::HashTrieMap::with_build_hasher_and_events),
+//! which then gets notified about Contention and Restructuring inside of the
+//! Map, like failed CAS-Operations or new Sub-Levels being created. This
+//! allows observing how contended a Map is, without having to modify it.
+//!
+//! When the `metrics` Feature is disabled, all the Hooks are compiled out
+//! completely and therefore have no Impact on the Performance of the Map.
+//!
+//! # Example
+//! ```
+//! # use nolock::hash_trie::{events::Counters, HashTrieMap};
+//! # use std::{collections::hash_map::RandomState, sync::Arc};
+//! let counters = Arc::new(Counters::new());
+//! let map = HashTrieMap::<u64, u64, RandomState>::with_build_hasher_and_events(
+//!     RandomState::new(),
+//!     counters.clone(),
+//! );
+//!
+//! for i in 0..1000 {
+//!     map.insert(i, i);
+//! }
+//!
+//! // Inserting that many Entries needs additional Sub-Levels
+//! assert!(counters.levels_created() > 0);
+//! ```
+
+use core::fmt::Debug;
+
+use crate::sync::atomic;
+
+/// The Place in the Map, where a CAS-Operation failed because of a
+/// concurrent Modification, after which the Operation is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CasFailure {
+    /// Appending an Entry to the End of a Chain
+    ChainAppend,
+    /// Replacing the End of a full Chain with a new Sub-Level
+    LevelExpansion,
+    /// Inserting an Entry into an empty Bucket
+    BucketInsert,
+}
+
+/// The Hooks that get called by a [`HashTrieMap`](super::HashTrieMap).
+///
+/// All the Hooks are called in the hot Path of the Map Operations, so they
+/// should be as cheap as possible, like simply incrementing a Counter.
+///
+/// Every Hook has an empty default Implementation, so you only need to
+/// implement the ones you are actually interested in.
+pub trait MapEvents: Send + Sync {
+    /// Called when a CAS-Operation failed, because another Thread modified
+    /// the same Part of the Map concurrently
+    fn on_cas_failure(&self, kind: CasFailure) {
+        let _ = kind;
+    }
+
+    /// Called when a Chain reached its maximum Length and was successfully
+    /// replaced with a new Sub-Level
+    fn on_level_created(&self) {}
+
+    /// Called when a Sub-Level became empty and was removed from the Map
+    /// again
+    fn on_level_collapsed(&self) {}
+
+    /// Called when a Lookup ran into a Chain, that is currently being moved
+    /// into a new Sub-Level, and therefore could not find the Entry
+    fn on_stale_lookup(&self) {}
+}
+
+/// A simple [`MapEvents`] implementation, that simply counts the Number of
+/// times each Hook was called
+pub struct Counters {
+    cas_failures: atomic::AtomicU64,
+    levels_created: atomic::AtomicU64,
+    levels_collapsed: atomic::AtomicU64,
+    stale_lookups: atomic::AtomicU64,
+}
+
+impl Counters {
+    /// Creates a new Set of Counters, all starting at 0
+    pub fn new() -> Self {
+        Self {
+            cas_failures: atomic::AtomicU64::new(0),
+            levels_created: atomic::AtomicU64::new(0),
+            levels_collapsed: atomic::AtomicU64::new(0),
+            stale_lookups: atomic::AtomicU64::new(0),
+        }
+    }
+
+    /// The Number of failed CAS-Operations
+    pub fn cas_failures(&self) -> u64 {
+        self.cas_failures.load(atomic::Ordering::Relaxed)
+    }
+
+    /// The Number of Sub-Levels that were created for full Chains
+    pub fn levels_created(&self) -> u64 {
+        self.levels_created.load(atomic::Ordering::Relaxed)
+    }
+
+    /// The Number of empty Sub-Levels that were removed again
+    pub fn levels_collapsed(&self) -> u64 {
+        self.levels_collapsed.load(atomic::Ordering::Relaxed)
+    }
+
+    /// The Number of Lookups that ran into a Chain that was being moved
+    pub fn stale_lookups(&self) -> u64 {
+        self.stale_lookups.load(atomic::Ordering::Relaxed)
+    }
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Counters {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Counters")
+            .field("cas_failures", &self.cas_failures())
+            .field("levels_created", &self.levels_created())
+            .field("levels_collapsed", &self.levels_collapsed())
+            .field("stale_lookups", &self.stale_lookups())
+            .finish()
+    }
+}
+
+impl MapEvents for Counters {
+    fn on_cas_failure(&self, _kind: CasFailure) {
+        self.cas_failures.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+
+    fn on_level_created(&self) {
+        self.levels_created.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+
+    fn on_level_collapsed(&self) {
+        self.levels_collapsed
+            .fetch_add(1, atomic::Ordering::Relaxed);
+    }
+
+    fn on_stale_lookup(&self) {
+        self.stale_lookups.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+}
@@ -7,11 +7,38 @@ use core::{
 
 use super::{
     entry::Entry,
+    instrument::{CasSite, Events},
     mptr::{self, boxed_entry, boxed_hashlevel, LoadResult},
     RefValue,
 };
 use crate::{hash_trie::mptr::PtrType, hyaline, sync::atomic};
 
+/// Set in the State of a HashLevel, while a Thread is checking if the
+/// HashLevel can be collapsed
+const CLOSING: usize = 0b01;
+/// Set in the State of a HashLevel, once it was empty and will be removed
+/// from its Parent. This is never cleared again
+const COLLAPSED: usize = 0b10;
+/// A single Thread that is currently modifying the Buckets of a HashLevel,
+/// every Bit below this is used for the Flags and the Generation
+const USER: usize = 1 << 16;
+/// The Generation of the State, which is incremented every time a Thread
+/// starts to check if the HashLevel can be collapsed, so that a Thread can
+/// not finish a Check that was started earlier
+const GENERATION: usize = (USER - 1) & !(CLOSING | COLLAPSED);
+
+/// Marks a Thread as currently modifying the Buckets of a HashLevel, which
+/// prevents the HashLevel from being collapsed, see [`HashLevel::enter`]
+struct LevelGuard<'l> {
+    state: &'l atomic::AtomicUsize,
+}
+
+impl<'l> Drop for LevelGuard<'l> {
+    fn drop(&mut self) {
+        self.state.fetch_sub(USER, atomic::Ordering::AcqRel);
+    }
+}
+
 pub(crate) struct HashLevel<K, V, const B: u8> {
     /// The Level of the HashLevel, this is used to determine which bits should
     /// be used to lookup the Key/Hash
@@ -24,13 +51,37 @@ pub(crate) struct HashLevel<K, V, const B: u8> {
     pub previous: *const HashLevel<K, V, B>,
     /// All the buckets for the current one
     buckets: Vec<mptr::TargetPtr<K, V>>,
+    /// The Event-Hooks of the Map, shared by all the HashLevels
+    pub events: Events,
+    /// The Number of Threads modifying the Buckets, as well as the Flags
+    /// used to collapse the HashLevel once it is empty
+    state: atomic::AtomicUsize,
     _pin_marker: PhantomPinned,
     _marker: PhantomData<(K, V)>,
 }
 
 impl<K, V, const B: u8> HashLevel<K, V, B> {
-    /// Creates a new HashLevel
+    /// Creates a new HashLevel, which uses the same Event-Hooks as the
+    /// `previous` HashLevel, if there is one
     pub fn new(previous: *const HashLevel<K, V, B>, level: usize) -> Box<Self> {
+        let events = if previous.is_null() {
+            Events::none()
+        } else {
+            // # Safety:
+            // A non-null previous Ptr always points to a HashLevel higher up
+            // in the Trie, which outlives all of its Sub-Levels
+            unsafe { &*previous }.events.clone()
+        };
+
+        Self::with_events(previous, level, events)
+    }
+
+    /// Creates a new HashLevel, that reports to the given Event-Hooks
+    pub fn with_events(
+        previous: *const HashLevel<K, V, B>,
+        level: usize,
+        events: Events,
+    ) -> Box<Self> {
         let bucket_count = 2usize.pow(B as u32);
         let buckets = Vec::with_capacity(bucket_count);
 
@@ -40,6 +91,8 @@ impl<K, V, const B: u8> HashLevel<K, V, B> {
             max_chain: 3,
             own: core::ptr::null(),
             buckets,
+            events,
+            state: atomic::AtomicUsize::new(0),
             _pin_marker: PhantomPinned,
             _marker: PhantomData,
         });
@@ -77,11 +130,165 @@ impl<K, V, const B: u8> HashLevel<K, V, B> {
         self.buckets.get(index)
     }
 
+    /// Marks the current Thread as modifying the Buckets of this HashLevel,
+    /// until the returned Guard is dropped.
+    ///
+    /// Returns `None` if the HashLevel has been collapsed, in which case it
+    /// must not be modified anymore and the Caller should continue on the
+    /// Previous HashLevel instead
+    fn enter(&self) -> Option<LevelGuard<'_>> {
+        loop {
+            let state = self.state.fetch_add(USER, atomic::Ordering::AcqRel);
+            if state & (CLOSING | COLLAPSED) == 0 {
+                return Some(LevelGuard { state: &self.state });
+            }
+            self.state.fetch_sub(USER, atomic::Ordering::AcqRel);
+
+            if state & COLLAPSED != 0 || self.resolve_closing(state) {
+                return None;
+            }
+        }
+    }
+
+    /// Whether all the Buckets of this HashLevel are empty
+    fn is_empty(&self) -> bool {
+        let empty = mptr::mark_as_previous(self.own as *const u8) as *mut ();
+        self.buckets
+            .iter()
+            .all(|b| b.raw_load(atomic::Ordering::Acquire) == empty)
+    }
+
+    /// Finishes the Check started by the Thread that set the `CLOSING` Flag,
+    /// which was observed in the given State. The HashLevel is collapsed if
+    /// it is still empty, otherwise it is opened up again.
+    ///
+    /// Returns whether the HashLevel has been collapsed
+    fn resolve_closing(&self, observed: usize) -> bool {
+        // While the Flag is set, no Thread can modify the Buckets, so every
+        // Thread finishing the Check comes to the same Result
+        let empty = self.is_empty();
+
+        let mut current = self.state.load(atomic::Ordering::Acquire);
+        loop {
+            if current & COLLAPSED != 0 {
+                return true;
+            }
+            if current & CLOSING == 0 || current & GENERATION != observed & GENERATION {
+                return false;
+            }
+
+            let new = if empty {
+                (current & !CLOSING) | COLLAPSED
+            } else {
+                current & !CLOSING
+            };
+            match self.state.compare_exchange(
+                current,
+                new,
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+            ) {
+                Ok(_) => return empty,
+                Err(updated) => {
+                    current = updated;
+                }
+            };
+        }
+    }
+
+    /// Attempts to collapse this HashLevel, if it is empty and no other
+    /// Thread is modifying it. A collapsed HashLevel is removed from the
+    /// Bucket of its Previous HashLevel, that the given Hash belongs to, and
+    /// retired using the Handle.
+    ///
+    /// The initial HashLevel is never collapsed
+    fn try_collapse(&self, hash: u64, handle: &mut hyaline::Handle<'_>) {
+        if self.previous.is_null() {
+            return;
+        }
+
+        // # Safety:
+        // The Previous HashLevel can only be collapsed after this HashLevel
+        // was removed from it and the Caller holds a Handle, so it can not
+        // be freed while we are using it
+        let previous = unsafe { &*self.previous };
+        let own_marked = mptr::mark_as_previous(self.own as *const u8) as *mut ();
+        match previous.get_bucket(hash) {
+            Some(bucket) if bucket.raw_load(atomic::Ordering::Acquire) == own_marked => {}
+            _ => return,
+        };
+
+        if !self.is_empty() {
+            return;
+        }
+
+        let state = self.state.load(atomic::Ordering::Acquire);
+        if state & !GENERATION != 0 {
+            return;
+        }
+
+        let closing = (state.wrapping_add(COLLAPSED << 1) & GENERATION) | CLOSING;
+        if self
+            .state
+            .compare_exchange(
+                state,
+                closing,
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+            )
+            .is_err()
+        {
+            return;
+        }
+
+        if self.resolve_closing(closing) {
+            self.unlink(hash, handle);
+        }
+    }
+
+    /// Removes this collapsed HashLevel from its Previous HashLevel, after
+    /// which the Previous HashLevel might be collapsed as well
+    fn unlink(&self, hash: u64, handle: &mut hyaline::Handle<'_>) {
+        // # Safety:
+        // See `try_collapse`
+        let previous = unsafe { &*self.previous };
+        let bucket = previous
+            .get_bucket(hash)
+            .expect("The Bucket should always exist for a valid Hash");
+
+        let own_marked = mptr::mark_as_previous(self.own as *const u8) as *mut Entry<K, V>;
+        if bucket
+            .cas_hashlevel::<B>(
+                own_marked,
+                previous.own as *mut (),
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+            )
+            .is_err()
+        {
+            // Another Thread already removed the HashLevel
+            return;
+        }
+
+        self.events.level_collapsed();
+
+        // # Safety:
+        // The HashLevel is no longer reachable from the Trie and is empty,
+        // so it only needs to be freed once no Thread accesses it anymore
+        unsafe {
+            handle.retire(own_marked as *const ());
+        }
+
+        previous.try_collapse(hash, handle);
+    }
+
     pub fn cleanup_buckets(&mut self, handle: &mut hyaline::Handle<'_>) {
+        // We have exclusive Access to the Trie, so no other Thread can observe or modify the
+        // Buckets anymore
         for bucket in self.buckets.iter() {
-            match bucket.load_ptr(atomic::Ordering::SeqCst) {
+            match bucket.load_ptr(atomic::Ordering::Relaxed) {
                 PtrType::Entry(ptr) => {
-                    bucket.raw_store(self.own as *mut (), atomic::Ordering::SeqCst);
+                    bucket.raw_store(self.own as *mut (), atomic::Ordering::Relaxed);
 
                     Entry::clean_up::<B>(ptr as *mut Entry<K, V>, self.own as *mut (), handle);
                 }
@@ -99,6 +306,95 @@ impl<K, V, const B: u8> HashLevel<K, V, B> {
         // TODO
         // todo!("Cleanup buckets")
     }
+
+    /// Creates empty Sub-Levels for all the empty Buckets of this HashLevel,
+    /// `depth` Levels deep, so that Chains don't have to be moved into new
+    /// Sub-Levels once they grow too long.
+    ///
+    /// Buckets that already contain Entries are left untouched, but existing
+    /// Sub-Levels are expanded further if needed. This is safe to call while
+    /// other Threads are accessing the HashLevel
+    pub fn reserve_levels(&self, depth: usize) {
+        // There are no more Bits left in the Hash for another Level
+        if depth == 0 || (self.level + 2) * (B as usize) > 64 {
+            return;
+        }
+
+        let _guard = match self.enter() {
+            Some(g) => g,
+            None => return,
+        };
+
+        let empty = mptr::mark_as_previous(self.own as *const u8) as *mut Entry<K, V>;
+        for bucket in self.buckets.iter() {
+            if let PtrType::HashLevel(ptr) = bucket.load_ptr(atomic::Ordering::Acquire) {
+                if ptr == self.own as *mut () {
+                    let new_level = Box::into_raw(HashLevel::new(self.own, self.level + 1));
+                    if bucket
+                        .cas_hashlevel::<B>(
+                            empty,
+                            new_level as *mut (),
+                            atomic::Ordering::AcqRel,
+                            atomic::Ordering::Acquire,
+                        )
+                        .is_err()
+                    {
+                        // Some other Thread already inserted something into
+                        // the Bucket
+                        let _ = unsafe { Box::from_raw(new_level) };
+                    }
+                }
+            }
+
+            if let LoadResult::HashLevel { level, ptr } = bucket.load::<B>() {
+                if ptr != self.own as *mut Self {
+                    level.reserve_levels(depth - 1);
+                }
+            }
+        }
+    }
+
+    /// Calls the given Function for every valid Entry that is reachable from
+    /// this HashLevel, including all of its Sub-Levels.
+    ///
+    /// The Caller needs to hold a Hyaline-Handle for the entire Duration of
+    /// this Call, so that none of the visited Entries are freed concurrently
+    pub fn for_each_entry<F>(&self, func: &mut F)
+    where
+        F: FnMut(&Entry<K, V>),
+    {
+        for bucket in self.buckets.iter() {
+            match bucket.load_ptr(atomic::Ordering::Acquire) {
+                PtrType::Entry(ptr) => {
+                    let mut current = unsafe { &*(ptr as *const Entry<K, V>) };
+                    loop {
+                        if current.is_valid(atomic::Ordering::Acquire) {
+                            func(current);
+                        }
+
+                        // A Chain always ends in a HashLevel, either the
+                        // current one or a new Sub-Level that is currently
+                        // being created, which will be visited through the
+                        // Bucket once the Entries were moved over to it
+                        match current.other.load_ptr(atomic::Ordering::Acquire) {
+                            PtrType::Entry(next_ptr) => {
+                                current = unsafe { &*(next_ptr as *const Entry<K, V>) };
+                            }
+                            PtrType::HashLevel(_) => break,
+                        };
+                    }
+                }
+                PtrType::HashLevel(ptr) => {
+                    if ptr == self.own as *mut () {
+                        continue;
+                    }
+
+                    let level = unsafe { &*(ptr as *const Self) };
+                    level.for_each_entry(func);
+                }
+            };
+        }
+    }
 }
 
 impl<K, V, const B: u8> HashLevel<K, V, B>
@@ -107,7 +403,13 @@ where
 {
     /// Attempts to append the Node `n` to the chain of Node `r`. Additionally
     /// this might cause the allocation of a new HashLevel
-    fn adjust_node_on_chain(&self, n: &Entry<K, V>, r: &Entry<K, V>, chain: usize) {
+    fn adjust_node_on_chain(
+        &self,
+        n: &Entry<K, V>,
+        r: &Entry<K, V>,
+        chain: usize,
+        handle: &mut hyaline::Handle<'_>,
+    ) {
         // Load the Next-Element in the Chain and if it is Hashlevel
         if let LoadResult::HashLevel { ptr: hash_ptr, .. } = r.other.load::<B>() {
             // If the current chain already has the Maximum length, create
@@ -122,10 +424,11 @@ where
                 match r.other.cas_hashlevel::<B>(
                     cas_ptr,
                     new_hash_ptr as *mut (),
-                    atomic::Ordering::SeqCst,
-                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Acquire,
                 ) {
                     Ok(_) => {
+                        self.events.level_created();
                         let new_hash = boxed_hashlevel(new_hash_ptr);
 
                         let bucket_index = self.get_bucket_index(n.hash);
@@ -133,20 +436,27 @@ where
 
                         match bucket.load::<B>() {
                             LoadResult::Entry { entry, .. } => {
-                                new_hash.adjust_chain_nodes(entry);
+                                new_hash.adjust_chain_nodes(entry, handle);
                             }
                             _ => {
-                                // println!("Expected Bucket to point to Entry");
+                                // Another Thread already moved the Chain
+                                // into a new HashLevel
                                 return;
                             }
                         };
 
-                        bucket.store_hashlevel(new_hash_ptr as *mut (), atomic::Ordering::SeqCst);
+                        bucket.store_hashlevel(new_hash_ptr as *mut (), atomic::Ordering::Release);
 
                         return;
                     }
                     Err(_) => {
-                        // println!("Failed CAS");
+                        self.events.cas_failure(CasSite::LevelExpansion);
+
+                        // # Safety:
+                        // The CAS failed, so no other Thread has ever seen
+                        // the new HashLevel and it does not contain any
+                        // Entries yet
+                        let _ = unsafe { Box::from_raw(new_hash_ptr) };
                     }
                 };
             } else {
@@ -157,13 +467,14 @@ where
                 match r.other.cas_entry::<B>(
                     cas_ptr,
                     n_ptr as *mut (),
-                    atomic::Ordering::SeqCst,
-                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Acquire,
                 ) {
                     Ok(_) => return,
                     Err(_) => {
                         // Something modified the Next-Ptr before us, so we
                         // should "retry"
+                        self.events.cas_failure(CasSite::ChainAppend);
                     }
                 }
             }
@@ -174,7 +485,7 @@ where
             // If the next Element is also an Entry, call this function
             // recursively with the next Entry as the Chain "root"
             LoadResult::Entry { entry, .. } => {
-                self.adjust_node_on_chain(n, entry, chain + 1);
+                self.adjust_node_on_chain(n, entry, chain + 1, handle);
             }
             // If the next Element is a HashLevel, try and insert the node
             // in the next HashLevel after this one
@@ -185,16 +496,31 @@ where
                     r = unsafe { &*r.previous };
                 }
 
-                r.adjust_node_on_hash(n);
+                r.adjust_node_on_hash(n, handle);
             }
         };
     }
 
     /// Adjusts the Node to fit into the current HashLevel
-    fn adjust_node_on_hash(&self, n: &Entry<K, V>) {
-        // Set the Next-Element to be the current HashLevel
+    fn adjust_node_on_hash(&self, n: &Entry<K, V>, handle: &mut hyaline::Handle<'_>) {
+        let _guard = match self.enter() {
+            Some(g) => g,
+            None => {
+                self.unlink(n.hash, handle);
+
+                // # Safety:
+                // Only Sub-Levels can be collapsed, which always have a
+                // Previous HashLevel that outlives them
+                let previous = unsafe { &*self.previous };
+                previous.adjust_node_on_hash(n, handle);
+                return;
+            }
+        };
+
+        // Set the Next-Element to be the current HashLevel, this is published
+        // by the CAS inserting the Node into the Bucket
         n.other
-            .store_hashlevel(self.own as *mut (), atomic::Ordering::SeqCst);
+            .store_hashlevel(self.own as *mut (), atomic::Ordering::Release);
 
         // Find the corresponding Bucket for the given Node
         let bucket_index = self.get_bucket_index(n.hash);
@@ -210,14 +536,14 @@ where
                 match bucket.cas_entry::<B>(
                     marked as *mut Entry<K, V>,
                     n_ptr as *mut (),
-                    atomic::Ordering::SeqCst,
-                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Acquire,
                 ) {
                     Ok(_) => {
                         return;
                     }
                     Err(_) => {
-                        // println!("Insert did not work");
+                        self.events.cas_failure(CasSite::BucketInsert);
                     }
                 };
             }
@@ -227,34 +553,47 @@ where
         match bucket.load::<B>() {
             // Bucket already contains a Node
             LoadResult::Entry { entry, .. } => {
-                self.adjust_node_on_chain(n, entry, 1);
+                self.adjust_node_on_chain(n, entry, 1, handle);
             }
             // Bucket points to a second HashLevel so we should
             // try and adjust the Node "onto" the newly found
             // HashLevel
             LoadResult::HashLevel { level: r, .. } => {
-                r.adjust_node_on_hash(n);
+                r.adjust_node_on_hash(n, handle);
             }
         };
     }
 
     /// Starts the adjustment process for the given Node as well as starting
     /// the adjustment for all the Nodes in its Chain
-    pub fn adjust_chain_nodes(&self, r: &Entry<K, V>) {
+    pub fn adjust_chain_nodes(&self, r: &Entry<K, V>, handle: &mut hyaline::Handle<'_>) {
         if let LoadResult::Entry { entry, .. } = r.other.load::<B>() {
-            self.adjust_chain_nodes(entry);
+            self.adjust_chain_nodes(entry, handle);
         }
-        self.adjust_node_on_hash(r);
+        self.adjust_node_on_hash(r, handle);
     }
 
-    /// Inserts the new Entry into the current HashLevel
+    /// Inserts the new Entry into the current HashLevel and returns the
+    /// Entry it replaced, if there already was one for the same Key
     pub fn insert_key_on_hash(
         &self,
         hash: u64,
         key: K,
         value: V,
         handle: &mut hyaline::Handle<'_>,
-    ) {
+    ) -> Option<*const Entry<K, V>> {
+        let _guard = match self.enter() {
+            Some(g) => g,
+            None => {
+                self.unlink(hash, handle);
+
+                // # Safety:
+                // See `adjust_node_on_hash`
+                let previous = unsafe { &*self.previous };
+                return previous.insert_key_on_hash(hash, key, value, handle);
+            }
+        };
+
         let bucket = self.buckets.get(self.get_bucket_index(hash)).expect(
             "The Bucket should always exist as there Hash should never be bigger than 2^bits",
         );
@@ -276,10 +615,10 @@ where
                 match bucket.cas_entry::<B>(
                     cas_ptr,
                     n_ptr as *mut (),
-                    atomic::Ordering::SeqCst,
-                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Acquire,
                 ) {
-                    Ok(_) => return,
+                    Ok(_) => return None,
                     Err(_) => {
                         new_entry = boxed_entry(n_ptr);
                     }
@@ -299,13 +638,19 @@ where
                 )
             }
             LoadResult::Entry { entry, .. } => {
-                entry.insert_key_on_chain(hash, &self, new_entry, 1, handle)
+                entry.insert_key_on_chain(hash, &self, bucket, new_entry, 1, handle)
             }
         }
     }
 
-    pub fn insert(&self, hash: u64, key: K, value: V, handle: &mut hyaline::Handle<'_>) {
-        self.insert_key_on_hash(hash, key, value, handle);
+    pub fn insert(
+        &self,
+        hash: u64,
+        key: K,
+        value: V,
+        handle: &mut hyaline::Handle<'_>,
+    ) -> Option<*const Entry<K, V>> {
+        self.insert_key_on_hash(hash, key, value, handle)
     }
 
     pub fn get<'a>(
@@ -314,6 +659,19 @@ where
         key: &K,
         handle: hyaline::Handle<'a>,
     ) -> Option<RefValue<'a, K, V>> {
+        let entry_ptr = self.find(hash, key)?;
+
+        Some(RefValue {
+            entry_ptr,
+            _handle: handle,
+        })
+    }
+
+    /// Searches for the Entry with the given Key.
+    ///
+    /// The Caller needs to hold a Hyaline-Handle for as long as it accesses
+    /// the returned Entry
+    pub fn find(&self, hash: u64, key: &K) -> Option<*const Entry<K, V>> {
         let bucket_index = self.get_bucket_index(hash);
         let bucket = self.buckets.get(bucket_index).expect(
             "The Bucket should always exist as there Hash should never be bigger than 2^bits",
@@ -326,11 +684,8 @@ where
         }
 
         match bucket.load::<B>() {
-            LoadResult::Entry { entry, .. } => match entry.get_chain(hash, &self, key, 1, handle) {
-                Ok(v) => Some(v),
-                _ => None,
-            },
-            LoadResult::HashLevel { level: sub_lvl, .. } => sub_lvl.get(hash, key, handle),
+            LoadResult::Entry { entry, .. } => entry.get_chain(hash, self, key, 1),
+            LoadResult::HashLevel { level: sub_lvl, .. } => sub_lvl.find(hash, key),
         }
     }
 
@@ -353,7 +708,7 @@ where
                 ..
             } => loop {
                 if &current_entry.key == key {
-                    current_entry.invalidate(atomic::Ordering::SeqCst);
+                    current_entry.invalidate(atomic::Ordering::Release);
                     return;
                 }
 
@@ -383,6 +738,10 @@ where
         to_remove: &Entry<K, V>,
         handle: &mut hyaline::Handle<'_>,
     ) {
+        // These need to be SeqCst, because we store the Previous-Ptr and then
+        // check that the Next-Ptr of the removed Entry has not changed in the
+        // mean time. With weaker Orderings, the Load could be reordered before
+        // the Store and we could miss an Entry appended concurrently
         let mut next_ptr = to_remove.other.raw_load(atomic::Ordering::SeqCst);
         loop {
             previous.raw_store(next_ptr, atomic::Ordering::SeqCst);
@@ -401,7 +760,85 @@ where
         return;
     }
 
+    /// Replaces the `to_replace` Entry, which is reached through `previous`,
+    /// with the `new` Entry.
+    ///
+    /// Returns false if `previous` no longer pointed to `to_replace`, in which
+    /// case nothing was modified
+    pub fn replace_entry_chain(
+        previous: &mptr::TargetPtr<K, V>,
+        to_replace: &Entry<K, V>,
+        new: *mut Entry<K, V>,
+    ) -> bool {
+        // # Safety:
+        // The new Entry is owned by the Caller and only gets published by
+        // the CAS below
+        let new_entry = unsafe { &*new };
+        let replace_ptr: *const Entry<K, V> = to_replace;
+
+        // Same as for removing an Entry, these need to be SeqCst, to not miss
+        // an Entry that is appended to the replaced Entry concurrently
+        let mut next_ptr = to_replace.other.raw_load(atomic::Ordering::SeqCst);
+        new_entry
+            .other
+            .raw_store(next_ptr, atomic::Ordering::SeqCst);
+
+        if previous
+            .cas_entry::<B>(
+                replace_ptr as *mut Entry<K, V>,
+                new as *mut (),
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            )
+            .is_err()
+        {
+            return false;
+        }
+
+        loop {
+            let tmp = to_replace.other.raw_load(atomic::Ordering::SeqCst);
+            if next_ptr == tmp {
+                break;
+            }
+            next_ptr = tmp;
+            new_entry
+                .other
+                .raw_store(next_ptr, atomic::Ordering::SeqCst);
+        }
+
+        true
+    }
+
+    /// Removes the Entry for the given Key from the Trie, starting at this
+    /// HashLevel, and afterwards collapses the HashLevel it was found on, if
+    /// that HashLevel is now empty
     fn invisible_entry(&self, hash: u64, key: &K, handle: &mut hyaline::Handle<'_>) {
+        let sub_lvl = {
+            let _guard = match self.enter() {
+                Some(g) => g,
+                // The HashLevel was empty, so it can not contain the Entry
+                None => return,
+            };
+
+            self.remove_from_level(hash, key, handle)
+        };
+
+        match sub_lvl {
+            Some(sub_lvl) => sub_lvl.invisible_entry(hash, key, handle),
+            None => self.try_collapse(hash, handle),
+        };
+    }
+
+    /// Removes the Entry for the given Key, if it is stored on this HashLevel.
+    ///
+    /// Returns the Sub-Level the Entry has to be searched for next, if it is
+    /// not stored on this HashLevel
+    fn remove_from_level(
+        &self,
+        hash: u64,
+        key: &K,
+        handle: &mut hyaline::Handle<'_>,
+    ) -> Option<&Self> {
         let bucket = self.get_bucket(hash).unwrap();
 
         match bucket.load::<B>() {
@@ -410,16 +847,16 @@ where
                 ptr: sub_lvl_ptr,
             } => {
                 if self.own == sub_lvl_ptr {
-                    return;
+                    return None;
                 }
 
-                sub_lvl.invisible_entry(hash, key, handle);
+                Some(sub_lvl)
             }
             LoadResult::Entry { mut entry, .. } => {
                 if &entry.key == key {
                     Self::remove_entry_chain(&bucket, entry, handle);
 
-                    return;
+                    return None;
                 }
 
                 loop {
@@ -429,17 +866,16 @@ where
                             ptr: sub_lvl_ptr,
                         } => {
                             if self.own == sub_lvl_ptr {
-                                return;
+                                return None;
                             }
-                            sub_lvl.invisible_entry(hash, key, handle);
-                            break;
+                            return Some(sub_lvl);
                         }
                         LoadResult::Entry {
                             entry: next_entry, ..
                         } => {
                             if &next_entry.key == key {
                                 Self::remove_entry_chain(&entry.other, next_entry, handle);
-                                return;
+                                return None;
                             }
 
                             entry = next_entry;
@@ -447,7 +883,7 @@ where
                     };
                 }
             }
-        };
+        }
     }
 
     pub fn remove_entry<'h>(&self, hash: u64, key: &K, handle: &mut hyaline::Handle<'h>) {
@@ -490,7 +926,7 @@ where
 impl<K, V, const B: u8> Drop for HashLevel<K, V, B> {
     fn drop(&mut self) {
         for bucket in self.buckets.iter() {
-            match bucket.load_ptr(atomic::Ordering::SeqCst) {
+            match bucket.load_ptr(atomic::Ordering::Relaxed) {
                 PtrType::Entry(_) => {}
                 PtrType::HashLevel(level_ptr) => {
                     if level_ptr == self.own as *mut () {
@@ -577,6 +1013,158 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn collision_expand_events() {
+        use crate::hash_trie::events::Counters;
+        use alloc::sync::Arc;
+
+        let counters = Arc::new(Counters::new());
+        let instance = hyaline::Hyaline::<4>::new(HashTrieMap::<u64, u64, RandomState>::free_func);
+        let hl = HashLevel::with_events(
+            0 as *const HashLevel<u64, u64, 4>,
+            0,
+            Events::new(counters.clone()),
+        );
+
+        // All the Hashes end up in the same Bucket on the first Level
+        for i in 0..5u64 {
+            hl.insert(0x1000000000000000 | (i << 56), i, i, &mut instance.enter());
+        }
+        assert_eq!(1, counters.levels_created());
+        assert_eq!(0, counters.cas_failures());
+
+        for i in 0..5u64 {
+            assert_eq!(
+                hl.get(0x1000000000000000 | (i << 56), &i, instance.enter())
+                    .unwrap(),
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn reserve_levels_insert_get() {
+        let instance = hyaline::Hyaline::<4>::new(HashTrieMap::<u64, u64, RandomState>::free_func);
+        let hl = HashLevel::new(0 as *const HashLevel<u64, u64, 4>, 0);
+
+        hl.insert(0x1234567890abcdef, 13, 123, &mut instance.enter());
+        hl.reserve_levels(2);
+
+        // The Bucket that already contained an Entry is left untouched
+        let bucket = hl.get_bucket(0x1234567890abcdef).unwrap();
+        assert!(matches!(
+            bucket.load_ptr(atomic::Ordering::SeqCst),
+            PtrType::Entry(_)
+        ));
+        let bucket = hl.get_bucket(0x2234567890abcdef).unwrap();
+        match bucket.load::<4>() {
+            LoadResult::HashLevel { level, ptr } => {
+                assert_ne!(hl.own, ptr as *const _);
+                assert_eq!(1, level.level);
+            }
+            LoadResult::Entry { .. } => panic!("Expected a Sub-Level"),
+        };
+
+        hl.insert(0x2234567890abcdef, 14, 124, &mut instance.enter());
+        assert_eq!(
+            hl.get(0x1234567890abcdef, &13, instance.enter()).unwrap(),
+            123
+        );
+        assert_eq!(
+            hl.get(0x2234567890abcdef, &14, instance.enter()).unwrap(),
+            124
+        );
+    }
+
+    /// Counts the Sub-Levels that are still reachable from the given Level
+    fn sub_levels(hl: &HashLevel<u64, u64, 4>) -> usize {
+        hl.buckets
+            .iter()
+            .map(|bucket| match bucket.load::<4>() {
+                LoadResult::HashLevel { level, ptr } if !core::ptr::eq(ptr, hl.own) => {
+                    1 + sub_levels(level)
+                }
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn collapse_after_remove() {
+        let instance = hyaline::Hyaline::<4>::new(HashTrieMap::<u64, u64, RandomState>::free_func);
+        let hl = HashLevel::new(core::ptr::null(), 0);
+
+        // All the Hashes end up in the same Bucket on the first two Levels
+        for i in 0..5u64 {
+            hl.insert(0x1200000000000000 | (i << 52), i, i, &mut instance.enter());
+        }
+        let levels = sub_levels(&hl);
+        assert!(levels > 0);
+
+        for i in 0..4u64 {
+            hl.remove_entry(0x1200000000000000 | (i << 52), &i, &mut instance.enter());
+        }
+        assert_eq!(levels, sub_levels(&hl));
+        assert_eq!(
+            hl.get(0x1200000000000000 | (4 << 52), &4, instance.enter())
+                .unwrap(),
+            4
+        );
+
+        hl.remove_entry(0x1200000000000000 | (4 << 52), &4, &mut instance.enter());
+        assert_eq!(0, sub_levels(&hl));
+        assert!(hl.is_empty());
+
+        // The Bucket can be used again after the collapse
+        hl.insert(0x1200000000000000, 0, 10, &mut instance.enter());
+        assert_eq!(
+            hl.get(0x1200000000000000, &0, instance.enter()).unwrap(),
+            10
+        );
+    }
+
+    #[test]
+    fn collapse_after_churn() {
+        let map = HashTrieMap::<u64, u64, RandomState>::new();
+
+        for round in 0..3 {
+            for i in 0..1000u64 {
+                map.insert(i, i + round);
+            }
+            assert!(sub_levels(&map.initial_level) > 0);
+
+            for i in 0..1000u64 {
+                map.remove(&i);
+            }
+            assert_eq!(0, sub_levels(&map.initial_level));
+            assert!(map.initial_level.is_empty());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn collapse_events() {
+        use crate::hash_trie::events::Counters;
+        use alloc::sync::Arc;
+
+        let counters = Arc::new(Counters::new());
+        let map = HashTrieMap::<u64, u64, RandomState>::with_build_hasher_and_events(
+            RandomState::new(),
+            counters.clone(),
+        );
+
+        for i in 0..1000u64 {
+            map.insert(i, i);
+        }
+        for i in 0..1000u64 {
+            map.remove(&i);
+        }
+
+        assert!(counters.levels_created() > 0);
+        assert_eq!(counters.levels_created(), counters.levels_collapsed());
+    }
+
     #[test]
     fn insert_remove() {
         let instance = hyaline::Hyaline::<4>::new(HashTrieMap::<u64, u64, RandomState>::free_func);
@@ -0,0 +1,81 @@
+//! The internal Handle used by the Map to call the [`MapEvents`] Hooks.
+//!
+//! Without the `metrics` Feature, the Handle is a zero-sized Type and all its
+//! Methods are empty, so the Hooks are compiled out entirely.
+
+#[cfg(feature = "metrics")]
+use alloc::sync::Arc;
+
+#[cfg(feature = "metrics")]
+use super::events::{CasFailure, MapEvents};
+
+/// The Place where a CAS-Operation failed, which is only passed on to the
+/// Hooks with the `metrics` Feature enabled
+#[derive(Clone, Copy)]
+pub(crate) enum CasSite {
+    ChainAppend,
+    LevelExpansion,
+    BucketInsert,
+}
+
+#[derive(Clone)]
+pub(crate) struct Events {
+    #[cfg(feature = "metrics")]
+    inner: Option<Arc<dyn MapEvents>>,
+}
+
+impl Events {
+    /// A Handle that does not report anything
+    pub const fn none() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            inner: None,
+        }
+    }
+
+    /// A Handle that reports to the given Events instance
+    #[cfg(feature = "metrics")]
+    pub fn new(events: Arc<dyn MapEvents>) -> Self {
+        Self {
+            inner: Some(events),
+        }
+    }
+
+    #[inline(always)]
+    pub fn cas_failure(&self, site: CasSite) {
+        #[cfg(feature = "metrics")]
+        if let Some(e) = self.inner.as_ref() {
+            e.on_cas_failure(match site {
+                CasSite::ChainAppend => CasFailure::ChainAppend,
+                CasSite::LevelExpansion => CasFailure::LevelExpansion,
+                CasSite::BucketInsert => CasFailure::BucketInsert,
+            });
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = site;
+    }
+
+    #[inline(always)]
+    pub fn level_created(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(e) = self.inner.as_ref() {
+            e.on_level_created();
+        }
+    }
+
+    #[inline(always)]
+    pub fn level_collapsed(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(e) = self.inner.as_ref() {
+            e.on_level_collapsed();
+        }
+    }
+
+    #[inline(always)]
+    pub fn stale_lookup(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(e) = self.inner.as_ref() {
+            e.on_stale_lookup();
+        }
+    }
+}
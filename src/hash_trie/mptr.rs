@@ -42,7 +42,7 @@ impl<K, V> TargetPtr<K, V> {
     }
 
     pub fn load<const B: u8>(&self) -> LoadResult<'_, K, V, B> {
-        let ptr = self.0.load(atomic::Ordering::SeqCst);
+        let ptr = self.0.load(atomic::Ordering::Acquire);
         if is_entry(ptr as *const u8) {
             let ptr = to_actual_ptr(ptr as *const u8) as *const ();
             let ptr = ptr as *mut Entry<K, V>;
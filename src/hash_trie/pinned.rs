@@ -0,0 +1,163 @@
+use core::{
+    cell::RefCell,
+    fmt::Debug,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::hyaline;
+
+use super::HashTrieMap;
+
+/// A View of a [`HashTrieMap`], that performs all of its Operations using a
+/// single Hyaline-Handle, created using [`HashTrieMap::pin`].
+///
+/// Every Operation on the [`HashTrieMap`] itself enters and leaves the
+/// Hyaline-Instance, which costs two atomic Operations every time. A
+/// PinnedMap only enters the Instance once, when it is created, which makes
+/// performing multiple Operations in a row cheaper.
+///
+/// # Guarantees
+/// * Operations performed through the same PinnedMap observe each other,
+///   so a `get` after an `insert` returns the inserted Value, unless another
+///   Thread modified the Key in the mean time.
+/// * All References returned by the PinnedMap stay valid for as long as the
+///   PinnedMap exists, even if the Entry is removed from the Map or its
+///   Value is overwritten in the mean time.
+///
+/// # Note
+/// Entries that are removed from the Map while the PinnedMap exists can only
+/// be freed once it is dropped, so a PinnedMap should not be kept around for
+/// longer than needed.
+///
+/// # Example
+/// ```
+/// # use nolock::hash_trie::HashTrieMap;
+/// # use std::collections::hash_map::RandomState;
+/// let map = HashTrieMap::<&str, u64, RandomState>::new();
+/// map.insert("balance", 100);
+///
+/// let pinned = map.pin();
+/// let balance = pinned.get(&"balance").unwrap();
+/// let previous = pinned.insert("balance", balance - 30).unwrap();
+///
+/// assert_eq!(100, *balance);
+/// assert_eq!(100, *previous);
+/// assert_eq!(Some(&70), pinned.get(&"balance"));
+/// ```
+pub struct PinnedMap<'m, K, V, H> {
+    map: &'m HashTrieMap<K, V, H>,
+    handle: RefCell<hyaline::Handle<'m>>,
+}
+
+impl<'m, K, V, H> PinnedMap<'m, K, V, H> {
+    pub(crate) fn new(map: &'m HashTrieMap<K, V, H>) -> Self {
+        Self {
+            map,
+            handle: RefCell::new(map.instance.enter()),
+        }
+    }
+}
+
+impl<'m, K, V, H> Debug for PinnedMap<'m, K, V, H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PinnedMap ()")
+    }
+}
+
+impl<'m, K, V, H> PinnedMap<'m, K, V, H>
+where
+    K: Hash + Eq,
+    H: BuildHasher,
+{
+    /// Inserts the given Key and Value into the Map and returns a Reference
+    /// to the previous Value for the Key, like [`HashTrieMap::insert`]
+    pub fn insert(&self, key: K, value: V) -> Option<&V> {
+        let hash = self.map.hash_key(&key);
+
+        let mut handle = self.handle.borrow_mut();
+        let previous = self
+            .map
+            .initial_level
+            .insert(hash, key, value, &mut handle)?;
+
+        // # Safety:
+        // The previous Entry was retired using our Handle, so it will not be
+        // freed before the Handle is dropped together with the PinnedMap
+        Some(unsafe { &(*previous).value })
+    }
+
+    /// Returns a Reference to the Value stored for the given Key
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let hash = self.map.hash_key(key);
+
+        let entry = self.map.initial_level.find(hash, key)?;
+        crate::poison::check(entry);
+
+        // # Safety:
+        // The Entry was reachable while we held the Handle, so it will not be
+        // freed before the Handle is dropped together with the PinnedMap
+        Some(unsafe { &(*entry).value })
+    }
+
+    /// Removes the Entry for the given Key from the Map
+    pub fn remove(&self, key: &K) {
+        let hash = self.map.hash_key(key);
+
+        let mut handle = self.handle.borrow_mut();
+        self.map.initial_level.remove_entry(hash, key, &mut handle);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::RandomState;
+
+    use super::*;
+
+    #[test]
+    fn read_your_writes() {
+        let map = HashTrieMap::<u64, u64, RandomState>::new();
+        let pinned = map.pin();
+
+        assert_eq!(None, pinned.get(&13));
+        assert_eq!(None, pinned.insert(13, 1));
+        assert_eq!(Some(&1), pinned.get(&13));
+        assert_eq!(Some(&1), pinned.insert(13, 2));
+        assert_eq!(Some(&2), pinned.get(&13));
+
+        pinned.remove(&13);
+        assert_eq!(None, pinned.get(&13));
+
+        drop(pinned);
+        assert!(map.get(&13).is_none());
+    }
+
+    #[test]
+    fn references_outlive_removal() {
+        let map = HashTrieMap::<u64, String, RandomState>::new();
+        let pinned = map.pin();
+
+        pinned.insert(1, "first".to_owned());
+        let first = pinned.get(&1).unwrap();
+        let replaced = pinned.insert(1, "second".to_owned()).unwrap();
+        pinned.remove(&1);
+
+        assert_eq!("first", first);
+        assert_eq!("first", replaced);
+        assert_eq!(None, pinned.get(&1));
+    }
+
+    #[test]
+    fn visible_to_map() {
+        let map = HashTrieMap::<u64, u64, RandomState>::new();
+        map.insert(1, 10);
+
+        let pinned = map.pin();
+        assert_eq!(Some(&10), pinned.get(&1));
+        pinned.insert(2, 20);
+
+        assert_eq!(map.get(&2).unwrap(), 20);
+        map.insert(3, 30);
+        assert_eq!(Some(&30), pinned.get(&3));
+    }
+}
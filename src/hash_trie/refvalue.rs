@@ -1,10 +1,15 @@
-use core::fmt::Debug;
+use alloc::borrow::ToOwned;
+use core::{fmt::Debug, ops::Deref};
 
 use crate::hyaline;
 
 use super::entry::Entry;
 
-/// TODO
+/// A Reference to a Value stored in the [`HashTrieMap`](super::HashTrieMap).
+///
+/// The Value is protected from being reclaimed for as long as the RefValue
+/// exists, even if the Entry is removed from the Map or its Value is
+/// overwritten in the mean time.
 pub struct RefValue<'a, K, V> {
     pub(crate) entry_ptr: *const Entry<K, V>,
     pub(crate) _handle: hyaline::Handle<'a>,
@@ -12,7 +17,6 @@ pub struct RefValue<'a, K, V> {
 
 impl<'a, K, V> Debug for RefValue<'a, K, V>
 where
-    K: Debug,
     V: Debug,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -21,10 +25,74 @@ where
 }
 
 impl<'a, K, V> RefValue<'a, K, V> {
-    /// TODO
+    /// Returns a Reference to the underlying Value
     pub fn value(&self) -> &V {
+        crate::poison::check(self.entry_ptr);
         unsafe { &(*self.entry_ptr).value }
     }
+
+    /// Projects the RefValue to some Part of the Value, while still keeping
+    /// the entire Value protected.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::hash_trie::HashTrieMap;
+    /// let map = HashTrieMap::new();
+    /// map.insert("key", (13, "value".to_owned()));
+    ///
+    /// let name = map.get(&"key").unwrap().map(|v| &v.1);
+    /// assert_eq!("value", name.as_str());
+    /// ```
+    pub fn map<U, F>(self, func: F) -> MappedRefValue<'a, U>
+    where
+        F: FnOnce(&V) -> &U,
+    {
+        let ptr = func(self.value()) as *const U;
+
+        MappedRefValue {
+            ptr,
+            _handle: self._handle,
+        }
+    }
+
+    /// Clones the underlying Value
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::hash_trie::HashTrieMap;
+    /// let map = HashTrieMap::new();
+    /// map.insert("key", 13);
+    ///
+    /// let value = map.get(&"key").unwrap();
+    /// assert_eq!(13, value.clone_value());
+    /// ```
+    pub fn clone_value(&self) -> V
+    where
+        V: Clone,
+    {
+        self.value().clone()
+    }
+
+    /// Converts the RefValue into an owned Value, which is no longer tied to
+    /// the Lifetime of the Map.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::hash_trie::HashTrieMap;
+    /// let map = HashTrieMap::new();
+    /// map.insert("key", "value".to_owned());
+    ///
+    /// let owned: String = map.get(&"key").unwrap().into_owned();
+    /// drop(map);
+    ///
+    /// assert_eq!("value", owned);
+    /// ```
+    pub fn into_owned(self) -> V::Owned
+    where
+        V: ToOwned,
+    {
+        self.value().to_owned()
+    }
 }
 
 impl<'a, K, V> AsRef<V> for RefValue<'a, K, V> {
@@ -33,6 +101,14 @@ impl<'a, K, V> AsRef<V> for RefValue<'a, K, V> {
     }
 }
 
+impl<'a, K, V> Deref for RefValue<'a, K, V> {
+    type Target = V;
+
+    fn deref(&self) -> &Self::Target {
+        self.value()
+    }
+}
+
 impl<'a, K, V> PartialEq for RefValue<'a, K, V>
 where
     V: PartialEq,
@@ -50,3 +126,73 @@ where
         self.value().eq(other)
     }
 }
+
+/// A Reference to some Part of a Value stored in the
+/// [`HashTrieMap`](super::HashTrieMap), created using [`RefValue::map`].
+///
+/// Like the [`RefValue`], this keeps the entire Value protected from being
+/// reclaimed for as long as it exists.
+pub struct MappedRefValue<'a, T> {
+    ptr: *const T,
+    _handle: hyaline::Handle<'a>,
+}
+
+impl<'a, T> Debug for MappedRefValue<'a, T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("MappedRefValue").field(self.value()).finish()
+    }
+}
+
+impl<'a, T> MappedRefValue<'a, T> {
+    /// Returns a Reference to the underlying Value
+    pub fn value(&self) -> &T {
+        unsafe { &*self.ptr }
+    }
+
+    /// Further projects the Reference to some Part of the current Value
+    pub fn map<U, F>(self, func: F) -> MappedRefValue<'a, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        let ptr = func(self.value()) as *const U;
+
+        MappedRefValue {
+            ptr,
+            _handle: self._handle,
+        }
+    }
+
+    /// Converts the Reference into an owned Value
+    pub fn into_owned(self) -> T::Owned
+    where
+        T: ToOwned,
+    {
+        self.value().to_owned()
+    }
+}
+
+impl<'a, T> AsRef<T> for MappedRefValue<'a, T> {
+    fn as_ref(&self) -> &T {
+        self.value()
+    }
+}
+
+impl<'a, T> Deref for MappedRefValue<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.value()
+    }
+}
+
+impl<'a, T> PartialEq<T> for MappedRefValue<'a, T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.value().eq(other)
+    }
+}
@@ -1,15 +1,30 @@
 //! A simple implementation of Hazard-Pointers, that also supports having
 //! multiple Hazard-Pointer-Domains
 //!
+//! # Fork-Safety
+//! After the Process is forked, only the forking Thread exists in the Child,
+//! but the Hazard-Pointers of all the other Threads would otherwise still
+//! protect their Ptrs and prevent them from ever being reclaimed. On Unix,
+//! the Domains therefore register a Handler using `pthread_atfork`, after
+//! which the Hazard-Pointers of all the Threads that did not survive the
+//! Fork are ignored in the Child.
+//!
+//! The Thread-Local State of those Threads, like their retired Ptrs, is kept
+//! until the Domain is dropped, as the Threads will never access it again,
+//! and the Child can keep using the Domains right away without first calling
+//! `exec`.
+//!
+//! [`OwnedGuard`]s are not bound to the Thread that created them, so their
+//! Hazard-Pointers are always respected, even if that Thread did not survive
+//! the Fork.
+//!
 //! # Reference:
 //! * [Hazard Pointers: Safe Memory Reclamation for Lock-Free Objects](https://www.eecg.utoronto.ca/~amza/ece1747h/papers/hazard_pointers.pdf)
 
-// TODO
-// Add better reuse of Hazard-Pointers, mainly that if a Domain instance is
-// dropped, it should mark all of its entries to be reused by other Domains
-
+mod fork;
 mod record;
-use crate::sync::atomic;
+mod record_cache;
+use crate::sync::{api, atomic};
 use std::{cell::RefCell, fmt::Debug, sync::Arc};
 
 use record::Record;
@@ -22,6 +37,16 @@ use domain::{DomainGlobal, TLDomain};
 mod guard;
 pub use guard::Guard;
 
+mod owned;
+pub use owned::OwnedGuard;
+
+mod pair;
+pub use pair::GuardPair;
+
+mod pinned;
+use pinned::PinnedRecords;
+pub use pinned::{PinnedGuard, PINNED_GUARDS};
+
 use crate::thread_data::ThreadData;
 
 mod global {
@@ -65,13 +90,28 @@ pub use global::*;
 /// share a single Domain, List 1 has to check the Hazard-Pointers for List 2
 /// everytime it needs to work with Hazard-Pointers although they are not
 /// relevant in that Case.
+/// ## Child Domains
+/// Every Domain has its own List of Hazard-Records, so creating a Domain for
+/// every Instance of a Datastructure also means that every Instance needs its
+/// own Records. A [`child`](Domain::child) Domain instead shares the Records
+/// of its Parent, but keeps its own retired Ptrs and Reclaim-Threshold, which
+/// isolates the Reclamation of an Instance without adding yet another List of
+/// Records that needs to be scanned.
 #[derive(Clone)]
 pub struct Domain {
     global: Arc<DomainGlobal>,
-    local: Arc<ThreadData<RefCell<TLDomain>>>,
+    local: Arc<ThreadData<Local>>,
     reclaim_threshold: usize,
 }
 
+/// The Thread-Local State of a Domain
+struct Local {
+    /// The Pinned-Records are kept outside of the TLDomain, so that they can
+    /// be borrowed by the PinnedGuards without borrowing the TLDomain
+    pinned: PinnedRecords,
+    domain: RefCell<TLDomain>,
+}
+
 impl Debug for Domain {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -89,6 +129,8 @@ impl Domain {
     /// `reclaim_threshold`: The Threshold for waiting Items before attempting
     /// to reclaim Memory
     pub fn new(reclaim_threshold: usize) -> Self {
+        fork::register();
+
         Self {
             global: Arc::new(DomainGlobal::new()),
             local: Arc::new(ThreadData::default()),
@@ -96,13 +138,55 @@ impl Domain {
         }
     }
 
-    fn get_local(&self) -> &RefCell<TLDomain> {
-        self.local.get_or(|| {
-            let global = self.global.clone();
-            RefCell::new(TLDomain::new(global, self.reclaim_threshold))
+    /// Creates a new child Domain, which shares the Hazard-Records with this
+    /// Domain, but has its own retired Ptrs and Reclaim-Threshold.
+    ///
+    /// Guards obtained from either Domain protect Ptrs retired in the other
+    /// one, as they all use the same Records, but every Domain only reclaims
+    /// the Ptrs that were retired through it.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::hazard_ptr;
+    /// # use std::sync::atomic;
+    /// let parent = hazard_ptr::Domain::new(64);
+    /// let child = parent.child(1);
+    ///
+    /// let ptr = Box::into_raw(Box::new(13));
+    /// let atom_ptr = atomic::AtomicPtr::new(ptr);
+    ///
+    /// // A Guard from the Parent also protects Ptrs retired in the Child
+    /// let guard = parent.protect(&atom_ptr, atomic::Ordering::SeqCst);
+    /// unsafe {
+    ///     child.retire(ptr, |p| { unsafe { Box::from_raw(p) }; });
+    /// }
+    /// child.reclaim();
+    /// assert_eq!(13, *guard);
+    ///
+    /// // The Records are shared between the Domains
+    /// assert_eq!(1, child.record_count());
+    /// # drop(guard);
+    /// # child.reclaim();
+    /// ```
+    pub fn child(&self, reclaim_threshold: usize) -> Self {
+        Self {
+            global: self.global.clone(),
+            local: Arc::new(ThreadData::default()),
+            reclaim_threshold,
+        }
+    }
+
+    fn get_local_state(&self) -> &Local {
+        self.local.get_or(|| Local {
+            pinned: PinnedRecords::new(self.global.clone()),
+            domain: RefCell::new(TLDomain::new(self.global.clone(), self.reclaim_threshold)),
         })
     }
 
+    fn get_local(&self) -> &RefCell<TLDomain> {
+        &self.get_local_state().domain
+    }
+
     /// Reads the Data from the given AtomicPtr and protects it using a Hazard-
     /// Ptr.
     /// Returns you a Guard through which you can interact with the Data loaded
@@ -135,7 +219,7 @@ impl Domain {
     /// ```
     pub fn protect<T>(
         &self,
-        atom_ptr: &atomic::AtomicPtr<T>,
+        atom_ptr: &api::atomic::AtomicPtr<T>,
         load_order: atomic::Ordering,
     ) -> Guard<T> {
         let local = self.get_local();
@@ -144,6 +228,106 @@ impl Domain {
         shared.protect(atom_ptr, load_order)
     }
 
+    /// Reads the Data from the Entry at `index` in the given Table of
+    /// AtomicPtrs and protects it using a Hazard-Ptr, like
+    /// [`protect`](Self::protect) does for a single AtomicPtr.
+    ///
+    /// The returned Guard can then be moved to other Entries of the Table
+    /// using [`Guard::protect_array`], which reuses its Hazard-Record instead
+    /// of acquiring a new one for every Lookup.
+    ///
+    /// # Panics
+    /// If the `index` is out of Bounds for the Table
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::hazard_ptr;
+    /// # use std::sync::atomic;
+    /// let domain = hazard_ptr::Domain::new(10);
+    ///
+    /// let table: Vec<_> = (0..4)
+    ///     .map(|i| atomic::AtomicPtr::new(Box::into_raw(Box::new(i))))
+    ///     .collect();
+    ///
+    /// let mut guard = domain.protect_array(&table, 1, atomic::Ordering::SeqCst);
+    /// assert_eq!(1, *guard);
+    ///
+    /// // Move the same Guard to another Entry of the Table
+    /// guard.protect_array(&table, 3, atomic::Ordering::SeqCst);
+    /// assert_eq!(3, *guard);
+    /// assert_eq!(1, domain.record_count());
+    ///
+    /// # drop(guard);
+    /// # for entry in table {
+    /// #     drop(unsafe { Box::from_raw(entry.into_inner()) });
+    /// # }
+    /// ```
+    pub fn protect_array<T>(
+        &self,
+        table: &[api::atomic::AtomicPtr<T>],
+        index: usize,
+        load_order: atomic::Ordering,
+    ) -> Guard<T> {
+        self.protect(&table[index], load_order)
+    }
+
+    /// Returns the total Number of Hazard-Records that have been allocated for
+    /// this Domain.
+    ///
+    /// Records are released once their Guard is dropped and can then be
+    /// reused by any Thread, so this only grows with the maximum Number of
+    /// Guards that were alive at the same Time.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::hazard_ptr;
+    /// let domain = hazard_ptr::Domain::new(10);
+    ///
+    /// let guard = domain.empty_guard::<usize>();
+    /// drop(guard);
+    ///
+    /// // Acquiring a new Guard reuses the released Record
+    /// let guard = domain.empty_guard::<usize>();
+    /// assert_eq!(1, domain.record_count());
+    /// # drop(guard);
+    /// ```
+    pub fn record_count(&self) -> usize {
+        self.global.record_count()
+    }
+
+    /// Takes a Snapshot of all the Ptrs that are currently protected by a
+    /// Hazard-Record of this Domain, which is mostly useful for Debugging,
+    /// like finding out which Ptrs are kept alive when hunting down Leaks.
+    ///
+    /// Every Entry is the Address of a protected Ptr together with the Number
+    /// of Records currently protecting it, sorted by the Address. Only the
+    /// Addresses are exposed, as the Data behind them may be reclaimed as soon
+    /// as the Snapshot has been taken.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::hazard_ptr;
+    /// # use std::sync::atomic;
+    /// let domain = hazard_ptr::Domain::new(10);
+    ///
+    /// let ptr = Box::into_raw(Box::new(13));
+    /// let atom_ptr = atomic::AtomicPtr::new(ptr);
+    ///
+    /// let first = domain.protect(&atom_ptr, atomic::Ordering::SeqCst);
+    /// let second = domain.protect(&atom_ptr, atomic::Ordering::SeqCst);
+    ///
+    /// let protected: Vec<_> = domain.iter_protected().collect();
+    /// assert_eq!(vec![(ptr as usize, 2)], protected);
+    ///
+    /// drop(first);
+    /// drop(second);
+    /// assert_eq!(0, domain.iter_protected().count());
+    /// # drop(unsafe { Box::from_raw(ptr) });
+    /// ```
+    pub fn iter_protected(&self) -> std::vec::IntoIter<(usize, usize)> {
+        self.global.protection_counts().into_iter()
+    }
+
     /// Creates a new empty Guard, that can then be used to protect any sort of
     /// Data behind an AtomicPtr.
     pub fn empty_guard<T>(&self) -> Guard<T> {
@@ -153,6 +337,140 @@ impl Domain {
         shared.empty_guard()
     }
 
+    /// Creates a new empty [`OwnedGuard`], which keeps the Records of this
+    /// Domain alive by itself and can therefore outlive the Domain and be
+    /// moved to other Threads.
+    pub fn owned_guard<T>(&self) -> OwnedGuard<T> {
+        OwnedGuard::new(self.empty_guard(), self.global.clone())
+    }
+
+    /// Reads the Data from the given AtomicPtr and protects it using an
+    /// [`OwnedGuard`], like [`protect`](Self::protect) does with a normal
+    /// Guard
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::hazard_ptr;
+    /// # use std::sync::atomic;
+    /// let domain = hazard_ptr::Domain::new(10);
+    ///
+    /// let ptr = Box::into_raw(Box::new(13));
+    /// let atom_ptr = atomic::AtomicPtr::new(ptr);
+    ///
+    /// let guard = domain.protect_owned(&atom_ptr, atomic::Ordering::SeqCst);
+    /// drop(domain);
+    /// assert_eq!(13, *guard);
+    ///
+    /// # drop(guard);
+    /// # drop(unsafe { Box::from_raw(ptr) });
+    /// ```
+    pub fn protect_owned<T>(
+        &self,
+        atom_ptr: &api::atomic::AtomicPtr<T>,
+        load_order: atomic::Ordering,
+    ) -> OwnedGuard<T> {
+        let mut guard: OwnedGuard<T> = self.owned_guard();
+        guard.protect(atom_ptr, load_order);
+        guard
+    }
+
+    /// Creates a new empty [`GuardPair`], which owns two Hazard-Records and
+    /// can be used to traverse linked Datastructures by rotating the
+    /// Protections between them, instead of acquiring a new Guard for every
+    /// Step.
+    pub fn guard_pair<T>(&self) -> GuardPair<T> {
+        let local = self.get_local();
+
+        let mut shared = local.borrow_mut();
+        GuardPair::new(shared.empty_guard(), shared.empty_guard())
+    }
+
+    /// Creates a new empty [`PinnedGuard`], that uses one of the Records
+    /// pinned to the current Thread instead of acquiring a Record from the
+    /// Domain, which avoids any Allocations or shared Operations when
+    /// obtaining and dropping it.
+    ///
+    /// Returns `None` if the current Thread already holds
+    /// [`PINNED_GUARDS`] PinnedGuards for this Domain, in which case a normal
+    /// Guard should be used instead.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::hazard_ptr;
+    /// # use std::sync::atomic;
+    /// let domain = hazard_ptr::Domain::new(10);
+    ///
+    /// let ptr = Box::into_raw(Box::new(13));
+    /// let atom_ptr = atomic::AtomicPtr::new(ptr);
+    ///
+    /// let mut guard = domain.pinned_guard::<i32>().unwrap();
+    /// guard.protect(&atom_ptr, atomic::Ordering::SeqCst);
+    /// assert_eq!(13, *guard);
+    ///
+    /// # drop(guard);
+    /// # drop(unsafe { Box::from_raw(ptr) });
+    /// ```
+    pub fn pinned_guard<T>(&self) -> Option<PinnedGuard<'_, T>> {
+        PinnedGuard::new(&self.get_local_state().pinned)
+    }
+
+    /// Reads the Data from the given AtomicPtr, protects it and runs the
+    /// given Closure with it, releasing the Protection again once the Closure
+    /// returns. The Closure gets `None` if the AtomicPtr contained a
+    /// Null-Ptr.
+    ///
+    /// This is meant for very short Reads, as it uses one of the
+    /// Pinned-Records of the current Thread, like a [`PinnedGuard`], so it
+    /// neither constructs a Guard nor returns a Record to the Domain
+    /// afterwards. Only if all the Pinned-Records are already in use, for
+    /// example when nesting more than [`PINNED_GUARDS`] Calls, a normal
+    /// [`Guard`] is used instead.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::hazard_ptr;
+    /// # use std::sync::atomic;
+    /// let domain = hazard_ptr::Domain::new(10);
+    ///
+    /// let ptr = Box::into_raw(Box::new(13));
+    /// let atom_ptr = atomic::AtomicPtr::new(ptr);
+    ///
+    /// let value = domain.with_protected(&atom_ptr, atomic::Ordering::SeqCst, |v| *v.unwrap());
+    /// assert_eq!(13, value);
+    /// assert_eq!(0, domain.iter_protected().count());
+    ///
+    /// # drop(unsafe { Box::from_raw(ptr) });
+    /// ```
+    pub fn with_protected<T, F, R>(
+        &self,
+        atom_ptr: &api::atomic::AtomicPtr<T>,
+        load_order: atomic::Ordering,
+        func: F,
+    ) -> R
+    where
+        F: FnOnce(Option<&T>) -> R,
+    {
+        let record = match self.get_local_state().pinned.claim_scoped() {
+            Some(r) => r,
+            None => {
+                let guard = self.protect(atom_ptr, load_order);
+                if guard.is_null() {
+                    return func(None);
+                }
+                return func(Some(&*guard));
+            }
+        };
+
+        let ptr = record.protect(atom_ptr, load_order);
+        crate::poison::check(ptr);
+
+        // # Safety:
+        // The Ptr is protected by the Record until it is dropped at the end
+        // of this Function, after the Closure has returned, so the Data can
+        // not be reclaimed while the Closure uses it
+        func(unsafe { ptr.as_ref() })
+    }
+
     /// Marks the given Ptr as retired and once no more Hazard-Ptrs protect
     /// the same Ptr, the given `retire_fn` function will be called to
     /// properly clean up the Data.
@@ -206,7 +524,7 @@ impl Domain {
 mod tests {
     use super::*;
 
-    use crate::sync::atomic;
+    use crate::sync::api::atomic;
 
     #[derive(Debug, Clone)]
     struct DropCheck {
@@ -228,6 +546,186 @@ mod tests {
         }
     }
 
+    #[test]
+    fn records_reused_across_threads() {
+        let domain = Arc::new(Domain::new(10));
+
+        {
+            let domain = domain.clone();
+            std::thread::spawn(move || {
+                let guard = domain.empty_guard::<usize>();
+                drop(guard);
+            })
+            .join()
+            .unwrap();
+        }
+        assert_eq!(1, domain.record_count());
+
+        let first = domain.empty_guard::<usize>();
+        assert_eq!(1, domain.record_count());
+
+        let second = domain.empty_guard::<usize>();
+        assert_eq!(2, domain.record_count());
+
+        drop(first);
+        drop(second);
+    }
+
+    #[test]
+    fn with_protected_nested() {
+        let domain = Domain::new(10);
+
+        let ptr = Box::into_raw(Box::new(13usize));
+        let atom_ptr = atomic::AtomicPtr::new(ptr);
+
+        fn nest(domain: &Domain, atom_ptr: &atomic::AtomicPtr<usize>, depth: usize) -> usize {
+            domain.with_protected(atom_ptr, atomic::Ordering::SeqCst, |value| {
+                let value = *value.unwrap();
+                if depth == 0 {
+                    return value;
+                }
+                value + nest(domain, atom_ptr, depth - 1)
+            })
+        }
+
+        // One more than the Pinned-Records, so the last one uses a Guard
+        assert_eq!(
+            13 * (PINNED_GUARDS + 1),
+            nest(&domain, &atom_ptr, PINNED_GUARDS)
+        );
+        assert_eq!(PINNED_GUARDS + 1, domain.record_count());
+        assert_eq!(0, domain.iter_protected().count());
+
+        let null_ptr = atomic::AtomicPtr::<usize>::new(std::ptr::null_mut());
+        assert!(domain.with_protected(&null_ptr, atomic::Ordering::SeqCst, |v| v.is_none()));
+
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+
+    #[test]
+    fn with_protected_keeps_alive() {
+        let domain = Domain::new(0);
+
+        let drop_chk = DropCheck::new();
+        let ptr = Box::into_raw(Box::new(drop_chk.clone()));
+        let atom_ptr = atomic::AtomicPtr::new(ptr);
+
+        domain.with_protected(&atom_ptr, atomic::Ordering::SeqCst, |value| {
+            assert!(value.is_some());
+            atom_ptr.store(std::ptr::null_mut(), atomic::Ordering::SeqCst);
+            unsafe {
+                domain.retire(ptr, |p| {
+                    drop(Box::from_raw(p));
+                })
+            };
+            assert_eq!(0, drop_chk.drop_count());
+        });
+
+        domain.reclaim();
+        assert_eq!(1, drop_chk.drop_count());
+    }
+
+    #[test]
+    fn guard_pair_keeps_prev_alive() {
+        let domain = Domain::new(0);
+
+        let first_chk = DropCheck::new();
+        let second_chk = DropCheck::new();
+        let first = Box::into_raw(Box::new(first_chk.clone()));
+        let second = Box::into_raw(Box::new(second_chk.clone()));
+        let atom_ptr = atomic::AtomicPtr::new(first);
+
+        let mut pair = domain.guard_pair::<DropCheck>();
+        pair.advance(&atom_ptr, atomic::Ordering::SeqCst);
+        assert!(pair.prev().is_null());
+        assert_eq!(first, pair.curr().as_raw());
+
+        atom_ptr.store(second, atomic::Ordering::SeqCst);
+        pair.advance(&atom_ptr, atomic::Ordering::SeqCst);
+        assert_eq!(first, pair.prev().as_raw());
+        assert_eq!(second, pair.curr().as_raw());
+
+        unsafe {
+            domain.retire(first, |p| {
+                drop(Box::from_raw(p));
+            })
+        };
+        domain.reclaim();
+        assert_eq!(0, first_chk.drop_count());
+
+        // Advancing again gives up the Protection of the first Node
+        atom_ptr.store(std::ptr::null_mut(), atomic::Ordering::SeqCst);
+        pair.advance(&atom_ptr, atomic::Ordering::SeqCst);
+        assert_eq!(second, pair.prev().as_raw());
+        assert!(pair.curr().is_null());
+        domain.reclaim();
+        assert_eq!(1, first_chk.drop_count());
+
+        assert_eq!(2, domain.record_count());
+
+        drop(pair);
+        drop(unsafe { Box::from_raw(second) });
+        assert_eq!(1, second_chk.drop_count());
+    }
+
+    #[test]
+    fn child_shares_records() {
+        let parent = Domain::new(usize::MAX);
+        let child = parent.child(usize::MAX);
+
+        let drop_chk = DropCheck::new();
+        let ptr = Box::into_raw(Box::new(drop_chk.clone()));
+        let atom_ptr = atomic::AtomicPtr::new(ptr);
+
+        let guard = child.protect(&atom_ptr, atomic::Ordering::SeqCst);
+        assert_eq!(1, parent.record_count());
+
+        unsafe {
+            parent.retire(ptr, |p| {
+                drop(Box::from_raw(p));
+            })
+        };
+
+        // The Child does not reclaim the Ptrs retired in the Parent
+        child.reclaim();
+        parent.reclaim();
+        assert_eq!(0, drop_chk.drop_count());
+
+        drop(guard);
+        child.reclaim();
+        assert_eq!(0, drop_chk.drop_count());
+
+        parent.reclaim();
+        assert_eq!(1, drop_chk.drop_count());
+    }
+
+    #[test]
+    fn protect_array_reuses_record() {
+        let domain = Domain::new(10);
+
+        let table: Vec<_> = (0..8usize)
+            .map(|i| atomic::AtomicPtr::new(Box::into_raw(Box::new(i))))
+            .collect();
+
+        let mut guard = domain.protect_array(&table, 0, atomic::Ordering::SeqCst);
+        for i in 0..8 {
+            guard.protect_array(&table, i, atomic::Ordering::SeqCst);
+            assert_eq!(i, *guard);
+        }
+        assert_eq!(1, domain.record_count());
+
+        // Swapping an Entry is picked up by the next Lookup
+        let replaced = table[2].swap(Box::into_raw(Box::new(13)), atomic::Ordering::SeqCst);
+        drop(unsafe { Box::from_raw(replaced) });
+        guard.protect_array(&table, 2, atomic::Ordering::SeqCst);
+        assert_eq!(13, *guard);
+
+        drop(guard);
+        for entry in table {
+            drop(unsafe { Box::from_raw(entry.into_inner()) });
+        }
+    }
+
     #[test]
     #[ignore = "Hazard-Pointers are currently not working"]
     fn local_domain_protect() {
@@ -270,6 +768,71 @@ mod tests {
         assert_eq!(1, drop_chk.drop_count());
         assert_eq!(1, second_drop_chk.drop_count());
     }
+
+    #[test]
+    #[cfg(unix)]
+    #[cfg_attr(miri, ignore)]
+    fn fork_ignores_other_threads() {
+        let domain = Arc::new(Domain::new(10));
+        let ptr = Box::into_raw(Box::new(13usize));
+        let atom_ptr = Arc::new(atomic::AtomicPtr::new(ptr));
+
+        // Another Thread keeps protecting the Ptr while the Process forks
+        let (protected_tx, protected_rx) = std::sync::mpsc::channel();
+        let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+        let handle = {
+            let domain = domain.clone();
+            let atom_ptr = atom_ptr.clone();
+            std::thread::spawn(move || {
+                let guard = domain.protect(&atom_ptr, atomic::Ordering::SeqCst);
+                protected_tx.send(()).unwrap();
+                done_rx.recv().unwrap();
+                drop(guard);
+            })
+        };
+        protected_rx.recv().unwrap();
+
+        let guard = domain.protect(&atom_ptr, atomic::Ordering::SeqCst);
+        assert_eq!(
+            vec![(ptr as usize, 2)],
+            domain.iter_protected().collect::<Vec<_>>()
+        );
+
+        // # Safety:
+        // The Child only inspects the Domain and then exits right away,
+        // without running any Destructors
+        match unsafe { libc::fork() } {
+            0 => {
+                // Only the Protection of the forking Thread is still relevant
+                let protected: Vec<_> = domain.iter_protected().collect();
+                let code = if protected == vec![(ptr as usize, 1)] {
+                    0
+                } else {
+                    1
+                };
+                unsafe { libc::_exit(code) };
+            }
+            pid => {
+                assert!(pid > 0, "Forking the Process failed");
+
+                let mut status = 0;
+                assert_eq!(pid, unsafe { libc::waitpid(pid, &mut status, 0) });
+                assert!(libc::WIFEXITED(status));
+                assert_eq!(0, libc::WEXITSTATUS(status));
+            }
+        };
+
+        // The Parent is not affected by the Fork
+        assert_eq!(
+            vec![(ptr as usize, 2)],
+            domain.iter_protected().collect::<Vec<_>>()
+        );
+
+        drop(guard);
+        done_tx.send(()).unwrap();
+        handle.join().unwrap();
+        drop(unsafe { Box::from_raw(ptr) });
+    }
 }
 
 #[cfg(loom)]
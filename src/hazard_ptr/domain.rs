@@ -1,20 +1,25 @@
 mod global;
 
-use crate::sync::atomic;
+use crate::sync::{api, atomic};
 pub use global::DomainGlobal;
 use std::{fmt::Debug, sync::Arc};
 
 use crate::queues::mpsc::jiffy;
 
-use super::{record::Record, retire_node::RetireNode, Guard};
+use super::{record::Record, record_cache::RecordCache, retire_node::RetireNode, Guard};
 
 /// A Thread-Local instance to interact with a single Hazard-Pointer-Domain
 pub struct TLDomain {
     /// The Refernce to the Shared-Global State for the Hazard-Pointer-Domain
     global: Arc<DomainGlobal>,
 
-    record_sender: Arc<jiffy::Sender<*mut Record<()>>>,
-    record_receiver: jiffy::Receiver<*mut Record<()>>,
+    /// The Records released by Guards of this Thread, which are tried first
+    /// when acquiring a new Record. These may have been acquired by other
+    /// Threads in the mean time, so they still need to be acquired again
+    records: Arc<RecordCache>,
+    /// The Records released by Guards of this Thread, that were dropped on
+    /// another Thread
+    remote_records: jiffy::Receiver<*mut Record<()>>,
 
     /// The Threshold at which it should try to reclaim all Memory marked
     /// as retired
@@ -50,13 +55,13 @@ impl Drop for TLDomain {
 impl TLDomain {
     /// Creates a new Domain with the given shared Global and reclaim Threshold
     pub fn new(global: Arc<DomainGlobal>, reclaim_threshold: usize) -> Self {
-        let (rx, tx) = jiffy::queue();
+        let (records, remote_records) = RecordCache::new();
 
         Self {
             r_threshold: reclaim_threshold,
             global,
-            record_sender: Arc::new(tx),
-            record_receiver: rx,
+            records: Arc::new(records),
+            remote_records,
             r_list: Vec::new(),
         }
     }
@@ -95,13 +100,16 @@ impl TLDomain {
     fn scan(&mut self) {
         // TODO
         // Otherwise we got some Problems in loom which im not really sure about at the moment
+        #[cfg(loom)]
         return;
+
         let plist = self.global.get_protections();
 
         let tmplist = std::mem::take(&mut self.r_list);
 
         for node in tmplist {
-            if plist.contains(&node.const_ptr()) {
+            // The Protections are sorted, so every Lookup is only O(log P)
+            if plist.binary_search(&node.const_ptr()).is_ok() {
                 self.r_list.push(node);
             } else {
                 // # Safety
@@ -120,6 +128,9 @@ impl TLDomain {
     /// see the new Hazard-Pointer as well
     fn generate_new_record(&mut self) -> *mut Record<()> {
         let n_record = Record::boxed_empty();
+        // The Record is not yet visible to any other Thread, so this can
+        // never fail
+        n_record.try_acquire();
         let n_record_ptr = Box::into_raw(n_record);
 
         self.global.append_record(n_record_ptr);
@@ -131,12 +142,32 @@ impl TLDomain {
     /// anything and should not be used to try and access the Data inside it,
     /// which would cause a Null-Ptr dereference
     pub fn empty_guard<T>(&mut self) -> Guard<T> {
-        let record_ptr = match self.record_receiver.try_dequeue() {
-            Ok(r) => r,
-            _ => self.generate_new_record(),
-        };
+        let record_ptr = self.acquire_record();
+
+        Guard::new(std::ptr::null_mut(), record_ptr, self.records.clone())
+    }
 
-        Guard::new(std::ptr::null_mut(), record_ptr, self.record_sender.clone())
+    /// Acquires a free Record, preferring the ones previously released by
+    /// this Thread, then any released Record in the Domain and only if none
+    /// is available allocates a new one
+    fn acquire_record(&mut self) -> *mut Record<()> {
+        while let Some(record_ptr) = self.records.pop_local() {
+            let record = unsafe { &*record_ptr };
+            if record.try_acquire() {
+                return record_ptr;
+            }
+        }
+        while let Ok(record_ptr) = self.remote_records.try_dequeue() {
+            let record = unsafe { &*record_ptr };
+            if record.try_acquire() {
+                return record_ptr;
+            }
+        }
+
+        match self.global.acquire_record() {
+            Some(r) => r,
+            None => self.generate_new_record(),
+        }
     }
 
     /// Loads the most recent Ptr-Value from the given AtomicPtr, protects it
@@ -144,7 +175,7 @@ impl TLDomain {
     /// the underlying protected Data
     pub fn protect<T>(
         &mut self,
-        atom_ptr: &atomic::AtomicPtr<T>,
+        atom_ptr: &api::atomic::AtomicPtr<T>,
         load_order: atomic::Ordering,
     ) -> Guard<T> {
         let mut guard: Guard<T> = self.empty_guard();
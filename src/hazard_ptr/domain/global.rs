@@ -1,4 +1,4 @@
-use std::{collections::HashSet, fmt::Debug, sync::atomic};
+use std::{fmt::Debug, sync::atomic};
 
 use crate::hazard_ptr::Record;
 
@@ -23,10 +23,14 @@ impl DomainGlobal {
         Self { records }
     }
 
-    /// Checks all the current Hazard-Pointers and returns a Set of all
-    /// currently protected PTRs stored in them
-    pub fn get_protections(&self) -> HashSet<*const ()> {
-        let mut plist = HashSet::new();
+    /// Checks all the current Hazard-Pointers and returns all the currently
+    /// protected PTRs stored in them, sorted and without Duplicates, so that
+    /// they can be searched using a Binary-Search.
+    ///
+    /// The Records of Threads that did not survive a Fork of the Process are
+    /// skipped, as their Ptrs will never be accessed again
+    pub fn get_protections(&self) -> Vec<*const ()> {
+        let mut plist = Vec::new();
 
         let ptr = self.records.load(atomic::Ordering::SeqCst);
         if ptr.is_null() {
@@ -37,8 +41,8 @@ impl DomainGlobal {
 
         loop {
             let ptr_val = current.ptr.load(atomic::Ordering::SeqCst);
-            if !ptr_val.is_null() {
-                plist.insert(ptr_val as *const ());
+            if !ptr_val.is_null() && !current.is_stale() {
+                plist.push(ptr_val as *const ());
             }
 
             let next_ptr = current.next.load(atomic::Ordering::SeqCst);
@@ -49,9 +53,71 @@ impl DomainGlobal {
             current = unsafe { &*next_ptr };
         }
 
+        plist.sort_unstable();
+        plist.dedup();
         plist
     }
 
+    /// Checks all the current Hazard-Pointers and returns the Address of every
+    /// currently protected PTR together with the Number of Records protecting
+    /// it, sorted by the Address
+    pub fn protection_counts(&self) -> Vec<(usize, usize)> {
+        let mut plist: Vec<usize> = Vec::new();
+
+        let mut current_ptr = self.records.load(atomic::Ordering::SeqCst);
+        while !current_ptr.is_null() {
+            let current = unsafe { &*current_ptr };
+
+            let ptr_val = current.ptr.load(atomic::Ordering::SeqCst);
+            if !ptr_val.is_null() && !current.is_stale() {
+                plist.push(ptr_val as usize);
+            }
+
+            current_ptr = current.next.load(atomic::Ordering::SeqCst);
+        }
+        plist.sort_unstable();
+
+        let mut counts: Vec<(usize, usize)> = Vec::new();
+        for addr in plist {
+            match counts.last_mut() {
+                Some((last, count)) if *last == addr => *count += 1,
+                _ => counts.push((addr, 1)),
+            }
+        }
+        counts
+    }
+
+    /// Attempts to acquire any Record in the List that is currently not in
+    /// use, like Records that were released by other Threads
+    pub fn acquire_record(&self) -> Option<*mut Record<()>> {
+        let mut current_ptr = self.records.load(atomic::Ordering::SeqCst);
+
+        while !current_ptr.is_null() {
+            let current = unsafe { &*current_ptr };
+            if current.try_acquire() {
+                return Some(current_ptr);
+            }
+
+            current_ptr = current.next.load(atomic::Ordering::SeqCst);
+        }
+
+        None
+    }
+
+    /// Returns the total Number of Records in the List, no matter if they are
+    /// currently in use or not
+    pub fn record_count(&self) -> usize {
+        let mut count = 0;
+        let mut current_ptr = self.records.load(atomic::Ordering::SeqCst);
+
+        while !current_ptr.is_null() {
+            count += 1;
+            current_ptr = unsafe { &*current_ptr }.next.load(atomic::Ordering::SeqCst);
+        }
+
+        count
+    }
+
     /// This is used to add a new Record to the End of the Hazard-Pointer-List
     pub fn append_record(&self, n_record_ptr: *mut Record<()>) {
         let ptr = self.records.load(atomic::Ordering::SeqCst);
@@ -130,37 +196,72 @@ mod tests {
     fn append_load_hazards() {
         let global = DomainGlobal::new();
 
-        {
-            let expected = HashSet::new();
-            assert_eq!(expected, global.get_protections());
-        }
+        assert!(global.get_protections().is_empty());
 
         let record_ptr = Box::into_raw(Record::<u64>::boxed_empty());
         global.append_record(record_ptr as *mut Record<()>);
 
-        {
-            let expected = HashSet::new();
-            assert_eq!(expected, global.get_protections());
-        }
+        assert!(global.get_protections().is_empty());
 
         let record = unsafe { &*record_ptr };
         record
             .ptr
             .store(0x123 as *mut u64, atomic::Ordering::SeqCst);
 
-        {
-            let mut expected = HashSet::new();
-            expected.insert(0x123 as *const ());
-            assert_eq!(expected, global.get_protections());
-        }
+        assert_eq!(vec![0x123 as *const ()], global.get_protections());
 
         record
             .ptr
             .store(std::ptr::null_mut(), atomic::Ordering::SeqCst);
 
-        {
-            let expected = HashSet::new();
-            assert_eq!(expected, global.get_protections());
+        assert!(global.get_protections().is_empty());
+    }
+
+    #[test]
+    fn protections_sorted() {
+        let global = DomainGlobal::new();
+
+        for ptr in [0x300, 0x100, 0x200, 0x100] {
+            let record = Record::<()>::boxed_empty();
+            record.ptr.store(ptr as *mut (), atomic::Ordering::SeqCst);
+            global.append_record(Box::into_raw(record));
         }
+
+        assert_eq!(
+            vec![0x100 as *const (), 0x200 as *const (), 0x300 as *const ()],
+            global.get_protections()
+        );
+    }
+
+    #[test]
+    fn protection_counts() {
+        let global = DomainGlobal::new();
+        assert!(global.protection_counts().is_empty());
+
+        for ptr in [0x300, 0x100, 0, 0x100] {
+            let record = Record::<()>::boxed_empty();
+            record.ptr.store(ptr as *mut (), atomic::Ordering::SeqCst);
+            global.append_record(Box::into_raw(record));
+        }
+
+        assert_eq!(vec![(0x100, 2), (0x300, 1)], global.protection_counts());
+    }
+
+    #[test]
+    fn acquire_released_record() {
+        let global = DomainGlobal::new();
+        assert_eq!(None, global.acquire_record());
+
+        let record_ptr = Box::into_raw(Record::boxed_empty());
+        let record = unsafe { &*record_ptr };
+        assert!(record.try_acquire());
+        global.append_record(record_ptr);
+
+        assert_eq!(1, global.record_count());
+        assert_eq!(None, global.acquire_record());
+
+        record.release();
+        assert_eq!(Some(record_ptr), global.acquire_record());
+        assert_eq!(1, global.record_count());
     }
 }
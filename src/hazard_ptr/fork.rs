@@ -0,0 +1,76 @@
+//! Tracks the Forks of the current Process, so that the Hazard-Records of
+//! Threads that did not survive a Fork can be recognized as stale.
+//!
+//! After a `fork()`, only the forking Thread exists in the Child, but the
+//! Records acquired by all the other Threads are still marked as in use and
+//! would protect their Ptrs forever, so the retired Memory could never be
+//! reclaimed again. Every Record therefore stores the Thread that acquired
+//! it together with the Fork-Generation it was acquired in, and a Handler
+//! registered using `pthread_atfork` advances the Generation in the Child,
+//! which only needs a few atomic Stores and is therefore safe to run there.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::thread_data::key::{Key, ThreadKey};
+
+/// The Number of Forks the current Process is removed from the original one
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+/// The Thread that survived the last Fork, as it was the one calling it
+static SURVIVOR: AtomicU64 = AtomicU64::new(0);
+/// The Thread that is currently forking, which becomes the Survivor in the
+/// Child. This is stored before the Fork, as looking up the current Thread
+/// may allocate, which is not allowed in the Child
+static FORKING: AtomicU64 = AtomicU64::new(0);
+
+/// Registers the Fork-Handlers, if they have not been registered yet
+pub fn register() {
+    #[cfg(unix)]
+    {
+        static REGISTER: std::sync::Once = std::sync::Once::new();
+        REGISTER.call_once(|| {
+            // # Safety:
+            // The Handlers are plain Functions, that live for the entire
+            // Duration of the Process
+            let result = unsafe { libc::pthread_atfork(Some(prepare), None, Some(child)) };
+            assert_eq!(0, result, "Registering the Fork-Handlers failed");
+        });
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn prepare() {
+    FORKING.store(current(), Ordering::SeqCst);
+}
+
+#[cfg(unix)]
+extern "C" fn child() {
+    SURVIVOR.store(FORKING.load(Ordering::SeqCst), Ordering::SeqCst);
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+std::thread_local! {
+    /// The Key of the current Thread, which is cached, as looking it up is
+    /// comparatively expensive and needed for every acquired Record
+    static CURRENT: u64 = ThreadKey::current();
+}
+
+/// The Key of the current Thread, which is used to identify the Owner of a
+/// Record
+pub fn current() -> u64 {
+    // The Thread-Local may already be destroyed, if a Record is acquired
+    // while the Thread is exiting
+    CURRENT
+        .try_with(|current| *current)
+        .unwrap_or_else(|_| ThreadKey::current())
+}
+
+/// The current Fork-Generation of the Process
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+/// The Thread that called the last Fork and therefore is the only Thread
+/// from before the Fork that still exists
+pub fn survivor() -> u64 {
+    SURVIVOR.load(Ordering::SeqCst)
+}
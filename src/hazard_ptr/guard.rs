@@ -1,12 +1,10 @@
 use std::fmt::Debug;
 
-use crate::sync::atomic;
+use crate::sync::{api, atomic};
 use std::ops::Deref;
 use std::sync::Arc;
 
-use crate::queues::mpsc::jiffy;
-
-use super::record::Record;
+use super::{record::Record, record_cache::RecordCache};
 
 /// A Guard protects a single Memory address and provides secure access to
 /// it, as long as the Guard is not dropped
@@ -15,27 +13,37 @@ pub struct Guard<T> {
     inner: *mut T,
     /// A Ptr to the actual Hazard-Record that protects the underlying Data
     record: *mut Record<()>,
-    /// The Queue-Sender on which to return the Hazard-Record once the Guard
-    /// is dropped to have a simpler schema for reusing Hazard-Pointers locally
-    record_returner: Arc<jiffy::Sender<*mut Record<()>>>,
+    /// The Cache of the Thread that acquired the Record, to which it is
+    /// returned once the Guard is dropped, so that the Thread reuses it first
+    record_returner: Arc<RecordCache>,
 }
 
 impl<T> Debug for Guard<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Guard ()")
+        write!(f, "Guard ({:p})", self.inner)
+    }
+}
+
+impl<T> PartialEq<*mut T> for Guard<T> {
+    fn eq(&self, other: &*mut T) -> bool {
+        self.inner == *other
+    }
+}
+impl<T> PartialEq<*const T> for Guard<T> {
+    fn eq(&self, other: &*const T) -> bool {
+        std::ptr::eq(self.inner, *other)
     }
 }
 
 impl<T> Drop for Guard<T> {
     fn drop(&mut self) {
         let record = unsafe { &*self.record };
-        record.reset();
+        record.release();
 
-        // TODO
-        // We can "savely" ignore this Result because even if we detect a
-        // failure in this case, there is nothing we can really do about it...
-        // I think?
-        let _ = self.record_returner.enqueue(self.record);
+        // Returning the Record to the local Thread is only a Hint for it to
+        // be reused first, as the Record can now be acquired by any Thread
+        // anyway
+        self.record_returner.put(self.record);
     }
 }
 
@@ -49,16 +57,13 @@ impl<T> Deref for Guard<T> {
         // be valid and the Data behind the PTR will also still be valid as
         // the Guard and it's Hazard-Pointer protect it and therefore prevent
         // it from being deallocated/reclaimed, while the Guard still exists
+        crate::poison::check(self.inner);
         unsafe { &*self.inner }
     }
 }
 
 impl<T> Guard<T> {
-    pub(crate) fn new(
-        ptr: *mut T,
-        record: *mut Record<()>,
-        returner: Arc<jiffy::Sender<*mut Record<()>>>,
-    ) -> Self {
+    pub(crate) fn new(ptr: *mut T, record: *mut Record<()>, returner: Arc<RecordCache>) -> Self {
         Self {
             inner: ptr,
             record,
@@ -66,11 +71,102 @@ impl<T> Guard<T> {
         }
     }
 
+    /// Marks the Record of the Guard as detached from the current Thread, as
+    /// the Guard may be moved to other Threads
+    pub(crate) fn detach(&self) {
+        let record = unsafe { &*self.record };
+        record.detach();
+    }
+
     /// Gets the underlying PTR to the Data protected by the Guard
+    ///
+    /// # Stability
+    /// The returned Ptr stays the same for as long as the Guard is not
+    /// updated using [`protect`](Self::protect) and the Data behind it will
+    /// not be reclaimed while the Guard exists
     pub fn raw(&self) -> *const T {
         self.inner as *const T
     }
 
+    /// Gets the underlying mutable PTR to the Data protected by the Guard,
+    /// with the same Guarantees as [`raw`](Self::raw).
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::hazard_ptr;
+    /// # use std::sync::atomic;
+    /// let domain = hazard_ptr::Domain::new(10);
+    ///
+    /// let ptr = Box::into_raw(Box::new(13));
+    /// let atom_ptr = atomic::AtomicPtr::new(ptr);
+    ///
+    /// let guard = domain.protect(&atom_ptr, atomic::Ordering::SeqCst);
+    /// assert_eq!(ptr, guard.as_raw());
+    /// assert!(guard == ptr);
+    ///
+    /// # drop(guard);
+    /// # drop(unsafe { Box::from_raw(ptr) });
+    /// ```
+    pub fn as_raw(&self) -> *mut T {
+        self.inner
+    }
+
+    /// Checks if the Guard currently protects a Null-Ptr, in which case it
+    /// must not be dereferenced
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::hazard_ptr;
+    /// let domain = hazard_ptr::Domain::new(10);
+    ///
+    /// let guard = domain.empty_guard::<usize>();
+    /// assert!(guard.is_null());
+    /// ```
+    pub fn is_null(&self) -> bool {
+        self.inner.is_null()
+    }
+
+    /// Takes ownership of the protected Data, returning `None` if the Guard
+    /// currently protects a Null-Ptr.
+    ///
+    /// The Hazard-Pointer used by the Guard is released again, like when
+    /// the Guard is dropped.
+    ///
+    /// # Safety
+    /// The Caller must guarantee that:
+    /// * the protected Ptr was allocated using a [`Box`]
+    /// * it has exclusive access to the Data, meaning that the Ptr is no
+    ///   longer reachable by any other Thread and is not protected by any
+    ///   other Guard
+    /// * the Ptr has not been and will not be retired
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::hazard_ptr;
+    /// # use std::sync::atomic;
+    /// let domain = hazard_ptr::Domain::new(10);
+    ///
+    /// let ptr = Box::into_raw(Box::new(13));
+    /// let atom_ptr = atomic::AtomicPtr::new(ptr);
+    ///
+    /// let guard = domain.protect(&atom_ptr, atomic::Ordering::SeqCst);
+    /// // Unlink the Ptr, so no one else can access it anymore
+    /// atom_ptr.store(std::ptr::null_mut(), atomic::Ordering::SeqCst);
+    ///
+    /// let owned = unsafe { guard.try_into_inner() };
+    /// assert_eq!(Some(Box::new(13)), owned);
+    /// ```
+    pub unsafe fn try_into_inner(self) -> Option<Box<T>> {
+        if self.inner.is_null() {
+            return None;
+        }
+
+        // # Safety:
+        // The Caller guarantees that the Ptr was allocated using a Box and
+        // that we have exclusive access to it
+        Some(unsafe { Box::from_raw(self.inner) })
+    }
+
     /// Loads the most recent Ptr-Value from the given AtomicPtr and updates
     /// the current Guard to now protect this new Ptr.
     ///
@@ -83,23 +179,27 @@ impl<T> Guard<T> {
     /// This is especially useful when iterating a Datastruture, as you often
     /// only have one Node you are currently processing and then move on
     /// to another one.
-    pub fn protect(&mut self, atom_ptr: &atomic::AtomicPtr<T>, load_order: atomic::Ordering) {
+    pub fn protect(&mut self, atom_ptr: &api::atomic::AtomicPtr<T>, load_order: atomic::Ordering) {
         let record = unsafe { &*self.record };
-        let mut protect_ptr = atom_ptr.load(load_order);
-        loop {
-            record
-                .ptr
-                .store(protect_ptr as *mut (), atomic::Ordering::SeqCst);
-
-            let n_ptr = atom_ptr.load(load_order);
-            if n_ptr == protect_ptr {
-                break;
-            }
-
-            protect_ptr = n_ptr;
-        }
+        self.inner = record.protect(atom_ptr, load_order);
+    }
 
-        self.inner = protect_ptr;
+    /// Loads the most recent Ptr-Value from the Entry at `index` in the given
+    /// Table of AtomicPtrs and updates the current Guard to now protect it.
+    ///
+    /// This works just like [`protect`](Self::protect) and is useful for
+    /// looking up different Entries of a Table, while reusing the same
+    /// Hazard-Pointer for all of them.
+    ///
+    /// # Panics
+    /// If the `index` is out of Bounds for the Table
+    pub fn protect_array(
+        &mut self,
+        table: &[api::atomic::AtomicPtr<T>],
+        index: usize,
+        load_order: atomic::Ordering,
+    ) {
+        self.protect(&table[index], load_order);
     }
 
     /// Converts the Guard into a Guard for a differnt underlying Type
@@ -0,0 +1,188 @@
+use std::{fmt::Debug, ops::Deref, sync::Arc};
+
+use crate::sync::{api, atomic};
+
+use super::{domain::DomainGlobal, Guard};
+
+/// A Guard that keeps the Hazard-Records of its Domain alive by itself, so
+/// it is not tied to the Domain it was obtained from.
+///
+/// A normal [`Guard`] must not outlive its Domain and can not be moved to
+/// other Threads, which makes it awkward to store it for longer, like in
+/// Datastructure-Nodes or in Tasks that need to be `'static` and `Send`. An
+/// OwnedGuard instead holds a Reference to the shared Records of the Domain,
+/// so they stay valid even if all the Handles to the Domain are dropped, and
+/// it can be sent to any Thread as long as the protected Data is `Sync`.
+/// Its Record is released and returned to the Domain, once it is dropped.
+///
+/// # Example
+/// ```rust
+/// # use nolock::hazard_ptr;
+/// # use std::sync::{atomic, Arc};
+/// let domain = hazard_ptr::Domain::new(10);
+///
+/// let ptr = Box::into_raw(Box::new(13));
+/// let atom_ptr = Arc::new(atomic::AtomicPtr::new(ptr));
+///
+/// let guard = domain.protect_owned(&atom_ptr, atomic::Ordering::SeqCst);
+/// // The Guard can outlive the Domain and be moved to another Thread
+/// drop(domain);
+/// let value = std::thread::spawn(move || *guard).join().unwrap();
+/// assert_eq!(13, value);
+///
+/// # drop(unsafe { Box::from_raw(ptr) });
+/// ```
+pub struct OwnedGuard<T> {
+    /// The Guard is declared first, so that its Record is released before the
+    /// Records of the Domain may be deallocated
+    guard: Guard<T>,
+    _global: Arc<DomainGlobal>,
+}
+
+// Safety:
+// Releasing the Record and returning it to the Cache of the Thread that
+// acquired it can be done from any Thread and the Record is detached, so its
+// Protection is not tied to the acquiring Thread. The protected Data is only
+// ever accessed through shared References, so it needs to be Sync
+unsafe impl<T> Send for OwnedGuard<T> where T: Sync {}
+unsafe impl<T> Sync for OwnedGuard<T> where T: Sync {}
+
+impl<T> Debug for OwnedGuard<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OwnedGuard ({:p})", self.guard.as_raw())
+    }
+}
+
+impl<T> PartialEq<*mut T> for OwnedGuard<T> {
+    fn eq(&self, other: &*mut T) -> bool {
+        self.guard == *other
+    }
+}
+impl<T> PartialEq<*const T> for OwnedGuard<T> {
+    fn eq(&self, other: &*const T) -> bool {
+        self.guard == *other
+    }
+}
+
+impl<T> Deref for OwnedGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T> OwnedGuard<T> {
+    pub(crate) fn new(guard: Guard<T>, global: Arc<DomainGlobal>) -> Self {
+        guard.detach();
+
+        Self {
+            guard,
+            _global: global,
+        }
+    }
+
+    /// Gets the underlying PTR to the Data protected by the Guard, see
+    /// [`Guard::raw`]
+    pub fn raw(&self) -> *const T {
+        self.guard.raw()
+    }
+
+    /// Gets the underlying mutable PTR to the Data protected by the Guard,
+    /// see [`Guard::as_raw`]
+    pub fn as_raw(&self) -> *mut T {
+        self.guard.as_raw()
+    }
+
+    /// Checks if the Guard currently protects a Null-Ptr, in which case it
+    /// must not be dereferenced
+    pub fn is_null(&self) -> bool {
+        self.guard.is_null()
+    }
+
+    /// Loads the most recent Ptr-Value from the given AtomicPtr and updates
+    /// the Guard to now protect this new Ptr, see [`Guard::protect`]
+    pub fn protect(&mut self, atom_ptr: &api::atomic::AtomicPtr<T>, load_order: atomic::Ordering) {
+        self.guard.protect(atom_ptr, load_order);
+    }
+
+    /// Loads the most recent Ptr-Value from the Entry at `index` in the given
+    /// Table of AtomicPtrs and updates the Guard to now protect it, see
+    /// [`Guard::protect_array`]
+    ///
+    /// # Panics
+    /// If the `index` is out of Bounds for the Table
+    pub fn protect_array(
+        &mut self,
+        table: &[api::atomic::AtomicPtr<T>],
+        index: usize,
+        load_order: atomic::Ordering,
+    ) {
+        self.guard.protect_array(table, index, load_order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Domain;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+
+    #[test]
+    fn outlives_domain() {
+        let domain = Domain::new(0);
+
+        let ptr = Box::into_raw(Box::new(13));
+        let atom_ptr = AtomicPtr::new(ptr);
+
+        let guard = domain.protect_owned(&atom_ptr, Ordering::SeqCst);
+        atom_ptr.store(std::ptr::null_mut(), Ordering::SeqCst);
+        unsafe { domain.retire(ptr, |p| drop(Box::from_raw(p))) };
+        domain.reclaim();
+        drop(domain);
+
+        assert_eq!(13, *guard);
+        assert!(guard == ptr);
+    }
+
+    #[test]
+    fn record_returned_on_drop() {
+        let domain = Domain::new(10);
+
+        let mut guard = domain.owned_guard::<usize>();
+        assert!(guard.is_null());
+
+        let ptr = Box::into_raw(Box::new(13));
+        let atom_ptr = AtomicPtr::new(ptr);
+        guard.protect(&atom_ptr, Ordering::SeqCst);
+        assert_eq!(
+            vec![(ptr as usize, 1)],
+            domain.iter_protected().collect::<Vec<_>>()
+        );
+
+        drop(guard);
+        assert_eq!(0, domain.iter_protected().count());
+
+        // The released Record is reused
+        let guard = domain.empty_guard::<usize>();
+        assert_eq!(1, domain.record_count());
+        drop(guard);
+
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn dropped_on_other_thread() {
+        let domain = Domain::new(10);
+
+        let ptr = Box::into_raw(Box::new(13));
+        let atom_ptr = AtomicPtr::new(ptr);
+
+        let guard = domain.protect_owned(&atom_ptr, Ordering::SeqCst);
+        let value = std::thread::spawn(move || *guard).join().unwrap();
+        assert_eq!(13, value);
+        assert_eq!(0, domain.iter_protected().count());
+
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt::Debug;
+
+use crate::sync::{api, atomic};
+
+use super::Guard;
+
+/// Two Guards that are used together to traverse a linked Datastructure,
+/// where the Node that was visited last (`prev`) has to stay protected while
+/// moving on to the next Node (`curr`).
+///
+/// Advancing the Pair swaps the Roles of its two Hazard-Records, so the
+/// current Node becomes the previous one without being protected again and
+/// the Record of the old previous Node is reused to protect the next Node.
+/// This means that a whole Traversal only ever uses the two Records acquired
+/// when the Pair was created.
+///
+/// # Example
+/// ```rust
+/// # use nolock::hazard_ptr;
+/// # use std::sync::atomic;
+/// struct Node {
+///     value: usize,
+///     next: atomic::AtomicPtr<Node>,
+/// }
+///
+/// let domain = hazard_ptr::Domain::new(10);
+///
+/// // Build the List 0 -> 1 -> 2
+/// let head = atomic::AtomicPtr::new(std::ptr::null_mut());
+/// for value in (0..3).rev() {
+///     let next = head.load(atomic::Ordering::SeqCst);
+///     let node = Box::into_raw(Box::new(Node { value, next: atomic::AtomicPtr::new(next) }));
+///     head.store(node, atomic::Ordering::SeqCst);
+/// }
+///
+/// let mut pair = domain.guard_pair::<Node>();
+/// pair.advance(&head, atomic::Ordering::SeqCst);
+/// while !pair.curr().is_null() {
+///     let next = &pair.curr().next as *const atomic::AtomicPtr<Node>;
+///     // # Safety: The Node is still protected as `prev` after advancing
+///     pair.advance(unsafe { &*next }, atomic::Ordering::SeqCst);
+///     assert!(pair.curr().is_null() || pair.curr().value == pair.prev().value + 1);
+/// }
+/// assert_eq!(2, pair.prev().value);
+/// assert_eq!(2, domain.record_count());
+///
+/// # drop(pair);
+/// # let mut current = head.load(atomic::Ordering::SeqCst);
+/// # while !current.is_null() {
+/// #     let node = unsafe { Box::from_raw(current) };
+/// #     current = node.next.load(atomic::Ordering::SeqCst);
+/// # }
+/// ```
+pub struct GuardPair<T> {
+    prev: Guard<T>,
+    curr: Guard<T>,
+}
+
+impl<T> Debug for GuardPair<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "GuardPair (prev: {:p}, curr: {:p})",
+            self.prev.raw(),
+            self.curr.raw()
+        )
+    }
+}
+
+impl<T> GuardPair<T> {
+    pub(crate) fn new(prev: Guard<T>, curr: Guard<T>) -> Self {
+        Self { prev, curr }
+    }
+
+    /// The Guard protecting the previously visited Node
+    pub fn prev(&self) -> &Guard<T> {
+        &self.prev
+    }
+
+    /// The Guard protecting the currently visited Node
+    pub fn curr(&self) -> &Guard<T> {
+        &self.curr
+    }
+
+    /// Loads the most recent Ptr-Value from the given AtomicPtr and protects
+    /// it as the previous Node, see [`Guard::protect`]
+    pub fn protect_prev(
+        &mut self,
+        atom_ptr: &api::atomic::AtomicPtr<T>,
+        load_order: atomic::Ordering,
+    ) {
+        self.prev.protect(atom_ptr, load_order);
+    }
+
+    /// Loads the most recent Ptr-Value from the given AtomicPtr and protects
+    /// it as the current Node, see [`Guard::protect`]
+    pub fn protect_curr(
+        &mut self,
+        atom_ptr: &api::atomic::AtomicPtr<T>,
+        load_order: atomic::Ordering,
+    ) {
+        self.curr.protect(atom_ptr, load_order);
+    }
+
+    /// Swaps the Roles of the two Guards, so the current Node becomes the
+    /// previous one and the other way around. This only swaps the Guards
+    /// themselves and does not touch their Hazard-Records.
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.prev, &mut self.curr);
+    }
+
+    /// Moves the Pair forward by one Node, the current Node becomes the
+    /// previous one and the most recent Ptr-Value loaded from the given
+    /// AtomicPtr is protected as the new current Node.
+    ///
+    /// The given AtomicPtr is usually stored in the current Node, which is
+    /// fine as that Node stays protected as the previous Node.
+    ///
+    /// # Protections
+    /// The Protection of the old previous Node is given up, so it must not be
+    /// accessed anymore through any Ptr obtained from it
+    pub fn advance(&mut self, atom_ptr: &api::atomic::AtomicPtr<T>, load_order: atomic::Ordering) {
+        self.swap();
+        self.curr.protect(atom_ptr, load_order);
+    }
+
+    /// Splits the Pair into its `(prev, curr)` Guards
+    pub fn into_guards(self) -> (Guard<T>, Guard<T>) {
+        (self.prev, self.curr)
+    }
+}
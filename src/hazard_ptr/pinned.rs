@@ -0,0 +1,267 @@
+use std::{cell::Cell, fmt::Debug, ops::Deref, sync::Arc};
+
+use crate::sync::{api, atomic};
+
+use super::{record::Record, DomainGlobal};
+
+/// The Number of Pinned-Records every Thread has for a single Domain, which
+/// is the maximum Number of [`PinnedGuard`]s a Thread can hold at the same
+/// Time for that Domain
+pub const PINNED_GUARDS: usize = 4;
+
+/// The Records that are permanently owned by a single Thread and handed out
+/// to its [`PinnedGuard`]s.
+///
+/// The Records are only allocated the first Time they are needed and then
+/// stay acquired for as long as the Thread-Local Data exists, so no other
+/// Thread can take them from the Global List of Records.
+pub(crate) struct PinnedRecords {
+    global: Arc<DomainGlobal>,
+    records: [Cell<*mut Record<()>>; PINNED_GUARDS],
+    /// A Bitmask of the Records that are currently used by a Guard
+    used: Cell<u8>,
+}
+
+// The Records are only ever accessed by the Thread owning them, as the
+// PinnedGuards borrowing them can not be send to other Threads
+unsafe impl Send for PinnedRecords {}
+
+impl PinnedRecords {
+    pub fn new(global: Arc<DomainGlobal>) -> Self {
+        Self {
+            global,
+            records: [(); PINNED_GUARDS].map(|_| Cell::new(std::ptr::null_mut())),
+            used: Cell::new(0),
+        }
+    }
+
+    /// Claims a free Record and returns its Index, returns `None` if all the
+    /// Records are currently in use
+    fn claim(&self) -> Option<(usize, &Record<()>)> {
+        let used = self.used.get();
+        let index = (!used).trailing_zeros() as usize;
+        if index >= PINNED_GUARDS {
+            return None;
+        }
+        self.used.set(used | (1 << index));
+
+        let mut record_ptr = self.records[index].get();
+        if record_ptr.is_null() {
+            let n_record = Record::boxed_empty();
+            // The Record is not yet visible to any other Thread, so this can
+            // never fail and as it is never released again, no other Thread
+            // will ever acquire it
+            n_record.try_acquire();
+            record_ptr = Box::into_raw(n_record);
+
+            self.global.append_record(record_ptr);
+            self.records[index].set(record_ptr);
+        }
+
+        Some((index, unsafe { &*record_ptr }))
+    }
+
+    /// Claims a free Record for a bounded Scope, which is unclaimed again once
+    /// the returned ScopedRecord is dropped, returns `None` if all the
+    /// Records are currently in use
+    pub fn claim_scoped(&self) -> Option<ScopedRecord<'_>> {
+        let (index, record) = self.claim()?;
+        Some(ScopedRecord {
+            index,
+            record,
+            records: self,
+        })
+    }
+
+    /// Resets the Record at the given Index and marks it as free again
+    fn unclaim(&self, index: usize) {
+        let record = unsafe { &*self.records[index].get() };
+        record.reset();
+
+        self.used.set(self.used.get() & !(1 << index));
+    }
+}
+
+impl Drop for PinnedRecords {
+    fn drop(&mut self) {
+        // The Records themselves are owned by the Global List, we only make
+        // them available again for other Threads
+        for record in self.records.iter() {
+            let record_ptr = record.get();
+            if !record_ptr.is_null() {
+                unsafe { &*record_ptr }.release();
+            }
+        }
+    }
+}
+
+/// A Pinned-Record claimed for a bounded Scope, which is unclaimed again once
+/// it is dropped, even if the Scope panics
+pub(crate) struct ScopedRecord<'a> {
+    index: usize,
+    record: &'a Record<()>,
+    records: &'a PinnedRecords,
+}
+
+impl<'a> Deref for ScopedRecord<'a> {
+    type Target = Record<()>;
+
+    fn deref(&self) -> &Self::Target {
+        self.record
+    }
+}
+
+impl<'a> Drop for ScopedRecord<'a> {
+    fn drop(&mut self) {
+        self.records.unclaim(self.index);
+    }
+}
+
+/// A Guard that uses one of the Pinned-Records of the current Thread, instead
+/// of acquiring a Record from the Domain like a normal [`Guard`](super::Guard).
+///
+/// Obtaining and dropping a PinnedGuard only has to update a Thread-Local
+/// Bitmask, which makes it well suited for short-lived Protections in a
+/// Hot-Path, as it never needs to allocate a new Record or go through the
+/// shared Queue of released Records.
+///
+/// # Limits
+/// Every Thread can only hold up to [`PINNED_GUARDS`] PinnedGuards for the
+/// same Domain at the same Time and a PinnedGuard can not be send to another
+/// Thread.
+pub struct PinnedGuard<'a, T> {
+    inner: *mut T,
+    index: usize,
+    record: &'a Record<()>,
+    records: &'a PinnedRecords,
+}
+
+impl<'a, T> Debug for PinnedGuard<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PinnedGuard ({:p})", self.inner)
+    }
+}
+
+impl<'a, T> Drop for PinnedGuard<'a, T> {
+    fn drop(&mut self) {
+        self.records.unclaim(self.index);
+    }
+}
+
+impl<'a, T> Deref for PinnedGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // # Safety:
+        //
+        // Same as for the normal Guard, the Data can not be reclaimed while
+        // the Record of the Guard protects it
+        crate::poison::check(self.inner);
+        unsafe { &*self.inner }
+    }
+}
+
+impl<'a, T> PinnedGuard<'a, T> {
+    /// Attempts to create a new empty Guard using one of the given Records
+    pub(crate) fn new(records: &'a PinnedRecords) -> Option<Self> {
+        let (index, record) = records.claim()?;
+
+        Some(Self {
+            inner: std::ptr::null_mut(),
+            index,
+            record,
+            records,
+        })
+    }
+
+    /// Gets the underlying PTR to the Data protected by the Guard
+    pub fn raw(&self) -> *const T {
+        self.inner as *const T
+    }
+
+    /// Checks if the Guard currently protects a Null-Ptr, in which case it
+    /// must not be dereferenced
+    pub fn is_null(&self) -> bool {
+        self.inner.is_null()
+    }
+
+    /// Loads the most recent Ptr-Value from the given AtomicPtr and updates
+    /// the current Guard to now protect this new Ptr, like
+    /// [`Guard::protect`](super::Guard::protect)
+    pub fn protect(&mut self, atom_ptr: &api::atomic::AtomicPtr<T>, load_order: atomic::Ordering) {
+        self.inner = self.record.protect(atom_ptr, load_order);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claim_all() {
+        let global = Arc::new(DomainGlobal::new());
+        let records = PinnedRecords::new(global.clone());
+
+        let guards: Vec<PinnedGuard<'_, usize>> = (0..PINNED_GUARDS)
+            .map(|_| PinnedGuard::new(&records).unwrap())
+            .collect();
+        assert!(PinnedGuard::<usize>::new(&records).is_none());
+        assert_eq!(PINNED_GUARDS, global.record_count());
+
+        drop(guards);
+        let guard = PinnedGuard::<usize>::new(&records);
+        assert!(guard.is_some());
+        assert_eq!(PINNED_GUARDS, global.record_count());
+    }
+
+    #[test]
+    fn records_stay_acquired() {
+        let global = Arc::new(DomainGlobal::new());
+        let records = PinnedRecords::new(global.clone());
+
+        drop(PinnedGuard::<usize>::new(&records).unwrap());
+        assert_eq!(None, global.acquire_record());
+
+        drop(records);
+        assert!(global.acquire_record().is_some());
+    }
+
+    #[test]
+    fn scoped_record_unclaims() {
+        let global = Arc::new(DomainGlobal::new());
+        let records = PinnedRecords::new(global.clone());
+
+        let ptr = Box::into_raw(Box::new(13usize));
+        let atom_ptr = api::atomic::AtomicPtr::new(ptr);
+
+        let scoped = records.claim_scoped().unwrap();
+        scoped.protect(&atom_ptr, atomic::Ordering::SeqCst);
+        assert_eq!(vec![ptr as *const ()], global.get_protections());
+        assert_eq!(1, records.used.get().count_ones());
+
+        drop(scoped);
+        assert!(global.get_protections().is_empty());
+        assert_eq!(0, records.used.get());
+
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+
+    #[test]
+    fn protect_resets() {
+        let global = Arc::new(DomainGlobal::new());
+        let records = PinnedRecords::new(global.clone());
+
+        let ptr = Box::into_raw(Box::new(13usize));
+        let atom_ptr = api::atomic::AtomicPtr::new(ptr);
+
+        let mut guard: PinnedGuard<'_, usize> = PinnedGuard::new(&records).unwrap();
+        guard.protect(&atom_ptr, atomic::Ordering::SeqCst);
+        assert_eq!(13, *guard);
+        assert_eq!(vec![ptr as *const ()], global.get_protections());
+
+        drop(guard);
+        assert!(global.get_protections().is_empty());
+
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+}
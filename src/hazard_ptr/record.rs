@@ -1,6 +1,27 @@
-use crate::sync::atomic;
+use crate::sync::{api, atomic};
 use std::fmt::Debug;
 
+use super::fork;
+
+/// The State of a Record that is not in use
+const FREE: u64 = 0;
+/// Set in the State of every Record that is currently in use
+const ACTIVE: u64 = 1 << 63;
+/// Set in the State of a Record that is used by a Guard, which may be moved
+/// to other Threads, so the Record is never owned by a single Thread
+const DETACHED: u64 = 1 << 62;
+/// The Number of Bits in the State that store the Thread owning the Record
+const OWNER_BITS: u32 = 40;
+const OWNER_MASK: u64 = (1 << OWNER_BITS) - 1;
+/// The Bits in the State that store the Fork-Generation the Record was
+/// acquired in
+const GENERATION_MASK: u64 = !(ACTIVE | DETACHED | OWNER_MASK);
+
+/// The State of a Record acquired by the current Thread
+fn owned_state() -> u64 {
+    ACTIVE | ((fork::generation() << OWNER_BITS) & GENERATION_MASK) | (fork::current() & OWNER_MASK)
+}
+
 /// A single Record in the List of Hazard-Pointer-Records
 pub struct Record<T> {
     /// The underlying Data-Ptr, if the Hazard-Pointer is currently
@@ -8,6 +29,10 @@ pub struct Record<T> {
     pub ptr: atomic::AtomicPtr<T>,
     /// The Pointer to the next element in the Linked-List
     pub next: atomic::AtomicPtr<Record<T>>,
+    /// Whether the Record is currently owned by a Guard or is free to be
+    /// acquired by any Thread, together with the Thread owning it and the
+    /// Fork-Generation it was acquired in
+    state: atomic::AtomicU64,
 }
 
 impl<T> Record<T> {
@@ -17,9 +42,50 @@ impl<T> Record<T> {
         Box::new(Self {
             ptr: atomic::AtomicPtr::new(std::ptr::null_mut()),
             next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+            state: atomic::AtomicU64::new(FREE),
         })
     }
 
+    /// Attempts to acquire the Record for exclusive use, returns `false` if
+    /// the Record is already in use by someone else
+    pub fn try_acquire(&self) -> bool {
+        self.state
+            .compare_exchange(
+                FREE,
+                owned_state(),
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::Relaxed,
+            )
+            .is_ok()
+    }
+
+    /// Marks the acquired Record as detached from the Thread that acquired
+    /// it, because its Guard may be moved to other Threads, which means that
+    /// it is never considered stale after a Fork
+    pub fn detach(&self) {
+        self.state.fetch_or(DETACHED, atomic::Ordering::SeqCst);
+    }
+
+    /// Checks if the Record is owned by a Thread that does not exist anymore,
+    /// because the Record was acquired before the Process was forked by
+    /// another Thread, in which case its Ptr is not actually protected
+    pub fn is_stale(&self) -> bool {
+        let state = self.state.load(atomic::Ordering::SeqCst);
+        if state & ACTIVE == 0 || state & DETACHED != 0 {
+            return false;
+        }
+
+        let generation = (fork::generation() << OWNER_BITS) & GENERATION_MASK;
+        state & GENERATION_MASK != generation && state & OWNER_MASK != fork::survivor() & OWNER_MASK
+    }
+
+    /// Resets the Record and marks it as no longer being in use, so that it
+    /// can be acquired again by any Thread
+    pub fn release(&self) {
+        self.reset();
+        self.state.store(FREE, atomic::Ordering::Release);
+    }
+
     /// Attempts to load the next Element in the Linked-List of Records,
     /// returns None if the Next-Ptr was Null at the Time of reading it,
     /// which might have changed in the mean time
@@ -41,6 +107,30 @@ impl<T> Record<T> {
     }
 }
 
+impl Record<()> {
+    /// Loads the most recent Ptr-Value from the given AtomicPtr and stores it
+    /// in this Record, until the stored Value is still the current one, at
+    /// which point the returned Ptr is protected by this Record
+    pub fn protect<T>(
+        &self,
+        atom_ptr: &api::atomic::AtomicPtr<T>,
+        load_order: atomic::Ordering,
+    ) -> *mut T {
+        let mut protect_ptr = atom_ptr.load(load_order);
+        loop {
+            self.ptr
+                .store(protect_ptr as *mut (), atomic::Ordering::SeqCst);
+
+            let n_ptr = atom_ptr.load(load_order);
+            if n_ptr == protect_ptr {
+                return protect_ptr;
+            }
+
+            protect_ptr = n_ptr;
+        }
+    }
+}
+
 impl<T> Debug for Record<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let ptr = self.ptr.load(atomic::Ordering::SeqCst);
@@ -53,6 +143,28 @@ impl<T> Debug for Record<T> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn acquire_release() {
+        let record = Record::<u32>::boxed_empty();
+
+        assert!(record.try_acquire());
+        assert!(!record.try_acquire());
+        // The Record was acquired in the current Process
+        assert!(!record.is_stale());
+        record.detach();
+        assert!(!record.is_stale());
+
+        record
+            .ptr
+            .store(0x123 as *mut u32, atomic::Ordering::SeqCst);
+        record.release();
+
+        assert!(record.ptr.load(atomic::Ordering::SeqCst).is_null());
+        assert!(record.try_acquire());
+        // Releasing the Record also clears the Detached-Flag
+        assert_eq!(owned_state(), record.state.load(atomic::Ordering::SeqCst));
+    }
+
     #[test]
     fn store_load_next() {
         let record = Record::<u32>::boxed_empty();
@@ -0,0 +1,122 @@
+use std::{cell::RefCell, fmt::Debug, thread::ThreadId};
+
+use crate::queues::mpsc::jiffy;
+
+use super::record::Record;
+
+std::thread_local! {
+    /// The Id of the current Thread, which is cached to avoid going through
+    /// `std::thread::current`, which clones an Arc every Time
+    static THREAD_ID: ThreadId = std::thread::current().id();
+}
+
+/// The Records released by the Guards of a single Thread, which are reused
+/// first when the Thread acquires a new Record.
+///
+/// Guards that are dropped on their owning Thread simply push their Record
+/// onto a local Vec, without any atomic Operations. Only Guards that are
+/// dropped on another Thread fall back to the Queue, which is drained by the
+/// owning Thread once the local Records run out.
+pub(crate) struct RecordCache {
+    owner: ThreadId,
+    local: RefCell<Vec<*mut Record<()>>>,
+    remote: jiffy::Sender<*mut Record<()>>,
+}
+
+// Safety:
+// The local Records are only ever accessed by the owning Thread, every other
+// Thread only uses the Queue, which is safe to use from any Thread
+unsafe impl Send for RecordCache {}
+unsafe impl Sync for RecordCache {}
+
+impl Debug for RecordCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RecordCache ()")
+    }
+}
+
+impl RecordCache {
+    /// Creates a new Cache owned by the current Thread, together with the
+    /// Receiver for the Records returned by other Threads
+    pub fn new() -> (Self, jiffy::Receiver<*mut Record<()>>) {
+        let (rx, tx) = jiffy::queue();
+
+        (
+            Self {
+                owner: std::thread::current().id(),
+                local: RefCell::new(Vec::new()),
+                remote: tx,
+            },
+            rx,
+        )
+    }
+
+    fn is_owner(&self) -> bool {
+        // While the Thread is being torn down, we can no longer tell if it is
+        // the Owner and simply use the Queue
+        THREAD_ID.try_with(|id| *id == self.owner).unwrap_or(false)
+    }
+
+    /// Returns an already released Record to the Cache.
+    ///
+    /// This is only a Hint for the owning Thread to reuse the Record first,
+    /// as it can be acquired by any Thread anyway
+    pub fn put(&self, record: *mut Record<()>) {
+        if self.is_owner() {
+            if let Ok(mut local) = self.local.try_borrow_mut() {
+                local.push(record);
+                return;
+            }
+        }
+
+        // If the owning Domain is already gone, there is nothing else to do
+        let _ = self.remote.enqueue(record);
+    }
+
+    /// Takes the most recently released Record from the local Records.
+    ///
+    /// This must only be called by the owning Thread
+    pub fn pop_local(&self) -> Option<*mut Record<()>> {
+        debug_assert!(self.is_owner());
+        self.local.borrow_mut().pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn put_on_owner() {
+        let (cache, mut remote) = RecordCache::new();
+
+        let record = Box::into_raw(Record::boxed_empty());
+        cache.put(record);
+
+        assert_eq!(Some(record), cache.pop_local());
+        assert_eq!(None, cache.pop_local());
+        assert!(remote.try_dequeue().is_err());
+
+        drop(unsafe { Box::from_raw(record) });
+    }
+
+    #[test]
+    fn put_on_other_thread() {
+        let (cache, mut remote) = RecordCache::new();
+        let cache = std::sync::Arc::new(cache);
+
+        let record = Box::into_raw(Record::boxed_empty());
+        let record_addr = record as usize;
+        {
+            let cache = cache.clone();
+            std::thread::spawn(move || cache.put(record_addr as *mut Record<()>))
+                .join()
+                .unwrap();
+        }
+
+        assert_eq!(None, cache.pop_local());
+        assert_eq!(Ok(record), remote.try_dequeue());
+
+        drop(unsafe { Box::from_raw(record) });
+    }
+}
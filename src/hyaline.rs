@@ -15,11 +15,79 @@
 //! the Handle acts as a Guard, so it should be kept around for as long as you are accessing the
 //! Datastructure.
 //!
+//! # Nested Handles
+//! Calling [Hyaline::enter] while the current Thread already holds a Handle for the same
+//! Instance creates a second, independent Handle, which is safe but registers the Thread twice
+//! and every Handle has to traverse the retired Objects on its own once it is dropped.
+//!
+//! With the `thread_data` Feature enabled, an Instance can instead be created using
+//! [Hyaline::new_reentrant], in which case only the first Handle of a Thread actually enters the
+//! Instance and all the nested Handles share its Reservation. The Reservation is only released
+//! once the last Handle of the Thread has been dropped, so the Handles can be dropped in any
+//! Order.
+//!
+//! ```rust
+//! # use nolock::hyaline::Hyaline;
+//! fn free(ptr: *const ()) {
+//!     let _ = unsafe { Box::from_raw(ptr as *mut u64) };
+//! }
+//!
+//! let instance = Hyaline::<4>::new_reentrant(free);
+//!
+//! let outer = instance.enter();
+//! let mut inner = instance.enter();
+//! assert!(inner.is_nested());
+//!
+//! unsafe { inner.retire(Box::into_raw(Box::new(13u64)) as *const ()) };
+//!
+//! // The Handles can be dropped in any Order
+//! drop(outer);
+//! drop(inner);
+//! ```
+//!
+//! # Retire Pressure
+//! Retired Objects are first collected in a Batch and only handed off for Reclamation once the
+//! Batch is full, after which they are freed as soon as all the Threads that could still see
+//! them have left the Instance. The current Backlog can be observed using [Hyaline::stats].
+//!
+//! If Threads stop retiring Objects, partially filled Batches are never handed off. An Instance
+//! can therefore be configured using [Hyaline::with_adaptive_flush], to also hand off partially
+//! filled Batches when a Handle is dropped, once the Number of pending Objects reached a given
+//! Threshold.
+//!
+//! ```rust
+//! # use nolock::hyaline::Hyaline;
+//! fn free(ptr: *const ()) {
+//!     let _ = unsafe { Box::from_raw(ptr as *mut u64) };
+//! }
+//!
+//! let instance = Hyaline::<4>::new(free).with_adaptive_flush(0);
+//!
+//! let mut handle = instance.enter();
+//! unsafe { handle.retire(Box::into_raw(Box::new(13u64)) as *const ()) };
+//! drop(handle);
+//!
+//! let stats = instance.stats();
+//! assert_eq!(1, stats.retired);
+//! assert_eq!(1, stats.freed);
+//! assert_eq!(0, stats.pending());
+//! ```
+//!
+//! # Memory Ordering
+//! Entering an Instance and retiring a Batch both issue a `SeqCst`-Fence. These Fences make
+//! sure that either a Thread retiring an Object sees that another Thread has entered the
+//! Instance, or that Thread does not see the Object anymore, even if the Datastructure itself
+//! only uses Acquire/Release Orderings to load and unlink its Objects. Apart from that, the
+//! Reference-Counts only need Acquire/Release Orderings to hand over the Objects to the Thread
+//! freeing them.
+//!
 //! ## C-Implementation
 //! [github](https://github.com/rusnikola/lfsmr)
 
 use alloc::boxed::Box;
 use atomic::Atomic;
+#[cfg(feature = "thread_data")]
+use core::cell::Cell;
 
 use crate::sync;
 
@@ -57,8 +125,103 @@ struct Node {
 }
 
 enum NodeMeta {
-    NrefNode { nref: sync::atomic::AtomicI64 },
-    Others { next: *const Node },
+    NrefNode {
+        nref: sync::atomic::AtomicI64,
+        /// The Time at which the Batch was handed off
+        #[cfg(feature = "std")]
+        retired_at: std::time::Instant,
+    },
+    Others {
+        next: *const Node,
+    },
+}
+
+/// The State of a single Thread for a reentrant Instance
+#[cfg(feature = "thread_data")]
+struct ThreadState {
+    /// The Number of Handles the Thread currently holds
+    depth: Cell<usize>,
+    /// The Ptr obtained by the first Handle, when it entered the Instance
+    hptr: Cell<*const Node>,
+}
+
+// The State is only accessed by its Thread and the Ptr is never dereferenced
+// through it, so it can be dropped on any other Thread
+#[cfg(feature = "thread_data")]
+unsafe impl Send for ThreadState {}
+
+#[cfg(feature = "thread_data")]
+impl Default for ThreadState {
+    fn default() -> Self {
+        Self {
+            depth: Cell::new(0),
+            hptr: Cell::new(core::ptr::null()),
+        }
+    }
+}
+
+/// A Snapshot of the Reclamation-Statistics of a [Hyaline] Instance, see [Hyaline::stats].
+///
+/// Objects are only counted once the Batch containing them is handed off for Reclamation, so
+/// Objects that are still collected in the Batch of a Handle are not included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetireStats {
+    /// The Number of Objects that have been handed off for Reclamation
+    pub retired: u64,
+    /// The Number of retired Objects that have already been freed
+    pub freed: u64,
+    /// The Number of Batches that have already been freed
+    pub batches_freed: u64,
+    /// The combined Time all the freed Batches spent between being handed off and being freed
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub total_residence: core::time::Duration,
+}
+
+impl RetireStats {
+    /// The Number of retired Objects that are still waiting to be freed
+    pub fn pending(&self) -> u64 {
+        self.retired.saturating_sub(self.freed)
+    }
+
+    /// The average Time a Batch spent between being handed off and being freed, if any Batch
+    /// has been freed yet
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn average_residence(&self) -> Option<core::time::Duration> {
+        if self.batches_freed == 0 {
+            return None;
+        }
+
+        Some(self.total_residence / self.batches_freed as u32)
+    }
+}
+
+/// The shared Counters backing the [RetireStats]
+struct Counters {
+    retired: sync::atomic::AtomicU64,
+    freed: sync::atomic::AtomicU64,
+    batches_freed: sync::atomic::AtomicU64,
+    #[cfg(feature = "std")]
+    residence_nanos: sync::atomic::AtomicU64,
+}
+
+impl Counters {
+    fn new() -> Self {
+        Self {
+            retired: sync::atomic::AtomicU64::new(0),
+            freed: sync::atomic::AtomicU64::new(0),
+            batches_freed: sync::atomic::AtomicU64::new(0),
+            #[cfg(feature = "std")]
+            residence_nanos: sync::atomic::AtomicU64::new(0),
+        }
+    }
+
+    fn pending(&self) -> u64 {
+        let freed = self.freed.load(sync::atomic::Ordering::Relaxed);
+        let retired = self.retired.load(sync::atomic::Ordering::Relaxed);
+        retired.saturating_sub(freed)
+    }
 }
 
 /// The Hyaline instance which stores all the needed information to manage the reclaimation Process
@@ -71,6 +234,13 @@ pub struct Hyaline<const K: usize = 4> {
     heads: [Atomic<u128>; K],
     batches: batchlist::BatchList<K>,
     free_fn: fn(*const ()),
+    counters: Counters,
+    /// The Number of pending Objects, at which partially filled Batches are handed off when a
+    /// Handle is dropped
+    flush_threshold: Option<u64>,
+    /// The State of every Thread, if the Instance is reentrant
+    #[cfg(feature = "thread_data")]
+    threads: Option<crate::thread_data::ThreadData<ThreadState>>,
 }
 
 /// The Handle acts like a Guard that Protects the entire Datastructure as long as it is held and
@@ -81,6 +251,11 @@ pub struct Handle<'a> {
     heads: &'a [Atomic<u128>],
     batch_handle: batchlist::BatchHandle<'a>,
     free_fn: fn(*const ()),
+    counters: &'a Counters,
+    flush_threshold: Option<u64>,
+    /// The State of the current Thread, if the Instance is reentrant
+    #[cfg(feature = "thread_data")]
+    thread: Option<&'a ThreadState>,
 }
 
 // This is currently only allowed because we need it to create the Array in `Hyaline::new` which
@@ -97,13 +272,98 @@ impl<const K: usize> Hyaline<K> {
             heads: [SINGLE_SLOT; K],
             batches: batchlist::BatchList::new(),
             free_fn,
+            counters: Counters::new(),
+            flush_threshold: None,
+            #[cfg(feature = "thread_data")]
+            threads: None,
+        }
+    }
+
+    /// Enables the adaptive Flushing of partially filled Batches.
+    ///
+    /// Once at least `pending_threshold` retired Objects are waiting to be freed, every Handle
+    /// hands off its partially filled Batch when it is dropped, instead of waiting for it to be
+    /// filled up by later Handles. A Threshold of 0 always hands off the Batches.
+    pub fn with_adaptive_flush(mut self, pending_threshold: usize) -> Self {
+        self.flush_threshold = Some(pending_threshold as u64);
+        self
+    }
+
+    /// Returns the current Reclamation-Statistics of the Instance.
+    ///
+    /// The Counters are updated independently of each other, so the Snapshot is only
+    /// approximate while other Threads are retiring or freeing Objects
+    pub fn stats(&self) -> RetireStats {
+        RetireStats {
+            retired: self.counters.retired.load(sync::atomic::Ordering::Relaxed),
+            freed: self.counters.freed.load(sync::atomic::Ordering::Relaxed),
+            batches_freed: self
+                .counters
+                .batches_freed
+                .load(sync::atomic::Ordering::Relaxed),
+            #[cfg(feature = "std")]
+            total_residence: core::time::Duration::from_nanos(
+                self.counters
+                    .residence_nanos
+                    .load(sync::atomic::Ordering::Relaxed),
+            ),
         }
     }
 
+    /// Creates a new reentrant Instance, which will actually free the underlying Data using the
+    /// provided `free_fn`.
+    ///
+    /// Nested calls to [`enter`](Self::enter) on the same Thread share the Reservation of the
+    /// first Handle, see the [module-level documentation](self) for more Details
+    #[cfg(feature = "thread_data")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "thread_data")))]
+    pub fn new_reentrant(free_fn: fn(*const ())) -> Self {
+        let mut instance = Self::new(free_fn);
+        instance.threads = Some(crate::thread_data::ThreadData::new());
+        instance
+    }
+
     /// This should be called at the start of every operation. As long as the returned handle is
     /// not dropped, the Data that can be accessed from this point going forward in the
     /// Datastructure is safe to access from this Thread.
     pub fn enter(&self) -> Handle<'_> {
+        #[cfg(feature = "thread_data")]
+        if let Some(threads) = self.threads.as_ref() {
+            let state = threads.get_or(ThreadState::default);
+
+            let depth = state.depth.get();
+            if depth == 0 {
+                state.hptr.set(self.enter_slot());
+            }
+            state.depth.set(depth + 1);
+
+            return Handle {
+                hptr: state.hptr.get(),
+                adjs: self.adjs,
+                heads: &self.heads,
+                batch_handle: self.batches.get_batch(),
+                free_fn: self.free_fn,
+                counters: &self.counters,
+                flush_threshold: self.flush_threshold,
+                thread: Some(state),
+            };
+        }
+
+        Handle {
+            hptr: self.enter_slot(),
+            adjs: self.adjs,
+            heads: &self.heads,
+            batch_handle: self.batches.get_batch(),
+            free_fn: self.free_fn,
+            counters: &self.counters,
+            flush_threshold: self.flush_threshold,
+            #[cfg(feature = "thread_data")]
+            thread: None,
+        }
+    }
+
+    /// Registers the current Thread in its Slot and returns the current Head of the Slot
+    fn enter_slot(&self) -> *const Node {
         // TODO
         let slot = 0;
 
@@ -114,17 +374,15 @@ impl<const K: usize> Hyaline<K> {
                     href: 1,
                 }
                 .into(),
-                atomic::Ordering::SeqCst,
+                atomic::Ordering::AcqRel,
             )
             .into();
 
-        Handle {
-            hptr: last.hptr,
-            adjs: self.adjs,
-            heads: &self.heads,
-            batch_handle: self.batches.get_batch(),
-            free_fn: self.free_fn,
-        }
+        // Pairs with the Fence in `retire_batch`, so that either the retiring Thread sees our
+        // Reference or we dont see the retired Objects, when loading them afterwards
+        sync::atomic::fence(sync::atomic::Ordering::SeqCst);
+
+        last.hptr
     }
 }
 impl<const K: usize> Drop for Hyaline<K> {
@@ -138,6 +396,17 @@ impl<const K: usize> Drop for Hyaline<K> {
 }
 
 impl<'a> Handle<'a> {
+    /// Checks if this Handle currently shares its Reservation with other Handles of the same
+    /// Thread, which is only possible for reentrant Instances
+    pub fn is_nested(&self) -> bool {
+        #[cfg(feature = "thread_data")]
+        if let Some(state) = self.thread {
+            return state.depth.get() > 1;
+        }
+
+        false
+    }
+
     /// Retires the given Ptr, which will be freed, using the provided `free_fn` when the Hyaline
     /// Instance was created, once it is save to do so.
     ///
@@ -148,49 +417,70 @@ impl<'a> Handle<'a> {
             return;
         }
 
+        self.flush_batch();
+
+        self.batch_handle.try_retire(ptr).unwrap();
+    }
+
+    /// Hands off all the Objects in the current Batch for Reclamation.
+    ///
+    /// Every Slot needs its own Node in the Batch, so Batches with fewer Objects than there are
+    /// Slots are padded with empty Nodes, which are skipped when the Batch is freed
+    fn flush_batch(&mut self) {
         let nrefnode_ptr = Box::into_raw(Box::new(Node {
             nrefnode: core::ptr::null(),
             batch_next: core::ptr::null(),
             meta: NodeMeta::NrefNode {
                 nref: sync::atomic::AtomicI64::new(0),
+                #[cfg(feature = "std")]
+                retired_at: std::time::Instant::now(),
             },
             data: core::ptr::null(),
         }));
 
-        let (head, _) = self.batch_handle.batch_iter().fold(
-            (core::ptr::null_mut(), core::ptr::null_mut()),
-            |(mut head, tail): (*mut Node, *mut Node), node| {
-                let entry = Node {
-                    meta: NodeMeta::Others {
-                        next: core::ptr::null(),
-                    },
-                    batch_next: core::ptr::null(),
-                    nrefnode: nrefnode_ptr as *const Node,
-                    data: node,
-                };
-                let entry_ptr = Box::into_raw(Box::new(entry));
+        let mut head: *mut Node = core::ptr::null_mut();
+        let mut tail: *mut Node = core::ptr::null_mut();
+        let mut append = |data: *const ()| {
+            let entry = Node {
+                meta: NodeMeta::Others {
+                    next: core::ptr::null(),
+                },
+                batch_next: core::ptr::null(),
+                nrefnode: nrefnode_ptr as *const Node,
+                data,
+            };
+            let entry_ptr = Box::into_raw(Box::new(entry));
 
-                if !tail.is_null() {
-                    let tail_node = unsafe { &mut *tail };
-                    tail_node.batch_next = entry_ptr as *const Node;
-                }
-                if head.is_null() {
-                    head = entry_ptr;
-                }
+            if !tail.is_null() {
+                let tail_node = unsafe { &mut *tail };
+                tail_node.batch_next = entry_ptr as *const Node;
+            }
+            if head.is_null() {
+                head = entry_ptr;
+            }
+            tail = entry_ptr;
+        };
 
-                (head, entry_ptr)
-            },
-        );
+        let mut count = 0;
+        for data in self.batch_handle.batch_iter() {
+            append(data);
+            count += 1;
+        }
+        for _ in count..self.heads.len() {
+            append(core::ptr::null());
+        }
 
         unsafe { &mut *nrefnode_ptr }.batch_next = head as *const Node;
 
+        self.counters
+            .retired
+            .fetch_add(count as u64, sync::atomic::Ordering::Relaxed);
+
         let batch = LocalBatch {
             firstnode: head as *const Node,
             nrefnode: nrefnode_ptr as *const Node,
         };
         self.retire_batch(batch);
-
-        self.batch_handle.try_retire(ptr).unwrap();
     }
 
     fn retire_batch(&self, batch: LocalBatch) {
@@ -200,15 +490,20 @@ impl<'a> Handle<'a> {
         let mut curr_node = batch.firstnode;
         unsafe {
             match &(*batch.nrefnode).meta {
-                NodeMeta::NrefNode { nref } => nref.store(0, sync::atomic::Ordering::SeqCst),
+                // The Batch is only published by inserting it into the Slots
+                NodeMeta::NrefNode { nref, .. } => nref.store(0, sync::atomic::Ordering::Relaxed),
                 _ => unreachable!(),
             };
         }
 
+        // Pairs with the Fence in `enter_slot`, so that every Thread that could still see the
+        // retired Objects is visible in the Slots
+        sync::atomic::fence(sync::atomic::Ordering::SeqCst);
+
         'slot: for raw_head in self.heads.iter() {
             let mut head: HeadPtr;
             loop {
-                head = raw_head.load(atomic::Ordering::SeqCst).into();
+                head = raw_head.load(atomic::Ordering::Acquire).into();
 
                 if head.href == 0 {
                     do_adj = true;
@@ -229,8 +524,8 @@ impl<'a> Handle<'a> {
                     .compare_exchange(
                         head.into(),
                         new.into(),
-                        atomic::Ordering::SeqCst,
-                        atomic::Ordering::SeqCst,
+                        atomic::Ordering::AcqRel,
+                        atomic::Ordering::Acquire,
                     )
                     .is_ok()
                 {
@@ -256,11 +551,12 @@ impl<'a> Handle<'a> {
         let ref_node = unsafe { &*(node_ref.nrefnode) };
 
         let ref_val = match &ref_node.meta {
-            NodeMeta::NrefNode { nref } => nref,
+            NodeMeta::NrefNode { nref, .. } => nref,
             _ => return,
         };
 
-        if ref_val.fetch_add(val, sync::atomic::Ordering::SeqCst) == val.wrapping_neg() {
+        // The last Adjustment frees the Batch, so it needs to acquire all the previous Releases
+        if ref_val.fetch_add(val, sync::atomic::Ordering::AcqRel) == val.wrapping_neg() {
             self.free_batch(ref_node.batch_next);
         }
     }
@@ -280,8 +576,8 @@ impl<'a> Handle<'a> {
 
             let ref_node = unsafe { &*(current_ref.nrefnode) };
             match &ref_node.meta {
-                NodeMeta::NrefNode { nref } => {
-                    if nref.fetch_add(-1, sync::atomic::Ordering::SeqCst) == 1 {
+                NodeMeta::NrefNode { nref, .. } => {
+                    if nref.fetch_add(-1, sync::atomic::Ordering::AcqRel) == 1 {
                         self.free_batch(ref_node.batch_next);
                     }
                 }
@@ -300,24 +596,62 @@ impl<'a> Handle<'a> {
         }
 
         let ref_node_ptr = unsafe { &*start }.nrefnode;
-        let _ = unsafe { Box::from_raw(ref_node_ptr as *mut Node) };
+        let ref_node = unsafe { Box::from_raw(ref_node_ptr as *mut Node) };
+        #[cfg(feature = "std")]
+        if let NodeMeta::NrefNode { retired_at, .. } = &ref_node.meta {
+            let residence = retired_at.elapsed().as_nanos() as u64;
+            self.counters
+                .residence_nanos
+                .fetch_add(residence, sync::atomic::Ordering::Relaxed);
+        }
+        drop(ref_node);
 
+        let mut freed = 0;
         let mut current = start;
         while !current.is_null() {
             let node = unsafe { &*current };
             let next = node.batch_next;
 
-            (self.free_fn)(node.data);
+            // Padding Nodes don't contain any Object
+            if !node.data.is_null() {
+                (self.free_fn)(node.data);
+                freed += 1;
+            }
 
-            let _ = unsafe { Box::from_raw(current as *mut Node) };
+            unsafe { crate::poison::drop_box(current as *mut Node) };
 
             current = next;
         }
+
+        self.counters
+            .freed
+            .fetch_add(freed, sync::atomic::Ordering::Relaxed);
+        self.counters
+            .batches_freed
+            .fetch_add(1, sync::atomic::Ordering::Relaxed);
     }
 }
 impl<'b> Drop for Handle<'b> {
     // This is the leave function in the Paper
     fn drop(&mut self) {
+        // Hand off the partially filled Batch while we are still registered, if there are too
+        // many Objects waiting to be freed already
+        if let Some(threshold) = self.flush_threshold {
+            if !self.batch_handle.is_empty() && self.counters.pending() >= threshold {
+                self.flush_batch();
+            }
+        }
+
+        // Only the last Handle of a Thread actually leaves a reentrant Instance
+        #[cfg(feature = "thread_data")]
+        if let Some(state) = self.thread {
+            let depth = state.depth.get() - 1;
+            state.depth.set(depth);
+            if depth > 0 {
+                return;
+            }
+        }
+
         // TODO
         let slot = 0;
 
@@ -325,7 +659,7 @@ impl<'b> Drop for Handle<'b> {
         let mut current: HeadPtr;
         let mut head: HeadPtr;
         loop {
-            head = self.heads[slot].load(atomic::Ordering::SeqCst).into();
+            head = self.heads[slot].load(atomic::Ordering::Acquire).into();
             current = head;
 
             if current.hptr != self.hptr {
@@ -346,12 +680,14 @@ impl<'b> Drop for Handle<'b> {
                 href: new_href,
             };
 
+            // Releases all our Accesses to the protected Objects, before they can be freed by
+            // another Thread
             if self.heads[slot]
                 .compare_exchange(
                     head.into(),
                     new.into(),
-                    atomic::Ordering::SeqCst,
-                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Acquire,
                 )
                 .is_ok()
             {
@@ -406,6 +742,159 @@ mod tests {
             handle.join().unwrap();
         }
     }
+
+    #[test]
+    fn stats_full_batches() {
+        let instance = Hyaline::<2>::new(box_dealloc_u8);
+
+        let mut handle = instance.enter();
+        for i in 0u8..5 {
+            unsafe {
+                handle.retire(Box::into_raw(Box::new(i)) as *const ());
+            }
+        }
+
+        // Only the first two full Batches have been handed off and the last Object is still
+        // in the Batch of the Handle
+        let stats = instance.stats();
+        assert_eq!(4, stats.retired);
+        assert_eq!(0, stats.freed);
+        assert_eq!(4, stats.pending());
+
+        drop(handle);
+
+        let stats = instance.stats();
+        assert_eq!(4, stats.retired);
+        assert_eq!(4, stats.freed);
+        assert_eq!(2, stats.batches_freed);
+        assert_eq!(0, stats.pending());
+        #[cfg(feature = "std")]
+        assert!(stats.average_residence().is_some());
+    }
+
+    #[test]
+    fn stats_pending_while_entered() {
+        let instance = Hyaline::<1>::new(box_dealloc_u8);
+
+        let reader = instance.enter();
+
+        let mut handle = instance.enter();
+        for i in 0u8..3 {
+            unsafe {
+                handle.retire(Box::into_raw(Box::new(i)) as *const ());
+            }
+        }
+        drop(handle);
+
+        // The Reader could still access the retired Objects
+        assert_eq!(2, instance.stats().pending());
+
+        drop(reader);
+        assert_eq!(0, instance.stats().pending());
+    }
+
+    #[test]
+    fn adaptive_flush_partial_batch() {
+        let instance = Hyaline::<4>::new(box_dealloc_u8).with_adaptive_flush(0);
+
+        let mut handle = instance.enter();
+        unsafe {
+            handle.retire(Box::into_raw(Box::new(13u8)) as *const ());
+        }
+        drop(handle);
+
+        let stats = instance.stats();
+        assert_eq!(1, stats.retired);
+        assert_eq!(1, stats.freed);
+        assert_eq!(1, stats.batches_freed);
+    }
+
+    #[test]
+    fn adaptive_flush_threshold() {
+        let instance = Hyaline::<2>::new(box_dealloc_u8).with_adaptive_flush(2);
+
+        // Nothing is pending, so the partial Batch is kept
+        let mut handle = instance.enter();
+        unsafe {
+            handle.retire(Box::into_raw(Box::new(1u8)) as *const ());
+        }
+        drop(handle);
+        assert_eq!(0, instance.stats().retired);
+
+        let reader = instance.enter();
+        let mut handle = instance.enter();
+        for i in 0u8..3 {
+            unsafe {
+                handle.retire(Box::into_raw(Box::new(i)) as *const ());
+            }
+        }
+        drop(handle);
+
+        // The Reader keeps the full Batch pending, so the partial Batch is handed off as well
+        assert_eq!(3, instance.stats().retired);
+        assert_eq!(3, instance.stats().pending());
+
+        // The Reader reused the Batch with the first Object, which is handed off now
+        drop(reader);
+        assert_eq!(4, instance.stats().retired);
+        assert_eq!(0, instance.stats().pending());
+    }
+
+    #[test]
+    #[cfg(feature = "thread_data")]
+    fn reentrant_nested() {
+        let instance = Hyaline::<1>::new_reentrant(box_dealloc_u8);
+
+        let outer = instance.enter();
+        assert!(!outer.is_nested());
+
+        let mut inner = instance.enter();
+        assert!(inner.is_nested());
+        assert_eq!(outer.hptr, inner.hptr);
+
+        unsafe {
+            inner.retire(Box::into_raw(Box::new(13u8)) as *const ());
+        }
+
+        // Dropping the outer Handle first should keep the Reservation alive
+        drop(outer);
+        assert!(!inner.is_nested());
+        drop(inner);
+
+        let handle = instance.enter();
+        assert!(!handle.is_nested());
+    }
+
+    #[test]
+    #[cfg(feature = "thread_data")]
+    fn reentrant_two_threads() {
+        let instance = Arc::new(Hyaline::<1>::new_reentrant(box_dealloc_u8));
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let inst = instance.clone();
+
+                std::thread::spawn(move || {
+                    for _ in 0..32 {
+                        let mut outer = inst.enter();
+                        let mut inner = inst.enter();
+                        assert!(inner.is_nested());
+
+                        for i in 0u8..2 {
+                            unsafe {
+                                outer.retire(Box::into_raw(Box::new(i)) as *const ());
+                                inner.retire(Box::into_raw(Box::new(i)) as *const ());
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
 }
 
 #[cfg(all(test, loom))]
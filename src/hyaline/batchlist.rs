@@ -121,6 +121,10 @@ impl<const N: usize> ListEntry<N> {
 }
 
 impl<'b> BatchHandle<'b> {
+    pub fn is_empty(&self) -> bool {
+        *self.index == 0
+    }
+
     pub fn try_retire(&mut self, ptr: *const ()) -> Result<(), *const ()> {
         if *self.index == self.nodes.len() {
             return Err(ptr);
@@ -6,21 +6,49 @@
 //!
 //! # Feature-Flags
 //! * `queues`: Enables all the Queues
-//! * `async`: Enables all the Async-Version of the Algorithms/Datastructures
+//! * `async`: Enables all the Async-Version of the Algorithms/Datastructures,
+//!   which also work in `no_std`-Executors, as they only depend on `core` and
+//!   `alloc`
+//! * `metrics`: Enables the optional Instrumentation-Hooks for the Queues
+//!   and the HashTrieMap
+//! * `metrics-facade`: Reports the Instrumentation to the `metrics` Facade,
+//!   see [`facade`](crate::facade)
+//! * `serde`: Enables Serialization of Queue-Snapshots and the HashTrieMap
 //! * `thread_data`: Enables the ThreadData Module
 //! * `hazard_ptr`: Enables the Hazard-Ptr implementation
-//! * `hyaline`: Enables the Hyaline implementation
+//! * `hyaline`: Enables the Hyaline implementation and the [`AtomicArc`](arc::AtomicArc)
 //! * `allocator`: Enables the Allocators
 //! * `full`: Enables all the Feature-Flags
 //!
 //! # Experimental-Feature-Flags
 //! * `hash_trie`: Enables the Hash-Trie-Map implementation
+//! * `test_util`: Replaces the internal Atomics to inject Yields in
+//!   Stress-Tests, see [`test_util`](crate::test_util)
+//! * `debug-validate`: Validates the State-Transitions of the Nodes in the
+//!   Jiffy-Queue and panics with a detailed Diagnostic on any Violation, see
+//!   [`validate`](crate::queues::mpsc::jiffy::validate)
+//! * `debug-poison`: Fills the Memory freed by the Datastructures with a
+//!   Pattern in Debug-Builds and panics if a Guard hands out poisoned Data,
+//!   to catch Use-After-Free Bugs closer to their Source
+//!
+//! # Utilities
+//! The low-level Building-Blocks in [`utils`], the lock-free Cells in
+//! [`cell`] and the Event-Recording in [`diagnostics`] are always available,
+//! no matter which Features are enabled
 
 extern crate alloc;
 
 #[cfg(feature = "allocator")]
 #[cfg_attr(docsrs, doc(cfg(feature = "allocator")))]
 pub mod allocator;
+#[cfg(feature = "hyaline")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hyaline")))]
+pub mod arc;
+pub mod cell;
+pub mod diagnostics;
+#[cfg(feature = "metrics-facade")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics-facade")))]
+pub mod facade;
 #[cfg(feature = "hash_trie")]
 #[cfg_attr(docsrs, doc(cfg(feature = "hash_trie")))]
 pub mod hash_trie;
@@ -36,5 +64,13 @@ pub mod queues;
 #[cfg(feature = "thread_data")]
 #[cfg_attr(docsrs, doc(cfg(feature = "thread_data")))]
 pub mod thread_data;
+pub mod utils;
+
+#[cfg(feature = "test_util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test_util")))]
+pub mod test_util;
 
+#[cfg(any(feature = "queues", feature = "hyaline"))]
+#[cfg_attr(not(feature = "queues"), allow(dead_code))]
+pub(crate) mod poison;
 pub(crate) mod sync;
@@ -0,0 +1,129 @@
+//! Poisoning of freed Memory, to catch Use-After-Free Bugs closer to their
+//! Source.
+//!
+//! With the `debug-poison` Feature in a Debug-Build, the Memory of the
+//! Segments and Nodes freed by the Queues, the Hash-Trie and Hyaline is
+//! filled with a fixed Pattern right before it is given back to the
+//! Allocator. The Guard-Types check the Data they hand out for this Pattern
+//! and panic if they find it, as that means the Data was already reclaimed
+//! while still being protected.
+//!
+//! This is only a best-effort Check, as the Allocator may reuse the Memory
+//! before it is accessed again and it only covers Memory freed by this Crate
+//! itself, not Data retired with a custom Function. Without the Feature or
+//! in Release-Builds all of this compiles down to the normal Deallocation.
+
+use alloc::boxed::Box;
+use core::{mem, ptr};
+
+/// Whether Memory is actually poisoned
+pub(crate) const ENABLED: bool = cfg!(all(feature = "debug-poison", debug_assertions));
+
+/// The Pattern that freed Memory is filled with
+const PATTERN: u8 = 0xA5;
+/// The maximum Number of Bytes at the Start of a Value that are checked
+const CHECKED_BYTES: usize = 32;
+
+/// Drops the Value in the Box behind the Ptr and poisons its Memory before
+/// deallocating it, like `drop(Box::from_raw(ptr))`
+///
+/// # Safety
+/// The Ptr needs to come from `Box::into_raw` and must not be used by the
+/// Caller afterwards, same as for `Box::from_raw`
+pub(crate) unsafe fn drop_box<T>(ptr: *mut T) {
+    if !ENABLED {
+        drop(unsafe { Box::from_raw(ptr) });
+        return;
+    }
+
+    // # Safety:
+    // The Value is dropped in place, after which the Memory is still owned by
+    // us and only overwritten and deallocated through a Box of
+    // `MaybeUninit<T>`, which has the same Layout but does not drop the Value
+    // a second time
+    unsafe {
+        ptr::drop_in_place(ptr);
+        fill(ptr);
+        drop(Box::from_raw(ptr as *mut mem::MaybeUninit<T>));
+    }
+}
+
+/// Fills the Memory of the Value behind the Ptr with the Pattern
+///
+/// # Safety
+/// The Ptr needs to be valid for Writes of `T` and the Value must already
+/// have been dropped
+pub(crate) unsafe fn fill<T>(ptr: *mut T) {
+    unsafe { ptr::write_bytes(ptr as *mut u8, PATTERN, mem::size_of::<T>()) };
+}
+
+/// Panics if the Value behind the Ptr has been poisoned, meaning that it was
+/// freed while still being accessed.
+///
+/// Values smaller than a Pointer are never considered poisoned, as they are
+/// too likely to contain the Pattern as a legitimate Value
+#[inline]
+#[track_caller]
+pub(crate) fn check<T>(ptr: *const T) {
+    if !ENABLED || ptr.is_null() || mem::size_of::<T>() < mem::size_of::<usize>() {
+        return;
+    }
+
+    let len = mem::size_of::<T>().min(CHECKED_BYTES);
+    let bytes = ptr as *const u8;
+    // # Safety:
+    // The Ptr is either valid or has been freed by us, in which case this is
+    // exactly the Access we want to detect. The Reads are volatile so they
+    // are not optimized based on the Value being valid
+    let poisoned = (0..len).all(|offset| unsafe { bytes.add(offset).read_volatile() } == PATTERN);
+    if poisoned {
+        use_after_free::<T>(ptr);
+    }
+}
+
+#[cold]
+#[track_caller]
+fn use_after_free<T>(ptr: *const T) -> ! {
+    panic!(
+        "Use-After-Free detected: {:p} points to the poisoned Memory of an already freed {}",
+        ptr,
+        core::any::type_name::<T>()
+    );
+}
+
+#[cfg(all(test, feature = "debug-poison", debug_assertions))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_not_poisoned() {
+        let boxed = Box::new([13u64; 4]);
+        check(&*boxed as *const [u64; 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Use-After-Free detected")]
+    fn poisoned_detected() {
+        let ptr = Box::into_raw(Box::new([13u64; 4]));
+
+        // Poison the Value without freeing it, so the Check itself is
+        // still a valid Access
+        unsafe { fill(ptr) };
+        check(ptr as *const [u64; 4]);
+    }
+
+    #[test]
+    fn small_never_poisoned() {
+        let value = PATTERN;
+        check(&value as *const u8);
+    }
+
+    #[test]
+    fn drop_box_drops() {
+        let value = alloc::sync::Arc::new(13);
+        let ptr = Box::into_raw(Box::new(value.clone()));
+
+        unsafe { drop_box(ptr) };
+        assert_eq!(1, alloc::sync::Arc::strong_count(&value));
+    }
+}
@@ -14,9 +14,82 @@
 //! These are queuest that allow multiple Producers and multiple Consumers.
 //! Each Element will only be consumed by a single Consumer and it is not known
 //! which Consumer will receive which Element
+//!
+//! # Sharded
+//! Multiple independent MPSC-Queues behind a single cloneable Sender, which
+//! distributes the Elements across them, and a Receiver, which services all
+//! of them fairly or can be split up into one Receiver per Shard
+//!
+//! # Index-Queue
+//! A bounded MPMC-Queue that only stores small Indices, which is used as the
+//! Building-Block for the MPMC-Queues, but can also be used on its own
+//!
+//! # Delay
+//! A Queue where every Element only becomes available once its Deadline has
+//! passed, which needs the `std` Feature
+//!
+//! # Disruptor
+//! A preallocated Ring in the Style of the LMAX-Disruptor, where multiple
+//! Producers publish Elements and every Consumer sees every Element, which
+//! allows building Pipelines of dependent Consumer-Stages
+//!
+//! # Priority
+//! A concurrent Priority-Queue, that always returns the Element with the
+//! smallest Priority first, which needs the `hyaline` Feature
+//!
+//! # Watch
+//! A conflating Channel, where the Senders overwrite a single latest Value
+//! and the Receiver only ever observes the most recent one, together with
+//! its Version
+//!
+//! # Builder
+//! Instead of calling the Constructors of the individual Queues, the
+//! [`Builder`] can be used to construct any of them using a uniform API
+//!
+//! # Snapshot
+//! Every Queue-Receiver can collect the Elements currently stored in its Queue
+//! into a [`Snapshot`](snapshot::Snapshot), which can be serialized when the
+//! `serde` Feature is enabled
+//!
+//! # Async
+//! The blocking Operations of the synchronous Queues block the whole Thread,
+//! so in async Code either the async Queues or the `dequeue_cooperative`
+//! Operations of the `cooperative` module should be used instead. The Halves
+//! of the jiffy, bounded SPSC and unbounded MPMC Queues can also be converted
+//! between their sync and async Variants at any Time, using `into_async` and
+//! `into_sync`
+//!
+//! # Cancellation
+//! The blocking Operations can also be stopped from another Thread, without
+//! closing the Queue, by using their `_cancellable` Variants together with a
+//! [`CancelToken`](cancel::CancelToken)
+//!
+//! # Notify
+//! A Consumer servicing many Queues, like the Shards of a sharded Queue, can
+//! use a [`ReadySet`](notify::ReadySet) to find out which of them became
+//! non-empty, instead of polling every single one
+//!
+//! # Adapter
+//! All the Receivers implement the common [`QueueReceiver`](adapter::QueueReceiver)
+//! Trait, which allows transforming their Elements using the Adapters in the
+//! `adapter` module, like mapping or filtering them while they are dequeued
+//!
+//! # Dynamic
+//! The Senders and Receivers of the Queues also implement the object-safe
+//! [`DynSender`](dynamic::DynSender) and [`DynReceiver`](dynamic::DynReceiver)
+//! Traits, which allow selecting the Queue at Runtime or storing the Halves
+//! of different Queues together, as `Box<dyn DynSender<T>>`
+//!
+//! # Metrics
+//! With the `metrics` Feature enabled, the Queues can also be created with
+//! Instrumentation-Hooks, see the `metrics` module for more Details
 
 /// The Error returned by the Enqueue Operation
+///
+/// More Variants may be added in the Future, so matching on it needs a
+/// Wildcard-Arm
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum EnqueueError {
     /// The Queue is full and therefore the current Element could not be enqueued on it
     Full,
@@ -24,18 +97,68 @@ pub enum EnqueueError {
     /// any more Elements / any Element that would be inserted at this point would never
     /// be consumed
     Closed,
+    /// The Element could only have been enqueued by allocating more Memory, which was not
+    /// allowed for the Operation
+    NoSpace,
+    /// The Element could only have been enqueued by allocating more Memory, but the
+    /// Allocation failed
+    AllocFailed,
+    /// The Queue stayed full until the Operation was cancelled using a
+    /// [`CancelToken`](cancel::CancelToken)
+    Cancelled,
 }
 
 /// The Error returned by the Dequeue Operation
+///
+/// More Variants may be added in the Future, so matching on it needs a
+/// Wildcard-Arm
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum DequeueError {
     /// The Queue is empty and therefore no Element could be dequeued at this point in time
     Empty,
     /// The Queue has been closed by the Sending Side and therefore no more Elements will
     /// be added to the Queue in the Future
     Closed,
+    /// The Queue stayed empty until the Operation was cancelled using a
+    /// [`CancelToken`](cancel::CancelToken)
+    Cancelled,
 }
 
+pub mod adapter;
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub mod batch;
+pub mod builder;
+pub use builder::Builder;
+pub mod cancel;
+
+#[cfg(any(feature = "async", feature = "hyaline"))]
+mod convert;
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub mod cooperative;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod delay;
+pub mod disruptor;
+pub mod dynamic;
+pub mod index_queue;
+mod instrument;
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub mod metrics;
 pub mod mpmc;
 pub mod mpsc;
+pub mod notify;
+#[cfg(feature = "hyaline")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hyaline")))]
+pub mod priority;
+pub mod sharded;
+pub mod shutdown;
+pub mod snapshot;
 pub mod spsc;
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub mod timeout;
+pub mod watch;
@@ -0,0 +1,456 @@
+//! Adapters, that transform the Elements of any Queue while they are being
+//! dequeued.
+//!
+//! The Receivers of all the Queues implement [`QueueReceiver`] and the async
+//! Receivers implement [`AsyncQueueReceiver`], which allows wrapping any of
+//! them in a [`MappedReceiver`], which applies a Closure to every dequeued
+//! Element, or a [`FilteredReceiver`], which drops all the Elements that do
+//! not match a Predicate.
+//!
+//! The Closures are applied directly to the dequeued Elements, without
+//! buffering them anywhere, so the Adapters are just as lock-free as the
+//! underlying Queue.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::{adapter::{FilteredReceiver, MappedReceiver, QueueReceiver}, mpsc::jiffy};
+//! let (rx, tx) = jiffy::queue::<usize>();
+//!
+//! let rx = FilteredReceiver::new(rx, |x: &usize| x % 2 == 0);
+//! let mut rx = MappedReceiver::new(rx, |x: usize| x.to_string());
+//!
+//! for i in 0..4 {
+//!     tx.enqueue(i).unwrap();
+//! }
+//!
+//! assert_eq!(Ok("0".to_string()), rx.try_dequeue());
+//! assert_eq!(Ok("2".to_string()), rx.try_dequeue());
+//! assert!(rx.try_dequeue().is_err());
+//! ```
+
+use core::fmt::Debug;
+
+#[cfg(feature = "async")]
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::DequeueError;
+
+/// The Receiving Half of a Queue
+pub trait QueueReceiver {
+    /// The Elements stored in the Queue
+    type Item;
+
+    /// Attempts to dequeue an Element, without waiting for one to be
+    /// enqueued
+    fn try_dequeue(&mut self) -> Result<Self::Item, DequeueError>;
+
+    /// Dequeues an Element, blocking until one is enqueued, and returns
+    /// `None` once the Queue is closed and empty
+    fn dequeue(&mut self) -> Option<Self::Item>;
+}
+
+/// The Receiving Half of an async Queue
+///
+/// # Dequeue-Future
+/// The Futures returned by [`dequeue`](AsyncQueueReceiver::dequeue) can be
+/// polled again, after they resolved, to wait for the next Element, which is
+/// needed by the [`FilteredReceiver`] to skip Elements without borrowing the
+/// Receiver again.
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub trait AsyncQueueReceiver {
+    /// The Elements stored in the Queue
+    type Item;
+    /// The Future returned by [`dequeue`](AsyncQueueReceiver::dequeue)
+    type Dequeue<'queue>: Future<Output = Result<Self::Item, DequeueError>> + Unpin
+    where
+        Self: 'queue;
+
+    /// Attempts to dequeue an Element, without waiting for one to be
+    /// enqueued
+    fn try_dequeue(&mut self) -> Result<Self::Item, DequeueError>;
+
+    /// Waits for the next Element, resolving to `Err(DequeueError::Closed)`
+    /// once the Queue is closed and empty
+    fn dequeue(&mut self) -> Self::Dequeue<'_>;
+}
+
+/// A Receiver, that applies a Closure to every Element dequeued from the
+/// underlying Receiver
+pub struct MappedReceiver<R, F> {
+    receiver: R,
+    func: F,
+}
+
+impl<R, F> MappedReceiver<R, F> {
+    /// Wraps the given Receiver, applying the Closure to all its Elements
+    pub fn new(receiver: R, func: F) -> Self {
+        Self { receiver, func }
+    }
+
+    /// Gets a Reference to the underlying Receiver
+    pub fn get_ref(&self) -> &R {
+        &self.receiver
+    }
+
+    /// Gets a mutable Reference to the underlying Receiver, which can be
+    /// used to dequeue the Elements without applying the Closure
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.receiver
+    }
+
+    /// Returns the underlying Receiver
+    pub fn into_inner(self) -> R {
+        self.receiver
+    }
+}
+
+impl<R, F> Debug for MappedReceiver<R, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Mapped-Receiver ()")
+    }
+}
+
+impl<R, F, U> QueueReceiver for MappedReceiver<R, F>
+where
+    R: QueueReceiver,
+    F: FnMut(R::Item) -> U,
+{
+    type Item = U;
+
+    fn try_dequeue(&mut self) -> Result<U, DequeueError> {
+        self.receiver.try_dequeue().map(&mut self.func)
+    }
+
+    fn dequeue(&mut self) -> Option<U> {
+        self.receiver.dequeue().map(&mut self.func)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R, F, U> AsyncQueueReceiver for MappedReceiver<R, F>
+where
+    R: AsyncQueueReceiver,
+    F: FnMut(R::Item) -> U,
+{
+    type Item = U;
+    type Dequeue<'queue>
+        = MappedFuture<'queue, R::Dequeue<'queue>, F>
+    where
+        Self: 'queue;
+
+    fn try_dequeue(&mut self) -> Result<U, DequeueError> {
+        self.receiver.try_dequeue().map(&mut self.func)
+    }
+
+    fn dequeue(&mut self) -> Self::Dequeue<'_> {
+        MappedFuture {
+            dequeue: self.receiver.dequeue(),
+            func: &mut self.func,
+        }
+    }
+}
+
+/// The Dequeue-Future of a [`MappedReceiver`]
+///
+/// # Cancel Safety
+/// This Future is cancel safe, as long as the Dequeue-Future of the
+/// underlying Receiver is cancel safe
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct MappedFuture<'queue, D, F> {
+    dequeue: D,
+    func: &'queue mut F,
+}
+
+#[cfg(feature = "async")]
+impl<'queue, D, F, T, U> Future for MappedFuture<'queue, D, F>
+where
+    D: Future<Output = Result<T, DequeueError>> + Unpin,
+    F: FnMut(T) -> U,
+{
+    type Output = Result<U, DequeueError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        match Pin::new(&mut this.dequeue).poll(cx) {
+            Poll::Ready(result) => Poll::Ready(result.map(&mut this.func)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'queue, D, F> Debug for MappedFuture<'queue, D, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Mapped-Dequeue-Operation ()")
+    }
+}
+
+/// A Receiver, that only returns the Elements of the underlying Receiver,
+/// that match the Predicate, and drops all the other ones
+pub struct FilteredReceiver<R, F> {
+    receiver: R,
+    predicate: F,
+}
+
+impl<R, F> FilteredReceiver<R, F> {
+    /// Wraps the given Receiver, only returning the Elements that match the
+    /// Predicate
+    pub fn new(receiver: R, predicate: F) -> Self {
+        Self {
+            receiver,
+            predicate,
+        }
+    }
+
+    /// Gets a Reference to the underlying Receiver
+    pub fn get_ref(&self) -> &R {
+        &self.receiver
+    }
+
+    /// Gets a mutable Reference to the underlying Receiver, which can be
+    /// used to dequeue the Elements without filtering them
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.receiver
+    }
+
+    /// Returns the underlying Receiver
+    pub fn into_inner(self) -> R {
+        self.receiver
+    }
+}
+
+impl<R, F> Debug for FilteredReceiver<R, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Filtered-Receiver ()")
+    }
+}
+
+impl<R, F> QueueReceiver for FilteredReceiver<R, F>
+where
+    R: QueueReceiver,
+    F: FnMut(&R::Item) -> bool,
+{
+    type Item = R::Item;
+
+    /// Attempts to dequeue the next Element, that matches the Predicate,
+    /// dropping all the Elements before it, that dont match it
+    fn try_dequeue(&mut self) -> Result<R::Item, DequeueError> {
+        loop {
+            let data = self.receiver.try_dequeue()?;
+            if (self.predicate)(&data) {
+                return Ok(data);
+            }
+        }
+    }
+
+    fn dequeue(&mut self) -> Option<R::Item> {
+        loop {
+            let data = self.receiver.dequeue()?;
+            if (self.predicate)(&data) {
+                return Some(data);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<R, F> AsyncQueueReceiver for FilteredReceiver<R, F>
+where
+    R: AsyncQueueReceiver,
+    F: FnMut(&R::Item) -> bool,
+{
+    type Item = R::Item;
+    type Dequeue<'queue>
+        = FilteredFuture<'queue, R::Dequeue<'queue>, F>
+    where
+        Self: 'queue;
+
+    fn try_dequeue(&mut self) -> Result<R::Item, DequeueError> {
+        loop {
+            let data = self.receiver.try_dequeue()?;
+            if (self.predicate)(&data) {
+                return Ok(data);
+            }
+        }
+    }
+
+    fn dequeue(&mut self) -> Self::Dequeue<'_> {
+        FilteredFuture {
+            dequeue: self.receiver.dequeue(),
+            predicate: &mut self.predicate,
+        }
+    }
+}
+
+/// The Dequeue-Future of a [`FilteredReceiver`]
+///
+/// # Cancel Safety
+/// This Future is cancel safe, as long as the Dequeue-Future of the
+/// underlying Receiver is cancel safe. The Elements that dont match the
+/// Predicate are dropped either Way
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct FilteredFuture<'queue, D, F> {
+    dequeue: D,
+    predicate: &'queue mut F,
+}
+
+#[cfg(feature = "async")]
+impl<'queue, D, F, T> Future for FilteredFuture<'queue, D, F>
+where
+    D: Future<Output = Result<T, DequeueError>> + Unpin,
+    F: FnMut(&T) -> bool,
+{
+    type Output = Result<T, DequeueError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        loop {
+            // The Dequeue-Future is polled again after it resolved with an
+            // Element that was skipped, see the Docs of AsyncQueueReceiver
+            match Pin::new(&mut this.dequeue).poll(cx) {
+                Poll::Ready(Ok(data)) if !(this.predicate)(&data) => {}
+                other => return other,
+            };
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'queue, D, F> Debug for FilteredFuture<'queue, D, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Filtered-Dequeue-Operation ()")
+    }
+}
+
+/// Implements [`QueueReceiver`] for a Receiver, by forwarding to its own
+/// `try_dequeue` and blocking Dequeue-Operation
+macro_rules! impl_receiver {
+    ($receiver:ty, $item:ty, $dequeue:ident) => {
+        impl<T> $crate::queues::adapter::QueueReceiver for $receiver {
+            type Item = $item;
+
+            fn try_dequeue(&mut self) -> Result<$item, $crate::queues::DequeueError> {
+                <$receiver>::try_dequeue(self)
+            }
+
+            fn dequeue(&mut self) -> Option<$item> {
+                <$receiver>::$dequeue(self)
+            }
+        }
+    };
+    ($receiver:ty) => {
+        $crate::queues::adapter::impl_receiver!($receiver, T, dequeue);
+    };
+}
+pub(crate) use impl_receiver;
+
+/// Implements [`AsyncQueueReceiver`] for an async Receiver, by forwarding to
+/// its own `try_dequeue` and `dequeue` Operations
+#[cfg(feature = "async")]
+macro_rules! impl_async_receiver {
+    ($receiver:ty, $future:ident) => {
+        impl<T> $crate::queues::adapter::AsyncQueueReceiver for $receiver {
+            type Item = T;
+            type Dequeue<'queue>
+                = $future<'queue, T>
+            where
+                Self: 'queue;
+
+            fn try_dequeue(&mut self) -> Result<T, $crate::queues::DequeueError> {
+                <$receiver>::try_dequeue(self)
+            }
+
+            fn dequeue(&mut self) -> Self::Dequeue<'_> {
+                <$receiver>::dequeue(self)
+            }
+        }
+    };
+}
+#[cfg(feature = "async")]
+pub(crate) use impl_async_receiver;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::queues::spsc::unbounded;
+
+    #[test]
+    fn map_filter() {
+        let (rx, mut tx) = unbounded::queue::<usize>();
+        let rx = FilteredReceiver::new(rx, |x: &usize| *x > 1);
+        let mut rx = MappedReceiver::new(rx, |x: usize| x * 10);
+
+        for i in 0..4 {
+            tx.enqueue(i).unwrap();
+        }
+        assert_eq!(Ok(20), QueueReceiver::try_dequeue(&mut rx));
+        assert_eq!(Some(30), QueueReceiver::dequeue(&mut rx));
+        assert_eq!(
+            Err(DequeueError::Empty),
+            QueueReceiver::try_dequeue(&mut rx)
+        );
+
+        tx.enqueue(1).unwrap();
+        drop(tx);
+        assert_eq!(None, QueueReceiver::dequeue(&mut rx));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn async_map_filter() {
+        use crate::queues::mpsc::jiffy;
+
+        let (rx, tx) = jiffy::async_queue::<usize>();
+        let rx = FilteredReceiver::new(rx, |x: &usize| x % 2 == 1);
+        let mut rx = MappedReceiver::new(rx, |x: usize| x + 100);
+
+        let handle = tokio::spawn(async move {
+            let mut received = Vec::new();
+            while let Ok(data) = rx.dequeue().await {
+                received.push(data);
+            }
+            received
+        });
+
+        for i in 0..6 {
+            tx.enqueue(i).unwrap();
+            tokio::task::yield_now().await;
+        }
+        drop(tx);
+
+        assert_eq!(vec![101, 103, 105], handle.await.unwrap());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn async_filter_waits_again() {
+        use crate::queues::mpmc::bounded;
+
+        let (rx, tx) = bounded::async_queue::<usize>(4);
+        let mut rx = FilteredReceiver::new(rx, |x: &usize| *x == 13);
+
+        let handle = tokio::spawn(async move { rx.dequeue().await });
+
+        // The skipped Element needs the Future to wait again afterwards
+        tokio::task::yield_now().await;
+        tx.try_enqueue(12).unwrap();
+        tokio::task::yield_now().await;
+        tx.try_enqueue(13).unwrap();
+
+        let result = tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+            .await
+            .expect("The Receiver was not woken up")
+            .unwrap();
+        assert_eq!(Ok(13), result);
+    }
+}
@@ -0,0 +1,155 @@
+//! Batched Dequeue-Operations for the async Receivers.
+//!
+//! Awaiting every single Element on its own means that the Task has to be
+//! woken up and scheduled again by the Executor for every Element, which adds
+//! up at high Message-Rates. The `dequeue_many` Operations on the async
+//! Receivers instead wait for the first Element and then take all the other
+//! Elements that are already in the Queue, up to a given Maximum, before
+//! yielding back to the Caller.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::mpsc::jiffy;
+//! async fn demo() {
+//!     let (mut rx, tx) = jiffy::async_queue::<usize>();
+//!
+//!     for i in 0..5 {
+//!         tx.enqueue(i).unwrap();
+//!     }
+//!
+//!     assert_eq!(Ok(vec![0, 1, 2]), rx.dequeue_many(3).await);
+//!     assert_eq!(Ok(vec![3, 4]), rx.dequeue_many(3).await);
+//! }
+//!
+//! # fn main() {
+//! #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! #   rt.block_on(demo());
+//! # }
+//! ```
+
+use alloc::vec::Vec;
+use core::{
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::DequeueError;
+
+/// A Dequeue-Future, that can also take further Elements out of its Queue
+/// without waiting, once it resolved with the first one
+pub trait PendingDequeue: Future<Output = Result<Self::Item, DequeueError>> + Unpin {
+    /// The Elements stored in the Queue
+    type Item;
+
+    /// Attempts to dequeue another Element, without waiting for one to be
+    /// enqueued
+    fn try_dequeue_next(&mut self) -> Result<Self::Item, DequeueError>;
+}
+
+/// The Future returned by the `dequeue_many` Operations of the async
+/// Receivers.
+///
+/// # Behaviour
+/// This waits for the first Element, just like the normal Dequeue-Future,
+/// and then additionally takes all the Elements that are already stored in
+/// the Queue, until it has taken `max` Elements. It resolves to
+/// `Err(DequeueError::Closed)` only if the Queue was closed before any
+/// Element could be dequeued.
+///
+/// A Maximum of 0 resolves immediately with an empty Vec.
+///
+/// # Cancel Safety
+/// This Future is cancel safe, all the Elements are dequeued in the same
+/// Poll in which the Future resolves with them
+pub struct DequeueMany<F> {
+    dequeue: F,
+    max: usize,
+}
+
+impl<F> DequeueMany<F> {
+    /// Creates a new Batch-Dequeue, that waits using the given
+    /// Dequeue-Future and takes at most `max` Elements
+    pub fn new(dequeue: F, max: usize) -> Self {
+        Self { dequeue, max }
+    }
+}
+
+impl<F> Debug for DequeueMany<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Dequeue-Many ({})", self.max)
+    }
+}
+
+impl<F> Future for DequeueMany<F>
+where
+    F: PendingDequeue,
+{
+    type Output = Result<Vec<F::Item>, DequeueError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if this.max == 0 {
+            return Poll::Ready(Ok(Vec::new()));
+        }
+
+        let first = match Pin::new(&mut this.dequeue).poll(cx) {
+            Poll::Ready(Ok(first)) => first,
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        };
+
+        let mut result = Vec::with_capacity(this.max);
+        result.push(first);
+        while result.len() < this.max {
+            match this.dequeue.try_dequeue_next() {
+                Ok(data) => result.push(data),
+                Err(_) => break,
+            };
+        }
+
+        Poll::Ready(Ok(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Dequeue-Future over a fixed List of Elements
+    struct Items(Vec<usize>);
+
+    impl Future for Items {
+        type Output = Result<usize, DequeueError>;
+
+        fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+            match self.get_mut().try_dequeue_next() {
+                Err(DequeueError::Empty) => Poll::Pending,
+                result => Poll::Ready(result),
+            }
+        }
+    }
+
+    impl PendingDequeue for Items {
+        type Item = usize;
+
+        fn try_dequeue_next(&mut self) -> Result<usize, DequeueError> {
+            if self.0.is_empty() {
+                return Err(DequeueError::Empty);
+            }
+            Ok(self.0.remove(0))
+        }
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn takes_at_most_max() {
+        assert_eq!(
+            Ok(vec![1, 2]),
+            DequeueMany::new(Items(vec![1, 2, 3]), 2).await
+        );
+        assert_eq!(Ok(vec![1, 2]), DequeueMany::new(Items(vec![1, 2]), 4).await);
+        assert_eq!(Ok(vec![]), DequeueMany::new(Items(vec![1]), 0).await);
+    }
+}
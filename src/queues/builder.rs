@@ -0,0 +1,341 @@
+//! A uniform Builder to construct any of the Queues
+//!
+//! The [`Builder`] tracks the chosen Configuration in its Type, so that
+//! `build` and `build_async` are only available for Configurations that are
+//! actually backed by a Queue in this crate. Every Queue built this way
+//! returns its Halves in the same Order as the other Constructors in this
+//! crate, `(Receiver, Sender)`.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::Builder;
+//! let (mut rx, mut tx) = Builder::new().bounded(16).spsc().build::<u64>();
+//!
+//! tx.try_enqueue(13).unwrap();
+//! assert_eq!(Ok(13), rx.try_dequeue());
+//! ```
+//!
+//! # Available Configurations
+//! | Capacity      | Kind   | Queue                                             | Async |
+//! |---------------|--------|---------------------------------------------------|-------|
+//! | `bounded(n)`  | `spsc` | [`spsc::bounded`](super::spsc::bounded)           | Yes   |
+//! | `unbounded()` | `spsc` | [`spsc::unbounded`](super::spsc::unbounded)       | Yes   |
+//! | `unbounded()` | `mpsc` | [`mpsc::jiffy`](super::mpsc::jiffy)               | Yes   |
+//! | `bounded(n)`  | `mpmc` | [`mpmc::bounded::scq`](super::mpmc::bounded::scq) | No    |
+//! | `unbounded()` | `mpmc` | `mpmc::unbounded`, needs the `hyaline` Feature    | Yes   |
+
+use core::fmt::Debug;
+
+use super::{instrument::Metrics, mpmc, mpsc, spsc};
+
+/// Marker for a [`Builder`] that has not been configured with a Capacity yet
+#[derive(Debug)]
+pub struct NoCapacity;
+
+/// Marker for a [`Builder`] that builds a bounded Queue
+#[derive(Debug)]
+pub struct Bounded {
+    capacity: usize,
+}
+
+/// Marker for a [`Builder`] that builds an unbounded Queue
+#[derive(Debug)]
+pub struct Unbounded {
+    segment_size: Option<usize>,
+}
+
+/// Marker for a [`Builder`] that has not been configured with a Kind of
+/// Queue yet
+#[derive(Debug)]
+pub struct NoKind;
+
+/// Marker for a [`Builder`] that builds a Single-Producer Single-Consumer Queue
+#[derive(Debug)]
+pub struct Spsc;
+
+/// Marker for a [`Builder`] that builds a Multi-Producer Single-Consumer Queue
+#[derive(Debug)]
+pub struct Mpsc {
+    ordering: mpsc::jiffy::OrderingMode,
+}
+
+/// Marker for a [`Builder`] that builds a Multi-Producer Multi-Consumer Queue
+#[derive(Debug)]
+pub struct Mpmc;
+
+/// The Builder to configure and construct a Queue, see the
+/// [module-level documentation](self) for more Details
+pub struct Builder<C, K> {
+    capacity: C,
+    kind: K,
+    metrics: Metrics,
+}
+
+impl<C, K> Debug for Builder<C, K>
+where
+    C: Debug,
+    K: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Builder")
+            .field("capacity", &self.capacity)
+            .field("kind", &self.kind)
+            .finish()
+    }
+}
+
+impl Builder<NoCapacity, NoKind> {
+    /// Creates a new Builder without any Configuration
+    pub const fn new() -> Self {
+        Self {
+            capacity: NoCapacity,
+            kind: NoKind,
+            metrics: Metrics::none(),
+        }
+    }
+}
+
+impl Default for Builder<NoCapacity, NoKind> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C, K> Builder<C, K> {
+    fn with_capacity<N>(self, capacity: N) -> Builder<N, K> {
+        Builder {
+            capacity,
+            kind: self.kind,
+            metrics: self.metrics,
+        }
+    }
+
+    fn with_kind<N>(self, kind: N) -> Builder<C, N> {
+        Builder {
+            capacity: self.capacity,
+            kind,
+            metrics: self.metrics,
+        }
+    }
+
+    /// Builds a bounded Queue, that can hold up to `capacity` Elements at
+    /// once
+    ///
+    /// # Panics
+    /// If the `capacity` is 0
+    pub fn bounded(self, capacity: usize) -> Builder<Bounded, K> {
+        assert!(
+            capacity > 0,
+            "A bounded Queue needs a Capacity of at least 1"
+        );
+
+        self.with_capacity(Bounded { capacity })
+    }
+
+    /// Builds an unbounded Queue, that grows as needed
+    pub fn unbounded(self) -> Builder<Unbounded, K> {
+        self.with_capacity(Unbounded { segment_size: None })
+    }
+
+    /// Builds a Single-Producer Single-Consumer Queue
+    pub fn spsc(self) -> Builder<C, Spsc> {
+        self.with_kind(Spsc)
+    }
+
+    /// Builds a Multi-Producer Single-Consumer Queue
+    pub fn mpsc(self) -> Builder<C, Mpsc> {
+        self.with_kind(Mpsc {
+            ordering: mpsc::jiffy::OrderingMode::default(),
+        })
+    }
+
+    /// Builds a Multi-Producer Multi-Consumer Queue
+    pub fn mpmc(self) -> Builder<C, Mpmc> {
+        self.with_kind(Mpmc)
+    }
+
+    /// Reports all the Operations of the built Queue to the given
+    /// [`QueueMetrics`](super::metrics::QueueMetrics)
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    pub fn metrics(mut self, metrics: alloc::sync::Arc<dyn super::metrics::QueueMetrics>) -> Self {
+        self.metrics = Metrics::new(metrics);
+        self
+    }
+}
+
+impl Builder<Bounded, Spsc> {
+    /// Builds a [`spsc::bounded`] Queue
+    pub fn build<T>(
+        self,
+    ) -> (
+        spsc::bounded::BoundedReceiver<T>,
+        spsc::bounded::BoundedSender<T>,
+    ) {
+        spsc::bounded::queue_instrumented(self.capacity.capacity, self.metrics)
+    }
+
+    /// Builds an async [`spsc::bounded`] Queue
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn build_async<T>(
+        self,
+    ) -> (
+        spsc::bounded::AsyncBoundedReceiver<T>,
+        spsc::bounded::AsyncBoundedSender<T>,
+    ) {
+        let (rx, tx) = self.build();
+        spsc::bounded::wrap_queue(rx, tx)
+    }
+}
+
+impl Builder<Unbounded, Spsc> {
+    /// Sets the Number of Elements that are stored in each of the Buffers
+    /// that make up the Queue
+    ///
+    /// # Panics
+    /// If the `size` is 0
+    pub fn segment_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "The Segments need a Size of at least 1");
+
+        self.capacity.segment_size = Some(size);
+        self
+    }
+
+    /// Builds a [`spsc::unbounded`] Queue
+    pub fn build<T>(
+        self,
+    ) -> (
+        spsc::unbounded::UnboundedReceiver<T>,
+        spsc::unbounded::UnboundedSender<T>,
+    ) {
+        let segment_size = self
+            .capacity
+            .segment_size
+            .unwrap_or(spsc::unbounded::DEFAULT_BUFFER_SIZE);
+
+        spsc::unbounded::queue_instrumented(segment_size, self.metrics)
+    }
+
+    /// Builds an async [`spsc::unbounded`] Queue
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn build_async<T>(
+        self,
+    ) -> (
+        spsc::unbounded::AsyncUnboundedReceiver<T>,
+        spsc::unbounded::AsyncUnboundedSender<T>,
+    ) {
+        let (rx, tx) = self.build();
+        spsc::unbounded::wrap_queue(rx, tx)
+    }
+}
+
+impl Builder<Unbounded, Mpsc> {
+    /// Sets the Ordering guarantees the Queue should provide
+    pub fn ordering(mut self, ordering: mpsc::jiffy::OrderingMode) -> Self {
+        self.kind.ordering = ordering;
+        self
+    }
+
+    /// Builds a [`mpsc::jiffy`] Queue
+    pub fn build<T>(self) -> (mpsc::jiffy::Receiver<T>, mpsc::jiffy::Sender<T>) {
+        mpsc::jiffy::queue_instrumented(self.kind.ordering, self.metrics)
+    }
+
+    /// Builds an async [`mpsc::jiffy`] Queue
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn build_async<T>(self) -> (mpsc::jiffy::AsyncReceiver<T>, mpsc::jiffy::AsyncSender<T>) {
+        let (rx, tx) = self.build();
+        mpsc::jiffy::wrap_queue(rx, tx)
+    }
+}
+
+impl Builder<Bounded, Mpmc> {
+    /// Builds a [`mpmc::bounded::scq`] Queue
+    pub fn build<T>(
+        self,
+    ) -> (
+        mpmc::bounded::scq::Receiver<T>,
+        mpmc::bounded::scq::Sender<T>,
+    ) {
+        mpmc::bounded::scq::queue_instrumented(self.capacity.capacity, self.metrics)
+    }
+}
+
+#[cfg(feature = "hyaline")]
+impl Builder<Unbounded, Mpmc> {
+    /// Builds a [`mpmc::unbounded`] Queue
+    #[cfg_attr(docsrs, doc(cfg(feature = "hyaline")))]
+    pub fn build<T>(self) -> (mpmc::unbounded::Receiver<T>, mpmc::unbounded::Sender<T>) {
+        mpmc::unbounded::queue_instrumented(self.metrics)
+    }
+
+    /// Builds an async [`mpmc::unbounded`] Queue
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "hyaline", feature = "async"))))]
+    pub fn build_async<T>(
+        self,
+    ) -> (
+        mpmc::unbounded::AsyncReceiver<T>,
+        mpmc::unbounded::AsyncSender<T>,
+    ) {
+        let (rx, tx) = self.build();
+        mpmc::unbounded::wrap_queue(rx, tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spsc_unbounded_segment_size() {
+        let (mut rx, mut tx) = Builder::new().unbounded().spsc().segment_size(2).build();
+
+        for i in 0..10 {
+            tx.enqueue(i).unwrap();
+        }
+        for i in 0..10 {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+    }
+
+    #[test]
+    fn mpsc_ordering() {
+        let (mut rx, tx) = Builder::new()
+            .mpsc()
+            .unbounded()
+            .ordering(mpsc::jiffy::OrderingMode::StrictFifo)
+            .build();
+
+        tx.enqueue(13).unwrap();
+        assert_eq!(Ok(13), rx.try_dequeue());
+    }
+
+    #[test]
+    fn mpmc_bounded() {
+        let (rx, tx) = Builder::new().bounded(1).mpmc().build();
+
+        tx.try_enqueue(13).unwrap();
+        assert!(tx.try_enqueue(14).is_err());
+        assert_eq!(Ok(13), rx.try_dequeue());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bounded_zero_capacity() {
+        Builder::new().bounded(0);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn spsc_bounded_async() {
+        let (mut rx, mut tx) = Builder::new().bounded(4).spsc().build_async::<u64>();
+
+        tx.enqueue(13).await.unwrap();
+        assert_eq!(Ok(13), rx.dequeue().await);
+    }
+}
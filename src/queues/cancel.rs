@@ -0,0 +1,186 @@
+//! Cooperative Cancellation for the blocking Dequeue- and Enqueue-Operations.
+//!
+//! The blocking Operations only return once they succeeded or the Queue was
+//! closed, so the only Way to stop a Thread that is waiting on them used to
+//! be dropping the other Half of the Queue, which also drops all the Elements
+//! that are still stored in it.
+//!
+//! The `dequeue_cancellable` and `enqueue_cancellable` Operations instead
+//! take a [`CancelToken`] and stop waiting once it has been cancelled from
+//! any other Thread, returning [`DequeueError::Cancelled`] or
+//! [`EnqueueError::Cancelled`]. The Queue itself is left untouched, so all
+//! the remaining Elements can still be drained afterwards.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::{cancel::CancelToken, mpsc::jiffy, DequeueError};
+//! # use std::sync::Arc;
+//! let (mut rx, tx) = jiffy::queue::<usize>();
+//! let token = Arc::new(CancelToken::new());
+//!
+//! let consumer = {
+//!     let token = token.clone();
+//!     std::thread::spawn(move || {
+//!         let mut received = Vec::new();
+//!         while let Ok(data) = rx.dequeue_cancellable(&token) {
+//!             received.push(data);
+//!         }
+//!         (rx, received)
+//!     })
+//! };
+//!
+//! tx.enqueue(13).unwrap();
+//! token.cancel();
+//!
+//! let (mut rx, mut received) = consumer.join().unwrap();
+//! // The Queue is still intact, so nothing was lost
+//! while let Ok(data) = rx.try_dequeue() {
+//!     received.push(data);
+//! }
+//! assert_eq!(vec![13], received);
+//! ```
+
+use core::fmt::Debug;
+
+use crate::{sync::native::atomic, utils::Backoff};
+
+use super::{DequeueError, EnqueueError};
+
+/// A Flag used to cancel blocking Queue-Operations from another Thread, see
+/// the [`module-level documentation`](self) for more Details
+///
+/// A Token stays cancelled until it is [`reset`](Self::reset), so it can be
+/// shared by all the Workers that should be stopped together.
+pub struct CancelToken {
+    cancelled: atomic::AtomicBool,
+}
+
+impl CancelToken {
+    /// Creates a new Token that has not been cancelled yet
+    pub const fn new() -> Self {
+        Self {
+            cancelled: atomic::AtomicBool::new(false),
+        }
+    }
+
+    /// Cancels all the Operations that are currently waiting on this Token,
+    /// as well as all future ones, until the Token is reset again
+    pub fn cancel(&self) {
+        self.cancelled.store(true, atomic::Ordering::Release);
+    }
+
+    /// Checks if the Token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(atomic::Ordering::Acquire)
+    }
+
+    /// Resets the Token, so it can be used for new Operations again
+    pub fn reset(&self) {
+        self.cancelled.store(false, atomic::Ordering::Release);
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for CancelToken {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "CancelToken (cancelled: {})", self.is_cancelled())
+    }
+}
+
+/// Repeatedly attempts to dequeue an Element, using an exponential Backoff
+/// in between Attempts, until it succeeds, the Queue is closed or the Token
+/// has been cancelled.
+///
+/// The Attempt is always made before checking the Token, so an Element that
+/// is already available is returned even if the Token was cancelled.
+pub(crate) fn dequeue<T, F>(token: &CancelToken, mut try_dequeue: F) -> Result<T, DequeueError>
+where
+    F: FnMut() -> Result<T, DequeueError>,
+{
+    let backoff = Backoff::new();
+    loop {
+        match try_dequeue() {
+            Err(DequeueError::Empty) if !token.is_cancelled() => backoff.snooze(),
+            Err(DequeueError::Empty) => return Err(DequeueError::Cancelled),
+            result => return result,
+        };
+    }
+}
+
+/// Repeatedly attempts to enqueue the Data, using an exponential Backoff in
+/// between Attempts, while the Queue is full and until the Token has been
+/// cancelled, in which case the Data is returned together with
+/// [`EnqueueError::Cancelled`]
+pub(crate) fn enqueue<T, F>(
+    token: &CancelToken,
+    mut data: T,
+    mut try_enqueue: F,
+) -> Result<(), (T, EnqueueError)>
+where
+    F: FnMut(T) -> Result<(), (T, EnqueueError)>,
+{
+    let backoff = Backoff::new();
+    loop {
+        match try_enqueue(data) {
+            Err((d, EnqueueError::Full)) if !token.is_cancelled() => {
+                data = d;
+                backoff.snooze();
+            }
+            Err((d, EnqueueError::Full)) => return Err((d, EnqueueError::Cancelled)),
+            result => return result,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dequeue_cancelled() {
+        let token = CancelToken::new();
+        token.cancel();
+
+        assert_eq!(
+            Err(DequeueError::Cancelled),
+            dequeue(&token, || Err::<usize, _>(DequeueError::Empty))
+        );
+        // Available Elements are still returned
+        assert_eq!(Ok(13), dequeue(&token, || Ok(13)));
+
+        token.reset();
+        let mut attempts = 0;
+        assert_eq!(
+            Ok(13),
+            dequeue(&token, || {
+                attempts += 1;
+                if attempts < 3 {
+                    Err(DequeueError::Empty)
+                } else {
+                    Ok(13)
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn enqueue_cancelled() {
+        let token = CancelToken::new();
+        token.cancel();
+
+        assert_eq!(
+            Err((13, EnqueueError::Cancelled)),
+            enqueue(&token, 13, |d| Err((d, EnqueueError::Full)))
+        );
+        assert_eq!(
+            Err((13, EnqueueError::Closed)),
+            enqueue(&token, 13, |d| Err((d, EnqueueError::Closed)))
+        );
+        assert_eq!(Ok(()), enqueue(&token, 13, |_| Ok(())));
+    }
+}
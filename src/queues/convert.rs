@@ -0,0 +1,57 @@
+//! The shared State that allows the Halves of a Queue to be converted between
+//! their sync and async Variants at any Time.
+//!
+//! Every Side of a Queue, on which an async Task can wait, keeps track of the
+//! Number of Handles on the other Side that are currently sync and therefore
+//! will not wake it up. As long as there are any, a waiting Task falls back
+//! to polling the Queue cooperatively, by waking itself right away, so it
+//! never waits forever on a Notification that will not come.
+//!
+//! Converting a Handle wakes up the waiting Task on the other Side, so it
+//! observes the new Number of sync Handles in its next Poll.
+
+use crate::sync::native::atomic;
+
+/// The Number of sync Handles on one Side of a Queue
+pub(crate) struct SyncPeers {
+    count: atomic::AtomicUsize,
+}
+
+impl SyncPeers {
+    /// Creates the State for the given Number of sync Handles
+    pub fn new(count: usize) -> Self {
+        Self {
+            count: atomic::AtomicUsize::new(count),
+        }
+    }
+
+    /// Registers a Handle that became sync, the waiting Task on the other
+    /// Side has to be woken up afterwards
+    pub fn add(&self) {
+        self.count.fetch_add(1, atomic::Ordering::SeqCst);
+        // Pairs with the Fence in `any`
+        atomic::fence(atomic::Ordering::SeqCst);
+    }
+
+    /// Unregisters a Handle that became async, the waiting Task on the other
+    /// Side has to be woken up afterwards
+    pub fn remove(&self) {
+        self.count.fetch_sub(1, atomic::Ordering::SeqCst);
+        // Pairs with the Fence in `any`
+        atomic::fence(atomic::Ordering::SeqCst);
+    }
+
+    /// Checks if there are currently any sync Handles.
+    ///
+    /// A waiting Task has to call this once before checking the Queue, as
+    /// the Elements enqueued by a sync Handle before it was converted are
+    /// only guaranteed to be visible afterwards, and once more after
+    /// registering its Waker, as a Handle may have become sync in the mean
+    /// Time. If either returns `true`, it has to poll again on its own.
+    pub fn any(&self) -> bool {
+        // Pairs with the Fences in `add` and `remove`, so that either we see
+        // the updated Count or the converted Handle sees our Waker
+        atomic::fence(atomic::Ordering::SeqCst);
+        self.count.load(atomic::Ordering::SeqCst) > 0
+    }
+}
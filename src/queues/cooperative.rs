@@ -0,0 +1,343 @@
+//! Async-friendly Variants of the blocking Queue-Operations.
+//!
+//! The blocking `dequeue`/`enqueue` Operations on the synchronous Queues
+//! simply spin, and eventually yield the Thread, until they succeed. Calling
+//! them from async Code blocks the Executor-Thread for as long as they wait,
+//! which starves all the other Tasks scheduled on it.
+//!
+//! The cooperative Variants instead only spin for a short bounded Time in
+//! every Poll and then yield back to the Executor, by waking their own Task
+//! and returning `Poll::Pending`, which works with any Runtime. This still
+//! keeps polling the Queue, so the dedicated async Queues, which are woken
+//! up once an Element is available, should be preferred if the Queue is
+//! going to be waited on a lot.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::mpsc::jiffy;
+//! async fn demo() {
+//!     let (mut rx, tx) = jiffy::queue::<usize>();
+//!
+//!     tx.enqueue(13).unwrap();
+//!     assert_eq!(Some(13), rx.dequeue_cooperative().await);
+//!
+//!     drop(tx);
+//!     assert_eq!(None, rx.dequeue_cooperative().await);
+//! }
+//!
+//! # fn main() {
+//! #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! #   rt.block_on(demo());
+//! # }
+//! ```
+
+use core::{
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{DequeueError, EnqueueError};
+use crate::utils::Backoff;
+
+/// The Number of Attempts in a single Poll, with an exponential Backoff in
+/// between, before yielding back to the Executor
+const SPIN_ATTEMPTS: u32 = 7;
+
+/// Yields back to the Executor, by waking the current Task right away and
+/// returning `Poll::Pending` once.
+///
+/// This works with any Runtime, as it only relies on the Waker of the Task.
+///
+/// # Example
+/// ```
+/// # use nolock::queues::cooperative;
+/// async fn demo() {
+///     cooperative::yield_now().await;
+/// }
+/// # fn main() {
+/// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// #   rt.block_on(demo());
+/// # }
+/// ```
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+/// The Future returned by [`yield_now`]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+impl Debug for YieldNow {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "YieldNow ()")
+    }
+}
+
+/// Creates a Future, that repeatedly calls `try_dequeue` until it returns an
+/// Element or [`DequeueError::Closed`], while yielding back to the Executor
+/// in between.
+///
+/// This is used to implement the `dequeue_cooperative` Operations on the
+/// Queues, but can also be used for any other Queue.
+///
+/// # Behaviour
+/// The Future resolves to `Some(data)` once an Element was dequeued or to
+/// `None` once the Queue is closed, just like the blocking `dequeue`
+/// Operations.
+///
+/// # Example
+/// ```
+/// # use nolock::queues::{cooperative, spsc::unbounded};
+/// async fn demo() {
+///     let (mut rx, mut tx) = unbounded::queue::<usize>();
+///
+///     tx.enqueue(13).unwrap();
+///     assert_eq!(Some(13), cooperative::dequeue(|| rx.try_dequeue()).await);
+/// }
+/// # fn main() {
+/// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// #   rt.block_on(demo());
+/// # }
+/// ```
+pub fn dequeue<T, F>(try_dequeue: F) -> CooperativeDequeue<F>
+where
+    F: FnMut() -> Result<T, DequeueError>,
+{
+    CooperativeDequeue { try_dequeue }
+}
+
+/// The Future returned by [`dequeue`] and the `dequeue_cooperative`
+/// Operations
+///
+/// # Cancel Safety
+/// This Future is cancel safe, as long as the `try_dequeue` Operation only
+/// removes an Element when it returns it
+pub struct CooperativeDequeue<F> {
+    try_dequeue: F,
+}
+
+impl<F> Unpin for CooperativeDequeue<F> {}
+
+impl<T, F> Future for CooperativeDequeue<F>
+where
+    F: FnMut() -> Result<T, DequeueError>,
+{
+    type Output = Option<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let backoff = Backoff::new();
+        for _ in 0..SPIN_ATTEMPTS {
+            match (self.try_dequeue)() {
+                Ok(data) => return Poll::Ready(Some(data)),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return Poll::Ready(None),
+                Err(DequeueError::Empty) => backoff.spin(),
+            };
+        }
+
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+impl<F> Debug for CooperativeDequeue<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "CooperativeDequeue ()")
+    }
+}
+
+/// Creates a Future, that repeatedly calls `try_enqueue` with the Data
+/// until it succeeds or fails with an Error other than
+/// [`EnqueueError::Full`], while yielding back to the Executor in between.
+///
+/// This is used to implement the `enqueue_cooperative` Operations on the
+/// Queues, but can also be used for any other Queue.
+///
+/// # Example
+/// ```
+/// # use nolock::queues::{cooperative, spsc::bounded};
+/// async fn demo() {
+///     let (mut rx, mut tx) = bounded::queue::<usize>(1);
+///
+///     cooperative::enqueue(13, |d| tx.try_enqueue(d)).await.unwrap();
+///     assert_eq!(Ok(13), rx.try_dequeue());
+/// }
+/// # fn main() {
+/// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// #   rt.block_on(demo());
+/// # }
+/// ```
+pub fn enqueue<T, F>(data: T, try_enqueue: F) -> CooperativeEnqueue<T, F>
+where
+    F: FnMut(T) -> Result<(), (T, EnqueueError)>,
+{
+    CooperativeEnqueue {
+        data: Some(data),
+        try_enqueue,
+    }
+}
+
+/// The Future returned by [`enqueue`] and the `enqueue_cooperative`
+/// Operations
+///
+/// # Cancel Safety
+/// Dropping the Future before it resolved drops the Data as well, use
+/// [`into_inner`](Self::into_inner) to get it back instead
+pub struct CooperativeEnqueue<T, F> {
+    data: Option<T>,
+    try_enqueue: F,
+}
+
+impl<T, F> CooperativeEnqueue<T, F> {
+    /// Consumes the Future and returns the Data, if it was not already
+    /// enqueued
+    pub fn into_inner(mut self) -> Option<T> {
+        self.data.take()
+    }
+}
+
+impl<T, F> Unpin for CooperativeEnqueue<T, F> {}
+
+impl<T, F> Future for CooperativeEnqueue<T, F>
+where
+    F: FnMut(T) -> Result<(), (T, EnqueueError)>,
+{
+    type Output = Result<(), (T, EnqueueError)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        let backoff = Backoff::new();
+        for _ in 0..SPIN_ATTEMPTS {
+            let data = this
+                .data
+                .take()
+                .expect("The Future should not be polled after it resolved");
+
+            match (this.try_enqueue)(data) {
+                Ok(()) => return Poll::Ready(Ok(())),
+                Err((data, EnqueueError::Full)) => {
+                    this.data = Some(data);
+                    backoff.spin();
+                }
+                Err(e) => return Poll::Ready(Err(e)),
+            };
+        }
+
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+impl<T, F> Debug for CooperativeEnqueue<T, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "CooperativeEnqueue ()")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::task::noop_waker;
+
+    #[test]
+    fn yield_now_once() {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut fut = yield_now();
+        assert!(Pin::new(&mut fut).poll(&mut cx).is_pending());
+        assert!(Pin::new(&mut fut).poll(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn dequeue_yields_while_empty() {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut calls = 0;
+        let mut fut = dequeue(|| {
+            calls += 1;
+            if calls > SPIN_ATTEMPTS {
+                Ok(13)
+            } else {
+                Err(DequeueError::Empty)
+            }
+        });
+
+        assert_eq!(Poll::Pending, Pin::new(&mut fut).poll(&mut cx));
+        assert_eq!(Poll::Ready(Some(13)), Pin::new(&mut fut).poll(&mut cx));
+    }
+
+    #[test]
+    fn dequeue_closed() {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut fut = dequeue(|| Err::<usize, _>(DequeueError::Closed));
+        assert_eq!(Poll::Ready(None), Pin::new(&mut fut).poll(&mut cx));
+    }
+
+    #[test]
+    fn enqueue_yields_while_full() {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut fut = enqueue(13, |d| Err((d, EnqueueError::Full)));
+        assert_eq!(Poll::Pending, Pin::new(&mut fut).poll(&mut cx));
+        assert_eq!(Some(13), fut.into_inner());
+
+        let mut fut = enqueue(13, |d| Err((d, EnqueueError::Closed)));
+        assert_eq!(
+            Poll::Ready(Err((13, EnqueueError::Closed))),
+            Pin::new(&mut fut).poll(&mut cx)
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn dequeue_does_not_starve_producer() {
+        let (mut rx, tx) = crate::queues::mpsc::jiffy::queue::<usize>();
+
+        // Both Tasks run on the same Thread, so the Producer only runs if the
+        // Consumer yields back to the Executor
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async move {
+                let consumer = tokio::task::spawn_local(async move {
+                    let mut received = 0;
+                    while let Some(value) = rx.dequeue_cooperative().await {
+                        assert_eq!(received, value);
+                        received += 1;
+                    }
+                    received
+                });
+                tokio::task::spawn_local(async move {
+                    for i in 0..100 {
+                        tx.enqueue(i).unwrap();
+                        yield_now().await;
+                    }
+                });
+
+                assert_eq!(100, consumer.await.unwrap());
+            })
+            .await;
+    }
+}
@@ -0,0 +1,507 @@
+//! A Delay-Queue, where every Element only becomes available to the Receiver
+//! once its Deadline has passed
+//!
+//! # Design
+//! The Producers insert their Elements, together with their Deadline, into a
+//! [`Jiffy-Queue`](super::mpsc::jiffy), so enqueuing stays lock-free and
+//! allocation is amortized just like on the underlying Queue.
+//! The Receiver then moves all the newly enqueued Elements into a Timing-Wheel
+//! that only it has access to, which means that the Wheel itself does not need
+//! any Synchronization. The Wheel divides the Time into Ticks of a fixed
+//! Resolution and every Slot of the Wheel stores the Elements of every Tick
+//! that maps onto it, so inserting an Element and finding the expired Elements
+//! of the current Tick are both cheap.
+//!
+//! Elements are never returned before their Deadline, but can be returned up
+//! to one Resolution later than their Deadline.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::delay;
+//! # use std::time::Duration;
+//! let (tx, mut rx) = delay::channel::<usize>();
+//!
+//! tx.enqueue_after(Duration::from_millis(5), 13).unwrap();
+//! tx.enqueue_after(Duration::from_millis(0), 14).unwrap();
+//!
+//! assert_eq!(Some(14), rx.dequeue());
+//! assert_eq!(Some(13), rx.dequeue());
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+use super::{mpsc::jiffy, DequeueError, EnqueueError};
+
+#[cfg(feature = "async")]
+mod async_queue;
+#[cfg(feature = "async")]
+pub use async_queue::NextExpiredFuture;
+
+/// The Resolution used by [`channel`]
+pub const DEFAULT_RESOLUTION: Duration = Duration::from_millis(1);
+/// The Number of Slots in the Timing-Wheel used by [`channel`]
+pub const DEFAULT_SLOTS: usize = 256;
+
+/// The Delay that is used instead of Delays, which are too large to be
+/// represented as an [`Instant`], and is practically never reached
+const FAR_FUTURE: Duration = Duration::from_secs(86400 * 365 * 30);
+
+/// A single Element together with its Deadline, as it is enqueued by the
+/// Producers
+struct Entry<T> {
+    deadline: Instant,
+    data: T,
+}
+
+/// The Timing-Wheel that is owned by the Receiver
+struct Wheel<T> {
+    /// The Point in Time at which Tick 0 starts
+    start: Instant,
+    /// The Length of a single Tick
+    resolution: Duration,
+    /// The next Tick that has not been processed yet
+    current: u64,
+    /// The Slots of the Wheel, every Element is stored together with the Tick
+    /// at which it expires
+    slots: Vec<Vec<(u64, T)>>,
+    /// The Number of Elements stored in the Slots
+    pending: usize,
+    /// The Elements that already expired but have not been returned yet
+    expired: VecDeque<T>,
+}
+
+impl<T> Wheel<T> {
+    fn new(start: Instant, resolution: Duration, slots: usize) -> Self {
+        Self {
+            start,
+            resolution,
+            current: 0,
+            slots: (0..slots).map(|_| Vec::new()).collect(),
+            pending: 0,
+            expired: VecDeque::new(),
+        }
+    }
+
+    /// The first Tick at whose Start the given Deadline has passed
+    fn deadline_tick(&self, deadline: Instant) -> u64 {
+        let nanos = deadline.saturating_duration_since(self.start).as_nanos();
+        let resolution = self.resolution.as_nanos();
+        nanos.div_ceil(resolution) as u64
+    }
+
+    /// The Tick that contains the given Point in Time
+    fn now_tick(&self, now: Instant) -> u64 {
+        let nanos = now.saturating_duration_since(self.start).as_nanos();
+        (nanos / self.resolution.as_nanos()) as u64
+    }
+
+    /// The Point in Time at which the given Tick starts
+    fn tick_start(&self, tick: u64) -> Instant {
+        self.start + Duration::from_nanos((tick as u128 * self.resolution.as_nanos()) as u64)
+    }
+
+    fn insert(&mut self, entry: Entry<T>) {
+        let tick = self.deadline_tick(entry.deadline);
+        if tick < self.current {
+            self.expired.push_back(entry.data);
+            return;
+        }
+
+        let index = (tick % self.slots.len() as u64) as usize;
+        self.slots[index].push((tick, entry.data));
+        self.pending += 1;
+    }
+
+    /// Moves all the Elements of the given Slot, that expire at or before
+    /// the given Tick, into `expired`
+    fn expire_slot(&mut self, index: usize, tick: u64, expired: &mut Vec<(u64, T)>) {
+        let entries = core::mem::take(&mut self.slots[index]);
+        for (entry_tick, data) in entries {
+            if entry_tick <= tick {
+                expired.push((entry_tick, data));
+                self.pending -= 1;
+            } else {
+                self.slots[index].push((entry_tick, data));
+            }
+        }
+    }
+
+    /// Processes all the Ticks up to and including the Tick of `now`
+    fn advance(&mut self, now: Instant) {
+        let now_tick = self.now_tick(now);
+        if now_tick < self.current {
+            return;
+        }
+
+        let mut expired = Vec::new();
+        let slot_count = self.slots.len() as u64;
+        if now_tick - self.current >= slot_count {
+            // Every Slot would be visited at least once, so a single Pass
+            // over all of them is enough, but the Elements then need to be
+            // sorted by their Tick afterwards
+            for index in 0..self.slots.len() {
+                self.expire_slot(index, now_tick, &mut expired);
+            }
+            expired.sort_by_key(|(tick, _)| *tick);
+        } else {
+            while self.current <= now_tick && self.pending > 0 {
+                let index = (self.current % slot_count) as usize;
+                self.expire_slot(index, self.current, &mut expired);
+                self.current += 1;
+            }
+        }
+
+        self.expired
+            .extend(expired.into_iter().map(|(_, data)| data));
+        self.current = now_tick + 1;
+    }
+
+    /// The Tick at which the next Element in the Wheel expires
+    fn next_tick(&self) -> Option<u64> {
+        if self.pending == 0 {
+            return None;
+        }
+
+        let slot_count = self.slots.len() as u64;
+        // All the remaining Elements expire at or after the current Tick, so
+        // the first Slot that contains an Element for its Tick in the
+        // current Rotation contains the next Element to expire
+        for offset in 0..slot_count {
+            let tick = self.current + offset;
+            let index = (tick % slot_count) as usize;
+            if self.slots[index].iter().any(|(t, _)| *t == tick) {
+                return Some(tick);
+            }
+        }
+
+        self.slots.iter().flatten().map(|(t, _)| *t).min()
+    }
+}
+
+/// The Sending-Half of a Delay-Queue, created by [`channel`]
+pub struct DelaySender<T> {
+    queue: jiffy::Sender<Entry<T>>,
+    /// Declared after the Queue, so that it is dropped after the Queue has
+    /// been closed
+    #[cfg(feature = "async")]
+    handle: async_queue::SenderHandle,
+}
+
+/// The Receiving-Half of a Delay-Queue, created by [`channel`]
+pub struct DelayReceiver<T> {
+    queue: jiffy::Receiver<Entry<T>>,
+    wheel: Wheel<T>,
+    #[cfg(feature = "async")]
+    timer: async_queue::Timer,
+}
+
+impl<T> Debug for DelaySender<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "DelaySender ()")
+    }
+}
+impl<T> Debug for DelayReceiver<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "DelayReceiver ()")
+    }
+}
+
+impl<T> DelaySender<T> {
+    /// Checks if the Queue has been closed by the Receiver
+    pub fn is_closed(&self) -> bool {
+        self.queue.is_closed()
+    }
+
+    /// Enqueues the Data, so that it can be dequeued once the `deadline` has
+    /// passed
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::delay;
+    /// # use std::time::{Duration, Instant};
+    /// let (tx, mut rx) = delay::channel::<usize>();
+    ///
+    /// tx.enqueue_at(Instant::now() + Duration::from_millis(2), 13).unwrap();
+    ///
+    /// assert_eq!(Some(13), rx.dequeue());
+    /// ```
+    pub fn enqueue_at(&self, deadline: Instant, data: T) -> Result<(), (T, EnqueueError)> {
+        self.queue
+            .enqueue(Entry { deadline, data })
+            .map_err(|(entry, e)| (entry.data, e))?;
+
+        #[cfg(feature = "async")]
+        self.handle.wake();
+
+        Ok(())
+    }
+
+    /// Enqueues the Data, so that it can be dequeued once the `delay` has
+    /// elapsed from now.
+    ///
+    /// Delays that are too large to be represented as an [`Instant`], like
+    /// [`Duration::MAX`], are treated as a Delay of roughly 30 Years
+    pub fn enqueue_after(&self, delay: Duration, data: T) -> Result<(), (T, EnqueueError)> {
+        let now = Instant::now();
+        let deadline = now.checked_add(delay).unwrap_or_else(|| now + FAR_FUTURE);
+
+        self.enqueue_at(deadline, data)
+    }
+}
+
+impl<T> DelayReceiver<T> {
+    /// Checks if the Queue has been closed by the Sender
+    ///
+    /// # Note
+    /// There might still be Elements left in the Queue, that have not
+    /// expired yet
+    pub fn is_closed(&self) -> bool {
+        self.queue.is_closed()
+    }
+
+    /// Moves all the newly enqueued Elements into the Wheel and returns
+    /// whether the Sender has closed the Queue
+    fn receive(&mut self) -> bool {
+        loop {
+            match self.queue.try_dequeue() {
+                Ok(entry) => self.wheel.insert(entry),
+                Err(DequeueError::Empty) => return false,
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return true,
+            }
+        }
+    }
+
+    /// Attempts to dequeue an Element whose Deadline has already passed
+    ///
+    /// # Returns
+    /// * `Ok(data)` if an Element has expired
+    /// * `Err(DequeueError::Empty)` if no Element has expired yet
+    /// * `Err(DequeueError::Closed)` if the Sender closed the Queue and no
+    ///   Elements are left in it
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::{delay, DequeueError};
+    /// # use std::time::Duration;
+    /// let (tx, mut rx) = delay::channel::<usize>();
+    ///
+    /// tx.enqueue_after(Duration::from_secs(60), 13).unwrap();
+    ///
+    /// assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    /// ```
+    pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        let closed = self.receive();
+        self.wheel.advance(Instant::now());
+
+        match self.wheel.expired.pop_front() {
+            Some(data) => Ok(data),
+            None if closed && self.wheel.pending == 0 => Err(DequeueError::Closed),
+            None => Err(DequeueError::Empty),
+        }
+    }
+
+    /// A blocking Dequeue, that waits until the next Element expires.
+    ///
+    /// The Thread is put to sleep for a single Resolution of the Queue between
+    /// Attempts, so this is not lock-free.
+    ///
+    /// # Returns
+    /// * `Some(data)` once an Element has expired
+    /// * `None` if the Sender closed the Queue and no Elements are left in it
+    pub fn dequeue(&mut self) -> Option<T> {
+        loop {
+            match self.try_dequeue() {
+                Ok(data) => return Some(data),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+                Err(DequeueError::Empty) => std::thread::sleep(self.wheel.resolution),
+            }
+        }
+    }
+
+    /// Returns the Point in Time at which the next Element will become
+    /// available, which is in the Past if there are already expired Elements
+    /// waiting to be dequeued
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::delay;
+    /// # use std::time::{Duration, Instant};
+    /// let (tx, mut rx) = delay::channel::<usize>();
+    /// assert_eq!(None, rx.next_deadline());
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(60);
+    /// tx.enqueue_at(deadline, 13).unwrap();
+    ///
+    /// assert!(rx.next_deadline().unwrap() >= deadline);
+    /// ```
+    pub fn next_deadline(&mut self) -> Option<Instant> {
+        self.receive();
+
+        if !self.wheel.expired.is_empty() {
+            return Some(self.wheel.tick_start(self.wheel.current.saturating_sub(1)));
+        }
+        self.wheel
+            .next_tick()
+            .map(|tick| self.wheel.tick_start(tick))
+    }
+
+    /// The Number of Elements that have not been dequeued yet, including the
+    /// ones that have not expired yet.
+    ///
+    /// This only counts the Elements that the Receiver has already seen and
+    /// might therefore miss Elements that are currently being enqueued
+    pub fn len(&mut self) -> usize {
+        self.receive();
+        self.wheel.pending + self.wheel.expired.len()
+    }
+
+    /// Checks if there are no Elements left in the Queue, see [`len`](Self::len)
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Creates a new Delay-Queue, with the [`DEFAULT_RESOLUTION`] and
+/// [`DEFAULT_SLOTS`], and returns the Halves as ([`DelaySender`],
+/// [`DelayReceiver`]), in the same Order as `std::sync::mpsc::channel`
+pub fn channel<T>() -> (DelaySender<T>, DelayReceiver<T>) {
+    channel_with_resolution(DEFAULT_RESOLUTION, DEFAULT_SLOTS)
+}
+
+/// Creates a new Delay-Queue, whose Timing-Wheel uses Ticks of the given
+/// `resolution` and has the given Number of `slots`, and returns the Halves
+/// as ([`DelaySender`], [`DelayReceiver`])
+///
+/// # Panics
+/// If the `resolution` or the Number of `slots` is 0
+///
+/// # Example
+/// ```
+/// # use nolock::queues::delay;
+/// # use std::time::Duration;
+/// let (tx, mut rx) = delay::channel_with_resolution::<usize>(Duration::from_micros(100), 1024);
+///
+/// tx.enqueue_after(Duration::from_millis(1), 13).unwrap();
+/// assert_eq!(Some(13), rx.dequeue());
+/// ```
+pub fn channel_with_resolution<T>(
+    resolution: Duration,
+    slots: usize,
+) -> (DelaySender<T>, DelayReceiver<T>) {
+    assert!(
+        resolution.as_nanos() > 0,
+        "The Resolution needs to be at least 1ns"
+    );
+    assert!(slots > 0, "The Wheel needs at least 1 Slot");
+
+    let (rx, tx) = jiffy::queue();
+    let start = Instant::now();
+
+    #[cfg(feature = "async")]
+    let shared = alloc::sync::Arc::new(async_queue::Shared::new(start));
+
+    (
+        DelaySender {
+            queue: tx,
+            #[cfg(feature = "async")]
+            handle: async_queue::SenderHandle::new(shared.clone()),
+        },
+        DelayReceiver {
+            queue: rx,
+            wheel: Wheel::new(start, resolution, slots),
+            #[cfg(feature = "async")]
+            timer: async_queue::Timer::new(shared),
+        },
+    )
+}
+
+crate::queues::adapter::impl_receiver!(DelayReceiver<T>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_before_deadline() {
+        let (tx, mut rx) = channel::<usize>();
+
+        tx.enqueue_after(Duration::from_millis(20), 13).unwrap();
+        let enqueued = Instant::now();
+
+        assert_eq!(Some(13), rx.dequeue());
+        assert!(enqueued.elapsed() >= Duration::from_millis(19));
+    }
+
+    #[test]
+    fn deadline_order() {
+        let (tx, mut rx) = channel::<usize>();
+
+        tx.enqueue_after(Duration::from_millis(30), 3).unwrap();
+        tx.enqueue_after(Duration::from_millis(10), 1).unwrap();
+        tx.enqueue_after(Duration::from_millis(20), 2).unwrap();
+
+        assert_eq!(Some(1), rx.dequeue());
+        assert_eq!(Some(2), rx.dequeue());
+        assert_eq!(Some(3), rx.dequeue());
+    }
+
+    #[test]
+    fn maximum_delay() {
+        let (tx, mut rx) = channel::<usize>();
+
+        tx.enqueue_after(Duration::MAX, 13).unwrap();
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+        assert!(rx.next_deadline().unwrap() > Instant::now() + Duration::from_secs(86400 * 365));
+    }
+
+    #[test]
+    fn past_deadline() {
+        let (tx, mut rx) = channel::<usize>();
+
+        let deadline = Instant::now();
+        std::thread::sleep(Duration::from_millis(2));
+
+        tx.enqueue_at(deadline, 13).unwrap();
+        assert_eq!(Ok(13), rx.try_dequeue());
+    }
+
+    #[test]
+    fn beyond_one_rotation() {
+        let (tx, mut rx) = channel_with_resolution::<usize>(Duration::from_millis(1), 4);
+
+        tx.enqueue_after(Duration::from_millis(10), 2).unwrap();
+        tx.enqueue_after(Duration::from_millis(2), 1).unwrap();
+
+        assert_eq!(Some(1), rx.dequeue());
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+        assert_eq!(Some(2), rx.dequeue());
+    }
+
+    #[test]
+    fn closed_after_remaining() {
+        let (tx, mut rx) = channel::<usize>();
+
+        tx.enqueue_after(Duration::from_millis(5), 13).unwrap();
+        drop(tx);
+
+        assert!(rx.is_closed());
+        assert_eq!(1, rx.len());
+        assert_eq!(Some(13), rx.dequeue());
+        assert_eq!(None, rx.dequeue());
+    }
+
+    #[test]
+    fn enqueue_closed() {
+        let (tx, rx) = channel::<usize>();
+        drop(rx);
+
+        assert_eq!(
+            Err((13, EnqueueError::Closed)),
+            tx.enqueue_after(Duration::from_millis(1), 13)
+        );
+    }
+}
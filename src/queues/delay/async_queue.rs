@@ -0,0 +1,266 @@
+use alloc::sync::Arc;
+use core::{fmt::Debug, future::Future, pin::Pin, task::Poll};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::utils::AtomicWaker;
+
+use crate::{
+    queues::DequeueError,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use super::DelayReceiver;
+
+/// Marks that there is currently no Deadline the Timer should wait for
+const NO_DEADLINE: u64 = u64::MAX;
+
+/// The State shared between the Sender, the Receiver and the Timer-Thread
+pub(crate) struct Shared {
+    /// The Point in Time from which the `deadline` is measured
+    start: Instant,
+    /// The Waker of the Receiver, that is currently waiting for the next
+    /// Element to expire
+    waker: AtomicWaker,
+    /// The Nanoseconds after `start` at which the Timer-Thread should wake up
+    /// the Receiver
+    deadline: AtomicU64,
+    /// Whether the Receiver has been dropped and the Timer-Thread should exit
+    closed: AtomicBool,
+}
+
+impl Shared {
+    pub(crate) fn new(start: Instant) -> Self {
+        Self {
+            start,
+            waker: AtomicWaker::new(),
+            deadline: AtomicU64::new(NO_DEADLINE),
+            closed: AtomicBool::new(false),
+        }
+    }
+}
+
+/// The Sender's Handle to the shared State, which wakes up the Receiver once
+/// it is dropped.
+///
+/// This needs to be dropped after the underlying Queue has been closed, so
+/// that the Receiver can observe the closed Queue once it is woken up
+pub(crate) struct SenderHandle {
+    shared: Arc<Shared>,
+}
+
+impl SenderHandle {
+    pub(crate) fn new(shared: Arc<Shared>) -> Self {
+        Self { shared }
+    }
+
+    /// Wakes up the Receiver, if it is currently waiting
+    pub(crate) fn wake(&self) {
+        self.shared.waker.wake();
+    }
+}
+
+impl Drop for SenderHandle {
+    fn drop(&mut self) {
+        self.wake();
+    }
+}
+
+/// The Handle to the Timer-Thread, which wakes up the Receiver once the next
+/// Element expires.
+///
+/// The Thread is only started once the Receiver first waits for an Element
+/// asynchronously and exits once the Receiver is dropped
+pub(crate) struct Timer {
+    shared: Arc<Shared>,
+    thread: Option<thread::Thread>,
+}
+
+impl Timer {
+    pub(crate) fn new(shared: Arc<Shared>) -> Self {
+        Self {
+            shared,
+            thread: None,
+        }
+    }
+
+    /// Makes sure that the Receiver is woken up at the given Point in Time,
+    /// or not at all if `deadline` is `None`
+    fn schedule(&mut self, deadline: Option<Instant>) {
+        let raw = match deadline {
+            Some(d) => d.saturating_duration_since(self.shared.start).as_nanos() as u64,
+            None => NO_DEADLINE,
+        };
+        self.shared.deadline.store(raw, Ordering::Release);
+
+        match self.thread.as_ref() {
+            Some(t) => t.unpark(),
+            None if raw != NO_DEADLINE => {
+                let shared = self.shared.clone();
+                let handle = thread::Builder::new()
+                    .name("nolock-delay-timer".into())
+                    .spawn(move || run_timer(shared))
+                    .expect("Spawning the Timer-Thread");
+                self.thread = Some(handle.thread().clone());
+            }
+            None => {}
+        };
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        if let Some(t) = self.thread.as_ref() {
+            t.unpark();
+        }
+    }
+}
+
+/// The Loop run by the Timer-Thread
+fn run_timer(shared: Arc<Shared>) {
+    while !shared.closed.load(Ordering::Acquire) {
+        let raw = shared.deadline.load(Ordering::Acquire);
+        if raw == NO_DEADLINE {
+            thread::park();
+            continue;
+        }
+
+        let deadline = shared.start + Duration::from_nanos(raw);
+        let now = Instant::now();
+        if now < deadline {
+            thread::park_timeout(deadline - now);
+            continue;
+        }
+
+        // Only clear the Deadline if the Receiver did not schedule a new one
+        // in the mean time
+        let _ =
+            shared
+                .deadline
+                .compare_exchange(raw, NO_DEADLINE, Ordering::AcqRel, Ordering::Acquire);
+        shared.waker.wake();
+    }
+}
+
+/// The Future returned by [`next_expired`](DelayReceiver::next_expired)
+///
+/// # Cancel Safety
+/// This Future is cancel safe, no Element is lost if it is dropped before it
+/// completed
+pub struct NextExpiredFuture<'queue, T> {
+    receiver: &'queue mut DelayReceiver<T>,
+}
+
+impl<'queue, T> Debug for NextExpiredFuture<'queue, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "NextExpiredFuture ()")
+    }
+}
+
+impl<'queue, T> Future for NextExpiredFuture<'queue, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> Poll<Self::Output> {
+        let receiver = &mut self.get_mut().receiver;
+
+        match receiver.try_dequeue() {
+            Ok(data) => return Poll::Ready(Some(data)),
+            Err(DequeueError::Closed | DequeueError::Cancelled) => return Poll::Ready(None),
+            Err(DequeueError::Empty) => {}
+        };
+
+        receiver.timer.shared.waker.register(cx.waker());
+
+        // Check again, in case an Element was enqueued before the Waker was
+        // registered
+        match receiver.try_dequeue() {
+            Ok(data) => return Poll::Ready(Some(data)),
+            Err(DequeueError::Closed | DequeueError::Cancelled) => return Poll::Ready(None),
+            Err(DequeueError::Empty) => {}
+        };
+
+        let deadline = receiver.next_deadline();
+        receiver.timer.schedule(deadline);
+
+        Poll::Pending
+    }
+}
+
+impl<T> DelayReceiver<T> {
+    /// Waits asynchronously until the next Element expires.
+    ///
+    /// The Receiver is woken up either by a newly enqueued Element or by a
+    /// Timer-Thread, that is started the first Time this Future has to wait
+    /// for an Element to expire, which makes this independent of any
+    /// specific async Runtime
+    ///
+    /// # Returns
+    /// * `Some(data)` once an Element has expired
+    /// * `None` if the Sender closed the Queue and no Elements are left in it
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::delay;
+    /// # use std::time::Duration;
+    /// async fn demo() {
+    ///   let (tx, mut rx) = delay::channel::<usize>();
+    ///
+    ///   tx.enqueue_after(Duration::from_millis(5), 13).unwrap();
+    ///
+    ///   assert_eq!(Some(13), rx.next_expired().await);
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    pub fn next_expired(&mut self) -> NextExpiredFuture<'_, T> {
+        NextExpiredFuture { receiver: self }
+    }
+
+    /// Polls for the next expired Element, which is the low-level Operation
+    /// behind [`next_expired`](Self::next_expired), for Executors or
+    /// Combinators that drive the Queue manually instead of awaiting a
+    /// Future
+    pub fn poll_next_expired(&mut self, cx: &mut core::task::Context<'_>) -> Poll<Option<T>> {
+        Pin::new(&mut self.next_expired()).poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::channel;
+    use super::*;
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn next_expired() {
+        let (tx, mut rx) = channel::<usize>();
+
+        tx.enqueue_after(Duration::from_millis(20), 2).unwrap();
+        tx.enqueue_after(Duration::from_millis(10), 1).unwrap();
+
+        assert_eq!(Some(1), rx.next_expired().await);
+        assert_eq!(Some(2), rx.next_expired().await);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn next_expired_enqueued_later() {
+        let (tx, mut rx) = channel::<usize>();
+
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            tx.enqueue_after(Duration::from_millis(5), 13).unwrap();
+        });
+
+        assert_eq!(Some(13), rx.next_expired().await);
+        assert_eq!(None, rx.next_expired().await);
+        handle.join().unwrap();
+    }
+}
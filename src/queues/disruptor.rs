@@ -0,0 +1,695 @@
+//! A Disruptor, in the Style of the LMAX-Disruptor, which allows multiple
+//! Consumers to process every Element of a single preallocated Ring, in
+//! Pipelines of dependent Stages.
+//!
+//! # Sequences
+//! Every Element published on the Ring gets a unique Sequence-Number.
+//! Producers claim their Sequences using a single `fetch_add` and afterwards
+//! publish them individually, so multiple Producers can write their Elements
+//! at the same Time.
+//!
+//! # Consumers
+//! Unlike the other Queues, every [`Consumer`] sees every Element and keeps
+//! track of its own Cursor into the Ring. A Consumer can depend on other
+//! Consumers, in which case it only sees an Element once all of its
+//! Dependencies have processed it, which allows building multi-stage
+//! Pipelines over a single Buffer. The Producers only reuse a Slot of the
+//! Ring, once every Consumer is done with it.
+//!
+//! Consumers only get shared Access to the Elements, so Results that should
+//! be passed on to later Stages need to be stored using interior
+//! Mutability, like Atomics, in the Elements themselves.
+//!
+//! # Stalls
+//! The Producers wait for the slowest Consumer, so a Consumer that is
+//! dropped, or stops processing Elements, while Producers are still active
+//! stalls the Producers once the Ring wraps around.
+//!
+//! # Example
+//! ```rust
+//! # use nolock::queues::disruptor;
+//! # use std::sync::atomic::{AtomicU64, Ordering};
+//! #[derive(Default)]
+//! struct Event {
+//!     value: u64,
+//!     doubled: AtomicU64,
+//! }
+//!
+//! let mut builder = disruptor::Builder::<Event>::new(64);
+//! let mut double = builder.consumer(&[]);
+//! let mut sum = builder.consumer(&[&double]);
+//! let mut producer = builder.build();
+//!
+//! for i in 0..10 {
+//!     producer.publish(|event| event.value = i);
+//! }
+//!
+//! // The first Stage computes the doubled Values
+//! double.try_consume(|_, event| {
+//!     event.doubled.store(event.value * 2, Ordering::Release);
+//! });
+//!
+//! // The second Stage only sees the Events once the first one is done
+//! let mut total = 0;
+//! sum.try_consume(|_, event| total += event.doubled.load(Ordering::Acquire));
+//! assert_eq!(90, total);
+//! ```
+//!
+//! # Reference:
+//! * [LMAX Disruptor: High performance alternative to bounded queues for exchanging data between concurrent threads](https://lmax-exchange.github.io/disruptor/disruptor.html)
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{cell::UnsafeCell, fmt::Debug};
+
+use crate::sync::native::atomic;
+
+use crate::{
+    queues::DequeueError,
+    utils::{Backoff, CachePadded},
+};
+
+/// A single Slot in the Ring
+struct Slot<T> {
+    value: UnsafeCell<T>,
+    /// The Sequence-Number, plus one, of the last Element that was published
+    /// in this Slot, or 0 if there was none
+    published: atomic::AtomicUsize,
+}
+
+/// Publishes a claimed Slot to the Consumers once it is dropped
+struct PublishGuard<'a, T> {
+    slot: &'a Slot<T>,
+    sequence: usize,
+}
+
+impl<T> Drop for PublishGuard<'_, T> {
+    fn drop(&mut self) {
+        self.slot
+            .published
+            .store(self.sequence + 1, atomic::Ordering::Release);
+    }
+}
+
+/// The Position of a Consumer, which is the Sequence-Number of the next
+/// Element it will process
+type Cursor = CachePadded<atomic::AtomicUsize>;
+
+/// The State shared by all the Producers and Consumers
+struct Shared<T> {
+    slots: Box<[Slot<T>]>,
+    mask: usize,
+    /// The next Sequence-Number to be claimed by a Producer
+    claim: CachePadded<atomic::AtomicUsize>,
+    /// The Number of Producers that are still alive
+    producers: atomic::AtomicUsize,
+}
+
+// Safety:
+// The Elements are written by the Producer that claimed their Slot and then
+// read by multiple Consumers at the same Time, so they need to be both Send
+// and Sync
+unsafe impl<T> Send for Shared<T> where T: Send + Sync {}
+unsafe impl<T> Sync for Shared<T> where T: Send + Sync {}
+
+impl<T> Shared<T> {
+    fn slot(&self, sequence: usize) -> &Slot<T> {
+        // # Safety:
+        // The Mask keeps the Index in the Range `0..slots.len()`
+        unsafe { self.slots.get_unchecked(sequence & self.mask) }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.producers.load(atomic::Ordering::Acquire) == 0
+    }
+}
+
+/// Determines which Elements a Consumer is allowed to see
+enum Barrier {
+    /// Every Element that has been published by the Producers
+    Published,
+    /// Every Element that has been processed by all these Consumers
+    Consumers(Vec<Arc<Cursor>>),
+}
+
+/// Used to set up the Consumers of a Disruptor, before the Producer is
+/// created
+pub struct Builder<T> {
+    shared: Arc<Shared<T>>,
+    /// The Cursors of all the Consumers and whether they are still a Leaf,
+    /// meaning that no other Consumer depends on them
+    cursors: Vec<(Arc<Cursor>, bool)>,
+}
+
+impl<T> Builder<T>
+where
+    T: Default,
+{
+    /// Creates a new Builder for a Disruptor, whose Ring has the given
+    /// Capacity, rounded up to the next Power of two, and is filled with
+    /// the Default-Value of `T`
+    ///
+    /// # Panics
+    /// If the Capacity is 0
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "The Capacity of a Disruptor can not be 0");
+        let capacity = capacity.next_power_of_two();
+
+        let slots: Vec<_> = (0..capacity)
+            .map(|_| Slot {
+                value: UnsafeCell::new(T::default()),
+                published: atomic::AtomicUsize::new(0),
+            })
+            .collect();
+
+        Self {
+            shared: Arc::new(Shared {
+                slots: slots.into_boxed_slice(),
+                mask: capacity - 1,
+                claim: CachePadded::new(atomic::AtomicUsize::new(0)),
+                producers: atomic::AtomicUsize::new(1),
+            }),
+            cursors: Vec::new(),
+        }
+    }
+}
+
+impl<T> Builder<T> {
+    /// Returns the Capacity of the Ring
+    pub fn capacity(&self) -> usize {
+        self.shared.slots.len()
+    }
+
+    /// Adds a new Consumer, that only sees an Element once all the given
+    /// Consumers have processed it, or as soon as it is published if there
+    /// are no Dependencies
+    ///
+    /// # Panics
+    /// If any of the Dependencies belongs to a different Disruptor
+    pub fn consumer(&mut self, dependencies: &[&Consumer<T>]) -> Consumer<T> {
+        let barrier = if dependencies.is_empty() {
+            Barrier::Published
+        } else {
+            let cursors = dependencies
+                .iter()
+                .map(|dependency| {
+                    assert!(
+                        Arc::ptr_eq(&self.shared, &dependency.shared),
+                        "The Dependency belongs to a different Disruptor"
+                    );
+
+                    if let Some((_, leaf)) = self
+                        .cursors
+                        .iter_mut()
+                        .find(|(cursor, _)| Arc::ptr_eq(cursor, &dependency.cursor))
+                    {
+                        *leaf = false;
+                    }
+                    dependency.cursor.clone()
+                })
+                .collect();
+            Barrier::Consumers(cursors)
+        };
+
+        let cursor = Arc::new(CachePadded::new(atomic::AtomicUsize::new(0)));
+        self.cursors.push((cursor.clone(), true));
+
+        Consumer {
+            shared: self.shared.clone(),
+            cursor,
+            barrier,
+        }
+    }
+
+    /// Finishes the Setup and returns the first Producer, which can be
+    /// cloned to obtain more Producers.
+    ///
+    /// The Producers only wait for the Consumers that no other Consumer
+    /// depends on, as these are always the slowest ones
+    pub fn build(self) -> Producer<T> {
+        let gating = self
+            .cursors
+            .into_iter()
+            .filter(|(_, leaf)| *leaf)
+            .map(|(cursor, _)| cursor)
+            .collect::<Vec<_>>();
+
+        Producer {
+            shared: self.shared,
+            gating: gating.into(),
+            cached_limit: 0,
+        }
+    }
+}
+
+impl<T> Debug for Builder<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Builder ()")
+    }
+}
+
+/// A Producer that publishes Elements on the Ring of a Disruptor, created by
+/// [`Builder::build`] or by cloning another Producer
+pub struct Producer<T> {
+    shared: Arc<Shared<T>>,
+    /// The Cursors of the Consumers, that need to be done with a Slot before
+    /// it can be reused
+    gating: Arc<[Arc<Cursor>]>,
+    /// The last observed Sequence-Number up to which Slots can be claimed
+    cached_limit: usize,
+}
+
+impl<T> Producer<T> {
+    /// Returns the Sequence-Number up to which Slots can currently be
+    /// claimed, without overwriting an Element that has not been processed
+    /// by every Consumer yet
+    fn limit(&self) -> usize {
+        let capacity = self.shared.slots.len();
+        match self
+            .gating
+            .iter()
+            .map(|cursor| cursor.load(atomic::Ordering::Acquire))
+            .min()
+        {
+            Some(slowest) => slowest + capacity,
+            // Without any Consumers, every Slot can always be reused
+            None => usize::MAX,
+        }
+    }
+
+    /// Writes the Element using the given Function and publishes it to the
+    /// Consumers
+    fn write<F>(&self, sequence: usize, func: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        let slot = self.shared.slot(sequence);
+
+        // The Slot is published when the Guard is dropped, even if the
+        // Function panics, as the Consumers would otherwise stall forever
+        // behind the claimed Sequence
+        let _guard = PublishGuard { slot, sequence };
+
+        // # Safety:
+        // We claimed the Sequence and every Consumer is done with the
+        // previous Element in this Slot, so we have exclusive Access to it
+        func(unsafe { &mut *slot.value.get() });
+    }
+
+    /// Claims the next Slot on the Ring, waiting with a [`Backoff`] for the
+    /// Consumers if the Ring is full, lets the Function update the Element
+    /// in it and publishes it. Returns the Sequence-Number of the Element
+    ///
+    /// # Panics
+    /// If the Function panics, the Element is still published in whatever
+    /// State the Function left it in, so the Consumers do not stall behind
+    /// the claimed Sequence
+    pub fn publish<F>(&mut self, func: F) -> usize
+    where
+        F: FnOnce(&mut T),
+    {
+        let sequence = self.shared.claim.fetch_add(1, atomic::Ordering::AcqRel);
+
+        let backoff = Backoff::new();
+        while sequence >= self.cached_limit {
+            self.cached_limit = self.limit();
+            if sequence < self.cached_limit {
+                break;
+            }
+            backoff.snooze();
+        }
+
+        self.write(sequence, func);
+        sequence
+    }
+
+    /// Attempts to claim the next Slot on the Ring, like
+    /// [`publish`](Self::publish), but fails with `None` instead of waiting
+    /// if the Ring is currently full
+    ///
+    /// # Panics
+    /// Like [`publish`](Self::publish), the Element is still published if
+    /// the Function panics
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::disruptor;
+    /// let mut builder = disruptor::Builder::<u64>::new(2);
+    /// let mut consumer = builder.consumer(&[]);
+    /// let mut producer = builder.build();
+    ///
+    /// assert_eq!(Some(0), producer.try_publish(|value| *value = 13));
+    /// assert_eq!(Some(1), producer.try_publish(|value| *value = 14));
+    /// assert_eq!(None, producer.try_publish(|value| *value = 15));
+    ///
+    /// assert_eq!(Ok(2), consumer.try_consume(|_, _| {}));
+    /// assert_eq!(Some(2), producer.try_publish(|value| *value = 15));
+    /// ```
+    pub fn try_publish<F>(&mut self, func: F) -> Option<usize>
+    where
+        F: FnOnce(&mut T),
+    {
+        let mut sequence = self.shared.claim.load(atomic::Ordering::Acquire);
+        loop {
+            if sequence >= self.cached_limit {
+                self.cached_limit = self.limit();
+                if sequence >= self.cached_limit {
+                    return None;
+                }
+            }
+
+            match self.shared.claim.compare_exchange_weak(
+                sequence,
+                sequence + 1,
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(current) => sequence = current,
+            };
+        }
+
+        self.write(sequence, func);
+        Some(sequence)
+    }
+
+    /// Returns the Capacity of the Ring
+    pub fn capacity(&self) -> usize {
+        self.shared.slots.len()
+    }
+}
+
+impl<T> Clone for Producer<T> {
+    fn clone(&self) -> Self {
+        self.shared.producers.fetch_add(1, atomic::Ordering::AcqRel);
+
+        Self {
+            shared: self.shared.clone(),
+            gating: self.gating.clone(),
+            cached_limit: 0,
+        }
+    }
+}
+
+impl<T> Debug for Producer<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Producer ()")
+    }
+}
+
+impl<T> Drop for Producer<T> {
+    fn drop(&mut self) {
+        self.shared.producers.fetch_sub(1, atomic::Ordering::AcqRel);
+    }
+}
+
+/// A Consumer that processes every Element on the Ring of a Disruptor, once
+/// all its Dependencies are done with it, created by [`Builder::consumer`]
+pub struct Consumer<T> {
+    shared: Arc<Shared<T>>,
+    cursor: Arc<Cursor>,
+    barrier: Barrier,
+}
+
+impl<T> Consumer<T> {
+    /// Returns the Sequence-Number up to which Elements can currently be
+    /// processed by this Consumer
+    fn available(&self, next: usize) -> usize {
+        match &self.barrier {
+            Barrier::Published => {
+                let claimed = self.shared.claim.load(atomic::Ordering::Acquire);
+
+                // The Elements may be published out of Order by different
+                // Producers, so we can only process them up to the first
+                // one that has not been published yet
+                let mut available = next;
+                while available < claimed
+                    && self
+                        .shared
+                        .slot(available)
+                        .published
+                        .load(atomic::Ordering::Acquire)
+                        == available + 1
+                {
+                    available += 1;
+                }
+                available
+            }
+            Barrier::Consumers(cursors) => cursors
+                .iter()
+                .map(|cursor| cursor.load(atomic::Ordering::Acquire))
+                .min()
+                .unwrap_or(next),
+        }
+    }
+
+    /// Processes all the Elements that are currently available to this
+    /// Consumer, by calling the Function with the Sequence-Number and the
+    /// Element, and returns the Number of processed Elements.
+    ///
+    /// # Returns
+    /// * `Ok(count)` if at least one Element was processed
+    /// * `Err(DequeueError::Empty)` if no Element is available right now
+    /// * `Err(DequeueError::Closed)` if all the Producers have been dropped
+    ///   and this Consumer has processed every Element
+    pub fn try_consume<F>(&mut self, mut func: F) -> Result<usize, DequeueError>
+    where
+        F: FnMut(usize, &T),
+    {
+        let next = self.cursor.load(atomic::Ordering::Relaxed);
+
+        // Load the closed State first, so that every Element that was
+        // published before the last Producer was dropped is available
+        let closed = self.shared.is_closed();
+        let available = self.available(next);
+
+        if available == next {
+            if closed && next == self.shared.claim.load(atomic::Ordering::Acquire) {
+                return Err(DequeueError::Closed);
+            }
+            return Err(DequeueError::Empty);
+        }
+
+        for sequence in next..available {
+            // # Safety:
+            // The Element has been published and the Producers will not
+            // reuse the Slot before our Cursor moves past it
+            func(sequence, unsafe {
+                &*self.shared.slot(sequence).value.get()
+            });
+        }
+
+        self.cursor.store(available, atomic::Ordering::Release);
+        Ok(available - next)
+    }
+
+    /// Processes the available Elements, like
+    /// [`try_consume`](Self::try_consume), but waits with a [`Backoff`] until
+    /// there is at least one Element available. Returns `None` once all the
+    /// Producers have been dropped and every Element has been processed
+    pub fn consume<F>(&mut self, mut func: F) -> Option<usize>
+    where
+        F: FnMut(usize, &T),
+    {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_consume(&mut func) {
+                Ok(count) => return Some(count),
+                Err(DequeueError::Empty) => backoff.snooze(),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+            };
+        }
+    }
+
+    /// Returns the Sequence-Number of the next Element this Consumer will
+    /// process
+    pub fn cursor(&self) -> usize {
+        self.cursor.load(atomic::Ordering::Acquire)
+    }
+}
+
+impl<T> Debug for Consumer<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Consumer ()")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_stage() {
+        let mut builder = Builder::<usize>::new(4);
+        let mut consumer = builder.consumer(&[]);
+        let mut producer = builder.build();
+
+        assert_eq!(Err(DequeueError::Empty), consumer.try_consume(|_, _| {}));
+
+        for i in 0..3 {
+            assert_eq!(i, producer.publish(|value| *value = i * 10));
+        }
+
+        let mut seen = Vec::new();
+        assert_eq!(
+            Ok(3),
+            consumer.try_consume(|sequence, value| seen.push((sequence, *value)))
+        );
+        assert_eq!(vec![(0, 0), (1, 10), (2, 20)], seen);
+        assert_eq!(3, consumer.cursor());
+    }
+
+    #[test]
+    fn capacity_rounded() {
+        let builder = Builder::<usize>::new(5);
+        assert_eq!(8, builder.capacity());
+    }
+
+    #[test]
+    fn dependent_stage_waits() {
+        let mut builder = Builder::<usize>::new(4);
+        let mut first = builder.consumer(&[]);
+        let mut second = builder.consumer(&[&first]);
+        let mut producer = builder.build();
+
+        producer.publish(|value| *value = 13);
+        assert_eq!(Err(DequeueError::Empty), second.try_consume(|_, _| {}));
+
+        assert_eq!(Ok(1), first.try_consume(|_, _| {}));
+        assert_eq!(Ok(1), second.try_consume(|_, value| assert_eq!(13, *value)));
+    }
+
+    #[test]
+    fn producers_gated_by_leaf() {
+        let mut builder = Builder::<usize>::new(2);
+        let mut first = builder.consumer(&[]);
+        let mut second = builder.consumer(&[&first]);
+        let mut producer = builder.build();
+
+        producer.publish(|_| {});
+        producer.publish(|_| {});
+        assert_eq!(None, producer.try_publish(|_| {}));
+
+        // The first Stage is done, but the Slots are still in use by the
+        // second Stage
+        assert_eq!(Ok(2), first.try_consume(|_, _| {}));
+        assert_eq!(None, producer.try_publish(|_| {}));
+
+        assert_eq!(Ok(2), second.try_consume(|_, _| {}));
+        assert_eq!(Some(2), producer.try_publish(|_| {}));
+    }
+
+    #[test]
+    fn closed_after_last_producer() {
+        let mut builder = Builder::<usize>::new(4);
+        let mut consumer = builder.consumer(&[]);
+        let mut producer = builder.build();
+        let second = producer.clone();
+
+        producer.publish(|value| *value = 13);
+        drop(producer);
+        drop(second);
+
+        assert_eq!(Ok(1), consumer.try_consume(|_, _| {}));
+        assert_eq!(Err(DequeueError::Closed), consumer.try_consume(|_, _| {}));
+    }
+
+    #[test]
+    #[should_panic(expected = "different Disruptor")]
+    fn foreign_dependency() {
+        let mut other = Builder::<usize>::new(4);
+        let foreign = other.consumer(&[]);
+
+        let mut builder = Builder::<usize>::new(4);
+        builder.consumer(&[&foreign]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn panicking_publish() {
+        let mut builder = Builder::<usize>::new(4);
+        let mut consumer = builder.consumer(&[]);
+        let mut producer = builder.build();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            producer.publish(|value| {
+                *value = 13;
+                panic!("Publish failed");
+            });
+        }));
+        assert!(result.is_err());
+        producer.publish(|value| *value = 14);
+
+        // The Consumer does not stall behind the Sequence of the panicking
+        // Producer
+        let mut seen = Vec::new();
+        assert_eq!(
+            Ok(2),
+            consumer.try_consume(|sequence, value| seen.push((sequence, *value)))
+        );
+        assert_eq!(vec![(0, 13), (1, 14)], seen);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn pipeline_multiple_producers() {
+        use std::sync::atomic::AtomicUsize;
+
+        #[derive(Default)]
+        struct Event {
+            value: usize,
+            doubled: AtomicUsize,
+        }
+
+        let mut builder = Builder::<Event>::new(64);
+        let mut double = builder.consumer(&[]);
+        let mut count = builder.consumer(&[]);
+        let mut sum = builder.consumer(&[&double, &count]);
+        let producer = builder.build();
+
+        let producers: Vec<_> = (0..4)
+            .map(|_| {
+                let mut producer = producer.clone();
+                std::thread::spawn(move || {
+                    for i in 1..=1000 {
+                        producer.publish(|event| event.value = i);
+                    }
+                })
+            })
+            .collect();
+        drop(producer);
+
+        let double = std::thread::spawn(move || {
+            while double
+                .consume(|_, event| {
+                    event
+                        .doubled
+                        .store(event.value * 2, atomic::Ordering::Release)
+                })
+                .is_some()
+            {}
+        });
+        let count = std::thread::spawn(move || {
+            let mut total = 0;
+            while let Some(processed) = count.consume(|_, _| {}) {
+                total += processed;
+            }
+            total
+        });
+        let sum = std::thread::spawn(move || {
+            let mut total = 0;
+            while sum
+                .consume(|_, event| total += event.doubled.load(atomic::Ordering::Acquire))
+                .is_some()
+            {}
+            total
+        });
+
+        for handle in producers {
+            handle.join().unwrap();
+        }
+        double.join().unwrap();
+
+        assert_eq!(4000, count.join().unwrap());
+        assert_eq!(4 * 1000 * 1001, sum.join().unwrap());
+    }
+}
@@ -0,0 +1,275 @@
+//! Object-safe Traits for the Halves of the Queues, which allow choosing the
+//! Queue-Implementation at Runtime.
+//!
+//! Every Queue has its own concrete Sender- and Receiver-Types, which leak
+//! into every Signature that stores or passes them around. The Senders of
+//! all the Queues implement [`DynSender`] and the Receivers implement
+//! [`DynReceiver`], so they can instead be used as `Box<dyn DynSender<T>>`
+//! and `Box<dyn DynReceiver<T>>`, for example to pick the Queue based on a
+//! Configuration or to store the Senders of different Queues in the same
+//! Collection.
+//!
+//! The Priority- and Delay-Queues need additional Information for every
+//! Element, so their Senders do not implement [`DynSender`], and the Halves
+//! of the async Queues are not covered either, as their Operations return
+//! different Futures for every Queue.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::{dynamic::{DynReceiver, DynSender}, mpmc, mpsc, spsc};
+//! type Halves = (Box<dyn DynSender<usize> + Send>, Box<dyn DynReceiver<usize> + Send>);
+//!
+//! fn create(kind: &str) -> Halves {
+//!     match kind {
+//!         "spsc" => {
+//!             let (rx, tx) = spsc::bounded::queue(16);
+//!             (Box::new(tx), Box::new(rx))
+//!         }
+//!         "mpsc" => {
+//!             let (rx, tx) = mpsc::jiffy::queue();
+//!             (Box::new(tx), Box::new(rx))
+//!         }
+//!         _ => {
+//!             let (rx, tx) = mpmc::bounded::scq::queue(16);
+//!             (Box::new(tx), Box::new(rx))
+//!         }
+//!     }
+//! }
+//!
+//! for kind in ["spsc", "mpsc", "mpmc"] {
+//!     let (mut tx, mut rx) = create(kind);
+//!
+//!     tx.enqueue(13).unwrap();
+//!     assert_eq!(Ok(13), rx.try_dequeue());
+//! }
+//! ```
+
+use alloc::boxed::Box;
+
+use super::{adapter::QueueReceiver, DequeueError, EnqueueError};
+use crate::utils::Backoff;
+
+/// The object-safe Sending Half of any Queue
+pub trait DynSender<T> {
+    /// Attempts to enqueue the Data, without waiting for Space to become
+    /// available in bounded Queues
+    fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)>;
+
+    /// Enqueues the Data, blocking until there is Space available in bounded
+    /// Queues, and only fails if the Queue has been closed or the needed
+    /// Memory could not be allocated
+    fn enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)>;
+
+    /// Checks if the Queue has been closed by the Receiving Side
+    fn is_closed(&self) -> bool;
+}
+
+/// The object-safe Receiving Half of any Queue, which is implemented for
+/// every [`QueueReceiver`]
+pub trait DynReceiver<T> {
+    /// Attempts to dequeue an Element, without waiting for one to be
+    /// enqueued
+    fn try_dequeue(&mut self) -> Result<T, DequeueError>;
+
+    /// Dequeues an Element, blocking until one is enqueued, and returns
+    /// `None` once the Queue is closed and empty
+    fn dequeue(&mut self) -> Option<T>;
+}
+
+impl<T, S> DynSender<T> for Box<S>
+where
+    S: DynSender<T> + ?Sized,
+{
+    fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        (**self).try_enqueue(data)
+    }
+
+    fn enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        (**self).enqueue(data)
+    }
+
+    fn is_closed(&self) -> bool {
+        (**self).is_closed()
+    }
+}
+
+impl<R> DynReceiver<R::Item> for R
+where
+    R: QueueReceiver,
+{
+    fn try_dequeue(&mut self) -> Result<R::Item, DequeueError> {
+        QueueReceiver::try_dequeue(self)
+    }
+
+    fn dequeue(&mut self) -> Option<R::Item> {
+        QueueReceiver::dequeue(self)
+    }
+}
+
+// The boxed Receivers are QueueReceivers themselves, so they can also be
+// wrapped in the Adapters
+impl<'a, T> QueueReceiver for Box<dyn DynReceiver<T> + 'a> {
+    type Item = T;
+
+    fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        (**self).try_dequeue()
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        (**self).dequeue()
+    }
+}
+
+impl<'a, T> QueueReceiver for Box<dyn DynReceiver<T> + Send + 'a> {
+    type Item = T;
+
+    fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        (**self).try_dequeue()
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        (**self).dequeue()
+    }
+}
+
+/// Blocks until the Data could be enqueued, for the Senders that only provide
+/// a non-blocking Enqueue-Operation.
+///
+/// This is simply spinning and therefore not lock-free
+pub(crate) fn enqueue_spinning<T, F>(
+    mut try_enqueue: F,
+    mut data: T,
+) -> Result<(), (T, EnqueueError)>
+where
+    F: FnMut(T) -> Result<(), (T, EnqueueError)>,
+{
+    let backoff = Backoff::new();
+    loop {
+        match try_enqueue(data) {
+            Err((returned, EnqueueError::Full)) => {
+                data = returned;
+                backoff.snooze();
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Implements [`DynSender`] for a Sender, by forwarding to its own
+/// non-blocking and blocking Enqueue-Operations
+macro_rules! impl_sender {
+    ($sender:ty, $try_enqueue:ident, $enqueue:ident) => {
+        impl<T> $crate::queues::dynamic::DynSender<T> for $sender {
+            fn try_enqueue(&mut self, data: T) -> Result<(), (T, $crate::queues::EnqueueError)> {
+                <$sender>::$try_enqueue(self, data)
+            }
+
+            fn enqueue(&mut self, data: T) -> Result<(), (T, $crate::queues::EnqueueError)> {
+                <$sender>::$enqueue(self, data)
+            }
+
+            fn is_closed(&self) -> bool {
+                <$sender>::is_closed(self)
+            }
+        }
+    };
+    ($sender:ty) => {
+        $crate::queues::dynamic::impl_sender!($sender, enqueue, enqueue);
+    };
+}
+pub(crate) use impl_sender;
+
+#[cfg(test)]
+mod tests {
+    use super::{DynReceiver, DynSender};
+
+    use alloc::{boxed::Box, vec::Vec};
+
+    use crate::queues::{adapter::MappedReceiver, mpmc, mpsc, spsc, DequeueError, EnqueueError};
+
+    #[test]
+    fn heterogeneous_senders() {
+        let (mut spsc_rx, spsc_tx) = spsc::unbounded::queue::<usize>();
+        let (mut jiffy_rx, jiffy_tx) = mpsc::jiffy::queue::<usize>();
+        let (mpmc_rx, mpmc_tx) = mpmc::bounded::ncq::queue::<usize>(1);
+
+        let mut senders: Vec<Box<dyn DynSender<usize>>> =
+            alloc::vec![Box::new(spsc_tx), Box::new(jiffy_tx), Box::new(mpmc_tx)];
+        for (i, tx) in senders.iter_mut().enumerate() {
+            assert!(!tx.is_closed());
+            tx.enqueue(i).unwrap();
+        }
+
+        assert_eq!(Ok(0), spsc_rx.try_dequeue());
+        assert_eq!(Ok(1), jiffy_rx.try_dequeue());
+        assert_eq!(Ok(2), mpmc_rx.try_dequeue());
+
+        // The bounded Queue can be full
+        senders[2].try_enqueue(3).unwrap();
+        assert_eq!(Err((4, EnqueueError::Full)), senders[2].try_enqueue(4));
+
+        drop(mpmc_rx);
+        assert!(senders[2].is_closed());
+        assert_eq!(Err((5, EnqueueError::Closed)), senders[2].enqueue(5));
+    }
+
+    #[test]
+    fn all_senders() {
+        let (mut const_rx, const_tx) = spsc::bounded::const_queue::<usize, 4>();
+        let (mut overwriting_rx, overwriting_tx) = spsc::bounded::overwriting_queue::<usize>(1);
+        let (bounded_tx, mut bounded_rx) = mpsc::jiffy::bounded::channel::<usize>(1);
+        let (watch_tx, mut watch_rx) = crate::queues::watch::channel::<usize>();
+
+        let mut senders: Vec<Box<dyn DynSender<usize>>> = alloc::vec![
+            Box::new(const_tx),
+            Box::new(overwriting_tx),
+            Box::new(bounded_tx),
+            Box::new(watch_tx),
+        ];
+        for tx in senders.iter_mut() {
+            tx.enqueue(1).unwrap();
+            // Only fails for the bounded jiffy Queue, as it is full
+            let _ = tx.try_enqueue(2);
+        }
+
+        assert_eq!(Ok(1), const_rx.try_dequeue());
+        assert_eq!(Ok(2), overwriting_rx.try_dequeue());
+        assert_eq!(Ok(1), bounded_rx.try_dequeue());
+        assert_eq!(Ok(2), watch_rx.try_dequeue());
+    }
+
+    #[test]
+    fn boxed_receiver() {
+        let (rx, mut tx) = spsc::bounded::queue::<usize>(4);
+        let mut rx: Box<dyn DynReceiver<usize>> = Box::new(rx);
+
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+        tx.enqueue(13).unwrap();
+        assert_eq!(Ok(13), rx.try_dequeue());
+
+        drop(tx);
+        assert_eq!(None, rx.dequeue());
+    }
+
+    #[test]
+    fn generic_over_boxes() {
+        fn forward<S, R>(tx: &mut S, rx: &mut R)
+        where
+            S: DynSender<usize>,
+            R: DynReceiver<usize>,
+        {
+            tx.try_enqueue(13).unwrap();
+            assert_eq!(Some(13), rx.dequeue());
+        }
+
+        let (rx, tx) = mpsc::jiffy::queue::<usize>();
+        let mut tx: Box<dyn DynSender<usize> + Send> = Box::new(tx);
+        let mut rx: Box<dyn DynReceiver<usize> + Send> = Box::new(rx);
+        forward(&mut tx, &mut rx);
+
+        // The boxed Receiver can also be used with the Adapters
+        let mut rx = MappedReceiver::new(rx, |x: usize| x * 2);
+        tx.enqueue(7).unwrap();
+        assert_eq!(Ok(14), rx.try_dequeue());
+    }
+}
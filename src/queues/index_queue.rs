@@ -0,0 +1,659 @@
+//! A bounded MPMC-Queue of Indices, based on the Scalable-Circular-Queue (SCQ)
+//! proposed in [the Paper](https://arxiv.org/pdf/1908.04511.pdf).
+//!
+//! This is the Building-Block used by the [`mpmc`](super::mpmc)-Queues to
+//! manage which Slots of their Data-Buffer are free or contain Data, but it is
+//! also useful on its own for building custom Structures, like Slot-Schedulers
+//! or Token-Buckets, that only need to pass around small Indices.
+//!
+//! # Semantics
+//! * An [`IndexQueue`] with a Capacity of `n` only accepts Indices in the
+//!   Range `0..n`
+//! * The Queue never holds more than `n` Indices at once. It is up to the User
+//!   to uphold this, usually by having every Index only exist once, either in
+//!   the Queue or owned by some Thread. Enqueuing more Indices than that will
+//!   not cause any memory unsafety, but may cause the Enqueue to spin forever
+//! * Once the Queue is [finalized](IndexQueue::finalize), all further Enqueue
+//!   Operations fail, but the Indices already in the Queue can still be
+//!   dequeued
+//!
+//! # Fairness
+//! An Enqueue claims a Slot and retries with the next one, if the Slot can
+//! not be used, so under heavy Contention a single Enqueuer could keep losing
+//! the Race for the Slots. Once an Enqueue failed too many Times in a Row, it
+//! registers itself as starving, which causes all the other Enqueuers to back
+//! off for a short bounded Time before claiming their next Slot, to let the
+//! starving Enqueuer catch up.
+//!
+//! # Example
+//! ```rust
+//! # use nolock::queues::index_queue::IndexQueue;
+//! // A Queue that can hold the Indices 0-9
+//! let queue = IndexQueue::new(10);
+//!
+//! queue.enqueue(3).unwrap();
+//! queue.enqueue(7).unwrap();
+//!
+//! assert_eq!(Some(3), queue.dequeue());
+//! assert_eq!(Some(7), queue.dequeue());
+//! assert_eq!(None, queue.dequeue());
+//! ```
+
+use crate::{
+    sync::atomic,
+    utils::{Backoff, Segment, Segments},
+};
+
+use super::EnqueueError;
+
+/// The Number of unusable Slots an Enqueue claims in a Row, before it is
+/// considered to be starving
+const STARVATION_LIMIT: usize = 16;
+/// The maximum Number of Times another Enqueuer backs off, while waiting for
+/// the starving Enqueuers to make Progress
+const STARVATION_BACKOFFS: usize = 7;
+
+mod entry_data;
+use entry_data::QueueEntryData;
+
+/// The Bit in the Tail that marks the Queue as finalized
+const FINALIZED: usize = 1usize << (usize::BITS - 1);
+
+/// A single Entry in the Queue
+#[derive(Debug)]
+struct QueueEntry(atomic::AtomicU64);
+
+impl QueueEntry {
+    /// Creates a new QueueEntry
+    pub fn new(invalid_index: u32) -> Self {
+        let data = QueueEntryData::new(true, 0, invalid_index);
+        Self(atomic::AtomicU64::new(data.into()))
+    }
+
+    /// Loads the underlying U64 into a valid QueueEntryData
+    pub fn load(&self, order: atomic::Ordering) -> QueueEntryData {
+        QueueEntryData::from(self.0.load(order))
+    }
+
+    /// Turns both `current` and `new` into u64's and then uses them for a
+    /// compare_exchange on the underlying Atomic-U64
+    pub fn cas<C, N>(
+        &self,
+        current: C,
+        new: N,
+        success: atomic::Ordering,
+        failure: atomic::Ordering,
+    ) -> Result<u64, u64>
+    where
+        C: Into<u64>,
+        N: Into<u64>,
+    {
+        self.0
+            .compare_exchange(current.into(), new.into(), success, failure)
+    }
+
+    /// Turns the given Value into a u64 and then stores that new Value into
+    /// the underlying Atomic-U64
+    pub fn store<N>(&self, new: N, order: atomic::Ordering)
+    where
+        N: Into<u64>,
+    {
+        self.0.store(new.into(), order)
+    }
+}
+
+/// A bounded lock-free Queue of Indices, see the
+/// [`module-level documentation`](self) for the exact Semantics
+#[derive(Debug)]
+pub struct IndexQueue {
+    /// The Number of usable Elements in the Queue
+    size: usize,
+    /// The Index used to mark an Entry as invalid
+    invalid_index: u32,
+    /// The underlying Buffer for all QueueEntries
+    entries: Segment<QueueEntry>,
+    /// The Head of the Queue
+    head: atomic::AtomicUsize,
+    /// The Tail of the Queue, the highest Bit is used to mark the Queue as
+    /// finalized
+    tail: atomic::AtomicUsize,
+    /// The current Threshold
+    threshold: atomic::AtomicIsize,
+    /// The Number of Enqueuers that are currently starving
+    starving: atomic::AtomicUsize,
+}
+
+impl IndexQueue {
+    /// Creates a new empty Queue that can hold the Indices `0..capacity`
+    ///
+    /// # Panics
+    /// If the `capacity` is 0 or too large for the Indices to be stored
+    /// internally, which is the case for anything above `u32::MAX / 2`
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::index_queue::IndexQueue;
+    /// let queue = IndexQueue::new(10);
+    ///
+    /// assert_eq!(10, queue.capacity());
+    /// ```
+    pub fn new(capacity: usize) -> Self {
+        Self::new_in(capacity, &Segments::default())
+    }
+
+    /// Creates a new empty Queue, like [`new`](Self::new), whose Buffer is
+    /// allocated using the given Segments
+    pub(crate) fn new_in(capacity: usize, segments: &Segments) -> Self {
+        assert!(capacity > 0, "The Capacity needs to be at least 1");
+        assert!(
+            capacity <= (u32::MAX / 2) as usize,
+            "The Capacity is too large"
+        );
+
+        // Calculate the invalid Index to use for this Queue
+        let invalid_index = (2 * capacity - 1) as u32;
+
+        // Create the Entries-Buffer
+        let entries = Segment::new_with(segments, 2 * capacity, || QueueEntry::new(invalid_index));
+
+        Self {
+            size: capacity,
+            invalid_index,
+            entries,
+            head: atomic::AtomicUsize::new(capacity * 2),
+            tail: atomic::AtomicUsize::new(capacity * 2),
+            threshold: atomic::AtomicIsize::new(-1),
+            starving: atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Creates a new Queue with the given Capacity that already contains all
+    /// the Indices `0..capacity` in ascending Order, which is the common
+    /// starting Point for a Queue of free Slots
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::index_queue::IndexQueue;
+    /// let queue = IndexQueue::new_full(2);
+    ///
+    /// assert_eq!(Some(0), queue.dequeue());
+    /// assert_eq!(Some(1), queue.dequeue());
+    /// assert_eq!(None, queue.dequeue());
+    /// ```
+    pub fn new_full(capacity: usize) -> Self {
+        Self::new_full_in(capacity, &Segments::default())
+    }
+
+    /// Creates a new full Queue, like [`new_full`](Self::new_full), whose
+    /// Buffer is allocated using the given Segments
+    pub(crate) fn new_full_in(capacity: usize, segments: &Segments) -> Self {
+        let queue = Self::new_in(capacity, segments);
+        for index in 0..capacity {
+            queue
+                .enqueue(index)
+                .expect("A newly created Queue is not finalized");
+        }
+        queue
+    }
+
+    /// The Number of Indices this Queue can hold, which is also the exclusive
+    /// upper Bound for the Indices that can be enqueued
+    pub fn capacity(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the approximate Number of Indices currently stored in the
+    /// Queue, based on the current Head and Tail.
+    ///
+    /// This is only a Snapshot and may already be outdated once it is
+    /// returned, if other Threads are accessing the Queue concurrently
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::index_queue::IndexQueue;
+    /// let queue = IndexQueue::new(10);
+    /// queue.enqueue(3).unwrap();
+    /// queue.enqueue(4).unwrap();
+    ///
+    /// assert_eq!(2, queue.len());
+    /// ```
+    pub fn len(&self) -> usize {
+        let head = self.head.load(atomic::Ordering::Acquire);
+        let tail = self.tail.load(atomic::Ordering::Acquire) & !FINALIZED;
+
+        // The Head may have overtaken the Tail, if Dequeuers found the Queue
+        // to be empty
+        tail.saturating_sub(head).min(self.size)
+    }
+
+    /// Checks if the Queue currently contains no Indices, see
+    /// [`len`](Self::len)
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Calculates the Cycle for a given Tail/Head index
+    fn cycle(raw: usize, capacity: usize) -> u32 {
+        (raw / (capacity * 2)) as u32
+    }
+
+    /// Moves the Tail forward to the Head, because Dequeuers have overtaken
+    /// it
+    fn catchup(&self, mut head: usize, mut tail: usize) {
+        loop {
+            // The Tail of a finalized Queue is never moved, as that would
+            // also clear the Finalized-Bit
+            if tail & FINALIZED != 0 {
+                return;
+            }
+
+            if self
+                .tail
+                .compare_exchange(
+                    tail,
+                    head,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                return;
+            }
+
+            head = self.head.load(atomic::Ordering::Acquire);
+            tail = self.tail.load(atomic::Ordering::Acquire);
+            if tail >= head {
+                return;
+            }
+        }
+    }
+
+    /// Resets the Threshold, which forces the next Dequeue Operations to
+    /// actually check the Entries of the Queue even if the Queue has
+    /// previously been detected as empty.
+    ///
+    /// This is needed by the unbounded Queue, as an Enqueue can be started
+    /// before and finish after an empty Dequeue, without updating the
+    /// Threshold in a way the Dequeuer will see
+    pub(crate) fn reset_threshold(&self) {
+        let thres_chk = (self.size * 3 - 1) as isize;
+        self.threshold.store(thres_chk, atomic::Ordering::Release);
+    }
+
+    /// Finalizes the Queue, after which no more Indices can be enqueued.
+    ///
+    /// Indices that are already in the Queue can still be dequeued as normal.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::index_queue::IndexQueue;
+    /// # use nolock::queues::EnqueueError;
+    /// let queue = IndexQueue::new(10);
+    /// queue.enqueue(3).unwrap();
+    ///
+    /// queue.finalize();
+    ///
+    /// assert_eq!(Err(EnqueueError::Closed), queue.enqueue(4));
+    /// assert_eq!(Some(3), queue.dequeue());
+    /// ```
+    pub fn finalize(&self) {
+        self.tail.fetch_or(FINALIZED, atomic::Ordering::SeqCst);
+    }
+
+    /// Checks if the Queue has been [finalized](Self::finalize)
+    pub fn is_finalized(&self) -> bool {
+        self.tail.load(atomic::Ordering::Acquire) & FINALIZED != 0
+    }
+
+    /// Backs off for a short bounded Time, while some other Enqueuers are
+    /// starving, to give them a Chance to claim a usable Slot
+    fn yield_to_starving(&self) {
+        let backoff = Backoff::new();
+        for _ in 0..STARVATION_BACKOFFS {
+            if self.starving.load(atomic::Ordering::Acquire) == 0 {
+                return;
+            }
+            backoff.spin();
+        }
+    }
+
+    /// Enqueues the given Index
+    ///
+    /// # Returns
+    /// * `Ok(())` if the Index was successfully enqueued
+    /// * `Err(EnqueueError::Closed)` if the Queue has been finalized
+    ///
+    /// # Panics
+    /// If the Index is not in the Range `0..capacity`
+    pub fn enqueue(&self, index: usize) -> Result<(), EnqueueError> {
+        assert!(
+            index < self.size,
+            "The Index needs to be smaller than the Capacity of the Queue"
+        );
+
+        let mut failed = 0;
+        loop {
+            let starving = failed >= STARVATION_LIMIT;
+            if !starving && self.starving.load(atomic::Ordering::Relaxed) > 0 {
+                self.yield_to_starving();
+            }
+
+            let tail = self.tail.fetch_add(1, atomic::Ordering::AcqRel);
+            if tail & FINALIZED != 0 {
+                if starving {
+                    self.starving.fetch_sub(1, atomic::Ordering::AcqRel);
+                }
+                return Err(EnqueueError::Closed);
+            }
+
+            let tail_cycle = Self::cycle(tail, self.size);
+            let j = tail % (self.size * 2);
+
+            let entry = self
+                .entries
+                .get(j)
+                .expect("The Index is always wrapped around the Length of the Entries");
+
+            loop {
+                let raw_entry = entry.load(atomic::Ordering::Acquire);
+                let entry_cycle = raw_entry.cycle();
+                let entry_index = raw_entry.index();
+
+                if entry_cycle < tail_cycle
+                    && entry_index == self.invalid_index
+                    && (raw_entry.is_safe() || self.head.load(atomic::Ordering::Acquire) <= tail)
+                {
+                    let new_value = QueueEntryData::new(true, tail_cycle, index as u32);
+                    if entry
+                        .cas(
+                            raw_entry,
+                            new_value,
+                            atomic::Ordering::AcqRel,
+                            atomic::Ordering::Relaxed,
+                        )
+                        .is_err()
+                    {
+                        continue;
+                    }
+
+                    let thres_chk = (self.size * 3 - 1) as isize;
+                    if self.threshold.load(atomic::Ordering::Acquire) != thres_chk {
+                        self.threshold.store(thres_chk, atomic::Ordering::Release);
+                    }
+
+                    if starving {
+                        self.starving.fetch_sub(1, atomic::Ordering::AcqRel);
+                    }
+                    return Ok(());
+                }
+                break;
+            }
+
+            // The claimed Slot could not be used
+            failed += 1;
+            if failed == STARVATION_LIMIT {
+                self.starving.fetch_add(1, atomic::Ordering::AcqRel);
+            }
+        }
+    }
+
+    /// Attempts to dequeue an Index from the Queue
+    ///
+    /// # Returns
+    /// * `Some(index)` if an Index was dequeued
+    /// * `None` if the Queue is empty
+    pub fn dequeue(&self) -> Option<usize> {
+        if self.threshold.load(atomic::Ordering::Acquire) < 0 {
+            return None;
+        }
+
+        loop {
+            let head = self.head.fetch_add(1, atomic::Ordering::AcqRel);
+            let head_cycle = Self::cycle(head, self.size);
+            let j = head % (self.size * 2);
+
+            let entry = self
+                .entries
+                .get(j)
+                .expect("The Index is always wrapped around the Length of the Entries");
+            loop {
+                let entry_data = entry.load(atomic::Ordering::Acquire);
+
+                let entry_cycle = entry_data.cycle();
+                let entry_index = entry_data.index();
+                let entry_safe = entry_data.is_safe();
+
+                if entry_cycle == head_cycle {
+                    entry.store(
+                        QueueEntryData::new(entry_safe, entry_cycle, self.invalid_index),
+                        atomic::Ordering::Release,
+                    );
+                    return Some(entry_index as usize);
+                }
+
+                let new = if entry_index == self.invalid_index {
+                    QueueEntryData::new(entry_safe, head_cycle, self.invalid_index)
+                } else {
+                    QueueEntryData::new(false, entry_cycle, entry_index)
+                };
+
+                if entry_cycle < head_cycle
+                    && entry
+                        .cas(
+                            entry_data,
+                            new,
+                            atomic::Ordering::AcqRel,
+                            atomic::Ordering::Relaxed,
+                        )
+                        .is_err()
+                {
+                    continue;
+                }
+
+                let raw_tail = self.tail.load(atomic::Ordering::Acquire);
+                if raw_tail & !FINALIZED <= head + 1 {
+                    self.catchup(head, raw_tail);
+                    self.threshold.fetch_add(-1, atomic::Ordering::AcqRel);
+                    return None;
+                }
+
+                if self.threshold.fetch_add(-1, atomic::Ordering::AcqRel) <= 0 {
+                    return None;
+                }
+
+                break;
+            }
+        }
+    }
+
+    /// Dequeues an Index from a [finalized](Self::finalize) Queue, which
+    /// unlike [`dequeue`](Self::dequeue) only returns `None` once every
+    /// Enqueue, that claimed its Slot before the Queue was finalized, has
+    /// either been dequeued or failed with [`EnqueueError::Closed`]
+    pub(crate) fn dequeue_finalized(&self) -> Option<usize> {
+        debug_assert!(self.is_finalized());
+
+        loop {
+            if let Some(index) = self.dequeue() {
+                return Some(index);
+            }
+
+            // Every Enqueue claimed a Slot before the current Tail, so once
+            // the Head has moved past it, the Slots of all of them have
+            // either been dequeued or invalidated, which makes the
+            // Enqueue fail
+            let head = self.head.load(atomic::Ordering::SeqCst);
+            let tail = self.tail.load(atomic::Ordering::SeqCst) & !FINALIZED;
+            if head >= tail {
+                return None;
+            }
+
+            // The Threshold may have run out, while some Enqueues were still
+            // in Progress
+            self.reset_threshold();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scq_new() {
+        IndexQueue::new(10);
+    }
+    #[test]
+    #[should_panic]
+    fn scq_new_zero_capacity() {
+        IndexQueue::new(0);
+    }
+    #[test]
+    fn scq_enqueue_single() {
+        let queue = IndexQueue::new(10);
+        assert_eq!(Ok(()), queue.enqueue(3));
+    }
+    #[test]
+    #[should_panic]
+    fn scq_enqueue_out_of_range() {
+        let queue = IndexQueue::new(10);
+        let _ = queue.enqueue(10);
+    }
+    #[test]
+    fn scq_enqueue_dequeue_single() {
+        let queue = IndexQueue::new(10);
+        assert_eq!(Ok(()), queue.enqueue(3));
+        assert_eq!(Some(3), queue.dequeue());
+    }
+    #[test]
+    fn scq_enqueue_dequeue_fill_multiple() {
+        let queue = IndexQueue::new(10);
+
+        for index in 0..(3 * 10) {
+            assert_eq!(Ok(()), queue.enqueue(index % 10));
+            assert_eq!(Some(index % 10), queue.dequeue());
+        }
+    }
+    #[test]
+    fn scq_new_full() {
+        let queue = IndexQueue::new_full(10);
+
+        for index in 0..10 {
+            assert_eq!(Some(index), queue.dequeue());
+        }
+        assert_eq!(None, queue.dequeue());
+    }
+
+    #[test]
+    fn scq_finalize() {
+        let queue = IndexQueue::new(10);
+        assert_eq!(Ok(()), queue.enqueue(1));
+
+        assert!(!queue.is_finalized());
+        queue.finalize();
+        assert!(queue.is_finalized());
+
+        assert_eq!(Err(EnqueueError::Closed), queue.enqueue(2));
+        assert_eq!(Some(1), queue.dequeue());
+        assert_eq!(None, queue.dequeue());
+        assert_eq!(None, queue.dequeue());
+    }
+
+    #[test]
+    fn scq_dequeue_finalized() {
+        let queue = IndexQueue::new(4);
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+        queue.finalize();
+
+        assert_eq!(Some(1), queue.dequeue_finalized());
+        assert_eq!(Some(2), queue.dequeue_finalized());
+        assert_eq!(None, queue.dequeue_finalized());
+
+        // The Dequeuers overtaking the Tail must not clear the Finalized-Bit
+        assert_eq!(None, queue.dequeue());
+        assert!(queue.is_finalized());
+        assert_eq!(Err(EnqueueError::Closed), queue.enqueue(3));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    #[cfg(feature = "std")]
+    fn scq_dequeue_finalized_concurrent() {
+        use alloc::sync::Arc;
+        use std::thread;
+
+        let queue = Arc::new(IndexQueue::new(8));
+        let producers: alloc::vec::Vec<_> = (0..2)
+            .map(|_| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    let mut enqueued = 0;
+                    while queue.enqueue(1).is_ok() {
+                        enqueued += 1;
+                        // Keep the Queue from ever becoming full
+                        while queue.len() > 4 && !queue.is_finalized() {
+                            thread::yield_now();
+                        }
+                    }
+                    enqueued
+                })
+            })
+            .collect();
+
+        let mut dequeued = 0;
+        for _ in 0..1000 {
+            if queue.dequeue().is_some() {
+                dequeued += 1;
+            }
+        }
+        queue.finalize();
+        while queue.dequeue_finalized().is_some() {
+            dequeued += 1;
+        }
+
+        let enqueued: usize = producers.into_iter().map(|p| p.join().unwrap()).sum();
+        assert_eq!(enqueued, dequeued);
+    }
+
+    #[test]
+    fn scq_dequeue_empty_after_finalize() {
+        let queue = IndexQueue::new(10);
+        queue.finalize();
+
+        assert_eq!(None, queue.dequeue());
+        assert_eq!(Err(EnqueueError::Closed), queue.enqueue(2));
+    }
+
+    #[test]
+    fn scq_enqueue_while_other_starving() {
+        let queue = IndexQueue::new(10);
+        queue.starving.store(1, atomic::Ordering::Relaxed);
+
+        // The Backoff is bounded, so the Enqueue still makes Progress
+        assert_eq!(Ok(()), queue.enqueue(3));
+        assert_eq!(Some(3), queue.dequeue());
+        assert_eq!(1, queue.starving.load(atomic::Ordering::Relaxed));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn scq_concurrent_starving_released() {
+        let queue = IndexQueue::new(4);
+
+        std::thread::scope(|scope| {
+            for index in 0..4 {
+                let queue = &queue;
+                scope.spawn(move || {
+                    for _ in 0..10_000 {
+                        queue.enqueue(index).unwrap();
+                        while queue.dequeue().is_none() {}
+                    }
+                });
+            }
+        });
+
+        assert_eq!(0, queue.starving.load(atomic::Ordering::Relaxed));
+        assert_eq!(None, queue.dequeue());
+    }
+}
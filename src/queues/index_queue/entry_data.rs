@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use core::fmt::Display;
 
 // Internal Storage
 //
@@ -31,8 +31,8 @@ impl QueueEntryData {
         (self.0 & 0xffffffff) as u32
     }
 
-    pub fn as_u64(self) -> u64 {
-        self.into()
+    pub fn as_u64(&self) -> u64 {
+        self.0
     }
 }
 impl From<u64> for QueueEntryData {
@@ -46,7 +46,7 @@ impl From<QueueEntryData> for u64 {
     }
 }
 impl Display for QueueEntryData {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "(IsSafe: {}, Cycle: {}, Index: {})",
@@ -0,0 +1,90 @@
+//! The internal Handle used by the Queues to call the [`QueueMetrics`] Hooks.
+//!
+//! Without the `metrics` Feature, the Handle is a zero-sized Type and all its
+//! Methods are empty, so the Hooks are compiled out entirely.
+
+#[cfg(feature = "metrics")]
+use alloc::sync::Arc;
+
+#[cfg(feature = "metrics")]
+use super::metrics::QueueMetrics;
+
+#[derive(Clone)]
+pub(crate) struct Metrics {
+    #[cfg(feature = "metrics")]
+    inner: Option<Arc<dyn QueueMetrics>>,
+}
+
+impl Metrics {
+    /// A Handle that does not report anything
+    pub const fn none() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            inner: None,
+        }
+    }
+
+    /// A Handle that reports to the given Metrics instance
+    #[cfg(feature = "metrics")]
+    pub fn new(metrics: Arc<dyn QueueMetrics>) -> Self {
+        Self {
+            inner: Some(metrics),
+        }
+    }
+
+    #[inline(always)]
+    pub fn enqueue(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(m) = self.inner.as_ref() {
+            m.on_enqueue();
+        }
+    }
+
+    #[inline(always)]
+    pub fn enqueue_many(&self, count: usize) {
+        #[cfg(feature = "metrics")]
+        if let Some(m) = self.inner.as_ref() {
+            for _ in 0..count {
+                m.on_enqueue();
+            }
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = count;
+    }
+
+    #[inline(always)]
+    pub fn dequeue(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(m) = self.inner.as_ref() {
+            m.on_dequeue();
+        }
+    }
+
+    #[inline(always)]
+    pub fn dequeue_many(&self, count: usize) {
+        #[cfg(feature = "metrics")]
+        if let Some(m) = self.inner.as_ref() {
+            for _ in 0..count {
+                m.on_dequeue();
+            }
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = count;
+    }
+
+    #[inline(always)]
+    pub fn full(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(m) = self.inner.as_ref() {
+            m.on_full();
+        }
+    }
+
+    #[inline(always)]
+    pub fn segment_alloc(&self) {
+        #[cfg(feature = "metrics")]
+        if let Some(m) = self.inner.as_ref() {
+            m.on_segment_alloc();
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Optional Instrumentation for the Queues
+//!
+//! The Queues can be created with a [`QueueMetrics`] instance, using their
+//! respective `queue_with_metrics` functions, which then gets notified about
+//! the Operations performed on the Queue. This allows you to collect Metrics
+//! about a Queue without having to wrap every single Call-Site yourself.
+//!
+//! When the `metrics` Feature is disabled, all the Hooks are compiled out
+//! completely and therefore have no Impact on the Performance of the Queues.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::metrics::Counters;
+//! # use nolock::queues::spsc::bounded;
+//! # use std::sync::Arc;
+//! let counters = Arc::new(Counters::new());
+//! let (mut rx, mut tx) = bounded::queue_with_metrics::<usize>(1, counters.clone());
+//!
+//! tx.try_enqueue(13).unwrap();
+//! assert!(tx.try_enqueue(14).is_err());
+//! assert_eq!(Ok(13), rx.try_dequeue());
+//!
+//! assert_eq!(1, counters.enqueued());
+//! assert_eq!(1, counters.dequeued());
+//! assert_eq!(1, counters.full());
+//! ```
+
+use core::fmt::Debug;
+
+use crate::sync::atomic;
+
+/// The Hooks that get called by an instrumented Queue.
+///
+/// All the Hooks are called in the hot Path of the Queue Operations, so they
+/// should be as cheap as possible, like simply incrementing a Counter.
+///
+/// Every Hook has an empty default Implementation, so you only need to
+/// implement the ones you are actually interested in.
+pub trait QueueMetrics: Send + Sync {
+    /// Called after an Item was successfully enqueued
+    fn on_enqueue(&self) {}
+
+    /// Called after an Item was successfully dequeued
+    fn on_dequeue(&self) {}
+
+    /// Called when an Item could not be enqueued, because the Queue was full.
+    ///
+    /// The blocking Enqueue-Operations retry until they succeed, so this
+    /// gets called for every failed Attempt they make.
+    fn on_full(&self) {}
+
+    /// Called when an unbounded Queue allocated a new Segment/Buffer to be
+    /// able to store more Items
+    fn on_segment_alloc(&self) {}
+}
+
+/// A simple [`QueueMetrics`] implementation, that simply counts the
+/// Number of times each Hook was called
+pub struct Counters {
+    enqueued: atomic::AtomicU64,
+    dequeued: atomic::AtomicU64,
+    full: atomic::AtomicU64,
+    segment_allocs: atomic::AtomicU64,
+}
+
+impl Counters {
+    /// Creates a new Set of Counters, all starting at 0
+    pub fn new() -> Self {
+        Self {
+            enqueued: atomic::AtomicU64::new(0),
+            dequeued: atomic::AtomicU64::new(0),
+            full: atomic::AtomicU64::new(0),
+            segment_allocs: atomic::AtomicU64::new(0),
+        }
+    }
+
+    /// The Number of Items that were enqueued
+    pub fn enqueued(&self) -> u64 {
+        self.enqueued.load(atomic::Ordering::Relaxed)
+    }
+
+    /// The Number of Items that were dequeued
+    pub fn dequeued(&self) -> u64 {
+        self.dequeued.load(atomic::Ordering::Relaxed)
+    }
+
+    /// The Number of Enqueue-Operations that failed because the Queue was full
+    pub fn full(&self) -> u64 {
+        self.full.load(atomic::Ordering::Relaxed)
+    }
+
+    /// The Number of Segments that were allocated
+    pub fn segment_allocs(&self) -> u64 {
+        self.segment_allocs.load(atomic::Ordering::Relaxed)
+    }
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Counters {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Counters")
+            .field("enqueued", &self.enqueued())
+            .field("dequeued", &self.dequeued())
+            .field("full", &self.full())
+            .field("segment_allocs", &self.segment_allocs())
+            .finish()
+    }
+}
+
+impl QueueMetrics for Counters {
+    fn on_enqueue(&self) {
+        self.enqueued.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+
+    fn on_dequeue(&self) {
+        self.dequeued.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+
+    fn on_full(&self) {
+        self.full.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+
+    fn on_segment_alloc(&self) {
+        self.segment_allocs.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+}
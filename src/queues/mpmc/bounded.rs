@@ -16,6 +16,12 @@
 
 use super::queue;
 
+#[cfg(feature = "async")]
+pub(crate) mod async_queue;
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub use async_queue::*;
+
 pub mod ncq {
     //! This Queue uses the Naive-Circular-Queue implementation provided in [the Paper](https://arxiv.org/pdf/1908.04511.pdf).
     //!
@@ -35,9 +41,15 @@ pub mod ncq {
     //! assert_eq!(Ok(10), rx.try_dequeue());
     //! ```
 
+    use alloc::vec::Vec;
     use core::fmt::Debug;
 
-    use crate::queues::{DequeueError, EnqueueError};
+    #[cfg(feature = "async")]
+    use crate::queues::cooperative;
+    use crate::queues::{
+        cancel::{self, CancelToken},
+        DequeueError, EnqueueError,
+    };
 
     use super::queue;
 
@@ -46,6 +58,17 @@ pub mod ncq {
     /// The sending Half for a NCQ based MPMC-Queue
     pub struct Sender<T>(queue::BoundedSender<T, queue::ncq::Queue>);
 
+    impl<T> Clone for Receiver<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+    impl<T> Clone for Sender<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+
     impl<T> Debug for Receiver<T> {
         fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
             write!(f, "NCQ-Receiver<{}>()", core::any::type_name::<T>())
@@ -57,12 +80,45 @@ pub mod ncq {
         }
     }
 
-    /// Creates a new NCQ-Queue with the given Capacity
+    /// Creates a new NCQ-Queue with the given Capacity as `(Receiver, Sender)`,
+    /// [`channel`] swaps the Halves to match the Order used by std
     pub fn queue<T>(capacity: usize) -> (Receiver<T>, Sender<T>) {
         let (rx, tx) = queue::queue_ncq(capacity);
         (Receiver(rx), Sender(tx))
     }
 
+    /// Creates a new NCQ-Queue with the given Capacity and returns the
+    /// Halves as ([`Sender`], [`Receiver`]), in the same Order as
+    /// `std::sync::mpsc::channel`
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpmc::bounded::ncq;
+    /// let (tx, rx) = ncq::channel::<usize>(4);
+    ///
+    /// tx.try_enqueue(13).unwrap();
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    /// ```
+    pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+        let (rx, tx) = queue(capacity);
+        (tx, rx)
+    }
+
+    /// Creates a new NCQ-Queue with the given Capacity, that reports all its
+    /// Operations to the given [`QueueMetrics`](crate::queues::metrics::QueueMetrics)
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    pub fn queue_with_metrics<T>(
+        capacity: usize,
+        metrics: alloc::sync::Arc<dyn crate::queues::metrics::QueueMetrics>,
+    ) -> (Receiver<T>, Sender<T>) {
+        let (rx, tx) = queue::queue_ncq_instrumented(
+            capacity,
+            crate::queues::instrument::Metrics::new(metrics),
+        );
+        (Receiver(rx), Sender(tx))
+    }
+
     impl<T> Sender<T> {
         /// Attempts to enqueue the Data on the Queue
         ///
@@ -109,6 +165,67 @@ pub mod ncq {
         pub fn is_closed(&self) -> bool {
             self.0.is_closed()
         }
+
+        /// The maximum Number of Elements that can be stored in the Queue
+        ///
+        /// # Example
+        /// ```rust
+        /// # use nolock::queues::mpmc::bounded::ncq;
+        /// let (rx, tx) = ncq::queue::<u64>(10);
+        ///
+        /// assert_eq!(10, tx.capacity());
+        /// # drop(rx);
+        /// ```
+        pub fn capacity(&self) -> usize {
+            self.0.capacity()
+        }
+
+        /// Returns the approximate Number of Elements currently stored in the
+        /// Queue, which allows Producers to shed Load before the Queue is
+        /// actually full.
+        ///
+        /// # Note
+        /// This also counts the Elements that are currently being enqueued or
+        /// dequeued by other Threads and may already be outdated once it is
+        /// returned.
+        ///
+        /// # Example
+        /// ```rust
+        /// # use nolock::queues::mpmc::bounded::ncq;
+        /// let (rx, tx) = ncq::queue::<u64>(10);
+        ///
+        /// tx.try_enqueue(13).unwrap();
+        /// tx.try_enqueue(14).unwrap();
+        /// assert_eq!(2, tx.len());
+        ///
+        /// rx.try_dequeue().unwrap();
+        /// assert_eq!(1, tx.len());
+        /// ```
+        pub fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        /// Checks if the Queue is currently empty, see [`len`](Self::len)
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Checks if the Queue is currently full, meaning that the next
+        /// Enqueue will most likely fail, see [`len`](Self::len)
+        ///
+        /// # Example
+        /// ```rust
+        /// # use nolock::queues::mpmc::bounded::ncq;
+        /// let (rx, tx) = ncq::queue::<u64>(1);
+        ///
+        /// assert_eq!(false, tx.is_full());
+        /// tx.try_enqueue(13).unwrap();
+        /// assert_eq!(true, tx.is_full());
+        /// # drop(rx);
+        /// ```
+        pub fn is_full(&self) -> bool {
+            self.0.is_full()
+        }
     }
 
     impl<T> Receiver<T> {
@@ -141,6 +258,70 @@ pub mod ncq {
             self.0.dequeue()
         }
 
+        /// Dequeues an Item from the Queue, blocking the current Thread
+        /// until one is available. Returns `None` once the Queue has been
+        /// closed by the Sending Half and all the Items were dequeued.
+        ///
+        /// The Thread first spins with an exponential Backoff and is only
+        /// parked if the Queue stays empty for longer, it is then woken up
+        /// again by the next Enqueue.
+        ///
+        /// # Example
+        /// ```rust
+        /// # use nolock::queues::mpmc::bounded::ncq;
+        /// let (rx, tx) = ncq::queue::<u64>(10);
+        ///
+        /// let handle = std::thread::spawn(move || {
+        ///     tx.try_enqueue(13).unwrap();
+        /// });
+        ///
+        /// assert_eq!(Some(13), rx.dequeue_blocking());
+        /// // The Sender has been dropped, so the Queue is closed
+        /// handle.join().unwrap();
+        /// assert_eq!(None, rx.dequeue_blocking());
+        /// ```
+        ///
+        /// # Async
+        /// This blocks the current Thread and therefore should not be called
+        /// from async Code, use [`dequeue_cooperative`](Self::dequeue_cooperative)
+        /// instead
+        #[cfg(feature = "std")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+        pub fn dequeue_blocking(&self) -> Option<T> {
+            self.0.dequeue_blocking()
+        }
+
+        /// A blocking dequeue operation like [`dequeue_blocking`](Self::dequeue_blocking), which
+        /// can additionally be cancelled from another Thread using the given
+        /// [`CancelToken`], see the [`cancel`](crate::queues::cancel) module for
+        /// more Details
+        ///
+        /// # Returns
+        /// * `Ok(data)` once an Element could be dequeued
+        /// * `Err(DequeueError::Closed)` if the Queue has been closed
+        /// * `Err(DequeueError::Cancelled)` if the Token was cancelled while the
+        ///   Queue was empty
+        ///
+        /// # Note
+        /// Unlike [`dequeue_blocking`](Self::dequeue_blocking), this never parks the Thread,
+        /// as a parked Thread would not notice the Cancellation, and instead keeps
+        /// yielding it to the OS-Scheduler while the Queue is empty
+        pub fn dequeue_cancellable(&self, token: &CancelToken) -> Result<T, DequeueError> {
+            cancel::dequeue(token, || self.try_dequeue())
+        }
+
+        /// The async-friendly Variant of [`dequeue_blocking`](Self::dequeue_blocking),
+        /// which yields back to the Executor in between Attempts instead of
+        /// blocking the Thread, see the [`cooperative`](crate::queues::cooperative)
+        /// module for more Details
+        #[cfg(feature = "async")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+        pub fn dequeue_cooperative(
+            &self,
+        ) -> cooperative::CooperativeDequeue<impl FnMut() -> Result<T, DequeueError> + '_> {
+            cooperative::dequeue(move || self.try_dequeue())
+        }
+
         /// Checks if the Sending Half has closed the Queue, meaning that
         /// no more new Elements will be added to the Queue.
         ///
@@ -165,12 +346,80 @@ pub mod ncq {
         pub fn is_closed(&self) -> bool {
             self.0.is_closed()
         }
+
+        /// Dequeues all the Elements that are currently stored in the Queue and
+        /// collects them into a [`Snapshot`](crate::queues::snapshot::Snapshot),
+        /// without closing the Queue.
+        ///
+        /// Elements that are enqueued concurrently may or may not be part of the
+        /// Snapshot.
+        ///
+        /// # Example
+        /// ```
+        /// # use nolock::queues::mpmc::bounded::ncq;
+        /// let (rx, tx) = ncq::queue::<usize>(10);
+        ///
+        /// tx.try_enqueue(13).unwrap();
+        /// tx.try_enqueue(14).unwrap();
+        ///
+        /// assert_eq!(&[13, 14], rx.snapshot().as_slice());
+        /// assert!(rx.snapshot().is_empty());
+        /// ```
+        pub fn snapshot(&self) -> crate::queues::snapshot::Snapshot<T> {
+            crate::queues::snapshot::Snapshot::drain(|| self.try_dequeue())
+        }
+
+        /// Closes the Queue for all the Senders and Receivers and returns all
+        /// the Elements that were still left in it.
+        ///
+        /// Every concurrent Enqueue either has its Element returned here or
+        /// fails with [`EnqueueError::Closed`], so no Element is lost. The
+        /// other Receivers can still dequeue some of the remaining Elements
+        /// concurrently and afterwards observe the Queue as closed.
+        ///
+        /// # Example
+        /// ```rust
+        /// # use nolock::queues::mpmc::bounded::ncq;
+        /// let (rx, tx) = ncq::queue::<u64>(10);
+        ///
+        /// tx.try_enqueue(13).unwrap();
+        /// tx.try_enqueue(14).unwrap();
+        ///
+        /// assert_eq!(vec![13, 14], rx.close_and_drain());
+        /// assert_eq!(true, tx.is_closed());
+        /// ```
+        pub fn close_and_drain(self) -> Vec<T> {
+            self.0.close_and_drain()
+        }
+    }
+
+    #[cfg(feature = "std")]
+    crate::queues::adapter::impl_receiver!(Receiver<T>, T, dequeue_blocking);
+
+    impl<T> crate::queues::dynamic::DynSender<T> for Sender<T> {
+        fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+            Sender::try_enqueue(self, data).map_err(|(err, data)| (data, err))
+        }
+
+        fn enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+            crate::queues::dynamic::enqueue_spinning(
+                |data| Sender::try_enqueue(self, data).map_err(|(err, data)| (data, err)),
+                data,
+            )
+        }
+
+        fn is_closed(&self) -> bool {
+            Sender::is_closed(self)
+        }
     }
 }
 
 pub mod scq {
     //! This Queue uses the Scalable-Circular-Queue implementation provided in [the Paper](https://arxiv.org/pdf/1908.04511.pdf).
     //!
+    //! The underlying Queue of Indices is also available on its own as the
+    //! [`IndexQueue`](crate::queues::index_queue::IndexQueue).
+    //!
     //! # Example:
     //! ```rust
     //! # use nolock::queues::mpmc::bounded::scq;
@@ -183,16 +432,35 @@ pub mod scq {
     //! assert_eq!(Ok(10), rx.try_dequeue());
     //! ```
 
+    use alloc::vec::Vec;
     use core::fmt::Debug;
 
-    use crate::queues::{DequeueError, EnqueueError};
+    #[cfg(feature = "async")]
+    use crate::queues::cooperative;
+    use crate::queues::{
+        cancel::{self, CancelToken},
+        index_queue::IndexQueue,
+        instrument::Metrics,
+        DequeueError, EnqueueError,
+    };
 
     use super::queue;
 
     /// The receiving Half for a SCQ based MPMC-Queue
-    pub struct Receiver<T>(queue::BoundedReceiver<T, queue::scq::Queue>);
+    pub struct Receiver<T>(queue::BoundedReceiver<T, IndexQueue>);
     /// The sending Half for a SCQ based MPMC-Queue
-    pub struct Sender<T>(queue::BoundedSender<T, queue::scq::Queue>);
+    pub struct Sender<T>(queue::BoundedSender<T, IndexQueue>);
+
+    impl<T> Clone for Receiver<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+    impl<T> Clone for Sender<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
 
     impl<T> Debug for Receiver<T> {
         fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -210,11 +478,50 @@ pub mod scq {
     /// Unlike the other Queues in this crate, this Queue combines the Producer and Consumer in
     /// a single Struct, as they dont have any restrictions that would limit the other half in
     /// some way and need to share certain state anyway.
+    ///
+    /// The Halves are returned as `(Receiver, Sender)`, use [`channel`] to
+    /// get the Sender first
     pub fn queue<T>(capacity: usize) -> (Receiver<T>, Sender<T>) {
         let (rx, tx) = queue::queue_scq(capacity);
         (Receiver(rx), Sender(tx))
     }
 
+    /// Creates a new SCQ-Queue with the given Capacity and returns the
+    /// Halves as ([`Sender`], [`Receiver`]), in the same Order as
+    /// `std::sync::mpsc::channel`
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpmc::bounded::scq;
+    /// let (tx, rx) = scq::channel::<usize>(4);
+    ///
+    /// tx.try_enqueue(13).unwrap();
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    /// ```
+    pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+        let (rx, tx) = queue(capacity);
+        (tx, rx)
+    }
+
+    /// Creates a new SCQ-Queue with the given Capacity, that reports all its
+    /// Operations to the given [`QueueMetrics`](crate::queues::metrics::QueueMetrics)
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    pub fn queue_with_metrics<T>(
+        capacity: usize,
+        metrics: alloc::sync::Arc<dyn crate::queues::metrics::QueueMetrics>,
+    ) -> (Receiver<T>, Sender<T>) {
+        queue_instrumented(capacity, Metrics::new(metrics))
+    }
+
+    pub(crate) fn queue_instrumented<T>(
+        capacity: usize,
+        metrics: Metrics,
+    ) -> (Receiver<T>, Sender<T>) {
+        let (rx, tx) = queue::queue_scq_instrumented(capacity, metrics);
+        (Receiver(rx), Sender(tx))
+    }
+
     impl<T> Sender<T> {
         /// Attempts to Enqueue the given Data
         ///
@@ -261,6 +568,67 @@ pub mod scq {
         pub fn is_closed(&self) -> bool {
             self.0.is_closed()
         }
+
+        /// The maximum Number of Elements that can be stored in the Queue
+        ///
+        /// # Example
+        /// ```rust
+        /// # use nolock::queues::mpmc::bounded::scq;
+        /// let (rx, tx) = scq::queue::<u64>(10);
+        ///
+        /// assert_eq!(10, tx.capacity());
+        /// # drop(rx);
+        /// ```
+        pub fn capacity(&self) -> usize {
+            self.0.capacity()
+        }
+
+        /// Returns the approximate Number of Elements currently stored in the
+        /// Queue, which allows Producers to shed Load before the Queue is
+        /// actually full.
+        ///
+        /// # Note
+        /// This also counts the Elements that are currently being enqueued or
+        /// dequeued by other Threads and may already be outdated once it is
+        /// returned.
+        ///
+        /// # Example
+        /// ```rust
+        /// # use nolock::queues::mpmc::bounded::scq;
+        /// let (rx, tx) = scq::queue::<u64>(10);
+        ///
+        /// tx.try_enqueue(13).unwrap();
+        /// tx.try_enqueue(14).unwrap();
+        /// assert_eq!(2, tx.len());
+        ///
+        /// rx.try_dequeue().unwrap();
+        /// assert_eq!(1, tx.len());
+        /// ```
+        pub fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        /// Checks if the Queue is currently empty, see [`len`](Self::len)
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Checks if the Queue is currently full, meaning that the next
+        /// Enqueue will most likely fail, see [`len`](Self::len)
+        ///
+        /// # Example
+        /// ```rust
+        /// # use nolock::queues::mpmc::bounded::scq;
+        /// let (rx, tx) = scq::queue::<u64>(1);
+        ///
+        /// assert_eq!(false, tx.is_full());
+        /// tx.try_enqueue(13).unwrap();
+        /// assert_eq!(true, tx.is_full());
+        /// # drop(rx);
+        /// ```
+        pub fn is_full(&self) -> bool {
+            self.0.is_full()
+        }
     }
 
     impl<T> Receiver<T> {
@@ -293,6 +661,70 @@ pub mod scq {
             self.0.dequeue()
         }
 
+        /// Dequeues an Item from the Queue, blocking the current Thread
+        /// until one is available. Returns `None` once the Queue has been
+        /// closed by the Sending Half and all the Items were dequeued.
+        ///
+        /// The Thread first spins with an exponential Backoff and is only
+        /// parked if the Queue stays empty for longer, it is then woken up
+        /// again by the next Enqueue.
+        ///
+        /// # Example
+        /// ```rust
+        /// # use nolock::queues::mpmc::bounded::scq;
+        /// let (rx, tx) = scq::queue::<u64>(10);
+        ///
+        /// let handle = std::thread::spawn(move || {
+        ///     tx.try_enqueue(13).unwrap();
+        /// });
+        ///
+        /// assert_eq!(Some(13), rx.dequeue_blocking());
+        /// // The Sender has been dropped, so the Queue is closed
+        /// handle.join().unwrap();
+        /// assert_eq!(None, rx.dequeue_blocking());
+        /// ```
+        ///
+        /// # Async
+        /// This blocks the current Thread and therefore should not be called
+        /// from async Code, use [`dequeue_cooperative`](Self::dequeue_cooperative)
+        /// instead
+        #[cfg(feature = "std")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+        pub fn dequeue_blocking(&self) -> Option<T> {
+            self.0.dequeue_blocking()
+        }
+
+        /// A blocking dequeue operation like [`dequeue_blocking`](Self::dequeue_blocking), which
+        /// can additionally be cancelled from another Thread using the given
+        /// [`CancelToken`], see the [`cancel`](crate::queues::cancel) module for
+        /// more Details
+        ///
+        /// # Returns
+        /// * `Ok(data)` once an Element could be dequeued
+        /// * `Err(DequeueError::Closed)` if the Queue has been closed
+        /// * `Err(DequeueError::Cancelled)` if the Token was cancelled while the
+        ///   Queue was empty
+        ///
+        /// # Note
+        /// Unlike [`dequeue_blocking`](Self::dequeue_blocking), this never parks the Thread,
+        /// as a parked Thread would not notice the Cancellation, and instead keeps
+        /// yielding it to the OS-Scheduler while the Queue is empty
+        pub fn dequeue_cancellable(&self, token: &CancelToken) -> Result<T, DequeueError> {
+            cancel::dequeue(token, || self.try_dequeue())
+        }
+
+        /// The async-friendly Variant of [`dequeue_blocking`](Self::dequeue_blocking),
+        /// which yields back to the Executor in between Attempts instead of
+        /// blocking the Thread, see the [`cooperative`](crate::queues::cooperative)
+        /// module for more Details
+        #[cfg(feature = "async")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+        pub fn dequeue_cooperative(
+            &self,
+        ) -> cooperative::CooperativeDequeue<impl FnMut() -> Result<T, DequeueError> + '_> {
+            cooperative::dequeue(move || self.try_dequeue())
+        }
+
         /// Checks if the Sending Half has closed the Queue, meaning that
         /// no more new Elements will be added to the Queue.
         ///
@@ -317,5 +749,70 @@ pub mod scq {
         pub fn is_closed(&self) -> bool {
             self.0.is_closed()
         }
+
+        /// Dequeues all the Elements that are currently stored in the Queue and
+        /// collects them into a [`Snapshot`](crate::queues::snapshot::Snapshot),
+        /// without closing the Queue.
+        ///
+        /// Elements that are enqueued concurrently may or may not be part of the
+        /// Snapshot.
+        ///
+        /// # Example
+        /// ```
+        /// # use nolock::queues::mpmc::bounded::scq;
+        /// let (rx, tx) = scq::queue::<usize>(10);
+        ///
+        /// tx.try_enqueue(13).unwrap();
+        /// tx.try_enqueue(14).unwrap();
+        ///
+        /// assert_eq!(&[13, 14], rx.snapshot().as_slice());
+        /// assert!(rx.snapshot().is_empty());
+        /// ```
+        pub fn snapshot(&self) -> crate::queues::snapshot::Snapshot<T> {
+            crate::queues::snapshot::Snapshot::drain(|| self.try_dequeue())
+        }
+
+        /// Closes the Queue for all the Senders and Receivers and returns all
+        /// the Elements that were still left in it.
+        ///
+        /// Every concurrent Enqueue either has its Element returned here or
+        /// fails with [`EnqueueError::Closed`], so no Element is lost. The
+        /// other Receivers can still dequeue some of the remaining Elements
+        /// concurrently and afterwards observe the Queue as closed.
+        ///
+        /// # Example
+        /// ```rust
+        /// # use nolock::queues::mpmc::bounded::scq;
+        /// let (rx, tx) = scq::queue::<u64>(10);
+        ///
+        /// tx.try_enqueue(13).unwrap();
+        /// tx.try_enqueue(14).unwrap();
+        ///
+        /// assert_eq!(vec![13, 14], rx.close_and_drain());
+        /// assert_eq!(true, tx.is_closed());
+        /// ```
+        pub fn close_and_drain(self) -> Vec<T> {
+            self.0.close_and_drain()
+        }
+    }
+
+    #[cfg(feature = "std")]
+    crate::queues::adapter::impl_receiver!(Receiver<T>, T, dequeue_blocking);
+
+    impl<T> crate::queues::dynamic::DynSender<T> for Sender<T> {
+        fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+            Sender::try_enqueue(self, data).map_err(|(err, data)| (data, err))
+        }
+
+        fn enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+            crate::queues::dynamic::enqueue_spinning(
+                |data| Sender::try_enqueue(self, data).map_err(|(err, data)| (data, err)),
+                data,
+            )
+        }
+
+        fn is_closed(&self) -> bool {
+            Sender::is_closed(self)
+        }
     }
 }
@@ -0,0 +1,600 @@
+use alloc::sync::Arc;
+use core::{fmt::Debug, future::Future, mem::ManuallyDrop, pin::Pin, task::Poll};
+
+use crate::queues::{
+    batch::{DequeueMany, PendingDequeue},
+    timeout::{DequeueTimeout, EnqueueTimeout, EnqueueTimeoutError, PendingEnqueue},
+    DequeueError, EnqueueError,
+};
+
+use super::scq;
+
+pub(crate) mod waiters;
+use waiters::{Waiter, WaiterQueue};
+
+/// The Waiters shared between all the Halves of a Queue
+struct Shared {
+    /// The Receivers waiting for an Element to be enqueued
+    receivers: WaiterQueue,
+    /// The Senders waiting for a free Slot in the Queue
+    senders: WaiterQueue,
+}
+
+/// The async Sending Half of a bounded MPMC-Queue, created using
+/// [`async_queue`]
+pub struct AsyncSender<T> {
+    queue: ManuallyDrop<scq::Sender<T>>,
+    shared: Arc<Shared>,
+}
+
+/// The async Receiving Half of a bounded MPMC-Queue, created using
+/// [`async_queue`]
+///
+/// # Fairness
+/// Multiple Consumers can wait on the same Receiver at the same Time, by
+/// sharing it between Tasks. Waiting Consumers are woken up in the same
+/// Order in which they started waiting, so no Consumer can be starved by
+/// the others. The same holds for Producers waiting for free Space in the
+/// Queue using [`AsyncSender::enqueue`].
+///
+/// A Consumer that is woken up, but does not find an Element, because it was
+/// taken by a Consumer that was not waiting before, has to wait again at the
+/// End of the Queue.
+pub struct AsyncReceiver<T> {
+    queue: ManuallyDrop<scq::Receiver<T>>,
+    shared: Arc<Shared>,
+}
+
+impl<T> Debug for AsyncSender<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "AsyncSender<{}>()", core::any::type_name::<T>())
+    }
+}
+impl<T> Debug for AsyncReceiver<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "AsyncReceiver<{}>()", core::any::type_name::<T>())
+    }
+}
+
+/// Creates a new async bounded MPMC-Queue with the given Capacity, which is
+/// based on the [`scq`]-Queue
+///
+/// # Example
+/// ```rust
+/// # use nolock::queues::mpmc::bounded;
+/// # async fn demo() {
+/// let (rx, tx) = bounded::async_queue::<u64>(10);
+///
+/// tx.enqueue(13).await.unwrap();
+/// assert_eq!(Ok(13), rx.dequeue().await);
+/// # }
+/// # let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// # rt.block_on(demo());
+/// ```
+pub fn async_queue<T>(capacity: usize) -> (AsyncReceiver<T>, AsyncSender<T>) {
+    let (rx, tx) = scq::queue(capacity);
+
+    let shared = Arc::new(Shared {
+        receivers: WaiterQueue::new(),
+        senders: WaiterQueue::new(),
+    });
+
+    (
+        AsyncReceiver {
+            queue: ManuallyDrop::new(rx),
+            shared: shared.clone(),
+        },
+        AsyncSender {
+            queue: ManuallyDrop::new(tx),
+            shared,
+        },
+    )
+}
+
+impl<T> Drop for AsyncSender<T> {
+    fn drop(&mut self) {
+        // Close the Queue first, so that the woken up Receivers observe it
+        unsafe { ManuallyDrop::drop(&mut self.queue) };
+        self.shared.receivers.notify_all();
+    }
+}
+impl<T> Drop for AsyncReceiver<T> {
+    fn drop(&mut self) {
+        // Close the Queue first, so that the woken up Senders observe it
+        unsafe { ManuallyDrop::drop(&mut self.queue) };
+        self.shared.senders.notify_all();
+    }
+}
+
+impl<T> AsyncSender<T> {
+    /// Attempts to enqueue the Data on the Queue, without waiting for free
+    /// Space, just like [`try_enqueue`](scq::Sender::try_enqueue)
+    pub fn try_enqueue(&self, data: T) -> Result<(), (EnqueueError, T)> {
+        self.queue.try_enqueue(data)?;
+        self.shared.receivers.notify_one();
+
+        Ok(())
+    }
+
+    /// Enqueues the Data on the Queue, waiting for free Space if the Queue is
+    /// currently full.
+    ///
+    /// The returned Future resolves with an Error once the Receiver has been
+    /// dropped, as no more Elements can be enqueued in that Case.
+    ///
+    /// # Cancel Safety
+    /// Dropping the returned Future before it resolved, will also drop the
+    /// Data it still holds. Use [`EnqueueFuture::into_inner`] to get the Data
+    /// back instead.
+    pub fn enqueue(&self, data: T) -> EnqueueFuture<'_, T> {
+        EnqueueFuture {
+            sender: self,
+            data: Some(data),
+            waiter: None,
+        }
+    }
+
+    /// Enqueues the Data, just like [`enqueue`](Self::enqueue), but stops
+    /// waiting for free Space once the given `sleep` Future resolves, in
+    /// which case the Data is returned as
+    /// `Err(EnqueueTimeoutError::Timeout(data))`.
+    ///
+    /// The `sleep` Future can come from any Runtime, see the
+    /// [`timeout`](crate::queues::timeout) module for more Details
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::mpmc::bounded;
+    /// # use nolock::queues::timeout::EnqueueTimeoutError;
+    /// # use std::time::Duration;
+    /// # async fn demo() {
+    /// let (rx, tx) = bounded::async_queue::<u64>(1);
+    /// tx.try_enqueue(13).unwrap();
+    ///
+    /// let sleep = tokio::time::sleep(Duration::from_millis(5));
+    /// assert_eq!(
+    ///     Err(EnqueueTimeoutError::Timeout(14)),
+    ///     tx.enqueue_within(14, sleep).await
+    /// );
+    /// # drop(rx);
+    /// # }
+    /// # let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    /// # rt.block_on(demo());
+    /// ```
+    pub fn enqueue_within<S>(&self, data: T, sleep: S) -> EnqueueTimeout<EnqueueFuture<'_, T>, S>
+    where
+        S: Future,
+    {
+        EnqueueTimeout::new(self.enqueue(data), sleep)
+    }
+
+    /// Checks if the Receiver has closed the Queue
+    pub fn is_closed(&self) -> bool {
+        self.queue.is_closed()
+    }
+
+    /// The maximum Number of Elements that can be stored in the Queue
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// Returns the approximate Number of Elements currently stored in the
+    /// Queue, see [`len`](scq::Sender::len)
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Checks if the Queue is currently empty, see [`len`](Self::len)
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> AsyncReceiver<T> {
+    /// Attempts to dequeue an Element from the Queue, without waiting for
+    /// one to be enqueued, just like [`try_dequeue`](scq::Receiver::try_dequeue)
+    pub fn try_dequeue(&self) -> Result<T, DequeueError> {
+        let data = self.queue.try_dequeue()?;
+        self.shared.senders.notify_one();
+
+        Ok(data)
+    }
+
+    /// Dequeues the next Element from the Queue, waiting for one to be
+    /// enqueued if the Queue is currently empty.
+    ///
+    /// The returned Future resolves with [`DequeueError::Closed`] once the
+    /// Sender has been dropped and there are no more Elements left in the
+    /// Queue.
+    ///
+    /// # Cancel Safety
+    /// This Future is cancel safe, an Element is only ever removed from the
+    /// Queue when the Future resolves with it. Dropping a Future that was
+    /// already woken up passes the Wakeup on to the next waiting Consumer
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::mpmc::bounded;
+    /// # async fn demo() {
+    /// let (rx, tx) = bounded::async_queue::<u64>(10);
+    ///
+    /// // Both Consumers wait on the same Receiver
+    /// let first = rx.dequeue();
+    /// let second = rx.dequeue();
+    ///
+    /// tx.try_enqueue(13).unwrap();
+    /// tx.try_enqueue(14).unwrap();
+    ///
+    /// assert_eq!(Ok(13), first.await);
+    /// assert_eq!(Ok(14), second.await);
+    /// # }
+    /// # let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// # rt.block_on(demo());
+    /// ```
+    pub fn dequeue(&self) -> DequeueFuture<'_, T> {
+        DequeueFuture {
+            receiver: self,
+            waiter: None,
+        }
+    }
+
+    /// Dequeues the next Element, just like [`dequeue`](Self::dequeue), but
+    /// stops waiting once the given `sleep` Future resolves, in which case
+    /// `Err(DequeueError::Empty)` is returned.
+    ///
+    /// The `sleep` Future can come from any Runtime, see the
+    /// [`timeout`](crate::queues::timeout) module for more Details
+    pub fn dequeue_timeout<S>(&self, sleep: S) -> DequeueTimeout<DequeueFuture<'_, T>, S>
+    where
+        S: Future,
+    {
+        DequeueTimeout::new(self.dequeue(), sleep)
+    }
+
+    /// Waits for the next Element, just like [`dequeue`](Self::dequeue), and
+    /// then also takes all the Elements that are already in the Queue, until
+    /// `max` Elements have been dequeued, see the
+    /// [`batch`](crate::queues::batch) module for more Details
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::mpmc::bounded;
+    /// # async fn demo() {
+    /// let (rx, tx) = bounded::async_queue::<u64>(10);
+    ///
+    /// tx.try_enqueue(13).unwrap();
+    /// tx.try_enqueue(14).unwrap();
+    /// tx.try_enqueue(15).unwrap();
+    ///
+    /// assert_eq!(Ok(vec![13, 14]), rx.dequeue_many(2).await);
+    /// assert_eq!(Ok(vec![15]), rx.dequeue_many(2).await);
+    /// # }
+    /// # let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// # rt.block_on(demo());
+    /// ```
+    pub fn dequeue_many(&self, max: usize) -> DequeueMany<DequeueFuture<'_, T>> {
+        DequeueMany::new(self.dequeue(), max)
+    }
+
+    /// Checks if the Sender has closed the Queue, there may still be
+    /// Elements left in it
+    pub fn is_closed(&self) -> bool {
+        self.queue.is_closed()
+    }
+}
+
+/// Cancels the Waiter, if there is one, and passes on a Notification that was
+/// not used
+fn release_waiter(waiter: Option<Arc<Waiter>>, waiters: &WaiterQueue) {
+    if let Some(waiter) = waiter {
+        if waiter.cancel() {
+            waiters.notify_one();
+        }
+    }
+}
+
+/// The Future returned by [`AsyncSender::enqueue`]
+///
+/// # Cancel Safety
+/// Dropping this Future hands any Notification it already received on to
+/// the next waiting Sender, so no Wakeup is lost, but the Data it still
+/// holds is dropped with it. Use [`into_inner`](Self::into_inner) to get
+/// the Data back first.
+pub struct EnqueueFuture<'queue, T> {
+    sender: &'queue AsyncSender<T>,
+    data: Option<T>,
+    /// Our Ticket in the Queue of waiting Senders
+    waiter: Option<Arc<Waiter>>,
+}
+
+impl<'queue, T> EnqueueFuture<'queue, T> {
+    /// Consumes the Future and returns the Data, if it was not already
+    /// enqueued
+    pub fn into_inner(mut self) -> Option<T> {
+        self.data.take()
+    }
+}
+
+impl<'queue, T> Unpin for EnqueueFuture<'queue, T> {}
+
+impl<'queue, T> Future for EnqueueFuture<'queue, T> {
+    type Output = Result<(), (EnqueueError, T)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        loop {
+            let data = this
+                .data
+                .take()
+                .expect("The Future should not be polled after it resolved");
+
+            match this.sender.try_enqueue(data) {
+                Ok(_) => {
+                    if let Some(waiter) = this.waiter.take() {
+                        waiter.cancel();
+                    }
+                    return Poll::Ready(Ok(()));
+                }
+                Err((EnqueueError::Full, data)) => {
+                    this.data = Some(data);
+                }
+                Err(e) => {
+                    if let Some(waiter) = this.waiter.take() {
+                        waiter.cancel();
+                    }
+                    return Poll::Ready(Err(e));
+                }
+            };
+
+            match this.waiter.as_ref() {
+                Some(waiter) if !waiter.is_notified() => {
+                    waiter.register(cx.waker());
+                    if !waiter.is_notified() {
+                        return Poll::Pending;
+                    }
+                }
+                // Either we are not waiting yet or we were notified, but the
+                // Slot was already taken by someone else
+                _ => {
+                    this.waiter = Some(this.sender.shared.senders.register(cx.waker()));
+                }
+            };
+        }
+    }
+}
+
+impl<'queue, T> PendingEnqueue for EnqueueFuture<'queue, T> {
+    type Item = T;
+
+    fn take_item(&mut self) -> Option<T> {
+        // Give up our Ticket, so that another waiting Sender gets the next
+        // free Slot instead
+        release_waiter(self.waiter.take(), &self.sender.shared.senders);
+        self.data.take()
+    }
+
+    fn into_result(output: Self::Output) -> Result<(), EnqueueTimeoutError<T>> {
+        output.map_err(|(e, data)| EnqueueTimeoutError::Failed(e, data))
+    }
+}
+
+impl<'queue, T> Drop for EnqueueFuture<'queue, T> {
+    fn drop(&mut self) {
+        release_waiter(self.waiter.take(), &self.sender.shared.senders);
+    }
+}
+
+/// The Future returned by [`AsyncReceiver::dequeue`]
+pub struct DequeueFuture<'queue, T> {
+    receiver: &'queue AsyncReceiver<T>,
+    /// Our Ticket in the Queue of waiting Receivers
+    waiter: Option<Arc<Waiter>>,
+}
+
+impl<'queue, T> Future for DequeueFuture<'queue, T> {
+    type Output = Result<T, DequeueError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        loop {
+            match this.receiver.try_dequeue() {
+                Err(DequeueError::Empty) => {}
+                result => {
+                    if let Some(waiter) = this.waiter.take() {
+                        waiter.cancel();
+                    }
+                    return Poll::Ready(result);
+                }
+            };
+
+            match this.waiter.as_ref() {
+                Some(waiter) if !waiter.is_notified() => {
+                    waiter.register(cx.waker());
+                    if !waiter.is_notified() {
+                        return Poll::Pending;
+                    }
+                }
+                // Either we are not waiting yet or we were notified, but the
+                // Element was already taken by someone else
+                _ => {
+                    this.waiter = Some(this.receiver.shared.receivers.register(cx.waker()));
+                }
+            };
+        }
+    }
+}
+
+impl<'queue, T> PendingDequeue for DequeueFuture<'queue, T> {
+    type Item = T;
+
+    fn try_dequeue_next(&mut self) -> Result<T, DequeueError> {
+        self.receiver.try_dequeue()
+    }
+}
+
+impl<'queue, T> Drop for DequeueFuture<'queue, T> {
+    fn drop(&mut self) {
+        release_waiter(self.waiter.take(), &self.receiver.shared.receivers);
+    }
+}
+
+crate::queues::adapter::impl_async_receiver!(AsyncReceiver<T>, DequeueFuture);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn enqueue_dequeue() {
+        let (rx, tx) = async_queue(4);
+
+        assert_eq!(Ok(()), tx.enqueue(13).await);
+        assert_eq!(Ok(13), rx.dequeue().await);
+    }
+
+    #[tokio::test]
+    async fn dequeue_many_frees_slots() {
+        let (rx, tx) = async_queue(2);
+        let tx = Arc::new(tx);
+
+        tx.try_enqueue(1).unwrap();
+        tx.try_enqueue(2).unwrap();
+
+        let sender = tx.clone();
+        let handle = tokio::spawn(async move { sender.enqueue(3).await });
+        tokio::task::yield_now().await;
+
+        assert_eq!(Ok(vec![1, 2]), rx.dequeue_many(4).await);
+        assert_eq!(Ok(()), handle.await.unwrap());
+        assert_eq!(Ok(vec![3]), rx.dequeue_many(4).await);
+    }
+
+    #[tokio::test]
+    async fn receivers_woken_in_order() {
+        let (rx, tx) = async_queue(8);
+        let rx = Arc::new(rx);
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        for id in 0..4 {
+            let rx = rx.clone();
+            let order = order.clone();
+            tokio::spawn(async move {
+                let value = rx.dequeue().await.unwrap();
+                order.lock().unwrap().push((id, value));
+            });
+            // Let the Consumer start waiting before spawning the next one
+            tokio::task::yield_now().await;
+        }
+
+        for value in 0..4 {
+            tx.try_enqueue(value).unwrap();
+            tokio::task::yield_now().await;
+        }
+        tokio::task::yield_now().await;
+
+        assert_eq!(vec![(0, 0), (1, 1), (2, 2), (3, 3)], *order.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn enqueue_waits_for_space() {
+        let (rx, tx) = async_queue(1);
+        tx.try_enqueue(1).unwrap();
+
+        let tx = Arc::new(tx);
+        let tx2 = tx.clone();
+        let handle = tokio::spawn(async move { tx2.enqueue(2).await.unwrap() });
+        tokio::task::yield_now().await;
+        assert!(!handle.is_finished());
+
+        assert_eq!(Ok(1), rx.dequeue().await);
+        handle.await.unwrap();
+        assert_eq!(Ok(2), rx.dequeue().await);
+    }
+
+    #[tokio::test]
+    async fn enqueue_within_times_out() {
+        let (rx, tx) = async_queue(1);
+        tx.try_enqueue(1).unwrap();
+
+        let tx = Arc::new(tx);
+        let tx2 = tx.clone();
+        let handle = tokio::spawn(async move { tx2.enqueue(3).await.unwrap() });
+        tokio::task::yield_now().await;
+
+        let sleep = tokio::time::sleep(core::time::Duration::from_millis(5));
+        assert_eq!(
+            Err(EnqueueTimeoutError::Timeout(2)),
+            tx.enqueue_within(2, sleep).await
+        );
+
+        // The Sender that is still waiting gets the free Slot
+        assert_eq!(Ok(1), rx.dequeue().await);
+        handle.await.unwrap();
+        assert_eq!(Ok(3), rx.dequeue().await);
+    }
+
+    #[tokio::test]
+    async fn sender_drop_wakes_receivers() {
+        let (rx, tx) = async_queue::<u64>(4);
+        let rx = Arc::new(rx);
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let rx = rx.clone();
+                tokio::spawn(async move { rx.dequeue().await })
+            })
+            .collect();
+        tokio::task::yield_now().await;
+
+        drop(tx);
+        for handle in handles {
+            assert_eq!(Err(DequeueError::Closed), handle.await.unwrap());
+        }
+    }
+
+    #[test]
+    fn dropped_notified_passes_on() {
+        use futures::task::noop_waker;
+
+        let (rx, tx) = async_queue::<u64>(4);
+        let waker = noop_waker();
+        let mut cx = core::task::Context::from_waker(&waker);
+
+        let mut first = rx.dequeue();
+        let mut second = rx.dequeue();
+        assert!(Pin::new(&mut first).poll(&mut cx).is_pending());
+        assert!(Pin::new(&mut second).poll(&mut cx).is_pending());
+
+        tx.try_enqueue(13).unwrap();
+        assert!(first.waiter.as_ref().unwrap().is_notified());
+        assert!(!second.waiter.as_ref().unwrap().is_notified());
+
+        drop(first);
+        assert!(second.waiter.as_ref().unwrap().is_notified());
+        assert_eq!(Poll::Ready(Ok(13)), Pin::new(&mut second).poll(&mut cx));
+    }
+
+    #[test]
+    fn enqueue_into_inner() {
+        use futures::task::noop_waker;
+
+        let (rx, tx) = async_queue::<u64>(1);
+        let waker = noop_waker();
+        let mut cx = core::task::Context::from_waker(&waker);
+
+        tx.try_enqueue(13).unwrap();
+        let mut fut = tx.enqueue(14);
+        assert!(Pin::new(&mut fut).poll(&mut cx).is_pending());
+        assert_eq!(Some(14), fut.into_inner());
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+        let mut fut = tx.enqueue(15);
+        assert_eq!(Poll::Ready(Ok(())), Pin::new(&mut fut).poll(&mut cx));
+        assert_eq!(None, fut.into_inner());
+        assert_eq!(Ok(15), rx.try_dequeue());
+    }
+}
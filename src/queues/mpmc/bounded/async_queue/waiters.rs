@@ -0,0 +1,243 @@
+//! A FIFO-Queue of waiting Futures, that is used to wake them up in the same
+//! Order in which they started waiting.
+//!
+//! Every waiting Future enqueues a Ticket ([`Waiter`]) into a jiffy-Queue, as
+//! any Number of Futures can start waiting at the same Time. The Tickets are
+//! only dequeued by whoever currently holds the `locked` Flag, every other
+//! Thread that wants to notify a Waiter only records its Notification and
+//! leaves the actual Wakeup to the current Holder.
+
+use alloc::sync::Arc;
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{self, AtomicBool, AtomicU8, AtomicUsize},
+    task::Waker,
+};
+
+use crate::utils::AtomicWaker;
+
+use crate::queues::mpsc::jiffy;
+
+/// The Waiter is still waiting to be notified
+const WAITING: u8 = 0;
+/// The Waiter has been notified and its Future should try again
+const NOTIFIED: u8 = 1;
+/// The Future of the Waiter is no longer interested in being notified
+const CANCELLED: u8 = 2;
+
+/// A single Ticket in the [`WaiterQueue`]
+pub(crate) struct Waiter {
+    state: AtomicU8,
+    waker: AtomicWaker,
+}
+
+impl Waiter {
+    /// Updates the Waker that should be woken once the Waiter is notified
+    pub fn register(&self, waker: &Waker) {
+        self.waker.register(waker);
+    }
+
+    /// Checks if the Waiter has already been notified
+    pub fn is_notified(&self) -> bool {
+        self.state.load(atomic::Ordering::Acquire) == NOTIFIED
+    }
+
+    /// Marks the Waiter as no longer waiting and returns `true` if it had
+    /// already been notified, in which case the Notification should be
+    /// passed on to the next Waiter, if it was not used
+    pub fn cancel(&self) -> bool {
+        self.state.swap(CANCELLED, atomic::Ordering::AcqRel) == NOTIFIED
+    }
+
+    /// Attempts to notify the Waiter, returns `false` if it was already
+    /// cancelled
+    fn notify(&self) -> bool {
+        if self
+            .state
+            .compare_exchange(
+                WAITING,
+                NOTIFIED,
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+            )
+            .is_err()
+        {
+            return false;
+        }
+
+        self.waker.wake();
+        true
+    }
+}
+
+/// The FIFO-Queue of [`Waiter`]s
+pub(crate) struct WaiterQueue {
+    tx: jiffy::Sender<Arc<Waiter>>,
+    /// Only accessed while holding the `locked` Flag
+    rx: UnsafeCell<jiffy::Receiver<Arc<Waiter>>>,
+    locked: AtomicBool,
+    /// The Number of Notifications that still need to be delivered
+    pending: AtomicUsize,
+    /// Whether all the current Waiters should be notified
+    all: AtomicBool,
+}
+
+// Safety:
+// The Receiver is only ever accessed by the Thread holding the `locked` Flag
+unsafe impl Sync for WaiterQueue {}
+
+impl WaiterQueue {
+    pub fn new() -> Self {
+        let (rx, tx) = jiffy::queue();
+
+        Self {
+            tx,
+            rx: UnsafeCell::new(rx),
+            locked: AtomicBool::new(false),
+            pending: AtomicUsize::new(0),
+            all: AtomicBool::new(false),
+        }
+    }
+
+    /// Enqueues a new Waiter with the given Waker at the End of the Queue.
+    ///
+    /// The Caller needs to check its Condition again after registering, as
+    /// Notifications that happened before are not delivered to the new Waiter
+    pub fn register(&self, waker: &Waker) -> Arc<Waiter> {
+        let waiter = Arc::new(Waiter {
+            state: AtomicU8::new(WAITING),
+            waker: AtomicWaker::new(),
+        });
+        waiter.register(waker);
+
+        // The Receiver lives as long as the Sender, so this can not fail
+        let _ = self.tx.enqueue(waiter.clone());
+
+        // Pairs with the Fence in `notify_one`/`notify_all`, so that either
+        // the Caller sees the updated Condition or the Notifier sees this
+        // Waiter
+        atomic::fence(atomic::Ordering::SeqCst);
+
+        waiter
+    }
+
+    /// Notifies the longest waiting Waiter, that has not been cancelled yet.
+    ///
+    /// If there is currently no Waiter, the Notification is discarded
+    pub fn notify_one(&self) {
+        atomic::fence(atomic::Ordering::SeqCst);
+
+        self.pending.fetch_add(1, atomic::Ordering::SeqCst);
+        self.deliver();
+    }
+
+    /// Notifies all the current Waiters
+    pub fn notify_all(&self) {
+        atomic::fence(atomic::Ordering::SeqCst);
+
+        self.all.store(true, atomic::Ordering::SeqCst);
+        self.deliver();
+    }
+
+    fn deliver(&self) {
+        while self.pending.load(atomic::Ordering::SeqCst) > 0
+            || self.all.load(atomic::Ordering::SeqCst)
+        {
+            if self
+                .locked
+                .compare_exchange(
+                    false,
+                    true,
+                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::SeqCst,
+                )
+                .is_err()
+            {
+                // The current Holder will deliver our Notification, as it
+                // checks for new Notifications after releasing the Flag
+                return;
+            }
+
+            // Make sure we see all the Waiters that registered before the
+            // Notifications we are about to deliver
+            atomic::fence(atomic::Ordering::SeqCst);
+
+            let all = self.all.swap(false, atomic::Ordering::SeqCst);
+            let mut pending = self.pending.swap(0, atomic::Ordering::SeqCst);
+
+            // Safety:
+            // We hold the `locked` Flag, so we have exclusive Access
+            let rx = unsafe { &mut *self.rx.get() };
+            while all || pending > 0 {
+                match rx.try_dequeue() {
+                    Ok(waiter) => {
+                        if waiter.notify() {
+                            pending = pending.saturating_sub(1);
+                        }
+                    }
+                    Err(_) => break,
+                };
+            }
+
+            self.locked.store(false, atomic::Ordering::SeqCst);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::task::noop_waker;
+
+    #[test]
+    fn notify_fifo() {
+        let queue = WaiterQueue::new();
+        let waker = noop_waker();
+
+        let first = queue.register(&waker);
+        let second = queue.register(&waker);
+
+        queue.notify_one();
+        assert!(first.is_notified());
+        assert!(!second.is_notified());
+
+        queue.notify_one();
+        assert!(second.is_notified());
+    }
+
+    #[test]
+    fn skip_cancelled() {
+        let queue = WaiterQueue::new();
+        let waker = noop_waker();
+
+        let first = queue.register(&waker);
+        let second = queue.register(&waker);
+        assert!(!first.cancel());
+
+        queue.notify_one();
+        assert!(second.is_notified());
+    }
+
+    #[test]
+    fn discard_without_waiters() {
+        let queue = WaiterQueue::new();
+        let waker = noop_waker();
+
+        queue.notify_one();
+
+        let waiter = queue.register(&waker);
+        assert!(!waiter.is_notified());
+    }
+
+    #[test]
+    fn notify_all() {
+        let queue = WaiterQueue::new();
+        let waker = noop_waker();
+
+        let waiters: Vec<_> = (0..4).map(|_| queue.register(&waker)).collect();
+        queue.notify_all();
+
+        assert!(waiters.iter().all(|w| w.is_notified()));
+    }
+}
@@ -1,39 +1,81 @@
 use alloc::{sync::Arc, vec::Vec};
-use core::{cell::UnsafeCell, mem::MaybeUninit, sync::atomic};
+use core::{cell::UnsafeCell, mem::MaybeUninit};
 
-use crate::queues::{DequeueError, EnqueueError};
+use crate::sync::native::atomic;
+
+use crate::queues::{index_queue::IndexQueue, instrument::Metrics, DequeueError, EnqueueError};
 
 pub mod ncq;
-pub mod scq;
 
-/// The Receiver Side of a generic MPMC-Queue, according to the related Paper, which allows for
-/// different implementations of the Underlying Queue for `aq` and `fq`
-pub struct BoundedReceiver<T, UQ> {
+/// The State shared between all the Receivers and Senders of a single Queue
+struct Shared<T, UQ>
+where
+    UQ: UnderlyingQueue,
+{
     /// The actual Buffer for all the Data-Entries
-    data: Arc<Vec<UnsafeCell<MaybeUninit<T>>>>,
+    data: Vec<UnsafeCell<MaybeUninit<T>>>,
     /// The "available"-Queue, contains all the Indices at which Data is currently
     /// stored and can be read from
-    aq: Arc<UQ>,
+    aq: UQ,
     /// The Queue for all the free Indices at which no Data is stored and
     /// therefore can be used to store Data in
-    fq: Arc<UQ>,
+    fq: UQ,
+    /// The Hooks to report the Operations to
+    metrics: Metrics,
+    /// Used to park the Receivers that are blocked in `dequeue_blocking`
+    #[cfg(feature = "std")]
+    events: crate::utils::EventCount,
+}
+
+impl<T, UQ> Drop for Shared<T, UQ>
+where
+    UQ: UnderlyingQueue,
+{
+    fn drop(&mut self) {
+        // Every Index that is still in the "available"-Queue points to an
+        // initialized Entry, which has not been consumed by anyone and
+        // therefore still needs to be dropped.
+        //
+        // This only runs once the last Receiver or Sender is dropped, so no
+        // other Thread can still access the Data-Buffer
+        while let Some(index) = self.aq.dequeue() {
+            let bucket = self
+                .data
+                .get_mut(index)
+                .expect("The received Index should always be in the Bounds of the Data-Buffer");
+
+            // # Safety:
+            // The Index was in the "available"-Queue, so the Entry contains
+            // initialized Data and we are the only ones with access to it
+            unsafe { bucket.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+/// The Receiver Side of a generic MPMC-Queue, according to the related Paper, which allows for
+/// different implementations of the Underlying Queue for `aq` and `fq`
+pub struct BoundedReceiver<T, UQ>
+where
+    UQ: UnderlyingQueue,
+{
+    /// The Buffer and Index-Queues shared with all the other Handles
+    shared: Arc<Shared<T, UQ>>,
     /// The Number of current Receivers
     rx_count: Arc<atomic::AtomicU64>,
     /// The Number of current Producers
     tx_count: Arc<atomic::AtomicU64>,
+    /// Whether this Receiver has already been removed from `rx_count`
+    count_released: bool,
 }
 
 /// The Sender Side of a generic MPMC-Queue, according to the related Paper, which allows for
 /// different implementations of the Underlying Queue for `aq` and `fq`
-pub struct BoundedSender<T, UQ> {
-    /// The actual Buffer for all the Data-Entries
-    data: Arc<Vec<UnsafeCell<MaybeUninit<T>>>>,
-    /// The "available"-Queue, contains all the Indices at which Data is currently
-    /// stored and can be read from
-    aq: Arc<UQ>,
-    /// The Queue for all the free Indices at which no Data is stored and
-    /// therefore can be used to store Data in
-    fq: Arc<UQ>,
+pub struct BoundedSender<T, UQ>
+where
+    UQ: UnderlyingQueue,
+{
+    /// The Buffer and Index-Queues shared with all the other Handles
+    shared: Arc<Shared<T, UQ>>,
     /// The Number of current Receivers
     rx_count: Arc<atomic::AtomicU64>,
     /// The Number of current Producers
@@ -43,17 +85,53 @@ pub struct BoundedSender<T, UQ> {
 /// This trait needs to be implemented by the Underlying-Queue that is used for
 /// the `aq` and `fq` Queues in the overall Queue.
 pub trait UnderlyingQueue {
-    /// Enqueues the given Index
-    fn enqueue(&self, index: usize);
+    /// Enqueues the given Index, which only fails once the Queue has been
+    /// finalized
+    fn enqueue(&self, index: usize) -> Result<(), EnqueueError>;
     /// Attempts to dequeue some Index
     fn dequeue(&self) -> Option<usize>;
+    /// Returns the approximate Number of Indices currently in the Queue
+    fn len(&self) -> usize;
+    /// Finalizes the Queue, after which all Enqueues fail
+    fn finalize(&self);
+    /// Checks if the Queue has been finalized
+    fn is_finalized(&self) -> bool;
+    /// Attempts to dequeue some Index from a finalized Queue, which only
+    /// returns `None` once every Enqueue that was started before the Queue
+    /// was finalized has either been dequeued or failed
+    fn dequeue_finalized(&self) -> Option<usize>;
+}
+
+impl UnderlyingQueue for IndexQueue {
+    fn enqueue(&self, index: usize) -> Result<(), EnqueueError> {
+        IndexQueue::enqueue(self, index)
+    }
+    fn dequeue(&self) -> Option<usize> {
+        IndexQueue::dequeue(self)
+    }
+    fn len(&self) -> usize {
+        IndexQueue::len(self)
+    }
+    fn finalize(&self) {
+        IndexQueue::finalize(self)
+    }
+    fn is_finalized(&self) -> bool {
+        IndexQueue::is_finalized(self)
+    }
+    fn dequeue_finalized(&self) -> Option<usize> {
+        IndexQueue::dequeue_finalized(self)
+    }
 }
 
 fn new_queue<T, UQ>(
     aq: UQ,
     fq: UQ,
     capacity: usize,
-) -> (BoundedReceiver<T, UQ>, BoundedSender<T, UQ>) {
+    metrics: Metrics,
+) -> (BoundedReceiver<T, UQ>, BoundedSender<T, UQ>)
+where
+    UQ: UnderlyingQueue,
+{
     let data = {
         // Creates a Vec with the given Capacity
         let mut tmp = Vec::with_capacity(capacity);
@@ -61,26 +139,29 @@ fn new_queue<T, UQ>(
         for _ in 0..capacity {
             tmp.push(UnsafeCell::new(MaybeUninit::uninit()));
         }
-        Arc::new(tmp)
+        tmp
     };
 
-    let aq_arc = Arc::new(aq);
-    let fq_arc = Arc::new(fq);
+    let shared = Arc::new(Shared {
+        data,
+        aq,
+        fq,
+        metrics,
+        #[cfg(feature = "std")]
+        events: crate::utils::EventCount::new(),
+    });
 
     let rx_count = Arc::new(atomic::AtomicU64::new(1));
     let tx_count = Arc::new(atomic::AtomicU64::new(1));
 
     let rx = BoundedReceiver {
-        data: data.clone(),
-        aq: aq_arc.clone(),
-        fq: fq_arc.clone(),
+        shared: shared.clone(),
         rx_count: rx_count.clone(),
         tx_count: tx_count.clone(),
+        count_released: false,
     };
     let tx = BoundedSender {
-        data,
-        aq: aq_arc,
-        fq: fq_arc,
+        shared,
         rx_count,
         tx_count,
     };
@@ -93,20 +174,49 @@ fn new_queue<T, UQ>(
 // for giving "synchronized" access to the underlying Data by the nature of the
 // algorithm.
 // Whether or not T is Sync is actually not important because we never actually
-// use T anywhere in the Code but instead just pass it around
-unsafe impl<T, UQ> Sync for BoundedReceiver<T, UQ> {}
-unsafe impl<T, UQ> Sync for BoundedSender<T, UQ> {}
+// use T anywhere in the Code but instead just pass it around. However sharing
+// a Reference between Threads allows enqueuing and dequeuing Elements on
+// different Threads, so T still needs to be Send
+unsafe impl<T, UQ> Sync for BoundedReceiver<T, UQ>
+where
+    T: Send,
+    UQ: UnderlyingQueue,
+{
+}
+unsafe impl<T, UQ> Sync for BoundedSender<T, UQ>
+where
+    T: Send,
+    UQ: UnderlyingQueue,
+{
+}
 
 // Safety:
 // The Queue is only Send if T is send, because even though we dont use T in
 // the Algorithm, we still store it. Therefore if you can't send T across
 // threads you can't send the Queue across Threads, because we also store them
 // and would therefore try to send them across Threads.
-unsafe impl<T, UQ> Send for BoundedReceiver<T, UQ> where T: Send {}
-unsafe impl<T, UQ> Send for BoundedSender<T, UQ> where T: Send {}
+unsafe impl<T, UQ> Send for BoundedReceiver<T, UQ>
+where
+    T: Send,
+    UQ: UnderlyingQueue,
+{
+}
+unsafe impl<T, UQ> Send for BoundedSender<T, UQ>
+where
+    T: Send,
+    UQ: UnderlyingQueue,
+{
+}
 
 pub fn queue_ncq<T>(
     capacity: usize,
+) -> (BoundedReceiver<T, ncq::Queue>, BoundedSender<T, ncq::Queue>) {
+    queue_ncq_instrumented(capacity, Metrics::none())
+}
+
+pub fn queue_ncq_instrumented<T>(
+    capacity: usize,
+    metrics: Metrics,
 ) -> (BoundedReceiver<T, ncq::Queue>, BoundedSender<T, ncq::Queue>) {
     // Create both of the needed Queues
     let aq = ncq::Queue::new(capacity);
@@ -114,25 +224,29 @@ pub fn queue_ncq<T>(
 
     // Fill `fq` with all the available Indices, in this case 0-capacity
     for index in 0..capacity {
-        fq.enqueue(index);
+        fq.enqueue(index)
+            .expect("The free-Queue is never finalized");
     }
 
-    new_queue(aq, fq, capacity)
+    new_queue(aq, fq, capacity, metrics)
 }
 
 pub fn queue_scq<T>(
     capacity: usize,
-) -> (BoundedReceiver<T, scq::Queue>, BoundedSender<T, scq::Queue>) {
-    // Create both of the needed Queues
-    let aq = scq::Queue::new(capacity);
-    let fq = scq::Queue::new(capacity);
-
-    // Fill `fq` with all the available Indices, in this case 0-capacity
-    for index in 0..capacity {
-        fq.enqueue(index);
-    }
+) -> (BoundedReceiver<T, IndexQueue>, BoundedSender<T, IndexQueue>) {
+    queue_scq_instrumented(capacity, Metrics::none())
+}
 
-    new_queue(aq, fq, capacity)
+pub fn queue_scq_instrumented<T>(
+    capacity: usize,
+    metrics: Metrics,
+) -> (BoundedReceiver<T, IndexQueue>, BoundedSender<T, IndexQueue>) {
+    // Create both of the needed Queues, with `fq` already containing all the
+    // available Indices, in this case 0-capacity
+    let aq = IndexQueue::new(capacity);
+    let fq = IndexQueue::new_full(capacity);
+
+    new_queue(aq, fq, capacity, metrics)
 }
 
 impl<T, UQ> BoundedSender<T, UQ>
@@ -150,16 +264,21 @@ where
         }
 
         // Attempt to get a free-Index to insert the data into
-        let index = match self.fq.dequeue() {
+        let index = match self.shared.fq.dequeue() {
             Some(i) => i,
-            None => return Err((EnqueueError::Full, data)),
+            None => {
+                self.shared.metrics.full();
+                return Err((EnqueueError::Full, data));
+            }
         };
 
         // Actually obtain the Bucket to insert into
         let bucket = self
+            .shared
             .data
             .get(index)
             .expect("The received Index should always be in the Bounds of the Data Buffer");
+        let bucket_ptr = bucket.get();
 
         // # Safety:
         // It is safe to get mutable access to the single Bucket of Data, because we got the index
@@ -168,23 +287,78 @@ where
         // Every index only exists once in either the free-Qeueu or the available-Queue and therefore
         // no two or more threads can obtain the same index at the same time and attempt to write to it
         // or read from it.
-        let bucket_ptr = bucket.get();
         unsafe { bucket_ptr.write(MaybeUninit::new(data)) };
 
-        // Enqueue the now filled index into the Queue for Indices that contain data
-        self.aq.enqueue(index);
+        // Enqueue the now filled index into the Queue for Indices that contain data,
+        // which fails if the Queue has been closed in the meantime
+        if let Err(err) = self.shared.aq.enqueue(index) {
+            // # Safety:
+            // The Index never made it into the available-Queue, so we still
+            // own the Bucket and the Data we just wrote to it
+            let data = unsafe { bucket_ptr.read().assume_init() };
+            self.shared
+                .fq
+                .enqueue(index)
+                .expect("The free-Queue is never finalized");
+            return Err((err, data));
+        }
+        self.shared.metrics.enqueue();
+        #[cfg(feature = "std")]
+        self.shared.events.notify_one();
         Ok(())
     }
 
     /// Checks if the Receiving Half of the Queue has been closed
     pub fn is_closed(&self) -> bool {
-        self.rx_count.load(atomic::Ordering::Acquire) == 0
+        self.rx_count.load(atomic::Ordering::Acquire) == 0 || self.shared.aq.is_finalized()
+    }
+
+    /// The maximum Number of Elements the Queue can hold
+    pub fn capacity(&self) -> usize {
+        self.shared.data.len()
+    }
+
+    /// The approximate Number of occupied Slots, which is derived from the
+    /// Number of Indices left in the free-Queue and therefore also includes
+    /// Slots that are currently being written to or read from
+    pub fn len(&self) -> usize {
+        self.capacity().saturating_sub(self.shared.fq.len())
+    }
+
+    /// Checks if all the Slots are currently occupied, see [`len`](Self::len)
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+}
+
+impl<T, UQ> Clone for BoundedSender<T, UQ>
+where
+    UQ: UnderlyingQueue,
+{
+    /// Creates another Sender for the same Queue, which keeps the Queue open
+    /// for the Receivers until it is dropped as well
+    fn clone(&self) -> Self {
+        self.tx_count.fetch_add(1, atomic::Ordering::Relaxed);
+
+        Self {
+            shared: self.shared.clone(),
+            rx_count: self.rx_count.clone(),
+            tx_count: self.tx_count.clone(),
+        }
     }
 }
 
-impl<T, UQ> Drop for BoundedSender<T, UQ> {
+impl<T, UQ> Drop for BoundedSender<T, UQ>
+where
+    UQ: UnderlyingQueue,
+{
     fn drop(&mut self) {
-        self.tx_count.fetch_sub(1, atomic::Ordering::AcqRel);
+        if self.tx_count.fetch_sub(1, atomic::Ordering::AcqRel) == 1 {
+            // Wake up all the blocked Receivers, so they can observe that the
+            // Queue has been closed
+            #[cfg(feature = "std")]
+            self.shared.events.notify_all();
+        }
     }
 }
 
@@ -193,7 +367,7 @@ where
     UQ: UnderlyingQueue,
 {
     pub fn dequeue(&self) -> Result<T, DequeueError> {
-        let index = match self.aq.dequeue() {
+        let index = match self.shared.aq.dequeue() {
             Some(i) => i,
             None => {
                 if self.is_closed() {
@@ -205,9 +379,11 @@ where
         };
 
         let bucket = self
+            .shared
             .data
             .get(index)
             .expect("The received Index should always be in the Bounds of the Data-Buffer");
+        let bucket_ptr = bucket.get();
 
         // # Safety:
         // It is safe to get mutable access to the single Bucket of Data, because we got the index
@@ -216,23 +392,135 @@ where
         // Every index only exists once in either the free-Qeueu or the available-Queue and therefore
         // no two or more threads can obtain the same index at the same time and attempt to write to it
         // or read from it.
-        let bucket_ptr = bucket.get();
-        let data = unsafe { bucket_ptr.replace(MaybeUninit::uninit()).assume_init() };
-
-        self.fq.enqueue(index);
+        //
+        // The Data is only read and the Bucket is left as is, because it is
+        // considered uninitialized again once its Index is back in the
+        // free-Queue. This avoids a Store to the Bucket and therefore keeps
+        // its Cache-Line clean for the next Producer that writes to it
+        let data = unsafe { bucket_ptr.read().assume_init() };
+
+        self.shared
+            .fq
+            .enqueue(index)
+            .expect("The free-Queue is never finalized");
+        self.shared.metrics.dequeue();
 
         Ok(data)
     }
 
-    /// Checks if the Sending Half of the Queue has been closed
+    /// Dequeues an Element, blocking the current Thread until one becomes
+    /// available or returns `None` once the Queue is closed and empty.
+    ///
+    /// The Receiver first spins with an exponential [`Backoff`] and only
+    /// parks the Thread if the Queue stays empty for longer, so short Gaps
+    /// between Elements do not pay for a full Sleep and Wakeup
+    ///
+    /// [`Backoff`]: crate::utils::Backoff
+    #[cfg(feature = "std")]
+    pub fn dequeue_blocking(&self) -> Option<T> {
+        let backoff = crate::utils::Backoff::new();
+
+        loop {
+            match self.dequeue() {
+                Ok(data) => return Some(data),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+                Err(DequeueError::Empty) => {}
+            };
+
+            if !backoff.is_completed() {
+                backoff.snooze();
+                continue;
+            }
+
+            let key = self.shared.events.prepare_wait();
+            match self.dequeue() {
+                Ok(data) => {
+                    self.shared.events.cancel_wait();
+                    return Some(data);
+                }
+                Err(DequeueError::Closed | DequeueError::Cancelled) => {
+                    self.shared.events.cancel_wait();
+                    return None;
+                }
+                Err(DequeueError::Empty) => self.shared.events.wait(key),
+            };
+        }
+    }
+
+    /// Checks if the Sending Half of the Queue has been closed, either by
+    /// dropping all the Senders or by [`close_and_drain`](Self::close_and_drain)
     pub fn is_closed(&self) -> bool {
-        self.tx_count.load(atomic::Ordering::Acquire) == 0
+        self.tx_count.load(atomic::Ordering::Acquire) == 0 || self.shared.aq.is_finalized()
+    }
+
+    /// Closes the Queue for all the Senders and Receivers and then dequeues
+    /// all the Elements that are still left.
+    ///
+    /// The Queue is closed by finalizing the available-Queue, so every
+    /// Enqueue either publishes its Index before that and is drained here,
+    /// or fails and hands the Element back to its Sender. Other Receivers
+    /// may still dequeue some of the remaining Elements concurrently, but
+    /// no Element is lost
+    pub fn close_and_drain(mut self) -> Vec<T> {
+        self.shared.aq.finalize();
+        self.rx_count.fetch_sub(1, atomic::Ordering::AcqRel);
+        self.count_released = true;
+
+        // Wake up all the other blocked Receivers, so they can observe that
+        // the Queue has been closed
+        #[cfg(feature = "std")]
+        self.shared.events.notify_all();
+
+        let mut result = Vec::new();
+        while let Some(index) = self.shared.aq.dequeue_finalized() {
+            let bucket = self
+                .shared
+                .data
+                .get(index)
+                .expect("The received Index should always be in the Bounds of the Data-Buffer");
+
+            // # Safety:
+            // The Index was dequeued from the available-Queue, so the Bucket
+            // contains initialized Data, see `dequeue`
+            result.push(unsafe { bucket.get().read().assume_init() });
+
+            self.shared
+                .fq
+                .enqueue(index)
+                .expect("The free-Queue is never finalized");
+            self.shared.metrics.dequeue();
+        }
+
+        result
+    }
+}
+
+impl<T, UQ> Clone for BoundedReceiver<T, UQ>
+where
+    UQ: UnderlyingQueue,
+{
+    /// Creates another Receiver for the same Queue, which keeps the Queue
+    /// open for the Senders until it is dropped as well
+    fn clone(&self) -> Self {
+        self.rx_count.fetch_add(1, atomic::Ordering::Relaxed);
+
+        Self {
+            shared: self.shared.clone(),
+            rx_count: self.rx_count.clone(),
+            tx_count: self.tx_count.clone(),
+            count_released: false,
+        }
     }
 }
 
-impl<T, UQ> Drop for BoundedReceiver<T, UQ> {
+impl<T, UQ> Drop for BoundedReceiver<T, UQ>
+where
+    UQ: UnderlyingQueue,
+{
     fn drop(&mut self) {
-        self.rx_count.fetch_sub(1, atomic::Ordering::AcqRel);
+        if !self.count_released {
+            self.rx_count.fetch_sub(1, atomic::Ordering::AcqRel);
+        }
     }
 }
 
@@ -305,6 +593,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sender_len() {
+        let (rx, tx) = queue_scq::<u64>(4);
+        assert_eq!(4, tx.capacity());
+        assert_eq!(0, tx.len());
+
+        tx.try_enqueue(1).unwrap();
+        tx.try_enqueue(2).unwrap();
+        assert_eq!(2, tx.len());
+        assert!(!tx.is_full());
+
+        tx.try_enqueue(3).unwrap();
+        tx.try_enqueue(4).unwrap();
+        assert!(tx.is_full());
+
+        rx.dequeue().unwrap();
+        assert_eq!(3, tx.len());
+    }
+
+    #[test]
+    fn close_and_drain() {
+        let (rx, tx) = queue_scq::<u64>(10);
+
+        for index in 0..5 {
+            assert_eq!(Ok(()), tx.try_enqueue(index));
+        }
+
+        assert_eq!(Vec::from([0, 1, 2, 3, 4]), rx.close_and_drain());
+        assert!(tx.is_closed());
+        assert_eq!(Err((EnqueueError::Closed, 15)), tx.try_enqueue(15));
+    }
+
     #[test]
     fn receiver_closed() {
         let (rx, tx) = queue_ncq::<u64>(10);
@@ -323,4 +643,195 @@ mod tests {
         drop(rx);
         assert!(tx.is_closed());
     }
+
+    #[test]
+    fn cloned_senders_close() {
+        let (rx, tx) = queue_ncq::<u64>(10);
+        let senders: Vec<_> = (0..4).map(|_| tx.clone()).collect();
+
+        drop(tx);
+        assert!(!rx.is_closed());
+
+        for (index, sender) in senders.into_iter().enumerate() {
+            assert!(!rx.is_closed());
+            assert_eq!(Ok(()), sender.try_enqueue(index as u64));
+        }
+        assert!(rx.is_closed());
+
+        // The Elements are still available after the Queue has been closed
+        for index in 0..4 {
+            assert_eq!(Ok(index), rx.dequeue());
+        }
+        assert_eq!(Err(DequeueError::Closed), rx.dequeue());
+    }
+    #[test]
+    fn cloned_receivers_close() {
+        let (rx, tx) = queue_scq::<u64>(10);
+        let receivers: Vec<_> = (0..4).map(|_| rx.clone()).collect();
+
+        drop(rx);
+        assert!(!tx.is_closed());
+
+        tx.try_enqueue(13).unwrap();
+        assert_eq!(Ok(13), receivers[2].dequeue());
+
+        drop(receivers);
+        assert!(tx.is_closed());
+        assert_eq!(Err((EnqueueError::Closed, 15)), tx.try_enqueue(15));
+    }
+    #[test]
+    fn cloned_receiver_close_and_drain() {
+        let (rx, tx) = queue_ncq::<u64>(10);
+        let rx2 = rx.clone();
+
+        tx.try_enqueue(1).unwrap();
+        tx.try_enqueue(2).unwrap();
+        assert_eq!(Ok(1), rx2.dequeue());
+
+        // Closing the Queue also closes it for the other Receivers
+        assert_eq!(Vec::from([2]), rx.close_and_drain());
+        assert!(tx.is_closed());
+        assert!(rx2.is_closed());
+        assert_eq!(Err((EnqueueError::Closed, 3)), tx.try_enqueue(3));
+        assert_eq!(Err(DequeueError::Closed), rx2.dequeue());
+    }
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    #[cfg(feature = "std")]
+    fn close_and_drain_concurrent() {
+        fn run<UQ>(rx: BoundedReceiver<u64, UQ>, tx: BoundedSender<u64, UQ>)
+        where
+            UQ: UnderlyingQueue + Send + Sync + 'static,
+        {
+            let producers: Vec<_> = (0..2)
+                .map(|_| {
+                    let tx = tx.clone();
+                    std::thread::spawn(move || {
+                        let mut enqueued = 0;
+                        loop {
+                            match tx.try_enqueue(1) {
+                                Ok(()) => enqueued += 1,
+                                Err((EnqueueError::Full, _)) => std::thread::yield_now(),
+                                Err((_, _)) => return enqueued,
+                            }
+                        }
+                    })
+                })
+                .collect();
+            drop(tx);
+
+            let mut dequeued = 0;
+            for _ in 0..200 {
+                if rx.dequeue().is_ok() {
+                    dequeued += 1;
+                }
+            }
+            dequeued += rx.close_and_drain().len();
+
+            let enqueued: usize = producers.into_iter().map(|p| p.join().unwrap()).sum();
+            assert_eq!(enqueued, dequeued);
+        }
+
+        for _ in 0..10 {
+            let (rx, tx) = queue_ncq::<u64>(4);
+            run(rx, tx);
+            let (rx, tx) = queue_scq::<u64>(4);
+            run(rx, tx);
+        }
+    }
+    #[test]
+    #[cfg(feature = "std")]
+    fn cloned_handles_threads() {
+        let (rx, tx) = queue_ncq::<u64>(8);
+
+        let producers: Vec<_> = (0..4)
+            .map(|_| {
+                let tx = tx.clone();
+                std::thread::spawn(move || {
+                    for index in 0..250 {
+                        let mut data = index;
+                        while let Err((_, d)) = tx.try_enqueue(data) {
+                            data = d;
+                            std::thread::yield_now();
+                        }
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let consumers: Vec<_> = (0..4)
+            .map(|_| {
+                let rx = rx.clone();
+                std::thread::spawn(move || {
+                    let mut count = 0;
+                    while rx.dequeue_blocking().is_some() {
+                        count += 1;
+                    }
+                    count
+                })
+            })
+            .collect();
+        drop(rx);
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let total: usize = consumers.into_iter().map(|c| c.join().unwrap()).sum();
+        assert_eq!(1000, total);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn dequeue_blocking_wakeup() {
+        let (rx, tx) = queue_scq::<u64>(10);
+
+        let handle = std::thread::spawn(move || {
+            // Give the Receiver enough Time to park itself
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            tx.try_enqueue(13).unwrap();
+            tx
+        });
+
+        assert_eq!(Some(13), rx.dequeue_blocking());
+        drop(handle.join().unwrap());
+        assert_eq!(None, rx.dequeue_blocking());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn dequeue_blocking_many() {
+        let (rx, tx) = queue_ncq::<u64>(4);
+        let rx = Arc::new(rx);
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let rx = rx.clone();
+                std::thread::spawn(move || {
+                    let mut count = 0;
+                    while rx.dequeue_blocking().is_some() {
+                        count += 1;
+                    }
+                    count
+                })
+            })
+            .collect();
+
+        for index in 0..1000 {
+            let mut data = index;
+            while let Err((_, d)) = tx.try_enqueue(data) {
+                data = d;
+                std::thread::yield_now();
+            }
+        }
+        // Wait until the Workers dequeued everything, before closing the
+        // Queue and waking them up
+        while tx.len() > 0 {
+            std::thread::yield_now();
+        }
+        drop(tx);
+
+        let total: usize = workers.into_iter().map(|w| w.join().unwrap()).sum();
+        assert_eq!(1000, total);
+    }
 }
@@ -1,7 +1,14 @@
+use crate::sync::native::atomic;
 use alloc::vec::Vec;
-use core::sync::atomic;
 
 use super::UnderlyingQueue;
+use crate::queues::EnqueueError;
+
+/// The Bit in the Tail that marks the Queue as finalized
+const FINALIZED: usize = 1usize << (usize::BITS - 1);
+/// The Index stored in the Entry at the Tail of a finalized Queue, which
+/// stops any Enqueue that still attempts to use the Entry
+const POISON: u32 = u32::MAX;
 
 struct QueueEntry(atomic::AtomicU64);
 
@@ -66,9 +73,13 @@ impl Queue {
 }
 
 impl UnderlyingQueue for Queue {
-    fn enqueue(&self, index: usize) {
+    fn enqueue(&self, index: usize) -> Result<(), EnqueueError> {
         let tail = loop {
-            let tail = self.tail.load(atomic::Ordering::Acquire);
+            let tail = self.tail.load(atomic::Ordering::SeqCst);
+            if tail & FINALIZED != 0 {
+                return Err(EnqueueError::Closed);
+            }
+
             let tail_cycle = Self::cycle(tail, self.entries.capacity());
             let j = tail % self.entries.capacity();
 
@@ -109,6 +120,14 @@ impl UnderlyingQueue for Queue {
             atomic::Ordering::AcqRel,
             atomic::Ordering::Relaxed,
         );
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        let head = self.head.load(atomic::Ordering::Acquire);
+        let tail = self.tail.load(atomic::Ordering::Acquire) & !FINALIZED;
+
+        tail.saturating_sub(head).min(self.entries.capacity())
     }
 
     fn dequeue(&self) -> Option<usize> {
@@ -129,6 +148,10 @@ impl UnderlyingQueue for Queue {
 
                 continue;
             }
+            // The End of a finalized Queue has been reached
+            if QueueEntry::index(raw_entry) == POISON {
+                return None;
+            }
 
             if self
                 .head
@@ -146,6 +169,49 @@ impl UnderlyingQueue for Queue {
 
         Some(raw_index as usize)
     }
+
+    fn finalize(&self) {
+        let tail = self.tail.fetch_or(FINALIZED, atomic::Ordering::SeqCst);
+        if tail & FINALIZED != 0 {
+            return;
+        }
+
+        // Every Entry before the Tail is already filled, but an Enqueue that
+        // loaded the Tail before it was finalized may still fill the Entry at
+        // the Tail itself. Either that Enqueue wins or the Entry is poisoned,
+        // which makes the Enqueue retry and observe the finalized Tail
+        let tail_cycle = Self::cycle(tail, self.entries.capacity());
+        let entry = self.entries.get(tail % self.entries.capacity()).expect("Because we always wrap around once we reach the end of the Vector, we can be sure that the Index we try to access is in the Vec itself");
+        loop {
+            let raw_entry = entry.load(atomic::Ordering::Acquire);
+            if QueueEntry::cycle(raw_entry) == tail_cycle {
+                return;
+            }
+
+            if entry
+                .cas(
+                    raw_entry,
+                    tail_cycle,
+                    POISON,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                return;
+            }
+        }
+    }
+
+    fn is_finalized(&self) -> bool {
+        self.tail.load(atomic::Ordering::Acquire) & FINALIZED != 0
+    }
+
+    fn dequeue_finalized(&self) -> Option<usize> {
+        // Once the Queue is finalized, no Entry after the Tail can be filled
+        // anymore, so an empty Dequeue means that the Queue stays empty
+        self.dequeue()
+    }
 }
 
 #[cfg(test)]
@@ -161,13 +227,26 @@ mod tests {
     fn enqueue_single() {
         let queue = Queue::new(10);
 
-        queue.enqueue(13);
+        queue.enqueue(13).unwrap();
     }
+    #[test]
+    fn len() {
+        let queue = Queue::new(10);
+        assert_eq!(0, queue.len());
+
+        queue.enqueue(13).unwrap();
+        queue.enqueue(14).unwrap();
+        assert_eq!(2, queue.len());
+
+        queue.dequeue();
+        assert_eq!(1, queue.len());
+    }
+
     #[test]
     fn enqueue_dequeue_single() {
         let queue = Queue::new(10);
 
-        queue.enqueue(13);
+        queue.enqueue(13).unwrap();
         assert_eq!(Some(13), queue.dequeue());
     }
     #[test]
@@ -175,8 +254,31 @@ mod tests {
         let queue = Queue::new(10);
 
         for index in 0..20 {
-            queue.enqueue(index);
+            queue.enqueue(index).unwrap();
             assert_eq!(Some(index), queue.dequeue());
         }
     }
+
+    #[test]
+    fn finalize() {
+        let queue = Queue::new(4);
+        for index in 0..6 {
+            queue.enqueue(index % 4).unwrap();
+            if index < 3 {
+                assert_eq!(Some(index % 4), queue.dequeue());
+            }
+        }
+
+        assert!(!queue.is_finalized());
+        queue.finalize();
+        assert!(queue.is_finalized());
+
+        assert_eq!(Err(EnqueueError::Closed), queue.enqueue(0));
+        assert_eq!(3, queue.len());
+        assert_eq!(Some(3), queue.dequeue_finalized());
+        assert_eq!(Some(0), queue.dequeue_finalized());
+        assert_eq!(Some(1), queue.dequeue_finalized());
+        assert_eq!(None, queue.dequeue_finalized());
+        assert_eq!(None, queue.dequeue());
+    }
 }
@@ -12,28 +12,74 @@
 use crate::sync::atomic;
 use std::{fmt::Debug, sync::Arc};
 
-use crate::{hyaline, queues::DequeueError};
+#[cfg(feature = "async")]
+use crate::queues::cooperative::{self, CooperativeDequeue};
+use crate::{
+    hyaline,
+    queues::{
+        cancel::{self, CancelToken},
+        instrument::Metrics,
+        shutdown::{Shutdown, ShutdownReport},
+        DequeueError, EnqueueError,
+    },
+    utils::{Backoff, EventCount, SegmentAlloc, Segments},
+};
 
 mod async_queue;
+pub(crate) use async_queue::wrap_queue;
 pub use async_queue::{async_queue, AsyncReceiver, AsyncSender};
 
 mod queue;
 
+mod stats;
+#[cfg(feature = "metrics")]
+pub use stats::Stats;
+
 const BUFFER_SIZE: usize = 128;
 
 /// The Receiver Half of an unbounded LSCQ Queue
 pub struct Receiver<T> {
     head: atomic::AtomicPtr<queue::BoundedQueue<T>>,
+    /// The Tail shared with the Sender, which needs to be moved forward
+    /// before the Head can move past it
+    tail: Arc<atomic::AtomicPtr<queue::BoundedQueue<T>>>,
     rx_count: Arc<atomic::AtomicU64>,
     tx_count: Arc<atomic::AtomicU64>,
     hyaline_instance: Arc<hyaline::Hyaline>,
+    /// Whether this Receiver has already been removed from `rx_count`
+    count_released: bool,
+    /// The Hooks to report the Operations to
+    metrics: Metrics,
+    /// The internal Counters shared with the Sender
+    stats: Arc<stats::Counters>,
+    /// Used to park the Receivers that are blocked in `dequeue_blocking`
+    events: Arc<EventCount>,
+    /// Counts the Elements that are dropped together with the Queue
+    shutdown: Arc<Shutdown>,
+    /// The Wakers of the async Receivers and the Number of sync Senders,
+    /// that will not wake them up
+    shared: Arc<async_queue::Shared>,
 }
 /// The Sender Half of an unbounded LSCQ Queue
 pub struct Sender<T> {
-    tail: atomic::AtomicPtr<queue::BoundedQueue<T>>,
+    tail: Arc<atomic::AtomicPtr<queue::BoundedQueue<T>>>,
     rx_count: Arc<atomic::AtomicU64>,
     tx_count: Arc<atomic::AtomicU64>,
     hyaline_instance: Arc<hyaline::Hyaline>,
+    /// The Hooks to report the Operations to
+    metrics: Metrics,
+    /// The internal Counters shared with the Receiver
+    stats: Arc<stats::Counters>,
+    /// Used to wake up the Receivers that are blocked in `dequeue_blocking`
+    events: Arc<EventCount>,
+    /// Used to allocate the Buffers of new Segments
+    segments: Segments,
+    /// Counts the Elements that are dropped together with the Queue, which
+    /// is shared with every Segment
+    shutdown: Arc<Shutdown>,
+    /// The Wakers of the async Receivers and the Number of sync Senders,
+    /// that will not wake them up
+    shared: Arc<async_queue::Shared>,
 }
 
 impl<T> Debug for Receiver<T> {
@@ -47,35 +93,113 @@ impl<T> Debug for Sender<T> {
     }
 }
 
+// Safety:
+// The Segments are only referenced through AtomicPtrs, which would make the
+// Halves Send and Sync for any T, but they are used to move the Elements
+// between Threads, so T needs to be Send
+unsafe impl<T> Send for Receiver<T> where T: Send {}
+unsafe impl<T> Sync for Receiver<T> where T: Send {}
+unsafe impl<T> Send for Sender<T> where T: Send {}
+unsafe impl<T> Sync for Sender<T> where T: Send {}
+
 fn free_fn<T>(ptr: *const ()) {
-    let boxed = unsafe { Box::from_raw(ptr as *mut queue::BoundedQueue<T>) };
-    drop(boxed);
+    unsafe { crate::poison::drop_box(ptr as *mut queue::BoundedQueue<T>) };
 }
 
-/// Creates a new unbounded LSCQ Queue
+/// Creates a new unbounded LSCQ Queue, returning the Receiver first, see
+/// [`channel`] for the `(Sender, Receiver)` Variant
 pub fn queue<T>() -> (Receiver<T>, Sender<T>) {
-    let initial_buffer = Box::new(queue::new_queue(BUFFER_SIZE));
+    queue_instrumented(Metrics::none())
+}
+
+/// Creates a new unbounded LSCQ Queue and returns the
+/// Halves as ([`Sender`], [`Receiver`]), in the same Order as
+/// `std::sync::mpsc::channel`
+///
+/// # Example
+/// ```
+/// # use nolock::queues::mpmc::unbounded;
+/// let (tx, rx) = unbounded::channel::<usize>();
+///
+/// tx.enqueue(13).unwrap();
+/// assert_eq!(Ok(13), rx.try_dequeue());
+/// ```
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let (rx, tx) = queue();
+    (tx, rx)
+}
+
+/// Creates a new unbounded LSCQ Queue, that reports all its Operations to the
+/// given [`QueueMetrics`](crate::queues::metrics::QueueMetrics)
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub fn queue_with_metrics<T>(
+    metrics: Arc<dyn crate::queues::metrics::QueueMetrics>,
+) -> (Receiver<T>, Sender<T>) {
+    queue_instrumented(Metrics::new(metrics))
+}
+
+/// Creates a new unbounded LSCQ Queue, that allocates the Buffers of its
+/// Segments using the given [`SegmentAlloc`], for example to place them on a
+/// specific NUMA-Node
+///
+/// # Example
+/// ```
+/// # use nolock::queues::mpmc::unbounded;
+/// # use std::sync::Arc;
+/// let (rx, tx) = unbounded::queue_with_segment_alloc::<usize>(Arc::new(std::alloc::System));
+///
+/// tx.enqueue(13).unwrap();
+/// assert_eq!(Ok(13), rx.try_dequeue());
+/// ```
+pub fn queue_with_segment_alloc<T>(alloc: Arc<dyn SegmentAlloc>) -> (Receiver<T>, Sender<T>) {
+    queue_in(Metrics::none(), Segments::new(alloc))
+}
+
+pub(crate) fn queue_instrumented<T>(metrics: Metrics) -> (Receiver<T>, Sender<T>) {
+    queue_in(metrics, Segments::default())
+}
+
+fn queue_in<T>(metrics: Metrics, segments: Segments) -> (Receiver<T>, Sender<T>) {
+    let shutdown = Arc::new(Shutdown::new());
+    let initial_buffer = Box::new(queue::new_queue(BUFFER_SIZE, &segments, shutdown.clone()));
     let initial_buffer_ptr = Box::into_raw(initial_buffer);
 
     let head = atomic::AtomicPtr::new(initial_buffer_ptr);
-    let tail = atomic::AtomicPtr::new(initial_buffer_ptr);
+    let tail = Arc::new(atomic::AtomicPtr::new(initial_buffer_ptr));
 
     let rx_count = Arc::new(atomic::AtomicU64::new(1));
     let tx_count = Arc::new(atomic::AtomicU64::new(1));
 
     let instance = Arc::new(hyaline::Hyaline::new(free_fn::<T>));
+    let stats = Arc::new(stats::Counters::default());
+    let events = Arc::new(EventCount::new());
+    let shared = Arc::new(async_queue::Shared::new());
 
     let rx = Receiver {
         head,
+        tail: tail.clone(),
         rx_count: rx_count.clone(),
         tx_count: tx_count.clone(),
         hyaline_instance: instance.clone(),
+        count_released: false,
+        metrics: metrics.clone(),
+        stats: stats.clone(),
+        events: events.clone(),
+        shutdown: shutdown.clone(),
+        shared: shared.clone(),
     };
     let tx = Sender {
         tail,
         rx_count,
         tx_count,
         hyaline_instance: instance,
+        metrics,
+        stats,
+        events,
+        shared,
+        segments,
+        shutdown,
     };
 
     (rx, tx)
@@ -89,13 +213,46 @@ impl<T> Sender<T> {
     /// # Example
     /// ```rust
     /// # use nolock::queues::mpmc::unbounded;
+    /// # use nolock::queues::EnqueueError;
     /// let (rx, tx) = unbounded::queue::<usize>();
     ///
     /// assert_eq!(Ok(()), tx.enqueue(123));
     ///
+    /// drop(rx);
+    /// assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue(13));
+    /// ```
+    pub fn enqueue(&self, data: T) -> Result<(), (T, EnqueueError)> {
+        self.enqueue_inner(data, true)
+    }
+
+    /// Attempts to enqueue the given Data on the Queue, like
+    /// [`enqueue`](Self::enqueue), but without allocating a new Segment.
+    ///
+    /// This allows Callers to bound the Growth of the Queue themselves, as it
+    /// fails with [`EnqueueError::Full`] once the current Segment is full,
+    /// even though [`enqueue`](Self::enqueue) would still succeed.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::mpmc::unbounded;
+    /// # use nolock::queues::EnqueueError;
+    /// let (rx, tx) = unbounded::queue::<usize>();
+    ///
+    /// let mut enqueued = 0;
+    /// while tx.try_enqueue(enqueued).is_ok() {
+    ///     enqueued += 1;
+    /// }
+    /// assert_eq!(Err((13, EnqueueError::Full)), tx.try_enqueue(13));
+    ///
+    /// // A normal Enqueue allocates a new Segment instead
+    /// assert_eq!(Ok(()), tx.enqueue(13));
     /// # drop(rx);
     /// ```
-    pub fn enqueue(&self, mut data: T) -> Result<(), T> {
+    pub fn try_enqueue(&self, data: T) -> Result<(), (T, EnqueueError)> {
+        self.enqueue_inner(data, false)
+    }
+
+    fn enqueue_inner(&self, mut data: T, allocate: bool) -> Result<(), (T, EnqueueError)> {
         let handle = self.hyaline_instance.enter();
 
         loop {
@@ -103,28 +260,52 @@ impl<T> Sender<T> {
             let tail = unsafe { &*tail_ptr };
 
             if self.is_closed() {
-                return Err(data);
+                return Err((data, EnqueueError::Closed));
             }
 
             let next_ptr = tail.next.load(atomic::Ordering::Acquire);
             if !next_ptr.is_null() {
-                let _ = self.tail.compare_exchange(
+                // The Tail lags behind the last Segment, so we help to move
+                // it forward before trying again
+                self.stats.tail_lagging();
+                self.stats.tail_cas(self.tail.compare_exchange(
                     tail_ptr,
                     next_ptr,
                     atomic::Ordering::AcqRel,
                     atomic::Ordering::Relaxed,
-                );
+                ));
                 continue;
             }
 
             data = match tail.try_enqueue(data) {
-                Ok(_) => return Ok(()),
+                Ok(_) => {
+                    self.stats.fast_path();
+                    self.metrics.enqueue();
+                    self.events.notify_one();
+                    return Ok(());
+                }
                 Err((_, d)) => d,
             };
 
+            if !allocate {
+                // Another Sender may have appended a new Segment in the mean
+                // Time, which we can still use without allocating, and the
+                // Segment may also have been finalized because the Queue
+                // was closed
+                if tail.next.load(atomic::Ordering::Acquire).is_null() && !self.is_closed() {
+                    return Err((data, EnqueueError::Full));
+                }
+                continue;
+            }
+
             let (n_queue_ptr, n_queue) = {
-                let raw = Box::new(queue::new_queue(BUFFER_SIZE));
+                let raw = Box::new(queue::new_queue(
+                    BUFFER_SIZE,
+                    &self.segments,
+                    self.shutdown.clone(),
+                ));
                 let raw_ptr = Box::into_raw(raw);
+                self.metrics.segment_alloc();
 
                 let raw_ref = unsafe { &*raw_ptr };
                 (raw_ptr, raw_ref)
@@ -134,21 +315,35 @@ impl<T> Sender<T> {
             match tail.next.compare_exchange(
                 std::ptr::null_mut(),
                 n_queue_ptr,
-                atomic::Ordering::AcqRel,
+                atomic::Ordering::SeqCst,
                 atomic::Ordering::Relaxed,
             ) {
                 Ok(_) => {
-                    let _ = self.tail.compare_exchange(
+                    self.stats.tail_cas(self.tail.compare_exchange(
                         tail_ptr,
                         n_queue_ptr,
                         atomic::Ordering::AcqRel,
                         atomic::Ordering::Relaxed,
-                    );
+                    ));
+                    self.stats.new_segment();
+
+                    // The Receiver may have closed the Queue, after we
+                    // checked it, and then missed the new Segment while
+                    // draining the Queue. In that Case we take the Element
+                    // back out of it, unless the Receiver already got it
+                    if self.rx_count.load(atomic::Ordering::SeqCst) == 0 {
+                        if let Ok(data) = n_queue.dequeue() {
+                            return Err((data, EnqueueError::Closed));
+                        }
+                    }
 
                     drop(handle);
+                    self.metrics.enqueue();
+                    self.events.notify_one();
                     return Ok(());
                 }
                 Err(_) => {
+                    self.stats.segment_append_failed();
                     data = n_queue.dequeue().expect("");
 
                     drop(unsafe { Box::from_raw(n_queue_ptr) });
@@ -175,10 +370,44 @@ impl<T> Sender<T> {
     pub fn is_closed(&self) -> bool {
         self.rx_count.load(atomic::Ordering::Acquire) == 0
     }
+
+    /// Returns a Report, that tells how many Elements were still left in the
+    /// Queue once it has been shut down, see the
+    /// [`shutdown`](crate::queues::shutdown) module for more Details
+    pub fn shutdown_report(&self) -> ShutdownReport {
+        self.shutdown.report()
+    }
+
+    /// Returns a Snapshot of the internal Counters of the Queue, which are
+    /// shared between the Sender and the Receiver, see [`Stats`] for the
+    /// individual Counters
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::mpmc::unbounded;
+    /// let (rx, tx) = unbounded::queue::<usize>();
+    ///
+    /// tx.enqueue(13).unwrap();
+    ///
+    /// let stats = tx.stats();
+    /// assert_eq!(1, stats.fast_path);
+    /// assert_eq!(0, stats.new_segment);
+    /// # drop(rx);
+    /// ```
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    pub fn stats(&self) -> Stats {
+        self.stats.snapshot()
+    }
 }
 impl<T> Drop for Sender<T> {
     fn drop(&mut self) {
-        self.tx_count.fetch_sub(1, atomic::Ordering::AcqRel);
+        if self.tx_count.fetch_sub(1, atomic::Ordering::AcqRel) == 1 {
+            // Wake up all the blocked Receivers, so they can observe that the
+            // Queue has been closed
+            self.events.notify_all();
+            self.shared.wakers.wakeup_all();
+        }
     }
 }
 
@@ -208,6 +437,7 @@ impl<T> Receiver<T> {
             let head = unsafe { &*head_ptr };
 
             if let Ok(data) = head.dequeue() {
+                self.metrics.dequeue();
                 return Ok(data);
             }
 
@@ -216,15 +446,26 @@ impl<T> Receiver<T> {
                 return Err(DequeueError::Empty);
             }
 
-            let thres_chk = (head.aq.size * 3 - 1) as isize;
-            head.aq
-                .threshold
-                .store(thres_chk, atomic::Ordering::Release);
+            head.reset_threshold();
 
             if let Ok(data) = head.dequeue() {
+                self.metrics.dequeue();
                 return Ok(data);
             }
 
+            // The Head-Segment may not be retired while the Tail still points
+            // to it, as a Sender that only loads the Tail afterwards would
+            // not be protected from its Reclamation. So if the Tail lags
+            // behind, it is moved forward before moving the Head past it
+            if self.tail.load(atomic::Ordering::Acquire) == head_ptr {
+                self.stats.tail_cas(self.tail.compare_exchange(
+                    head_ptr,
+                    next_ptr,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Relaxed,
+                ));
+            }
+
             if self
                 .head
                 .compare_exchange(
@@ -246,6 +487,91 @@ impl<T> Receiver<T> {
         }
     }
 
+    /// Dequeues an Entry from the Queue, blocking the current Thread until
+    /// one is available. Returns `None` once the Queue has been closed by the
+    /// Sender Side, like [`try_dequeue`](Self::try_dequeue) would return
+    /// [`DequeueError::Closed`].
+    ///
+    /// The Thread first spins with an exponential [`Backoff`] and is only
+    /// parked if the Queue stays empty for longer, it is then woken up again
+    /// by the next Enqueue. This makes it well suited for Worker-Pools, where
+    /// the Workers should not burn CPU-Time while there is no Work.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::mpmc::unbounded;
+    /// let (tx, rx) = unbounded::channel::<usize>();
+    ///
+    /// let worker = std::thread::spawn(move || rx.dequeue_blocking());
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// assert_eq!(Some(13), worker.join().unwrap());
+    /// ```
+    ///
+    /// # Async
+    /// This blocks the current Thread and therefore should not be called from async
+    /// Code, use [`dequeue_cooperative`](Self::dequeue_cooperative) instead
+    pub fn dequeue_blocking(&self) -> Option<T> {
+        let backoff = Backoff::new();
+
+        loop {
+            match self.try_dequeue() {
+                Ok(data) => return Some(data),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+                Err(DequeueError::Empty) => {}
+            };
+
+            if !backoff.is_completed() {
+                backoff.snooze();
+                continue;
+            }
+
+            let key = self.events.prepare_wait();
+            match self.try_dequeue() {
+                Ok(data) => {
+                    self.events.cancel_wait();
+                    return Some(data);
+                }
+                Err(DequeueError::Closed | DequeueError::Cancelled) => {
+                    self.events.cancel_wait();
+                    return None;
+                }
+                Err(DequeueError::Empty) => self.events.wait(key),
+            };
+        }
+    }
+
+    /// A blocking dequeue operation like [`dequeue_blocking`](Self::dequeue_blocking), which
+    /// can additionally be cancelled from another Thread using the given
+    /// [`CancelToken`], see the [`cancel`](crate::queues::cancel) module for
+    /// more Details
+    ///
+    /// # Returns
+    /// * `Ok(data)` once an Element could be dequeued
+    /// * `Err(DequeueError::Closed)` if the Queue has been closed
+    /// * `Err(DequeueError::Cancelled)` if the Token was cancelled while the
+    ///   Queue was empty
+    ///
+    /// # Note
+    /// Unlike [`dequeue_blocking`](Self::dequeue_blocking), this never parks the Thread,
+    /// as a parked Thread would not notice the Cancellation, and instead keeps
+    /// yielding it to the OS-Scheduler while the Queue is empty
+    pub fn dequeue_cancellable(&self, token: &CancelToken) -> Result<T, DequeueError> {
+        cancel::dequeue(token, || self.try_dequeue())
+    }
+
+    /// The async-friendly Variant of [`dequeue_blocking`](Self::dequeue_blocking), which yields
+    /// back to the Executor in between Attempts instead of blocking the Thread,
+    /// see the [`cooperative`](crate::queues::cooperative) module for more
+    /// Details
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn dequeue_cooperative(
+        &self,
+    ) -> CooperativeDequeue<impl FnMut() -> Result<T, DequeueError> + '_> {
+        cooperative::dequeue(move || self.try_dequeue())
+    }
+
     /// Checks if the Queue has been closed by the Sender Side
     ///
     /// # Note
@@ -268,12 +594,100 @@ impl<T> Receiver<T> {
     pub fn is_closed(&self) -> bool {
         self.tx_count.load(atomic::Ordering::Acquire) == 0
     }
+
+    /// Returns a Report, that tells how many Elements were still left in the
+    /// Queue once it has been shut down, see the
+    /// [`shutdown`](crate::queues::shutdown) module for more Details
+    pub fn shutdown_report(&self) -> ShutdownReport {
+        self.shutdown.report()
+    }
+
+    /// Dequeues all the Elements that are currently stored in the Queue and
+    /// collects them into a [`Snapshot`](crate::queues::snapshot::Snapshot),
+    /// without closing the Queue.
+    ///
+    /// Elements that are enqueued concurrently may or may not be part of the
+    /// Snapshot.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpmc::unbounded;
+    /// let (rx, tx) = unbounded::queue::<usize>();
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// tx.enqueue(14).unwrap();
+    ///
+    /// assert_eq!(&[13, 14], rx.snapshot().as_slice());
+    /// assert!(rx.snapshot().is_empty());
+    /// ```
+    pub fn snapshot(&self) -> crate::queues::snapshot::Snapshot<T> {
+        crate::queues::snapshot::Snapshot::drain(|| self.try_dequeue())
+    }
+
+    /// Returns a Snapshot of the internal Counters of the Queue, like
+    /// [`Sender::stats`]
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    pub fn stats(&self) -> Stats {
+        self.stats.snapshot()
+    }
+
+    /// Closes the Queue from the Receiving Side and returns all the
+    /// Elements that were still left in it.
+    ///
+    /// Every concurrent Enqueue either has its Element returned here or
+    /// fails with [`EnqueueError::Closed`], so no Element is lost.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::mpmc::unbounded;
+    /// let (rx, tx) = unbounded::queue::<usize>();
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// tx.enqueue(14).unwrap();
+    ///
+    /// assert_eq!(vec![13, 14], rx.close_and_drain());
+    /// assert_eq!(true, tx.is_closed());
+    /// ```
+    pub fn close_and_drain(mut self) -> Vec<T> {
+        let handle = self.hyaline_instance.enter();
+
+        self.rx_count.fetch_sub(1, atomic::Ordering::SeqCst);
+        self.count_released = true;
+
+        // Every Segment is finalized, before it is drained, so every
+        // Enqueue into it either completes before the Segment is drained or
+        // fails and then observes that the Queue has been closed. A Sender
+        // that appends a new Segment afterwards checks the Queue again,
+        // after appending it, so the Segment is either seen here or the
+        // Sender takes its Element back
+        let mut result = Vec::new();
+        let mut current_ptr = self.head.load(atomic::Ordering::Acquire);
+        loop {
+            let current = unsafe { &*current_ptr };
+            while let Some(data) = current.dequeue_finalized() {
+                self.metrics.dequeue();
+                result.push(data);
+            }
+
+            let next_ptr = current.next.load(atomic::Ordering::SeqCst);
+            if next_ptr.is_null() {
+                break;
+            }
+            current_ptr = next_ptr;
+        }
+        drop(handle);
+
+        result
+    }
 }
 impl<T> Drop for Receiver<T> {
     fn drop(&mut self) {
         let mut handle = self.hyaline_instance.enter();
 
-        self.rx_count.fetch_sub(1, atomic::Ordering::AcqRel);
+        if !self.count_released {
+            self.rx_count.fetch_sub(1, atomic::Ordering::AcqRel);
+        }
 
         let mut current_ptr = self.head.load(atomic::Ordering::SeqCst);
         let mut current = unsafe { &*current_ptr };
@@ -301,10 +715,76 @@ impl<T> Drop for Receiver<T> {
     }
 }
 
+crate::queues::adapter::impl_receiver!(Receiver<T>, T, dequeue_blocking);
+crate::queues::dynamic::impl_sender!(Sender<T>, try_enqueue, enqueue);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Counts the Segments that are currently allocated
+    #[derive(Default)]
+    struct CountingSegments(std::sync::atomic::AtomicUsize);
+
+    unsafe impl SegmentAlloc for CountingSegments {
+        unsafe fn alloc_segment(&self, layout: std::alloc::Layout) -> *mut u8 {
+            self.0.fetch_add(1, atomic::Ordering::SeqCst);
+            unsafe { std::alloc::System.alloc_segment(layout) }
+        }
+
+        unsafe fn dealloc_segment(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+            self.0.fetch_sub(1, atomic::Ordering::SeqCst);
+            unsafe { std::alloc::System.dealloc_segment(ptr, layout) }
+        }
+    }
+
+    #[test]
+    fn segment_alloc_used() {
+        let counting = Arc::new(CountingSegments::default());
+        let (rx, tx) = queue_with_segment_alloc(counting.clone());
+        // The Data-Buffer and both Index-Queues of the first Segment
+        assert_eq!(3, counting.0.load(atomic::Ordering::SeqCst));
+
+        for i in 0..(2 * BUFFER_SIZE) {
+            tx.enqueue(i).unwrap();
+        }
+        assert!(counting.0.load(atomic::Ordering::SeqCst) > 3);
+
+        for i in 0..(2 * BUFFER_SIZE) {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+
+        drop(rx);
+        drop(tx);
+        assert_eq!(0, counting.0.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn try_enqueue_does_not_allocate() {
+        let counting = Arc::new(CountingSegments::default());
+        let (rx, tx) = queue_with_segment_alloc(counting.clone());
+
+        for i in 0..BUFFER_SIZE {
+            assert_eq!(Ok(()), tx.try_enqueue(i));
+        }
+        assert_eq!(
+            Err((BUFFER_SIZE, EnqueueError::Full)),
+            tx.try_enqueue(BUFFER_SIZE)
+        );
+        assert_eq!(3, counting.0.load(atomic::Ordering::SeqCst));
+
+        // Once a new Segment has been appended, it is used as well
+        tx.enqueue(BUFFER_SIZE).unwrap();
+        assert_eq!(Ok(()), tx.try_enqueue(BUFFER_SIZE + 1));
+
+        for i in 0..(BUFFER_SIZE + 2) {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+
+        drop(rx);
+        assert_eq!(Err((0, EnqueueError::Closed)), tx.try_enqueue(0));
+    }
+
     #[test]
     fn new_queue() {
         queue::<u64>();
@@ -326,6 +806,71 @@ mod tests {
         assert_eq!(Ok(13), rx.try_dequeue());
     }
 
+    #[test]
+    fn close_and_drain() {
+        let (rx, tx) = queue::<usize>();
+
+        for index in 0..(BUFFER_SIZE * 2) {
+            assert_eq!(Ok(()), tx.enqueue(index));
+        }
+
+        let drained = rx.close_and_drain();
+        assert_eq!((0..(BUFFER_SIZE * 2)).collect::<Vec<_>>(), drained);
+        assert!(tx.is_closed());
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue(13));
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.try_enqueue(13));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn close_and_drain_concurrent() {
+        for _ in 0..10 {
+            let (rx, tx) = queue::<usize>();
+            let tx = Arc::new(tx);
+
+            let producers: Vec<_> = (0..2)
+                .map(|_| {
+                    let tx = tx.clone();
+                    std::thread::spawn(move || {
+                        let mut enqueued = 0;
+                        while tx.enqueue(1).is_ok() {
+                            enqueued += 1;
+                        }
+                        enqueued
+                    })
+                })
+                .collect();
+
+            let mut dequeued = 0;
+            for _ in 0..(BUFFER_SIZE * 2) {
+                if rx.try_dequeue().is_ok() {
+                    dequeued += 1;
+                }
+            }
+            dequeued += rx.close_and_drain().len();
+
+            let enqueued: usize = producers.into_iter().map(|p| p.join().unwrap()).sum();
+            assert_eq!(enqueued, dequeued);
+        }
+    }
+
+    #[test]
+    fn shutdown_report_counts_remaining() {
+        let (rx, tx) = queue::<usize>();
+        let report = tx.shutdown_report();
+
+        for index in 0..(BUFFER_SIZE + 3) {
+            assert_eq!(Ok(()), tx.enqueue(index));
+        }
+        assert_eq!(Ok(0), rx.try_dequeue());
+
+        drop(rx);
+        assert_eq!(None, report.closed_with_remaining());
+
+        drop(tx);
+        assert_eq!(Some(BUFFER_SIZE + 2), report.closed_with_remaining());
+    }
+
     #[test]
     fn enqueue_fill_multiple() {
         let (rx, tx) = queue::<usize>();
@@ -382,8 +927,48 @@ mod tests {
         assert_eq!(Ok(()), tx.enqueue(13));
         drop(rx);
 
-        assert_eq!(Err(14), tx.enqueue(14));
+        assert_eq!(Err((14, EnqueueError::Closed)), tx.enqueue(14));
+    }
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn stats_new_segments() {
+        let (rx, tx) = queue::<usize>();
+
+        for index in 0..(BUFFER_SIZE * 3) {
+            tx.enqueue(index).unwrap();
+        }
+
+        let stats = tx.stats();
+        assert_eq!(
+            (BUFFER_SIZE * 3) as u64,
+            stats.fast_path + stats.new_segment
+        );
+        assert!(stats.new_segment >= 2);
+        assert_eq!(0, stats.segment_append_failed);
+        assert_eq!(stats, rx.stats());
+    }
+
+    #[test]
+    fn receiver_moves_lagging_tail() {
+        let (rx, tx) = queue::<usize>();
+
+        for index in 0..(BUFFER_SIZE * 2) {
+            tx.enqueue(index).unwrap();
+        }
+        // Simulate a Sender that appended a Segment, but did not yet move
+        // the Tail forward
+        let first = rx.head.load(atomic::Ordering::Acquire);
+        tx.tail.store(first, atomic::Ordering::Release);
+
+        for index in 0..(BUFFER_SIZE * 2) {
+            assert_eq!(Ok(index), rx.try_dequeue());
+        }
+        assert_ne!(first, tx.tail.load(atomic::Ordering::Acquire));
+
+        tx.enqueue(13).unwrap();
+        assert_eq!(Ok(13), rx.try_dequeue());
     }
+
     #[test]
     fn dequeue_on_closed() {
         let (rx, tx) = queue::<u64>();
@@ -393,4 +978,51 @@ mod tests {
 
         assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
     }
+
+    #[test]
+    fn dequeue_blocking_wakeup() {
+        let (rx, tx) = queue::<u64>();
+
+        let handle = std::thread::spawn(move || {
+            // Give the Receiver enough Time to park itself
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            tx.enqueue(13).unwrap();
+            tx
+        });
+
+        assert_eq!(Some(13), rx.dequeue_blocking());
+        drop(handle.join().unwrap());
+        assert_eq!(None, rx.dequeue_blocking());
+    }
+
+    #[test]
+    fn dequeue_blocking_closed() {
+        let (rx, tx) = queue::<u64>();
+
+        let handle = std::thread::spawn(move || rx.dequeue_blocking());
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        drop(tx);
+        assert_eq!(None, handle.join().unwrap());
+    }
+
+    #[test]
+    fn dequeue_cancellable() {
+        let (rx, tx) = queue::<u64>();
+        let token = std::sync::Arc::new(CancelToken::new());
+
+        let handle = {
+            let token = token.clone();
+            std::thread::spawn(move || (rx.dequeue_cancellable(&token), rx))
+        };
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        token.cancel();
+        let (result, rx) = handle.join().unwrap();
+        assert_eq!(Err(DequeueError::Cancelled), result);
+
+        tx.enqueue(13).unwrap();
+        token.reset();
+        assert_eq!(Ok(13), rx.dequeue_cancellable(&token));
+    }
 }
@@ -1,6 +1,11 @@
-use alloc::sync::Arc;
+use core::future::Future;
 
-use crate::queues::DequeueError;
+use crate::queues::{
+    batch::{DequeueMany, PendingDequeue},
+    convert::SyncPeers,
+    timeout::DequeueTimeout,
+    DequeueError, EnqueueError,
+};
 
 use super::{queue, Receiver, Sender};
 
@@ -23,8 +28,8 @@ mod waker_list {
         task::Waker,
     };
 
+    use crate::utils::AtomicWaker;
     use atomic::Ordering;
-    use futures::task::AtomicWaker;
 
     /// A Lock-Free append-only linked list to store a list of Wakers
     pub struct WakerList {
@@ -137,47 +142,145 @@ mod waker_list {
     }
 }
 
+/// The State shared between all the Halves of a Queue, regardless of them
+/// being sync or async
+pub(crate) struct Shared {
+    /// The Wakers of the waiting async Receivers
+    pub(crate) wakers: waker_list::WakerList,
+    /// The Number of sync Senders, which do not wake up the Receivers
+    senders: SyncPeers,
+}
+
+impl Shared {
+    /// Creates the State for a new Queue, whose Halves all start out sync
+    pub fn new() -> Self {
+        Self {
+            wakers: waker_list::WakerList::new(),
+            senders: SyncPeers::new(1),
+        }
+    }
+}
+
 /// The sending site of the queue
+///
+/// # Conversion
+/// A sync [`Sender`] can be turned into an AsyncSender at any Time using
+/// [`Sender::into_async`] and back using [`into_sync`](Self::into_sync).
+/// While the Sender is sync, the waiting async Receivers can not be woken up
+/// by it and instead poll the Queue cooperatively, by yielding back to the
+/// Executor in every Poll.
 pub struct AsyncSender<T> {
     sender: Sender<T>,
-    wakers: Arc<waker_list::WakerList>,
 }
 
 /// The receiving site of the queue
+///
+/// # Conversion
+/// A sync [`Receiver`] can be turned into an AsyncReceiver at any Time using
+/// [`Receiver::into_async`] and back using [`into_sync`](Self::into_sync)
 pub struct AsyncReceiver<T> {
     recv: Receiver<T>,
-    wakers: Arc<waker_list::WakerList>,
 }
 
 /// Creates a new asynchronous Queue
 pub fn async_queue<T>() -> (AsyncReceiver<T>, AsyncSender<T>) {
     let (raw_recv, raw_send) = queue::<T>();
+    wrap_queue(raw_recv, raw_send)
+}
 
-    let wakers = Arc::new(waker_list::WakerList::new());
+/// Wraps the given sync Queue Halves into their async Variants
+pub(crate) fn wrap_queue<T>(
+    raw_recv: Receiver<T>,
+    raw_send: Sender<T>,
+) -> (AsyncReceiver<T>, AsyncSender<T>) {
+    (raw_recv.into_async(), raw_send.into_async())
+}
 
-    let recv = AsyncReceiver {
-        recv: raw_recv,
-        wakers: wakers.clone(),
-    };
-    let send = AsyncSender {
-        sender: raw_send,
-        wakers,
-    };
+impl<T> Sender<T> {
+    /// Converts this Sender into an [`AsyncSender`] for the same Queue, which
+    /// wakes up the waiting async Receivers for every enqueued Item
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpmc::unbounded;
+    /// async fn demo() {
+    ///   let (rx, tx) = unbounded::queue::<usize>();
+    ///   tx.enqueue(13).unwrap();
+    ///
+    ///   let (rx, tx) = (rx.into_async(), tx.into_async());
+    ///   tx.enqueue(14).unwrap();
+    ///
+    ///   assert_eq!(Ok(13), rx.dequeue().await);
+    ///   assert_eq!(Ok(14), rx.dequeue().await);
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    pub fn into_async(self) -> AsyncSender<T> {
+        self.shared.senders.remove();
+        // The Receivers may currently be waiting cooperatively, so they need
+        // to observe that they will now be woken up instead
+        self.shared.wakers.wakeup_all();
+
+        AsyncSender { sender: self }
+    }
+}
 
-    (recv, send)
+impl<T> Receiver<T> {
+    /// Converts this Receiver into an [`AsyncReceiver`] for the same Queue
+    pub fn into_async(self) -> AsyncReceiver<T> {
+        AsyncReceiver { recv: self }
+    }
 }
 
 impl<T> AsyncSender<T> {
-    /// TODO
-    pub fn enqueue(&self, data: T) -> Result<(), T> {
+    /// Converts this AsyncSender back into a sync [`Sender`] for the same
+    /// Queue, after which the waiting async Receivers have to poll the Queue
+    /// cooperatively
+    pub fn into_sync(self) -> Sender<T> {
+        let sender = self.sender;
+
+        sender.shared.senders.add();
+        // The Receivers may currently be waiting for us to wake them up
+        sender.shared.wakers.wakeup_all();
+
+        sender
+    }
+
+    /// Enqueues the Data and wakes up the waiting async Receivers, see
+    /// [`Sender::enqueue`]
+    ///
+    /// The Queue is unbounded, so this never has to wait and there is no
+    /// Future that could be cancelled. The Data is handed back as part of
+    /// the Error if it could not be enqueued.
+    pub fn enqueue(&self, data: T) -> Result<(), (T, EnqueueError)> {
         self.sender.enqueue(data)?;
-        self.wakers.wakeup_all();
+        self.sender.shared.wakers.wakeup_all();
+
+        Ok(())
+    }
+
+    /// Enqueues the Data without allocating a new Segment and wakes up the
+    /// waiting async Receivers, see [`Sender::try_enqueue`]
+    pub fn try_enqueue(&self, data: T) -> Result<(), (T, EnqueueError)> {
+        self.sender.try_enqueue(data)?;
+        self.sender.shared.wakers.wakeup_all();
 
         Ok(())
     }
 }
 
 impl<T> AsyncReceiver<T> {
+    /// Converts this AsyncReceiver back into a sync [`Receiver`] for the same
+    /// Queue
+    pub fn into_sync(self) -> Receiver<T> {
+        self.recv
+    }
+
     /// TODO
     pub fn try_dequeue(&self) -> Result<T, DequeueError> {
         self.recv.try_dequeue()
@@ -185,16 +288,53 @@ impl<T> AsyncReceiver<T> {
 
     /// TODO
     pub fn dequeue(&self) -> DequeueFuture<'_, T> {
-        DequeueFuture {
-            recv: &self.recv,
-            wakers: &self.wakers,
-        }
+        DequeueFuture { recv: &self.recv }
+    }
+
+    /// Polls for the next Item, which is the low-level Operation behind
+    /// [`dequeue`](Self::dequeue), for Executors or Combinators that drive
+    /// the Queue manually instead of awaiting a Future.
+    ///
+    /// Returns `Poll::Pending` if the Queue is currently empty, in which case
+    /// the Waker of the given Context is woken once an Item is enqueued
+    pub fn poll_dequeue(
+        &self,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Result<T, DequeueError>> {
+        core::pin::Pin::new(&mut self.dequeue()).poll(cx)
+    }
+
+    /// Dequeues the next Item, just like [`dequeue`](Self::dequeue), but
+    /// stops waiting once the given `sleep` Future resolves, in which case
+    /// `Err(DequeueError::Empty)` is returned.
+    ///
+    /// The `sleep` Future can come from any Runtime, see the
+    /// [`timeout`](crate::queues::timeout) module for more Details
+    pub fn dequeue_timeout<S>(&self, sleep: S) -> DequeueTimeout<DequeueFuture<'_, T>, S>
+    where
+        S: Future,
+    {
+        DequeueTimeout::new(self.dequeue(), sleep)
+    }
+
+    /// Waits for the next Item, just like [`dequeue`](Self::dequeue), and
+    /// then also takes all the Items that are already in the Queue, until
+    /// `max` Items have been dequeued, see the
+    /// [`batch`](crate::queues::batch) module for more Details
+    pub fn dequeue_many(&self, max: usize) -> DequeueMany<DequeueFuture<'_, T>> {
+        DequeueMany::new(self.dequeue(), max)
     }
 }
 
+/// The Future returned by the [`dequeue`](AsyncReceiver::dequeue) operation
+///
+/// # Cancel Safety
+/// This Future is cancel safe. Every Poll only takes an Item out of the
+/// Queue in the same Step that returns it, and the Waker it registered in
+/// the shared Waker-List is simply woken without Effect once the Future is
+/// gone, so other Receivers still get every Item.
 pub struct DequeueFuture<'s, T> {
     recv: &'s Receiver<T>,
-    wakers: &'s waker_list::WakerList,
 }
 
 impl<'s, T> core::future::Future for DequeueFuture<'s, T> {
@@ -204,13 +344,16 @@ impl<'s, T> core::future::Future for DequeueFuture<'s, T> {
         self: core::pin::Pin<&mut Self>,
         cx: &mut core::task::Context<'_>,
     ) -> core::task::Poll<Self::Output> {
+        // A sync Sender will not wake us up, so we have to keep polling
+        let cooperative = self.recv.shared.senders.any();
+
         match self.recv.try_dequeue() {
             Ok(r) => return core::task::Poll::Ready(Ok(r)),
             Err(DequeueError::Empty) => {}
             Err(e) => return core::task::Poll::Ready(Err(e)),
         };
 
-        self.wakers.register_waker(cx.waker());
+        self.recv.shared.wakers.register_waker(cx.waker());
 
         match self.recv.try_dequeue() {
             Ok(r) => return core::task::Poll::Ready(Ok(r)),
@@ -218,12 +361,27 @@ impl<'s, T> core::future::Future for DequeueFuture<'s, T> {
             Err(e) => return core::task::Poll::Ready(Err(e)),
         };
 
+        if cooperative || self.recv.shared.senders.any() {
+            cx.waker().wake_by_ref();
+        }
+
         core::task::Poll::Pending
     }
 }
 
+impl<'s, T> PendingDequeue for DequeueFuture<'s, T> {
+    type Item = T;
+
+    fn try_dequeue_next(&mut self) -> Result<T, DequeueError> {
+        self.recv.try_dequeue()
+    }
+}
+
+crate::queues::adapter::impl_async_receiver!(AsyncReceiver<T>, DequeueFuture);
+
 #[cfg(test)]
 mod tests {
+    use alloc::sync::Arc;
     use core::sync::atomic::{AtomicBool, AtomicU64};
 
     use atomic::Ordering;
@@ -244,6 +402,18 @@ mod tests {
         assert_eq!(Ok(10), recv.dequeue().await);
     }
 
+    #[tokio::test]
+    async fn enqueue_dequeue_many() {
+        let (recv, send) = async_queue();
+
+        for i in 0..5 {
+            assert_eq!(Ok(()), send.enqueue(i));
+        }
+
+        assert_eq!(Ok(vec![0, 1, 2]), recv.dequeue_many(3).await);
+        assert_eq!(Ok(vec![3, 4]), recv.dequeue_many(3).await);
+    }
+
     #[tokio::test]
     async fn dequeue_enqueue() {
         let (recv, send) = async_queue();
@@ -303,4 +473,26 @@ mod tests {
 
         assert_eq!(2, woken.load(Ordering::SeqCst));
     }
+
+    #[tokio::test]
+    async fn sync_sender_async_receiver() {
+        let (recv, send) = queue::<usize>();
+        let recv = recv.into_async();
+
+        let handle = tokio::spawn(async move { (recv.dequeue().await, recv) });
+
+        // Let the Receiver wait, before enqueueing without waking it up
+        tokio::task::yield_now().await;
+        assert_eq!(Ok(()), send.enqueue(10));
+
+        let (result, recv) = tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+            .await
+            .expect("The Receiver did not observe the Item")
+            .unwrap();
+        assert_eq!(Ok(10), result);
+
+        let send = send.into_async().into_sync();
+        assert_eq!(Ok(()), send.enqueue(11));
+        assert_eq!(Ok(11), recv.into_sync().try_dequeue());
+    }
 }
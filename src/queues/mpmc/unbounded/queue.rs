@@ -1,42 +1,47 @@
 use crate::sync::atomic;
-use std::{cell::UnsafeCell, mem::MaybeUninit, sync::Arc};
+use std::{cell::UnsafeCell, fmt::Debug, mem::MaybeUninit, sync::Arc};
 
-use crate::queues::{DequeueError, EnqueueError};
-
-mod scq;
+use crate::{
+    queues::{index_queue::IndexQueue, shutdown::Shutdown, DequeueError, EnqueueError},
+    utils::{Segment, Segments},
+};
 
 pub struct BoundedQueue<T> {
     /// The actual Buffer for all the Data-Entries
-    data: Arc<Vec<UnsafeCell<MaybeUninit<T>>>>,
+    data: Arc<Segment<UnsafeCell<MaybeUninit<T>>>>,
     /// The "available"-Queue, contains all the Indices at which Data is currently
     /// stored and can be read from
-    pub aq: Arc<scq::Queue>,
+    aq: Arc<IndexQueue>,
     /// The Queue for all the free Indices at which no Data is stored and
     /// therefore can be used to store Data in
-    fq: Arc<scq::Queue>,
+    fq: Arc<IndexQueue>,
 
     pub next: atomic::AtomicPtr<Self>,
+    /// Counts the Elements that are still left in the Segment, once it is
+    /// dropped
+    shutdown: Arc<Shutdown>,
 }
 
-pub fn new_queue<T>(capacity: usize) -> BoundedQueue<T> {
-    let data = {
-        // Creates a Vec with the given Capacity
-        let mut tmp = Vec::with_capacity(capacity);
-        // Add empty Data-Points to the Vec, until its capacity is reached
-        for _ in 0..capacity {
-            tmp.push(UnsafeCell::new(MaybeUninit::uninit()));
-        }
-        Arc::new(tmp)
-    };
+/// Creates a new Segment, whose Buffers are all allocated using the given
+/// Segments
+pub fn new_queue<T>(
+    capacity: usize,
+    segments: &Segments,
+    shutdown: Arc<Shutdown>,
+) -> BoundedQueue<T> {
+    let data = Arc::new(Segment::new_with(segments, capacity, || {
+        UnsafeCell::new(MaybeUninit::uninit())
+    }));
 
-    // Create both of the needed Queues
-    let aq = scq::Queue::new(capacity);
-    let fq = scq::Queue::new(capacity);
+    // Create both of the needed Queues, with `fq` already containing all the
+    // available Indices, in this case 0-capacity
+    let aq = IndexQueue::new_in(capacity, segments);
+    let fq = IndexQueue::new_full_in(capacity, segments);
 
-    // Fill `fq` with all the available Indices, in this case 0-capacity
-    for index in 0..capacity {
-        fq.enqueue(index).expect("Works as expected");
-    }
+    // The Buffer and both Queues need to agree on the Capacity, as every
+    // Index is always stored in exactly one of the Queues
+    debug_assert_eq!(data.len(), aq.capacity());
+    debug_assert_eq!(data.len(), fq.capacity());
 
     let aq_arc = Arc::new(aq);
     let fq_arc = Arc::new(fq);
@@ -46,6 +51,7 @@ pub fn new_queue<T>(capacity: usize) -> BoundedQueue<T> {
         aq: aq_arc,
         fq: fq_arc,
         next: atomic::AtomicPtr::new(std::ptr::null_mut()),
+        shutdown,
     }
 }
 
@@ -54,8 +60,10 @@ pub fn new_queue<T>(capacity: usize) -> BoundedQueue<T> {
 // for giving "synchronized" access to the underlying Data by the nature of the
 // algorithm.
 // Whether or not T is Sync is actually not important because we never actually
-// use T anywhere in the Code but instead just pass it around
-unsafe impl<T> Sync for BoundedQueue<T> {}
+// use T anywhere in the Code but instead just pass it around. However sharing
+// a Reference between Threads allows enqueuing and dequeuing Elements on
+// different Threads, so T still needs to be Send
+unsafe impl<T> Sync for BoundedQueue<T> where T: Send {}
 
 // Safety:
 // The Queue is only Send if T is send, because even though we dont use T in
@@ -64,13 +72,62 @@ unsafe impl<T> Sync for BoundedQueue<T> {}
 // and would therefore try to send them across Threads.
 unsafe impl<T> Send for BoundedQueue<T> where T: Send {}
 
+impl<T> Debug for BoundedQueue<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "LSCQ-Segment (capacity: {}, len: {}, finalized: {})",
+            self.capacity(),
+            self.len(),
+            self.is_finalized()
+        )
+    }
+}
+
 impl<T> BoundedQueue<T> {
+    /// The Number of Elements that can be stored in the Segment at the same
+    /// Time.
+    ///
+    /// The IndexQueues internally use twice as many Entries, but they only
+    /// ever hold the Indices `0..capacity` of the Data-Buffer, so this is the
+    /// effective Capacity of the whole Segment
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Checks if the Segment has been finalized, after which it will never
+    /// accept any new Elements again, even once Elements are dequeued
+    pub fn is_finalized(&self) -> bool {
+        self.aq.is_finalized()
+    }
+
+    /// The approximate Number of Elements that are currently stored in the
+    /// Segment and can be dequeued, which does not include Elements that are
+    /// still being enqueued
+    pub fn len(&self) -> usize {
+        self.aq.len()
+    }
+
+    /// Resets the Threshold of the "available"-Queue, so that Dequeuers
+    /// check it again for Elements that were enqueued after it was found
+    /// to be empty
+    pub fn reset_threshold(&self) {
+        self.aq.reset_threshold();
+    }
+
     /// Attempts to enqueue an item on the Queue
     ///
     /// # Returns
     /// * `Ok(())` if the item was successfully enqueued
-    /// * `Err(data)` if the Queue is full and the item could not be enqueued
+    /// * `Err(data)` if the Queue is full or has been finalized and the item
+    ///   could not be enqueued
     pub fn try_enqueue(&self, data: T) -> Result<(), (EnqueueError, T)> {
+        // A finalized Segment rejects the Index anyway, so we dont even need
+        // to take one from the free Indices
+        if self.is_finalized() {
+            return Err((EnqueueError::Full, data));
+        }
+
         // Attempt to get a free-Index to insert the data into
         let index = match self.fq.dequeue() {
             Some(i) => i,
@@ -104,17 +161,36 @@ impl<T> BoundedQueue<T> {
                 let bucket_ptr = bucket.get();
                 let old = unsafe { bucket_ptr.replace(MaybeUninit::uninit()).assume_init() };
 
+                // The Index was never made available, so it is returned to
+                // the free Indices, to keep every Index in one of the Queues
+                self.fq
+                    .enqueue(index)
+                    .expect("The Queue of free Indices is never finalized");
+
                 Err((EnqueueError::Full, old))
             }
         }
     }
 
     pub fn dequeue(&self) -> Result<T, DequeueError> {
-        let index = match self.aq.dequeue() {
-            Some(i) => i,
-            None => return Err(DequeueError::Empty),
-        };
+        match self.aq.dequeue() {
+            Some(index) => Ok(self.take(index)),
+            None => Err(DequeueError::Empty),
+        }
+    }
+
+    /// Finalizes the Segment and dequeues the next Element, which only
+    /// returns `None` once every Enqueue, that was started before, has
+    /// either been dequeued or failed
+    pub fn dequeue_finalized(&self) -> Option<T> {
+        self.aq.finalize();
+        self.aq.dequeue_finalized().map(|index| self.take(index))
+    }
 
+    /// Takes the Data out of the Bucket for the given Index, that was
+    /// dequeued from the "available"-Queue, and returns the Index to the
+    /// free Indices
+    fn take(&self, index: usize) -> T {
         let bucket = self
             .data
             .get(index)
@@ -130,9 +206,25 @@ impl<T> BoundedQueue<T> {
         let bucket_ptr = bucket.get();
         let data = unsafe { bucket_ptr.replace(MaybeUninit::uninit()).assume_init() };
 
-        self.fq.enqueue(index).expect("");
+        self.fq
+            .enqueue(index)
+            .expect("The Queue of free Indices is never finalized");
+
+        data
+    }
+}
 
-        Ok(data)
+impl<T> Drop for BoundedQueue<T> {
+    fn drop(&mut self) {
+        // Drop all the Entries that are still stored in the Queue, as they
+        // would otherwise be leaked because the Buffer only stores
+        // MaybeUninit-Entries
+        let mut remaining = 0;
+        while let Ok(data) = self.dequeue() {
+            drop(data);
+            remaining += 1;
+        }
+        self.shutdown.record_dropped(remaining);
     }
 }
 
@@ -142,7 +234,9 @@ mod tests {
 
     #[test]
     fn enqueue_finalize() {
-        let queue = new_queue(10);
+        let queue = new_queue(10, &Segments::default(), Arc::new(Shutdown::new()));
+        assert_eq!(10, queue.capacity());
+        assert!(!queue.is_finalized());
 
         for index in 0..10 {
             queue
@@ -152,11 +246,31 @@ mod tests {
 
         // This will finalize it
         assert_eq!(Err((EnqueueError::Full, 0)), queue.try_enqueue(0));
+        assert!(queue.is_finalized());
+        assert_eq!(10, queue.len());
 
         queue.dequeue().expect("The Queue contains elements");
+        assert_eq!(9, queue.len());
 
         // The Queue has been finalized and therefore does not accept any new Entries even
         // if there is now an empty slot in it
         assert_eq!(Err((EnqueueError::Full, 0)), queue.try_enqueue(0));
     }
+
+    #[test]
+    fn finalized_keeps_free_indices() {
+        let queue = new_queue(4, &Segments::default(), Arc::new(Shutdown::new()));
+        queue.try_enqueue(0).unwrap();
+
+        // Finalizing the Segment, while Indices are still free, must not lose
+        // any of them
+        queue.aq.finalize();
+        assert_eq!(Err((EnqueueError::Full, 1)), queue.try_enqueue(1));
+        assert_eq!(3, queue.fq.len());
+
+        assert_eq!(Ok(0), queue.dequeue());
+        assert_eq!(4, queue.fq.len());
+        assert_eq!(0, queue.len());
+        assert_eq!(4, queue.capacity());
+    }
 }
@@ -0,0 +1,104 @@
+//! The internal Counters used to collect the [`Stats`] of a Queue.
+//!
+//! Without the `metrics` Feature, the Counters are a zero-sized Type and all
+//! their Methods are empty, so they are compiled out entirely.
+
+#[cfg(feature = "metrics")]
+use crate::sync::atomic;
+
+/// A Snapshot of the internal Counters of an unbounded Queue, obtained using
+/// [`Sender::stats`](super::Sender::stats) or
+/// [`Receiver::stats`](super::Receiver::stats).
+///
+/// All the Counters are updated using relaxed `fetch_add`s, so a Snapshot
+/// taken while other Threads are operating on the Queue may be slightly
+/// inconsistent.
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    /// The Number of Enqueues that stored their Element in the current Tail-
+    /// Segment
+    pub fast_path: u64,
+    /// The Number of Enqueues that had to append a new Segment, because the
+    /// Tail-Segment was full
+    pub new_segment: u64,
+    /// The Number of new Segments that could not be appended, because
+    /// another Sender appended its Segment first
+    pub segment_append_failed: u64,
+    /// The Number of times a Sender found the Tail-Pointer lagging behind the
+    /// actual last Segment
+    pub tail_lagging: u64,
+    /// The Number of failed CAS-Operations on the Tail-Pointer, by either
+    /// the Senders or the Receivers
+    pub tail_cas_failed: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct Counters {
+    #[cfg(feature = "metrics")]
+    fast_path: atomic::AtomicU64,
+    #[cfg(feature = "metrics")]
+    new_segment: atomic::AtomicU64,
+    #[cfg(feature = "metrics")]
+    segment_append_failed: atomic::AtomicU64,
+    #[cfg(feature = "metrics")]
+    tail_lagging: atomic::AtomicU64,
+    #[cfg(feature = "metrics")]
+    tail_cas_failed: atomic::AtomicU64,
+}
+
+#[cfg(feature = "metrics")]
+macro_rules! count {
+    ($counter:expr) => {
+        $counter.fetch_add(1, atomic::Ordering::Relaxed);
+    };
+}
+
+impl Counters {
+    #[inline(always)]
+    pub fn fast_path(&self) {
+        #[cfg(feature = "metrics")]
+        count!(self.fast_path);
+    }
+
+    #[inline(always)]
+    pub fn new_segment(&self) {
+        #[cfg(feature = "metrics")]
+        count!(self.new_segment);
+    }
+
+    #[inline(always)]
+    pub fn segment_append_failed(&self) {
+        #[cfg(feature = "metrics")]
+        count!(self.segment_append_failed);
+    }
+
+    #[inline(always)]
+    pub fn tail_lagging(&self) {
+        #[cfg(feature = "metrics")]
+        count!(self.tail_lagging);
+    }
+
+    /// Counts the given Result of a CAS on the Tail-Pointer, if it failed
+    #[inline(always)]
+    pub fn tail_cas<P>(&self, result: Result<P, P>) {
+        #[cfg(feature = "metrics")]
+        if result.is_err() {
+            count!(self.tail_cas_failed);
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = result;
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn snapshot(&self) -> Stats {
+        Stats {
+            fast_path: self.fast_path.load(atomic::Ordering::Relaxed),
+            new_segment: self.new_segment.load(atomic::Ordering::Relaxed),
+            segment_append_failed: self.segment_append_failed.load(atomic::Ordering::Relaxed),
+            tail_lagging: self.tail_lagging.load(atomic::Ordering::Relaxed),
+            tail_cas_failed: self.tail_cas_failed.load(atomic::Ordering::Relaxed),
+        }
+    }
+}
@@ -8,5 +8,17 @@
 //! Jiffy is also an Unbounded-Queue, which makes it useful for a wide variety
 //! of use-cases, and its good performance characteristics also mean that it
 //! should be useable even in performance critical environments.
+//!
+//! ## Intrusive
+//! The [`intrusive`] Queue stores the Link to the next Element in the
+//! Elements themselves, so it never allocates or copies anything itself. This
+//! is useful when the Elements are already allocated and get reused.
+//!
+//! ## Priority
+//! The [`priority`] Channel has a fixed Number of Priority-Levels, each of
+//! them backed by a Jiffy-Queue, and the Receiver always dequeues from the
+//! highest Level that has Elements available.
 
+pub mod intrusive;
 pub mod jiffy;
+pub mod priority;
@@ -0,0 +1,543 @@
+//! An intrusive, unbounded MPSC Queue, where the Elements themselves store
+//! the Link to the next Element in the Queue
+//!
+//! Because the Link is embedded in the Elements, the Queue itself never
+//! allocates or copies anything when enqueueing or dequeueing, it only ever
+//! passes around the already allocated Elements. This makes it a good fit for
+//! pre-allocated Elements, that get send through the Queue over and over
+//! again.
+//!
+//! # Example
+//! ```rust
+//! use nolock::queues::mpsc::intrusive::{self, Link, Linked};
+//!
+//! #[derive(Debug)]
+//! #[repr(C)]
+//! struct Command {
+//!     // The Link needs to be the first Field
+//!     link: Link,
+//!     id: usize,
+//! }
+//!
+//! // Safety:
+//! // The Command is `repr(C)` and the Link is its first Field
+//! unsafe impl Linked for Command {}
+//!
+//! let (tx, mut rx) = intrusive::channel::<Command>();
+//!
+//! tx.enqueue(Box::new(Command { link: Link::new(), id: 13 })).unwrap();
+//!
+//! let command = rx.try_dequeue().unwrap();
+//! assert_eq!(13, command.id);
+//!
+//! // The Command can be reused without allocating a new one
+//! tx.enqueue(command).unwrap();
+//! ```
+//!
+//! # Progress
+//! A Producer that gets interrupted in the middle of an Enqueue-Operation
+//! temporarily hides all the Elements enqueued after it from the Consumer,
+//! until it finishes its Operation. In that case the Consumer simply sees the
+//! Queue as empty.
+//!
+//! # Reference:
+//! * [Intrusive MPSC node-based queue](https://www.1024cores.net/home/lock-free-algorithms/queues/intrusive-mpsc-node-based-queue)
+
+use alloc::{boxed::Box, sync::Arc};
+use core::{cell::UnsafeCell, fmt::Debug, marker::PhantomData};
+
+use crate::sync::native::atomic;
+
+use crate::{
+    queues::{DequeueError, EnqueueError},
+    utils::Backoff,
+};
+
+/// The Link that needs to be embedded into every Element of the Queue, see
+/// [`Linked`] for the exact Requirements
+pub struct Link {
+    next: atomic::AtomicPtr<Link>,
+}
+
+impl Link {
+    /// Creates a new unlinked Link
+    pub const fn new() -> Self {
+        Self {
+            next: atomic::AtomicPtr::new(core::ptr::null_mut()),
+        }
+    }
+}
+
+impl Default for Link {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Link {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Link ()")
+    }
+}
+
+/// Marks a Type that can be stored in the intrusive Queue
+///
+/// # Safety
+/// The Type must be `#[repr(C)]` and its first Field must be a [`Link`], so
+/// that a Ptr to the Type is also a valid Ptr to its Link and the other way
+/// around
+pub unsafe trait Linked {}
+
+/// The State shared between the Sender and Receiver
+struct Inner<T> {
+    /// The Stub-Link, which is stored in the Queue whenever the Queue would
+    /// otherwise be empty
+    stub: Box<Link>,
+    /// The Link that was last enqueued, used by the Producers
+    head: atomic::AtomicPtr<Link>,
+    /// The Link that will be dequeued next, only used by the Consumer
+    tail: UnsafeCell<*mut Link>,
+    /// Whether one of the two Sides has been dropped
+    closed: atomic::AtomicBool,
+    /// The Number of Senders that are still alive
+    senders: atomic::AtomicUsize,
+    _marker: PhantomData<Box<T>>,
+}
+
+impl<T> Inner<T> {
+    fn stub_ptr(&self) -> *mut Link {
+        &*self.stub as *const Link as *mut Link
+    }
+}
+
+impl<T> Inner<T>
+where
+    T: Linked,
+{
+    fn push(&self, link: *mut Link) {
+        // # Safety:
+        // The Link is either the Stub or belongs to an Element that we now
+        // own, so no one else is accessing it
+        unsafe { &*link }
+            .next
+            .store(core::ptr::null_mut(), atomic::Ordering::Relaxed);
+
+        let previous = self.head.swap(link, atomic::Ordering::AcqRel);
+
+        // Between the Swap and this Store, the Elements following the
+        // previous Head are not reachable by the Consumer yet
+        //
+        // # Safety:
+        // The previous Head can not have been dequeued yet, because the
+        // Consumer never dequeues the current Head and it was the Head until
+        // our Swap
+        unsafe { &*previous }
+            .next
+            .store(link, atomic::Ordering::Release);
+    }
+
+    /// Attempts to pop the next Element from the Queue
+    ///
+    /// # Safety
+    /// Must only ever be called by a single Thread at a time
+    unsafe fn pop(&self) -> Option<Box<T>> {
+        let stub = self.stub_ptr();
+        let tail_cell = self.tail.get();
+
+        // # Safety:
+        // Only the Consumer ever accesses the Tail and every Link still in
+        // the Queue stays valid until it has been dequeued
+        let mut tail = unsafe { *tail_cell };
+        let mut next = unsafe { &*tail }.next.load(atomic::Ordering::Acquire);
+
+        if tail == stub {
+            if next.is_null() {
+                return None;
+            }
+
+            unsafe { *tail_cell = next };
+            tail = next;
+            next = unsafe { &*next }.next.load(atomic::Ordering::Acquire);
+        }
+
+        if !next.is_null() {
+            unsafe { *tail_cell = next };
+            return Some(unsafe { Box::from_raw(tail as *mut T) });
+        }
+
+        // If the Tail is not the Head, a Producer is still in the middle of
+        // linking its Element behind the Tail
+        let head = self.head.load(atomic::Ordering::Acquire);
+        if tail != head {
+            return None;
+        }
+
+        // The Tail is the last Element in the Queue, so we push the Stub
+        // behind it, to be able to dequeue it without emptying the Queue
+        self.push(stub);
+
+        next = unsafe { &*tail }.next.load(atomic::Ordering::Acquire);
+        if !next.is_null() {
+            unsafe { *tail_cell = next };
+            return Some(unsafe { Box::from_raw(tail as *mut T) });
+        }
+
+        None
+    }
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let stub = self.stub_ptr();
+        let mut current = *self.tail.get_mut();
+
+        // Both Sides are gone, so there are no Producers in the middle of an
+        // Enqueue and the Queue is a simple linked List ending in null
+        while !current.is_null() {
+            let next = unsafe { &*current }.next.load(atomic::Ordering::Acquire);
+            if current != stub {
+                drop(unsafe { Box::from_raw(current as *mut T) });
+            }
+            current = next;
+        }
+    }
+}
+
+/// The Sending-Half of the Queue, which can be cloned to get multiple
+/// Producers for the same Queue
+pub struct Sender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// The Receiving-Half of the Queue
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Sender<T>
+where
+    T: Linked,
+{
+    /// Checks if the Queue has been closed by the Consumer
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(atomic::Ordering::Acquire)
+    }
+
+    /// Enqueues the given Element, this only fails if the Queue has been
+    /// closed by the Consumer
+    pub fn enqueue(&self, element: Box<T>) -> Result<(), (Box<T>, EnqueueError)> {
+        if self.is_closed() {
+            return Err((element, EnqueueError::Closed));
+        }
+
+        self.inner.push(Box::into_raw(element) as *mut Link);
+        Ok(())
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Intrusive-Sender ()")
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.inner.senders.fetch_add(1, atomic::Ordering::AcqRel);
+
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        // Only the last Sender closes the Queue
+        if self.inner.senders.fetch_sub(1, atomic::Ordering::AcqRel) == 1 {
+            self.inner.closed.store(true, atomic::Ordering::Release);
+        }
+    }
+}
+
+impl<T> Receiver<T>
+where
+    T: Linked,
+{
+    /// Checks if the Queue has been closed by the Producers
+    ///
+    /// # Note
+    /// Even when this indicates that the Queue is closed, there might still be
+    /// Items left in the Queue that the Consumer should dequeue first to make
+    /// sure that no data is lost
+    pub fn is_closed(&self) -> bool {
+        self.inner.closed.load(atomic::Ordering::Acquire)
+    }
+
+    /// Attempts to dequeue the next Element from the Queue
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::queues::mpsc::intrusive::{self, Link, Linked};
+    /// # use nolock::queues::DequeueError;
+    /// # #[repr(C)]
+    /// # struct Command {
+    /// #     link: Link,
+    /// #     id: usize,
+    /// # }
+    /// # unsafe impl Linked for Command {}
+    /// let (tx, mut rx) = intrusive::channel::<Command>();
+    ///
+    /// assert!(matches!(rx.try_dequeue(), Err(DequeueError::Empty)));
+    ///
+    /// drop(tx);
+    /// assert!(matches!(rx.try_dequeue(), Err(DequeueError::Closed)));
+    /// ```
+    pub fn try_dequeue(&mut self) -> Result<Box<T>, DequeueError> {
+        // # Safety:
+        // We have mutable access to the single Receiver
+        if let Some(element) = unsafe { self.inner.pop() } {
+            return Ok(element);
+        }
+
+        if self.is_closed() {
+            // The Producer may have enqueued another Element before closing
+            // the Queue
+            return match unsafe { self.inner.pop() } {
+                Some(element) => Ok(element),
+                None => Err(DequeueError::Closed),
+            };
+        }
+
+        Err(DequeueError::Empty)
+    }
+
+    /// A blocking dequeue Operation, which spins, with an exponential
+    /// [`Backoff`], until it either dequeued an Element or the Queue has been
+    /// closed
+    pub fn dequeue(&mut self) -> Option<Box<T>> {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_dequeue() {
+                Ok(element) => return Some(element),
+                Err(DequeueError::Empty) => backoff.snooze(),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+            };
+        }
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Intrusive-Receiver ()")
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.inner.closed.store(true, atomic::Ordering::Release);
+    }
+}
+
+// Safety:
+// The Elements are only ever owned by a single Side of the Queue at a time
+// and the shared State is only modified using atomic Operations, except for
+// the Tail, which is only accessed by the single Receiver
+unsafe impl<T> Send for Sender<T> where T: Send {}
+unsafe impl<T> Sync for Sender<T> where T: Send {}
+unsafe impl<T> Send for Receiver<T> where T: Send {}
+unsafe impl<T> Sync for Receiver<T> where T: Send {}
+
+/// Creates a new empty Queue and returns the Halves as ([`Sender`],
+/// [`Receiver`]), in the same Order as `std::sync::mpsc::channel`
+pub fn channel<T>() -> (Sender<T>, Receiver<T>)
+where
+    T: Linked,
+{
+    let stub = Box::new(Link::new());
+    let stub_ptr = &*stub as *const Link as *mut Link;
+
+    let inner = Arc::new(Inner {
+        stub,
+        head: atomic::AtomicPtr::new(stub_ptr),
+        tail: UnsafeCell::new(stub_ptr),
+        closed: atomic::AtomicBool::new(false),
+        senders: atomic::AtomicUsize::new(1),
+        _marker: PhantomData,
+    });
+
+    (
+        Sender {
+            inner: inner.clone(),
+        },
+        Receiver { inner },
+    )
+}
+
+impl<T> crate::queues::adapter::QueueReceiver for Receiver<T>
+where
+    T: Linked,
+{
+    type Item = Box<T>;
+
+    fn try_dequeue(&mut self) -> Result<Box<T>, DequeueError> {
+        Receiver::try_dequeue(self)
+    }
+
+    fn dequeue(&mut self) -> Option<Box<T>> {
+        Receiver::dequeue(self)
+    }
+}
+
+impl<T> crate::queues::dynamic::DynSender<Box<T>> for Sender<T>
+where
+    T: Linked,
+{
+    fn try_enqueue(&mut self, element: Box<T>) -> Result<(), (Box<T>, EnqueueError)> {
+        Sender::enqueue(self, element)
+    }
+
+    fn enqueue(&mut self, element: Box<T>) -> Result<(), (Box<T>, EnqueueError)> {
+        Sender::enqueue(self, element)
+    }
+
+    fn is_closed(&self) -> bool {
+        Sender::is_closed(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    #[repr(C)]
+    struct Element {
+        link: Link,
+        value: usize,
+    }
+
+    unsafe impl Linked for Element {}
+
+    fn element(value: usize) -> Box<Element> {
+        Box::new(Element {
+            link: Link::new(),
+            value,
+        })
+    }
+
+    #[test]
+    fn enqueue_dequeue() {
+        let (tx, mut rx) = channel::<Element>();
+
+        for i in 0..10 {
+            tx.enqueue(element(i)).unwrap();
+        }
+        for i in 0..10 {
+            assert_eq!(i, rx.try_dequeue().unwrap().value);
+        }
+        assert!(matches!(rx.try_dequeue(), Err(DequeueError::Empty)));
+    }
+
+    #[test]
+    fn reuse_element() {
+        let (tx, mut rx) = channel::<Element>();
+
+        tx.enqueue(element(13)).unwrap();
+        let mut elem = rx.try_dequeue().unwrap();
+        elem.value = 14;
+        tx.enqueue(elem).unwrap();
+
+        assert_eq!(14, rx.try_dequeue().unwrap().value);
+    }
+
+    #[test]
+    fn enqueue_closed() {
+        let (tx, rx) = channel::<Element>();
+        drop(rx);
+
+        let (elem, err) = tx.enqueue(element(13)).unwrap_err();
+        assert_eq!(13, elem.value);
+        assert_eq!(EnqueueError::Closed, err);
+    }
+
+    #[test]
+    fn closed_after_last_sender() {
+        let (tx, mut rx) = channel::<Element>();
+        let tx2 = tx.clone();
+
+        tx.enqueue(element(13)).unwrap();
+        drop(tx);
+        assert!(!rx.is_closed());
+
+        tx2.enqueue(element(14)).unwrap();
+        drop(tx2);
+        assert!(rx.is_closed());
+
+        assert_eq!(13, rx.try_dequeue().unwrap().value);
+        assert_eq!(14, rx.try_dequeue().unwrap().value);
+        assert_eq!(DequeueError::Closed, rx.try_dequeue().unwrap_err());
+    }
+
+    #[test]
+    fn drops_remaining() {
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug)]
+        #[repr(C)]
+        struct Counted {
+            link: Link,
+        }
+        unsafe impl Linked for Counted {}
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                DROPPED.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let (tx, mut rx) = channel::<Counted>();
+        for _ in 0..5 {
+            tx.enqueue(Box::new(Counted { link: Link::new() })).unwrap();
+        }
+        drop(rx.try_dequeue().unwrap());
+        drop(rx);
+        drop(tx);
+
+        assert_eq!(5, DROPPED.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn multiple_producers() {
+        let (tx, mut rx) = channel::<Element>();
+
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let tx = tx.clone();
+                std::thread::spawn(move || {
+                    for i in 0..250 {
+                        tx.enqueue(element(t * 1000 + i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut last = [None; 4];
+        let mut count = 0;
+        while let Some(elem) = rx.dequeue() {
+            let producer = elem.value / 1000;
+            let index = elem.value % 1000;
+            assert!(!matches!(last[producer], Some(l) if l >= index));
+            last[producer] = Some(index);
+            count += 1;
+        }
+        assert_eq!(1000, count);
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}
@@ -14,27 +14,136 @@
 //! assert_eq!(Ok(13), rx.try_dequeue());
 //! ```
 //!
+//! # Ordering
+//! By default the Queue only guarantees that the Elements of a single
+//! Producer are dequeued in the Order they were enqueued. If an Element from
+//! one Producer is still being written, the Receiver skips ahead and returns
+//! Elements from other Producers that were enqueued later, instead of
+//! waiting for it.
+//!
+//! If the global Order matters, the Queue can be created with
+//! [`OrderingMode::StrictFifo`] using [`queue_with_ordering`], in which case
+//! the Receiver instead waits for the earliest pending Element, at the cost of
+//! a single slow Producer delaying all the other Elements.
+//!
+//! # Allocations
+//! The Queue allocates new Buffers as it grows, which are reused once they
+//! have been fully consumed. If Elements need to be enqueued from a Context in
+//! which allocating is not allowed, like a Signal-Handler,
+//! [`Sender::try_enqueue_no_alloc`] can be used, which fails instead of
+//! appending a new Buffer to the Queue. If allocating is fine, but running out
+//! of Memory should not abort the Process, [`Sender::enqueue_fallible`]
+//! reports a failed Allocation as [`EnqueueError::AllocFailed`] instead.
+//!
+//! # Backpressure
+//! The Queue itself is unbounded, but the [`bounded`] Module provides a
+//! Facade that limits the Number of Elements in the Queue using Credits,
+//! while still using the wait-free Enqueue of the Queue.
+//!
+//! # Zero-Sized Types
+//! If the Elements are zero-sized, like `()`, the Queue does not allocate
+//! any Buffers and instead only counts the Number of Elements in it, which
+//! makes it a cheap Token-Channel.
+//!
 //! # Reference:
 //! * [Jiffy: A Fast, Memory Efficient, Wait-Free Multi-Producers Single-Consumer Queue](https://arxiv.org/pdf/2010.14189.pdf)
 
-use alloc::{boxed::Box, sync::Arc};
-use core::{fmt::Debug, sync::atomic};
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::fmt::Debug;
+
+use crate::sync::native::atomic;
 
-/// The Size of each Buffer in the "BufferList"
+/// The Size of each Buffer in the "BufferList", which needs to be a Power of
+/// two, so that the Locations stay consistent when they wrap around
 const BUFFER_SIZE: usize = 1024;
+/// The Number of consumed Buffers that are kept around to be reused for new
+/// Buffers, instead of allocating new ones
+const BUFFER_CACHE_SIZE: usize = 4;
+
+/// Checks if the absolute Location `a` comes before the Location `b`.
+///
+/// The Locations simply wrap around once they overflow, which can happen
+/// for long running Queues on 32-bit Targets, so they are compared using
+/// their wrapping Distance instead of their absolute Values. This is correct
+/// as long as the two Locations are less than `usize::MAX / 2` apart
+const fn is_before(a: usize, b: usize) -> bool {
+    (a.wrapping_sub(b) as isize) < 0
+}
+
+/// Calculates the absolute Location of the first Node in the Buffer with the
+/// given Position in the List of Buffers
+const fn buffer_start(position_in_queue: usize) -> usize {
+    position_in_queue.wrapping_sub(1).wrapping_mul(BUFFER_SIZE)
+}
+
+/// Calculates the absolute Location right after the last Node in the Buffer
+/// with the given Position in the List of Buffers
+const fn buffer_end(position_in_queue: usize) -> usize {
+    position_in_queue.wrapping_mul(BUFFER_SIZE)
+}
 
 mod node;
-use node::NodeState;
+use node::{Location, NodeState};
+
+#[cfg(feature = "debug-validate")]
+#[cfg_attr(docsrs, doc(cfg(feature = "debug-validate")))]
+pub mod validate;
 
 mod bufferlist;
-use bufferlist::BufferList;
+use bufferlist::{BufferCache, BufferList};
+
+pub mod bounded;
 
 #[cfg(feature = "async")]
 mod async_queue;
 #[cfg(feature = "async")]
 pub use async_queue::*;
 
-use crate::queues::{DequeueError, EnqueueError};
+#[cfg(feature = "async")]
+use crate::queues::cooperative::{self, CooperativeDequeue};
+use crate::{
+    queues::{
+        cancel::{self, CancelToken},
+        instrument::Metrics,
+        shutdown::{Shutdown, ShutdownReport},
+        DequeueError, EnqueueError,
+    },
+    utils::{Backoff, SegmentAlloc, Segments},
+};
+
+/// Checks if the Elements are zero-sized, in which case the Queue only counts
+/// the Elements instead of storing them in Buffers
+const fn is_zst<T>() -> bool {
+    core::mem::size_of::<T>() == 0
+}
+
+/// Recreates an Instance of a zero-sized Type.
+///
+/// # Safety
+/// `T` needs to be zero-sized and the Caller must own an Instance of it, that
+/// was previously forgotten, as otherwise this could create an Instance of a
+/// Type that can not be constructed normally
+unsafe fn recreate_zst<T>() -> T {
+    debug_assert!(is_zst::<T>());
+    unsafe { core::ptr::NonNull::<T>::dangling().as_ptr().read() }
+}
+
+/// The Bit in the Tokens, that is set once the Receiver took all the Tokens
+/// while closing the Queue, after which no more Tokens can be added
+const TOKENS_CLOSED: usize = 1usize << (usize::BITS - 1);
+
+/// The Ordering guarantees provided by a Jiffy-Queue, see the
+/// [`module-level documentation`](self) for more details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrderingMode {
+    /// Elements of a single Producer are dequeued in Order, but the Receiver
+    /// may skip over Elements that are still being enqueued
+    #[default]
+    Relaxed,
+    /// All Elements are dequeued in the Order in which they were assigned
+    /// their Slot in the Queue, the Receiver never skips over any Elements
+    StrictFifo,
+}
 
 /// One of the Sender, created by calling [`queue`]
 pub struct Sender<T> {
@@ -42,9 +151,22 @@ pub struct Sender<T> {
     closed: Arc<atomic::AtomicBool>,
     /// This is a shared Usize that Points to the Location in the overall
     /// Buffer-List, where the next Item should be enqueued
-    tail: atomic::AtomicUsize,
+    tail: Arc<atomic::AtomicUsize>,
     /// This is a shared Pointer to the Last Buffer in the Buffer-List
     tail_of_queue: atomic::AtomicPtr<BufferList<T>>,
+    /// The Number of Elements in the Queue, which is only used instead of
+    /// the Buffers if the Elements are zero-sized
+    tokens: Arc<atomic::AtomicUsize>,
+    /// The Cache of consumed Buffers, that can be reused
+    cache: Arc<BufferCache<T>>,
+    /// Counts the Elements that are dropped together with the Queue
+    shutdown: Arc<Shutdown>,
+    /// The Hooks to report the Operations to
+    metrics: Metrics,
+    /// The Waker of the Receiver, when it is async, and the Number of sync
+    /// Senders, that will not wake it up
+    #[cfg(feature = "async")]
+    shared: Arc<async_queue::Shared>,
 }
 
 /// The Single Receiver of a Jiffy-Queue, created by calling [`queue`]
@@ -54,13 +176,43 @@ pub struct Receiver<T> {
     /// This is a simply Ptr to the current Buffer from where items will be
     /// dequeued
     head_of_queue: *mut BufferList<T>,
+    /// The Index of the next Node to dequeue in the current Buffer, which is
+    /// kept out of the shared BufferList as only the Receiver needs it
+    head: usize,
+    /// The Tail shared with the Sender, which is only used to find the last
+    /// claimed Location, when draining the Queue
+    tail: Arc<atomic::AtomicUsize>,
+    /// The Number of Elements in the Queue, which is only used instead of
+    /// the Buffers if the Elements are zero-sized
+    tokens: Arc<atomic::AtomicUsize>,
+    /// The Cache to which consumed Buffers are returned
+    cache: Arc<BufferCache<T>>,
+    /// Counts the Elements that are dropped together with the Queue
+    shutdown: Arc<Shutdown>,
+    /// The Ordering guarantees this Receiver should uphold
+    ordering: OrderingMode,
+    /// The Sequence-Number of the last dequeued Element
+    last_sequence: Option<usize>,
+    /// Whether this Receiver already closed the Queue, while the Sender was
+    /// still alive, in which case the Sender is responsible for cleaning up
+    closed_by_receiver: bool,
+    /// The Hooks to report the Operations to
+    metrics: Metrics,
+    /// The Waker of this Receiver, when it is async, and the Number of sync
+    /// Senders, that will not wake it up
+    #[cfg(feature = "async")]
+    shared: Arc<async_queue::Shared>,
 }
 
 /// This function is responsible for properly closing the Queue and depending
 /// on the Situation, cleaning up all the Data that is still left to be cleaned
 /// up
-fn close_side<T, F>(closed: &atomic::AtomicBool, get_ptr: F)
-where
+fn close_side<T, F>(
+    closed: &atomic::AtomicBool,
+    tokens: &atomic::AtomicUsize,
+    shutdown: &Shutdown,
+    get_ptr: F,
+) where
     F: Fn() -> *mut BufferList<T>,
 {
     // Attempt to "CAS" the closed value, assuming that the other side was
@@ -78,9 +230,23 @@ where
         // that has access to the Queue and therefore it our job to
         // properly clean up all the shared State, before we can also
         // exit
+        Err(_) if is_zst::<T>() => {
+            // If the Receiver already took all the Tokens, while closing the
+            // Queue, the remaining Count only consists of failed Enqueues
+            let remaining = match tokens.swap(0, atomic::Ordering::SeqCst) {
+                count if count & TOKENS_CLOSED != 0 => 0,
+                count => count,
+            };
+            for _ in 0..remaining {
+                // Safety:
+                // Every Token was created by forgetting an Element
+                drop(unsafe { recreate_zst::<T>() });
+            }
+            shutdown.record_dropped(remaining);
+        }
         Err(_) => {
             let buffer_list_ptr = get_ptr();
-            BufferList::deallocate_all(buffer_list_ptr);
+            shutdown.record_dropped(BufferList::deallocate_all(buffer_list_ptr));
         }
     };
 }
@@ -102,6 +268,13 @@ impl<T> Sender<T> {
         self.closed.load(atomic::Ordering::Acquire)
     }
 
+    /// Returns a Report, that tells how many Elements were still left in the
+    /// Queue once it has been shut down, see the
+    /// [`shutdown`](crate::queues::shutdown) module for more Details
+    pub fn shutdown_report(&self) -> ShutdownReport {
+        self.shutdown.report()
+    }
+
     /// Enqueues the given Data on the queue
     ///
     /// # Returns
@@ -123,25 +296,68 @@ impl<T> Sender<T> {
     /// # drop(rx);
     /// ```
     pub fn enqueue(&self, data: T) -> Result<(), (T, EnqueueError)> {
+        self.enqueue_with_sequence(data).map(|_| ())
+    }
+
+    /// Enqueues the given Data on the queue, just like [`enqueue`](Self::enqueue),
+    /// but also returns the Sequence-Number assigned to the Data.
+    ///
+    /// # Sequence-Number
+    /// Every Element enqueued on the Queue gets a unique Sequence-Number,
+    /// starting at 0 and increasing by one for every Element. These can be
+    /// used to correlate Elements across Producers and Consumer, see
+    /// [`Receiver::last_sequence`] for the Consumer side.
+    ///
+    /// The Sequence-Numbers wrap around to 0 once they overflow a `usize`,
+    /// which can happen for long running Queues on 32-bit Targets, so they
+    /// are only unique among the last `usize::MAX` Elements.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// let (mut rx, tx) = jiffy::queue::<usize>();
+    ///
+    /// assert_eq!(Ok(0), tx.enqueue_with_sequence(13));
+    /// assert_eq!(Ok(1), tx.enqueue_with_sequence(14));
+    ///
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    /// assert_eq!(Some(0), rx.last_sequence());
+    /// ```
+    pub fn enqueue_with_sequence(&self, data: T) -> Result<usize, (T, EnqueueError)> {
         if self.is_closed() {
             return Err((data, EnqueueError::Closed));
         }
 
+        if is_zst::<T>() {
+            // The Receiver takes all the Tokens at once, when it closes the
+            // Queue, so a Token can only be added before that
+            if self.tokens.fetch_add(1, atomic::Ordering::AcqRel) & TOKENS_CLOSED != 0 {
+                return Err((data, EnqueueError::Closed));
+            }
+            let location = self.tail.fetch_add(1, atomic::Ordering::AcqRel);
+
+            // The Element itself does not need to be stored, because it can
+            // simply be recreated once it is dequeued
+            core::mem::forget(data);
+
+            self.metrics.enqueue();
+            return Ok(location);
+        }
+
         // Load our target absolute position, on where to insert the next
         // Element
         //
-        // This needs to use at least Ordering::AcqRel because we would otherwise
-        // have one half of the load-store operation be Ordering::Relaxed, which
-        // is not what we need
-        let location = self.tail.fetch_add(1, atomic::Ordering::AcqRel);
+        // This needs to be SeqCst, as it pairs with the Receiver closing the
+        // Queue and then loading the Tail in `close_and_drain`
+        let location = self.tail.fetch_add(1, atomic::Ordering::SeqCst);
 
         // Get the current tail-buffer, where we would initially attempt to
         // insert the Element into
         let mut tmp_buffer_ptr = self.tail_of_queue.load(atomic::Ordering::Acquire);
-        let mut tmp_buffer = unsafe { &*tmp_buffer_ptr };
+        let tmp_buffer = unsafe { &*tmp_buffer_ptr };
 
         // Get the current End position of the received buffer
-        let mut end = tmp_buffer.position_in_queue * BUFFER_SIZE;
+        let mut end = buffer_end(tmp_buffer.position_in_queue);
         // If the Target-Location is beyond the current Buffer, we need
         // to either create a new Buffer and append it to the Queue or
         // simply walk the List of Buffers in the Queue until we find one
@@ -149,47 +365,287 @@ impl<T> Sender<T> {
         // However this does not garantuee, that the resulting buffer
         // actually contains our Target-Location, because the buffer we
         // find could come after the Buffer that we actually need
-        while location >= end {
+        while !is_before(location, end) {
             // Move to the next Buffer in the Queue, this will also automatically create
             // a new Buffer if there is no next Buffer currently available
-            tmp_buffer_ptr = tmp_buffer.go_to_next(tmp_buffer_ptr, &self.tail_of_queue);
-            tmp_buffer = unsafe { &*tmp_buffer_ptr };
+            let tmp_buffer = unsafe { &*tmp_buffer_ptr };
+            tmp_buffer_ptr =
+                tmp_buffer.go_to_next(tmp_buffer_ptr, &self.tail_of_queue, &self.cache);
 
             // Recalculate the current End of the new Tail-Buffer
-            end = tmp_buffer.position_in_queue * BUFFER_SIZE;
+            end = buffer_end(unsafe { &*tmp_buffer_ptr }.position_in_queue);
+        }
+
+        if self.closed.load(atomic::Ordering::SeqCst) {
+            self.abandon_at(location, tmp_buffer_ptr);
+            return Err((data, EnqueueError::Closed));
+        }
+
+        self.store_at(location, tmp_buffer_ptr, data, true);
+        self.metrics.enqueue();
+
+        Ok(location)
+    }
+
+    /// Attempts to enqueue the given Data, without ever allocating any
+    /// Memory, which makes it usable in Contexts where allocating is not
+    /// allowed, like Signal-Handlers.
+    ///
+    /// Normally a new Buffer is allocated, or taken from the Cache of
+    /// consumed Buffers, ahead of time once the current Buffer starts to
+    /// fill up. This Operation never appends a new Buffer and instead fails
+    /// with [`EnqueueError::NoSpace`] if the Element would not fit into the
+    /// Buffers that are already part of the Queue.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// # use nolock::queues::EnqueueError;
+    /// let (mut rx, tx) = jiffy::queue::<usize>();
+    ///
+    /// // The initial Buffer still has Space
+    /// assert_eq!(Ok(()), tx.try_enqueue_no_alloc(13));
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    ///
+    /// // Fill up all the Buffers that are currently allocated
+    /// let mut i = 0;
+    /// let data = loop {
+    ///     match tx.try_enqueue_no_alloc(i) {
+    ///         Ok(()) => i += 1,
+    ///         Err((data, EnqueueError::NoSpace)) => break data,
+    ///         Err(_) => unreachable!(),
+    ///     }
+    /// };
+    ///
+    /// // A normal Enqueue is still possible, as it allocates a new Buffer
+    /// assert_eq!(Ok(()), tx.enqueue(data));
+    /// ```
+    pub fn try_enqueue_no_alloc(&self, data: T) -> Result<(), (T, EnqueueError)> {
+        self.try_enqueue_claimed(data, false)
+    }
+
+    /// Enqueues the given Data, like [`enqueue`](Self::enqueue), but fails
+    /// with [`EnqueueError::AllocFailed`] instead of aborting the Process if
+    /// a new Buffer is needed and its Allocation fails.
+    ///
+    /// Unlike [`enqueue`](Self::enqueue), this does not allocate the next
+    /// Buffer ahead of time, but only once an Element does not fit into the
+    /// Buffers that are already part of the Queue. The Element is only
+    /// enqueued if there is a Buffer for it, so a failed Allocation never
+    /// leaves a Gap in the Queue that the Receiver would have to wait on.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// # use nolock::queues::EnqueueError;
+    /// let (mut rx, tx) = jiffy::queue::<usize>();
+    ///
+    /// for i in 0..4096 {
+    ///     match tx.enqueue_fallible(i) {
+    ///         Ok(()) => {}
+    ///         // Back off and shed Load instead of aborting
+    ///         Err((_, EnqueueError::AllocFailed)) => break,
+    ///         Err(_) => unreachable!(),
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Ok(0), rx.try_dequeue());
+    /// ```
+    pub fn enqueue_fallible(&self, data: T) -> Result<(), (T, EnqueueError)> {
+        self.try_enqueue_claimed(data, true)
+    }
+
+    /// Enqueues the Data, but only claims a Location in the Queue once there
+    /// is a Buffer for it. If there is no such Buffer, the next one is either
+    /// allocated without aborting on Failure, if `allocate` is set, or the
+    /// Operation fails with [`EnqueueError::NoSpace`]
+    fn try_enqueue_claimed(&self, data: T, allocate: bool) -> Result<(), (T, EnqueueError)> {
+        if self.is_closed() {
+            return Err((data, EnqueueError::Closed));
+        }
+
+        if is_zst::<T>() {
+            return self.enqueue_with_sequence(data).map(|_| ());
+        }
+
+        // Every Location that is claimed, also has to be filled, otherwise
+        // the Receiver might wait on it forever. So we only claim the next
+        // Location, once we know that there already is a Buffer for it
+        let (location, buffer_ptr) = loop {
+            let location = self.tail.load(atomic::Ordering::Acquire);
+
+            let mut buffer_ptr = self.tail_of_queue.load(atomic::Ordering::Acquire);
+            while !is_before(
+                location,
+                buffer_end(unsafe { &*buffer_ptr }.position_in_queue),
+            ) {
+                let buffer = unsafe { &*buffer_ptr };
+                let next_ptr = buffer.next.load(atomic::Ordering::Acquire);
+                if !next_ptr.is_null() {
+                    buffer_ptr = next_ptr;
+                    continue;
+                }
+
+                if !allocate {
+                    self.metrics.full();
+                    return Err((data, EnqueueError::NoSpace));
+                }
+
+                match buffer.try_allocate_next(buffer_ptr, &self.tail_of_queue, &self.cache) {
+                    Some(next_ptr) => buffer_ptr = next_ptr,
+                    None => return Err((data, EnqueueError::AllocFailed)),
+                };
+            }
+
+            if self
+                .tail
+                .compare_exchange_weak(
+                    location,
+                    location.wrapping_add(1),
+                    atomic::Ordering::SeqCst,
+                    atomic::Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                break (location, buffer_ptr);
+            }
+        };
+
+        if self.closed.load(atomic::Ordering::SeqCst) {
+            self.abandon_at(location, buffer_ptr);
+            return Err((data, EnqueueError::Closed));
+        }
+
+        self.store_at(location, buffer_ptr, data, false);
+        self.metrics.enqueue();
+
+        Ok(())
+    }
+
+    /// Stores the Data at the given, already claimed, Location in the Queue.
+    ///
+    /// The given Buffer needs to end after the Location, but may also start
+    /// after it, in which case we walk back to the right Buffer. If
+    /// `allocate` is set, the next Buffer is allocated ahead of time once
+    /// the last Buffer starts to fill up
+    fn store_at(
+        &self,
+        location: usize,
+        tmp_buffer_ptr: *mut BufferList<T>,
+        data: T,
+        allocate: bool,
+    ) {
+        let (tmp_buffer_ptr, index, last_buffer) = Self::find_node(location, tmp_buffer_ptr);
+        let tmp_buffer = unsafe { &*tmp_buffer_ptr };
+
+        // Actually store the Data into the Buffer at the previously
+        // calculated Index
+        unsafe { tmp_buffer.buffer.get_unchecked(index) }
+            .store(data, Location::new(tmp_buffer.position_in_queue, index));
+
+        if allocate && last_buffer && index == 2 {
+            tmp_buffer.allocate_next(tmp_buffer_ptr, &self.tail_of_queue, &self.cache);
         }
+    }
+
+    /// Gives up on the given, already claimed, Location in the Queue, after
+    /// the Queue has been closed, so that the Receiver does not wait for it
+    /// when draining the Queue
+    fn abandon_at(&self, location: usize, tmp_buffer_ptr: *mut BufferList<T>) {
+        let (tmp_buffer_ptr, index, _) = Self::find_node(location, tmp_buffer_ptr);
+        let tmp_buffer = unsafe { &*tmp_buffer_ptr };
+
+        unsafe { tmp_buffer.buffer.get_unchecked(index) }
+            .abandon(Location::new(tmp_buffer.position_in_queue, index));
+    }
+
+    /// Finds the Buffer containing the given Location, starting at the given
+    /// Buffer, which needs to end after the Location, and walking back from
+    /// there. Returns the Buffer, the Index of the Location in it and
+    /// whether it is the given Buffer
+    fn find_node(
+        location: usize,
+        mut tmp_buffer_ptr: *mut BufferList<T>,
+    ) -> (*mut BufferList<T>, usize, bool) {
+        let mut tmp_buffer = unsafe { &*tmp_buffer_ptr };
+        crate::poison::check(tmp_buffer_ptr);
 
         // Calculate the Starting-Location of the currently loaded
         // Buffer
-        let mut start = (tmp_buffer.position_in_queue - 1) * BUFFER_SIZE;
+        let mut start = buffer_start(tmp_buffer.position_in_queue);
 
         let mut last_buffer = true;
         // If the Target-Location is before the current Buffer's start,
         // we need to move back in the List of Buffers until we find the one
         // that actually contains our Target-Location
-        while location < start {
+        while is_before(location, start) {
             // Load the previous Buffer in regards to our current one
-            tmp_buffer_ptr = tmp_buffer.previous as *mut BufferList<T>;
+            tmp_buffer_ptr = tmp_buffer.previous();
+            crate::poison::check(tmp_buffer_ptr);
             tmp_buffer = unsafe { &*tmp_buffer_ptr };
 
             last_buffer = false;
 
             // Recalculate the Buffers Starting position for the new one
-            start = (tmp_buffer.position_in_queue - 1) * BUFFER_SIZE;
+            start = buffer_start(tmp_buffer.position_in_queue);
         }
 
         // Calculate the concrete Target-Index in the final Buffer
-        let index = location - start;
+        let index = location.wrapping_sub(start);
 
-        // Actually store the Data into the Buffer at the previously
-        // calculated Index
-        unsafe { tmp_buffer.buffer.get_unchecked(index) }.store(data);
+        (tmp_buffer_ptr, index, last_buffer)
+    }
+
+    /// Fills the Queue with all the Elements from the Iterator, before any
+    /// Half has been shared with another Thread.
+    ///
+    /// As there can be no concurrent Operations, the Locations and Buffers
+    /// do not need to be claimed atomically and are simply written in Order
+    fn prefill<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let location = self.tail.load(atomic::Ordering::Relaxed);
+        let mut count = 0;
+
+        if is_zst::<T>() {
+            for data in iter {
+                core::mem::forget(data);
+                count += 1;
+            }
+            self.tokens.fetch_add(count, atomic::Ordering::Release);
+        } else {
+            let mut buffer_ptr = self.tail_of_queue.load(atomic::Ordering::Relaxed);
+            let mut buffer = unsafe { &*buffer_ptr };
+            let mut index = location.wrapping_sub(buffer_start(buffer.position_in_queue));
+
+            for data in iter {
+                if index == BUFFER_SIZE {
+                    let next_ptr = Box::into_raw(self.cache.get(
+                        buffer_ptr as *const _,
+                        buffer.position_in_queue.wrapping_add(1),
+                    ));
+                    buffer.next.store(next_ptr, atomic::Ordering::Release);
+
+                    buffer_ptr = next_ptr;
+                    buffer = unsafe { &*buffer_ptr };
+                    index = 0;
+                }
+
+                unsafe { buffer.buffer.get_unchecked(index) }
+                    .store(data, Location::new(buffer.position_in_queue, index));
+
+                index += 1;
+                count += 1;
+            }
 
-        if last_buffer && index == 2 {
-            tmp_buffer.allocate_next(tmp_buffer_ptr, &self.tail_of_queue);
+            self.tail_of_queue
+                .store(buffer_ptr, atomic::Ordering::Release);
         }
 
-        Ok(())
+        self.tail
+            .store(location.wrapping_add(count), atomic::Ordering::Release);
+        self.metrics.enqueue_many(count);
     }
 }
 
@@ -201,9 +657,14 @@ impl<T> Debug for Sender<T> {
 
 impl<T> Drop for Sender<T> {
     fn drop(&mut self) {
-        close_side(&self.closed, || {
+        close_side(&self.closed, &self.tokens, &self.shutdown, || {
             self.tail_of_queue.load(atomic::Ordering::Acquire)
         });
+
+        // The Queue is now marked as closed, so an async Receiver will see
+        // that once it is woken up
+        #[cfg(feature = "async")]
+        self.shared.waker.wake();
     }
 }
 
@@ -232,6 +693,73 @@ impl<T> Receiver<T> {
         self.closed.load(atomic::Ordering::Acquire)
     }
 
+    /// Returns a Report, that tells how many Elements were still left in the
+    /// Queue once it has been shut down, see the
+    /// [`shutdown`](crate::queues::shutdown) module for more Details
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// let (rx, tx) = jiffy::queue::<usize>();
+    /// let report = rx.shutdown_report();
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// drop(tx);
+    /// drop(rx);
+    ///
+    /// assert_eq!(Some(1), report.closed_with_remaining());
+    /// ```
+    pub fn shutdown_report(&self) -> ShutdownReport {
+        self.shutdown.report()
+    }
+
+    /// Returns the Sequence-Number of the last Element that was dequeued,
+    /// or `None` if no Element has been dequeued yet.
+    ///
+    /// This is the same Sequence-Number that was returned by
+    /// [`Sender::enqueue_with_sequence`] for the Element, which can be used
+    /// to correlate Elements or to detect Gaps in the received Elements,
+    /// like when the Queue is not in [`OrderingMode::StrictFifo`].
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// let (mut rx, tx) = jiffy::queue::<usize>();
+    ///
+    /// assert_eq!(None, rx.last_sequence());
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// tx.enqueue(14).unwrap();
+    ///
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    /// assert_eq!(Some(0), rx.last_sequence());
+    /// assert_eq!(Ok(14), rx.try_dequeue());
+    /// assert_eq!(Some(1), rx.last_sequence());
+    /// ```
+    pub fn last_sequence(&self) -> Option<usize> {
+        self.last_sequence
+    }
+
+    /// Records that the Data was dequeued from the Node at the current Head
+    /// of the given Buffer and advances the Head past it
+    fn dequeued_head(&mut self, buffer: &BufferList<T>, data: T) -> T {
+        self.last_sequence = Some(Self::sequence(buffer, self.head));
+
+        // Advance the Head of the current Buffer to the next Node
+        self.head += 1;
+
+        // Move to the next Buffer if we need to
+        self.move_to_next_buffer();
+        self.metrics.dequeue();
+        data
+    }
+
+    /// Calculates the Sequence-Number of the Node at the given Index in the
+    /// given Buffer
+    fn sequence(buffer: &BufferList<T>, index: usize) -> usize {
+        buffer_start(buffer.position_in_queue).wrapping_add(index)
+    }
+
     /// Checks if the end of the current Buffer has been reached and if that
     /// is the case, we need to attempt to switch over to the next Buffer in
     /// the List of Buffers
@@ -242,7 +770,7 @@ impl<T> Receiver<T> {
 
         // If the current Queue has reached its end, we should attempt to
         // switch over to the next Buffer
-        if current_queue.head >= BUFFER_SIZE {
+        if self.head >= BUFFER_SIZE {
             // Lines 63 - 65
             // can be ommited in this case as the next_ptr will then also be 0 and therefore
             // the next check should catch that
@@ -257,15 +785,17 @@ impl<T> Receiver<T> {
 
             // Store the next Buffer as the current Buffer
             self.head_of_queue = next_ptr;
+            self.head = 0;
 
-            // Drop and therefore free the previously current Buffer
-            drop(unsafe { Box::from_raw(current_queue_ptr) });
+            // Return the previously current Buffer to the Cache, so it can
+            // be reused by the Producers
+            self.cache.put(unsafe { Box::from_raw(current_queue_ptr) });
 
             // Set the new Heads previous PTR to null to indicate that there
             // is no more valid Previous-BufferList.
             // This is needed for the cleanup of the Queue after the fact
-            let next = unsafe { &mut *self.head_of_queue };
-            next.previous = core::ptr::null();
+            let next = unsafe { &*self.head_of_queue };
+            next.clear_previous();
         }
 
         true
@@ -291,11 +821,15 @@ impl<T> Receiver<T> {
     /// assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
     /// ```
     pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        if is_zst::<T>() {
+            return self.try_dequeue_token();
+        }
+
         // Loads the current Buffer that should be used
-        let mut current_queue = unsafe { &mut *self.head_of_queue };
+        let mut current_queue = unsafe { &*self.head_of_queue };
 
         // Attempt to get the current Entry that we want to dequeue
-        let mut n = match current_queue.buffer.get(current_queue.head) {
+        let mut n = match current_queue.buffer.get(self.head) {
             Some(n) => n,
             None => {
                 // This path is hit, once we reached the end of the current
@@ -307,13 +841,13 @@ impl<T> Receiver<T> {
                 // Attempt to move to the next Buffer again
                 self.move_to_next_buffer();
                 // Reload the current Buffer
-                current_queue = unsafe { &mut *self.head_of_queue };
+                current_queue = unsafe { &*self.head_of_queue };
 
                 // Retry the loading of the Node, we use the `?` in this case,
                 // because if we dont find it again, there is nothing else we
                 // can really do and should simply return None as there was
                 // currently nothing to load
-                match current_queue.buffer.get(current_queue.head) {
+                match current_queue.buffer.get(self.head) {
                     Some(n) => n,
                     None => return Err(DequeueError::Empty),
                 }
@@ -322,19 +856,19 @@ impl<T> Receiver<T> {
 
         // Find the first node that is not set to Handled
         while n.get_state() == NodeState::Handled {
-            current_queue.head += 1;
+            self.head += 1;
 
             if !self.move_to_next_buffer() {
                 return Err(DequeueError::Empty);
             }
 
-            current_queue = unsafe { &mut *self.head_of_queue };
-            n = match current_queue.buffer.get(current_queue.head) {
+            current_queue = unsafe { &*self.head_of_queue };
+            n = match current_queue.buffer.get(self.head) {
                 Some(n) => n,
                 None => {
                     self.move_to_next_buffer();
-                    current_queue = unsafe { &mut *self.head_of_queue };
-                    match current_queue.buffer.get(current_queue.head) {
+                    current_queue = unsafe { &*self.head_of_queue };
+                    match current_queue.buffer.get(self.head) {
                         Some(t) => t,
                         None => return Err(DequeueError::Empty),
                     }
@@ -343,30 +877,46 @@ impl<T> Receiver<T> {
         }
 
         // Load the State of the current Node
+        let location = Location::new(current_queue.position_in_queue, self.head);
         match n.get_state() {
             // If it is Set that means that the Node has Data set and we can
             // simply load the Data from it
-            NodeState::Set => {
-                // Load the Data from the current Node
-                let data = n
-                    .load()
-                    .expect("Data should be loadable and node shoudl be Set");
-
-                // Advance the Head of the current Buffer to the next Node
-                current_queue.head += 1;
-
-                // Move to the next Buffer if we need to
-                self.move_to_next_buffer();
-                // Return the loaded Data
-                Ok(data)
+            NodeState::Set => match n.load(location) {
+                Some(data) => Ok(self.dequeued_head(current_queue, data)),
+                None => Err(DequeueError::Empty),
+            },
+            // In Strict-Fifo mode we are not allowed to skip over the Node,
+            // but a Producer might have already claimed it and just not yet
+            // published its Data, so we wait a bounded Time for it before
+            // reporting the Queue as being empty, unless the Queue has been
+            // closed. If no Producer claimed the Node yet, the Queue is
+            // simply empty and there is nothing to wait for
+            NodeState::Empty if self.ordering == OrderingMode::StrictFifo => {
+                let sequence = Self::sequence(current_queue, self.head);
+                let claimed = is_before(sequence, self.tail.load(atomic::Ordering::Acquire));
+
+                let published = if claimed {
+                    n.load_published(location)
+                } else {
+                    n.load(location)
+                };
+                match published {
+                    Some(data) => Ok(self.dequeued_head(current_queue, data)),
+                    None if !self.is_closed() => Err(DequeueError::Empty),
+                    // Once the Queue is closed, all the Enqueue operations
+                    // have completed, so if the Node is still empty there is
+                    // nothing left to dequeue. Otherwise the Node was set
+                    // between our last check and now, so we simply retry
+                    None if n.get_state() == NodeState::Set => self.try_dequeue(),
+                    None => Err(DequeueError::Closed),
+                }
             }
             // If the found Node is set to empty, we should search the rest
             // of the Buffers of the Queue to find if any other Node has been
             // Set and if we find one return that
             NodeState::Empty => {
                 // Load the current Head of the Queue
-                let tmp_head_of_queue = unsafe { &*self.head_of_queue };
-                let tmp_head = tmp_head_of_queue.head;
+                let tmp_head = self.head;
 
                 // Look for the next Set Node
                 // This returns the Buffer and the Index in the Buffer
@@ -407,6 +957,14 @@ impl<T> Receiver<T> {
                             }
                         }
                     };
+
+                    // Some Node before the found one might have been set
+                    // while we were scanning, in which case we need to
+                    // dequeue that one first to preserve the Order of
+                    // Elements from a single Producer
+                    let (n_queue, n_head) =
+                        BufferList::rescan(self.head_of_queue, tmp_head, n_queue, n_head);
+
                     (unsafe { &*n_queue }, n_head)
                 };
 
@@ -416,49 +974,124 @@ impl<T> Receiver<T> {
                     None => return Err(DequeueError::Empty),
                 };
 
-                // Actually load the Data from the Node
-                let data = tmp_n
-                    .load()
-                    .expect("Data should be loadable and node shoudl be Set");
+                // Actually load the Data from the Node, which was observed
+                // as Set by the Scan and can therefore only fail if the Queue
+                // is in an inconsistent State
+                let location = Location::new(tmp_head_of_queue.position_in_queue, tmp_head);
+                let data = match tmp_n.load(location) {
+                    Some(d) => d,
+                    None => return Err(DequeueError::Empty),
+                };
+                self.last_sequence = Some(Self::sequence(tmp_head_of_queue, tmp_head));
+                self.metrics.dequeue();
 
                 Ok(data)
+            }
+            _ => Err(DequeueError::Empty),
+        }
+    }
 
-                /*
-                let mut head_of_queue = self.load_head_of_queue();
-                let (tmp_head_of_queue, tmp_head) = BufferList::rescan(
-                    self.head_of_queue as *mut BufferList<T>,
-                    tmp_head_of_queue,
-                    tmp_head,
-                );
+    /// Dequeues a single Token, if the Elements are zero-sized.
+    ///
+    /// As the Elements are indistinguishable, the Sequence-Numbers are simply
+    /// assigned in the Order in which the Tokens are dequeued
+    fn try_dequeue_token(&mut self) -> Result<T, DequeueError> {
+        // The Closed-Flag needs to be loaded before the Tokens, because the
+        // Sender only closes the Queue after its last Enqueue
+        let closed = self.is_closed();
+
+        // We are the only one removing Tokens, so if there is at least one
+        // Token, it can not be removed by anyone else in the mean time
+        if self.tokens.load(atomic::Ordering::Acquire) == 0 {
+            return Err(if closed {
+                DequeueError::Closed
+            } else {
+                DequeueError::Empty
+            });
+        }
+        self.tokens.fetch_sub(1, atomic::Ordering::AcqRel);
 
-                let tmp_n = tmp_head_of_queue.buffer.get(tmp_head).unwrap();
+        self.last_sequence = Some(self.last_sequence.map_or(0, |s| s.wrapping_add(1)));
+        self.metrics.dequeue();
 
-                let data = tmp_n.load();
-                tmp_n.handled();
+        // Safety:
+        // The Token was created by forgetting an Element in the Sender
+        Ok(unsafe { recreate_zst() })
+    }
 
-                if tmp_head_of_queue.position_in_queue == head_of_queue.position_in_queue
-                    && head_of_queue.head == tmp_head
-                {
-                    head_of_queue.head += 1;
-                    self.move_to_next_buffer();
-                }
+    /// Attempts to dequeue the next Element, like
+    /// [`try_dequeue`](Self::try_dequeue), but spins for up to `iterations`
+    /// Rounds, with an exponential [`Backoff`], if the Queue is empty, before
+    /// giving up.
+    ///
+    /// While spinning, only the next Node is checked, instead of redoing the
+    /// entire Dequeue every Round. So in [`OrderingMode::Relaxed`], an
+    /// Element after a Node that is still being enqueued is only found by
+    /// the final Attempt once the Spin-Budget is exhausted.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// # use nolock::queues::DequeueError;
+    /// let (mut rx, tx) = jiffy::queue::<usize>();
+    ///
+    /// assert_eq!(Err(DequeueError::Empty), rx.try_dequeue_spin(16));
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// assert_eq!(Ok(13), rx.try_dequeue_spin(16));
+    /// ```
+    pub fn try_dequeue_spin(&mut self, iterations: usize) -> Result<T, DequeueError> {
+        match self.try_dequeue() {
+            Err(DequeueError::Empty) => {}
+            result => return result,
+        };
 
-                Some(data)
-                */
+        let backoff = Backoff::new();
+        for _ in 0..iterations {
+            if self.next_ready() {
+                match self.try_dequeue() {
+                    Err(DequeueError::Empty) => {}
+                    result => return result,
+                };
             }
-            _ => Err(DequeueError::Empty),
+            backoff.spin();
+        }
+
+        self.try_dequeue()
+    }
+
+    /// Cheaply checks if the next Dequeue could make Progress, meaning that
+    /// the Queue has been closed or the next Node is no longer empty
+    fn next_ready(&self) -> bool {
+        if self.is_closed() {
+            return true;
+        }
+
+        if is_zst::<T>() {
+            return self.tokens.load(atomic::Ordering::Acquire) > 0;
+        }
+
+        let current_queue = unsafe { &*self.head_of_queue };
+        match current_queue.buffer.get(self.head) {
+            Some(n) => n.get_state() != NodeState::Empty,
+            None => !current_queue.next.load(atomic::Ordering::Acquire).is_null(),
         }
     }
 
     /// This is a simple blocking dequeue. This is definetly not lock free
-    /// anymore and will simply spin and try to dequeue an item over and over
-    /// again.
+    /// anymore and will simply spin, with an exponential [`Backoff`], and try
+    /// to dequeue an item over and over again.
     ///
     /// # Behaviour
     /// This function will block until it either successfully dequeues an item
     /// from the Queue and will then return `Some(data)` or until the Queue has
     /// been closed by the other Side, in which case it will return `None`
+    ///
+    /// # Async
+    /// This blocks the current Thread and therefore should not be called from async
+    /// Code, use [`dequeue_cooperative`](Self::dequeue_cooperative) instead
     pub fn dequeue(&mut self) -> Option<T> {
+        let backoff = Backoff::new();
         loop {
             // Attempt to Dequeue an item
             match self.try_dequeue() {
@@ -468,15 +1101,153 @@ impl<T> Receiver<T> {
                 Err(e) => match e {
                     // If we had a simply error telling us that there is item
                     // in the Queue, we should simply continue
-                    DequeueError::Empty => {}
+                    DequeueError::Empty => backoff.snooze(),
                     // If the Queue has been closed, there is nothing we could
                     // retrieve in the Future and therefore we return None
-                    DequeueError::Closed => return None,
+                    DequeueError::Closed | DequeueError::Cancelled => return None,
                 },
             };
         }
     }
 
+    /// A blocking dequeue operation like [`dequeue`](Self::dequeue), which
+    /// can additionally be cancelled from another Thread using the given
+    /// [`CancelToken`], see the [`cancel`](crate::queues::cancel) module for
+    /// more Details
+    ///
+    /// # Returns
+    /// * `Ok(data)` once an Element could be dequeued
+    /// * `Err(DequeueError::Closed)` if the Queue has been closed
+    /// * `Err(DequeueError::Cancelled)` if the Token was cancelled while the
+    ///   Queue was empty
+    pub fn dequeue_cancellable(&mut self, token: &CancelToken) -> Result<T, DequeueError> {
+        cancel::dequeue(token, || self.try_dequeue())
+    }
+
+    /// The async-friendly Variant of [`dequeue`](Self::dequeue), which yields
+    /// back to the Executor in between Attempts instead of blocking the Thread,
+    /// see the [`cooperative`](crate::queues::cooperative) module for more
+    /// Details
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn dequeue_cooperative(
+        &mut self,
+    ) -> CooperativeDequeue<impl FnMut() -> Result<T, DequeueError> + '_> {
+        cooperative::dequeue(move || self.try_dequeue())
+    }
+
+    /// Dequeues all the Elements that are currently stored in the Queue and
+    /// collects them into a [`Snapshot`](crate::queues::snapshot::Snapshot),
+    /// without closing the Queue.
+    ///
+    /// Elements that are enqueued concurrently may or may not be part of the
+    /// Snapshot.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// let (mut rx, tx) = jiffy::queue::<usize>();
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// tx.enqueue(14).unwrap();
+    ///
+    /// assert_eq!(&[13, 14], rx.snapshot().as_slice());
+    /// assert!(rx.snapshot().is_empty());
+    /// ```
+    pub fn snapshot(&mut self) -> crate::queues::snapshot::Snapshot<T> {
+        crate::queues::snapshot::Snapshot::drain(|| self.try_dequeue())
+    }
+
+    /// Closes the Queue and returns all the Elements that are still left in
+    /// it.
+    ///
+    /// Once this returns, every attempt to enqueue more Data will fail with
+    /// [`EnqueueError::Closed`].
+    /// An Enqueue that is running concurrently with this either fails as
+    /// well and keeps its Element, or its Element is returned here, as this
+    /// waits for every Producer that already claimed a Node in the Queue.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// let (rx, tx) = jiffy::queue::<usize>();
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// tx.enqueue(14).unwrap();
+    ///
+    /// assert_eq!(vec![13, 14], rx.close_and_drain());
+    /// assert!(tx.is_closed());
+    /// ```
+    pub fn close_and_drain(mut self) -> Vec<T> {
+        self.closed_by_receiver = self
+            .closed
+            .compare_exchange(
+                false,
+                true,
+                atomic::Ordering::SeqCst,
+                atomic::Ordering::SeqCst,
+            )
+            .is_ok();
+
+        let mut result = Vec::new();
+        if is_zst::<T>() {
+            let count = self.tokens.swap(TOKENS_CLOSED, atomic::Ordering::AcqRel);
+            for _ in 0..count {
+                self.metrics.dequeue();
+                // Safety:
+                // Every Token was created by forgetting an Element
+                result.push(unsafe { recreate_zst() });
+            }
+            return result;
+        }
+
+        // Every Producer checks the Queue again after claiming its Location,
+        // so every Location it could still store its Element at comes
+        // before the current Tail
+        let end = self.tail.load(atomic::Ordering::SeqCst);
+        self.drain_until(end, &mut result);
+
+        result
+    }
+
+    /// Dequeues all the Elements before the given Location in Order, waiting
+    /// for the Producers that claimed one of the Nodes to either store their
+    /// Element or give up on it
+    fn drain_until(&mut self, end: usize, result: &mut Vec<T>) {
+        let backoff = Backoff::new();
+        loop {
+            if !self.move_to_next_buffer() {
+                // The current Buffer has been consumed, but a Producer may
+                // still be appending the next one
+                let current_queue = unsafe { &*self.head_of_queue };
+                if !is_before(buffer_end(current_queue.position_in_queue), end) {
+                    return;
+                }
+                backoff.snooze();
+                continue;
+            }
+
+            let current_queue = unsafe { &*self.head_of_queue };
+            if !is_before(Self::sequence(current_queue, self.head), end) {
+                return;
+            }
+
+            let n = &current_queue.buffer[self.head];
+            match n.get_state() {
+                NodeState::Handled => self.head += 1,
+                NodeState::Set => {
+                    let location = Location::new(current_queue.position_in_queue, self.head);
+                    let data = n
+                        .load(location)
+                        .expect("Only the Receiver loads the Data of a Set Node");
+                    result.push(self.dequeued_head(current_queue, data));
+                    backoff.reset();
+                }
+                NodeState::Empty => backoff.snooze(),
+            };
+        }
+    }
+
     /// Returns a RefIter for the Queue, this allows you to still use the
     /// Queue-Receiver once the Iterator has been dropped
     pub fn iter_mut<'queue, 'iter>(&'queue mut self) -> RefIter<'iter, T>
@@ -510,9 +1281,10 @@ impl<'queue, T> IntoIterator for &'queue mut Receiver<T> {
 }
 
 // These are both save to manually implement because we would garantuee that
-// they are save to share across threads, because the algorithm garantuees it
-unsafe impl<T> Send for Receiver<T> {}
-unsafe impl<T> Sync for Receiver<T> {}
+// they are save to share across threads, because the algorithm garantuees it.
+// However the Elements are still moved across Threads, so they need to be Send
+unsafe impl<T> Send for Receiver<T> where T: Send {}
+unsafe impl<T> Sync for Receiver<T> where T: Send {}
 
 impl<T> Debug for Receiver<T> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -522,7 +1294,13 @@ impl<T> Debug for Receiver<T> {
 
 impl<T> Drop for Receiver<T> {
     fn drop(&mut self) {
-        close_side(&self.closed, || {
+        // The Queue was already closed by `close_and_drain` and the Sender
+        // will clean up everything once it is dropped
+        if self.closed_by_receiver {
+            return;
+        }
+
+        close_side(&self.closed, &self.tokens, &self.shutdown, || {
             let mut current_ptr = self.head_of_queue;
             let mut current = unsafe { &*current_ptr };
 
@@ -539,33 +1317,209 @@ impl<T> Drop for Receiver<T> {
     }
 }
 
-/// Creates a new empty Queue and returns their ([`Receiver`], [`Sender`])
+/// Creates a new empty Queue and returns their ([`Receiver`], [`Sender`]),
+/// while [`channel`] returns them the other Way around
 pub fn queue<T>() -> (Receiver<T>, Sender<T>) {
-    let initial_buffer = BufferList::boxed(core::ptr::null(), 1);
-    let initial_ptr = Box::into_raw(initial_buffer);
+    queue_with_ordering(OrderingMode::default())
+}
 
-    let tail = atomic::AtomicUsize::new(0);
-    let tail_of_queue = atomic::AtomicPtr::new(initial_ptr);
+/// Creates a new empty Queue and returns the
+/// Halves as ([`Sender`], [`Receiver`]), in the same Order as
+/// `std::sync::mpsc::channel`
+///
+/// # Example
+/// ```
+/// # use nolock::queues::mpsc::jiffy;
+/// let (tx, mut rx) = jiffy::channel::<usize>();
+///
+/// tx.enqueue(13).unwrap();
+/// assert_eq!(Ok(13), rx.try_dequeue());
+/// ```
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let (rx, tx) = queue();
+    (tx, rx)
+}
+
+/// Creates a new empty Queue, that provides the given Ordering guarantees,
+/// and returns their ([`Receiver`], [`Sender`])
+///
+/// # Example
+/// ```
+/// # use nolock::queues::mpsc::jiffy;
+/// let (mut rx, tx) = jiffy::queue_with_ordering(jiffy::OrderingMode::StrictFifo);
+///
+/// tx.enqueue(13).unwrap();
+/// tx.enqueue(14).unwrap();
+///
+/// assert_eq!(Ok(13), rx.try_dequeue());
+/// assert_eq!(Ok(14), rx.try_dequeue());
+/// ```
+pub fn queue_with_ordering<T>(ordering: OrderingMode) -> (Receiver<T>, Sender<T>) {
+    queue_instrumented(ordering, Metrics::none())
+}
+
+/// Creates a new Queue, that already contains all the Elements from the
+/// Iterator in Order, and returns their ([`Receiver`], [`Sender`])
+///
+/// The Elements are written directly into the Buffers of the Queue, as no
+/// other Thread can access it yet, which is considerably faster than
+/// enqueueing them one by one, for example when restoring a Queue on Startup.
+///
+/// # Example
+/// ```
+/// # use nolock::queues::mpsc::jiffy;
+/// let (mut rx, tx) = jiffy::queue_from_iter(0..4096);
+///
+/// tx.enqueue(4096).unwrap();
+/// for i in 0..=4096 {
+///     assert_eq!(Ok(i), rx.try_dequeue());
+/// }
+/// ```
+pub fn queue_from_iter<T, I>(iter: I) -> (Receiver<T>, Sender<T>)
+where
+    I: IntoIterator<Item = T>,
+{
+    let (rx, mut tx) = queue();
+    tx.prefill(iter);
+    (rx, tx)
+}
+
+/// Creates a new empty Queue, that provides the given Ordering guarantees and
+/// reports all its Operations to the given
+/// [`QueueMetrics`](crate::queues::metrics::QueueMetrics)
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub fn queue_with_metrics<T>(
+    ordering: OrderingMode,
+    metrics: Arc<dyn crate::queues::metrics::QueueMetrics>,
+) -> (Receiver<T>, Sender<T>) {
+    queue_instrumented(ordering, Metrics::new(metrics))
+}
+
+/// Creates a new empty Queue, that provides the given Ordering guarantees
+/// and allocates the Buffers of its Segments using the given
+/// [`SegmentAlloc`], for example to place them on a specific NUMA-Node
+///
+/// # Example
+/// ```
+/// # use nolock::queues::mpsc::jiffy;
+/// # use std::sync::Arc;
+/// let alloc = Arc::new(std::alloc::System);
+/// let (mut rx, tx) = jiffy::queue_with_segment_alloc(jiffy::OrderingMode::default(), alloc);
+///
+/// tx.enqueue(13).unwrap();
+/// assert_eq!(Ok(13), rx.try_dequeue());
+/// ```
+pub fn queue_with_segment_alloc<T>(
+    ordering: OrderingMode,
+    alloc: Arc<dyn SegmentAlloc>,
+) -> (Receiver<T>, Sender<T>) {
+    queue_in(ordering, Metrics::none(), Segments::new(alloc))
+}
+
+pub(crate) fn queue_instrumented<T>(
+    ordering: OrderingMode,
+    metrics: Metrics,
+) -> (Receiver<T>, Sender<T>) {
+    queue_in(ordering, metrics, Segments::default())
+}
+
+fn queue_in<T>(
+    ordering: OrderingMode,
+    metrics: Metrics,
+    segments: Segments,
+) -> (Receiver<T>, Sender<T>) {
+    // Zero-sized Elements are only counted, so there is no need for any
+    // Buffers
+    let initial_ptr = if is_zst::<T>() {
+        core::ptr::null_mut()
+    } else {
+        Box::into_raw(BufferList::boxed(core::ptr::null(), 1, &segments))
+    };
+
+    let tail = Arc::new(atomic::AtomicUsize::new(0));
+    let tail_of_queue = atomic::AtomicPtr::new(initial_ptr);
 
     let closed = Arc::new(atomic::AtomicBool::new(false));
+    let tokens = Arc::new(atomic::AtomicUsize::new(0));
+    let cache = Arc::new(BufferCache::new(metrics.clone(), segments));
+    let shutdown = Arc::new(Shutdown::new());
+    #[cfg(feature = "async")]
+    let shared = Arc::new(async_queue::Shared::new());
 
     (
         Receiver {
             closed: closed.clone(),
             head_of_queue: initial_ptr,
+            head: 0,
+            tail: tail.clone(),
+            tokens: tokens.clone(),
+            cache: cache.clone(),
+            shutdown: shutdown.clone(),
+            ordering,
+            last_sequence: None,
+            closed_by_receiver: false,
+            metrics: metrics.clone(),
+            #[cfg(feature = "async")]
+            shared: shared.clone(),
         },
         Sender {
             closed,
             tail,
             tail_of_queue,
+            tokens,
+            cache,
+            shutdown,
+            metrics,
+            #[cfg(feature = "async")]
+            shared,
         },
     )
 }
 
+crate::queues::adapter::impl_receiver!(Receiver<T>);
+crate::queues::dynamic::impl_sender!(Sender<T>);
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Counts the Segments that are currently allocated
+    #[derive(Default)]
+    struct CountingSegments(atomic::AtomicUsize);
+
+    unsafe impl SegmentAlloc for CountingSegments {
+        unsafe fn alloc_segment(&self, layout: core::alloc::Layout) -> *mut u8 {
+            self.0.fetch_add(1, atomic::Ordering::SeqCst);
+            unsafe { std::alloc::System.alloc_segment(layout) }
+        }
+
+        unsafe fn dealloc_segment(&self, ptr: *mut u8, layout: core::alloc::Layout) {
+            self.0.fetch_sub(1, atomic::Ordering::SeqCst);
+            unsafe { std::alloc::System.dealloc_segment(ptr, layout) }
+        }
+    }
+
+    #[test]
+    fn segment_alloc_used() {
+        let counting = Arc::new(CountingSegments::default());
+        let (mut rx, tx) = queue_with_segment_alloc(OrderingMode::default(), counting.clone());
+        assert_eq!(1, counting.0.load(atomic::Ordering::SeqCst));
+
+        for i in 0..(3 * BUFFER_SIZE) {
+            tx.enqueue(i).unwrap();
+        }
+        assert!(counting.0.load(atomic::Ordering::SeqCst) >= 3);
+
+        for i in 0..(3 * BUFFER_SIZE) {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+
+        drop(rx);
+        drop(tx);
+        assert_eq!(0, counting.0.load(atomic::Ordering::SeqCst));
+    }
+
     #[test]
     fn dequeue_empty() {
         let (mut rx, tx) = queue::<u8>();
@@ -603,6 +1557,87 @@ mod tests {
         }
     }
 
+    #[test]
+    fn prefilled() {
+        let (mut rx, tx) = queue_from_iter(0..(2 * BUFFER_SIZE + 3));
+
+        tx.enqueue(2 * BUFFER_SIZE + 3).unwrap();
+        for i in 0..(2 * BUFFER_SIZE + 4) {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+
+    #[test]
+    fn prefilled_dropped() {
+        let value = Arc::new(13);
+
+        let (rx, tx) = queue_from_iter((0..(BUFFER_SIZE + 1)).map(|_| value.clone()));
+        drop(rx);
+        drop(tx);
+
+        assert_eq!(1, Arc::strong_count(&value));
+    }
+
+    #[test]
+    fn prefilled_zst() {
+        let (mut rx, tx) = queue_from_iter(core::iter::repeat_n((), 3));
+
+        tx.enqueue(()).unwrap();
+        for _ in 0..4 {
+            assert_eq!(Ok(()), rx.try_dequeue());
+        }
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+
+    #[test]
+    fn enqueue_no_alloc() {
+        let (mut rx, tx) = queue();
+
+        // Only the initial Buffer exists, as the no-alloc Path never
+        // allocates the next Buffer ahead of time
+        for i in 0..BUFFER_SIZE {
+            tx.try_enqueue_no_alloc(i).unwrap();
+        }
+        assert_eq!(
+            Err((BUFFER_SIZE, EnqueueError::NoSpace)),
+            tx.try_enqueue_no_alloc(BUFFER_SIZE)
+        );
+
+        // A normal Enqueue appends a new Buffer, which can then be used
+        tx.enqueue(BUFFER_SIZE).unwrap();
+        tx.try_enqueue_no_alloc(BUFFER_SIZE + 1).unwrap();
+
+        for i in 0..(BUFFER_SIZE + 2) {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+
+    #[test]
+    fn enqueue_no_alloc_closed() {
+        let (rx, tx) = queue::<usize>();
+        drop(rx);
+
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.try_enqueue_no_alloc(13));
+    }
+
+    #[test]
+    fn enqueue_fallible() {
+        let (mut rx, tx) = queue::<usize>();
+
+        for i in 0..(BUFFER_SIZE * 3) {
+            tx.enqueue_fallible(i).unwrap();
+        }
+        for i in 0..(BUFFER_SIZE * 3) {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+
+        drop(rx);
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue_fallible(13));
+    }
+
     #[test]
     fn fill_mulitple_buffers() {
         let (mut rx, tx) = queue();
@@ -635,6 +1670,173 @@ mod tests {
 
         assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
     }
+    #[test]
+    fn close_and_drain() {
+        let (rx, tx) = queue::<usize>();
+
+        for i in 0..(BUFFER_SIZE + 10) {
+            tx.enqueue(i).unwrap();
+        }
+
+        let drained = rx.close_and_drain();
+        assert_eq!((0..(BUFFER_SIZE + 10)).collect::<Vec<_>>(), drained);
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue(13));
+    }
+    #[test]
+    fn close_and_drain_sender_dropped() {
+        let (rx, tx) = queue::<usize>();
+
+        tx.enqueue(13).unwrap();
+        drop(tx);
+
+        assert_eq!(alloc::vec![13], rx.close_and_drain());
+    }
+    #[test]
+    fn shutdown_report() {
+        let (mut rx, tx) = queue::<usize>();
+        let report = rx.shutdown_report();
+
+        for i in 0..(BUFFER_SIZE + 2) {
+            tx.enqueue(i).unwrap();
+        }
+        assert_eq!(Ok(0), rx.try_dequeue());
+
+        drop(rx);
+        assert_eq!(None, report.closed_with_remaining());
+        drop(tx);
+        assert_eq!(Some(BUFFER_SIZE + 1), report.closed_with_remaining());
+
+        // Elements returned by close_and_drain are not lost
+        let (rx, tx) = queue::<usize>();
+        let report = tx.shutdown_report();
+        tx.enqueue(13).unwrap();
+        assert_eq!(alloc::vec![13], rx.close_and_drain());
+        drop(tx);
+        assert_eq!(Some(0), report.closed_with_remaining());
+    }
+    #[test]
+    fn close_and_drain_zst() {
+        let (rx, tx) = queue::<()>();
+        let report = tx.shutdown_report();
+
+        tx.enqueue(()).unwrap();
+        tx.enqueue(()).unwrap();
+
+        assert_eq!(2, rx.close_and_drain().len());
+        assert_eq!(Err(((), EnqueueError::Closed)), tx.enqueue(()));
+
+        drop(tx);
+        assert_eq!(Some(0), report.closed_with_remaining());
+    }
+    #[test]
+    fn close_and_drain_abandoned() {
+        let (rx, tx) = queue::<usize>();
+        tx.enqueue(13).unwrap();
+
+        // Claim a Location, like a concurrent Producer would, which then
+        // observes that the Queue was closed and gives up on its Node
+        let pending = tx.tail.fetch_add(1, atomic::Ordering::SeqCst);
+        tx.closed.store(true, atomic::Ordering::SeqCst);
+        let tail_ptr = tx.tail_of_queue.load(atomic::Ordering::SeqCst);
+        tx.abandon_at(pending, tail_ptr);
+        tx.closed.store(false, atomic::Ordering::SeqCst);
+
+        tx.enqueue(14).unwrap();
+        assert_eq!(alloc::vec![13, 14], rx.close_and_drain());
+    }
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn close_and_drain_concurrent() {
+        for ordering in [OrderingMode::Relaxed, OrderingMode::StrictFifo] {
+            for _ in 0..10 {
+                let (mut rx, tx) = queue_with_ordering::<usize>(ordering);
+                let tx = Arc::new(tx);
+
+                let producers: Vec<_> = (0..2)
+                    .map(|_| {
+                        let tx = tx.clone();
+                        std::thread::spawn(move || {
+                            let mut enqueued = 0;
+                            while tx.enqueue(enqueued).is_ok() {
+                                enqueued += 1;
+                            }
+                            enqueued
+                        })
+                    })
+                    .collect();
+
+                let mut dequeued = 0;
+                for _ in 0..(BUFFER_SIZE * 2) {
+                    if rx.try_dequeue().is_ok() {
+                        dequeued += 1;
+                    }
+                }
+                dequeued += rx.close_and_drain().len();
+
+                let enqueued: usize = producers.into_iter().map(|p| p.join().unwrap()).sum();
+                assert_eq!(enqueued, dequeued);
+            }
+        }
+    }
+    #[test]
+    fn shutdown_report_zst() {
+        let (rx, tx) = queue::<()>();
+        let report = tx.shutdown_report();
+
+        for _ in 0..3 {
+            tx.enqueue(()).unwrap();
+        }
+
+        drop(tx);
+        drop(rx);
+        assert_eq!(Some(3), report.closed_with_remaining());
+    }
+    #[test]
+    fn zst_tokens() {
+        let (mut rx, tx) = queue::<()>();
+        assert!(rx.head_of_queue.is_null());
+
+        for i in 0..(BUFFER_SIZE + 2) {
+            assert_eq!(Ok(i), tx.enqueue_with_sequence(()));
+        }
+        for i in 0..(BUFFER_SIZE + 2) {
+            assert_eq!(Ok(()), rx.try_dequeue());
+            assert_eq!(Some(i), rx.last_sequence());
+        }
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+
+        tx.enqueue(()).unwrap();
+        drop(tx);
+        assert_eq!(Ok(()), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+    }
+
+    #[test]
+    fn zst_dropped_with_queue() {
+        static DROPPED: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+
+        #[derive(Debug)]
+        struct Token;
+        impl Drop for Token {
+            fn drop(&mut self) {
+                DROPPED.fetch_add(1, atomic::Ordering::SeqCst);
+            }
+        }
+
+        let (mut rx, tx) = queue::<Token>();
+        for _ in 0..3 {
+            tx.enqueue(Token).unwrap();
+        }
+        assert_eq!(0, DROPPED.load(atomic::Ordering::SeqCst));
+
+        assert!(rx.try_dequeue().is_ok());
+        assert_eq!(1, DROPPED.load(atomic::Ordering::SeqCst));
+
+        drop(rx);
+        drop(tx);
+        assert_eq!(3, DROPPED.load(atomic::Ordering::SeqCst));
+    }
+
     #[test]
     fn enqueue_dequeue_closed() {
         let (mut rx, tx) = queue::<usize>();
@@ -658,6 +1860,227 @@ mod tests {
         drop(rx);
     }
 
+    #[test]
+    fn strict_fifo_waits_for_pending() {
+        let (mut rx, tx) = queue_with_ordering::<usize>(OrderingMode::StrictFifo);
+
+        // Simulate a Producer that obtained the first Slot but has not yet
+        // stored its Data
+        tx.tail.fetch_add(1, atomic::Ordering::SeqCst);
+        tx.enqueue(14).unwrap();
+
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+
+        let buffer = unsafe { &*rx.head_of_queue };
+        buffer.buffer[0].store(13, Location::new(buffer.position_in_queue, 0));
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Ok(14), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+    #[test]
+    fn strict_fifo_empty() {
+        let (mut rx, tx) = queue_with_ordering::<usize>(OrderingMode::StrictFifo);
+
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+
+        tx.enqueue(13).unwrap();
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+    #[test]
+    fn relaxed_skips_pending() {
+        let (mut rx, tx) = queue_with_ordering::<usize>(OrderingMode::Relaxed);
+
+        tx.tail.fetch_add(1, atomic::Ordering::SeqCst);
+        tx.enqueue(14).unwrap();
+
+        assert_eq!(Ok(14), rx.try_dequeue());
+
+        let buffer = unsafe { &*rx.head_of_queue };
+        buffer.buffer[0].store(13, Location::new(buffer.position_in_queue, 0));
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+    }
+    #[test]
+    fn strict_fifo_closed() {
+        let (mut rx, tx) = queue_with_ordering::<usize>(OrderingMode::StrictFifo);
+
+        tx.enqueue(13).unwrap();
+        drop(tx);
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+    }
+
+    #[test]
+    fn sequence_across_buffers() {
+        let (mut rx, tx) = queue();
+
+        let elements = BUFFER_SIZE * 3;
+        for i in 0..elements {
+            assert_eq!(Ok(i), tx.enqueue_with_sequence(i));
+        }
+        for i in 0..elements {
+            assert_eq!(Ok(i), rx.try_dequeue());
+            assert_eq!(Some(i), rx.last_sequence());
+        }
+    }
+    #[test]
+    fn sequence_skipped_pending() {
+        let (mut rx, tx) = queue::<usize>();
+
+        tx.tail.fetch_add(1, atomic::Ordering::SeqCst);
+        tx.enqueue(14).unwrap();
+
+        assert_eq!(Ok(14), rx.try_dequeue());
+        assert_eq!(Some(1), rx.last_sequence());
+
+        let buffer = unsafe { &*rx.head_of_queue };
+        buffer.buffer[0].store(13, Location::new(buffer.position_in_queue, 0));
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Some(0), rx.last_sequence());
+    }
+
+    /// Creates a Queue whose next Location is the given one, which needs to
+    /// be the Start of a Buffer
+    fn queue_starting_at(location: usize) -> (Receiver<usize>, Sender<usize>) {
+        assert_eq!(0, location % BUFFER_SIZE);
+
+        let (rx, tx) = queue::<usize>();
+        let buffer_ptr = tx.tail_of_queue.load(atomic::Ordering::SeqCst);
+        unsafe { (*buffer_ptr).position_in_queue = location / BUFFER_SIZE + 1 };
+        tx.tail.store(location, atomic::Ordering::SeqCst);
+
+        (rx, tx)
+    }
+
+    #[test]
+    fn location_wraparound() {
+        let start = 0usize.wrapping_sub(2 * BUFFER_SIZE);
+        let (mut rx, tx) = queue_starting_at(start);
+
+        let elements = BUFFER_SIZE * 4;
+        for i in 0..elements {
+            assert_eq!(Ok(start.wrapping_add(i)), tx.enqueue_with_sequence(i));
+        }
+        for i in 0..elements {
+            assert_eq!(Ok(i), rx.try_dequeue());
+            assert_eq!(Some(start.wrapping_add(i)), rx.last_sequence());
+        }
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+    #[test]
+    fn location_wraparound_fallible() {
+        let start = 0usize.wrapping_sub(BUFFER_SIZE);
+        let (mut rx, tx) = queue_starting_at(start);
+
+        for i in 0..(BUFFER_SIZE * 3) {
+            tx.enqueue_fallible(i).unwrap();
+        }
+        for i in 0..(BUFFER_SIZE * 3) {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+    }
+    #[test]
+    fn location_wraparound_pending() {
+        let start = 0usize.wrapping_sub(BUFFER_SIZE);
+        let (mut rx, tx) = queue_starting_at(start);
+
+        for i in 0..(BUFFER_SIZE - 1) {
+            tx.enqueue(i).unwrap();
+        }
+
+        // Claim the last Location before the Wraparound, but only store the
+        // Data once the Tail has moved past the Wraparound
+        let pending = tx.tail.fetch_add(1, atomic::Ordering::SeqCst);
+        assert_eq!(usize::MAX, pending);
+        for i in 0..(BUFFER_SIZE + 2) {
+            tx.enqueue(i).unwrap();
+        }
+
+        for i in 0..(BUFFER_SIZE - 1) {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+
+        let tail_ptr = tx.tail_of_queue.load(atomic::Ordering::SeqCst);
+        tx.store_at(pending, tail_ptr, 13, false);
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Some(pending), rx.last_sequence());
+        for i in 0..(BUFFER_SIZE + 2) {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+    }
+    #[test]
+    #[cfg(target_pointer_width = "32")]
+    #[ignore = "Enqueues more than usize::MAX Elements"]
+    fn location_wraparound_long_running() {
+        let (mut rx, tx) = queue::<usize>();
+
+        let elements = usize::MAX as u64 + 1 + 4 * BUFFER_SIZE as u64;
+        for i in 0..elements {
+            let expected = i as usize;
+            assert_eq!(Ok(expected), tx.enqueue_with_sequence(expected));
+            assert_eq!(Ok(expected), rx.try_dequeue());
+            assert_eq!(Some(expected), rx.last_sequence());
+        }
+    }
+
+    #[test]
+    fn try_dequeue_spin_closed() {
+        let (mut rx, tx) = queue::<usize>();
+
+        tx.enqueue(13).unwrap();
+        drop(tx);
+
+        assert_eq!(Ok(13), rx.try_dequeue_spin(4));
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue_spin(4));
+    }
+    #[test]
+    fn try_dequeue_spin_buffer_end() {
+        let (mut rx, tx) = queue::<usize>();
+
+        for i in 0..BUFFER_SIZE {
+            tx.enqueue(i).unwrap();
+        }
+        for i in 0..BUFFER_SIZE {
+            assert_eq!(Ok(i), rx.try_dequeue_spin(0));
+        }
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue_spin(4));
+
+        tx.enqueue(BUFFER_SIZE).unwrap();
+        assert!(rx.next_ready());
+        assert_eq!(Ok(BUFFER_SIZE), rx.try_dequeue_spin(4));
+    }
+    #[test]
+    #[cfg(feature = "std")]
+    fn try_dequeue_spin_concurrent() {
+        let (mut rx, tx) = queue::<usize>();
+
+        let producer = std::thread::spawn(move || {
+            for i in 0..1000 {
+                tx.enqueue(i).unwrap();
+            }
+        });
+
+        let mut expected = 0;
+        loop {
+            match rx.try_dequeue_spin(64) {
+                Ok(value) => {
+                    assert_eq!(expected, value);
+                    expected += 1;
+                }
+                Err(DequeueError::Empty) => {}
+                Err(DequeueError::Closed | DequeueError::Cancelled) => break,
+            };
+        }
+        assert_eq!(1000, expected);
+
+        producer.join().unwrap();
+    }
+
     #[test]
     fn iter_mut() {
         let (mut rx, tx) = queue::<usize>();
@@ -671,4 +2094,25 @@ mod tests {
 
         assert!(rx.is_closed());
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn dequeue_cancellable_other_thread() {
+        let (mut rx, tx) = queue::<usize>();
+        let token = Arc::new(CancelToken::new());
+
+        let handle = {
+            let token = token.clone();
+            std::thread::spawn(move || (rx.dequeue_cancellable(&token), rx))
+        };
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        token.cancel();
+        let (result, mut rx) = handle.join().unwrap();
+        assert_eq!(Err(DequeueError::Cancelled), result);
+
+        // The Queue is still usable after the Cancellation
+        tx.enqueue(13).unwrap();
+        assert_eq!(Ok(13), rx.try_dequeue());
+    }
 }
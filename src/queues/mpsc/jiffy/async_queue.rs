@@ -1,29 +1,113 @@
-use alloc::sync::Arc;
+use crate::utils::AtomicWaker;
 use core::{fmt::Debug, future::Future, task::Poll};
-use futures::task::AtomicWaker;
 
-use crate::queues::{DequeueError, EnqueueError};
+use crate::queues::{
+    batch::{DequeueMany, PendingDequeue},
+    convert::SyncPeers,
+    timeout::DequeueTimeout,
+    DequeueError, EnqueueError,
+};
 
-use super::{queue, Receiver, Sender};
+use super::{queue_with_ordering, OrderingMode, Receiver, Sender};
+
+/// The State shared between the Halves of a Queue, regardless of them being
+/// sync or async
+pub(crate) struct Shared {
+    /// The Waker to inform an async Receiver of any newly enqueued Items
+    pub(crate) waker: AtomicWaker,
+    /// The Number of sync Senders, which do not wake up the Receiver
+    senders: SyncPeers,
+}
+
+impl Shared {
+    /// Creates the State for a new Queue, whose Halves all start out sync
+    pub fn new() -> Self {
+        Self {
+            waker: AtomicWaker::new(),
+            senders: SyncPeers::new(1),
+        }
+    }
+}
 
 /// This is the asynchronous Version of the [`Jiffy-Receiver`](Receiver)
+///
+/// # Conversion
+/// A sync [`Receiver`] can be turned into an AsyncReceiver at any Time using
+/// [`Receiver::into_async`] and back using [`into_sync`](Self::into_sync).
+/// While the Sender is still sync, a waiting [`DequeueFuture`] can not be
+/// woken up by it and instead polls the Queue cooperatively, by yielding
+/// back to the Executor in every Poll.
 pub struct AsyncReceiver<T> {
-    /// The shared Waker to inform this receiver of any newly enqueued items
-    waker: Arc<AtomicWaker>,
     /// The actual underlying Queue
     queue: Receiver<T>,
 }
 
 /// This is the asynchronous Version of the [`Jiffy-Sender`](Sender)
+///
+/// # Closing
+/// Dropping the Sender closes the Queue and wakes up the Receiver, so that a
+/// pending [`DequeueFuture`] resolves to `Err(DequeueError::Closed)` once all
+/// the remaining Elements have been dequeued, instead of waiting forever
+///
+/// # Conversion
+/// A sync [`Sender`] can be turned into an AsyncSender at any Time using
+/// [`Sender::into_async`] and back using [`into_sync`](Self::into_sync)
 pub struct AsyncSender<T> {
-    /// The shared Waker to wake up the Receiver if it is still waiting for
-    /// an new Item to be enqueued
-    waker: Arc<AtomicWaker>,
     /// The actual underlying Queue
     queue: Sender<T>,
 }
 
+impl<T> Receiver<T> {
+    /// Converts this Receiver into an [`AsyncReceiver`] for the same Queue,
+    /// which keeps all the Elements currently in the Queue
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// async fn demo() {
+    ///   let (rx, tx) = jiffy::queue::<usize>();
+    ///   tx.enqueue(13).unwrap();
+    ///
+    ///   let (mut rx, tx) = (rx.into_async(), tx.into_async());
+    ///   tx.enqueue(14).unwrap();
+    ///
+    ///   assert_eq!(Ok(13), rx.dequeue().await);
+    ///   assert_eq!(Ok(14), rx.dequeue().await);
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn into_async(self) -> AsyncReceiver<T> {
+        AsyncReceiver { queue: self }
+    }
+}
+
+impl<T> Sender<T> {
+    /// Converts this Sender into an [`AsyncSender`] for the same Queue, which
+    /// wakes up the Receiver, once it is async, for every enqueued Item
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn into_async(self) -> AsyncSender<T> {
+        self.shared.senders.remove();
+        // The Receiver may currently be waiting cooperatively, so it needs to
+        // observe that it will now be woken up instead
+        self.shared.waker.wake();
+
+        AsyncSender { queue: self }
+    }
+}
+
 impl<T> AsyncReceiver<T> {
+    /// Converts this AsyncReceiver back into a sync [`Receiver`] for the same
+    /// Queue
+    pub fn into_sync(self) -> Receiver<T> {
+        self.queue
+    }
+
     /// Checks if the current Queue has been closed by the Producer
     ///
     /// # Note
@@ -45,6 +129,14 @@ impl<T> AsyncReceiver<T> {
         self.queue.try_dequeue()
     }
 
+    /// Returns the Sequence-Number of the last dequeued Element.
+    ///
+    /// This is the same as [`last_sequence`](Receiver::last_sequence) on the
+    /// normal Jiffy-Queue
+    pub fn last_sequence(&self) -> Option<usize> {
+        self.queue.last_sequence()
+    }
+
     /// This is the asynchronous version of the blocking
     /// [`dequeue`](Receiver::dequeue) operation on the normal Jiffy-Queue
     ///
@@ -74,10 +166,98 @@ impl<T> AsyncReceiver<T> {
     pub fn dequeue(&mut self) -> DequeueFuture<'_, T> {
         // Return the right DequeueFuture
         DequeueFuture {
-            waker: &self.waker,
             queue: &mut self.queue,
         }
     }
+
+    /// Polls for the next Item, which is the low-level Operation behind
+    /// [`dequeue`](Self::dequeue), for Executors or Combinators that drive
+    /// the Queue manually instead of awaiting a Future.
+    ///
+    /// Returns `Poll::Pending` if the Queue is currently empty, in which case
+    /// the Waker of the given Context is woken once an Item is enqueued or
+    /// the Queue is closed
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// # use std::task::{Context, Poll};
+    /// # use futures::task::noop_waker;
+    /// let (mut rx, tx) = jiffy::async_queue::<usize>();
+    /// let waker = noop_waker();
+    /// let mut cx = Context::from_waker(&waker);
+    ///
+    /// assert!(rx.poll_dequeue(&mut cx).is_pending());
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// assert_eq!(Poll::Ready(Ok(13)), rx.poll_dequeue(&mut cx));
+    /// ```
+    pub fn poll_dequeue(
+        &mut self,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Result<T, DequeueError>> {
+        core::pin::Pin::new(&mut self.dequeue()).poll(cx)
+    }
+
+    /// Dequeues the next Item, just like [`dequeue`](Self::dequeue), but
+    /// stops waiting once the given `sleep` Future resolves, in which case
+    /// `Err(DequeueError::Empty)` is returned.
+    ///
+    /// The `sleep` Future can come from any Runtime, see the
+    /// [`timeout`](crate::queues::timeout) module for more Details
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// # use std::time::Duration;
+    /// async fn demo() {
+    ///   let (mut rx, tx) = jiffy::async_queue::<usize>();
+    ///
+    ///   tx.enqueue(13).unwrap();
+    ///
+    ///   let sleep = tokio::time::sleep(Duration::from_millis(10));
+    ///   assert_eq!(Ok(13), rx.dequeue_timeout(sleep).await);
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    pub fn dequeue_timeout<S>(&mut self, sleep: S) -> DequeueTimeout<DequeueFuture<'_, T>, S>
+    where
+        S: Future,
+    {
+        DequeueTimeout::new(self.dequeue(), sleep)
+    }
+
+    /// Waits for the next Item, just like [`dequeue`](Self::dequeue), and
+    /// then also takes all the Items that are already in the Queue, until
+    /// `max` Items have been dequeued, see the
+    /// [`batch`](crate::queues::batch) module for more Details
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy;
+    /// async fn demo() {
+    ///   let (mut rx, tx) = jiffy::async_queue::<usize>();
+    ///
+    ///   tx.enqueue(13).unwrap();
+    ///   tx.enqueue(14).unwrap();
+    ///
+    ///   assert_eq!(Ok(vec![13, 14]), rx.dequeue_many(8).await);
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    pub fn dequeue_many(&mut self, max: usize) -> DequeueMany<DequeueFuture<'_, T>> {
+        DequeueMany::new(self.dequeue(), max)
+    }
 }
 
 impl<T> Debug for AsyncReceiver<T> {
@@ -94,11 +274,15 @@ impl<T> Debug for AsyncReceiver<T> {
 /// from the Queue, which will then return `Ok(data)`, or if it encountered a
 /// "fatal" [`DequeueError`], like when the Queue has been closed, which will
 /// then resolve to `Err(DequeueError)`.
+///
+/// # Cancel Safety
+/// This Future is cancel safe. It borrows the only Receiver and leaves the
+/// Queue untouched unless it resolves with an Element, and a dropped Future
+/// only leaves behind its Waker, which is replaced by the next Dequeue.
 pub struct DequeueFuture<'queue, T> {
-    /// This is the Waker on which we will be notified in case the Sender will
-    /// enqueue a new Item in the Queue
-    waker: &'queue AtomicWaker,
-    /// The actual underlying Queue from which we will dequeue the Item
+    /// The actual underlying Queue from which we will dequeue the Item, which
+    /// also holds the Waker on which we will be notified in case the Sender
+    /// will enqueue a new Item in the Queue
     queue: &'queue mut Receiver<T>,
 }
 
@@ -109,6 +293,9 @@ impl<'queue, T> Future for DequeueFuture<'queue, T> {
         mut self: core::pin::Pin<&mut Self>,
         cx: &mut core::task::Context<'_>,
     ) -> core::task::Poll<Self::Output> {
+        // A sync Sender will not wake us up, so we have to keep polling
+        let cooperative = self.queue.shared.senders.any();
+
         // Attempt to Dequeue an Item
         match self.queue.try_dequeue() {
             // If it worked, simply return Ready with the Data as the Result
@@ -118,17 +305,29 @@ impl<'queue, T> Future for DequeueFuture<'queue, T> {
                 DequeueError::Empty => {
                     // Update the shared Waker with the right Waker for the current
                     // Task
-                    self.waker.register(cx.waker());
+                    self.queue.shared.waker.register(cx.waker());
+
+                    if cooperative || self.queue.shared.senders.any() {
+                        cx.waker().wake_by_ref();
+                    }
 
                     // Indicate the we are still waiting for data
                     Poll::Pending
                 }
-                DequeueError::Closed => Poll::Ready(Err(DequeueError::Closed)),
+                DequeueError::Closed | DequeueError::Cancelled => Poll::Ready(Err(e)),
             },
         }
     }
 }
 
+impl<'queue, T> PendingDequeue for DequeueFuture<'queue, T> {
+    type Item = T;
+
+    fn try_dequeue_next(&mut self) -> Result<T, DequeueError> {
+        self.queue.try_dequeue()
+    }
+}
+
 impl<'queue, T> Debug for DequeueFuture<'queue, T> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "Async-Dequeue-Operation ()")
@@ -136,12 +335,26 @@ impl<'queue, T> Debug for DequeueFuture<'queue, T> {
 }
 
 impl<T> AsyncSender<T> {
+    /// Converts this AsyncSender back into a sync [`Sender`] for the same
+    /// Queue, after which a waiting async Receiver has to poll the Queue
+    /// cooperatively
+    pub fn into_sync(self) -> Sender<T> {
+        let queue = self.queue;
+
+        queue.shared.senders.add();
+        // The Receiver may currently be waiting for us to wake it up
+        queue.shared.waker.wake();
+
+        queue
+    }
+
     /// Checks if the Queue has been closed by the Consumer
     pub fn is_closed(&self) -> bool {
         self.queue.is_closed()
     }
 
-    /// Enqueues the given Data
+    /// Enqueues the given Data, which completes immediately as the Queue is
+    /// unbounded, and gives the Data back if the Receiver has been dropped
     ///
     /// # Example:
     /// ```
@@ -167,9 +380,22 @@ impl<T> AsyncSender<T> {
         self.queue.enqueue(data)?;
 
         // Notify the Receiver about new Data
-        self.waker.wake();
+        self.queue.shared.waker.wake();
         Ok(())
     }
+
+    /// Enqueues the given Data and returns the Sequence-Number assigned to
+    /// it.
+    ///
+    /// This is the same as [`enqueue_with_sequence`](Sender::enqueue_with_sequence)
+    /// on the normal Jiffy-Queue
+    pub fn enqueue_with_sequence(&self, data: T) -> Result<usize, (T, EnqueueError)> {
+        let sequence = self.queue.enqueue_with_sequence(data)?;
+
+        // Notify the Receiver about new Data
+        self.queue.shared.waker.wake();
+        Ok(sequence)
+    }
 }
 
 impl<T> Debug for AsyncSender<T> {
@@ -180,18 +406,26 @@ impl<T> Debug for AsyncSender<T> {
 
 /// Creates an async Jiffy-Queue Pair of ([`AsyncReceiver`], [`AsyncSender`])
 pub fn async_queue<T>() -> (AsyncReceiver<T>, AsyncSender<T>) {
-    let (u_rx, u_tx) = queue();
-    let waker = Arc::new(AtomicWaker::new());
+    async_queue_with_ordering(OrderingMode::default())
+}
+
+/// Creates a new async Jiffy-Queue, that provides the given Ordering
+/// guarantees
+pub fn async_queue_with_ordering<T>(ordering: OrderingMode) -> (AsyncReceiver<T>, AsyncSender<T>) {
+    let (u_rx, u_tx) = queue_with_ordering(ordering);
+    wrap_queue(u_rx, u_tx)
+}
 
-    (
-        AsyncReceiver {
-            waker: waker.clone(),
-            queue: u_rx,
-        },
-        AsyncSender { waker, queue: u_tx },
-    )
+/// Wraps the given sync Queue Halves into their async Variants
+pub(crate) fn wrap_queue<T>(
+    u_rx: Receiver<T>,
+    u_tx: Sender<T>,
+) -> (AsyncReceiver<T>, AsyncSender<T>) {
+    (u_rx.into_async(), u_tx.into_async())
 }
 
+crate::queues::adapter::impl_async_receiver!(AsyncReceiver<T>, DequeueFuture);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -204,4 +438,97 @@ mod tests {
         tx.enqueue(13).unwrap();
         assert_eq!(Ok(13), rx.dequeue().await);
     }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn sender_drop_wakes_receiver() {
+        let (mut rx, tx) = async_queue::<usize>();
+
+        let handle = tokio::spawn(async move { rx.dequeue().await });
+
+        // Let the Receiver register its Waker, before closing the Queue
+        tokio::task::yield_now().await;
+        drop(tx);
+
+        let result = tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+            .await
+            .expect("The Receiver was not woken up")
+            .unwrap();
+        assert_eq!(Err(DequeueError::Closed), result);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn dequeue_many_waits_for_first() {
+        let (mut rx, tx) = async_queue::<usize>();
+
+        let handle = tokio::spawn(async move {
+            let batch = rx.dequeue_many(16).await;
+            (rx, batch)
+        });
+
+        // Let the Receiver start waiting, before enqueuing anything
+        tokio::task::yield_now().await;
+        tx.enqueue(13).unwrap();
+
+        let (mut rx, batch) = handle.await.unwrap();
+        assert_eq!(Ok(vec![13]), batch);
+
+        for i in 0..4 {
+            tx.enqueue(i).unwrap();
+        }
+        drop(tx);
+        assert_eq!(Ok(vec![0, 1, 2, 3]), rx.dequeue_many(16).await);
+        assert_eq!(Err(DequeueError::Closed), rx.dequeue_many(16).await);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn sync_sender_async_receiver() {
+        let (rx, tx) = queue_with_ordering::<usize>(OrderingMode::default());
+        let mut rx = rx.into_async();
+
+        let handle = tokio::spawn(async move { rx.dequeue().await });
+
+        // Let the Receiver wait, before enqueueing without waking it up
+        tokio::task::yield_now().await;
+        tx.enqueue(13).unwrap();
+
+        let result = tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+            .await
+            .expect("The Receiver did not observe the Item")
+            .unwrap();
+        assert_eq!(Ok(13), result);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn convert_sender_while_waiting() {
+        let (mut rx, tx) = async_queue::<usize>();
+
+        let handle = tokio::spawn(async move {
+            let first = rx.dequeue().await;
+            let second = rx.dequeue().await;
+            (first, second, rx.into_sync())
+        });
+
+        tokio::task::yield_now().await;
+        let tx = tx.into_sync();
+        tx.enqueue(13).unwrap();
+
+        tokio::task::yield_now().await;
+        let tx = tx.into_async();
+        tx.enqueue(14).unwrap();
+
+        let (first, second, mut rx) =
+            tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+                .await
+                .expect("The Receiver did not observe the Items")
+                .unwrap();
+        assert_eq!(Ok(13), first);
+        assert_eq!(Ok(14), second);
+
+        drop(tx);
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+    }
 }
@@ -0,0 +1,687 @@
+//! A bounded Facade over the unbounded Jiffy-Queue, which provides
+//! Backpressure using Credits.
+//!
+//! The Queue starts out with one Credit per Element it can hold. Every
+//! Enqueue first takes a Credit, which is a single atomic Operation, and then
+//! uses the normal wait-free Enqueue of the underlying Queue. The Receiver
+//! returns the Credit once it dequeued the Element again. If there are no
+//! Credits left, the Queue is considered full.
+//!
+//! With the `async` Feature enabled, Producers can also wait for a Credit to
+//! become available using [`Sender::acquire`], or wait and enqueue in one
+//! Step using [`Sender::send`].
+//!
+//! # Example
+//! ```
+//! use nolock::queues::mpsc::jiffy::bounded;
+//! use nolock::queues::EnqueueError;
+//!
+//! let (tx, mut rx) = bounded::channel::<usize>(2);
+//!
+//! tx.try_enqueue(1).unwrap();
+//! tx.try_enqueue(2).unwrap();
+//! assert_eq!(Err((3, EnqueueError::Full)), tx.try_enqueue(3));
+//!
+//! // Dequeuing an Element returns its Credit
+//! assert_eq!(Ok(1), rx.try_dequeue());
+//! tx.try_enqueue(3).unwrap();
+//! ```
+
+use alloc::sync::Arc;
+use core::{fmt::Debug, mem::ManuallyDrop};
+
+use crate::{
+    queues::{
+        cancel::{self, CancelToken},
+        DequeueError, EnqueueError,
+    },
+    sync::native::atomic,
+    utils::{Backoff, CachePadded},
+};
+
+#[cfg(feature = "async")]
+use crate::queues::mpmc::bounded::async_queue::waiters::{Waiter, WaiterQueue};
+#[cfg(feature = "async")]
+use core::{future::Future, pin::Pin, task::Poll};
+
+/// The Credits shared between the Sender and the Receiver
+struct Credits {
+    /// The Number of Elements that can still be enqueued
+    available: CachePadded<atomic::AtomicUsize>,
+    /// The maximum Number of Elements in the Queue
+    capacity: usize,
+    /// The Producers waiting for a Credit to become available
+    #[cfg(feature = "async")]
+    waiters: WaiterQueue,
+}
+
+impl Credits {
+    /// Attempts to take a single Credit
+    fn try_take(&self) -> bool {
+        let mut current = self.available.load(atomic::Ordering::Acquire);
+        loop {
+            if current == 0 {
+                return false;
+            }
+
+            match self.available.compare_exchange_weak(
+                current,
+                current - 1,
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+            ) {
+                Ok(_) => return true,
+                Err(c) => current = c,
+            };
+        }
+    }
+
+    /// Returns a single Credit and notifies a waiting Producer
+    fn release(&self) {
+        self.available.fetch_add(1, atomic::Ordering::AcqRel);
+
+        #[cfg(feature = "async")]
+        self.waiters.notify_one();
+    }
+}
+
+/// The Sending-Half of a credit-bounded Jiffy-Queue
+pub struct Sender<T> {
+    queue: super::Sender<T>,
+    credits: Arc<Credits>,
+}
+
+/// The Receiving-Half of a credit-bounded Jiffy-Queue
+pub struct Receiver<T> {
+    /// The underlying Queue, which is manually dropped to close the Queue
+    /// before waking up the waiting Producers
+    queue: ManuallyDrop<super::Receiver<T>>,
+    credits: Arc<Credits>,
+}
+
+/// A single Credit taken from the Queue, which allows enqueuing one Element
+/// without the Queue being full.
+///
+/// Dropping the Permit without using it returns the Credit to the Queue
+pub struct Permit<'queue, T> {
+    sender: &'queue Sender<T>,
+}
+
+/// Creates a new Queue, that can hold at most `capacity` Elements, and
+/// returns the Halves as ([`Sender`], [`Receiver`])
+///
+/// # Example
+/// ```
+/// use nolock::queues::mpsc::jiffy::bounded;
+///
+/// let (tx, mut rx) = bounded::channel::<usize>(16);
+/// assert_eq!(16, tx.capacity());
+///
+/// tx.try_enqueue(13).unwrap();
+/// assert_eq!(Ok(13), rx.try_dequeue());
+/// ```
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let (rx, tx) = super::queue();
+    let credits = Arc::new(Credits {
+        available: CachePadded::new(atomic::AtomicUsize::new(capacity)),
+        capacity,
+        #[cfg(feature = "async")]
+        waiters: WaiterQueue::new(),
+    });
+
+    (
+        Sender {
+            queue: tx,
+            credits: credits.clone(),
+        },
+        Receiver {
+            queue: ManuallyDrop::new(rx),
+            credits,
+        },
+    )
+}
+
+impl<T> Sender<T> {
+    /// Checks if the Receiver has been dropped
+    pub fn is_closed(&self) -> bool {
+        self.queue.is_closed()
+    }
+
+    /// The maximum Number of Elements in the Queue
+    pub fn capacity(&self) -> usize {
+        self.credits.capacity
+    }
+
+    /// The Number of Credits that are currently available, which is the
+    /// approximate Number of Elements that can still be enqueued
+    pub fn available(&self) -> usize {
+        self.credits.available.load(atomic::Ordering::Acquire)
+    }
+
+    /// Attempts to take a Credit, returns [`EnqueueError::Full`] if there
+    /// are none left or [`EnqueueError::Closed`] if the Receiver has been
+    /// dropped
+    ///
+    /// # Example
+    /// ```
+    /// use nolock::queues::mpsc::jiffy::bounded;
+    ///
+    /// let (tx, mut rx) = bounded::channel::<usize>(1);
+    ///
+    /// let permit = tx.try_acquire().unwrap();
+    /// assert!(tx.try_acquire().is_err());
+    ///
+    /// permit.enqueue(13).unwrap();
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    /// ```
+    pub fn try_acquire(&self) -> Result<Permit<'_, T>, EnqueueError> {
+        if self.is_closed() {
+            return Err(EnqueueError::Closed);
+        }
+        if !self.credits.try_take() {
+            return Err(EnqueueError::Full);
+        }
+
+        Ok(Permit { sender: self })
+    }
+
+    /// Enqueues the Data, if there is still a Credit available
+    pub fn try_enqueue(&self, data: T) -> Result<(), (T, EnqueueError)> {
+        match self.try_acquire() {
+            Ok(permit) => permit.enqueue(data),
+            Err(e) => Err((data, e)),
+        }
+    }
+
+    /// Waits until a Credit becomes available, which resolves to
+    /// [`EnqueueError::Closed`] if the Receiver is dropped in the meantime.
+    ///
+    /// # Cancel Safety
+    /// This Future is cancel safe, a Credit is only taken once the Future
+    /// resolves with the Permit for it
+    ///
+    /// # Example
+    /// ```
+    /// use nolock::queues::mpsc::jiffy::bounded;
+    ///
+    /// async fn demo() {
+    ///   let (tx, mut rx) = bounded::channel::<usize>(1);
+    ///
+    ///   tx.try_enqueue(1).unwrap();
+    ///   assert!(tx.try_acquire().is_err());
+    ///
+    ///   assert_eq!(Ok(1), rx.try_dequeue());
+    ///   let permit = tx.acquire().await.unwrap();
+    ///   permit.enqueue(2).unwrap();
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn acquire(&self) -> AcquireFuture<'_, T> {
+        AcquireFuture {
+            sender: self,
+            waiter: None,
+        }
+    }
+
+    /// Enqueues the Data, waiting for a Credit to become available if the
+    /// Queue is currently full, which resolves to `Err((data, EnqueueError::Closed))`
+    /// if the Receiver is dropped in the meantime.
+    ///
+    /// # Cancel Safety
+    /// No Credit is taken before the Future resolves, so dropping it never
+    /// shrinks the Queue, but the Data it still holds is dropped with it.
+    /// Use [`SendFuture::into_inner`] to get the Data back instead, for
+    /// example after losing a `select!`.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::jiffy::bounded;
+    /// # use std::task::{Context, Poll};
+    /// # use std::future::Future;
+    /// # use std::pin::Pin;
+    /// # use futures::task::noop_waker;
+    /// let (tx, mut rx) = bounded::channel::<usize>(1);
+    /// tx.try_enqueue(13).unwrap();
+    ///
+    /// // The Queue is full, so this can not make any progress
+    /// let mut send = tx.send(14);
+    /// let waker = noop_waker();
+    /// let mut cx = Context::from_waker(&waker);
+    /// assert!(Pin::new(&mut send).poll(&mut cx).is_pending());
+    ///
+    /// // Give up on sending, but keep the Data
+    /// assert_eq!(Some(14), send.into_inner());
+    /// # assert_eq!(Ok(13), rx.try_dequeue());
+    /// ```
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn send(&self, data: T) -> SendFuture<'_, T> {
+        SendFuture {
+            acquire: self.acquire(),
+            data: Some(data),
+        }
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Bounded-Jiffy-Sender ()")
+    }
+}
+
+impl<'queue, T> Permit<'queue, T> {
+    /// Enqueues the Data using the Credit of this Permit
+    pub fn enqueue(self, data: T) -> Result<(), (T, EnqueueError)> {
+        match self.sender.queue.enqueue(data) {
+            Ok(()) => {
+                // The Credit is now owned by the Element and returned by the
+                // Receiver, once it dequeues the Element
+                core::mem::forget(self);
+                Ok(())
+            }
+            // Dropping the Permit returns the Credit
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<'queue, T> Drop for Permit<'queue, T> {
+    fn drop(&mut self) {
+        self.sender.credits.release();
+    }
+}
+
+impl<'queue, T> Debug for Permit<'queue, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Permit ()")
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Checks if the Sender has been dropped
+    ///
+    /// # Note
+    /// There might still be Elements left in the Queue
+    pub fn is_closed(&self) -> bool {
+        self.queue.is_closed()
+    }
+
+    /// The maximum Number of Elements in the Queue
+    pub fn capacity(&self) -> usize {
+        self.credits.capacity
+    }
+
+    /// Attempts to dequeue the next Element and returns its Credit to the
+    /// Sender
+    pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        let data = self.queue.try_dequeue()?;
+        self.credits.release();
+
+        Ok(data)
+    }
+
+    /// Blocks until an Element can be dequeued or returns None, if the Queue
+    /// has been closed and there are no more Elements left.
+    ///
+    /// This is simply spinning and therefore not lock-free
+    pub fn dequeue(&mut self) -> Option<T> {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_dequeue() {
+                Ok(data) => return Some(data),
+                Err(DequeueError::Empty) => backoff.snooze(),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+            };
+        }
+    }
+
+    /// A blocking dequeue operation like [`dequeue`](Self::dequeue), which
+    /// can additionally be cancelled from another Thread using the given
+    /// [`CancelToken`], see the [`cancel`](crate::queues::cancel) module for
+    /// more Details
+    ///
+    /// # Returns
+    /// * `Ok(data)` once an Element could be dequeued
+    /// * `Err(DequeueError::Closed)` if the Queue has been closed
+    /// * `Err(DequeueError::Cancelled)` if the Token was cancelled while the
+    ///   Queue was empty
+    pub fn dequeue_cancellable(&mut self, token: &CancelToken) -> Result<T, DequeueError> {
+        cancel::dequeue(token, || self.try_dequeue())
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        // Safety:
+        // The Queue is never accessed again after this
+        unsafe { ManuallyDrop::drop(&mut self.queue) };
+
+        // The Queue is now marked as closed, so the waiting Producers will
+        // see that once they are woken up
+        #[cfg(feature = "async")]
+        self.credits.waiters.notify_all();
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Bounded-Jiffy-Receiver ()")
+    }
+}
+
+/// The Future returned by [`Sender::acquire`]
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct AcquireFuture<'queue, T> {
+    sender: &'queue Sender<T>,
+    /// Our Ticket in the Queue of waiting Producers
+    waiter: Option<Arc<Waiter>>,
+}
+
+#[cfg(feature = "async")]
+impl<'queue, T> Future for AcquireFuture<'queue, T> {
+    type Output = Result<Permit<'queue, T>, EnqueueError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        loop {
+            match this.sender.try_acquire() {
+                Err(EnqueueError::Full) => {}
+                result => {
+                    if let Some(waiter) = this.waiter.take() {
+                        waiter.cancel();
+                    }
+                    return Poll::Ready(result);
+                }
+            };
+
+            match this.waiter.as_ref() {
+                Some(waiter) if !waiter.is_notified() => {
+                    waiter.register(cx.waker());
+                    if !waiter.is_notified() {
+                        return Poll::Pending;
+                    }
+                }
+                // Either we are not waiting yet or we were notified, but the
+                // Credit was already taken by someone else
+                _ => {
+                    this.waiter = Some(this.sender.credits.waiters.register(cx.waker()));
+                }
+            };
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'queue, T> Drop for AcquireFuture<'queue, T> {
+    fn drop(&mut self) {
+        if let Some(waiter) = self.waiter.take() {
+            // Pass on a Notification that we did not use
+            if waiter.cancel() {
+                self.sender.credits.waiters.notify_one();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'queue, T> Debug for AcquireFuture<'queue, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Acquire-Operation ()")
+    }
+}
+
+/// The Future returned by [`Sender::send`]
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct SendFuture<'queue, T> {
+    /// Waits for the Credit that is used to enqueue the Data
+    acquire: AcquireFuture<'queue, T>,
+    data: Option<T>,
+}
+
+#[cfg(feature = "async")]
+impl<'queue, T> SendFuture<'queue, T> {
+    /// Consumes the Future and returns the Data, if it was not already
+    /// enqueued or returned as part of an Error
+    pub fn into_inner(mut self) -> Option<T> {
+        self.data.take()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'queue, T> Unpin for SendFuture<'queue, T> {}
+
+#[cfg(feature = "async")]
+impl<'queue, T> Future for SendFuture<'queue, T> {
+    type Output = Result<(), (T, EnqueueError)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        // The Future already resolved, so we must not take another Credit
+        if this.data.is_none() {
+            return Poll::Ready(Ok(()));
+        }
+
+        let result = match Pin::new(&mut this.acquire).poll(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        let data = this.data.take().expect("The Data was checked above");
+
+        match result {
+            Ok(permit) => Poll::Ready(permit.enqueue(data)),
+            Err(e) => Poll::Ready(Err((data, e))),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'queue, T> Debug for SendFuture<'queue, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Send-Operation ()")
+    }
+}
+
+crate::queues::adapter::impl_receiver!(Receiver<T>);
+
+impl<T> crate::queues::dynamic::DynSender<T> for Sender<T> {
+    fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        Sender::try_enqueue(self, data)
+    }
+
+    fn enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        crate::queues::dynamic::enqueue_spinning(|data| Sender::try_enqueue(self, data), data)
+    }
+
+    fn is_closed(&self) -> bool {
+        Sender::is_closed(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_and_release() {
+        let (tx, mut rx) = channel::<usize>(3);
+
+        for i in 0..3 {
+            tx.try_enqueue(i).unwrap();
+        }
+        assert_eq!(0, tx.available());
+        assert_eq!(Err((3, EnqueueError::Full)), tx.try_enqueue(3));
+
+        assert_eq!(Ok(0), rx.try_dequeue());
+        assert_eq!(1, tx.available());
+        tx.try_enqueue(3).unwrap();
+
+        for i in 1..4 {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+        assert_eq!(3, tx.available());
+    }
+
+    #[test]
+    fn unused_permit_returns_credit() {
+        let (tx, rx) = channel::<usize>(1);
+
+        let permit = tx.try_acquire().unwrap();
+        assert_eq!(0, tx.available());
+        drop(permit);
+        assert_eq!(1, tx.available());
+
+        drop(rx);
+    }
+
+    #[test]
+    fn closed() {
+        let (tx, rx) = channel::<usize>(1);
+
+        drop(rx);
+        assert!(tx.is_closed());
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.try_enqueue(13));
+        assert_eq!(1, tx.available());
+    }
+
+    #[test]
+    fn receiver_closed() {
+        let (tx, mut rx) = channel::<usize>(1);
+
+        tx.try_enqueue(13).unwrap();
+        drop(tx);
+
+        assert!(rx.is_closed());
+        assert_eq!(Some(13), rx.dequeue());
+        assert_eq!(None, rx.dequeue());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn bounded_concurrent() {
+        let (tx, mut rx) = channel::<usize>(4);
+
+        let producer = std::thread::spawn(move || {
+            for i in 0..1000 {
+                let mut data = i;
+                while let Err((d, _)) = tx.try_enqueue(data) {
+                    assert!(tx.available() <= 4);
+                    data = d;
+                    std::thread::yield_now();
+                }
+            }
+        });
+
+        for i in 0..1000 {
+            assert_eq!(Some(i), rx.dequeue());
+        }
+        assert_eq!(None, rx.dequeue());
+
+        producer.join().unwrap();
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    #[cfg_attr(miri, ignore)]
+    async fn acquire_waits_for_credit() {
+        let (tx, mut rx) = channel::<usize>(1);
+        let tx = Arc::new(tx);
+
+        tx.try_enqueue(1).unwrap();
+
+        let handle = {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let permit = tx.acquire().await.unwrap();
+                permit.enqueue(2).unwrap();
+            })
+        };
+
+        // Let the Producer start waiting for a Credit
+        tokio::task::yield_now().await;
+        assert_eq!(Ok(1), rx.try_dequeue());
+
+        tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+            .await
+            .expect("The Producer was not woken up")
+            .unwrap();
+        assert_eq!(Ok(2), rx.try_dequeue());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "async")]
+    #[cfg_attr(miri, ignore)]
+    async fn acquire_closed() {
+        let (tx, rx) = channel::<usize>(1);
+        let tx = Arc::new(tx);
+
+        tx.try_enqueue(1).unwrap();
+
+        let handle = {
+            let tx = tx.clone();
+            tokio::spawn(async move { tx.acquire().await.map(|_| ()) })
+        };
+
+        tokio::task::yield_now().await;
+        drop(rx);
+
+        let result = tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+            .await
+            .expect("The Producer was not woken up")
+            .unwrap();
+        assert_eq!(Err(EnqueueError::Closed), result);
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn send_into_inner() {
+        let (tx, mut rx) = channel::<usize>(1);
+        tx.try_enqueue(13).unwrap();
+
+        let waker = futures::task::noop_waker();
+        let mut cx = core::task::Context::from_waker(&waker);
+
+        let mut fut = tx.send(14);
+        assert!(Pin::new(&mut fut).poll(&mut cx).is_pending());
+        assert_eq!(Some(14), fut.into_inner());
+
+        // The abandoned Future did not take the Credit
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(1, tx.available());
+
+        let mut fut = tx.send(15);
+        assert_eq!(Poll::Ready(Ok(())), Pin::new(&mut fut).poll(&mut cx));
+        assert_eq!(Poll::Ready(Ok(())), Pin::new(&mut fut).poll(&mut cx));
+        assert_eq!(None, fut.into_inner());
+        assert_eq!(Ok(15), rx.try_dequeue());
+
+        let mut fut = tx.send(16);
+        drop(rx);
+        assert_eq!(
+            Poll::Ready(Err((16, EnqueueError::Closed))),
+            Pin::new(&mut fut).poll(&mut cx)
+        );
+    }
+
+    #[test]
+    fn dequeue_cancellable() {
+        let (tx, mut rx) = channel::<usize>(2);
+        let token = CancelToken::new();
+        token.cancel();
+
+        assert_eq!(Err(DequeueError::Cancelled), rx.dequeue_cancellable(&token));
+        tx.try_enqueue(13).unwrap();
+        assert_eq!(Ok(13), rx.dequeue_cancellable(&token));
+        assert_eq!(2, tx.available());
+    }
+}
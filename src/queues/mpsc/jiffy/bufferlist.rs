@@ -1,45 +1,189 @@
-use alloc::{boxed::Box, vec::Vec};
-use core::{fmt::Debug, sync::atomic};
+use alloc::boxed::Box;
+use core::fmt::Debug;
+
+use crate::sync::native::atomic;
 
 use super::{
     node::{Node, NodeState},
-    BUFFER_SIZE,
+    BUFFER_CACHE_SIZE, BUFFER_SIZE,
+};
+use crate::{
+    poison,
+    queues::{instrument::Metrics, mpmc::bounded::scq},
+    utils::{Segment, Segments},
 };
 
 /// A single Buffer
+///
+/// # Aliasing
+/// A BufferList is shared between all the Producers and the Receiver, which
+/// only ever access it through shared References. Every Field that is
+/// modified while the BufferList is shared therefore uses interior
+/// Mutability, so that no `&mut` Reference to a shared BufferList is ever
+/// created. The Index of the next Node to dequeue is only relevant to the
+/// Receiver and is therefore stored in the Receiver itself, instead of in
+/// the shared BufferList
 pub struct BufferList<T> {
-    /// The Previous Buffer in the List of buffers
-    pub previous: *const BufferList<T>,
+    /// The Previous Buffer in the List of buffers, which is reset by the
+    /// Receiver once it starts consuming this Buffer
+    previous: atomic::AtomicPtr<BufferList<T>>,
     /// The Next Buffer in the List of buffers
     pub next: atomic::AtomicPtr<BufferList<T>>,
-    /// The Buffer of nodes
-    pub buffer: Vec<Node<T>>,
-    /// The Last read value by the consumer
-    pub head: usize,
+    /// The Buffer of nodes, which is allocated using the SegmentAlloc of the
+    /// Queue
+    pub buffer: Segment<Node<T>>,
     /// The Position in the Overall List of Buffers,
     /// initialized to 1
     pub position_in_queue: usize,
 }
 
-impl<T> BufferList<T> {
-    /// Creates a new Boxed-BufferList
-    pub fn boxed(previous: *const Self, position_in_queue: usize) -> Box<Self> {
-        let buffer = {
-            let mut raw = Vec::with_capacity(BUFFER_SIZE);
-            for _ in 0..BUFFER_SIZE {
-                raw.push(Node::default());
+// Safety:
+// The BufferList only stores the Previous-Ptr as a raw Pointer, which is
+// never dereferenced without going through the Queue itself, so it is save
+// to send a BufferList across Threads as long as the Data is Send
+unsafe impl<T> Send for BufferList<T> where T: Send {}
+
+/// A Cache of BufferLists, that were fully consumed by the Receiver and can
+/// be reused by the Producers instead of allocating a new BufferList
+pub struct BufferCache<T> {
+    /// Used by the Producers to get a BufferList from the Cache
+    rx: scq::Receiver<Box<BufferList<T>>>,
+    /// Used by the Receiver to return a consumed BufferList to the Cache
+    tx: scq::Sender<Box<BufferList<T>>>,
+    /// The Hooks to report newly allocated BufferLists to
+    metrics: Metrics,
+    /// Used to allocate the Buffers of new BufferLists
+    segments: Segments,
+}
+
+impl<T> BufferCache<T> {
+    /// Creates a new empty Cache, which reports every BufferList it has to
+    /// allocate to the given Metrics and allocates their Buffers using the
+    /// given Segments
+    pub fn new(metrics: Metrics, segments: Segments) -> Self {
+        let (rx, tx) = scq::queue(BUFFER_CACHE_SIZE);
+        Self {
+            rx,
+            tx,
+            metrics,
+            segments,
+        }
+    }
+
+    /// Obtains a BufferList, for the given Position, from the Cache or
+    /// allocates a new one if the Cache is currently empty
+    pub fn get(
+        &self,
+        previous: *const BufferList<T>,
+        position_in_queue: usize,
+    ) -> Box<BufferList<T>> {
+        match self.rx.try_dequeue() {
+            Ok(mut buffer) => {
+                buffer.reset(previous, position_in_queue);
+                buffer
+            }
+            Err(_) => {
+                self.metrics.segment_alloc();
+                BufferList::boxed(previous, position_in_queue, &self.segments)
+            }
+        }
+    }
+
+    /// Obtains a BufferList, for the given Position, like [`get`](Self::get),
+    /// but returns `None` instead of aborting if a new BufferList has to be
+    /// allocated and the Allocation fails
+    pub fn try_get(
+        &self,
+        previous: *const BufferList<T>,
+        position_in_queue: usize,
+    ) -> Option<Box<BufferList<T>>> {
+        match self.rx.try_dequeue() {
+            Ok(mut buffer) => {
+                buffer.reset(previous, position_in_queue);
+                Some(buffer)
             }
+            Err(_) => {
+                let buffer = BufferList::try_boxed(previous, position_in_queue, &self.segments)?;
+                self.metrics.segment_alloc();
+                Some(buffer)
+            }
+        }
+    }
 
-            raw
-        };
+    /// Returns the given BufferList to the Cache, if the Cache is already
+    /// full the BufferList will simply be dropped
+    pub fn put(&self, buffer: Box<BufferList<T>>) {
+        if let Err((_, buffer)) = self.tx.try_enqueue(buffer) {
+            // # Safety:
+            // The Cache is full, so the BufferList is simply freed
+            unsafe { poison::drop_box(Box::into_raw(buffer)) };
+        }
+    }
+}
+
+impl<T> Debug for BufferCache<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "BufferCache ()")
+    }
+}
+
+impl<T> BufferList<T> {
+    /// Creates a new Boxed-BufferList, whose Buffer is allocated using the
+    /// given Segments
+    pub fn boxed(
+        previous: *const Self,
+        position_in_queue: usize,
+        segments: &Segments,
+    ) -> Box<Self> {
+        let buffer = Segment::new_with(segments, BUFFER_SIZE, Node::default);
 
         Box::new(Self {
-            previous,
+            previous: atomic::AtomicPtr::new(previous as *mut Self),
+            next: atomic::AtomicPtr::new(core::ptr::null_mut()),
+            buffer,
+            position_in_queue,
+        })
+    }
+
+    /// Creates a new Boxed-BufferList, like [`boxed`](Self::boxed), but
+    /// returns `None` instead of aborting if any of the Allocations fail
+    pub fn try_boxed(
+        previous: *const Self,
+        position_in_queue: usize,
+        segments: &Segments,
+    ) -> Option<Box<Self>> {
+        let buffer = Segment::try_new_with(segments, BUFFER_SIZE, Node::default)?;
+
+        crate::utils::try_box(Self {
+            previous: atomic::AtomicPtr::new(previous as *mut Self),
             next: atomic::AtomicPtr::new(core::ptr::null_mut()),
             buffer,
-            head: 0,
             position_in_queue,
         })
+        .ok()
+    }
+
+    /// Loads the Ptr to the previous BufferList
+    pub fn previous(&self) -> *mut Self {
+        self.previous.load(atomic::Ordering::Acquire)
+    }
+
+    /// Unlinks the previous BufferList, once it has been fully consumed
+    pub fn clear_previous(&self) {
+        self.previous
+            .store(core::ptr::null_mut(), atomic::Ordering::Release);
+    }
+
+    /// Resets the BufferList, so that it can be reused at the given new
+    /// Position in the Queue
+    fn reset(&mut self, previous: *const Self, position_in_queue: usize) {
+        for node in self.buffer.iter_mut() {
+            node.reset();
+        }
+
+        *self.previous.get_mut() = previous as *mut Self;
+        *self.next.get_mut() = core::ptr::null_mut();
+        self.position_in_queue = position_in_queue;
     }
 
     /// Folds a fully handled buffer in the middle of the queue
@@ -63,15 +207,15 @@ impl<T> BufferList<T> {
             return None;
         }
 
-        let previous_ptr = self.previous as *mut Self;
+        let previous_ptr = self.previous();
 
-        let next = unsafe { &mut *next_ptr };
-        next.previous = previous_ptr;
+        let next = unsafe { &*next_ptr };
+        next.previous.store(previous_ptr, atomic::Ordering::Release);
 
         let previous = unsafe { &*previous_ptr };
         previous.next.store(next_ptr, atomic::Ordering::Release);
 
-        Some(next)
+        Some(next_ptr)
     }
 
     /// Attempts to find a Set Node starting from `tmp_head`
@@ -88,11 +232,7 @@ impl<T> BufferList<T> {
         let mut flag_move_to_new_buffer = false;
         let mut flag_buffer_all_handled = true;
 
-        let mut tmp_n = {
-            let n_ref = tmp_head_of_queue.buffer.get(tmp_head).unwrap();
-            let n_ptr = n_ref as *const Node<T> as *mut Node<T>;
-            unsafe { &*n_ptr }
-        };
+        let mut tmp_n = tmp_head_of_queue.buffer.get(tmp_head).unwrap();
 
         loop {
             let state = tmp_n.get_state();
@@ -114,7 +254,7 @@ impl<T> BufferList<T> {
                             let old = std::mem::replace(&mut tmp_head_of_queue, n_head_of_queue);
                             drop(ManuallyDrop::into_inner(old));
 
-                            tmp_head = tmp_head_of_queue.head;
+                            tmp_head = 0;
                             flag_move_to_new_buffer = true;
                             flag_buffer_all_handled = true;
                         }
@@ -129,18 +269,59 @@ impl<T> BufferList<T> {
 
                     tmp_head_of_queue_ptr = next_ptr;
                     tmp_head_of_queue = unsafe { &*tmp_head_of_queue_ptr };
-                    tmp_head = tmp_head_of_queue.head;
+                    tmp_head = 0;
                     flag_buffer_all_handled = true;
                     flag_move_to_new_buffer = true;
                 }
             }
 
-            tmp_n = {
-                let n_ref = tmp_head_of_queue.buffer.get(tmp_head).unwrap();
-                let n_ptr = n_ref as *const Node<T> as *mut Node<T>;
-                unsafe { &*n_ptr }
-            };
+            tmp_n = tmp_head_of_queue.buffer.get(tmp_head).unwrap();
+        }
+    }
+
+    /// Checks all the Nodes between the Head and the previously found Set-Node
+    /// (the result of [`scan`](Self::scan)) again and returns the earliest
+    /// Node that is now Set.
+    ///
+    /// This is needed because a Node before the found one could have been
+    /// set after we already checked it, in which case the found Node might
+    /// belong to the same Producer and was enqueued after that Node.
+    ///
+    /// # Returns
+    /// The Buffer and the Index of the earliest Set-Node
+    pub fn rescan(
+        head_of_queue_ptr: *mut BufferList<T>,
+        head: usize,
+        mut found_ptr: *mut BufferList<T>,
+        mut found: usize,
+    ) -> (*mut BufferList<T>, usize) {
+        let mut current_ptr = head_of_queue_ptr;
+        let mut current = head;
+
+        while current_ptr != found_ptr || current != found {
+            let current_queue = unsafe { &*current_ptr };
+
+            if current_queue.buffer[current].get_state() == NodeState::Set {
+                // Found an earlier Set-Node, so we use that one and start
+                // over, as there could now be an even earlier one
+                found_ptr = current_ptr;
+                found = current;
+
+                current_ptr = head_of_queue_ptr;
+                current = head;
+                continue;
+            }
+
+            current += 1;
+            if current >= BUFFER_SIZE {
+                // The found Node is always reachable from the Head, so there
+                // has to be a next Buffer at this point
+                current_ptr = current_queue.next.load(atomic::Ordering::Acquire);
+                current = 0;
+            }
         }
+
+        (found_ptr, found)
     }
 
     /// This attempts to allocate a new BufferList and store it as the next-Ptr for
@@ -149,9 +330,42 @@ impl<T> BufferList<T> {
         &self,
         self_ptr: *mut Self,
         tail_of_queue: &atomic::AtomicPtr<Self>,
+        cache: &BufferCache<T>,
     ) -> *mut Self {
         // Create/Allocate the new Buffer
-        let next_buffer = BufferList::boxed(self_ptr as *const Self, self.position_in_queue + 1);
+        let next_buffer = cache.get(
+            self_ptr as *const Self,
+            self.position_in_queue.wrapping_add(1),
+        );
+        self.append_next(self_ptr, tail_of_queue, cache, next_buffer)
+    }
+
+    /// This attempts to allocate a new BufferList and append it, like
+    /// [`allocate_next`](Self::allocate_next), but returns `None` instead of
+    /// aborting if the Allocation fails
+    pub fn try_allocate_next(
+        &self,
+        self_ptr: *mut Self,
+        tail_of_queue: &atomic::AtomicPtr<Self>,
+        cache: &BufferCache<T>,
+    ) -> Option<*mut Self> {
+        let next_buffer = cache.try_get(
+            self_ptr as *const Self,
+            self.position_in_queue.wrapping_add(1),
+        )?;
+        Some(self.append_next(self_ptr, tail_of_queue, cache, next_buffer))
+    }
+
+    /// Attempts to store the given BufferList as the next-Ptr for this Buffer
+    /// as well as storing it as the new Tail-Of-Queue and returns the
+    /// BufferList that follows this one afterwards
+    fn append_next(
+        &self,
+        self_ptr: *mut Self,
+        tail_of_queue: &atomic::AtomicPtr<Self>,
+        cache: &BufferCache<T>,
+        next_buffer: Box<Self>,
+    ) -> *mut Self {
         let next_buffer_ptr = Box::into_raw(next_buffer);
 
         // Try to append the new Buffer to this one.
@@ -190,9 +404,9 @@ impl<T> BufferList<T> {
             }
             Err(previous) => {
                 // Someone else already created the next Buffer following the
-                // current one, meaning that we should just clean up the Buffer
-                // we created and then we have to do nothing more
-                drop(unsafe { Box::from_raw(next_buffer_ptr) });
+                // current one, meaning that we should just return the Buffer
+                // we created to the Cache and then we have to do nothing more
+                cache.put(unsafe { Box::from_raw(next_buffer_ptr) });
 
                 previous
             }
@@ -204,7 +418,12 @@ impl<T> BufferList<T> {
     ///
     /// # Returns
     /// The Ptr to the next Buffer in the BufferList
-    pub fn go_to_next(&self, self_ptr: *mut Self, tail: &atomic::AtomicPtr<Self>) -> *mut Self {
+    pub fn go_to_next(
+        &self,
+        self_ptr: *mut Self,
+        tail: &atomic::AtomicPtr<Self>,
+        cache: &BufferCache<T>,
+    ) -> *mut Self {
         // Load the Ptr to the next Element in the Buffer-List
         let next = self.next.load(atomic::Ordering::Acquire);
 
@@ -216,20 +435,52 @@ impl<T> BufferList<T> {
 
         // If we have no next Element in the BufferList, we attempt to create
         // and append a new Buffer
-        self.allocate_next(self_ptr, tail)
+        self.allocate_next(self_ptr, tail, cache)
     }
 
     /// This function is responsible for deallocating the BufferList pointed to
-    /// by the given Ptr, as well as all the previous BufferLists, by walking
-    /// the entire Chain of BufferLists
-    pub fn deallocate_all(ptr: *mut Self) {
+    /// by the given Ptr, as well as all the previous and following BufferLists,
+    /// by walking the entire Chain of BufferLists.
+    ///
+    /// Dropping the BufferLists also drops all the Nodes in them, which in
+    /// turn drops all the Data that has not been dequeued yet. Returns the
+    /// Number of Elements that were dropped this way.
+    pub fn deallocate_all(ptr: *mut Self) -> usize {
+        if ptr.is_null() {
+            return 0;
+        }
+
+        // The given Ptr is not necessarily the last BufferList, because the
+        // Tail-Of-Queue might not have been updated after appending a new
+        // BufferList, so we first move to the actual End of the List
         let mut current_ptr = ptr;
-        while !current_ptr.is_null() {
-            let current = unsafe { Box::from_raw(current_ptr) };
-            current_ptr = current.previous as *mut Self;
+        loop {
+            let next_ptr = unsafe { &*current_ptr }
+                .next
+                .load(atomic::Ordering::Acquire);
+            if next_ptr.is_null() {
+                break;
+            }
+            current_ptr = next_ptr;
+        }
 
-            drop(current);
+        let mut dropped = 0;
+        while !current_ptr.is_null() {
+            let current = unsafe { &*current_ptr };
+            let previous_ptr = current.previous();
+            dropped += current
+                .buffer
+                .iter()
+                .filter(|node| node.get_state() == NodeState::Set)
+                .count();
+
+            // # Safety:
+            // We have exclusive Access to the entire Chain of BufferLists
+            unsafe { poison::drop_box(current_ptr) };
+            current_ptr = previous_ptr;
         }
+
+        dropped
     }
 }
 
@@ -237,14 +488,15 @@ impl<T> Debug for BufferList<T> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "BufferList ( position_in_queue = {}, head = {} )",
-            self.position_in_queue, self.head
+            "BufferList ( position_in_queue = {} )",
+            self.position_in_queue
         )
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::node::Location;
     use super::*;
     use std::mem::ManuallyDrop;
 
@@ -252,16 +504,18 @@ mod tests {
     fn folding_success() {
         let tail_ptr = atomic::AtomicPtr::new(std::ptr::null_mut());
 
-        let first_list = BufferList::boxed(std::ptr::null_mut(), 0);
+        let first_list = BufferList::<u32>::boxed(std::ptr::null_mut(), 0, &Segments::default());
         let first_list_ptr = Box::into_raw(first_list);
         let first_list = unsafe { &*first_list_ptr };
 
-        first_list.allocate_next(first_list_ptr, &tail_ptr);
+        let cache = BufferCache::new(Metrics::none(), Segments::default());
+
+        first_list.allocate_next(first_list_ptr, &tail_ptr, &cache);
 
         let second_list_ptr = first_list.next.load(atomic::Ordering::SeqCst);
         let second_list = unsafe { &*second_list_ptr };
 
-        second_list.allocate_next(second_list_ptr, &tail_ptr);
+        second_list.allocate_next(second_list_ptr, &tail_ptr, &cache);
         let third_list_ptr = second_list.next.load(atomic::Ordering::SeqCst);
 
         let result_next = second_list.fold().unwrap();
@@ -272,7 +526,7 @@ mod tests {
             third_list_ptr,
             first_list.next.load(atomic::Ordering::SeqCst)
         );
-        assert_eq!(first_list_ptr, third_list.previous as *mut BufferList<u32>);
+        assert_eq!(first_list_ptr, third_list.previous());
         assert_eq!(third_list_ptr, result_next);
 
         unsafe { Box::from_raw(first_list_ptr) };
@@ -284,11 +538,15 @@ mod tests {
     fn folding_failure() {
         let tail_ptr = atomic::AtomicPtr::new(std::ptr::null_mut());
 
-        let first_list = BufferList::boxed(std::ptr::null_mut(), 0);
+        let first_list = BufferList::<u32>::boxed(std::ptr::null_mut(), 0, &Segments::default());
         let first_list_ptr = Box::into_raw(first_list);
         let first_list = unsafe { Box::from_raw(first_list_ptr) };
 
-        first_list.allocate_next(first_list_ptr, &tail_ptr);
+        first_list.allocate_next(
+            first_list_ptr,
+            &tail_ptr,
+            &BufferCache::new(Metrics::none(), Segments::default()),
+        );
 
         let second_list_ptr = first_list.next.load(atomic::Ordering::SeqCst);
         let mut second_list = ManuallyDrop::new(unsafe { Box::from_raw(second_list_ptr) });
@@ -299,18 +557,73 @@ mod tests {
             second_list_ptr,
             first_list.next.load(atomic::Ordering::SeqCst)
         );
-        assert_eq!(first_list_ptr, second_list.previous as *mut BufferList<u32>);
+        assert_eq!(first_list_ptr, second_list.previous());
 
         unsafe { ManuallyDrop::drop(&mut second_list) };
     }
 
+    #[test]
+    fn rescan_finds_earlier() {
+        let tail_ptr = atomic::AtomicPtr::new(std::ptr::null_mut());
+        let cache = BufferCache::new(Metrics::none(), Segments::default());
+
+        let first_list_ptr = Box::into_raw(BufferList::boxed(
+            std::ptr::null_mut(),
+            1,
+            &Segments::default(),
+        ));
+        let first_list = unsafe { &*first_list_ptr };
+        let second_list_ptr = first_list.allocate_next(first_list_ptr, &tail_ptr, &cache);
+        let second_list = unsafe { &*second_list_ptr };
+
+        second_list.buffer[5].store(14, Location::new(2, 5));
+        assert_eq!(
+            (second_list_ptr, 5),
+            BufferList::rescan(first_list_ptr, 0, second_list_ptr, 5)
+        );
+
+        first_list.buffer[BUFFER_SIZE - 1].store(13, Location::new(1, BUFFER_SIZE - 1));
+        assert_eq!(
+            (first_list_ptr, BUFFER_SIZE - 1),
+            BufferList::rescan(first_list_ptr, 0, second_list_ptr, 5)
+        );
+
+        BufferList::deallocate_all(first_list_ptr);
+    }
+
+    #[test]
+    fn cache_reuse() {
+        let cache = BufferCache::new(Metrics::none(), Segments::default());
+
+        let list = cache.get(std::ptr::null(), 1);
+        list.buffer[0].store(13, Location::new(1, 0));
+        assert_eq!(Some(13), list.buffer[0].load(Location::new(1, 0)));
+        let list_ptr = &*list as *const BufferList<u64>;
+
+        cache.put(list);
+
+        let previous = 0x1234 as *const BufferList<u64>;
+        let reused = cache.get(previous, 3);
+        assert_eq!(list_ptr, &*reused as *const BufferList<u64>);
+        assert_eq!(previous as *mut _, reused.previous());
+        assert_eq!(3, reused.position_in_queue);
+        assert!(reused
+            .buffer
+            .iter()
+            .all(|n| n.get_state() == NodeState::Empty));
+    }
+
     #[test]
     fn scan() {
-        let raw_list = BufferList::boxed(std::ptr::null_mut(), 0);
+        let raw_list = BufferList::boxed(std::ptr::null_mut(), 0, &Segments::default());
         let raw_list_ptr = Box::into_raw(raw_list);
 
         let buffer_list = unsafe { &*raw_list_ptr };
-        buffer_list.buffer.get(2).unwrap().store(13);
+        buffer_list
+            .buffer
+            .get(2)
+            .unwrap()
+            .store(13, Location::new(0, 2));
 
         let (result_buffer, result_head) = BufferList::scan(raw_list_ptr, 0);
 
@@ -1,7 +1,12 @@
-use core::{cell::UnsafeCell, fmt::Debug, sync::atomic};
+use core::{cell::UnsafeCell, fmt::Debug, mem::MaybeUninit};
+
+use crate::{sync::native::atomic, utils::Backoff};
+
+#[cfg(feature = "debug-validate")]
+use super::validate;
 
 /// The possible States of a Node
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NodeState {
     /// The Node is either empty or currently being written to
     Empty,
@@ -32,10 +37,59 @@ impl NodeState {
     }
 }
 
+/// The Location of a Node in the Queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    /// The Position of the Buffer containing the Node in the List of
+    /// Buffers, starting at 1
+    pub position_in_queue: usize,
+    /// The Index of the Node in its Buffer
+    pub index: usize,
+}
+
+impl core::fmt::Display for Location {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "position_in_queue = {}, index = {}",
+            self.position_in_queue, self.index
+        )
+    }
+}
+
+impl Location {
+    /// Creates a new Location for the Node at the given Index in the Buffer
+    pub const fn new(position_in_queue: usize, index: usize) -> Self {
+        Self {
+            position_in_queue,
+            index,
+        }
+    }
+}
+
+/// The Number of Rounds, with an exponential Backoff in between, that
+/// [`Node::load_published`] waits for a Producer to publish its Data
+pub const PUBLISH_RETRIES: usize = 7;
+
 /// A single Entry in the Queue
+///
+/// # Publication
+/// The Data of a Node is published using the State of the Node:
+/// * The Producer first writes the Data and only afterwards stores the
+///   `Set`-State with `Release` Ordering
+/// * The Consumer loads the State with `Acquire` Ordering and only reads the
+///   Data once it observed the `Set`-State
+///
+/// This means that the Data is initialized if, and only if, the Node is in
+/// the `Set`-State, so loading it never has to deal with missing Data. A
+/// Producer might however already have claimed a Node, by advancing the Tail
+/// of the Queue, without having published its Data yet, in which case the
+/// Node is still `Empty` and [`load_published`](Self::load_published) can be
+/// used to wait a bounded Time for the Data.
 pub struct Node<T> {
-    /// The actual Datat itself that is stored in the Node
-    data: UnsafeCell<Option<T>>,
+    /// The actual Datat itself that is stored in the Node, which is only
+    /// initialized while the Node is in the `Set`-State
+    data: UnsafeCell<MaybeUninit<T>>,
     /// This holds one of three Values indicating the "State" of
     /// the Value
     is_set: atomic::AtomicU8,
@@ -44,7 +98,7 @@ pub struct Node<T> {
 impl<T> Node<T> {
     pub fn new() -> Self {
         Self {
-            data: UnsafeCell::new(None),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
             is_set: atomic::AtomicU8::new(NodeState::Empty.to_u8()),
         }
     }
@@ -57,52 +111,164 @@ impl<T> Node<T> {
     }
 
     /// Stores the given Data into the Node updating its Data-Field
-    /// as well as its `is_set` State to `NodeState::Set`
-    pub fn store(&self, data: T) {
+    /// as well as its `is_set` State to `NodeState::Set`.
+    ///
+    /// The Location is only used to report invalid State-Transitions with
+    /// the `debug-validate` Feature
+    pub fn store(&self, data: T, location: Location) {
+        #[cfg(feature = "debug-validate")]
+        validate::expect(
+            location,
+            self.is_set.load(atomic::Ordering::Acquire),
+            NodeState::Empty,
+            NodeState::Set,
+        );
+        #[cfg(not(feature = "debug-validate"))]
+        let _ = location;
+
         // # Safety:
         // This is safe because every Cell is only ever written to by a single
         // Producer and will not be read by the Consumer until the State of the
         // Node is changed to Set.
         // This means that we have exclusive access to the current Data in the
         // Node and therefore it is safe to mutate it directly without other
-        // forms of synchronization
+        // forms of synchronization.
+        // The Data is written through the raw Ptr, without creating a
+        // `&mut` Reference, as the Node itself is shared with the Receiver.
+        // The Node is still Empty, so there is no old Data to drop
         let raw_ptr = self.data.get();
-        let mut_data = unsafe { &mut *raw_ptr };
-        mut_data.replace(data);
+        unsafe { raw_ptr.write(MaybeUninit::new(data)) };
 
-        // Update the State of the Node to indicate to the Consumer that this
-        // node is now ready to be read/consumed
+        // Publish the Data to the Consumer, the Release-Ordering makes sure
+        // that the Write of the Data above happens before the Consumer
+        // observes the Node as Set
+        #[cfg(not(feature = "debug-validate"))]
         self.is_set
             .store(NodeState::Set.to_u8(), atomic::Ordering::Release);
+        #[cfg(feature = "debug-validate")]
+        validate::transition(
+            location,
+            self.is_set
+                .swap(NodeState::Set.to_u8(), atomic::Ordering::AcqRel),
+            NodeState::Set,
+        );
+    }
+
+    /// Marks a Node, that was claimed by a Producer, as Handled without ever
+    /// storing any Data into it, so that the Receiver skips over it.
+    ///
+    /// This is used by Producers that claimed the Node, but then found the
+    /// Queue closed, in which case they keep their Data
+    pub fn abandon(&self, location: Location) {
+        #[cfg(not(feature = "debug-validate"))]
+        {
+            let _ = location;
+            self.is_set
+                .store(NodeState::Handled.to_u8(), atomic::Ordering::Release);
+        }
+        #[cfg(feature = "debug-validate")]
+        validate::transition_from(
+            location,
+            self.is_set
+                .swap(NodeState::Handled.to_u8(), atomic::Ordering::AcqRel),
+            NodeState::Empty,
+            NodeState::Handled,
+        );
     }
 
     /// Attempts to load the Data from the Node itself, this can only be
-    /// done once, and automatically sets the node to being handled
-    pub fn load(&self) -> Option<T> {
+    /// done once, and automatically sets the node to being handled.
+    ///
+    /// Returns None if the Node has not been published yet or has already
+    /// been handled
+    pub fn load(&self, location: Location) -> Option<T> {
+        // The Acquire-Load pairs with the Release-Store in `store`, so the
+        // Data is visible to us once we observe the Node as Set
         if self.get_state() != NodeState::Set {
             return None;
         }
 
         // # Safety:
-        // This is save to do, because the value is only ever consumed by a
-        // single Consumer and after the value has been set, no more procuder
-        // will touch this entire Node again.
-        let raw_ptr = self.data.get();
-        let mut_data = unsafe { &mut *raw_ptr };
-        // We can safely unwrap this value as well, because this function
-        // is only ever called once and before it is called, the consumer will
-        // check that the Node is marked as Set. After this Node was visited it
-        // will never again be visited and therefore this wont be called again
-        // with a now empty data entry.
-        let data = mut_data.take().unwrap();
+        // The Node is Set, so the Data has been fully written and
+        // initialized by the Producer, which will not touch the Node again.
+        // There is only a single Consumer, which immediately marks the Node
+        // as Handled afterwards, so the Data is only ever read once
+        let data = unsafe { (*self.data.get()).as_ptr().read() };
 
-        self.is_set
-            .store(NodeState::Handled.to_u8(), atomic::Ordering::Release);
+        #[cfg(not(feature = "debug-validate"))]
+        {
+            let _ = location;
+            self.is_set
+                .store(NodeState::Handled.to_u8(), atomic::Ordering::Release);
+        }
+        #[cfg(feature = "debug-validate")]
+        validate::transition(
+            location,
+            self.is_set
+                .swap(NodeState::Handled.to_u8(), atomic::Ordering::AcqRel),
+            NodeState::Handled,
+        );
 
         Some(data)
     }
+
+    /// Loads the Data from a Node, that has already been claimed by a
+    /// Producer, but might not have been published yet.
+    ///
+    /// This waits for up to [`PUBLISH_RETRIES`] Rounds, with an exponential
+    /// Backoff in between, for the Node to become Set. If the Producer did
+    /// not publish its Data in that Time, None is returned and the Node is
+    /// left untouched, so the Consumer can treat it like an Empty Node and
+    /// simply try again later, instead of spinning indefinitely.
+    pub fn load_published(&self, location: Location) -> Option<T> {
+        let backoff = Backoff::new();
+        for _ in 0..PUBLISH_RETRIES {
+            match self.get_state() {
+                NodeState::Empty => backoff.spin(),
+                _ => break,
+            };
+        }
+
+        self.load(location)
+    }
 }
 
+impl<T> Node<T> {
+    /// Drops the Data in the Node, if it has been published but not yet
+    /// handled
+    fn drop_data(&mut self) {
+        if *self.is_set.get_mut() == NodeState::Set.to_u8() {
+            // # Safety:
+            // The Node is Set, so the Data is initialized and has not been
+            // read by the Consumer
+            unsafe { self.data.get_mut().as_mut_ptr().drop_in_place() };
+        }
+    }
+
+    /// Resets the Node back into its initial Empty-State, so that it can be
+    /// reused
+    ///
+    /// # Note
+    /// This requires exclusive access to the Node, so it is only used on
+    /// BufferLists that are no longer shared with any other Thread
+    pub fn reset(&mut self) {
+        self.drop_data();
+        *self.is_set.get_mut() = NodeState::Empty.to_u8();
+    }
+}
+
+impl<T> Drop for Node<T> {
+    fn drop(&mut self) {
+        self.drop_data();
+    }
+}
+
+// Safety:
+// The Data is only written by the single Producer that claimed the Node and
+// only read by the single Consumer after it was published through the State,
+// so sharing a Node only moves the Data between Threads
+unsafe impl<T> Sync for Node<T> where T: Send {}
+
 impl<T> Default for Node<T> {
     fn default() -> Self {
         Self::new()
@@ -124,21 +290,44 @@ impl<T> Debug for Node<T> {
 mod tests {
     use super::*;
 
+    const LOCATION: Location = Location::new(1, 0);
+
     #[test]
     fn node_store_load() {
         let node: Node<u64> = Default::default();
 
-        node.store(15);
-        assert_eq!(Some(15), node.load());
+        node.store(15, LOCATION);
+        assert_eq!(Some(15), node.load(LOCATION));
     }
 
     #[test]
     fn node_store_load_multiple() {
         let node: Node<u64> = Default::default();
 
-        node.store(15);
-        assert_eq!(Some(15), node.load());
-        assert_eq!(None, node.load());
+        node.store(15, LOCATION);
+        assert_eq!(Some(15), node.load(LOCATION));
+        assert_eq!(None, node.load(LOCATION));
+    }
+
+    #[test]
+    fn node_reset() {
+        let mut node: Node<u64> = Default::default();
+
+        node.store(13, LOCATION);
+        assert_eq!(Some(13), node.load(LOCATION));
+        assert_eq!(NodeState::Handled, node.get_state());
+
+        node.reset();
+        assert_eq!(NodeState::Empty, node.get_state());
+    }
+
+    #[test]
+    fn node_abandon() {
+        let node: Node<u64> = Default::default();
+
+        node.abandon(LOCATION);
+        assert_eq!(NodeState::Handled, node.get_state());
+        assert_eq!(None, node.load(LOCATION));
     }
 
     #[test]
@@ -146,7 +335,61 @@ mod tests {
         let node: Node<u64> = Default::default();
 
         assert_eq!(NodeState::Empty, node.get_state());
-        node.store(13);
+        node.store(13, LOCATION);
         assert_eq!(NodeState::Set, node.get_state());
     }
+
+    #[test]
+    fn node_load_published_unclaimed() {
+        let node: Node<u64> = Default::default();
+
+        assert_eq!(None, node.load_published(LOCATION));
+        assert_eq!(NodeState::Empty, node.get_state());
+
+        node.store(13, LOCATION);
+        assert_eq!(Some(13), node.load_published(LOCATION));
+        assert_eq!(None, node.load_published(LOCATION));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn node_load_published_concurrent() {
+        let node = std::sync::Arc::new(Node::<u64>::new());
+
+        let producer = {
+            let node = node.clone();
+            std::thread::spawn(move || node.store(13, LOCATION))
+        };
+
+        let data = loop {
+            if let Some(data) = node.load_published(LOCATION) {
+                break data;
+            }
+        };
+        producer.join().unwrap();
+
+        assert_eq!(13, data);
+        assert_eq!(NodeState::Handled, node.get_state());
+    }
+
+    #[test]
+    fn node_drop_unhandled() {
+        let data = std::sync::Arc::new(13);
+
+        let mut node = Node::new();
+        node.store(data.clone(), LOCATION);
+        node.reset();
+        assert_eq!(1, std::sync::Arc::strong_count(&data));
+
+        let node = Node::new();
+        node.store(data.clone(), LOCATION);
+        drop(node);
+        assert_eq!(1, std::sync::Arc::strong_count(&data));
+
+        let node = Node::new();
+        node.store(data.clone(), LOCATION);
+        drop(node.load(LOCATION));
+        drop(node);
+        assert_eq!(1, std::sync::Arc::strong_count(&data));
+    }
 }
@@ -0,0 +1,150 @@
+//! Validation of the State-Transitions of the Nodes in the Queue.
+//!
+//! Every Node should only ever go through `Empty -> Set -> Handled`, before
+//! its Buffer is reused and the Node is reset to `Empty` again. A Producer
+//! that claimed a Node, but then found the Queue closed, instead moves it
+//! directly from `Empty` to `Handled`, so the Receiver skips it. With the
+//! `debug-validate` Feature, every Transition is checked against this
+//! Sequence and any Violation panics with the Location of the Node and its
+//! prior State, instead of failing somewhere later on an opaque `unwrap`.
+//!
+//! The Transitions can also be observed by installing a Hook using
+//! [`set_transition_hook`], for example to record them in a Ring-Buffer and
+//! dump them once a Violation is detected.
+//!
+//! # Example
+//! ```rust
+//! # use nolock::queues::mpsc::jiffy;
+//! # use nolock::queues::mpsc::jiffy::validate::{self, Transition};
+//! fn hook(transition: &Transition) {
+//!     assert!(transition.from != transition.to);
+//! }
+//! validate::set_transition_hook(hook);
+//!
+//! let (mut rx, tx) = jiffy::queue();
+//! tx.enqueue(13).unwrap();
+//! assert_eq!(Ok(13), rx.try_dequeue());
+//!
+//! validate::clear_transition_hook();
+//! ```
+
+use core::{fmt, ptr};
+
+use crate::sync::native::atomic;
+
+pub use super::node::{Location, NodeState};
+
+/// A single State-Transition of a Node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    /// The Node that changed its State
+    pub location: Location,
+    /// The State of the Node before the Transition
+    pub from: NodeState,
+    /// The State of the Node after the Transition
+    pub to: NodeState,
+}
+
+/// The currently installed Hook, which is either Null or a valid
+/// `fn(&Transition)`
+static HOOK: atomic::AtomicPtr<()> = atomic::AtomicPtr::new(ptr::null_mut());
+
+/// Installs the Hook that is called for every State-Transition of a Node in
+/// any Jiffy-Queue, replacing the previously installed one.
+///
+/// The Hook is called by the Thread performing the Transition, right after
+/// it was validated, so it should be cheap and must not use the Queue itself
+pub fn set_transition_hook(hook: fn(&Transition)) {
+    HOOK.store(hook as *mut (), atomic::Ordering::Release);
+}
+
+/// Removes the currently installed Hook, if there is one
+pub fn clear_transition_hook() {
+    HOOK.store(ptr::null_mut(), atomic::Ordering::Release);
+}
+
+/// Checks that the Node was in the `expected` State, before moving to `to`
+pub(crate) fn expect(location: Location, raw_state: u8, expected: NodeState, to: NodeState) {
+    match NodeState::from_u8(raw_state) {
+        Some(state) if state == expected => {}
+        _ => violation(
+            location,
+            raw_state,
+            format_args!("expected {:?} before moving to {:?}", expected, to),
+        ),
+    };
+}
+
+/// Validates the Transition of the Node from the `raw_from` State to `to` and
+/// reports it to the installed Hook
+pub(crate) fn transition(location: Location, raw_from: u8, to: NodeState) {
+    let expected = match to {
+        NodeState::Set => NodeState::Empty,
+        NodeState::Handled => NodeState::Set,
+        NodeState::Empty => NodeState::Handled,
+    };
+    transition_from(location, raw_from, expected, to);
+}
+
+/// Validates the Transition of the Node from the `raw_from` State, which
+/// should be `expected`, to `to` and reports it to the installed Hook
+pub(crate) fn transition_from(
+    location: Location,
+    raw_from: u8,
+    expected: NodeState,
+    to: NodeState,
+) {
+    expect(location, raw_from, expected, to);
+
+    let hook_ptr = HOOK.load(atomic::Ordering::Acquire);
+    if hook_ptr.is_null() {
+        return;
+    }
+
+    // # Safety:
+    // The Hook is only ever set to a valid `fn(&Transition)` or to Null,
+    // which we checked above
+    let hook: fn(&Transition) = unsafe { core::mem::transmute(hook_ptr) };
+    hook(&Transition {
+        location,
+        from: expected,
+        to,
+    });
+}
+
+/// Panics with a Diagnostic about the Node at the given Location
+#[cold]
+#[track_caller]
+pub(crate) fn violation(location: Location, raw_state: u8, reason: fmt::Arguments<'_>) -> ! {
+    let state = NodeState::from_u8(raw_state);
+    panic!(
+        "Invalid Jiffy-Node State-Transition ({}), prior State = {:?} (raw = {}): {}",
+        location, state, raw_state, reason
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCATION: Location = Location::new(1, 0);
+
+    #[test]
+    fn valid_transitions() {
+        transition(LOCATION, NodeState::Empty.to_u8(), NodeState::Set);
+        transition(LOCATION, NodeState::Set.to_u8(), NodeState::Handled);
+        transition(LOCATION, NodeState::Handled.to_u8(), NodeState::Empty);
+    }
+
+    #[test]
+    #[should_panic(expected = "position_in_queue = 1, index = 0")]
+    fn store_twice() {
+        transition(LOCATION, NodeState::Set.to_u8(), NodeState::Set);
+    }
+
+    #[test]
+    #[should_panic(expected = "prior State = None (raw = 7)")]
+    fn invalid_raw_state() {
+        transition(LOCATION, 7, NodeState::Handled);
+    }
+}
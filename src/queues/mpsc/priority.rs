@@ -0,0 +1,407 @@
+//! A MPSC-Channel with a fixed Number of Priority-Levels, where the Receiver
+//! always dequeues from the highest Priority that has Elements available.
+//!
+//! # Design
+//! Every Priority-Level is backed by its own [`jiffy`] Queue,
+//! so enqueuing stays wait-free, and a shared Bitmap marks the Levels that
+//! may contain Elements. A Producer sets the Bit of its Level after
+//! enqueuing and the Receiver only clears it once it found the Level empty,
+//! checking the Level once more afterwards so no Element is missed. This
+//! allows the Receiver to find the highest non-empty Level using a single
+//! Load, instead of polling every Level in turn.
+//!
+//! # Priorities
+//! The Priority `0` is the highest one, so its Elements are always dequeued
+//! first, and a Channel supports at most [`MAX_LEVELS`] Priority-Levels.
+//! Within a single Level, the Elements follow the Ordering of the Jiffy-Queue.
+//!
+//! As the Priorities are strict, a steady Stream of high-priority Elements
+//! starves all the lower Levels.
+//!
+//! This is different from the `queues::priority` Queue, which supports
+//! arbitrary Priorities and multiple Consumers, but needs the `hyaline`
+//! Feature.
+//!
+//! # Example
+//! ```
+//! use nolock::queues::mpsc::priority;
+//!
+//! let (tx, mut rx) = priority::channel::<&str>(3);
+//!
+//! tx.enqueue(2, "background").unwrap();
+//! tx.enqueue(0, "urgent").unwrap();
+//! tx.enqueue(1, "normal").unwrap();
+//!
+//! assert_eq!(Ok("urgent"), rx.try_dequeue());
+//! assert_eq!(Ok("normal"), rx.try_dequeue());
+//! assert_eq!(Ok("background"), rx.try_dequeue());
+//! ```
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::fmt::Debug;
+
+use crate::{
+    queues::{
+        cancel::{self, CancelToken},
+        DequeueError, EnqueueError,
+    },
+    sync::native::atomic,
+    utils::{AtomicWaker, Backoff},
+};
+
+use super::jiffy;
+
+#[cfg(feature = "async")]
+mod async_queue;
+#[cfg(feature = "async")]
+pub use async_queue::*;
+
+/// The maximum Number of Priority-Levels a single Channel supports
+pub const MAX_LEVELS: usize = usize::BITS as usize;
+
+/// The State shared between the Senders and the Receiver
+struct Ready {
+    /// The Levels that may contain Elements, where Bit `n` belongs to Level `n`
+    levels: atomic::AtomicUsize,
+    /// The Waker of an async Receiver, which is woken once a Level becomes
+    /// non-empty or the Channel is closed
+    waker: AtomicWaker,
+}
+
+/// The State shared between all the Senders
+struct Shared<T> {
+    /// The Senders of the individual Levels
+    lanes: Box<[jiffy::Sender<T>]>,
+    ready: Arc<Ready>,
+}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        // Close all the Levels before waking up the Receiver, so that it
+        // observes the Channel as closed
+        drop(core::mem::take(&mut self.lanes));
+        self.ready.waker.wake();
+    }
+}
+
+/// The Sending-Half of the Channel, which can be cloned to get multiple
+/// Senders for the same Channel
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// The Receiving-Half of the Channel
+pub struct Receiver<T> {
+    /// The Receivers of the individual Levels
+    lanes: Box<[jiffy::Receiver<T>]>,
+    ready: Arc<Ready>,
+}
+
+impl<T> Sender<T> {
+    /// The Number of Priority-Levels of the Channel
+    pub fn levels(&self) -> usize {
+        self.shared.lanes.len()
+    }
+
+    /// Checks if the Channel has been closed by the Receiver
+    pub fn is_closed(&self) -> bool {
+        self.shared.lanes[0].is_closed()
+    }
+
+    /// Enqueues the Data with the given Priority, where `0` is the highest
+    /// Priority
+    ///
+    /// # Panics
+    /// If the Priority is not smaller than the Number of Levels of the
+    /// Channel
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::priority;
+    /// let (tx, mut rx) = priority::channel::<usize>(2);
+    ///
+    /// tx.enqueue(1, 13).unwrap();
+    /// tx.enqueue(0, 14).unwrap();
+    ///
+    /// assert_eq!(Ok(14), rx.try_dequeue());
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    /// ```
+    pub fn enqueue(&self, priority: usize, data: T) -> Result<(), (T, EnqueueError)> {
+        let lanes = &self.shared.lanes;
+        assert!(
+            priority < lanes.len(),
+            "The Priority {} is out of Range for {} Levels",
+            priority,
+            lanes.len()
+        );
+
+        lanes[priority].enqueue(data)?;
+
+        // Only the Producer that marks the Level as non-empty needs to wake up
+        // the Receiver, as the Receiver only waits once it cleared the Bits of
+        // all the Levels it found empty
+        let bit = 1 << priority;
+        let previous = self
+            .shared
+            .ready
+            .levels
+            .fetch_or(bit, atomic::Ordering::AcqRel);
+        if previous & bit == 0 {
+            self.shared.ready.waker.wake();
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Sender ()")
+    }
+}
+
+impl<T> Receiver<T> {
+    /// The Number of Priority-Levels of the Channel
+    pub fn levels(&self) -> usize {
+        self.lanes.len()
+    }
+
+    /// Checks if the Channel has been closed by the Senders
+    ///
+    /// # Note
+    /// There may still be Elements left in the Channel, even if it has been
+    /// closed
+    pub fn is_closed(&self) -> bool {
+        // The Levels are closed in Order, so the last one is closed once all
+        // of them are
+        self.lanes[self.lanes.len() - 1].is_closed()
+    }
+
+    /// Attempts to dequeue the Element with the highest Priority
+    ///
+    /// # Returns
+    /// [`DequeueError::Closed`] is only returned once all the Senders have
+    /// been dropped and no Elements are left on any Level
+    pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        let mut ready = self.ready.levels.load(atomic::Ordering::Acquire);
+        while ready != 0 {
+            let level = ready.trailing_zeros() as usize;
+            let bit = 1 << level;
+
+            if let Ok(data) = self.lanes[level].try_dequeue() {
+                return Ok(data);
+            }
+
+            // The Level is empty, so we clear its Bit, but an Element may have
+            // been enqueued before we did so, whose Producer then saw the Bit
+            // still being set, so we need to check the Level once more
+            self.ready.levels.fetch_and(!bit, atomic::Ordering::AcqRel);
+            if let Ok(data) = self.lanes[level].try_dequeue() {
+                self.ready.levels.fetch_or(bit, atomic::Ordering::AcqRel);
+                return Ok(data);
+            }
+
+            ready &= !bit;
+        }
+
+        if !self.is_closed() {
+            return Err(DequeueError::Empty);
+        }
+
+        // All the Senders are gone, so every Element is already visible, but
+        // its Bit may have been cleared by an earlier Dequeue in between
+        for lane in self.lanes.iter_mut() {
+            if let Ok(data) = lane.try_dequeue() {
+                return Ok(data);
+            }
+        }
+        Err(DequeueError::Closed)
+    }
+
+    /// This is a simple blocking dequeue, which spins, with an exponential
+    /// [`Backoff`], until an Element could be dequeued from any of the
+    /// Levels.
+    ///
+    /// # Behaviour
+    /// Returns `Some(data)` once an Element was dequeued or `None` once all
+    /// the Senders have been dropped and all the Levels are empty
+    pub fn dequeue(&mut self) -> Option<T> {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_dequeue() {
+                Ok(data) => return Some(data),
+                Err(DequeueError::Empty) => backoff.snooze(),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+            };
+        }
+    }
+
+    /// A blocking dequeue operation like [`dequeue`](Self::dequeue), which
+    /// can additionally be cancelled from another Thread using the given
+    /// [`CancelToken`], see the [`cancel`](crate::queues::cancel) module for
+    /// more Details
+    ///
+    /// # Returns
+    /// * `Ok(data)` once an Element could be dequeued
+    /// * `Err(DequeueError::Closed)` if the Channel has been closed
+    /// * `Err(DequeueError::Cancelled)` if the Token was cancelled while the
+    ///   Channel was empty
+    pub fn dequeue_cancellable(&mut self, token: &CancelToken) -> Result<T, DequeueError> {
+        cancel::dequeue(token, || self.try_dequeue())
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Receiver ()")
+    }
+}
+
+/// Creates a new empty Channel with the given Number of Priority-Levels and
+/// returns the Halves as ([`Sender`], [`Receiver`]), in the same Order as
+/// `std::sync::mpsc::channel`
+///
+/// # Panics
+/// If the Number of Levels is zero or larger than [`MAX_LEVELS`]
+///
+/// # Example
+/// ```
+/// # use nolock::queues::mpsc::priority;
+/// let (tx, mut rx) = priority::channel::<usize>(4);
+/// assert_eq!(4, tx.levels());
+///
+/// tx.enqueue(3, 13).unwrap();
+/// drop(tx);
+///
+/// assert_eq!(Some(13), rx.dequeue());
+/// assert_eq!(None, rx.dequeue());
+/// ```
+pub fn channel<T>(levels: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(levels > 0, "A priority Channel needs at least one Level");
+    assert!(
+        levels <= MAX_LEVELS,
+        "A priority Channel supports at most {} Levels",
+        MAX_LEVELS
+    );
+
+    let (rxs, txs): (Vec<_>, Vec<_>) = (0..levels).map(|_| jiffy::queue()).unzip();
+    let ready = Arc::new(Ready {
+        levels: atomic::AtomicUsize::new(0),
+        waker: AtomicWaker::new(),
+    });
+
+    let rx = Receiver {
+        lanes: rxs.into_boxed_slice(),
+        ready: ready.clone(),
+    };
+    let tx = Sender {
+        shared: Arc::new(Shared {
+            lanes: txs.into_boxed_slice(),
+            ready,
+        }),
+    };
+    (tx, rx)
+}
+
+crate::queues::adapter::impl_receiver!(Receiver<T>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highest_priority_first() {
+        let (tx, mut rx) = channel::<usize>(3);
+
+        tx.enqueue(2, 20).unwrap();
+        tx.enqueue(1, 10).unwrap();
+        tx.enqueue(2, 21).unwrap();
+        tx.enqueue(0, 0).unwrap();
+
+        assert_eq!(Ok(0), rx.try_dequeue());
+        assert_eq!(Ok(10), rx.try_dequeue());
+        assert_eq!(Ok(20), rx.try_dequeue());
+
+        tx.enqueue(1, 11).unwrap();
+        assert_eq!(Ok(11), rx.try_dequeue());
+        assert_eq!(Ok(21), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+        assert_eq!(0, rx.ready.levels.load(atomic::Ordering::Acquire));
+    }
+
+    #[test]
+    fn closed_once_all_empty() {
+        let (tx, mut rx) = channel::<usize>(2);
+        let tx2 = tx.clone();
+
+        tx.enqueue(1, 13).unwrap();
+        drop(tx);
+        assert!(!rx.is_closed());
+
+        drop(tx2);
+        assert!(rx.is_closed());
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+    }
+
+    #[test]
+    fn receiver_dropped() {
+        let (tx, rx) = channel::<usize>(2);
+        drop(rx);
+
+        assert!(tx.is_closed());
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue(0, 13));
+    }
+
+    #[test]
+    fn max_levels() {
+        let (tx, mut rx) = channel::<usize>(MAX_LEVELS);
+
+        tx.enqueue(MAX_LEVELS - 1, 13).unwrap();
+        assert_eq!(Ok(13), rx.try_dequeue());
+    }
+
+    #[test]
+    #[should_panic]
+    fn priority_out_of_range() {
+        let (tx, _rx) = channel::<usize>(2);
+        let _ = tx.enqueue(2, 13);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn concurrent_producers() {
+        let (tx, mut rx) = channel::<usize>(4);
+
+        let producers: Vec<_> = (0..4)
+            .map(|p| {
+                let tx = tx.clone();
+                std::thread::spawn(move || {
+                    for i in 0..1000 {
+                        tx.enqueue(i % 4, p * 1000 + i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut received = Vec::new();
+        while let Some(data) = rx.dequeue() {
+            received.push(data);
+        }
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        received.sort_unstable();
+        assert_eq!((0..4000).collect::<Vec<_>>(), received);
+    }
+}
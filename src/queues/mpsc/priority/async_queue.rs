@@ -0,0 +1,220 @@
+use core::{fmt::Debug, future::Future, task::Poll};
+
+use crate::queues::{
+    batch::{DequeueMany, PendingDequeue},
+    DequeueError,
+};
+
+use super::{channel, Receiver, Sender};
+
+/// This is the asynchronous Version of the priority [`Receiver`], which
+/// waits for an Element on any of the Levels using a single Future
+///
+/// The [`Sender`] is the same for the sync and async Receiver, as enqueuing
+/// never waits and always wakes up a waiting Receiver.
+pub struct AsyncReceiver<T> {
+    /// The actual underlying Channel
+    queue: Receiver<T>,
+}
+
+impl<T> Receiver<T> {
+    /// Converts this Receiver into an [`AsyncReceiver`] for the same
+    /// Channel, which keeps all the Elements currently in the Channel
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn into_async(self) -> AsyncReceiver<T> {
+        AsyncReceiver { queue: self }
+    }
+}
+
+impl<T> AsyncReceiver<T> {
+    /// Converts this AsyncReceiver back into a sync [`Receiver`] for the same
+    /// Channel
+    pub fn into_sync(self) -> Receiver<T> {
+        self.queue
+    }
+
+    /// The Number of Priority-Levels of the Channel
+    pub fn levels(&self) -> usize {
+        self.queue.levels()
+    }
+
+    /// Checks if the Channel has been closed by the Senders
+    ///
+    /// # Note
+    /// There may still be Elements left in the Channel, even if it has been
+    /// closed
+    pub fn is_closed(&self) -> bool {
+        self.queue.is_closed()
+    }
+
+    /// Attempts to dequeue the Element with the highest Priority, see
+    /// [`Receiver::try_dequeue`]
+    pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        self.queue.try_dequeue()
+    }
+
+    /// Dequeues the Element with the highest Priority, waiting until an
+    /// Element is enqueued on any of the Levels if the Channel is currently
+    /// empty
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::mpsc::priority;
+    /// async fn demo() {
+    ///   let (tx, mut rx) = priority::async_channel::<usize>(2);
+    ///
+    ///   tx.enqueue(1, 13).unwrap();
+    ///   tx.enqueue(0, 14).unwrap();
+    ///
+    ///   assert_eq!(Ok(14), rx.dequeue().await);
+    ///   assert_eq!(Ok(13), rx.dequeue().await);
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    pub fn dequeue(&mut self) -> DequeueFuture<'_, T> {
+        DequeueFuture {
+            queue: &mut self.queue,
+        }
+    }
+
+    /// Waits for the next Element, just like [`dequeue`](Self::dequeue), and
+    /// then also takes all the Elements that are already in the Channel, in
+    /// the Order of their Priorities, until `max` Elements have been
+    /// dequeued, see the [`batch`](crate::queues::batch) module for more
+    /// Details
+    pub fn dequeue_many(&mut self, max: usize) -> DequeueMany<DequeueFuture<'_, T>> {
+        DequeueMany::new(self.dequeue(), max)
+    }
+}
+
+impl<T> Debug for AsyncReceiver<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Async-Receiver ()")
+    }
+}
+
+/// This is the Future returned by the [`dequeue`](AsyncReceiver::dequeue)
+/// Operation on the [`AsyncReceiver`]
+///
+/// # Behaviour
+/// The Future resolves with the Element with the highest Priority, once any
+/// Level contains an Element, or with `Err(DequeueError::Closed)` once all
+/// the Senders have been dropped and the Channel is empty.
+///
+/// # Cancel Safety
+/// This Future is cancel safe, an Element is only ever removed from the
+/// Channel when the Future resolves with it
+pub struct DequeueFuture<'queue, T> {
+    queue: &'queue mut Receiver<T>,
+}
+
+impl<'queue, T> Future for DequeueFuture<'queue, T> {
+    type Output = Result<T, DequeueError>;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Self::Output> {
+        let queue = &mut *self.get_mut().queue;
+
+        match queue.try_dequeue() {
+            Err(DequeueError::Empty) => {}
+            result => return Poll::Ready(result),
+        };
+
+        // The Bits of all the empty Levels have been cleared by now, so the
+        // next Producer will wake us up, but it may have done so before the
+        // Waker was registered, so we need to check once more
+        queue.ready.waker.register(cx.waker());
+        match queue.try_dequeue() {
+            Err(DequeueError::Empty) => Poll::Pending,
+            result => Poll::Ready(result),
+        }
+    }
+}
+
+impl<'queue, T> PendingDequeue for DequeueFuture<'queue, T> {
+    type Item = T;
+
+    fn try_dequeue_next(&mut self) -> Result<T, DequeueError> {
+        self.queue.try_dequeue()
+    }
+}
+
+impl<'queue, T> Debug for DequeueFuture<'queue, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Async-Dequeue-Operation ()")
+    }
+}
+
+/// Creates a new empty Channel with the given Number of Priority-Levels and
+/// an async Receiver, returning the Halves as ([`Sender`], [`AsyncReceiver`])
+///
+/// # Panics
+/// If the Number of Levels is zero or larger than
+/// [`MAX_LEVELS`](super::MAX_LEVELS)
+pub fn async_channel<T>(levels: usize) -> (Sender<T>, AsyncReceiver<T>) {
+    let (tx, rx) = channel(levels);
+    (tx, rx.into_async())
+}
+
+crate::queues::adapter::impl_async_receiver!(AsyncReceiver<T>, DequeueFuture);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn enqueue_wakes_receiver() {
+        let (tx, mut rx) = async_channel::<usize>(4);
+
+        let handle = tokio::spawn(async move { rx.dequeue().await });
+
+        // Let the Receiver register its Waker, before enqueuing anything
+        tokio::task::yield_now().await;
+        tx.enqueue(3, 13).unwrap();
+
+        let result = tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+            .await
+            .expect("The Receiver was not woken up")
+            .unwrap();
+        assert_eq!(Ok(13), result);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn sender_drop_wakes_receiver() {
+        let (tx, mut rx) = async_channel::<usize>(2);
+
+        let handle = tokio::spawn(async move { rx.dequeue().await });
+
+        tokio::task::yield_now().await;
+        drop(tx);
+
+        let result = tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+            .await
+            .expect("The Receiver was not woken up")
+            .unwrap();
+        assert_eq!(Err(DequeueError::Closed), result);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn dequeue_many_by_priority() {
+        let (tx, mut rx) = async_channel::<usize>(3);
+
+        tx.enqueue(2, 20).unwrap();
+        tx.enqueue(0, 0).unwrap();
+        tx.enqueue(1, 10).unwrap();
+        tx.enqueue(0, 1).unwrap();
+
+        assert_eq!(Ok(vec![0, 1, 10]), rx.dequeue_many(3).await);
+        assert_eq!(Ok(vec![20]), rx.dequeue_many(3).await);
+    }
+}
@@ -0,0 +1,404 @@
+//! Notifications about which of multiple Sources became ready, like the
+//! Shards of a sharded Queue that are no longer empty.
+//!
+//! A [`ReadySet`] is a lock-free Bitmap with one Bit per Source. Producers
+//! [`set`](ReadySet::set) the Bit of their Source once they made it ready,
+//! for example after enqueuing an Element on their Shard, and the Consumer
+//! atomically fetches and clears the ready Bits using
+//! [`take`](ReadySet::take), before servicing the returned Sources. The
+//! Consumer can also wait for any Bit to be set, either blocking the Thread
+//! using [`wait_blocking`](ReadySet::wait_blocking) or in async Code using
+//! [`wait`](ReadySet::wait).
+//!
+//! # Protocol
+//! As long as the Producers only set the Bit after making their Source
+//! ready and the Consumer only services a Source after taking its Bit, no
+//! Notification is ever lost: a Source that becomes ready while it is being
+//! serviced has its Bit set again and is returned by the next `take`. A
+//! Source may however be returned without having anything to do, if the
+//! Consumer already serviced it after the Bit was set.
+//!
+//! Only a single Consumer should take and wait on a Set at a Time.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::{mpsc::jiffy, notify::ReadySet};
+//! # use std::sync::Arc;
+//! let ready = Arc::new(ReadySet::new(4));
+//! let (mut rxs, txs): (Vec<_>, Vec<_>) = (0..4).map(|_| jiffy::queue::<usize>()).unzip();
+//!
+//! txs[2].enqueue(13).unwrap();
+//! ready.set(2);
+//!
+//! let mut received = Vec::new();
+//! for shard in ready.take() {
+//!     while let Ok(data) = rxs[shard].try_dequeue() {
+//!         received.push(data);
+//!     }
+//! }
+//! assert_eq!(vec![13], received);
+//! assert!(ready.is_empty());
+//! ```
+
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::Debug;
+
+use crate::{sync::native::atomic, utils::AtomicWaker};
+
+#[cfg(feature = "async")]
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The Number of Bits in a single Word of the Bitmap
+const WORD_BITS: usize = usize::BITS as usize;
+
+/// A lock-free Set of ready Sources, see the
+/// [`module-level documentation`](self) for more Details
+pub struct ReadySet {
+    words: Box<[atomic::AtomicUsize]>,
+    capacity: usize,
+    /// The Waker of an async Consumer waiting for any Bit to be set
+    waker: AtomicWaker,
+    /// The Threads waiting for any Bit to be set
+    #[cfg(feature = "std")]
+    events: crate::utils::EventCount,
+}
+
+impl ReadySet {
+    /// Creates a new empty Set for the given Number of Sources
+    ///
+    /// # Panics
+    /// If the `capacity` is 0
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "A ReadySet needs at least one Source");
+
+        let words: Vec<_> = (0..=(capacity - 1) / WORD_BITS)
+            .map(|_| atomic::AtomicUsize::new(0))
+            .collect();
+        Self {
+            words: words.into_boxed_slice(),
+            capacity,
+            waker: AtomicWaker::new(),
+            #[cfg(feature = "std")]
+            events: crate::utils::EventCount::new(),
+        }
+    }
+
+    /// The Number of Sources in the Set
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Marks the Source with the given Index as ready and wakes up the
+    /// Consumer, if it is waiting.
+    ///
+    /// Returns `true` if the Source was not already marked as ready
+    ///
+    /// # Panics
+    /// If the `index` is not smaller than the Capacity of the Set
+    pub fn set(&self, index: usize) -> bool {
+        assert!(
+            index < self.capacity,
+            "The Index {} is out of Range for {} Sources",
+            index,
+            self.capacity
+        );
+
+        let bit = 1 << (index % WORD_BITS);
+        let previous = self.words[index / WORD_BITS].fetch_or(bit, atomic::Ordering::SeqCst);
+        if previous & bit != 0 {
+            // The Consumer has not taken the Bit yet, so it was already woken
+            // up by whoever set it
+            return false;
+        }
+
+        self.waker.wake();
+        #[cfg(feature = "std")]
+        self.events.notify_one();
+
+        true
+    }
+
+    /// Checks if the Source with the given Index is currently marked as
+    /// ready
+    ///
+    /// # Panics
+    /// If the `index` is not smaller than the Capacity of the Set
+    pub fn is_set(&self, index: usize) -> bool {
+        assert!(
+            index < self.capacity,
+            "The Index {} is out of Range for {} Sources",
+            index,
+            self.capacity
+        );
+
+        let bit = 1 << (index % WORD_BITS);
+        self.words[index / WORD_BITS].load(atomic::Ordering::SeqCst) & bit != 0
+    }
+
+    /// Checks if no Source is currently marked as ready
+    pub fn is_empty(&self) -> bool {
+        self.words
+            .iter()
+            .all(|word| word.load(atomic::Ordering::SeqCst) == 0)
+    }
+
+    /// Takes all the ready Sources out of the Set and returns their Indices
+    /// in ascending Order.
+    ///
+    /// The Bits are cleared one Word at a Time while iterating, so if the
+    /// Iterator is dropped early, all the Sources that were not returned yet
+    /// stay marked as ready
+    pub fn take(&self) -> Ready<'_> {
+        Ready {
+            set: self,
+            word: 0,
+            bits: 0,
+        }
+    }
+
+    /// Blocks the current Thread until any Source is marked as ready and
+    /// then takes all the ready Sources, like [`take`](Self::take).
+    ///
+    /// The Thread first spins with an exponential
+    /// [`Backoff`](crate::utils::Backoff) and is only parked if no Source
+    /// becomes ready for longer.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::notify::ReadySet;
+    /// # use std::sync::Arc;
+    /// let ready = Arc::new(ReadySet::new(8));
+    ///
+    /// let consumer = {
+    ///     let ready = ready.clone();
+    ///     std::thread::spawn(move || ready.wait_blocking().collect::<Vec<_>>())
+    /// };
+    ///
+    /// ready.set(5);
+    /// assert_eq!(vec![5], consumer.join().unwrap());
+    /// ```
+    ///
+    /// # Async
+    /// This blocks the current Thread and therefore should not be called
+    /// from async Code, use [`wait`](Self::wait) instead
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn wait_blocking(&self) -> Ready<'_> {
+        let backoff = crate::utils::Backoff::new();
+
+        while self.is_empty() {
+            if !backoff.is_completed() {
+                backoff.snooze();
+                continue;
+            }
+
+            let key = self.events.prepare_wait();
+            if !self.is_empty() {
+                self.events.cancel_wait();
+                break;
+            }
+            self.events.wait(key);
+        }
+
+        self.take()
+    }
+
+    /// Waits until any Source is marked as ready and then takes all the
+    /// ready Sources, like [`take`](Self::take)
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::notify::ReadySet;
+    /// async fn demo() {
+    ///   let ready = ReadySet::new(8);
+    ///
+    ///   ready.set(1);
+    ///   ready.set(6);
+    ///   assert_eq!(vec![1, 6], ready.wait().await.collect::<Vec<_>>());
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn wait(&self) -> WaitFuture<'_> {
+        WaitFuture { set: self }
+    }
+}
+
+impl Debug for ReadySet {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ReadySet (capacity: {})", self.capacity)
+    }
+}
+
+/// The Iterator over the ready Sources, returned by [`ReadySet::take`]
+pub struct Ready<'set> {
+    set: &'set ReadySet,
+    /// The Index of the next Word that will be taken from the Set
+    word: usize,
+    /// The Bits of the last taken Word, that have not been returned yet
+    bits: usize,
+}
+
+impl<'set> Iterator for Ready<'set> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.bits == 0 {
+            let word = self.set.words.get(self.word)?;
+            self.bits = word.swap(0, atomic::Ordering::SeqCst);
+            self.word += 1;
+        }
+
+        let bit = self.bits.trailing_zeros() as usize;
+        self.bits &= self.bits - 1;
+        Some((self.word - 1) * WORD_BITS + bit)
+    }
+}
+
+impl<'set> Drop for Ready<'set> {
+    fn drop(&mut self) {
+        if self.bits != 0 {
+            // The Consumer has not been woken up for these Bits yet, so they
+            // are simply put back without waking it
+            self.set.words[self.word - 1].fetch_or(self.bits, atomic::Ordering::SeqCst);
+        }
+    }
+}
+
+impl<'set> Debug for Ready<'set> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Ready ()")
+    }
+}
+
+/// The Future returned by [`ReadySet::wait`]
+///
+/// # Cancel Safety
+/// This Future is cancel safe, the ready Sources are only taken out of the
+/// Set by the returned [`Ready`] Iterator
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct WaitFuture<'set> {
+    set: &'set ReadySet,
+}
+
+#[cfg(feature = "async")]
+impl<'set> Future for WaitFuture<'set> {
+    type Output = Ready<'set>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let set = self.set;
+        if !set.is_empty() {
+            return Poll::Ready(set.take());
+        }
+
+        // Register first, so a concurrent `set` can not be missed
+        set.waker.register(cx.waker());
+        if !set.is_empty() {
+            return Poll::Ready(set.take());
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'set> Debug for WaitFuture<'set> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Wait-Future ()")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_take() {
+        let ready = ReadySet::new(WORD_BITS * 2 + 1);
+        assert!(ready.is_empty());
+
+        assert!(ready.set(WORD_BITS * 2));
+        assert!(ready.set(3));
+        assert!(!ready.set(3));
+        assert!(ready.set(WORD_BITS));
+        assert!(ready.is_set(3));
+
+        assert_eq!(
+            vec![3, WORD_BITS, WORD_BITS * 2],
+            ready.take().collect::<Vec<_>>()
+        );
+        assert!(ready.is_empty());
+        assert!(!ready.is_set(3));
+        assert_eq!(None, ready.take().next());
+    }
+
+    #[test]
+    fn dropped_iterator_keeps_remaining() {
+        let ready = ReadySet::new(WORD_BITS * 2);
+        ready.set(1);
+        ready.set(2);
+        ready.set(WORD_BITS + 1);
+
+        let mut taken = ready.take();
+        assert_eq!(Some(1), taken.next());
+        drop(taken);
+
+        assert_eq!(vec![2, WORD_BITS + 1], ready.take().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_out_of_range() {
+        let ready = ReadySet::new(4);
+        ready.set(4);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn wait_blocking_wakeup() {
+        let ready = alloc::sync::Arc::new(ReadySet::new(4));
+
+        let consumer = {
+            let ready = ready.clone();
+            std::thread::spawn(move || ready.wait_blocking().collect::<Vec<_>>())
+        };
+
+        // Give the Consumer Time to park its Thread
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        ready.set(2);
+        assert_eq!(vec![2], consumer.join().unwrap());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn wait_wakes_task() {
+        let ready = alloc::sync::Arc::new(ReadySet::new(4));
+
+        let handle = {
+            let ready = ready.clone();
+            tokio::spawn(async move { ready.wait().await.collect::<Vec<_>>() })
+        };
+
+        // Let the Task register its Waker, before setting anything
+        tokio::task::yield_now().await;
+        ready.set(0);
+
+        let result = tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+            .await
+            .expect("The Consumer was not woken up")
+            .unwrap();
+        assert_eq!(vec![0], result);
+    }
+}
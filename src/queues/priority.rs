@@ -0,0 +1,555 @@
+//! A concurrent lock-free Priority-Queue based on a Skiplist
+//!
+//! # Design
+//! The Queue is the SkipQueue described in "The Art of Multiprocessor
+//! Programming", which is built on top of a lock-free Skiplist that stores
+//! all the Elements sorted by their Priority.
+//! Inserting an Element simply inserts it into the Skiplist, while removing
+//! the Element with the smallest Priority walks the lowest Level of the
+//! Skiplist and claims the first Element that has not been claimed by any
+//! other Thread yet, before it is physically removed from the Skiplist.
+//!
+//! Elements with the same Priority are returned in the Order in which they
+//! were inserted.
+//!
+//! Removed Elements are reclaimed using [`Hyaline`](crate::hyaline), so the
+//! Queue can be used from any Number of Threads concurrently.
+//!
+//! # Consistency
+//! Like the original SkipQueue, this Queue is only quiescently consistent,
+//! meaning that [`pop_min`](PriorityQueue::pop_min) might return an Element
+//! that was inserted concurrently with it, even though an Element with a
+//! smaller Priority was inserted concurrently as well. Once all Operations
+//! have completed, the Queue behaves like a normal sequential Priority-Queue.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::priority::PriorityQueue;
+//! let queue = PriorityQueue::new();
+//!
+//! queue.insert(3, "third");
+//! queue.insert(1, "first");
+//! queue.insert(2, "second");
+//!
+//! assert_eq!(Some((1, "first")), queue.pop_min());
+//! assert_eq!(Some((2, "second")), queue.pop_min());
+//! assert_eq!(Some((3, "third")), queue.pop_min());
+//! assert_eq!(None, queue.pop_min());
+//! ```
+//!
+//! # Reference
+//! * [The Art of Multiprocessor Programming](https://dl.acm.org/doi/book/10.5555/2385452)
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{cell::UnsafeCell, fmt::Debug, mem::ManuallyDrop};
+
+use crate::{hyaline, sync::atomic};
+
+/// The maximum Height of a Node in the Skiplist
+const MAX_LEVEL: usize = 24;
+
+/// The Bit in the Next-Ptr of a Node, that marks the Node as removed on that
+/// Level of the Skiplist
+const MARK: usize = 1;
+
+fn is_marked<N>(ptr: *mut N) -> bool {
+    ptr as usize & MARK != 0
+}
+fn marked<N>(ptr: *mut N) -> *mut N {
+    (ptr as usize | MARK) as *mut N
+}
+fn unmarked<N>(ptr: *mut N) -> *mut N {
+    (ptr as usize & !MARK) as *mut N
+}
+
+/// Derives a pseudo-random Height for a new Node, where every additional
+/// Level is half as likely as the previous one
+fn random_height(seed: u64) -> usize {
+    // SplitMix64 to spread the sequential Seeds over all the Bits
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+
+    (z.trailing_ones() as usize + 1).min(MAX_LEVEL)
+}
+
+struct Node<P, T> {
+    priority: P,
+    /// The Sequence-Number of the Insert, used to break ties between
+    /// Elements with the same Priority so that every Key is unique
+    seq: u64,
+    /// The Data is moved out by the Thread that claimed the Node
+    data: UnsafeCell<ManuallyDrop<T>>,
+    /// Whether the Node has been claimed by a `pop_min` Operation
+    taken: atomic::AtomicBool,
+    /// The Number of Operations, the inserting and the removing one, that
+    /// still need to be done with the Node before it can be retired
+    pending: atomic::AtomicU8,
+    next: Box<[atomic::AtomicPtr<Node<P, T>>]>,
+}
+
+impl<P, T> Node<P, T>
+where
+    P: Ord,
+{
+    fn boxed(priority: P, seq: u64, data: T, height: usize) -> Box<Self> {
+        Box::new(Self {
+            priority,
+            seq,
+            data: UnsafeCell::new(ManuallyDrop::new(data)),
+            taken: atomic::AtomicBool::new(false),
+            pending: atomic::AtomicU8::new(2),
+            next: (0..height)
+                .map(|_| atomic::AtomicPtr::new(core::ptr::null_mut()))
+                .collect(),
+        })
+    }
+
+    /// Checks if this Node is sorted before the given Key
+    fn is_before(&self, priority: &P, seq: u64) -> bool {
+        (&self.priority, self.seq) < (priority, seq)
+    }
+}
+
+/// The Predecessors and Successors of a Key on every Level of the Skiplist
+struct Position<P, T> {
+    preds: [*const atomic::AtomicPtr<Node<P, T>>; MAX_LEVEL],
+    succs: [*mut Node<P, T>; MAX_LEVEL],
+}
+
+impl<P, T> Position<P, T> {
+    fn new() -> Self {
+        Self {
+            preds: [core::ptr::null(); MAX_LEVEL],
+            succs: [core::ptr::null_mut(); MAX_LEVEL],
+        }
+    }
+}
+
+/// A concurrent lock-free Priority-Queue, that always returns the Element
+/// with the smallest Priority first, see the
+/// [module-level documentation](self) for more Details
+pub struct PriorityQueue<P, T> {
+    /// The Next-Ptrs of the Head of the Skiplist
+    head: Box<[atomic::AtomicPtr<Node<P, T>>]>,
+    /// The Sequence-Number for the next Insert
+    seq: atomic::AtomicU64,
+    instance: hyaline::Hyaline,
+}
+
+impl<P, T> Debug for PriorityQueue<P, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PriorityQueue ()")
+    }
+}
+
+unsafe impl<P, T> Send for PriorityQueue<P, T>
+where
+    P: Send + Sync,
+    T: Send,
+{
+}
+unsafe impl<P, T> Sync for PriorityQueue<P, T>
+where
+    P: Send + Sync,
+    T: Send,
+{
+}
+
+fn free_fn<P, T>(ptr: *const ()) {
+    // The Data has already been moved out by the Thread that claimed the
+    // Node, so only the Node itself is dropped here
+    let _ = unsafe { Box::from_raw(ptr as *mut Node<P, T>) };
+}
+
+impl<P, T> PriorityQueue<P, T>
+where
+    P: Ord,
+{
+    /// Creates a new empty Priority-Queue
+    pub fn new() -> Self {
+        Self {
+            head: (0..MAX_LEVEL)
+                .map(|_| atomic::AtomicPtr::new(core::ptr::null_mut()))
+                .collect(),
+            seq: atomic::AtomicU64::new(0),
+            instance: hyaline::Hyaline::new(free_fn::<P, T>),
+        }
+    }
+
+    /// Finds the Predecessors and Successors of the given Key on every Level
+    /// and physically removes all the marked Nodes it encounters on the way.
+    ///
+    /// The returned Successors are the first Nodes, that are not sorted
+    /// before the Key, on their respective Level.
+    ///
+    /// # Safety
+    /// The Caller needs to hold a Hyaline-Handle for the entire Time it uses
+    /// the returned Position
+    unsafe fn find(&self, priority: &P, seq: u64, position: &mut Position<P, T>) {
+        'retry: loop {
+            let mut pred: &[atomic::AtomicPtr<Node<P, T>>] = &self.head;
+
+            for level in (0..MAX_LEVEL).rev() {
+                let mut current = pred[level].load(atomic::Ordering::Acquire);
+                if is_marked(current) {
+                    // The Predecessor is being removed itself
+                    continue 'retry;
+                }
+
+                while !current.is_null() {
+                    // # Safety:
+                    // The Node was reachable after we entered Hyaline, so it
+                    // can not be freed before our Handle is dropped
+                    let current_node = unsafe { &*current };
+                    let succ = current_node.next[level].load(atomic::Ordering::Acquire);
+
+                    if is_marked(succ) {
+                        match pred[level].compare_exchange(
+                            current,
+                            unmarked(succ),
+                            atomic::Ordering::AcqRel,
+                            atomic::Ordering::Acquire,
+                        ) {
+                            Ok(_) => {
+                                current = unmarked(succ);
+                                continue;
+                            }
+                            Err(_) => continue 'retry,
+                        };
+                    }
+
+                    if !current_node.is_before(priority, seq) {
+                        break;
+                    }
+
+                    pred = &current_node.next;
+                    current = succ;
+                }
+
+                position.preds[level] = &pred[level];
+                position.succs[level] = current;
+            }
+
+            return;
+        }
+    }
+
+    /// Marks that either the inserting or the removing Operation is done with
+    /// the Node and retires it, if it was the last one.
+    ///
+    /// # Safety
+    /// Every Operation must only call this once per Node and the removing
+    /// Operation must only call this after it marked the Node on every Level
+    unsafe fn release(&self, node_ptr: *mut Node<P, T>, handle: &mut hyaline::Handle<'_>) {
+        let node = unsafe { &*node_ptr };
+        if node.pending.fetch_sub(1, atomic::Ordering::AcqRel) != 1 {
+            return;
+        }
+
+        // Both Operations are done, so the Node is marked on every Level and
+        // will not be linked into any further Levels, which means that it is
+        // unreachable once the `find` removed it from every Level
+        let mut position = Position::new();
+        unsafe {
+            self.find(&node.priority, node.seq, &mut position);
+            handle.retire(node_ptr as *const ());
+        }
+    }
+
+    /// Inserts the Data with the given Priority into the Queue
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::priority::PriorityQueue;
+    /// let queue = PriorityQueue::new();
+    ///
+    /// queue.insert(13, "data");
+    /// assert_eq!(Some((13, "data")), queue.pop_min());
+    /// ```
+    pub fn insert(&self, priority: P, data: T) {
+        let mut handle = self.instance.enter();
+
+        let seq = self.seq.fetch_add(1, atomic::Ordering::Relaxed);
+        let height = random_height(seq);
+        let node_ptr = Box::into_raw(Node::boxed(priority, seq, data, height));
+        // # Safety:
+        // The Node is only retired once this Operation called `release`
+        let node = unsafe { &*node_ptr };
+
+        let mut position = Position::new();
+        loop {
+            unsafe { self.find(&node.priority, seq, &mut position) };
+
+            node.next[0].store(position.succs[0], atomic::Ordering::Relaxed);
+            let pred = unsafe { &*position.preds[0] };
+            if pred
+                .compare_exchange(
+                    position.succs[0],
+                    node_ptr,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                break;
+            }
+        }
+
+        'levels: for level in 1..height {
+            loop {
+                let succ = position.succs[level];
+
+                // Only the removing Operation marks the Ptr, in which case we
+                // stop linking the Node into any further Levels
+                let current = node.next[level].load(atomic::Ordering::Acquire);
+                if is_marked(current)
+                    || (current != succ
+                        && node.next[level]
+                            .compare_exchange(
+                                current,
+                                succ,
+                                atomic::Ordering::AcqRel,
+                                atomic::Ordering::Acquire,
+                            )
+                            .is_err())
+                {
+                    break 'levels;
+                }
+
+                let pred = unsafe { &*position.preds[level] };
+                if pred
+                    .compare_exchange(
+                        succ,
+                        node_ptr,
+                        atomic::Ordering::AcqRel,
+                        atomic::Ordering::Relaxed,
+                    )
+                    .is_ok()
+                {
+                    break;
+                }
+
+                unsafe { self.find(&node.priority, seq, &mut position) };
+            }
+        }
+
+        unsafe { self.release(node_ptr, &mut handle) };
+    }
+
+    /// Removes the Element with the smallest Priority from the Queue
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::priority::PriorityQueue;
+    /// let queue = PriorityQueue::new();
+    ///
+    /// queue.insert(2, "second");
+    /// queue.insert(1, "first");
+    ///
+    /// assert_eq!(Some((1, "first")), queue.pop_min());
+    /// assert_eq!(Some((2, "second")), queue.pop_min());
+    /// assert_eq!(None, queue.pop_min());
+    /// ```
+    pub fn pop_min(&self) -> Option<(P, T)>
+    where
+        P: Clone,
+    {
+        let mut handle = self.instance.enter();
+
+        let mut current = self.head[0].load(atomic::Ordering::Acquire);
+        while !current.is_null() {
+            let node_ptr = unmarked(current);
+            // # Safety:
+            // The Node was reachable after we entered Hyaline, so it can not
+            // be freed before our Handle is dropped
+            let node = unsafe { &*node_ptr };
+
+            if !node.taken.load(atomic::Ordering::Acquire)
+                && node
+                    .taken
+                    .compare_exchange(
+                        false,
+                        true,
+                        atomic::Ordering::AcqRel,
+                        atomic::Ordering::Relaxed,
+                    )
+                    .is_ok()
+            {
+                // # Safety:
+                // We are the only Thread that claimed the Node, so we are the
+                // only one to ever access its Data
+                let data = unsafe { ManuallyDrop::take(&mut *node.data.get()) };
+                let priority = node.priority.clone();
+
+                for level in (0..node.next.len()).rev() {
+                    let mut next = node.next[level].load(atomic::Ordering::Acquire);
+                    while !is_marked(next) {
+                        match node.next[level].compare_exchange(
+                            next,
+                            marked(next),
+                            atomic::Ordering::AcqRel,
+                            atomic::Ordering::Acquire,
+                        ) {
+                            Ok(_) => break,
+                            Err(n) => next = n,
+                        };
+                    }
+                }
+
+                unsafe { self.release(node_ptr, &mut handle) };
+                return Some((priority, data));
+            }
+
+            // The Node may be removed concurrently, in which case its Next-Ptr
+            // is marked, but it still leads to the rest of the Level
+            current = unmarked(node.next[0].load(atomic::Ordering::Acquire));
+        }
+
+        None
+    }
+
+    /// Checks if there are currently no Elements in the Queue
+    pub fn is_empty(&self) -> bool {
+        let _handle = self.instance.enter();
+
+        let mut current = self.head[0].load(atomic::Ordering::Acquire);
+        while !current.is_null() {
+            let node = unsafe { &*current };
+            if !node.taken.load(atomic::Ordering::Acquire) {
+                return false;
+            }
+            current = unmarked(node.next[0].load(atomic::Ordering::Acquire));
+        }
+
+        true
+    }
+}
+
+impl<P, T> Default for PriorityQueue<P, T>
+where
+    P: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P, T> Drop for PriorityQueue<P, T> {
+    fn drop(&mut self) {
+        // Every Node that is still linked has not been claimed, because every
+        // claimed Node has been removed from the Skiplist before the
+        // claiming Operation returned
+        let mut nodes = Vec::new();
+        let mut current = unmarked(self.head[0].load(atomic::Ordering::Acquire));
+        while !current.is_null() {
+            nodes.push(current);
+            current = unmarked(unsafe { &*current }.next[0].load(atomic::Ordering::Acquire));
+        }
+
+        for node_ptr in nodes {
+            let mut node = unsafe { Box::from_raw(node_ptr) };
+            unsafe { ManuallyDrop::drop(node.data.get_mut()) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::sync::Arc;
+
+    #[test]
+    fn sorted() {
+        let queue = PriorityQueue::new();
+
+        for i in [5, 3, 8, 1, 9, 2, 7, 4, 6, 0] {
+            queue.insert(i, i * 10);
+        }
+
+        for i in 0..10 {
+            assert_eq!(Some((i, i * 10)), queue.pop_min());
+        }
+        assert_eq!(None, queue.pop_min());
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn same_priority_fifo() {
+        let queue = PriorityQueue::new();
+
+        queue.insert(1, "a");
+        queue.insert(0, "first");
+        queue.insert(1, "b");
+        queue.insert(1, "c");
+
+        assert_eq!(Some((0, "first")), queue.pop_min());
+        assert_eq!(Some((1, "a")), queue.pop_min());
+        assert_eq!(Some((1, "b")), queue.pop_min());
+        assert_eq!(Some((1, "c")), queue.pop_min());
+    }
+
+    #[test]
+    fn drops_remaining() {
+        let data = Arc::new(());
+
+        let queue = PriorityQueue::new();
+        for i in 0..100 {
+            queue.insert(i, data.clone());
+        }
+        for _ in 0..50 {
+            queue.pop_min().unwrap();
+        }
+        assert_eq!(51, Arc::strong_count(&data));
+
+        drop(queue);
+        assert_eq!(1, Arc::strong_count(&data));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn concurrent() {
+        let queue = Arc::new(PriorityQueue::new());
+
+        let producers: Vec<_> = (0..4u64)
+            .map(|t| {
+                let queue = queue.clone();
+                std::thread::spawn(move || {
+                    for i in 0..1000 {
+                        queue.insert(i * 4 + t, ());
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..4)
+            .map(|_| {
+                let queue = queue.clone();
+                std::thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for _ in 0..10_000 {
+                        if let Some((p, _)) = queue.pop_min() {
+                            popped.push(p);
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        for handle in producers {
+            handle.join().unwrap();
+        }
+        let mut popped: Vec<_> = consumers
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        while let Some((p, _)) = queue.pop_min() {
+            popped.push(p);
+        }
+
+        popped.sort_unstable();
+        assert_eq!((0..4000).collect::<Vec<_>>(), popped);
+    }
+}
@@ -0,0 +1,436 @@
+//! Distributes the Elements across multiple independent Jiffy-Queues.
+//!
+//! A single [`jiffy`] Queue has a single Consumer, which needs to scan the
+//! Buffers of the Queue for the next Element and can therefore become the
+//! Bottleneck if a lot of Producers enqueue Elements at the same Time.
+//! Splitting the Work across multiple Shards, which are independent Queues,
+//! spreads the Load and allows every Shard to be consumed by a different
+//! Worker, using [`Receiver::into_shards`].
+//!
+//! # Distribution
+//! The [`Sender`] can be cloned and picks the Shard for every Element either
+//! * in a Round-Robin Fashion, using [`Sender::enqueue`]
+//! * based on the Hash of a Key, using [`Sender::enqueue_by`], so that all
+//!   the Elements with the same Key end up in the same Shard and are
+//!   therefore dequeued in the Order they were enqueued in
+//! * explicitly, using [`Sender::enqueue_to`]
+//!
+//! # Ordering
+//! The Elements are only ordered within a single Shard, so Elements that
+//! were enqueued on different Shards can be dequeued in any Order.
+//!
+//! # Example
+//! ```
+//! use nolock::queues::sharded;
+//!
+//! let (tx, mut rx) = sharded::channel::<usize>(4);
+//!
+//! let tx2 = tx.clone();
+//! tx.enqueue(1).unwrap();
+//! tx2.enqueue(2).unwrap();
+//!
+//! let mut received = [rx.try_dequeue().unwrap(), rx.try_dequeue().unwrap()];
+//! received.sort_unstable();
+//! assert_eq!([1, 2], received);
+//! ```
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+};
+
+use crate::{sync::native::atomic, utils::Backoff};
+
+use super::{mpsc::jiffy, DequeueError, EnqueueError};
+
+/// The State shared between all the Senders
+struct Shared<T> {
+    /// The Senders of the individual Shards
+    shards: Box<[jiffy::Sender<T>]>,
+    /// The Counter used to pick the next Shard for Round-Robin Enqueues
+    next: atomic::AtomicUsize,
+}
+
+/// The Sending-Half of the sharded Queue, which can be cloned to get
+/// multiple Senders for the same Queue
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// The Receiving-Half of the sharded Queue
+pub struct Receiver<T> {
+    /// The Receivers of the individual Shards
+    shards: Vec<jiffy::Receiver<T>>,
+    /// The Shard that will be checked first by the next Dequeue
+    next: usize,
+}
+
+/// A simple FNV-1a Hasher, which is used to pick the Shard for a Key and is
+/// available without the `std` Feature
+struct ShardHasher(u64);
+
+impl Default for ShardHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for ShardHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+impl<T> Sender<T> {
+    /// The Number of Shards in the Queue
+    pub fn shards(&self) -> usize {
+        self.shared.shards.len()
+    }
+
+    /// Checks if the Queue has been closed by the Receiver, which is the Case
+    /// once the Receivers of all the Shards have been dropped
+    ///
+    /// # Note
+    /// After [`Receiver::into_shards`], the Receivers of the individual
+    /// Shards can be dropped independently, so Enqueues on a Shard whose
+    /// Receiver was dropped may already fail while the Queue is not closed
+    pub fn is_closed(&self) -> bool {
+        self.shared.shards.iter().all(|shard| shard.is_closed())
+    }
+
+    /// Enqueues the Data on the next Shard, in a Round-Robin Fashion
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::sharded;
+    /// let (tx, mut rx) = sharded::channel::<usize>(2);
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// tx.enqueue(14).unwrap();
+    ///
+    /// assert_eq!(Ok(13), rx.try_dequeue_from(0));
+    /// assert_eq!(Ok(14), rx.try_dequeue_from(1));
+    /// ```
+    pub fn enqueue(&self, data: T) -> Result<(), (T, EnqueueError)> {
+        let shard = self.shared.next.fetch_add(1, atomic::Ordering::Relaxed);
+        self.enqueue_to(shard, data)
+    }
+
+    /// Enqueues the Data on the Shard with the given Index, which wraps
+    /// around if it is larger than the Number of Shards
+    pub fn enqueue_to(&self, shard: usize, data: T) -> Result<(), (T, EnqueueError)> {
+        let shards = &self.shared.shards;
+        shards[shard % shards.len()].enqueue(data)
+    }
+
+    /// Enqueues the Data on the Shard picked by the Hash of the given Key, so
+    /// all the Elements with the same Key are enqueued on the same Shard
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::sharded;
+    /// let (tx, mut rx) = sharded::channel::<(&str, usize)>(4);
+    ///
+    /// let shard = tx.shard_for(&"first");
+    /// tx.enqueue_by(&"first", ("first", 1)).unwrap();
+    /// tx.enqueue_by(&"first", ("first", 2)).unwrap();
+    ///
+    /// assert_eq!(Ok(("first", 1)), rx.try_dequeue_from(shard));
+    /// assert_eq!(Ok(("first", 2)), rx.try_dequeue_from(shard));
+    /// ```
+    pub fn enqueue_by<K>(&self, key: &K, data: T) -> Result<(), (T, EnqueueError)>
+    where
+        K: Hash + ?Sized,
+    {
+        self.enqueue_to(self.shard_for(key), data)
+    }
+
+    /// Returns the Index of the Shard that Elements enqueued with the given
+    /// Key using [`enqueue_by`](Self::enqueue_by) are stored in
+    pub fn shard_for<K>(&self, key: &K) -> usize
+    where
+        K: Hash + ?Sized,
+    {
+        let mut hasher = ShardHasher::default();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.shared.shards.len() as u64) as usize
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Sender ()")
+    }
+}
+
+impl<T> Receiver<T> {
+    /// The Number of Shards in the Queue
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Checks if the Queue has been closed by the Senders
+    ///
+    /// # Note
+    /// Just like for the individual Queues, there may still be Elements left
+    /// in the Shards, even if the Queue has been closed
+    pub fn is_closed(&self) -> bool {
+        self.shards.iter().all(|shard| shard.is_closed())
+    }
+
+    /// Attempts to dequeue an Element from any of the Shards.
+    ///
+    /// The Shards are checked in a Round-Robin Fashion, starting after the
+    /// Shard the last Element was dequeued from, so a single busy Shard can
+    /// not starve the other ones.
+    ///
+    /// # Returns
+    /// [`DequeueError::Closed`] is only returned once all the Shards have
+    /// been closed and are empty
+    pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        let count = self.shards.len();
+
+        let mut closed = 0;
+        for offset in 0..count {
+            let index = (self.next + offset) % count;
+            match self.shards[index].try_dequeue() {
+                Ok(data) => {
+                    self.next = (index + 1) % count;
+                    return Ok(data);
+                }
+                Err(DequeueError::Closed | DequeueError::Cancelled) => closed += 1,
+                Err(DequeueError::Empty) => {}
+            };
+        }
+
+        if closed == count {
+            Err(DequeueError::Closed)
+        } else {
+            Err(DequeueError::Empty)
+        }
+    }
+
+    /// Attempts to dequeue an Element from the Shard with the given Index,
+    /// which wraps around if it is larger than the Number of Shards
+    pub fn try_dequeue_from(&mut self, shard: usize) -> Result<T, DequeueError> {
+        let count = self.shards.len();
+        self.shards[shard % count].try_dequeue()
+    }
+
+    /// This is a simple blocking dequeue, which spins, with an exponential
+    /// [`Backoff`], until an Element could be dequeued from any of the
+    /// Shards.
+    ///
+    /// # Behaviour
+    /// Returns `Some(data)` once an Element was dequeued or `None` once all
+    /// the Shards have been closed and are empty
+    pub fn dequeue(&mut self) -> Option<T> {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_dequeue() {
+                Ok(data) => return Some(data),
+                Err(DequeueError::Empty) => backoff.snooze(),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+            };
+        }
+    }
+
+    /// Splits the Receiver into the Receivers of the individual Shards, so
+    /// that every Shard can be consumed by a different Worker
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::sharded;
+    /// let (tx, rx) = sharded::channel::<usize>(2);
+    ///
+    /// tx.enqueue_to(1, 13).unwrap();
+    ///
+    /// let mut shards = rx.into_shards();
+    /// assert_eq!(Ok(13), shards[1].try_dequeue());
+    /// ```
+    pub fn into_shards(self) -> Vec<jiffy::Receiver<T>> {
+        self.shards
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Receiver ()")
+    }
+}
+
+/// Creates a new empty Queue with the given Number of Shards and returns the
+/// Halves as ([`Sender`], [`Receiver`])
+///
+/// # Panics
+/// If the Number of Shards is zero
+///
+/// # Example
+/// ```
+/// # use nolock::queues::sharded;
+/// let (tx, mut rx) = sharded::channel::<usize>(4);
+/// assert_eq!(4, tx.shards());
+///
+/// tx.enqueue(13).unwrap();
+/// drop(tx);
+///
+/// assert_eq!(Some(13), rx.dequeue());
+/// assert_eq!(None, rx.dequeue());
+/// ```
+pub fn channel<T>(shards: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(shards > 0, "A sharded Queue needs at least one Shard");
+
+    let (txs, rxs): (Vec<_>, Vec<_>) = (0..shards).map(|_| jiffy::channel()).unzip();
+
+    let tx = Sender {
+        shared: Arc::new(Shared {
+            shards: txs.into_boxed_slice(),
+            next: atomic::AtomicUsize::new(0),
+        }),
+    };
+    let rx = Receiver {
+        shards: rxs,
+        next: 0,
+    };
+    (tx, rx)
+}
+
+crate::queues::adapter::impl_receiver!(Receiver<T>);
+crate::queues::dynamic::impl_sender!(Sender<T>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_robin() {
+        let (tx, mut rx) = channel::<usize>(3);
+
+        for i in 0..6 {
+            tx.enqueue(i).unwrap();
+        }
+
+        assert_eq!(Ok(0), rx.try_dequeue_from(0));
+        assert_eq!(Ok(3), rx.try_dequeue_from(0));
+        assert_eq!(Ok(1), rx.try_dequeue_from(1));
+        assert_eq!(Ok(2), rx.try_dequeue_from(2));
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue_from(0));
+    }
+
+    #[test]
+    fn same_key_same_shard() {
+        let (tx, rx) = channel::<usize>(8);
+
+        for i in 0..16 {
+            tx.enqueue_by("key", i).unwrap();
+        }
+
+        let mut shards = rx.into_shards();
+        let shard = &mut shards[tx.shard_for("key")];
+        for i in 0..16 {
+            assert_eq!(Ok(i), shard.try_dequeue());
+        }
+    }
+
+    #[test]
+    fn dequeue_services_all_shards() {
+        let (tx, mut rx) = channel::<usize>(2);
+
+        // A busy Shard should not starve the other one
+        for i in 0..4 {
+            tx.enqueue_to(0, i).unwrap();
+        }
+        tx.enqueue_to(1, 100).unwrap();
+
+        assert_eq!(Ok(0), rx.try_dequeue());
+        assert_eq!(Ok(100), rx.try_dequeue());
+        assert_eq!(Ok(1), rx.try_dequeue());
+    }
+
+    #[test]
+    fn closed_once_all_empty() {
+        let (tx, mut rx) = channel::<usize>(2);
+        let tx2 = tx.clone();
+
+        tx.enqueue_to(1, 13).unwrap();
+        drop(tx);
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue_from(0));
+        assert!(!rx.is_closed());
+
+        drop(tx2);
+        assert!(rx.is_closed());
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+    }
+
+    #[test]
+    fn receiver_dropped() {
+        let (tx, rx) = channel::<usize>(2);
+        drop(rx);
+
+        assert!(tx.is_closed());
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue(13));
+    }
+
+    #[test]
+    fn closed_once_all_shards_dropped() {
+        let (tx, rx) = channel::<usize>(3);
+
+        let mut shards = rx.into_shards();
+        let last = shards.pop().unwrap();
+        drop(shards);
+
+        // The last Shard still accepts Elements
+        assert!(!tx.is_closed());
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue_to(0, 13));
+        assert_eq!(Ok(()), tx.enqueue_to(2, 14));
+
+        drop(last);
+        assert!(tx.is_closed());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn concurrent_producers() {
+        let (tx, mut rx) = channel::<usize>(4);
+
+        let producers: Vec<_> = (0..4)
+            .map(|p| {
+                let tx = tx.clone();
+                std::thread::spawn(move || {
+                    for i in 0..1000 {
+                        tx.enqueue(p * 1000 + i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut received = Vec::new();
+        while let Some(data) = rx.dequeue() {
+            received.push(data);
+        }
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        received.sort_unstable();
+        assert_eq!((0..4000).collect::<Vec<_>>(), received);
+    }
+}
@@ -0,0 +1,134 @@
+//! Reports how many Elements were lost when a Queue was shut down
+//!
+//! Once the Receiver of a Queue has been dropped, all the Elements that are
+//! still left in the Queue are dropped together with it, once the last Handle
+//! is gone. A [`ShutdownReport`] can be obtained from any Handle of the
+//! supported Queues before that happens and outlives the Queue, so the Number
+//! of lost Elements can still be checked afterwards, for example for
+//! Auditing.
+//!
+//! The Report is supported by the [`spsc::bounded`](super::spsc::bounded),
+//! [`spsc::unbounded`](super::spsc::unbounded),
+//! [`mpsc::jiffy`](super::mpsc::jiffy) and, with the `hyaline` Feature,
+//! the `mpmc::unbounded` Queues.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::mpsc::jiffy;
+//! let (rx, tx) = jiffy::queue::<usize>();
+//! let report = tx.shutdown_report();
+//!
+//! tx.enqueue(13).unwrap();
+//! tx.enqueue(14).unwrap();
+//!
+//! // The Queue still exists
+//! drop(rx);
+//! assert_eq!(None, report.closed_with_remaining());
+//!
+//! // The Queue has been shut down with 2 Elements still in it
+//! drop(tx);
+//! assert_eq!(Some(2), report.closed_with_remaining());
+//! ```
+
+use alloc::sync::Arc;
+use core::fmt::Debug;
+
+use crate::sync::native::atomic;
+
+/// The State shared between a Queue and all of its Reports
+struct State {
+    /// The Number of Elements that were dropped together with the Queue
+    dropped: atomic::AtomicUsize,
+    /// Whether the Queue has been completely shut down
+    complete: atomic::AtomicBool,
+}
+
+/// A Report of how many Elements were still left in a Queue when it was shut
+/// down, see the [`module-level documentation`](self) for more Details
+#[derive(Clone)]
+pub struct ShutdownReport {
+    state: Arc<State>,
+}
+
+impl ShutdownReport {
+    /// Returns the Number of Elements that were still left in the Queue and
+    /// therefore dropped, once all the Handles of the Queue have been dropped
+    /// and its Memory was released, or `None` if the Queue still exists.
+    ///
+    /// # Note
+    /// Elements that were dequeued using `close_and_drain` are returned to
+    /// the Caller and therefore not counted as lost.
+    pub fn closed_with_remaining(&self) -> Option<usize> {
+        if !self.state.complete.load(atomic::Ordering::Acquire) {
+            return None;
+        }
+
+        Some(self.state.dropped.load(atomic::Ordering::Acquire))
+    }
+}
+
+impl Debug for ShutdownReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ShutdownReport ({:?})", self.closed_with_remaining())
+    }
+}
+
+/// The Part of the Report that is owned by the Queue itself, which counts the
+/// dropped Elements and completes the Report once the last Reference to it
+/// is released
+pub(crate) struct Shutdown {
+    state: Arc<State>,
+}
+
+impl Shutdown {
+    /// Creates the Shutdown-State for a new Queue
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(State {
+                dropped: atomic::AtomicUsize::new(0),
+                complete: atomic::AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Creates a new Report for the Queue
+    pub fn report(&self) -> ShutdownReport {
+        ShutdownReport {
+            state: self.state.clone(),
+        }
+    }
+
+    /// Records that the given Number of Elements are dropped, because they
+    /// were still left in the Queue
+    pub fn record_dropped(&self, count: usize) {
+        if count > 0 {
+            self.state
+                .dropped
+                .fetch_add(count, atomic::Ordering::AcqRel);
+        }
+    }
+}
+
+impl Drop for Shutdown {
+    fn drop(&mut self) {
+        self.state.complete.store(true, atomic::Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completes_on_drop() {
+        let shutdown = Shutdown::new();
+        let report = shutdown.report();
+
+        shutdown.record_dropped(2);
+        shutdown.record_dropped(0);
+        assert_eq!(None, report.closed_with_remaining());
+
+        drop(shutdown);
+        assert_eq!(Some(2), report.closed_with_remaining());
+    }
+}
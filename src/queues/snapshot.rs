@@ -0,0 +1,162 @@
+//! A Snapshot of the Elements that were stored in a Queue
+//!
+//! Every Queue-Receiver provides a `snapshot` Method, that dequeues all the
+//! Elements that are currently stored in the Queue and collects them into a
+//! [`Snapshot`]. This Snapshot can then be inspected, turned back into the
+//! Elements or, with the `serde` Feature enabled, be serialized to persist
+//! the Contents of the Queue.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::spsc::unbounded;
+//! let (mut tx, mut rx) = unbounded::channel::<usize>();
+//!
+//! tx.enqueue(13).unwrap();
+//! tx.enqueue(14).unwrap();
+//!
+//! let snapshot = rx.snapshot();
+//! assert_eq!(&[13, 14], snapshot.as_slice());
+//!
+//! // Restore the Elements into the Queue
+//! for data in snapshot {
+//!     tx.enqueue(data).unwrap();
+//! }
+//! assert_eq!(Ok(13), rx.try_dequeue());
+//! ```
+
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+
+use super::DequeueError;
+
+/// The Elements that were dequeued from a Queue, in the Order in which they
+/// were dequeued
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot<T> {
+    elements: Vec<T>,
+}
+
+impl<T> Snapshot<T> {
+    /// Creates a new Snapshot that contains the given Elements
+    pub fn new(elements: Vec<T>) -> Self {
+        Self { elements }
+    }
+
+    /// Dequeues Elements using the given Function, until it returns an
+    /// Error, meaning that the Queue is either empty or closed
+    pub(crate) fn drain<F>(mut dequeue: F) -> Self
+    where
+        F: FnMut() -> Result<T, DequeueError>,
+    {
+        let mut elements = Vec::new();
+        while let Ok(data) = dequeue() {
+            elements.push(data);
+        }
+
+        Self { elements }
+    }
+
+    /// The Number of Elements in the Snapshot
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Checks if the Snapshot contains no Elements
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the Elements of the Snapshot as a Slice
+    pub fn as_slice(&self) -> &[T] {
+        &self.elements
+    }
+
+    /// Returns the Elements of the Snapshot
+    pub fn into_vec(self) -> Vec<T> {
+        self.elements
+    }
+}
+
+impl<T> Default for Snapshot<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<T> From<Vec<T>> for Snapshot<T> {
+    fn from(elements: Vec<T>) -> Self {
+        Self::new(elements)
+    }
+}
+
+impl<T> FromIterator<T> for Snapshot<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl<T> IntoIterator for Snapshot<T> {
+    type Item = T;
+    type IntoIter = alloc::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for Snapshot<T>
+where
+    T: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.elements.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for Snapshot<T>
+where
+    T: serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drain_until_empty() {
+        let mut remaining = alloc::vec![13, 14];
+        let snapshot = Snapshot::drain(|| {
+            if remaining.is_empty() {
+                Err(DequeueError::Empty)
+            } else {
+                Ok(remaining.remove(0))
+            }
+        });
+
+        assert_eq!(&[13, 14], snapshot.as_slice());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        let snapshot: Snapshot<usize> = Snapshot::new(alloc::vec![13, 14, 15]);
+
+        let serialized = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!("[13,14,15]", serialized);
+
+        let deserialized: Snapshot<usize> = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(snapshot, deserialized);
+    }
+}
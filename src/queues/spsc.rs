@@ -11,7 +11,14 @@
 //! enough for most use-cases, however since this Queue is unbounded it has
 //! a broader range of applications as it can "grow" as needed without
 //! having to sacrifice a lot of performance.
+//!
+//! # Bytes
+//! The Byte-Queue is a bounded Ring-Buffer of Bytes, which transfers Chunks
+//! of Bytes instead of individual Elements and also allows for reading and
+//! writing the Ring-Buffer directly without copying the Data.
 
 pub mod bounded;
 
+pub mod bytes;
+
 pub mod unbounded;
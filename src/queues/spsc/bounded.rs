@@ -13,40 +13,94 @@
 //! assert_eq!(Ok(13), rx.try_dequeue());
 //! ```
 //!
+//! # Const-Capacity
+//! If the Capacity is known at compile-time, [`const_queue`] can be used
+//! instead, which stores the Buffer in an Array of the given Size.
+//!
+//! # Overwriting
+//! If newer Elements are more important than older ones, like for Telemetry,
+//! [`overwriting_queue`] creates a Queue that overwrites its oldest Element
+//! once it is full, instead of rejecting the new one.
+//!
 //! # Reference:
 //! * [FastForward for Efficient Pipeline Parallelism - A Cache-Optimized Concurrent Lock-Free Queue](https://www.researchgate.net/publication/213894711_FastForward_for_Efficient_Pipeline_Parallelism_A_Cache-Optimized_Concurrent_Lock-Free_Queue)
 
 use alloc::{sync::Arc, vec::Vec};
-use core::{fmt::Debug, sync::atomic};
+use core::fmt::Debug;
 
-use crate::queues::{DequeueError, EnqueueError};
+use crate::sync::native::atomic;
+
+#[cfg(feature = "async")]
+use crate::queues::cooperative::{self, CooperativeDequeue, CooperativeEnqueue};
+use crate::{
+    queues::{
+        cancel::{self, CancelToken},
+        instrument::Metrics,
+        shutdown::{Shutdown, ShutdownReport},
+        DequeueError, EnqueueError,
+    },
+    utils::Backoff,
+};
 
 #[cfg(feature = "async")]
 mod async_queue;
 #[cfg(feature = "async")]
 pub use async_queue::*;
 
+mod buffer;
+use buffer::Buffer;
+
 mod node;
-use node::Node;
+
+mod const_queue;
+pub use const_queue::{const_queue, ConstBoundedReceiver, ConstBoundedSender};
+
+mod overwriting;
+pub use overwriting::{overwriting_queue, OverwritingReceiver, OverwritingSender};
+
+/// An Alias for the [`BoundedSender`], using the same Name as the Sending-Half
+/// of the other Queues
+pub type Sender<T> = BoundedSender<T>;
+/// An Alias for the [`BoundedReceiver`], using the same Name as the
+/// Receiving-Half of the other Queues
+pub type Receiver<T> = BoundedReceiver<T>;
 
 /// The Sending-Half for the queue
 pub struct BoundedSender<T> {
     /// Indicates if the Queue has been closed or not
     closed: Arc<atomic::AtomicBool>,
+    /// Set while the Producer is storing an Element, so that the Consumer
+    /// can wait for it to finish, when closing the Queue
+    enqueuing: Arc<atomic::AtomicBool>,
     /// The Index of the next Node to read in the Buffer
     head: usize,
     /// The underlying Buffer of Nodes
-    buffer: Arc<Vec<Node<T>>>,
+    buffer: Arc<Buffer<T>>,
+    /// The Hooks to report the Operations to
+    metrics: Metrics,
+    /// The Wakers of both Halves, when they are async, and the Number of
+    /// sync Halves, that will not wake them up
+    #[cfg(feature = "async")]
+    shared: Arc<async_queue::Shared>,
 }
 
 /// The Receiving-Half for the Queue
 pub struct BoundedReceiver<T> {
     /// Indicates if the Queue has been closed or not
     closed: Arc<atomic::AtomicBool>,
+    /// Set while the Producer is storing an Element, so that the Consumer
+    /// can wait for it to finish, when closing the Queue
+    enqueuing: Arc<atomic::AtomicBool>,
     /// The Index of the next Node to store Data into
     tail: usize,
     /// The underlying Buffer of Nodes
-    buffer: Arc<Vec<Node<T>>>,
+    buffer: Arc<Buffer<T>>,
+    /// The Hooks to report the Operations to
+    metrics: Metrics,
+    /// The Wakers of both Halves, when they are async, and the Number of
+    /// sync Halves, that will not wake them up
+    #[cfg(feature = "async")]
+    shared: Arc<async_queue::Shared>,
 }
 
 /// Calculates the Index of the next Element in the Buffer and wraps around
@@ -82,6 +136,13 @@ impl<T> BoundedSender<T> {
         self.closed.load(atomic::Ordering::Acquire)
     }
 
+    /// Returns a Report, that tells how many Elements were still left in the
+    /// Queue once it has been shut down, see the
+    /// [`shutdown`](crate::queues::shutdown) module for more Details
+    pub fn shutdown_report(&self) -> ShutdownReport {
+        self.buffer.shutdown.report()
+    }
+
     /// Attempts to Enqueue the given piece of Data
     ///
     /// # Example:
@@ -115,7 +176,7 @@ impl<T> BoundedSender<T> {
     /// # drop(rx);
     /// ```
     pub fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
-        if self.is_closed() {
+        if !self.begin_enqueue() {
             return Err((data, EnqueueError::Closed));
         }
 
@@ -127,12 +188,16 @@ impl<T> BoundedSender<T> {
         // store the new Element, meaning that the Buffer is full and we should
         // Error out indicating this
         if buffer_entry.is_set() {
+            self.end_enqueue();
+            self.metrics.full();
             return Err((data, EnqueueError::Full));
         }
 
         // The Node is not already set meaning that we can simply store the
         // given Data into the Node
         buffer_entry.store(data);
+        self.end_enqueue();
+        self.metrics.enqueue();
 
         // Advance the current Head, where we insert the Elements, onto the
         // next Position
@@ -143,21 +208,190 @@ impl<T> BoundedSender<T> {
     }
 
     /// A blocking enqueue Operation. This is obviously not lock-free anymore
-    /// and will simply spin while trying to enqueue the Data until it works
+    /// and will simply spin, with an exponential [`Backoff`], while trying to
+    /// enqueue the Data until it works
+    ///
+    /// # Async
+    /// This blocks the current Thread and therefore should not be called from
+    /// async Code, use [`enqueue_cooperative`](Self::enqueue_cooperative)
+    /// instead
     pub fn enqueue(&mut self, mut data: T) -> Result<(), (T, EnqueueError)> {
+        let backoff = Backoff::new();
         loop {
             match self.try_enqueue(data) {
                 Ok(_) => return Ok(()),
                 Err((d, e)) => match e {
                     EnqueueError::Full => {
                         data = d;
+                        backoff.snooze();
                     }
-                    EnqueueError::Closed => return Err((d, EnqueueError::Closed)),
+                    EnqueueError::Closed
+                    | EnqueueError::NoSpace
+                    | EnqueueError::AllocFailed
+                    | EnqueueError::Cancelled => return Err((d, e)),
                 },
             };
         }
     }
 
+    /// A blocking enqueue operation like [`enqueue`](Self::enqueue), which
+    /// can additionally be cancelled from another Thread using the given
+    /// [`CancelToken`], see the [`cancel`](crate::queues::cancel) module for
+    /// more Details
+    ///
+    /// # Returns
+    /// * `Ok(())` once the Data could be enqueued
+    /// * `Err((data, EnqueueError::Closed))` if the Queue has been closed
+    /// * `Err((data, EnqueueError::Cancelled))` if the Token was cancelled
+    ///   while the Queue was full
+    pub fn enqueue_cancellable(
+        &mut self,
+        data: T,
+        token: &CancelToken,
+    ) -> Result<(), (T, EnqueueError)> {
+        cancel::enqueue(token, data, |data| self.try_enqueue(data))
+    }
+
+    /// The async-friendly Variant of [`enqueue`](Self::enqueue), which yields
+    /// back to the Executor in between Attempts instead of blocking the
+    /// Thread, see the [`cooperative`](crate::queues::cooperative) module for
+    /// more Details
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// async fn demo() {
+    ///     let (mut rx, mut tx) = bounded::queue::<usize>(1);
+    ///
+    ///     tx.enqueue_cooperative(13).await.unwrap();
+    ///     assert_eq!(Some(13), rx.dequeue_cooperative().await);
+    /// }
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn enqueue_cooperative(
+        &mut self,
+        data: T,
+    ) -> CooperativeEnqueue<T, impl FnMut(T) -> Result<(), (T, EnqueueError)> + '_> {
+        cooperative::enqueue(data, move |d| self.try_enqueue(d))
+    }
+
+    /// Attempts to Enqueue as many Elements from the given Slice as there is
+    /// currently room for, in Order, and returns the Number of Elements that
+    /// were enqueued.
+    ///
+    /// If the whole Batch fits into the Queue, only a single Node needs to be
+    /// checked for being free, instead of checking every Node individually.
+    ///
+    /// # Note
+    /// If the Queue has been closed, nothing will be enqueued and this
+    /// returns `0`, which can be distinguished from a full Queue using
+    /// [`is_closed`](Self::is_closed).
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// let (mut rx, mut tx) = bounded::queue::<usize>(4);
+    ///
+    /// assert_eq!(3, tx.try_enqueue_slice(&[1, 2, 3]));
+    /// // Only one more Element fits into the Queue
+    /// assert_eq!(1, tx.try_enqueue_slice(&[4, 5]));
+    ///
+    /// assert_eq!(Ok(1), rx.try_dequeue());
+    /// ```
+    pub fn try_enqueue_slice(&mut self, data: &[T]) -> usize
+    where
+        T: Clone,
+    {
+        let length = self.buffer.len();
+        let count = data.len().min(length);
+        if count == 0 || !self.begin_enqueue() {
+            return 0;
+        }
+
+        // The Consumer empties the Nodes in Order, so if the last Node of the
+        // Batch is already free, all the Nodes before it are free as well
+        let last_index = (self.head + count - 1) % length;
+        if self.buffer[last_index].is_set() {
+            self.end_enqueue();
+            return data
+                .iter()
+                .take_while(|item| self.try_enqueue((*item).clone()).is_ok())
+                .count();
+        }
+
+        for item in &data[..count] {
+            // # Safety:
+            // The Head is always kept in the Range `0..length`
+            let buffer_entry = unsafe { self.buffer.get_unchecked(self.head) };
+            buffer_entry.store(item.clone());
+
+            self.head = next_element(self.head, length);
+        }
+        self.end_enqueue();
+        self.metrics.enqueue_many(count);
+
+        count
+    }
+
+    /// Announces that the Producer is about to store Elements into the
+    /// Buffer, which fails if the Queue has already been closed.
+    ///
+    /// Together with [`BoundedReceiver::close`] this ensures that either the
+    /// Consumer sees the Elements, when draining the Queue after closing it,
+    /// or the Producer sees that the Queue has been closed
+    #[inline(always)]
+    fn begin_enqueue(&self) -> bool {
+        self.enqueuing.store(true, atomic::Ordering::SeqCst);
+        if self.closed.load(atomic::Ordering::SeqCst) {
+            self.end_enqueue();
+            return false;
+        }
+        true
+    }
+
+    /// Marks the End of the Stores started with
+    /// [`begin_enqueue`](Self::begin_enqueue)
+    #[inline(always)]
+    fn end_enqueue(&self) {
+        self.enqueuing.store(false, atomic::Ordering::Release);
+    }
+
+    /// Fills the Queue with all the Elements from the Iterator, before the
+    /// Receiver has been shared with another Thread, so no Node needs to be
+    /// checked for being free
+    ///
+    /// # Panics
+    /// If the Iterator yields more Elements than the Capacity of the Queue
+    fn prefill<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let length = self.buffer.len();
+
+        let mut count = 0;
+        for item in iter {
+            assert!(
+                count < length,
+                "The Iterator yielded more Elements than the Capacity ({}) of the Queue",
+                length
+            );
+
+            // # Safety:
+            // The Head is always kept in the Range `0..length`
+            let buffer_entry = unsafe { self.buffer.get_unchecked(self.head) };
+            buffer_entry.store(item);
+
+            self.head = next_element(self.head, length);
+            count += 1;
+        }
+        self.metrics.enqueue_many(count);
+    }
+
     /// Checks if the current Queue is full
     pub fn is_full(&self) -> bool {
         // If the Node where we would insert the next Element is already set
@@ -165,6 +399,66 @@ impl<T> BoundedSender<T> {
         // Queue, meaning that the Queue is currently full
         self.buffer[self.head].is_set()
     }
+
+    /// Returns the current Capacity of the Queue
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Grows the Queue to the given Capacity, without having to drain and
+    /// rebuild the Queue. Does nothing if the Queue already has at least
+    /// the given Capacity.
+    ///
+    /// # Behaviour
+    /// All future Elements are enqueued into a new Buffer of the given
+    /// Capacity. The Consumer keeps dequeuing the Elements that are still
+    /// left in the old Buffer and only then moves on to the new one, so the
+    /// Order of the Elements is preserved. The old Buffer is freed once the
+    /// Consumer moved past it.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// # use nolock::queues::EnqueueError;
+    /// let (mut rx, mut tx) = bounded::queue::<usize>(1);
+    ///
+    /// tx.try_enqueue(13).unwrap();
+    /// assert_eq!(Err((14, EnqueueError::Full)), tx.try_enqueue(14));
+    ///
+    /// tx.grow(4);
+    /// assert_eq!(4, tx.capacity());
+    /// tx.try_enqueue(14).unwrap();
+    ///
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    /// assert_eq!(Ok(14), rx.try_dequeue());
+    /// ```
+    pub fn grow(&mut self, new_capacity: usize) {
+        if new_capacity <= self.buffer.len() {
+            return;
+        }
+
+        self.metrics.segment_alloc();
+        self.link_buffer(new_capacity);
+    }
+
+    /// Links a new empty Buffer with the same Capacity as the current one,
+    /// after which all future Elements are enqueued into the new Buffer.
+    ///
+    /// This is used by the unbounded Queue to append a new Segment, once the
+    /// current one is full, without reporting it to the Metrics
+    pub(crate) fn link_segment(&mut self) {
+        self.link_buffer(self.buffer.len());
+    }
+
+    fn link_buffer(&mut self, capacity: usize) {
+        let n_buffer = Arc::new(Buffer::new(capacity, self.buffer.shutdown.clone()));
+
+        // After linking the new Buffer, we must not store anything into the
+        // old one anymore, as the Consumer may already have moved on from it
+        self.buffer.set_next(n_buffer.clone());
+        self.buffer = n_buffer;
+        self.head = 0;
+    }
 }
 
 impl<T> Debug for BoundedSender<T> {
@@ -176,11 +470,19 @@ impl<T> Debug for BoundedSender<T> {
 impl<T> Drop for BoundedSender<T> {
     fn drop(&mut self) {
         self.closed.store(true, atomic::Ordering::Release);
+
+        // An async Receiver will see that the Queue has been closed, once it
+        // is woken up
+        #[cfg(feature = "async")]
+        self.shared.rx_waker.wake();
     }
 }
 
-unsafe impl<T> Send for BoundedSender<T> {}
-unsafe impl<T> Sync for BoundedSender<T> {}
+// Safety:
+// The Halves can only be used to move Elements between Threads, so they are
+// only Send and Sync if the Elements themselves can be send across Threads
+unsafe impl<T> Send for BoundedSender<T> where T: Send {}
+unsafe impl<T> Sync for BoundedSender<T> where T: Send {}
 
 impl<T> BoundedReceiver<T> {
     /// Checks if the Queue has been closed by the Producer
@@ -204,6 +506,27 @@ impl<T> BoundedReceiver<T> {
         self.closed.load(atomic::Ordering::Acquire)
     }
 
+    /// Returns a Report, that tells how many Elements were still left in the
+    /// Queue once it has been shut down, see the
+    /// [`shutdown`](crate::queues::shutdown) module for more Details
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// let (rx, mut tx) = bounded::queue::<usize>(4);
+    /// let report = rx.shutdown_report();
+    ///
+    /// tx.try_enqueue(13).unwrap();
+    /// drop(rx);
+    /// assert_eq!(None, report.closed_with_remaining());
+    ///
+    /// drop(tx);
+    /// assert_eq!(Some(1), report.closed_with_remaining());
+    /// ```
+    pub fn shutdown_report(&self) -> ShutdownReport {
+        self.buffer.shutdown.report()
+    }
+
     /// Attempts to Dequeue a single Element from the Queue
     ///
     /// # Example
@@ -239,12 +562,21 @@ impl<T> BoundedReceiver<T> {
         // If the Node is not set, we should return an Error as the Queue is
         // empty and there is nothing for us to return in this Operation
         if !buffer_entry.is_set() {
+            // The Producer may have grown the Queue and therefore moved on to
+            // a new Buffer, in which case we need to move on as well
+            if self.advance_buffer() {
+                return self.try_dequeue();
+            }
+
             // Check if the Queue has been marked as closed
             if self.is_closed() {
-                // We need to recheck the current Node, because it may have
-                // been set in the mean time and then the closed flag was
-                // updated
-                if !buffer_entry.is_set() {
+                // We need to recheck the current Node and Buffer, because they
+                // may have been updated in the mean time and then the closed
+                // flag was updated
+                if self.advance_buffer() {
+                    return self.try_dequeue();
+                }
+                if !self.buffer[self.tail].is_set() {
                     return Err(DequeueError::Closed);
                 }
             }
@@ -254,6 +586,7 @@ impl<T> BoundedReceiver<T> {
 
         // If the Node is set, we can load the Data out of the Node itself
         let data = buffer_entry.load();
+        self.metrics.dequeue();
 
         // Advance the current Tail, indicating where we should read the next
         // Element from, onto the next Node in the Buffer
@@ -264,25 +597,234 @@ impl<T> BoundedReceiver<T> {
     }
 
     /// A blocking dequeue operations. This is not lock-free anymore and simply
-    /// spins while trying to dequeue until it works.
+    /// spins, with an exponential [`Backoff`], while trying to dequeue until
+    /// it works.
+    ///
+    /// # Async
+    /// This blocks the current Thread and therefore should not be called from async
+    /// Code, use [`dequeue_cooperative`](Self::dequeue_cooperative) instead
     pub fn dequeue(&mut self) -> Option<T> {
+        let backoff = Backoff::new();
         loop {
             match self.try_dequeue() {
                 Ok(d) => return Some(d),
                 Err(e) => match e {
-                    DequeueError::Empty => {}
-                    DequeueError::Closed => return None,
+                    DequeueError::Empty => backoff.snooze(),
+                    DequeueError::Closed | DequeueError::Cancelled => return None,
                 },
             };
         }
     }
 
+    /// A blocking dequeue operation like [`dequeue`](Self::dequeue), which
+    /// can additionally be cancelled from another Thread using the given
+    /// [`CancelToken`], see the [`cancel`](crate::queues::cancel) module for
+    /// more Details
+    ///
+    /// # Returns
+    /// * `Ok(data)` once an Element could be dequeued
+    /// * `Err(DequeueError::Closed)` if the Queue has been closed
+    /// * `Err(DequeueError::Cancelled)` if the Token was cancelled while the
+    ///   Queue was empty
+    pub fn dequeue_cancellable(&mut self, token: &CancelToken) -> Result<T, DequeueError> {
+        cancel::dequeue(token, || self.try_dequeue())
+    }
+
+    /// The async-friendly Variant of [`dequeue`](Self::dequeue), which yields
+    /// back to the Executor in between Attempts instead of blocking the Thread,
+    /// see the [`cooperative`](crate::queues::cooperative) module for more
+    /// Details
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn dequeue_cooperative(
+        &mut self,
+    ) -> CooperativeDequeue<impl FnMut() -> Result<T, DequeueError> + '_> {
+        cooperative::dequeue(move || self.try_dequeue())
+    }
+
+    /// Dequeues all the Elements that are currently stored in the Queue and
+    /// collects them into a [`Snapshot`](crate::queues::snapshot::Snapshot),
+    /// without closing the Queue.
+    ///
+    /// Elements that are enqueued concurrently may or may not be part of the
+    /// Snapshot.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// let (mut rx, mut tx) = bounded::queue::<usize>(16);
+    ///
+    /// tx.try_enqueue(13).unwrap();
+    /// tx.try_enqueue(14).unwrap();
+    ///
+    /// assert_eq!(&[13, 14], rx.snapshot().as_slice());
+    /// assert!(rx.snapshot().is_empty());
+    /// ```
+    pub fn snapshot(&mut self) -> crate::queues::snapshot::Snapshot<T> {
+        crate::queues::snapshot::Snapshot::drain(|| self.try_dequeue())
+    }
+
+    /// Closes the Queue and returns all the Elements that were still left
+    /// in it, in the Order they were enqueued.
+    ///
+    /// After this, the Producer will no longer be able to enqueue any Data
+    /// and every Element it enqueued successfully before is returned here,
+    /// even if it was enqueued concurrently.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// # use nolock::queues::EnqueueError;
+    /// let (rx, mut tx) = bounded::queue::<usize>(16);
+    ///
+    /// tx.try_enqueue(13).unwrap();
+    /// tx.try_enqueue(14).unwrap();
+    ///
+    /// assert_eq!(vec![13, 14], rx.close_and_drain());
+    /// assert_eq!(Err((15, EnqueueError::Closed)), tx.try_enqueue(15));
+    /// ```
+    pub fn close_and_drain(mut self) -> Vec<T> {
+        self.close();
+
+        let mut result = Vec::new();
+        while let Ok(data) = self.try_dequeue() {
+            result.push(data);
+        }
+
+        result
+    }
+
+    /// Closes the Queue, after which the Producer will no longer be able to
+    /// enqueue any Data, while the remaining Elements can still be dequeued.
+    ///
+    /// This waits for an Enqueue, that is still storing its Element, to
+    /// finish, so every Element the Producer successfully enqueued is in
+    /// the Buffer once this returns
+    pub(crate) fn close(&self) {
+        self.closed.store(true, atomic::Ordering::SeqCst);
+
+        let backoff = Backoff::new();
+        while self.enqueuing.load(atomic::Ordering::SeqCst) {
+            backoff.snooze();
+        }
+    }
+
+    /// Attempts to Dequeue up to `max` Elements from the Queue and appends
+    /// them, in Order, to the given Vec. Returns the Number of Elements that
+    /// were dequeued.
+    ///
+    /// If at least `max` Elements are ready, only a single Node needs to be
+    /// checked for being set, instead of checking every Node individually.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// let (mut rx, mut tx) = bounded::queue::<usize>(8);
+    ///
+    /// tx.try_enqueue_slice(&[1, 2, 3, 4]);
+    ///
+    /// let mut result = Vec::new();
+    /// assert_eq!(3, rx.try_dequeue_into(&mut result, 3));
+    /// assert_eq!(1, rx.try_dequeue_into(&mut result, 3));
+    /// assert_eq!(vec![1, 2, 3, 4], result);
+    /// ```
+    pub fn try_dequeue_into(&mut self, target: &mut Vec<T>, max: usize) -> usize {
+        let length = self.buffer.len();
+        let count = max.min(length);
+        if count == 0 {
+            return 0;
+        }
+
+        // The Producer fills the Nodes in Order, so if the last Node of the
+        // Batch is already set, all the Nodes before it are set as well
+        let last_index = (self.tail + count - 1) % length;
+        if !self.buffer[last_index].is_set() {
+            let mut dequeued = 0;
+            while dequeued < count {
+                match self.try_dequeue() {
+                    Ok(data) => target.push(data),
+                    Err(_) => break,
+                };
+                dequeued += 1;
+            }
+            return dequeued;
+        }
+
+        target.reserve(count);
+        for _ in 0..count {
+            // # Safety:
+            // The Tail is always kept in the Range `0..length`
+            let buffer_entry = unsafe { self.buffer.get_unchecked(self.tail) };
+            target.push(buffer_entry.load());
+
+            self.tail = next_element(self.tail, length);
+        }
+        self.metrics.dequeue_many(count);
+
+        count
+    }
+
     /// Checks if the current queue is empty
     pub fn is_empty(&self) -> bool {
         // If the current Node where would dequeue the next Item from is not
         // marked as being set, the Node contains no `set` Nodes and therefore
-        // the Queue is currently empty
-        !self.buffer[self.tail].is_set()
+        // the Buffer is currently empty
+        if self.buffer[self.tail].is_set() {
+            return false;
+        }
+
+        // The Producer may have already moved on to newer Buffers, which we
+        // would start reading from their first Node
+        let mut next = self.buffer.next();
+        while let Some(buffer) = next {
+            if buffer[0].is_set() {
+                return false;
+            }
+            next = buffer.next();
+        }
+
+        true
+    }
+
+    /// Dequeues the next Element, if there is one, without checking if the
+    /// Queue has been closed and without reporting it to the Metrics, which
+    /// is left to the Caller
+    pub(crate) fn pop_ready(&mut self) -> Option<T> {
+        loop {
+            // # Safety:
+            // The Tail is always kept in the Range `0..length`
+            let buffer_entry = unsafe { self.buffer.get_unchecked(self.tail) };
+            if buffer_entry.is_set() {
+                let data = buffer_entry.load();
+                self.tail = next_element(self.tail, self.buffer.len());
+                return Some(data);
+            }
+
+            if !self.advance_buffer() {
+                return None;
+            }
+        }
+    }
+
+    /// Moves on to the next Buffer, if the Producer already moved on to it
+    /// and the current Buffer has been completely consumed. Returns whether
+    /// or not it moved on to the next Buffer
+    fn advance_buffer(&mut self) -> bool {
+        let next = match self.buffer.next() {
+            Some(n) => n,
+            None => return false,
+        };
+
+        // The Producer stores no more Data into the current Buffer after
+        // linking the next one, so if the current Node is still not set,
+        // there is nothing left in this Buffer
+        if self.buffer[self.tail].is_set() {
+            return false;
+        }
+
+        self.buffer = next;
+        self.tail = 0;
+        true
     }
 }
 
@@ -295,39 +837,122 @@ impl<T> Debug for BoundedReceiver<T> {
 impl<T> Drop for BoundedReceiver<T> {
     fn drop(&mut self) {
         self.closed.store(true, atomic::Ordering::Release);
+
+        // An async Sender will see that the Queue has been closed, once it is
+        // woken up
+        #[cfg(feature = "async")]
+        self.shared.tx_waker.wake();
     }
 }
 
-unsafe impl<T> Send for BoundedReceiver<T> {}
-unsafe impl<T> Sync for BoundedReceiver<T> {}
+// Safety:
+// The Halves can only be used to move Elements between Threads, so they are
+// only Send and Sync if the Elements themselves can be send across Threads
+unsafe impl<T> Send for BoundedReceiver<T> where T: Send {}
+unsafe impl<T> Sync for BoundedReceiver<T> where T: Send {}
 
 /// Creates a new Bounded-Queue with the given Capacity and returns the
-/// corresponding Handles ([`BoundedReceiver`], [`BoundedSender`])
+/// corresponding Handles ([`BoundedReceiver`], [`BoundedSender`]).
+/// [`channel`] creates the same Queue, but returns the Sender first
 pub fn queue<T>(capacity: usize) -> (BoundedReceiver<T>, BoundedSender<T>) {
+    queue_instrumented(capacity, Metrics::none())
+}
+
+/// Creates a new Bounded-Queue with the given Capacity and returns the
+/// Halves as ([`Sender`], [`Receiver`]), in the same Order as
+/// `std::sync::mpsc::channel`
+///
+/// # Example
+/// ```
+/// # use nolock::queues::spsc::bounded;
+/// let (mut tx, mut rx) = bounded::channel::<usize>(4);
+///
+/// tx.try_enqueue(13).unwrap();
+/// assert_eq!(Ok(13), rx.try_dequeue());
+/// ```
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let (rx, tx) = queue(capacity);
+    (tx, rx)
+}
+
+/// Creates a new Bounded-Queue with the given Capacity, that already contains
+/// all the Elements from the Iterator in Order, and returns the
+/// corresponding Handles ([`BoundedReceiver`], [`BoundedSender`])
+///
+/// The Elements are written directly into the Buffer, without checking every
+/// Node for being free first, as no other Thread can access the Queue yet.
+///
+/// # Panics
+/// If the Iterator yields more Elements than the given Capacity
+///
+/// # Example
+/// ```
+/// # use nolock::queues::spsc::bounded;
+/// # use nolock::queues::EnqueueError;
+/// let (mut rx, mut tx) = bounded::queue_prefilled(4, 0..3);
+///
+/// assert_eq!(Ok(()), tx.try_enqueue(3));
+/// assert_eq!(Err((4, EnqueueError::Full)), tx.try_enqueue(4));
+///
+/// assert_eq!(Ok(0), rx.try_dequeue());
+/// ```
+pub fn queue_prefilled<T, I>(capacity: usize, iter: I) -> (BoundedReceiver<T>, BoundedSender<T>)
+where
+    I: IntoIterator<Item = T>,
+{
+    let (rx, mut tx) = queue(capacity);
+    tx.prefill(iter);
+    (rx, tx)
+}
+
+/// Creates a new Bounded-Queue with the given Capacity, that reports all its
+/// Operations to the given [`QueueMetrics`](crate::queues::metrics::QueueMetrics)
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub fn queue_with_metrics<T>(
+    capacity: usize,
+    metrics: Arc<dyn crate::queues::metrics::QueueMetrics>,
+) -> (BoundedReceiver<T>, BoundedSender<T>) {
+    queue_instrumented(capacity, Metrics::new(metrics))
+}
+
+pub(crate) fn queue_instrumented<T>(
+    capacity: usize,
+    metrics: Metrics,
+) -> (BoundedReceiver<T>, BoundedSender<T>) {
     // Create the underlying Buffer of Nodes and fill it up with empty Nodes
     // as the initial Configuration
-    let mut raw_buffer = Vec::with_capacity(capacity);
-    for _ in 0..capacity {
-        raw_buffer.push(Node::new());
-    }
-
     let closed = Arc::new(atomic::AtomicBool::new(false));
-    let buffer = Arc::new(raw_buffer);
+    let enqueuing = Arc::new(atomic::AtomicBool::new(false));
+    let buffer = Arc::new(Buffer::new(capacity, Arc::new(Shutdown::new())));
+    #[cfg(feature = "async")]
+    let shared = Arc::new(async_queue::Shared::new());
 
     (
         BoundedReceiver {
             closed: closed.clone(),
+            enqueuing: enqueuing.clone(),
             buffer: buffer.clone(),
             tail: 0,
+            metrics: metrics.clone(),
+            #[cfg(feature = "async")]
+            shared: shared.clone(),
         },
         BoundedSender {
             closed,
+            enqueuing,
             buffer,
             head: 0,
+            metrics,
+            #[cfg(feature = "async")]
+            shared,
         },
     )
 }
 
+crate::queues::adapter::impl_receiver!(BoundedReceiver<T>);
+crate::queues::dynamic::impl_sender!(BoundedSender<T>, try_enqueue, enqueue);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,6 +974,78 @@ mod tests {
         drop(rx);
     }
     #[test]
+    fn prefilled() {
+        let (mut rx, mut tx) = queue_prefilled(4, 0..4);
+
+        assert_eq!(Err((4, EnqueueError::Full)), tx.try_enqueue(4));
+        for i in 0..4 {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+        assert_eq!(Ok(()), tx.try_enqueue(4));
+        assert_eq!(Ok(4), rx.try_dequeue());
+    }
+
+    #[test]
+    #[should_panic(expected = "more Elements than the Capacity")]
+    fn prefilled_overflow() {
+        let _ = queue_prefilled(2, 0..3);
+    }
+
+    #[test]
+    fn batch_wrapped() {
+        let (mut rx, mut tx) = queue(4);
+
+        assert_eq!(3, tx.try_enqueue_slice(&[0, 1, 2]));
+        let mut result = Vec::new();
+        assert_eq!(2, rx.try_dequeue_into(&mut result, 2));
+        assert_eq!(Vec::from([0, 1]), result);
+
+        // Wraps around the End of the Buffer
+        assert_eq!(3, tx.try_enqueue_slice(&[3, 4, 5, 6]));
+        assert_eq!(0, tx.try_enqueue_slice(&[7]));
+
+        result.clear();
+        assert_eq!(4, rx.try_dequeue_into(&mut result, 10));
+        assert_eq!(Vec::from([2, 3, 4, 5]), result);
+        assert_eq!(0, rx.try_dequeue_into(&mut result, 10));
+    }
+    #[test]
+    fn batch_closed() {
+        let (rx, mut tx) = queue(4);
+        drop(rx);
+
+        assert_eq!(0, tx.try_enqueue_slice(&[0, 1]));
+    }
+    #[test]
+    fn close_and_drain_wrapped() {
+        let (mut rx, mut tx) = queue(3);
+
+        for i in 0..5 {
+            tx.try_enqueue(i).unwrap();
+            if i < 3 {
+                assert_eq!(Ok(i), rx.try_dequeue());
+            }
+        }
+
+        assert_eq!(Vec::from([3, 4]), rx.close_and_drain());
+        assert_eq!(Err((5, EnqueueError::Closed)), tx.try_enqueue(5));
+    }
+    #[test]
+    fn shutdown_report_grown() {
+        let (mut rx, mut tx) = queue(2);
+        let report = tx.shutdown_report();
+
+        tx.try_enqueue(0).unwrap();
+        tx.try_enqueue(1).unwrap();
+        tx.grow(4);
+        tx.try_enqueue(2).unwrap();
+        assert_eq!(Ok(0), rx.try_dequeue());
+
+        drop(rx);
+        drop(tx);
+        assert_eq!(Some(2), report.closed_with_remaining());
+    }
+    #[test]
     fn dequeue_will_block() {
         let (mut rx, tx) = queue::<usize>(1);
 
@@ -432,4 +1129,130 @@ mod tests {
         rx.try_dequeue().unwrap();
         assert!(!tx.is_full());
     }
+
+    #[test]
+    fn grow_wrapped() {
+        let (mut rx, mut tx) = queue::<usize>(3);
+
+        // Move the Head and Tail to the middle of the Buffer, so that the
+        // remaining Elements wrap around the End of the old Buffer
+        for i in 0..2 {
+            tx.try_enqueue(i).unwrap();
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+        for i in 2..5 {
+            tx.try_enqueue(i).unwrap();
+        }
+        assert!(tx.is_full());
+
+        tx.grow(8);
+        assert_eq!(8, tx.capacity());
+        for i in 5..13 {
+            tx.try_enqueue(i).unwrap();
+        }
+        assert!(tx.is_full());
+
+        for i in 0..3 {
+            assert_eq!(Ok(i + 2), rx.try_dequeue());
+        }
+        assert!(!rx.is_empty());
+        for i in 5..13 {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+        assert!(rx.is_empty());
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+
+    #[test]
+    fn grow_smaller() {
+        let (rx, mut tx) = queue::<usize>(4);
+
+        tx.grow(2);
+        assert_eq!(4, tx.capacity());
+
+        drop(rx);
+    }
+
+    #[test]
+    fn grow_empty_then_closed() {
+        let (mut rx, mut tx) = queue::<usize>(2);
+
+        tx.grow(4);
+        assert!(rx.is_empty());
+        tx.try_enqueue(13).unwrap();
+        assert!(!rx.is_empty());
+        drop(tx);
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn close_and_drain_concurrent() {
+        for _ in 0..10 {
+            let (mut rx, mut tx) = queue::<usize>(8);
+
+            let handle = std::thread::spawn(move || {
+                let mut enqueued = 0;
+                loop {
+                    match tx.try_enqueue(enqueued) {
+                        Ok(()) => enqueued += 1,
+                        Err((_, EnqueueError::Full)) => std::thread::yield_now(),
+                        Err((_, _)) => return enqueued,
+                    }
+                }
+            });
+
+            let mut dequeued = 0;
+            for _ in 0..100 {
+                if rx.try_dequeue().is_ok() {
+                    dequeued += 1;
+                }
+            }
+            dequeued += rx.close_and_drain().len();
+
+            assert_eq!(handle.join().unwrap(), dequeued);
+        }
+    }
+
+    #[test]
+    fn grow_concurrent() {
+        let (mut rx, mut tx) = queue::<usize>(1);
+
+        let handle = std::thread::spawn(move || {
+            for i in 0..1000 {
+                if i % 100 == 0 {
+                    let capacity = tx.capacity();
+                    tx.grow(capacity + 1);
+                }
+                tx.enqueue(i).unwrap();
+            }
+        });
+
+        for i in 0..1000 {
+            assert_eq!(Some(i), rx.dequeue());
+        }
+        assert_eq!(None, rx.dequeue());
+
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn cancellable() {
+        let (mut rx, mut tx) = queue::<usize>(1);
+        let token = CancelToken::new();
+        token.cancel();
+
+        assert_eq!(Err(DequeueError::Cancelled), rx.dequeue_cancellable(&token));
+        assert_eq!(Ok(()), tx.enqueue_cancellable(13, &token));
+        assert_eq!(
+            Err((14, EnqueueError::Cancelled)),
+            tx.enqueue_cancellable(14, &token)
+        );
+        assert_eq!(Ok(13), rx.dequeue_cancellable(&token));
+
+        drop(tx);
+        assert_eq!(Err(DequeueError::Closed), rx.dequeue_cancellable(&token));
+    }
 }
@@ -1,44 +1,126 @@
-use alloc::sync::Arc;
 use core::{fmt::Debug, future::Future, task::Poll};
 
-use futures::task::AtomicWaker;
+use crate::utils::AtomicWaker;
 
-use crate::queues::{DequeueError, EnqueueError};
+use crate::queues::{
+    convert::SyncPeers,
+    timeout::{DequeueTimeout, EnqueueTimeout, EnqueueTimeoutError, PendingEnqueue},
+    DequeueError, EnqueueError,
+};
 
 use super::{BoundedReceiver, BoundedSender};
 
+/// The State shared between the Halves of a Queue, regardless of them being
+/// sync or async
+pub(crate) struct Shared {
+    /// The Waker of an async Receiver waiting for an Item
+    pub(crate) rx_waker: AtomicWaker,
+    /// The Waker of an async Sender waiting for free Space
+    pub(crate) tx_waker: AtomicWaker,
+    /// Whether the Sender is sync and therefore does not wake the Receiver
+    senders: SyncPeers,
+    /// Whether the Receiver is sync and therefore does not wake the Sender
+    receivers: SyncPeers,
+}
+
+impl Shared {
+    /// Creates the State for a new Queue, whose Halves both start out sync
+    pub fn new() -> Self {
+        Self {
+            rx_waker: AtomicWaker::new(),
+            tx_waker: AtomicWaker::new(),
+            senders: SyncPeers::new(1),
+            receivers: SyncPeers::new(1),
+        }
+    }
+}
+
 /// An async variant of the [`BoundedSender`] that allows your to efficiently
 /// use this Queue in async Contexts as well.
 ///
-/// Created using the [`async_queue`] method
+/// Created using the [`async_queue`] method or by converting a
+/// [`BoundedSender`] using [`into_async`](BoundedSender::into_async).
+///
+/// # Conversion
+/// The Halves can be converted between their sync and async Variants
+/// independently of each other and at any Time. While the other Half is
+/// sync, a waiting Future can not be woken up by it and instead polls the
+/// Queue cooperatively, by yielding back to the Executor in every Poll.
 pub struct AsyncBoundedSender<T> {
-    rx_waker: Arc<AtomicWaker>,
-    tx_waker: Arc<AtomicWaker>,
     queue: BoundedSender<T>,
 }
 
 /// An async variant of the [`BoundedReceiver`] that allows your to efficiently
 /// use this Queue in async Contexts as well.
 ///
-/// Created using the [`async_queue`] method
+/// Created using the [`async_queue`] method or by converting a
+/// [`BoundedReceiver`] using [`into_async`](BoundedReceiver::into_async),
+/// see [`AsyncBoundedSender`] for the Details of the Conversion
 pub struct AsyncBoundedReceiver<T> {
-    rx_waker: Arc<AtomicWaker>,
-    tx_waker: Arc<AtomicWaker>,
     queue: BoundedReceiver<T>,
 }
 
+impl<T> BoundedSender<T> {
+    /// Converts this Sender into an [`AsyncBoundedSender`] for the same Queue
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// async fn demo() {
+    ///   let (rx, mut tx) = bounded::queue::<usize>(4);
+    ///   tx.try_enqueue(13).unwrap();
+    ///
+    ///   let (mut rx, mut tx) = (rx.into_async(), tx.into_async());
+    ///   tx.enqueue(14).await.unwrap();
+    ///
+    ///   assert_eq!(Ok(13), rx.dequeue().await);
+    ///   assert_eq!(Ok(14), rx.dequeue().await);
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn into_async(self) -> AsyncBoundedSender<T> {
+        self.shared.senders.remove();
+        // The Receiver may currently be waiting cooperatively, so it needs to
+        // observe that it will now be woken up instead
+        self.shared.rx_waker.wake();
+
+        AsyncBoundedSender { queue: self }
+    }
+}
+
+impl<T> BoundedReceiver<T> {
+    /// Converts this Receiver into an [`AsyncBoundedReceiver`] for the same
+    /// Queue
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn into_async(self) -> AsyncBoundedReceiver<T> {
+        self.shared.receivers.remove();
+        // The Sender may currently be waiting cooperatively, so it needs to
+        // observe that it will now be woken up instead
+        self.shared.tx_waker.wake();
+
+        AsyncBoundedReceiver { queue: self }
+    }
+}
+
 /// The Future returned when enqueueing an Item
 ///
 /// # Behaviour
 /// This Future only resolves when it either successfully enqueued the Item
 /// in the Queue (`Ok`) or when the Queue gets closed by the Consumer and
 /// therefore no more Items can be enqueued into it (`Err`)
+///
+/// # Cancel Safety
+/// Dropping this Future before it resolved will also drop the Item it still
+/// holds. To recover the Item instead, use [`into_inner`](Self::into_inner)
+/// on the Future, which returns the Item if it has not yet been enqueued.
 pub struct EnqueueFuture<'queue, T> {
-    /// The Waker to notify a potential waiting Dequeue-Operation
-    rx_waker: &'queue AtomicWaker,
-    /// The Waker for this type of Future
-    tx_waker: &'queue AtomicWaker,
-    /// The actual underlying Queue
+    /// The actual underlying Queue, which also holds the Wakers
     queue: &'queue mut BoundedSender<T>,
     /// The Data that the User wants to enqueue
     data: Option<T>,
@@ -50,16 +132,31 @@ pub struct EnqueueFuture<'queue, T> {
 /// This Future only resolves when it either successfully dequeued an Item
 /// and then returns it (`Ok(item)`) or when the Queue was closed by the Producer
 /// and there are no more Items left in it to be dequeued (`Err`)
+///
+/// # Cancel Safety
+/// This Future is cancel safe. The Item is only dequeued in the Poll that
+/// also returns it, which is also when the Slot is freed and a waiting
+/// Sender is woken, so dropping a pending Future does not affect either
+/// Side.
 pub struct DequeueFuture<'queue, T> {
-    /// The Waker for this type of Future
-    rx_waker: &'queue AtomicWaker,
-    /// The Waker to notify a potential waiting Enqueue-Operation
-    tx_waker: &'queue AtomicWaker,
-    /// The actual underlying Queue
+    /// The actual underlying Queue, which also holds the Wakers
     queue: &'queue mut BoundedReceiver<T>,
 }
 
 impl<T> AsyncBoundedSender<T> {
+    /// Converts this AsyncBoundedSender back into a sync [`BoundedSender`]
+    /// for the same Queue, after which a waiting async Receiver has to poll
+    /// the Queue cooperatively
+    pub fn into_sync(self) -> BoundedSender<T> {
+        let queue = self.queue;
+
+        queue.shared.senders.add();
+        // The Receiver may currently be waiting for us to wake it up
+        queue.shared.rx_waker.wake();
+
+        queue
+    }
+
     /// Checks if the Queue has been closed by the Consumer
     pub fn is_closed(&self) -> bool {
         self.queue.is_closed()
@@ -67,15 +164,135 @@ impl<T> AsyncBoundedSender<T> {
 
     /// The async variant of the blocking [`enqueue`](BoundedSender::enqueue)
     /// operation on the Non-Async version of the Queue
+    ///
+    /// # Cancel Safety
+    /// If the returned Future is dropped before it resolved, the Item will be
+    /// dropped with it. Use [`EnqueueFuture::into_inner`] to get the Item
+    /// back, for example after a timeout or when losing a `select!`.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// # use std::task::{Context, Poll};
+    /// # use std::future::Future;
+    /// # use std::pin::Pin;
+    /// # use futures::task::noop_waker;
+    /// let (mut rx, mut tx) = bounded::async_queue::<u64>(1);
+    /// tx.try_enqueue(13).unwrap();
+    ///
+    /// // The Queue is full, so this can not make any progress
+    /// let mut enqueue = tx.enqueue(14);
+    /// let waker = noop_waker();
+    /// let mut cx = Context::from_waker(&waker);
+    /// assert!(Pin::new(&mut enqueue).poll(&mut cx).is_pending());
+    ///
+    /// // Give up on enqueueing, but keep the Item
+    /// assert_eq!(Some(14), enqueue.into_inner());
+    /// # assert_eq!(Ok(13), rx.try_dequeue());
+    /// ```
     pub fn enqueue(&mut self, data: T) -> EnqueueFuture<'_, T> {
         EnqueueFuture {
-            rx_waker: &self.rx_waker,
-            tx_waker: &self.tx_waker,
             queue: &mut self.queue,
             data: Some(data),
         }
     }
 
+    /// Enqueues the Item, just like [`enqueue`](Self::enqueue), but stops
+    /// waiting for free Space once the given `sleep` Future resolves, in
+    /// which case the Item is returned as
+    /// `Err(EnqueueTimeoutError::Timeout(data))`.
+    ///
+    /// The `sleep` Future can come from any Runtime, see the
+    /// [`timeout`](crate::queues::timeout) module for more Details
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// # use nolock::queues::timeout::EnqueueTimeoutError;
+    /// # use std::time::Duration;
+    /// async fn demo() {
+    ///   let (mut rx, mut tx) = bounded::async_queue::<usize>(1);
+    ///   tx.try_enqueue(13).unwrap();
+    ///
+    ///   // The Queue stays full, so the Item is given back
+    ///   let sleep = tokio::time::sleep(Duration::from_millis(5));
+    ///   assert_eq!(
+    ///     Err(EnqueueTimeoutError::Timeout(14)),
+    ///     tx.enqueue_within(14, sleep).await
+    ///   );
+    ///   # assert_eq!(Ok(13), rx.try_dequeue());
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    pub fn enqueue_within<S>(
+        &mut self,
+        data: T,
+        sleep: S,
+    ) -> EnqueueTimeout<EnqueueFuture<'_, T>, S>
+    where
+        S: Future,
+    {
+        EnqueueTimeout::new(self.enqueue(data), sleep)
+    }
+
+    /// Polls to enqueue the Item in the given Slot, which is the low-level
+    /// Operation behind [`enqueue`](Self::enqueue), for Executors or
+    /// Combinators that drive the Queue manually instead of awaiting a
+    /// Future.
+    ///
+    /// The Item is only taken out of the Slot once it was enqueued, so it
+    /// stays in the Slot if the Queue is currently full, in which case
+    /// `Poll::Pending` is returned and the Waker of the given Context is
+    /// woken once there is Space again, or if the Queue has been closed.
+    /// An empty Slot is treated as already enqueued.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// # use std::task::{Context, Poll};
+    /// # use futures::task::noop_waker;
+    /// let (mut rx, mut tx) = bounded::async_queue::<u64>(1);
+    /// let waker = noop_waker();
+    /// let mut cx = Context::from_waker(&waker);
+    ///
+    /// let mut slot = Some(13);
+    /// assert_eq!(Poll::Ready(Ok(())), tx.poll_enqueue(&mut cx, &mut slot));
+    /// assert_eq!(None, slot);
+    ///
+    /// // The Queue is full, so the Item stays in its Slot
+    /// let mut slot = Some(14);
+    /// assert!(tx.poll_enqueue(&mut cx, &mut slot).is_pending());
+    /// assert_eq!(Some(14), slot);
+    /// # assert_eq!(Ok(13), rx.try_dequeue());
+    /// ```
+    pub fn poll_enqueue(
+        &mut self,
+        cx: &mut core::task::Context<'_>,
+        slot: &mut Option<T>,
+    ) -> Poll<Result<(), EnqueueError>> {
+        let mut future = EnqueueFuture {
+            queue: &mut self.queue,
+            data: slot.take(),
+        };
+
+        match core::pin::Pin::new(&mut future).poll(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(())),
+            Poll::Ready(Err((data, e))) => {
+                *slot = Some(data);
+                Poll::Ready(Err(e))
+            }
+            Poll::Pending => {
+                *slot = future.into_inner();
+                Poll::Pending
+            }
+        }
+    }
+
     /// Attempts to enqueue the given Data on the Queue.
     ///
     /// This behaves just like the [`try_enqueue`](BoundedSender::try_enqueue)
@@ -83,7 +300,7 @@ impl<T> AsyncBoundedSender<T> {
     pub fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
         match self.queue.try_enqueue(data) {
             Ok(_) => {
-                self.rx_waker.wake();
+                self.queue.shared.rx_waker.wake();
                 Ok(())
             }
             Err(e) => Err(e),
@@ -94,6 +311,12 @@ impl<T> AsyncBoundedSender<T> {
     pub fn is_full(&self) -> bool {
         self.queue.is_full()
     }
+
+    /// Grows the Queue to the given Capacity, see
+    /// [`grow`](BoundedSender::grow) for more Details
+    pub fn grow(&mut self, new_capacity: usize) {
+        self.queue.grow(new_capacity);
+    }
 }
 
 impl<T> Debug for AsyncBoundedSender<T> {
@@ -103,6 +326,19 @@ impl<T> Debug for AsyncBoundedSender<T> {
 }
 
 impl<T> AsyncBoundedReceiver<T> {
+    /// Converts this AsyncBoundedReceiver back into a sync
+    /// [`BoundedReceiver`] for the same Queue, after which a waiting async
+    /// Sender has to poll the Queue cooperatively
+    pub fn into_sync(self) -> BoundedReceiver<T> {
+        let queue = self.queue;
+
+        queue.shared.receivers.add();
+        // The Sender may currently be waiting for us to wake it up
+        queue.shared.tx_waker.wake();
+
+        queue
+    }
+
     /// Checks if the Queue has been closed by the Producer
     ///
     /// # Note
@@ -117,12 +353,36 @@ impl<T> AsyncBoundedReceiver<T> {
     /// operation on the Non-Async version of the Queue
     pub fn dequeue(&mut self) -> DequeueFuture<'_, T> {
         DequeueFuture {
-            rx_waker: &self.rx_waker,
-            tx_waker: &self.tx_waker,
             queue: &mut self.queue,
         }
     }
 
+    /// Polls for the next Item, which is the low-level Operation behind
+    /// [`dequeue`](Self::dequeue), for Executors or Combinators that drive
+    /// the Queue manually instead of awaiting a Future.
+    ///
+    /// Returns `Poll::Pending` if the Queue is currently empty, in which case
+    /// the Waker of the given Context is woken once an Item is enqueued
+    pub fn poll_dequeue(
+        &mut self,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Result<T, DequeueError>> {
+        core::pin::Pin::new(&mut self.dequeue()).poll(cx)
+    }
+
+    /// Dequeues the next Item, just like [`dequeue`](Self::dequeue), but
+    /// stops waiting once the given `sleep` Future resolves, in which case
+    /// `Err(DequeueError::Empty)` is returned.
+    ///
+    /// The `sleep` Future can come from any Runtime, see the
+    /// [`timeout`](crate::queues::timeout) module for more Details
+    pub fn dequeue_timeout<S>(&mut self, sleep: S) -> DequeueTimeout<DequeueFuture<'_, T>, S>
+    where
+        S: Future,
+    {
+        DequeueTimeout::new(self.dequeue(), sleep)
+    }
+
     /// Attempts to dequeue a single Item from the Queue.
     ///
     /// This behaves just like the non-async
@@ -130,7 +390,7 @@ impl<T> AsyncBoundedReceiver<T> {
     pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
         match self.queue.try_dequeue() {
             Ok(d) => {
-                self.tx_waker.wake();
+                self.queue.shared.tx_waker.wake();
                 Ok(d)
             }
             Err(e) => Err(e),
@@ -149,6 +409,19 @@ impl<T> Debug for AsyncBoundedReceiver<T> {
     }
 }
 
+impl<'queue, T> EnqueueFuture<'queue, T> {
+    /// Consumes the Future and returns the Item, if it has not yet been
+    /// enqueued.
+    ///
+    /// This allows the Item to be recovered when the Future is abandoned
+    /// before it resolved, instead of silently dropping it. Returns `None`
+    /// if the Future already resolved, in which case the Item was either
+    /// enqueued or returned as part of the Error.
+    pub fn into_inner(self) -> Option<T> {
+        self.data
+    }
+}
+
 impl<'queue, T> Unpin for EnqueueFuture<'queue, T> {}
 
 impl<'queue, T> Future for EnqueueFuture<'queue, T> {
@@ -163,24 +436,46 @@ impl<'queue, T> Future for EnqueueFuture<'queue, T> {
             None => return Poll::Ready(Ok(())),
         };
 
+        // A sync Receiver will not wake us up, so we have to keep polling
+        let cooperative = self.queue.shared.receivers.any();
+
         match self.queue.try_enqueue(data) {
             Ok(_) => {
-                self.rx_waker.wake();
+                self.queue.shared.rx_waker.wake();
                 Poll::Ready(Ok(()))
             }
             Err((d, e)) => match e {
                 EnqueueError::Full => {
                     self.data.replace(d);
-                    self.tx_waker.register(cx.waker());
+                    self.queue.shared.tx_waker.register(cx.waker());
+
+                    if cooperative || self.queue.shared.receivers.any() {
+                        cx.waker().wake_by_ref();
+                    }
 
                     Poll::Pending
                 }
-                EnqueueError::Closed => Poll::Ready(Err((d, e))),
+                EnqueueError::Closed
+                | EnqueueError::NoSpace
+                | EnqueueError::AllocFailed
+                | EnqueueError::Cancelled => Poll::Ready(Err((d, e))),
             },
         }
     }
 }
 
+impl<'queue, T> PendingEnqueue for EnqueueFuture<'queue, T> {
+    type Item = T;
+
+    fn take_item(&mut self) -> Option<T> {
+        self.data.take()
+    }
+
+    fn into_result(output: Self::Output) -> Result<(), EnqueueTimeoutError<T>> {
+        output.map_err(|(data, e)| EnqueueTimeoutError::Failed(e, data))
+    }
+}
+
 impl<'queue, T> Debug for EnqueueFuture<'queue, T> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "Enqueue-Future ()")
@@ -196,17 +491,24 @@ impl<'queue, T> Future for DequeueFuture<'queue, T> {
         mut self: core::pin::Pin<&mut Self>,
         cx: &mut core::task::Context<'_>,
     ) -> Poll<Self::Output> {
+        // A sync Sender will not wake us up, so we have to keep polling
+        let cooperative = self.queue.shared.senders.any();
+
         match self.queue.try_dequeue() {
             Ok(d) => {
-                self.tx_waker.wake();
+                self.queue.shared.tx_waker.wake();
                 Poll::Ready(Ok(d))
             }
             Err(e) => match e {
                 DequeueError::Empty => {
-                    self.rx_waker.register(cx.waker());
+                    self.queue.shared.rx_waker.register(cx.waker());
+
+                    if cooperative || self.queue.shared.senders.any() {
+                        cx.waker().wake_by_ref();
+                    }
                     Poll::Pending
                 }
-                DequeueError::Closed => Poll::Ready(Err(DequeueError::Closed)),
+                DequeueError::Closed | DequeueError::Cancelled => Poll::Ready(Err(e)),
             },
         }
     }
@@ -222,24 +524,19 @@ impl<'queue, T> Debug for DequeueFuture<'queue, T> {
 /// ([`AsyncBoundedReceiver`], [`AsyncBoundedSender`])
 pub fn async_queue<T>(size: usize) -> (AsyncBoundedReceiver<T>, AsyncBoundedSender<T>) {
     let (u_rx, u_tx) = super::queue(size);
+    wrap_queue(u_rx, u_tx)
+}
 
-    let rx_waker = Arc::new(AtomicWaker::new());
-    let tx_waker = Arc::new(AtomicWaker::new());
-
-    (
-        AsyncBoundedReceiver {
-            rx_waker: rx_waker.clone(),
-            tx_waker: tx_waker.clone(),
-            queue: u_rx,
-        },
-        AsyncBoundedSender {
-            rx_waker,
-            tx_waker,
-            queue: u_tx,
-        },
-    )
+/// Wraps the given sync Queue Halves into their async Variants
+pub(crate) fn wrap_queue<T>(
+    u_rx: BoundedReceiver<T>,
+    u_tx: BoundedSender<T>,
+) -> (AsyncBoundedReceiver<T>, AsyncBoundedSender<T>) {
+    (u_rx.into_async(), u_tx.into_async())
 }
 
+crate::queues::adapter::impl_async_receiver!(AsyncBoundedReceiver<T>, DequeueFuture);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,4 +549,104 @@ mod tests {
         tx.enqueue(13).await.unwrap();
         assert_eq!(Ok(13), rx.dequeue().await);
     }
+
+    #[test]
+    fn poll_enqueue_dequeue() {
+        let (mut rx, mut tx) = async_queue::<usize>(1);
+
+        let waker = futures::task::noop_waker();
+        let mut cx = core::task::Context::from_waker(&waker);
+
+        assert!(rx.poll_dequeue(&mut cx).is_pending());
+
+        let mut slot = Some(13);
+        assert_eq!(Poll::Ready(Ok(())), tx.poll_enqueue(&mut cx, &mut slot));
+        let mut slot = Some(14);
+        assert!(tx.poll_enqueue(&mut cx, &mut slot).is_pending());
+
+        assert_eq!(Poll::Ready(Ok(13)), rx.poll_dequeue(&mut cx));
+        assert_eq!(Poll::Ready(Ok(())), tx.poll_enqueue(&mut cx, &mut slot));
+        assert_eq!(None, slot);
+
+        drop(rx);
+        let mut slot = Some(15);
+        assert_eq!(
+            Poll::Ready(Err(EnqueueError::Closed)),
+            tx.poll_enqueue(&mut cx, &mut slot)
+        );
+        assert_eq!(Some(15), slot);
+    }
+
+    #[test]
+    fn enqueue_into_inner() {
+        let (mut rx, mut tx) = async_queue::<usize>(1);
+        tx.try_enqueue(13).unwrap();
+
+        let waker = futures::task::noop_waker();
+        let mut cx = core::task::Context::from_waker(&waker);
+
+        let mut fut = tx.enqueue(14);
+        assert!(core::pin::Pin::new(&mut fut).poll(&mut cx).is_pending());
+        assert_eq!(Some(14), fut.into_inner());
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+
+        let mut fut = tx.enqueue(15);
+        assert_eq!(
+            Poll::Ready(Ok(())),
+            core::pin::Pin::new(&mut fut).poll(&mut cx)
+        );
+        assert_eq!(None, fut.into_inner());
+        assert_eq!(Ok(15), rx.try_dequeue());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn async_sender_sync_receiver() {
+        let (mut rx, tx) = super::super::queue::<usize>(1);
+        let mut tx = tx.into_async();
+        tx.try_enqueue(13).unwrap();
+
+        // The Queue is full, so the Sender has to wait for the sync Receiver,
+        // which will not wake it up
+        let handle = tokio::spawn(async move { tx.enqueue(14).await.map(|_| tx) });
+
+        tokio::task::yield_now().await;
+        assert_eq!(Ok(13), rx.try_dequeue());
+
+        let tx = tokio::time::timeout(core::time::Duration::from_secs(5), handle)
+            .await
+            .expect("The Sender did not observe the free Space")
+            .unwrap()
+            .unwrap();
+        assert_eq!(Ok(14), rx.try_dequeue());
+
+        let mut tx = tx.into_sync();
+        let mut rx = rx.into_async();
+        tx.try_enqueue(15).unwrap();
+        assert_eq!(Ok(15), rx.dequeue().await);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn enqueue_within() {
+        let (mut rx, mut tx) = async_queue::<usize>(1);
+        tx.try_enqueue(13).unwrap();
+
+        let result = tx.enqueue_within(14, async {}).await;
+        assert_eq!(Err(EnqueueTimeoutError::Timeout(14)), result);
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+        let result = tx.enqueue_within(15, core::future::pending::<()>()).await;
+        assert_eq!(Ok(()), result);
+        assert_eq!(Ok(15), rx.try_dequeue());
+
+        drop(rx);
+        let result = tx.enqueue_within(16, async {}).await;
+        assert_eq!(
+            Err(EnqueueTimeoutError::Failed(EnqueueError::Closed, 16)),
+            result
+        );
+    }
 }
@@ -0,0 +1,96 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::ops::Deref;
+
+use crate::{queues::shutdown::Shutdown, sync::native::atomic};
+
+use super::node::Node;
+
+/// The Buffer of Nodes used by the Queue.
+///
+/// When the Queue is grown, the Producer links the new larger Buffer to the
+/// current one, so that the Consumer can follow it once it has consumed all
+/// the remaining Nodes in the current Buffer
+pub struct Buffer<T> {
+    /// The actual Nodes of the Buffer
+    nodes: Vec<Node<T>>,
+    /// The Buffer that replaced this one, this is either null or a Ptr
+    /// obtained from [`Arc::into_raw`]
+    next: atomic::AtomicPtr<Buffer<T>>,
+    /// Counts the Elements that are dropped together with the Buffers of the
+    /// Queue, which is shared by all of them
+    pub shutdown: Arc<Shutdown>,
+}
+
+impl<T> Buffer<T> {
+    /// Creates a new Buffer with the given Number of empty Nodes
+    pub fn new(capacity: usize, shutdown: Arc<Shutdown>) -> Self {
+        let mut nodes = Vec::with_capacity(capacity);
+        for _ in 0..capacity {
+            nodes.push(Node::new());
+        }
+
+        Self {
+            nodes,
+            next: atomic::AtomicPtr::new(core::ptr::null_mut()),
+            shutdown,
+        }
+    }
+
+    /// Links the given Buffer as the Successor of this Buffer.
+    ///
+    /// This must only be called once per Buffer and only by the Producer,
+    /// after which it must not store any more Data into this Buffer
+    pub fn set_next(&self, next: Arc<Self>) {
+        let ptr = Arc::into_raw(next) as *mut Self;
+        let previous = self.next.swap(ptr, atomic::Ordering::AcqRel);
+        debug_assert!(previous.is_null());
+    }
+
+    /// Loads the Successor of this Buffer, if the Producer already linked one
+    pub fn next(&self) -> Option<Arc<Self>> {
+        let ptr = self.next.load(atomic::Ordering::Acquire);
+        if ptr.is_null() {
+            return None;
+        }
+
+        // # Safety:
+        // The Ptr was obtained from `Arc::into_raw` and the Reference it
+        // represents is only released when this Buffer is dropped, so it is
+        // still valid and we can create a new Reference from it
+        unsafe {
+            Arc::increment_strong_count(ptr);
+            Some(Arc::from_raw(ptr))
+        }
+    }
+}
+
+impl<T> Deref for Buffer<T> {
+    type Target = [Node<T>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.nodes
+    }
+}
+
+impl<T> Drop for Buffer<T> {
+    fn drop(&mut self) {
+        let remaining = self.nodes.iter().filter(|node| node.is_set()).count();
+        self.shutdown.record_dropped(remaining);
+
+        // The Successors are released in a Loop instead of recursively, as
+        // the unbounded Queue can build up arbitrarily long Chains of Buffers
+        let mut ptr = *self.next.get_mut();
+        while !ptr.is_null() {
+            // # Safety:
+            // The Ptr was obtained from `Arc::into_raw` in `set_next`
+            let next = unsafe { Arc::from_raw(ptr) };
+
+            // If this was the last Reference to the Successor, we unlink its
+            // own Successor before dropping it and continue with that one
+            ptr = match Arc::try_unwrap(next) {
+                Ok(mut buffer) => core::mem::replace(buffer.next.get_mut(), core::ptr::null_mut()),
+                Err(_) => break,
+            };
+        }
+    }
+}
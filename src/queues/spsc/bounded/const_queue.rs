@@ -0,0 +1,367 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::fmt::Debug;
+
+use crate::sync::native::atomic;
+
+use crate::{
+    queues::{DequeueError, EnqueueError},
+    utils::Backoff,
+};
+
+use super::{next_element, node::Node};
+
+/// The State shared between the two Halves of a Const-Queue
+struct Shared<T, const N: usize> {
+    /// Indicates if the Queue has been closed or not
+    closed: atomic::AtomicBool,
+    /// Set while the Producer is storing an Element, so that the Consumer
+    /// can wait for it to finish, when closing the Queue
+    enqueuing: atomic::AtomicBool,
+    /// The underlying Buffer of Nodes
+    buffer: [Node<T>; N],
+}
+
+/// The Sending-Half for a bounded Queue with a compile-time Capacity of `N`,
+/// created using [`const_queue`]
+pub struct ConstBoundedSender<T, const N: usize> {
+    /// The Index of the next Node to store Data into
+    head: usize,
+    /// The State shared with the Receiver
+    shared: Arc<Shared<T, N>>,
+}
+
+/// The Receiving-Half for a bounded Queue with a compile-time Capacity of `N`,
+/// created using [`const_queue`]
+pub struct ConstBoundedReceiver<T, const N: usize> {
+    /// The Index of the next Node to read in the Buffer
+    tail: usize,
+    /// The State shared with the Sender
+    shared: Arc<Shared<T, N>>,
+}
+
+impl<T, const N: usize> ConstBoundedSender<T, N> {
+    /// Returns whether or not the Queue has been closed by the Consumer
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(atomic::Ordering::Acquire)
+    }
+
+    /// Attempts to Enqueue the given piece of Data
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// # use nolock::queues::EnqueueError;
+    /// let (mut rx, mut tx) = bounded::const_queue::<usize, 1>();
+    ///
+    /// assert_eq!(Ok(()), tx.try_enqueue(13));
+    /// assert_eq!(Err((14, EnqueueError::Full)), tx.try_enqueue(14));
+    ///
+    /// # drop(rx);
+    /// ```
+    pub fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        // Announce the Store before checking if the Queue has been closed,
+        // see `BoundedSender::begin_enqueue`
+        self.shared.enqueuing.store(true, atomic::Ordering::SeqCst);
+        if self.shared.closed.load(atomic::Ordering::SeqCst) {
+            self.shared
+                .enqueuing
+                .store(false, atomic::Ordering::Release);
+            return Err((data, EnqueueError::Closed));
+        }
+
+        // # Safety:
+        // The Head is always kept in the Range `0..N` by `next_element`
+        let buffer_entry = unsafe { self.shared.buffer.get_unchecked(self.head) };
+
+        // If the Node is already set, there is no room left in the Queue
+        if buffer_entry.is_set() {
+            self.shared
+                .enqueuing
+                .store(false, atomic::Ordering::Release);
+            return Err((data, EnqueueError::Full));
+        }
+
+        buffer_entry.store(data);
+        self.shared
+            .enqueuing
+            .store(false, atomic::Ordering::Release);
+        self.head = next_element(self.head, N);
+
+        Ok(())
+    }
+
+    /// A blocking enqueue Operation. This is obviously not lock-free anymore
+    /// and will simply spin, with an exponential [`Backoff`], while trying to
+    /// enqueue the Data until it works
+    pub fn enqueue(&mut self, mut data: T) -> Result<(), (T, EnqueueError)> {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_enqueue(data) {
+                Ok(_) => return Ok(()),
+                Err((d, EnqueueError::Full)) => {
+                    data = d;
+                    backoff.snooze();
+                }
+                Err((d, e)) => return Err((d, e)),
+            };
+        }
+    }
+
+    /// Checks if the current Queue is full
+    pub fn is_full(&self) -> bool {
+        self.shared.buffer[self.head].is_set()
+    }
+}
+
+impl<T, const N: usize> Debug for ConstBoundedSender<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ConstBoundedSender<{}> ()", N)
+    }
+}
+
+impl<T, const N: usize> Drop for ConstBoundedSender<T, N> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, atomic::Ordering::Release);
+    }
+}
+
+// Safety:
+// The Halves can only be used to move Elements between Threads, so they are
+// only Send and Sync if the Elements themselves can be send across Threads
+unsafe impl<T, const N: usize> Send for ConstBoundedSender<T, N> where T: Send {}
+unsafe impl<T, const N: usize> Sync for ConstBoundedSender<T, N> where T: Send {}
+
+impl<T, const N: usize> ConstBoundedReceiver<T, N> {
+    /// Checks if the Queue has been closed by the Producer
+    ///
+    /// # Note
+    /// Even when this indicates that the Queue has been closed, there might
+    /// still be Items in the Queue left that should first be dequeued by the
+    /// Consumer before discarding the entire Queue
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(atomic::Ordering::Acquire)
+    }
+
+    /// Attempts to Dequeue a single Element from the Queue
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// # use nolock::queues::DequeueError;
+    /// let (mut rx, mut tx) = bounded::const_queue::<usize, 16>();
+    ///
+    /// tx.try_enqueue(13).unwrap();
+    ///
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    /// assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    /// ```
+    pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        // # Safety:
+        // The Tail is always kept in the Range `0..N` by `next_element`
+        let buffer_entry = unsafe { self.shared.buffer.get_unchecked(self.tail) };
+
+        if !buffer_entry.is_set() {
+            // We need to recheck the current Node, because it may have been
+            // set in the mean time and then the closed flag was updated
+            if self.is_closed() && !buffer_entry.is_set() {
+                return Err(DequeueError::Closed);
+            }
+
+            return Err(DequeueError::Empty);
+        }
+
+        let data = buffer_entry.load();
+        self.tail = next_element(self.tail, N);
+
+        Ok(data)
+    }
+
+    /// A blocking dequeue operations. This is not lock-free anymore and simply
+    /// spins, with an exponential [`Backoff`], while trying to dequeue until
+    /// it works.
+    pub fn dequeue(&mut self) -> Option<T> {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_dequeue() {
+                Ok(d) => return Some(d),
+                Err(DequeueError::Empty) => backoff.snooze(),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+            };
+        }
+    }
+
+    /// Closes the Queue and returns all the Elements that were still left
+    /// in it.
+    ///
+    /// This is the same as
+    /// [`close_and_drain`](super::BoundedReceiver::close_and_drain) on the
+    /// normal bounded Queue
+    pub fn close_and_drain(mut self) -> Vec<T> {
+        self.shared.closed.store(true, atomic::Ordering::SeqCst);
+
+        // Wait for an Enqueue, that is still storing its Element, to finish
+        let backoff = Backoff::new();
+        while self.shared.enqueuing.load(atomic::Ordering::SeqCst) {
+            backoff.snooze();
+        }
+
+        let mut result = Vec::new();
+        while let Ok(data) = self.try_dequeue() {
+            result.push(data);
+        }
+
+        result
+    }
+
+    /// Checks if the current queue is empty
+    pub fn is_empty(&self) -> bool {
+        !self.shared.buffer[self.tail].is_set()
+    }
+}
+
+impl<T, const N: usize> Debug for ConstBoundedReceiver<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ConstBoundedReceiver<{}> ()", N)
+    }
+}
+
+impl<T, const N: usize> Drop for ConstBoundedReceiver<T, N> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, atomic::Ordering::Release);
+    }
+}
+
+// Safety:
+// The Halves can only be used to move Elements between Threads, so they are
+// only Send and Sync if the Elements themselves can be send across Threads
+unsafe impl<T, const N: usize> Send for ConstBoundedReceiver<T, N> where T: Send {}
+unsafe impl<T, const N: usize> Sync for ConstBoundedReceiver<T, N> where T: Send {}
+
+/// Creates a new Bounded-Queue with the Capacity `N` and returns the
+/// corresponding Handles ([`ConstBoundedReceiver`], [`ConstBoundedSender`]).
+///
+/// Unlike [`queue`](super::queue), the Capacity is known at compile-time and
+/// the Nodes are stored in an Array directly in the shared Allocation,
+/// which avoids the extra Indirection of a Vec.
+///
+/// # Panics
+/// If `N` is 0
+///
+/// # Example
+/// ```
+/// # use nolock::queues::spsc::bounded;
+/// let (mut rx, mut tx) = bounded::const_queue::<usize, 16>();
+///
+/// tx.try_enqueue(13).unwrap();
+/// assert_eq!(Ok(13), rx.try_dequeue());
+/// ```
+pub fn const_queue<T, const N: usize>() -> (ConstBoundedReceiver<T, N>, ConstBoundedSender<T, N>) {
+    assert!(N > 0, "The Capacity needs to be at least 1");
+
+    let shared = Arc::new(Shared {
+        closed: atomic::AtomicBool::new(false),
+        enqueuing: atomic::AtomicBool::new(false),
+        buffer: core::array::from_fn(|_| Node::new()),
+    });
+
+    (
+        ConstBoundedReceiver {
+            tail: 0,
+            shared: shared.clone(),
+        },
+        ConstBoundedSender { head: 0, shared },
+    )
+}
+
+impl<T, const N: usize> crate::queues::adapter::QueueReceiver for ConstBoundedReceiver<T, N> {
+    type Item = T;
+
+    fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        ConstBoundedReceiver::try_dequeue(self)
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        ConstBoundedReceiver::dequeue(self)
+    }
+}
+
+impl<T, const N: usize> crate::queues::dynamic::DynSender<T> for ConstBoundedSender<T, N> {
+    fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        ConstBoundedSender::try_enqueue(self, data)
+    }
+
+    fn enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        ConstBoundedSender::enqueue(self, data)
+    }
+
+    fn is_closed(&self) -> bool {
+        ConstBoundedSender::is_closed(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enqueue_dequeue() {
+        let (mut rx, mut tx) = const_queue::<usize, 10>();
+
+        assert_eq!(Ok(()), tx.try_enqueue(13));
+        assert_eq!(Ok(13), rx.try_dequeue());
+    }
+    #[test]
+    fn enqueue_dequeue_full_buffer() {
+        let (mut rx, mut tx) = const_queue::<usize, 3>();
+
+        for i in 0..4 {
+            assert_eq!(Ok(()), tx.try_enqueue(i));
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+    }
+    #[test]
+    fn enqueue_full() {
+        let (rx, mut tx) = const_queue::<usize, 2>();
+
+        assert!(!tx.is_full());
+        assert_eq!(Ok(()), tx.try_enqueue(13));
+        assert_eq!(Ok(()), tx.try_enqueue(14));
+        assert!(tx.is_full());
+        assert_eq!(Err((15, EnqueueError::Full)), tx.try_enqueue(15));
+
+        drop(rx);
+    }
+    #[test]
+    fn enqueue_is_closed() {
+        let (rx, mut tx) = const_queue::<usize, 3>();
+
+        drop(rx);
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.try_enqueue(13));
+    }
+    #[test]
+    fn enqueue_dequeue_is_closed() {
+        let (mut rx, mut tx) = const_queue::<usize, 3>();
+
+        tx.try_enqueue(13).unwrap();
+        drop(tx);
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+        assert_eq!(None, rx.dequeue());
+    }
+    #[test]
+    fn close_and_drain() {
+        let (rx, mut tx) = const_queue::<usize, 4>();
+
+        tx.try_enqueue(13).unwrap();
+        tx.try_enqueue(14).unwrap();
+
+        assert_eq!(Vec::from([13, 14]), rx.close_and_drain());
+        assert_eq!(Err((15, EnqueueError::Closed)), tx.try_enqueue(15));
+    }
+    #[test]
+    #[should_panic]
+    fn zero_capacity() {
+        const_queue::<usize, 0>();
+    }
+}
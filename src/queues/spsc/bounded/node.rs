@@ -1,4 +1,6 @@
-use core::{cell::UnsafeCell, sync::atomic};
+use core::cell::UnsafeCell;
+
+use crate::sync::native::atomic;
 
 /// A Node is a single Entry in the Buffer of the Queue
 pub struct Node<T> {
@@ -26,13 +28,10 @@ impl<T> Node<T> {
     /// Stores the given Data into the current Node and marks the Node as being
     /// `set` and ready to be consumed
     pub fn store(&self, data: T) {
-        // Get the mutable access to the underlying Data in order to overwrite
-        // it with the given new Data
+        // Store the Data through the raw Ptr, without creating a `&mut`
+        // Reference, as the Node itself is shared with the Receiver
         let d_ptr = self.data.get();
-        let mut_data = unsafe { &mut *d_ptr };
-
-        // Actually store the Data into the Node
-        mut_data.replace(data);
+        drop(unsafe { d_ptr.replace(Some(data)) });
 
         // Mark the Node as `set` again
         self.is_set.store(true, atomic::Ordering::Release);
@@ -41,13 +40,10 @@ impl<T> Node<T> {
     /// Attempts to load the current Data from the Node and marks the Data as
     /// empty again
     pub fn load(&self) -> T {
-        // Get the mutable access to the underlying Data in order to properly
-        // take it out and replace it with empty Data
+        // Take the Data out through the raw Ptr and replace it with empty
+        // Data, without creating a `&mut` Reference to the shared Node
         let d_ptr = self.data.get();
-        let mut_data = unsafe { &mut *d_ptr };
-
-        // Take the Data out of the Option
-        let data = mut_data.take().unwrap();
+        let data = unsafe { d_ptr.replace(None) }.unwrap();
         // Mark the Node as empty again
         self.is_set.store(false, atomic::Ordering::Release);
 
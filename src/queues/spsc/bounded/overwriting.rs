@@ -0,0 +1,403 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::fmt::Debug;
+
+use crate::sync::native::atomic;
+
+use crate::{
+    queues::{DequeueError, EnqueueError},
+    utils::Backoff,
+};
+
+use super::node::Node;
+
+/// A single Entry in the Buffer of an Overwriting-Queue
+struct Slot<T> {
+    /// The Node storing the actual Data
+    node: Node<T>,
+    /// The Position of the Element that was stored in the Node last, which
+    /// is only updated after the Data itself was stored.
+    ///
+    /// A set Node may still contain the Element from the previous Round,
+    /// while the Producer is taking it out to overwrite it, so the Consumer
+    /// needs to check this before claiming the Element for a Position
+    position: atomic::AtomicUsize,
+}
+
+/// The State shared between the two Halves of an Overwriting-Queue
+struct Shared<T> {
+    /// Indicates if the Queue has been closed or not
+    closed: atomic::AtomicBool,
+    /// The Position of the oldest Element in the Queue, which is advanced by
+    /// the Consumer when dequeuing and by the Producer when it overwrites the
+    /// oldest Element, so only one of them can ever take it
+    tail: atomic::AtomicUsize,
+    /// The underlying Buffer of Slots
+    buffer: Vec<Slot<T>>,
+}
+
+impl<T> Shared<T> {
+    /// Gets the Slot for the given Position
+    fn slot(&self, position: usize) -> &Slot<T> {
+        // # Safety:
+        // The Index is always in the Range `0..len` because of the Modulo
+        unsafe { self.buffer.get_unchecked(position % self.buffer.len()) }
+    }
+}
+
+/// The Sending-Half for a bounded Queue that overwrites its oldest Element
+/// once it is full, created using [`overwriting_queue`]
+pub struct OverwritingSender<T> {
+    /// The Position of the next Node to store Data into
+    head: usize,
+    /// The State shared with the Receiver
+    shared: Arc<Shared<T>>,
+}
+
+/// The Receiving-Half for a bounded Queue that overwrites its oldest Element
+/// once it is full, created using [`overwriting_queue`]
+pub struct OverwritingReceiver<T> {
+    /// The State shared with the Sender
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> OverwritingSender<T> {
+    /// Returns whether or not the Queue has been closed by the Consumer
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(atomic::Ordering::Acquire)
+    }
+
+    /// Returns the Capacity of the Queue
+    pub fn capacity(&self) -> usize {
+        self.shared.buffer.len()
+    }
+
+    /// Enqueues the given piece of Data, if the Queue is already full the
+    /// oldest Element that has not been dequeued yet is removed to make room
+    /// for it and returned as `Ok(Some(oldest))`.
+    ///
+    /// # Progress
+    /// If the Consumer is dequeuing the oldest Element at the same Time, this
+    /// waits for it to finish taking the Element out of its Node, which
+    /// only takes a bounded Number of Steps
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// let (mut rx, mut tx) = bounded::overwriting_queue::<usize>(2);
+    ///
+    /// assert_eq!(Ok(None), tx.enqueue(1));
+    /// assert_eq!(Ok(None), tx.enqueue(2));
+    /// // The Queue is full, so the oldest Element is overwritten
+    /// assert_eq!(Ok(Some(1)), tx.enqueue(3));
+    ///
+    /// assert_eq!(Ok(2), rx.try_dequeue());
+    /// assert_eq!(Ok(3), rx.try_dequeue());
+    /// ```
+    pub fn enqueue(&mut self, data: T) -> Result<Option<T>, (T, EnqueueError)> {
+        if self.is_closed() {
+            return Err((data, EnqueueError::Closed));
+        }
+
+        let slot = self.shared.slot(self.head);
+        let node = &slot.node;
+
+        let mut overwritten = None;
+        if node.is_set() {
+            // The Queue is full and the Node still contains the oldest
+            // Element, which we try to take before the Consumer does
+            let oldest = self.head.wrapping_sub(self.shared.buffer.len());
+            match self.shared.tail.compare_exchange(
+                oldest,
+                oldest.wrapping_add(1),
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    overwritten = Some(node.load());
+                }
+                Err(_) => {
+                    // The Consumer took the Element and is about to clear
+                    // the Node, so we only need to wait for it to finish
+                    let backoff = Backoff::new();
+                    while node.is_set() {
+                        backoff.spin();
+                    }
+                }
+            };
+        }
+
+        node.store(data);
+        slot.position.store(self.head, atomic::Ordering::Release);
+        self.head = self.head.wrapping_add(1);
+
+        Ok(overwritten)
+    }
+}
+
+impl<T> Debug for OverwritingSender<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "OverwritingSender ()")
+    }
+}
+
+impl<T> Drop for OverwritingSender<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, atomic::Ordering::Release);
+    }
+}
+
+// Safety:
+// The Halves can only be used to move Elements between Threads, so they are
+// only Send and Sync if the Elements themselves can be send across Threads
+unsafe impl<T> Send for OverwritingSender<T> where T: Send {}
+unsafe impl<T> Sync for OverwritingSender<T> where T: Send {}
+
+impl<T> OverwritingReceiver<T> {
+    /// Checks if the Queue has been closed by the Producer
+    ///
+    /// # Note
+    /// Even when this indicates that the Queue has been closed, there might
+    /// still be Items in the Queue left that should first be dequeued by the
+    /// Consumer before discarding the entire Queue
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(atomic::Ordering::Acquire)
+    }
+
+    /// Attempts to Dequeue the oldest Element from the Queue
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bounded;
+    /// # use nolock::queues::DequeueError;
+    /// let (mut rx, mut tx) = bounded::overwriting_queue::<usize>(4);
+    ///
+    /// tx.enqueue(13).unwrap();
+    ///
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    /// assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    /// ```
+    pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        loop {
+            let tail = self.shared.tail.load(atomic::Ordering::Acquire);
+            let slot = self.shared.slot(tail);
+
+            // The Node may be empty or still contain the Element from the
+            // previous Round, which the Producer is currently overwriting
+            if slot.position.load(atomic::Ordering::Acquire) != tail {
+                // The Producer may have overwritten the oldest Element in the
+                // mean Time, which leaves its Node empty for a short Time
+                if self.shared.tail.load(atomic::Ordering::Acquire) != tail {
+                    continue;
+                }
+
+                // We need to recheck the Slot, because it may have been set
+                // in the mean time and then the closed flag was updated
+                if self.is_closed() && slot.position.load(atomic::Ordering::Acquire) != tail {
+                    return Err(DequeueError::Closed);
+                }
+
+                return Err(DequeueError::Empty);
+            }
+
+            // Claim the Element, which fails if the Producer overwrote it
+            // in the mean Time
+            if self
+                .shared
+                .tail
+                .compare_exchange(
+                    tail,
+                    tail.wrapping_add(1),
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Acquire,
+                )
+                .is_err()
+            {
+                continue;
+            }
+
+            return Ok(slot.node.load());
+        }
+    }
+
+    /// A blocking dequeue operations. This is not lock-free anymore and simply
+    /// spins, with an exponential [`Backoff`], while trying to dequeue until
+    /// it works.
+    pub fn dequeue(&mut self) -> Option<T> {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_dequeue() {
+                Ok(d) => return Some(d),
+                Err(DequeueError::Empty) => backoff.snooze(),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+            };
+        }
+    }
+}
+
+impl<T> Debug for OverwritingReceiver<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "OverwritingReceiver ()")
+    }
+}
+
+impl<T> Drop for OverwritingReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, atomic::Ordering::Release);
+    }
+}
+
+// Safety:
+// The Halves can only be used to move Elements between Threads, so they are
+// only Send and Sync if the Elements themselves can be send across Threads
+unsafe impl<T> Send for OverwritingReceiver<T> where T: Send {}
+unsafe impl<T> Sync for OverwritingReceiver<T> where T: Send {}
+
+/// Creates a new Bounded-Queue with the given Capacity, which overwrites its
+/// oldest Element instead of rejecting new ones once it is full, and returns
+/// the corresponding Handles ([`OverwritingReceiver`], [`OverwritingSender`]).
+///
+/// This is useful for Streams where newer Data is more important than older
+/// Data, like Telemetry, where a slow Consumer should only ever see the most
+/// recent Samples.
+///
+/// # Panics
+/// If the Capacity is 0
+///
+/// # Example
+/// ```
+/// # use nolock::queues::spsc::bounded;
+/// let (mut rx, mut tx) = bounded::overwriting_queue::<usize>(3);
+///
+/// for sample in 0..10 {
+///     tx.enqueue(sample).unwrap();
+/// }
+///
+/// // Only the most recent Samples are left
+/// assert_eq!(Ok(7), rx.try_dequeue());
+/// assert_eq!(Ok(8), rx.try_dequeue());
+/// assert_eq!(Ok(9), rx.try_dequeue());
+/// ```
+pub fn overwriting_queue<T>(capacity: usize) -> (OverwritingReceiver<T>, OverwritingSender<T>) {
+    assert!(capacity > 0, "The Capacity needs to be at least 1");
+
+    let mut buffer = Vec::with_capacity(capacity);
+    for index in 0..capacity {
+        buffer.push(Slot {
+            node: Node::new(),
+            // The Slot did not store any Element in the Round before the
+            // first one
+            position: atomic::AtomicUsize::new(index.wrapping_sub(capacity)),
+        });
+    }
+
+    let shared = Arc::new(Shared {
+        closed: atomic::AtomicBool::new(false),
+        tail: atomic::AtomicUsize::new(0),
+        buffer,
+    });
+
+    (
+        OverwritingReceiver {
+            shared: shared.clone(),
+        },
+        OverwritingSender { head: 0, shared },
+    )
+}
+
+crate::queues::adapter::impl_receiver!(OverwritingReceiver<T>);
+
+// The Queue never becomes full, so Elements are simply overwritten and
+// dropped when used as a DynSender
+impl<T> crate::queues::dynamic::DynSender<T> for OverwritingSender<T> {
+    fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        OverwritingSender::enqueue(self, data).map(|_| ())
+    }
+
+    fn enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        OverwritingSender::enqueue(self, data).map(|_| ())
+    }
+
+    fn is_closed(&self) -> bool {
+        OverwritingSender::is_closed(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enqueue_dequeue() {
+        let (mut rx, mut tx) = overwriting_queue::<usize>(2);
+
+        assert_eq!(Ok(None), tx.enqueue(1));
+        assert_eq!(Ok(1), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+
+    #[test]
+    fn overwrites_oldest() {
+        let (mut rx, mut tx) = overwriting_queue::<usize>(2);
+
+        assert_eq!(Ok(None), tx.enqueue(1));
+        assert_eq!(Ok(None), tx.enqueue(2));
+        assert_eq!(Ok(Some(1)), tx.enqueue(3));
+        assert_eq!(Ok(Some(2)), tx.enqueue(4));
+
+        assert_eq!(Ok(3), rx.try_dequeue());
+        assert_eq!(Ok(None), tx.enqueue(5));
+        assert_eq!(Ok(Some(4)), tx.enqueue(6));
+
+        assert_eq!(Ok(5), rx.try_dequeue());
+        assert_eq!(Ok(6), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+
+    #[test]
+    fn closed() {
+        let (mut rx, mut tx) = overwriting_queue::<usize>(2);
+
+        tx.enqueue(1).unwrap();
+        drop(tx);
+        assert_eq!(Ok(1), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+
+        let (rx, mut tx) = overwriting_queue::<usize>(2);
+        drop(rx);
+        assert_eq!(Err((1, EnqueueError::Closed)), tx.enqueue(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_capacity() {
+        overwriting_queue::<usize>(0);
+    }
+
+    #[test]
+    fn concurrent_in_order() {
+        let (mut rx, mut tx) = overwriting_queue::<usize>(4);
+
+        const COUNT: usize = 100_000;
+        let handle = std::thread::spawn(move || {
+            let mut overwritten = 0;
+            for i in 0..COUNT {
+                if tx.enqueue(i).unwrap().is_some() {
+                    overwritten += 1;
+                }
+            }
+            overwritten
+        });
+
+        let mut received = 0;
+        let mut last = None;
+        while let Some(value) = rx.dequeue() {
+            assert!(last.map(|l| l < value).unwrap_or(true));
+            last = Some(value);
+            received += 1;
+        }
+
+        let overwritten = handle.join().unwrap();
+        assert_eq!(COUNT, received + overwritten);
+        assert_eq!(Some(COUNT - 1), last);
+    }
+}
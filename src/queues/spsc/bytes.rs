@@ -0,0 +1,547 @@
+//! A bounded lock-free Ring-Buffer of Bytes
+//!
+//! Instead of transferring individual Elements, this transfers arbitrary
+//! Chunks of Bytes from the Producer to the Consumer, which is useful for
+//! streaming Data, like Audio-Frames, where the Data is produced and consumed
+//! in Chunks of varying Sizes.
+//!
+//! # Zero-Copy
+//! Besides [`write`](ByteSender::write) and [`read`](ByteReceiver::read),
+//! which copy the Data from/into a given Buffer, the Ring-Buffer can also be
+//! accessed directly:
+//! * The Producer can [`reserve`](ByteSender::reserve) a Region of the
+//!   Ring-Buffer, write its Data directly into it and then
+//!   [`commit`](ByteSender::commit) the written Bytes
+//! * The Consumer can [`peek`](ByteReceiver::peek) at the readable Region of
+//!   the Ring-Buffer and then [`consume`](ByteReceiver::consume) the Bytes it
+//!   processed
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::spsc::bytes;
+//! let (mut tx, mut rx) = bytes::channel(16);
+//!
+//! assert_eq!(Ok(5), tx.write(b"hello"));
+//!
+//! let mut buffer = [0; 16];
+//! assert_eq!(Ok(5), rx.read(&mut buffer));
+//! assert_eq!(b"hello", &buffer[..5]);
+//! ```
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{cell::UnsafeCell, fmt::Debug};
+
+use crate::{
+    queues::{DequeueError, EnqueueError},
+    sync::native::atomic,
+    utils::CachePadded,
+};
+
+/// The State shared between the Sender and the Receiver
+struct Shared {
+    /// The underlying Ring-Buffer, whose Length is the Capacity rounded up to
+    /// the next Power of two, so the Positions can still be mapped to it
+    /// once they wrapped around
+    buffer: Box<[UnsafeCell<u8>]>,
+    /// The maximum Number of Bytes in the Ring-Buffer at once
+    capacity: usize,
+    /// The total Number of Bytes the Receiver has consumed so far, which
+    /// wraps around
+    head: CachePadded<atomic::AtomicUsize>,
+    /// The total Number of Bytes the Sender has committed so far, which
+    /// wraps around
+    tail: CachePadded<atomic::AtomicUsize>,
+    /// Indicates if either Side has been dropped
+    closed: atomic::AtomicBool,
+}
+
+impl Shared {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The Number of Bytes from the given Position until the End of the
+    /// Ring-Buffer
+    fn until_end(&self, position: usize) -> usize {
+        self.buffer.len() - (position & (self.buffer.len() - 1))
+    }
+
+    /// Returns a Ptr to the Byte at the given Position in the Ring-Buffer
+    fn ptr(&self, position: usize) -> *mut u8 {
+        let start = UnsafeCell::raw_get(self.buffer.as_ptr());
+        unsafe { start.add(position & (self.buffer.len() - 1)) }
+    }
+}
+
+/// The Sending-Half of the Byte-Queue
+pub struct ByteSender {
+    shared: Arc<Shared>,
+    /// The local Copy of the Tail, as only the Sender modifies it
+    tail: usize,
+    /// The last known Value of the Head
+    cached_head: usize,
+    /// The Number of Bytes that were returned by the last Reservation
+    reserved: usize,
+}
+
+/// The Receiving-Half of the Byte-Queue
+pub struct ByteReceiver {
+    shared: Arc<Shared>,
+    /// The local Copy of the Head, as only the Receiver modifies it
+    head: usize,
+    /// The Value of the Tail, when it was last loaded
+    cached_tail: usize,
+}
+
+impl ByteSender {
+    /// The Number of Bytes the Queue can hold at once
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity()
+    }
+
+    /// Returns whether or not the Queue has been closed by the Receiver
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(atomic::Ordering::Acquire)
+    }
+
+    /// Returns the Number of free Bytes, only reloading the Head from the
+    /// Receiver if there are less than `needed` free Bytes
+    fn free(&mut self, needed: usize) -> usize {
+        let capacity = self.capacity();
+        let free = capacity - self.tail.wrapping_sub(self.cached_head);
+        if free >= needed {
+            return free;
+        }
+
+        self.cached_head = self.shared.head.load(atomic::Ordering::Acquire);
+        capacity - self.tail.wrapping_sub(self.cached_head)
+    }
+
+    /// Reserves a continuous Region of up to `max` Bytes in the Ring-Buffer,
+    /// which can then be written to directly and published to the Receiver
+    /// using [`commit`](Self::commit).
+    ///
+    /// The returned Region may be shorter than `max`, if there is not
+    /// enough free Space or the Region would wrap around the End of the
+    /// Ring-Buffer, in which case a second Reservation returns the rest.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bytes;
+    /// let (mut tx, mut rx) = bytes::channel(8);
+    ///
+    /// let region = tx.reserve(3).unwrap();
+    /// region.copy_from_slice(&[1, 2, 3]);
+    /// tx.commit(3);
+    ///
+    /// assert_eq!(Ok(&[1, 2, 3][..]), rx.peek());
+    /// ```
+    pub fn reserve(&mut self, max: usize) -> Result<&mut [u8], EnqueueError> {
+        if self.is_closed() {
+            return Err(EnqueueError::Closed);
+        }
+
+        let free = self.free(max);
+        if free == 0 {
+            return Err(EnqueueError::Full);
+        }
+
+        let length = max.min(free).min(self.shared.until_end(self.tail));
+        self.reserved = length;
+
+        // Safety:
+        // The Region between the Tail and the Head is only ever accessed by
+        // the Sender and the Receiver will only access it once it was
+        // committed, which requires the returned Borrow to end
+        Ok(unsafe { core::slice::from_raw_parts_mut(self.shared.ptr(self.tail), length) })
+    }
+
+    /// Publishes the first `count` Bytes of the last Reservation to the
+    /// Receiver.
+    ///
+    /// # Panics
+    /// If `count` is larger than the last Reservation
+    pub fn commit(&mut self, count: usize) {
+        assert!(
+            count <= self.reserved,
+            "Committed {} Bytes, but only {} were reserved",
+            count,
+            self.reserved
+        );
+
+        self.reserved = 0;
+        self.tail = self.tail.wrapping_add(count);
+        self.shared.tail.store(self.tail, atomic::Ordering::Release);
+    }
+
+    /// Writes as many Bytes from the given Data as there is currently Space
+    /// for and returns the Number of written Bytes.
+    ///
+    /// # Returns
+    /// * `Ok(n)` if `n` Bytes were written, which may be less than the
+    ///   Length of the Data
+    /// * `Err(EnqueueError::Full)` if there was no Space for even a single
+    ///   Byte
+    /// * `Err(EnqueueError::Closed)` if the Receiver has been dropped
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bytes;
+    /// # use nolock::queues::EnqueueError;
+    /// let (mut tx, mut rx) = bytes::channel(4);
+    ///
+    /// assert_eq!(Ok(4), tx.write(b"hello"));
+    /// assert_eq!(Err(EnqueueError::Full), tx.write(b"o"));
+    /// # drop(rx);
+    /// ```
+    pub fn write(&mut self, data: &[u8]) -> Result<usize, EnqueueError> {
+        let mut written = 0;
+        while written < data.len() {
+            let region = match self.reserve(data.len() - written) {
+                Ok(r) => r,
+                Err(e) if written == 0 => return Err(e),
+                Err(_) => break,
+            };
+
+            let length = region.len();
+            region.copy_from_slice(&data[written..written + length]);
+            self.commit(length);
+
+            written += length;
+        }
+
+        Ok(written)
+    }
+}
+
+impl ByteReceiver {
+    /// The Number of Bytes the Queue can hold at once
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity()
+    }
+
+    /// Returns whether or not the Queue has been closed by the Sender
+    ///
+    /// # Note
+    /// Even if the Queue has been closed, there might still be Bytes left in
+    /// it that can be read
+    pub fn is_closed(&self) -> bool {
+        self.shared.closed.load(atomic::Ordering::Acquire)
+    }
+
+    /// The Number of Bytes that can currently be read
+    pub fn len(&mut self) -> usize {
+        self.cached_tail = self.shared.tail.load(atomic::Ordering::Acquire);
+        self.cached_tail.wrapping_sub(self.head)
+    }
+
+    /// Checks if there are currently no Bytes that can be read
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the Number of readable Bytes
+    fn available(&mut self) -> Result<usize, DequeueError> {
+        // The Closed-Flag needs to be loaded before the Tail, because the
+        // Sender only closes the Queue after its last Commit
+        let closed = self.is_closed();
+        match self.len() {
+            0 if closed => Err(DequeueError::Closed),
+            0 => Err(DequeueError::Empty),
+            available => Ok(available),
+        }
+    }
+
+    /// Returns the continuous Region of readable Bytes in the Ring-Buffer.
+    ///
+    /// The Region may not contain all the readable Bytes, if they wrap around
+    /// the End of the Ring-Buffer, in which case the rest is returned once
+    /// the current Region was [`consumed`](Self::consume).
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bytes;
+    /// let (mut tx, mut rx) = bytes::channel(8);
+    ///
+    /// tx.write(&[1, 2, 3]).unwrap();
+    ///
+    /// let region = rx.peek().unwrap();
+    /// assert_eq!(&[1, 2, 3], region);
+    /// rx.consume(2);
+    ///
+    /// assert_eq!(Ok(&[3][..]), rx.peek());
+    /// ```
+    pub fn peek(&mut self) -> Result<&[u8], DequeueError> {
+        let available = self.available()?;
+
+        let length = available.min(self.shared.until_end(self.head));
+
+        // Safety:
+        // The Region between the Head and the Tail has already been
+        // committed by the Sender and will not be modified until it is
+        // consumed
+        Ok(unsafe { core::slice::from_raw_parts(self.shared.ptr(self.head), length) })
+    }
+
+    /// Marks the first `count` readable Bytes as consumed, which frees them
+    /// up to be written again by the Sender
+    ///
+    /// # Panics
+    /// If `count` is larger than the Number of readable Bytes
+    pub fn consume(&mut self, count: usize) {
+        let available = self.cached_tail.wrapping_sub(self.head);
+        assert!(
+            count <= available,
+            "Consumed {} Bytes, but only {} were readable",
+            count,
+            available
+        );
+
+        self.head = self.head.wrapping_add(count);
+        self.shared.head.store(self.head, atomic::Ordering::Release);
+    }
+
+    /// Reads as many Bytes into the given Buffer as are currently available
+    /// and returns the Number of read Bytes.
+    ///
+    /// # Returns
+    /// * `Ok(n)` if `n` Bytes were read, which may be less than the Length of
+    ///   the Buffer
+    /// * `Err(DequeueError::Empty)` if there were no Bytes to read
+    /// * `Err(DequeueError::Closed)` if there were no Bytes to read and the
+    ///   Sender has been dropped
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::bytes;
+    /// # use nolock::queues::DequeueError;
+    /// let (mut tx, mut rx) = bytes::channel(8);
+    ///
+    /// tx.write(b"hello").unwrap();
+    /// drop(tx);
+    ///
+    /// let mut buffer = [0; 3];
+    /// assert_eq!(Ok(3), rx.read(&mut buffer));
+    /// assert_eq!(b"hel", &buffer);
+    /// assert_eq!(Ok(2), rx.read(&mut buffer));
+    /// assert_eq!(b"lo", &buffer[..2]);
+    /// assert_eq!(Err(DequeueError::Closed), rx.read(&mut buffer));
+    /// ```
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize, DequeueError> {
+        let mut read = 0;
+        while read < buffer.len() {
+            let region = match self.peek() {
+                Ok(r) => r,
+                Err(e) if read == 0 => return Err(e),
+                Err(_) => break,
+            };
+
+            let length = region.len().min(buffer.len() - read);
+            buffer[read..read + length].copy_from_slice(&region[..length]);
+            self.consume(length);
+
+            read += length;
+        }
+
+        Ok(read)
+    }
+
+    /// Reads all the Bytes that are currently available into a new Vec
+    pub fn read_to_vec(&mut self) -> Result<Vec<u8>, DequeueError> {
+        let mut result = alloc::vec![0; self.available()?];
+        let read = self.read(&mut result)?;
+        result.truncate(read);
+        Ok(result)
+    }
+}
+
+impl Debug for ByteSender {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ByteSender ()")
+    }
+}
+impl Debug for ByteReceiver {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ByteReceiver ()")
+    }
+}
+
+impl Drop for ByteSender {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, atomic::Ordering::Release);
+    }
+}
+impl Drop for ByteReceiver {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, atomic::Ordering::Release);
+    }
+}
+
+// This is safe to share across Threads, because the Sender and Receiver only
+// ever access disjoint Regions of the Ring-Buffer
+unsafe impl Send for Shared {}
+unsafe impl Sync for Shared {}
+
+/// Creates a new Byte-Queue with the Capacity for the given Number of Bytes
+/// and returns the Halves as ([`ByteSender`], [`ByteReceiver`])
+///
+/// # Note
+/// The Ring-Buffer itself is allocated with the Capacity rounded up to the
+/// next Power of two, but it never holds more than `capacity` Bytes
+///
+/// # Panics
+/// If the Capacity is 0
+pub fn channel(capacity: usize) -> (ByteSender, ByteReceiver) {
+    assert!(capacity > 0, "The Capacity needs to be at least 1");
+
+    let length = capacity
+        .checked_next_power_of_two()
+        .expect("The Capacity is too large");
+    let buffer: Box<[UnsafeCell<u8>]> = (0..length).map(|_| UnsafeCell::new(0)).collect();
+    let shared = Arc::new(Shared {
+        buffer,
+        capacity,
+        head: CachePadded::new(atomic::AtomicUsize::new(0)),
+        tail: CachePadded::new(atomic::AtomicUsize::new(0)),
+        closed: atomic::AtomicBool::new(false),
+    });
+
+    (
+        ByteSender {
+            shared: shared.clone(),
+            tail: 0,
+            cached_head: 0,
+            reserved: 0,
+        },
+        ByteReceiver {
+            shared,
+            head: 0,
+            cached_tail: 0,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_read() {
+        let (mut tx, mut rx) = channel(8);
+
+        assert_eq!(Err(DequeueError::Empty), rx.read(&mut [0; 4]));
+
+        assert_eq!(Ok(5), tx.write(b"hello"));
+        assert_eq!(5, rx.len());
+
+        let mut buffer = [0; 8];
+        assert_eq!(Ok(5), rx.read(&mut buffer));
+        assert_eq!(b"hello", &buffer[..5]);
+        assert!(rx.is_empty());
+    }
+
+    #[test]
+    fn wraps_around() {
+        let (mut tx, mut rx) = channel(8);
+        assert_eq!(Ok(6), tx.write(b"abcdef"));
+        assert_eq!(Ok(4), rx.read(&mut [0; 4]));
+
+        // This wraps around the End of the Ring-Buffer
+        assert_eq!(Ok(6), tx.write(b"ghijkl"));
+        assert_eq!(Err(EnqueueError::Full), tx.write(b"m"));
+
+        assert_eq!(Ok(&b"efgh"[..]), rx.peek());
+        assert_eq!(Ok(b"efghijkl".to_vec()), rx.read_to_vec());
+    }
+
+    #[test]
+    fn positions_wrap_around() {
+        let (mut tx, mut rx) = channel(5);
+
+        // Start the Positions right before they wrap around
+        let start = usize::MAX - 1;
+        tx.tail = start;
+        tx.cached_head = start;
+        tx.shared.head.store(start, atomic::Ordering::Release);
+        tx.shared.tail.store(start, atomic::Ordering::Release);
+        rx.head = start;
+        rx.cached_tail = start;
+
+        // The unread Bytes before and after the Wrap-Around must not overlap
+        assert_eq!(Ok(2), tx.write(b"ab"));
+        assert_eq!(Ok(2), tx.write(b"cd"));
+        assert_eq!(Ok(b"abcd".to_vec()), rx.read_to_vec());
+
+        assert_eq!(Ok(5), tx.write(b"abcdefg"));
+        assert_eq!(Err(EnqueueError::Full), tx.write(b"h"));
+        assert_eq!(Ok(b"abcde".to_vec()), rx.read_to_vec());
+    }
+
+    #[test]
+    fn reserve_commit() {
+        let (mut tx, mut rx) = channel(4);
+
+        let region = tx.reserve(8).unwrap();
+        assert_eq!(4, region.len());
+        region[..2].copy_from_slice(b"ab");
+        tx.commit(2);
+
+        assert_eq!(Ok(&b"ab"[..]), rx.peek());
+        rx.consume(1);
+        assert_eq!(Ok(&b"b"[..]), rx.peek());
+    }
+
+    #[test]
+    #[should_panic]
+    fn commit_more_than_reserved() {
+        let (mut tx, _rx) = channel(4);
+
+        tx.reserve(2).unwrap();
+        tx.commit(3);
+    }
+
+    #[test]
+    fn closed() {
+        let (mut tx, rx) = channel(4);
+        drop(rx);
+        assert!(tx.is_closed());
+        assert_eq!(Err(EnqueueError::Closed), tx.write(b"a"));
+
+        let (mut tx, mut rx) = channel(4);
+        tx.write(b"ab").unwrap();
+        drop(tx);
+        assert!(rx.is_closed());
+        assert_eq!(Ok(b"ab".to_vec()), rx.read_to_vec());
+        assert_eq!(Err(DequeueError::Closed), rx.read_to_vec());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn stream_across_threads() {
+        let (mut tx, mut rx) = channel(64);
+
+        let handle = std::thread::spawn(move || {
+            let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+            let mut written = 0;
+            while written < data.len() {
+                let end = (written + 37).min(data.len());
+                if let Ok(n) = tx.write(&data[written..end]) {
+                    written += n;
+                }
+            }
+        });
+
+        let mut received = Vec::new();
+        loop {
+            match rx.read_to_vec() {
+                Ok(chunk) => received.extend(chunk),
+                Err(DequeueError::Empty) => {}
+                Err(DequeueError::Closed | DequeueError::Cancelled) => break,
+            };
+        }
+        handle.join().unwrap();
+
+        let expected: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        assert_eq!(expected, received);
+    }
+}
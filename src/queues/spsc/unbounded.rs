@@ -13,37 +13,63 @@
 //! assert_eq!(Ok(13), rx.try_dequeue());
 //! ```
 //!
+//! # Segments
+//! The Queue is made up of bounded Segments, which are linked directly to
+//! each other. Once the current Segment is full, the Producer appends a new
+//! one to it and the Consumer follows the Link once it consumed all the
+//! Elements in the previous Segment, which is then freed.
+//!
 //! # Reference:
 //! * [An Efficient Unbounded Lock-Free Queue - for Multi-core Systems](https://link.springer.com/content/pdf/10.1007%2F978-3-642-32820-6_65.pdf)
 
-mod d_spsc;
+mod drain;
+pub use drain::DrainAll;
+
+use alloc::vec::Vec;
+use core::fmt::Debug;
 
+#[cfg(feature = "metrics")]
 use alloc::sync::Arc;
-use core::{fmt::Debug, sync::atomic};
 
 use super::bounded;
-use crate::queues::{DequeueError, EnqueueError};
+#[cfg(feature = "async")]
+use crate::queues::cooperative::{self, CooperativeDequeue};
+use crate::{
+    queues::{
+        cancel::{self, CancelToken},
+        instrument::Metrics,
+        shutdown::ShutdownReport,
+        DequeueError, EnqueueError,
+    },
+    utils::Backoff,
+};
 
 #[cfg(feature = "async")]
 mod async_queue;
 #[cfg(feature = "async")]
 pub use async_queue::*;
 
+/// The Size of each Buffer, if not configured otherwise
+pub(crate) const DEFAULT_BUFFER_SIZE: usize = 64;
+
 // TODO
 // Add Support for the Caches to improve the Performance and reduce the overhead
 // of the Allocator
 
+/// An Alias for the [`UnboundedSender`], using the same Name as the Sending-Half
+/// of the other Queues
+pub type Sender<T> = UnboundedSender<T>;
+/// An Alias for the [`UnboundedReceiver`], using the same Name as the
+/// Receiving-Half of the other Queues
+pub type Receiver<T> = UnboundedReceiver<T>;
+
 /// The Sender-Half of an unbounded Queue
 pub struct UnboundedSender<T> {
-    /// Indicates if the Queue has been closed or not
-    closed: Arc<atomic::AtomicBool>,
-    /// The Size of each Buffer
-    buffer_size: usize,
-    /// The current Buffer, where we insert entries
+    /// The current Segment, where we insert entries, to which new Segments
+    /// are linked once it becomes full
     buf_w: bounded::BoundedSender<T>,
-    /// This is used to inform the Consumer aboutu any new Buffers we allocate
-    /// in case the current one becomes full
-    inuse_sender: d_spsc::UnboundedSender<bounded::BoundedReceiver<T>>,
+    /// The Hooks to report the Operations to
+    metrics: Metrics,
 }
 
 impl<T> UnboundedSender<T> {
@@ -60,19 +86,14 @@ impl<T> UnboundedSender<T> {
     /// assert_eq!(true, tx.is_closed());
     /// ```
     pub fn is_closed(&self) -> bool {
-        self.closed.load(atomic::Ordering::Acquire)
+        self.buf_w.is_closed()
     }
 
-    /// Creates a new BoundedQueue and sends the Receiving half of the new
-    /// BoundedQueue to the Consumer, using the `inuse_sender`.
-    fn next_w(&mut self) -> bounded::BoundedSender<T> {
-        // Creates the new BoundedQueue with the configured BufferSize
-        let (rx, tx) = bounded::queue(self.buffer_size);
-        // Sends the Receiving half of the newly created BoundedQueue to the
-        // Consumer half
-        self.inuse_sender.enqueue(rx).unwrap();
-        // Return the sending Half to the caller
-        tx
+    /// Returns a Report, that tells how many Elements were still left in the
+    /// Queue once it has been shut down, see the
+    /// [`shutdown`](crate::queues::shutdown) module for more Details
+    pub fn shutdown_report(&self) -> ShutdownReport {
+        self.buf_w.shutdown_report()
     }
 
     /// Enqueues the Data
@@ -100,32 +121,32 @@ impl<T> UnboundedSender<T> {
     /// assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue(13));
     /// ```
     pub fn enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
-        if self.is_closed() {
-            return Err((data, EnqueueError::Closed));
-        }
-
-        // Attempt to enqueue the Data into the current BoundedQueue.
+        // Attempt to enqueue the Data into the current Segment.
         //
         // NOTE:
-        // We first assume that the current BoundedQueue has still room as this
+        // We first assume that the current Segment has still room as this
         // will be the case most of the Time and therefore helps to reduce
         // the time taken for the "fastest hot path" without impacting the
         // alternative very much
         //
-        // If this fails, we know that the BoundedQueue has to be full and
-        // therefore we start the process to create a new Buffer
-        if let Err((data, _)) = self.buf_w.try_enqueue(data) {
-            // Create new BoundedQueue and set it as the current BoundedQueue
-            // to use for any other writes/enqueues
-            self.buf_w = self.next_w();
-            // Retry the Enqueue operation with the new BoundedQueue
-            //
-            // This should always succeed because we just now created the
-            // BoundedQueue meaning that is still empty
-            if self.buf_w.try_enqueue(data).is_err() {
-                panic!("The new Buffer is always empty");
+        // If this fails because the Segment is full, we link a new Segment
+        // to it and use that one for any other writes/enqueues
+        match self.buf_w.try_enqueue(data) {
+            Ok(()) => {}
+            Err((data, EnqueueError::Full)) => {
+                self.buf_w.link_segment();
+                self.metrics.segment_alloc();
+
+                // Retry the Enqueue operation with the new Segment
+                //
+                // This should always succeed because we just now created the
+                // Segment meaning that is still empty, unless the Queue has
+                // been closed in the mean time
+                self.buf_w.try_enqueue(data)?;
             }
-        }
+            Err(e) => return Err(e),
+        };
+        self.metrics.enqueue();
 
         Ok(())
     }
@@ -137,24 +158,19 @@ impl<T> Debug for UnboundedSender<T> {
     }
 }
 
-impl<T> Drop for UnboundedSender<T> {
-    fn drop(&mut self) {
-        self.closed.store(true, atomic::Ordering::Release);
-    }
-}
-
-unsafe impl<T> Send for UnboundedSender<T> {}
-unsafe impl<T> Sync for UnboundedSender<T> {}
+// Safety:
+// The Halves can only be used to move Elements between Threads, so they are
+// only Send and Sync if the Elements themselves can be send across Threads
+unsafe impl<T> Send for UnboundedSender<T> where T: Send {}
+unsafe impl<T> Sync for UnboundedSender<T> where T: Send {}
 
 /// The Receiver-Half of an unbounded Queue
 pub struct UnboundedReceiver<T> {
-    /// Indicates if the Queue has been closed or not
-    closed: Arc<atomic::AtomicBool>,
-    /// The current BoundedQueue from which items are being Dequeued
+    /// The current Segment from which items are being Dequeued, which moves
+    /// on to the next Segment once the current one has been consumed
     buf_r: bounded::BoundedReceiver<T>,
-    /// This is used to receive information about any new BoundedQueues created
-    /// by the sending Half of this Queue
-    inuse_recv: d_spsc::UnboundedReceiver<bounded::BoundedReceiver<T>>,
+    /// The Hooks to report the Operations to
+    metrics: Metrics,
 }
 
 impl<T> UnboundedReceiver<T> {
@@ -171,7 +187,14 @@ impl<T> UnboundedReceiver<T> {
     /// assert_eq!(true, rx.is_closed());
     /// ```
     pub fn is_closed(&self) -> bool {
-        self.buf_r.is_closed() && !self.inuse_recv.has_next()
+        self.buf_r.is_closed()
+    }
+
+    /// Returns a Report, that tells how many Elements were still left in the
+    /// Queue once it has been shut down, see the
+    /// [`shutdown`](crate::queues::shutdown) module for more Details
+    pub fn shutdown_report(&self) -> ShutdownReport {
+        self.buf_r.shutdown_report()
     }
 
     /// Attempts to dequeue a single Element from the Queue
@@ -198,51 +221,130 @@ impl<T> UnboundedReceiver<T> {
     /// # drop(tx);
     /// ```
     pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
-        // Attempt to Dequeue an element from the current BoundedQueue
-        match self.buf_r.try_dequeue() {
-            // If we dequeued an Item, simply return that and we are done
-            Ok(d) => Ok(d),
-            // If we receive this Error, we know that the Queue is empty, but
-            // the Producer has not moved on to another Queue, as it would then
-            // be considered Closed and would return a different Error
-            Err(DequeueError::Empty) => Err(DequeueError::Empty),
-            // This indicates that the Producer has dropped the current Queue,
-            // which indicates that they either moved on to a new Queue already,
-            // as this one had been completly filled before, or that the entire
-            // Producer has been dropped.
-            //
-            // We therefore attempt to get the Next queue, to which the
-            // Producer would have moved on
-            Err(DequeueError::Closed) => match self.inuse_recv.try_dequeue() {
-                // If we find a new Queue, we can simply replace our old one
-                // with the new one and then attempt to Dequeue th first
-                // Element of it as our result
-                Ok(n_queue) => {
-                    self.buf_r = n_queue;
-                    self.buf_r.try_dequeue()
-                }
-                // If we cant find a new Queue, that means that the Producer
-                // has been closed and therefore the entire Queue is now also
-                // closed as there are no more Entries left in the Queue
-                Err(_) => Err(DequeueError::Closed),
-            },
+        // The current Segment automatically moves on to the next Segment, once
+        // the Producer linked one and all the Elements in the current one have
+        // been dequeued
+        let result = self.buf_r.try_dequeue();
+
+        if result.is_ok() {
+            self.metrics.dequeue();
         }
+        result
     }
 
     /// A simple blocking dequeue operation. This is not lock-free anymore
-    /// (obviously) and simply spins while trying to dequeue an element from
-    /// the Queue until it succeeds
+    /// (obviously) and simply spins, with an exponential [`Backoff`], while
+    /// trying to dequeue an element from the Queue until it succeeds
+    ///
+    /// # Async
+    /// This blocks the current Thread and therefore should not be called from async
+    /// Code, use [`dequeue_cooperative`](Self::dequeue_cooperative) instead
     pub fn dequeue(&mut self) -> Option<T> {
+        let backoff = Backoff::new();
         loop {
             match self.try_dequeue() {
                 Ok(d) => return Some(d),
                 Err(e) => match e {
-                    DequeueError::Empty => {}
-                    DequeueError::Closed => return None,
+                    DequeueError::Empty => backoff.snooze(),
+                    DequeueError::Closed | DequeueError::Cancelled => return None,
                 },
             };
         }
     }
+
+    /// A blocking dequeue operation like [`dequeue`](Self::dequeue), which
+    /// can additionally be cancelled from another Thread using the given
+    /// [`CancelToken`], see the [`cancel`](crate::queues::cancel) module for
+    /// more Details
+    ///
+    /// # Returns
+    /// * `Ok(data)` once an Element could be dequeued
+    /// * `Err(DequeueError::Closed)` if the Queue has been closed
+    /// * `Err(DequeueError::Cancelled)` if the Token was cancelled while the
+    ///   Queue was empty
+    pub fn dequeue_cancellable(&mut self, token: &CancelToken) -> Result<T, DequeueError> {
+        cancel::dequeue(token, || self.try_dequeue())
+    }
+
+    /// The async-friendly Variant of [`dequeue`](Self::dequeue), which yields
+    /// back to the Executor in between Attempts instead of blocking the Thread,
+    /// see the [`cooperative`](crate::queues::cooperative) module for more
+    /// Details
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn dequeue_cooperative(
+        &mut self,
+    ) -> CooperativeDequeue<impl FnMut() -> Result<T, DequeueError> + '_> {
+        cooperative::dequeue(move || self.try_dequeue())
+    }
+
+    /// Dequeues all the Elements that are currently stored in the Queue and
+    /// collects them into a [`Snapshot`](crate::queues::snapshot::Snapshot),
+    /// without closing the Queue.
+    ///
+    /// Elements that are enqueued concurrently may or may not be part of the
+    /// Snapshot.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::unbounded;
+    /// let (mut rx, mut tx) = unbounded::queue::<usize>();
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// tx.enqueue(14).unwrap();
+    ///
+    /// assert_eq!(&[13, 14], rx.snapshot().as_slice());
+    /// assert!(rx.snapshot().is_empty());
+    /// ```
+    pub fn snapshot(&mut self) -> crate::queues::snapshot::Snapshot<T> {
+        crate::queues::snapshot::Snapshot::drain(|| self.try_dequeue())
+    }
+
+    /// Returns an Iterator that dequeues all the Elements, that are currently
+    /// visible in the Queue, across all its Buffers, see [`DrainAll`] for
+    /// more Details
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::unbounded;
+    /// let (mut rx, mut tx) = unbounded::queue::<usize>();
+    ///
+    /// for i in 0..200 {
+    ///     tx.enqueue(i).unwrap();
+    /// }
+    ///
+    /// assert_eq!((0..200).collect::<Vec<_>>(), rx.drain_all().collect::<Vec<_>>());
+    /// assert!(rx.try_dequeue().is_err());
+    /// ```
+    pub fn drain_all(&mut self) -> DrainAll<'_, T> {
+        DrainAll::new(self)
+    }
+
+    /// Closes the Queue and returns all the Elements that were still left
+    /// in it, in the Order they were enqueued.
+    ///
+    /// # Example
+    /// ```
+    /// # use nolock::queues::spsc::unbounded;
+    /// let (rx, mut tx) = unbounded::queue::<usize>();
+    ///
+    /// for i in 0..100 {
+    ///     tx.enqueue(i).unwrap();
+    /// }
+    ///
+    /// assert_eq!((0..100).collect::<Vec<_>>(), rx.close_and_drain());
+    /// assert!(tx.is_closed());
+    /// ```
+    pub fn close_and_drain(mut self) -> Vec<T> {
+        self.buf_r.close();
+
+        let mut result = Vec::new();
+        while let Ok(data) = self.try_dequeue() {
+            result.push(data);
+        }
+
+        result
+    }
 }
 
 impl<T> Debug for UnboundedReceiver<T> {
@@ -251,39 +353,68 @@ impl<T> Debug for UnboundedReceiver<T> {
     }
 }
 
-impl<T> Drop for UnboundedReceiver<T> {
-    fn drop(&mut self) {
-        self.closed.store(true, atomic::Ordering::Release);
-    }
+// Safety:
+// The Halves can only be used to move Elements between Threads, so they are
+// only Send and Sync if the Elements themselves can be send across Threads
+unsafe impl<T> Send for UnboundedReceiver<T> where T: Send {}
+unsafe impl<T> Sync for UnboundedReceiver<T> where T: Send {}
+
+/// Creates a new Queue as ([`UnboundedReceiver`], [`UnboundedSender`]),
+/// the [`channel`] Constructor returns the Sender first instead
+pub fn queue<T>() -> (UnboundedReceiver<T>, UnboundedSender<T>) {
+    queue_instrumented(DEFAULT_BUFFER_SIZE, Metrics::none())
 }
 
-unsafe impl<T> Send for UnboundedReceiver<T> {}
-unsafe impl<T> Sync for UnboundedReceiver<T> {}
+/// Creates a new Queue and returns the
+/// Halves as ([`Sender`], [`Receiver`]), in the same Order as
+/// `std::sync::mpsc::channel`
+///
+/// # Example
+/// ```
+/// # use nolock::queues::spsc::unbounded;
+/// let (mut tx, mut rx) = unbounded::channel::<usize>();
+///
+/// tx.enqueue(13).unwrap();
+/// assert_eq!(Ok(13), rx.try_dequeue());
+/// ```
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let (rx, tx) = queue();
+    (tx, rx)
+}
 
-/// Creates a new Queue
-pub fn queue<T>() -> (UnboundedReceiver<T>, UnboundedSender<T>) {
-    let buffer_size = 64;
+/// Creates a new Queue, that reports all its Operations to the given
+/// [`QueueMetrics`](crate::queues::metrics::QueueMetrics)
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+pub fn queue_with_metrics<T>(
+    metrics: Arc<dyn crate::queues::metrics::QueueMetrics>,
+) -> (UnboundedReceiver<T>, UnboundedSender<T>) {
+    queue_instrumented(DEFAULT_BUFFER_SIZE, Metrics::new(metrics))
+}
 
-    let (inuse_rx, inuse_tx) = d_spsc::unbounded_basic_queue();
+pub(crate) fn queue_instrumented<T>(
+    buffer_size: usize,
+    metrics: Metrics,
+) -> (UnboundedReceiver<T>, UnboundedSender<T>) {
+    // The Segments report nothing themselves, as the Operations are
+    // reported by the unbounded Queue instead
     let (initial_rx, initial_tx) = bounded::queue(buffer_size);
 
-    let closed = Arc::new(atomic::AtomicBool::new(false));
-
     (
         UnboundedReceiver {
-            closed: closed.clone(),
             buf_r: initial_rx,
-            inuse_recv: inuse_rx,
+            metrics: metrics.clone(),
         },
         UnboundedSender {
-            closed,
-            buffer_size,
             buf_w: initial_tx,
-            inuse_sender: inuse_tx,
+            metrics,
         },
     )
 }
 
+crate::queues::adapter::impl_receiver!(UnboundedReceiver<T>);
+crate::queues::dynamic::impl_sender!(UnboundedSender<T>);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -316,6 +447,130 @@ mod tests {
 
         assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue(13));
     }
+
+    #[test]
+    fn enqueue_closed_full_segment() {
+        let (rx, mut tx) = queue_instrumented(1, Metrics::none());
+        tx.enqueue(12).unwrap();
+        drop(rx);
+
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue(13));
+    }
+
+    #[test]
+    fn close_and_drain_multiple_buffers() {
+        let (mut rx, mut tx) = queue();
+
+        for i in 0..200 {
+            tx.enqueue(i).unwrap();
+        }
+        assert_eq!(Ok(0), rx.try_dequeue());
+
+        assert_eq!((1..200).collect::<Vec<_>>(), rx.close_and_drain());
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue(13));
+    }
+    #[test]
+    fn shutdown_report_multiple_buffers() {
+        let (mut rx, mut tx) = queue();
+        let report = rx.shutdown_report();
+
+        for i in 0..(3 * DEFAULT_BUFFER_SIZE + 5) {
+            tx.enqueue(i).unwrap();
+        }
+        assert_eq!(Ok(0), rx.try_dequeue());
+
+        drop(tx);
+        assert_eq!(None, report.closed_with_remaining());
+
+        drop(rx);
+        assert_eq!(
+            Some(3 * DEFAULT_BUFFER_SIZE + 4),
+            report.closed_with_remaining()
+        );
+    }
+    #[test]
+    fn drain_all_multiple_buffers() {
+        let (mut rx, mut tx) = queue();
+
+        for i in 0..(3 * DEFAULT_BUFFER_SIZE + 5) {
+            tx.enqueue(i).unwrap();
+        }
+        assert_eq!(Ok(0), rx.try_dequeue());
+
+        let drained: Vec<_> = rx.drain_all().collect();
+        assert_eq!(
+            (1..(3 * DEFAULT_BUFFER_SIZE + 5)).collect::<Vec<_>>(),
+            drained
+        );
+
+        // The Queue can still be used normally afterwards
+        tx.enqueue(13).unwrap();
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+
+    #[test]
+    fn drain_all_partial() {
+        let (mut rx, mut tx) = queue();
+
+        for i in 0..(2 * DEFAULT_BUFFER_SIZE) {
+            tx.enqueue(i).unwrap();
+        }
+
+        // Only take some of the Elements, the Rest stays in the Queue
+        let drained: Vec<_> = rx.drain_all().take(DEFAULT_BUFFER_SIZE + 1).collect();
+        assert_eq!((0..(DEFAULT_BUFFER_SIZE + 1)).collect::<Vec<_>>(), drained);
+        assert_eq!(Ok(DEFAULT_BUFFER_SIZE + 1), rx.try_dequeue());
+    }
+
+    #[test]
+    fn drain_all_closed() {
+        let (mut rx, mut tx) = queue();
+
+        for i in 0..(DEFAULT_BUFFER_SIZE + 1) {
+            tx.enqueue(i).unwrap();
+        }
+        drop(tx);
+
+        assert_eq!(DEFAULT_BUFFER_SIZE + 1, rx.drain_all().count());
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+    }
+
+    #[test]
+    fn segments_linked_directly() {
+        let (mut rx, mut tx) = queue();
+
+        // Fill multiple Segments, before the Consumer starts dequeuing
+        for i in 0..(4 * DEFAULT_BUFFER_SIZE) {
+            tx.enqueue(i).unwrap();
+        }
+        for i in 0..(2 * DEFAULT_BUFFER_SIZE + 1) {
+            assert_eq!(Ok(i), rx.try_dequeue());
+        }
+
+        drop(tx);
+        assert!(rx.is_closed());
+        assert_eq!(Ok(2 * DEFAULT_BUFFER_SIZE + 1), rx.try_dequeue());
+        assert_eq!(
+            ((2 * DEFAULT_BUFFER_SIZE + 2)..(4 * DEFAULT_BUFFER_SIZE)).collect::<Vec<_>>(),
+            rx.close_and_drain()
+        );
+    }
+
+    #[test]
+    fn long_segment_chain_dropped() {
+        let (rx, mut tx) = queue_instrumented(1, Metrics::none());
+
+        // Every Element gets its own Segment, which should all be released
+        // without recursing through the entire Chain
+        for i in 0..100_000 {
+            tx.enqueue(i).unwrap();
+        }
+
+        drop(tx);
+        drop(rx);
+    }
+
     #[test]
     fn dequeue_closed() {
         let (mut rx, tx) = queue::<usize>();
@@ -323,4 +578,15 @@ mod tests {
 
         assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
     }
+
+    #[test]
+    fn dequeue_cancellable() {
+        let (mut rx, mut tx) = queue::<usize>();
+        let token = CancelToken::new();
+        token.cancel();
+
+        assert_eq!(Err(DequeueError::Cancelled), rx.dequeue_cancellable(&token));
+        tx.enqueue(13).unwrap();
+        assert_eq!(Ok(13), rx.dequeue_cancellable(&token));
+    }
 }
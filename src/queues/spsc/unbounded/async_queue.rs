@@ -1,9 +1,9 @@
 use alloc::sync::Arc;
 use core::{fmt::Debug, future::Future, task::Poll};
 
-use futures::task::AtomicWaker;
+use crate::utils::AtomicWaker;
 
-use crate::queues::{DequeueError, EnqueueError};
+use crate::queues::{timeout::DequeueTimeout, DequeueError, EnqueueError};
 
 use super::{queue, UnboundedReceiver, UnboundedSender};
 
@@ -32,6 +32,11 @@ pub struct AsyncUnboundedReceiver<T> {
 /// the Producer and the Queue is currently empty, therefore no more Elements
 /// will be added to the Queue meaning that we would wait forever, instead it
 /// returns `Err(DequeueError)`
+///
+/// # Cancel Safety
+/// This Future is cancel safe. The Element is only popped from the Buffer
+/// when the Future resolves with it, so dropping a pending Future simply
+/// leaves the next Element in place for the next Dequeue.
 pub struct DequeueFuture<'queue, T> {
     rx_waker: &'queue AtomicWaker,
     queue: &'queue mut UnboundedReceiver<T>,
@@ -43,7 +48,9 @@ impl<T> AsyncUnboundedSender<T> {
         self.queue.is_closed()
     }
 
-    /// Enqueues the given Data on the Queue
+    /// Enqueues the given Data on the Queue, without waiting as the Queue
+    /// grows as needed. If the Receiver is gone, the Data is returned in the
+    /// Error instead
     pub fn enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
         self.queue.enqueue(data)?;
         self.rx_waker.wake();
@@ -71,6 +78,32 @@ impl<T> AsyncUnboundedReceiver<T> {
         }
     }
 
+    /// Polls for the next Item, which is the low-level Operation behind
+    /// [`dequeue`](Self::dequeue), for Executors or Combinators that drive
+    /// the Queue manually instead of awaiting a Future.
+    ///
+    /// Returns `Poll::Pending` if the Queue is currently empty, in which case
+    /// the Waker of the given Context is woken once an Item is enqueued
+    pub fn poll_dequeue(
+        &mut self,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Result<T, DequeueError>> {
+        core::pin::Pin::new(&mut self.dequeue()).poll(cx)
+    }
+
+    /// Dequeues the next Item, just like [`dequeue`](Self::dequeue), but
+    /// stops waiting once the given `sleep` Future resolves, in which case
+    /// `Err(DequeueError::Empty)` is returned.
+    ///
+    /// The `sleep` Future can come from any Runtime, see the
+    /// [`timeout`](crate::queues::timeout) module for more Details
+    pub fn dequeue_timeout<S>(&mut self, sleep: S) -> DequeueTimeout<DequeueFuture<'_, T>, S>
+    where
+        S: Future,
+    {
+        DequeueTimeout::new(self.dequeue(), sleep)
+    }
+
     /// This attempts to dequeue the next Item from the Queue.
     ///
     /// This behaves just like the normal
@@ -100,7 +133,7 @@ impl<'queue, T> Future for DequeueFuture<'queue, T> {
                     self.rx_waker.register(cx.waker());
                     Poll::Pending
                 }
-                DequeueError::Closed => Poll::Ready(Err(DequeueError::Closed)),
+                DequeueError::Closed | DequeueError::Cancelled => Poll::Ready(Err(e)),
             },
         }
     }
@@ -116,7 +149,14 @@ impl<'queue, T> Debug for DequeueFuture<'queue, T> {
 /// ([`AsyncUnboundedReceiver`], [`AsyncUnboundedSender`])
 pub fn async_queue<T>() -> (AsyncUnboundedReceiver<T>, AsyncUnboundedSender<T>) {
     let (u_rx, u_tx) = queue();
+    wrap_queue(u_rx, u_tx)
+}
 
+/// Wraps the given sync Queue Halves into their async Variants
+pub(crate) fn wrap_queue<T>(
+    u_rx: UnboundedReceiver<T>,
+    u_tx: UnboundedSender<T>,
+) -> (AsyncUnboundedReceiver<T>, AsyncUnboundedSender<T>) {
     let rx_waker = Arc::new(AtomicWaker::new());
 
     (
@@ -131,6 +171,8 @@ pub fn async_queue<T>() -> (AsyncUnboundedReceiver<T>, AsyncUnboundedSender<T>)
     )
 }
 
+crate::queues::adapter::impl_async_receiver!(AsyncUnboundedReceiver<T>, DequeueFuture);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,52 @@
+use core::fmt::Debug;
+
+use super::UnboundedReceiver;
+
+/// Iterator that dequeues all the Elements that are currently visible in an
+/// Unbounded-Queue, created by calling
+/// [`drain_all`](UnboundedReceiver::drain_all)
+///
+/// # Behaviour
+/// The Iterator never blocks and returns `None` once there are no more
+/// Elements ready in the Queue, even if the Producer is still active.
+/// Elements are taken directly from the current Segment, without checking if
+/// the Queue has been closed on every Element like
+/// [`try_dequeue`](UnboundedReceiver::try_dequeue). Elements that are not
+/// consumed before the Iterator is dropped simply stay in the Queue.
+pub struct DrainAll<'queue, T> {
+    recv: &'queue mut UnboundedReceiver<T>,
+    /// The Number of Elements dequeued so far, which are only reported to
+    /// the Metrics once the Iterator is dropped
+    count: usize,
+}
+
+impl<'queue, T> DrainAll<'queue, T> {
+    /// Creates a new Iterator for the given Receiver
+    pub(crate) fn new(recv: &'queue mut UnboundedReceiver<T>) -> Self {
+        Self { recv, count: 0 }
+    }
+}
+
+impl<'queue, T> Iterator for DrainAll<'queue, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The Segment moves on to the next one by itself, once the Producer
+        // linked one and the current one has been exhausted
+        let data = self.recv.buf_r.pop_ready()?;
+        self.count += 1;
+        Some(data)
+    }
+}
+
+impl<'queue, T> Debug for DrainAll<'queue, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "DrainAll ()")
+    }
+}
+
+impl<'queue, T> Drop for DrainAll<'queue, T> {
+    fn drop(&mut self) {
+        self.recv.metrics.dequeue_many(self.count);
+    }
+}
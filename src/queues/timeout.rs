@@ -0,0 +1,212 @@
+//! Timeouts for the async Dequeue- and Enqueue-Operations, which work with
+//! any Runtime.
+//!
+//! Instead of depending on the Timers of a specific Runtime, the
+//! `dequeue_timeout` Operations on the async Receivers take an arbitrary
+//! Sleep-Future, like `tokio::time::sleep(...)` or the equivalent of any
+//! other Executor, and stop waiting for an Element once it resolves.
+//!
+//! In the same Way, the `enqueue_within` Operations on the async bounded
+//! Senders stop waiting for free Space once the Sleep-Future resolves and
+//! return the Item, so it can be dropped or handled otherwise instead of
+//! buffering it indefinitely.
+//!
+//! # Example
+//! ```
+//! # use nolock::queues::{mpsc::jiffy, DequeueError};
+//! # use std::time::Duration;
+//! async fn demo() {
+//!     let (mut rx, tx) = jiffy::async_queue::<usize>();
+//!
+//!     // Nothing was enqueued, so this runs into the Timeout
+//!     let sleep = tokio::time::sleep(Duration::from_millis(5));
+//!     assert_eq!(Err(DequeueError::Empty), rx.dequeue_timeout(sleep).await);
+//!
+//!     tx.enqueue(13).unwrap();
+//!     let sleep = tokio::time::sleep(Duration::from_millis(5));
+//!     assert_eq!(Ok(13), rx.dequeue_timeout(sleep).await);
+//! }
+//!
+//! # fn main() {
+//! #   let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+//! #   rt.block_on(demo());
+//! # }
+//! ```
+
+use core::{
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{DequeueError, EnqueueError};
+
+/// The Future returned by the `dequeue_timeout` Operations of the async
+/// Receivers.
+///
+/// # Behaviour
+/// This resolves to the Result of the inner Dequeue-Future, if that resolves
+/// first, or to `Err(DequeueError::Empty)` once the Sleep-Future resolves
+/// without any Element being dequeued. The Dequeue-Future is always polled
+/// first, so an Element that is already available is returned even if the
+/// Sleep-Future has already elapsed.
+///
+/// # Cancel Safety
+/// This Future is cancel safe, as long as the inner Dequeue-Future is cancel
+/// safe, which is the case for all the Queues in this Crate
+pub struct DequeueTimeout<F, S> {
+    dequeue: F,
+    sleep: S,
+}
+
+impl<F, S> DequeueTimeout<F, S> {
+    /// Creates a new Timeout for the given Dequeue-Future, which stops
+    /// waiting once the `sleep` Future resolves
+    pub fn new(dequeue: F, sleep: S) -> Self {
+        Self { dequeue, sleep }
+    }
+}
+
+impl<F, S> Debug for DequeueTimeout<F, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Dequeue-Timeout ()")
+    }
+}
+
+impl<T, F, S> Future for DequeueTimeout<F, S>
+where
+    F: Future<Output = Result<T, DequeueError>>,
+    S: Future,
+{
+    type Output = Result<T, DequeueError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // # Safety:
+        // Neither of the Fields is ever moved out of the pinned Future, so
+        // it is safe to pin them as well
+        let this = unsafe { self.get_unchecked_mut() };
+        let dequeue = unsafe { Pin::new_unchecked(&mut this.dequeue) };
+        let sleep = unsafe { Pin::new_unchecked(&mut this.sleep) };
+
+        if let Poll::Ready(result) = dequeue.poll(cx) {
+            return Poll::Ready(result);
+        }
+
+        match sleep.poll(cx) {
+            Poll::Ready(_) => Poll::Ready(Err(DequeueError::Empty)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// The Error returned by the `enqueue_within` Operations of the async
+/// bounded Senders, which always gives back the Item that could not be
+/// enqueued
+#[derive(Debug, PartialEq)]
+pub enum EnqueueTimeoutError<T> {
+    /// No Space became available in the Queue before the Sleep-Future
+    /// resolved
+    Timeout(T),
+    /// The Item could not be enqueued for any other Reason, like the Queue
+    /// being closed by the Receiver
+    Failed(EnqueueError, T),
+}
+
+impl<T> EnqueueTimeoutError<T> {
+    /// Returns the Item that could not be enqueued
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Timeout(data) => data,
+            Self::Failed(_, data) => data,
+        }
+    }
+}
+
+/// An Enqueue-Future that holds on to its Item until it was enqueued, which
+/// allows an [`EnqueueTimeout`] to give the Item back once it elapsed
+pub trait PendingEnqueue: Future + Unpin {
+    /// The Item that is being enqueued
+    type Item;
+
+    /// Takes the Item out of the Future, if it has not yet been enqueued
+    fn take_item(&mut self) -> Option<Self::Item>;
+
+    /// Converts the Output of the Future into the Result of the Timeout
+    fn into_result(output: Self::Output) -> Result<(), EnqueueTimeoutError<Self::Item>>;
+}
+
+/// The Future returned by the `enqueue_within` Operations of the async
+/// bounded Senders.
+///
+/// # Behaviour
+/// This resolves to the Result of the inner Enqueue-Future, if that resolves
+/// first, or to `Err(EnqueueTimeoutError::Timeout(data))` once the
+/// Sleep-Future resolves without the Item being enqueued. The Enqueue-Future
+/// is always polled first, so an Item is still enqueued if there is Space
+/// for it, even if the Sleep-Future has already elapsed.
+pub struct EnqueueTimeout<F, S> {
+    enqueue: F,
+    sleep: S,
+}
+
+impl<F, S> EnqueueTimeout<F, S> {
+    /// Creates a new Timeout for the given Enqueue-Future, which stops
+    /// waiting once the `sleep` Future resolves
+    pub fn new(enqueue: F, sleep: S) -> Self {
+        Self { enqueue, sleep }
+    }
+}
+
+impl<F, S> Debug for EnqueueTimeout<F, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Enqueue-Timeout ()")
+    }
+}
+
+impl<F, S> Future for EnqueueTimeout<F, S>
+where
+    F: PendingEnqueue,
+    S: Future,
+{
+    type Output = Result<(), EnqueueTimeoutError<F::Item>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // # Safety:
+        // The Sleep-Future is never moved out of the pinned Future, so it is
+        // safe to pin it as well, while the Enqueue-Future is Unpin
+        let this = unsafe { self.get_unchecked_mut() };
+        let sleep = unsafe { Pin::new_unchecked(&mut this.sleep) };
+
+        if let Poll::Ready(output) = Pin::new(&mut this.enqueue).poll(cx) {
+            return Poll::Ready(F::into_result(output));
+        }
+
+        match sleep.poll(cx) {
+            Poll::Ready(_) => match this.enqueue.take_item() {
+                Some(data) => Poll::Ready(Err(EnqueueTimeoutError::Timeout(data))),
+                None => Poll::Ready(Ok(())),
+            },
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn dequeue_first() {
+        let result = DequeueTimeout::new(async { Ok(13) }, async {}).await;
+        assert_eq!(Ok(13), result);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn sleep_elapsed() {
+        let result = DequeueTimeout::new(core::future::pending::<Result<u8, _>>(), async {}).await;
+        assert_eq!(Err(DequeueError::Empty), result);
+    }
+}
@@ -0,0 +1,569 @@
+//! A conflating Channel, that only ever stores the latest Value.
+//!
+//! Every Sender overwrites the currently stored Value, so the Receiver only
+//! ever observes the most recent Value and any Value that was overwritten
+//! before the Receiver got to it is simply dropped. This is useful for
+//! Data, like Sensor-Readings, where only the latest State is of Interest and
+//! processing every intermediate Value would just be wasted Work.
+//!
+//! Every Value gets a Version, which counts the Values that have been
+//! enqueued so far, which allows the Receiver to tell how many Values it has
+//! missed.
+//!
+//! # Example
+//! ```
+//! use nolock::queues::watch;
+//! use nolock::queues::DequeueError;
+//!
+//! let (tx, mut rx) = watch::channel::<usize>();
+//!
+//! tx.enqueue(1).unwrap();
+//! tx.enqueue(2).unwrap();
+//! tx.enqueue(3).unwrap();
+//!
+//! // Only the latest Value is received
+//! assert_eq!(Ok(3), rx.try_dequeue());
+//! assert_eq!(3, rx.version());
+//! assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+//! ```
+//!
+//! # Ordering
+//! Every Value is moved into the Slot with a single atomic Swap, so the
+//! Channel is lock-free. Values from concurrent Senders can end up in the
+//! Slot in a different Order than their Versions, but the Receiver never
+//! observes the Versions going backwards and simply skips a Value that is
+//! older than the last one it received.
+
+use alloc::{boxed::Box, sync::Arc};
+use core::{fmt::Debug, ptr};
+
+use crate::{
+    sync::native::atomic,
+    utils::{try_box, Backoff},
+};
+
+use super::{DequeueError, EnqueueError};
+
+#[cfg(feature = "async")]
+use crate::utils::AtomicWaker;
+#[cfg(feature = "async")]
+use core::{future::Future, pin::Pin, task::Poll};
+
+/// A single Value together with its Version
+struct Slot<T> {
+    version: usize,
+    value: T,
+}
+
+/// The State shared between all the Senders and the Receiver
+struct Shared<T> {
+    /// The latest Value, which is null if there is no new Value
+    slot: atomic::AtomicPtr<Slot<T>>,
+    /// The Version of the most recently enqueued Value
+    version: atomic::AtomicUsize,
+    /// The Number of Senders that are still alive
+    senders: atomic::AtomicUsize,
+    /// Whether or not the Receiver has been dropped
+    receiver_closed: atomic::AtomicBool,
+    /// The Waker of the Receiver, if it is waiting for a new Value
+    #[cfg(feature = "async")]
+    waker: AtomicWaker,
+}
+
+impl<T> Shared<T> {
+    /// Takes the current Value out of the Slot, if there is one
+    fn take(&self) -> Option<Box<Slot<T>>> {
+        let ptr = self.slot.swap(ptr::null_mut(), atomic::Ordering::AcqRel);
+        if ptr.is_null() {
+            return None;
+        }
+
+        // # Safety:
+        // The Pointer was created using Box::into_raw and swapping it out of
+        // the Slot gives us exclusive Ownership of it
+        Some(unsafe { Box::from_raw(ptr) })
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        drop(self.take());
+    }
+}
+
+/// The Sending-Half of a [`watch`](self) Channel, which can be cloned to
+/// create multiple Senders
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// The Receiving-Half of a [`watch`](self) Channel
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    /// The Version of the last received Value
+    version: usize,
+}
+
+// Safety:
+// The Values are only ever moved between Threads, but never shared, so they
+// only need to be Send
+unsafe impl<T> Send for Sender<T> where T: Send {}
+unsafe impl<T> Sync for Sender<T> where T: Send {}
+unsafe impl<T> Send for Receiver<T> where T: Send {}
+unsafe impl<T> Sync for Receiver<T> where T: Send {}
+
+/// Creates a new empty Channel and returns the Halves as ([`Sender`],
+/// [`Receiver`]), in the same Order as `std::sync::mpsc::channel`
+///
+/// # Example
+/// ```
+/// use nolock::queues::watch;
+///
+/// let (tx, mut rx) = watch::channel();
+///
+/// assert_eq!(1, tx.enqueue(13).unwrap());
+/// assert_eq!(Ok(13), rx.try_dequeue());
+/// ```
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        slot: atomic::AtomicPtr::new(ptr::null_mut()),
+        version: atomic::AtomicUsize::new(0),
+        senders: atomic::AtomicUsize::new(1),
+        receiver_closed: atomic::AtomicBool::new(false),
+        #[cfg(feature = "async")]
+        waker: AtomicWaker::new(),
+    });
+
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared, version: 0 },
+    )
+}
+
+impl<T> Sender<T> {
+    /// Checks if the Receiver has been dropped
+    pub fn is_closed(&self) -> bool {
+        self.shared.receiver_closed.load(atomic::Ordering::Acquire)
+    }
+
+    /// Replaces the currently stored Value with the given Value, dropping the
+    /// previous Value if the Receiver has not received it yet.
+    ///
+    /// Returns the Version of the new Value on Success
+    ///
+    /// # Example
+    /// ```
+    /// use nolock::queues::watch;
+    ///
+    /// let (tx, mut rx) = watch::channel();
+    ///
+    /// assert_eq!(1, tx.enqueue(1).unwrap());
+    /// assert_eq!(2, tx.enqueue(2).unwrap());
+    ///
+    /// assert_eq!(Ok(2), rx.try_dequeue());
+    /// ```
+    pub fn enqueue(&self, value: T) -> Result<usize, (T, EnqueueError)> {
+        if self.is_closed() {
+            return Err((value, EnqueueError::Closed));
+        }
+
+        let version = self.shared.version.fetch_add(1, atomic::Ordering::AcqRel) + 1;
+        let slot = match try_box(Slot { version, value }) {
+            Ok(s) => s,
+            Err(slot) => return Err((slot.value, EnqueueError::AllocFailed)),
+        };
+
+        let previous = self
+            .shared
+            .slot
+            .swap(Box::into_raw(slot), atomic::Ordering::AcqRel);
+        if !previous.is_null() {
+            // # Safety:
+            // The Pointer was created using Box::into_raw and swapping it out
+            // of the Slot gives us exclusive Ownership of it
+            drop(unsafe { Box::from_raw(previous) });
+        }
+
+        #[cfg(feature = "async")]
+        self.shared.waker.wake();
+
+        Ok(version)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, atomic::Ordering::Relaxed);
+
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, atomic::Ordering::AcqRel) == 1 {
+            #[cfg(feature = "async")]
+            self.shared.waker.wake();
+        }
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Watch-Sender ()")
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Checks if all the Senders have been dropped
+    ///
+    /// # Note
+    /// There might still be a last Value that has not been received yet
+    pub fn is_closed(&self) -> bool {
+        self.shared.senders.load(atomic::Ordering::Acquire) == 0
+    }
+
+    /// The Version of the last received Value, which is 0 if no Value has
+    /// been received yet
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// The Version of the most recently enqueued Value, which allows
+    /// checking how many Values have been enqueued since the last received
+    /// one
+    ///
+    /// # Example
+    /// ```
+    /// use nolock::queues::watch;
+    ///
+    /// let (tx, mut rx) = watch::channel();
+    ///
+    /// tx.enqueue(1).unwrap();
+    /// rx.try_dequeue().unwrap();
+    ///
+    /// tx.enqueue(2).unwrap();
+    /// tx.enqueue(3).unwrap();
+    /// assert_eq!(2, rx.latest_version() - rx.version());
+    /// ```
+    pub fn latest_version(&self) -> usize {
+        self.shared.version.load(atomic::Ordering::Acquire)
+    }
+
+    /// Checks if there is a new Value that has not been received yet
+    pub fn has_changed(&self) -> bool {
+        !self.shared.slot.load(atomic::Ordering::Acquire).is_null()
+    }
+
+    /// Takes the latest Value, if there is one that has not been received
+    /// yet
+    fn take_newer(&mut self) -> Option<T> {
+        let slot = self.shared.take()?;
+
+        // A concurrent Sender could have stored an older Value after a newer
+        // one, which we already received
+        if slot.version <= self.version {
+            return None;
+        }
+
+        self.version = slot.version;
+        Some(slot.value)
+    }
+
+    /// Attempts to receive the latest Value
+    ///
+    /// # Example
+    /// ```
+    /// use nolock::queues::watch;
+    /// use nolock::queues::DequeueError;
+    ///
+    /// let (tx, mut rx) = watch::channel();
+    /// assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    ///
+    /// tx.enqueue(13).unwrap();
+    /// assert_eq!(Ok(13), rx.try_dequeue());
+    ///
+    /// drop(tx);
+    /// assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+    /// ```
+    pub fn try_dequeue(&mut self) -> Result<T, DequeueError> {
+        if let Some(value) = self.take_newer() {
+            return Ok(value);
+        }
+
+        if !self.is_closed() {
+            return Err(DequeueError::Empty);
+        }
+
+        // The last Sender could have enqueued a Value just before it was
+        // dropped
+        match self.take_newer() {
+            Some(value) => Ok(value),
+            None => Err(DequeueError::Closed),
+        }
+    }
+
+    /// Blocks until a new Value is available or returns None, if all the
+    /// Senders have been dropped.
+    ///
+    /// This is simply spinning and therefore not lock-free
+    pub fn dequeue(&mut self) -> Option<T> {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_dequeue() {
+                Ok(value) => return Some(value),
+                Err(DequeueError::Empty) => backoff.snooze(),
+                Err(DequeueError::Closed | DequeueError::Cancelled) => return None,
+            };
+        }
+    }
+
+    /// Polls for a new Value, which is the low-level Operation behind
+    /// [`dequeue_async`](Self::dequeue_async).
+    ///
+    /// Returns `Poll::Pending` if there is no new Value, in which case the
+    /// Waker of the given Context is woken once a new Value is enqueued or
+    /// the last Sender is dropped
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn poll_dequeue(
+        &mut self,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Result<T, DequeueError>> {
+        match self.try_dequeue() {
+            Err(DequeueError::Empty) => {}
+            result => return Poll::Ready(result),
+        };
+
+        self.shared.waker.register(cx.waker());
+
+        // A Value could have been enqueued before we registered the Waker
+        match self.try_dequeue() {
+            Err(DequeueError::Empty) => Poll::Pending,
+            result => Poll::Ready(result),
+        }
+    }
+
+    /// Asynchronously waits for a new Value
+    ///
+    /// # Example
+    /// ```
+    /// use nolock::queues::watch;
+    ///
+    /// async fn demo() {
+    ///   let (tx, mut rx) = watch::channel::<usize>();
+    ///
+    ///   tx.enqueue(1).unwrap();
+    ///   tx.enqueue(2).unwrap();
+    ///
+    ///   assert_eq!(Ok(2), rx.dequeue_async().await);
+    /// }
+    ///
+    /// # fn main() {
+    /// #   let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// #
+    /// #   rt.block_on(demo());
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn dequeue_async(&mut self) -> DequeueFuture<'_, T> {
+        DequeueFuture { receiver: self }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared
+            .receiver_closed
+            .store(true, atomic::Ordering::Release);
+        drop(self.shared.take());
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Watch-Receiver ()")
+    }
+}
+
+/// The Future returned by [`dequeue_async`](Receiver::dequeue_async), which
+/// resolves once a new Value is available or all the Senders have been
+/// dropped
+///
+/// # Cancel Safety
+/// This Future is cancel safe, a Value is only ever taken out of the Channel
+/// when the Future resolves with it
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct DequeueFuture<'recv, T> {
+    receiver: &'recv mut Receiver<T>,
+}
+
+#[cfg(feature = "async")]
+impl<'recv, T> Future for DequeueFuture<'recv, T> {
+    type Output = Result<T, DequeueError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut core::task::Context<'_>) -> Poll<Self::Output> {
+        self.receiver.poll_dequeue(cx)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'recv, T> Debug for DequeueFuture<'recv, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Watch-DequeueFuture ()")
+    }
+}
+
+crate::queues::adapter::impl_receiver!(Receiver<T>);
+
+impl<T> crate::queues::dynamic::DynSender<T> for Sender<T> {
+    fn try_enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        Sender::enqueue(self, data).map(|_| ())
+    }
+
+    fn enqueue(&mut self, data: T) -> Result<(), (T, EnqueueError)> {
+        Sender::enqueue(self, data).map(|_| ())
+    }
+
+    fn is_closed(&self) -> bool {
+        Sender::is_closed(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflates() {
+        let (tx, mut rx) = channel();
+
+        for i in 0..10 {
+            assert_eq!(i + 1, tx.enqueue(i).unwrap());
+        }
+
+        assert!(rx.has_changed());
+        assert_eq!(Ok(9), rx.try_dequeue());
+        assert_eq!(10, rx.version());
+        assert!(!rx.has_changed());
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+    }
+
+    #[test]
+    fn skips_older_version() {
+        let (tx, mut rx) = channel();
+
+        tx.enqueue(1).unwrap();
+        assert_eq!(Ok(1), rx.try_dequeue());
+
+        // Simulates a Sender that got an older Version, but stored its Value
+        // after the newer one was already received
+        rx.shared.slot.store(
+            Box::into_raw(Box::new(Slot {
+                version: 1,
+                value: 0,
+            })),
+            atomic::Ordering::Release,
+        );
+        assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+        assert_eq!(1, rx.version());
+
+        drop(tx);
+    }
+
+    #[test]
+    fn closed_after_last_sender() {
+        let (tx, mut rx) = channel();
+        let tx2 = tx.clone();
+
+        drop(tx);
+        assert!(!rx.is_closed());
+
+        tx2.enqueue(13).unwrap();
+        drop(tx2);
+        assert!(rx.is_closed());
+
+        assert_eq!(Ok(13), rx.try_dequeue());
+        assert_eq!(Err(DequeueError::Closed), rx.try_dequeue());
+        assert_eq!(None, rx.dequeue());
+    }
+
+    #[test]
+    fn enqueue_closed() {
+        let (tx, rx) = channel();
+        drop(rx);
+
+        assert!(tx.is_closed());
+        assert_eq!(Err((13, EnqueueError::Closed)), tx.enqueue(13));
+    }
+
+    #[test]
+    fn drops_overwritten_values() {
+        let value = Arc::new(());
+        let (tx, rx) = channel();
+
+        tx.enqueue(value.clone()).unwrap();
+        tx.enqueue(value.clone()).unwrap();
+        assert_eq!(2, Arc::strong_count(&value));
+
+        drop(rx);
+        assert_eq!(1, Arc::strong_count(&value));
+        drop(tx);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn concurrent_versions_increase() {
+        let (tx, mut rx) = channel();
+
+        let handles: std::vec::Vec<_> = (0..4)
+            .map(|_| {
+                let tx = tx.clone();
+                std::thread::spawn(move || {
+                    for i in 0..1000 {
+                        tx.enqueue(i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut last = 0;
+        while rx.dequeue().is_some() {
+            assert!(rx.version() > last);
+            last = rx.version();
+        }
+        assert!(last <= 4000);
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn poll_dequeue_wakes() {
+        use futures::task::noop_waker;
+
+        let (tx, mut rx) = channel::<usize>();
+        let waker = noop_waker();
+        let mut cx = core::task::Context::from_waker(&waker);
+
+        assert!(rx.poll_dequeue(&mut cx).is_pending());
+
+        tx.enqueue(13).unwrap();
+        assert_eq!(Poll::Ready(Ok(13)), rx.poll_dequeue(&mut cx));
+
+        drop(tx);
+        assert_eq!(
+            Poll::Ready(Err(DequeueError::Closed)),
+            rx.poll_dequeue(&mut cx)
+        );
+    }
+}
@@ -1,4 +1,31 @@
-#[cfg(not(loom))]
+//! The Synchronization-Primitives used throughout the Crate
+//!
+//! When running under loom, these are replaced by the Primitives provided by
+//! loom and with the `test_util` Feature enabled, the Atomics are replaced by
+//! Wrappers that can inject Yields, see [`test_util`](crate::test_util)
+
+#[cfg(all(not(loom), feature = "test_util"))]
+pub use crate::test_util::atomic;
+#[cfg(all(not(loom), not(feature = "test_util")))]
 pub use core::sync::*;
 #[cfg(loom)]
 pub use loom::sync::*;
+
+/// The Atomics for Datastructures that are not modeled using loom, which are
+/// therefore only replaced with the `test_util` Feature enabled
+pub mod native {
+    #[cfg(feature = "test_util")]
+    pub use crate::test_util::atomic;
+    #[cfg(not(feature = "test_util"))]
+    pub use core::sync::atomic;
+}
+
+/// The Atomics that are part of the Public-API, like the Pointers that should
+/// be protected by a Hazard-Pointer, which are never replaced with the
+/// `test_util` Feature, because they are provided by the User
+pub mod api {
+    #[cfg(not(loom))]
+    pub use core::sync::atomic;
+    #[cfg(loom)]
+    pub use loom::sync::atomic;
+}
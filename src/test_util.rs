@@ -0,0 +1,280 @@
+//! Utilities to make Stress-Tests of the Datastructures more reproducible
+//!
+//! With the `test_util` Feature enabled, all the Atomics used internally by
+//! the Crate are replaced by Wrappers, that run an Injection-Point before
+//! every atomic Operation. While an Injection is active, every
+//! Injection-Point may yield the current Thread, spin for a bit or even put
+//! the Thread to sleep, based on a Seed.
+//!
+//! This creates a lot more unusual Interleavings than normal Stress-Tests,
+//! and while it can not make the OS-Scheduler deterministic, reusing the Seed
+//! of a failed Run makes it a lot more likely to hit the same Bug again. This
+//! is useful for Datastructures that are too large to be modeled using loom.
+//!
+//! # Stress
+//! The [`stress`] Harness builds on top of this and checks that a Queue
+//! neither loses, duplicates nor reorders any Messages, while being used by
+//! any Number of Producers and Consumers.
+//!
+//! When the Feature is disabled, none of this exists and the normal Atomics
+//! are used, so there is no Overhead.
+//!
+//! # Example
+//! ```
+//! # use nolock::{queues::mpsc::jiffy, test_util};
+//! // Uses the Seed from the `NOLOCK_TEST_SEED` Environment-Variable, if it
+//! // is set, or a random one otherwise
+//! let _injection = test_util::inject_yields(test_util::seed_from_env());
+//!
+//! let (mut rx, tx) = jiffy::queue::<usize>();
+//! let handle = std::thread::spawn(move || {
+//!     for i in 0..100 {
+//!         tx.enqueue(i).unwrap();
+//!     }
+//! });
+//!
+//! for i in 0..100 {
+//!     assert_eq!(i, rx.dequeue().unwrap());
+//! }
+//! handle.join().unwrap();
+//! ```
+
+use core::fmt::Debug;
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::{Duration, SystemTime},
+};
+
+pub mod atomic;
+pub mod stress;
+
+/// The Name of the Environment-Variable that is used by [`seed_from_env`]
+pub const SEED_ENV: &str = "NOLOCK_TEST_SEED";
+
+/// Whether an Injection is currently active
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// The Seed of the currently active Injection
+static SEED: AtomicU64 = AtomicU64::new(0);
+/// Incremented for every new Injection, so that the Threads know when to
+/// reseed their Generators
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+/// The Number of Threads that have already seeded their Generators for the
+/// current Injection
+static THREADS: AtomicU64 = AtomicU64::new(0);
+static YIELD_ONE_IN: AtomicU32 = AtomicU32::new(0);
+static SPIN_ONE_IN: AtomicU32 = AtomicU32::new(0);
+static SLEEP_ONE_IN: AtomicU32 = AtomicU32::new(0);
+static MAX_SLEEP_MICROS: AtomicU64 = AtomicU64::new(0);
+
+/// Only a single Injection can be active at a Time
+static ACTIVE: Mutex<()> = Mutex::new(());
+
+std::thread_local! {
+    /// The Generation for which the Generator was seeded and the current
+    /// State of the Generator
+    static GENERATOR: Cell<(u64, u64)> = const { Cell::new((0, 0)) };
+}
+
+/// Configures how often the different Actions are performed at an
+/// Injection-Point.
+///
+/// Every Probability is given as `1 in n`, so a Value of `8` means that the
+/// Action is performed at every 8th Injection-Point on average and a Value
+/// of `0` disables the Action entirely
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// How often the Thread yields to the OS-Scheduler
+    pub yield_one_in: u32,
+    /// How often the Thread spins for a random Number of Iterations
+    pub spin_one_in: u32,
+    /// How often the Thread is put to sleep for up to `max_sleep`
+    pub sleep_one_in: u32,
+    /// The maximum Duration for which a Thread is put to sleep
+    pub max_sleep: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            yield_one_in: 8,
+            spin_one_in: 4,
+            sleep_one_in: 512,
+            max_sleep: Duration::from_micros(100),
+        }
+    }
+}
+
+/// The Guard for an active Injection, which stops the Injection once it is
+/// dropped
+pub struct InjectionGuard {
+    _active: MutexGuard<'static, ()>,
+}
+
+impl Debug for InjectionGuard {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "InjectionGuard ()")
+    }
+}
+
+impl Drop for InjectionGuard {
+    fn drop(&mut self) {
+        ENABLED.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Starts injecting Yields, using the given Seed and the default [`Config`].
+///
+/// The Injection is active on all Threads until the returned Guard is
+/// dropped. If another Injection is currently active, this blocks until it
+/// is stopped, so that multiple Tests don't interfere with each other.
+pub fn inject_yields(seed: u64) -> InjectionGuard {
+    inject_yields_with(seed, Config::default())
+}
+
+/// Starts injecting Yields, using the given Seed and [`Config`].
+///
+/// See [`inject_yields`] for more Details
+pub fn inject_yields_with(seed: u64, config: Config) -> InjectionGuard {
+    let active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+
+    YIELD_ONE_IN.store(config.yield_one_in, Ordering::SeqCst);
+    SPIN_ONE_IN.store(config.spin_one_in, Ordering::SeqCst);
+    SLEEP_ONE_IN.store(config.sleep_one_in, Ordering::SeqCst);
+    MAX_SLEEP_MICROS.store(
+        (config.max_sleep.as_micros() as u64).max(1),
+        Ordering::SeqCst,
+    );
+    SEED.store(seed, Ordering::SeqCst);
+    THREADS.store(0, Ordering::SeqCst);
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    ENABLED.store(true, Ordering::SeqCst);
+
+    InjectionGuard { _active: active }
+}
+
+/// Loads the Seed from the [`SEED_ENV`] Environment-Variable or generates a
+/// random Seed, if it is not set.
+///
+/// The Seed is also printed to Stderr, so that it can be used to reproduce a
+/// failed Run
+///
+/// # Panics
+/// If the Environment-Variable is set, but does not contain a valid Seed
+pub fn seed_from_env() -> u64 {
+    let seed = match std::env::var(SEED_ENV) {
+        Ok(raw) => raw.parse().expect("The Seed should be a valid u64"),
+        Err(_) => SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0),
+    };
+
+    std::eprintln!("Injecting Yields with {}={}", SEED_ENV, seed);
+    seed
+}
+
+/// The Actions that can be performed at an Injection-Point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Nothing,
+    Spin(u32),
+    Yield,
+    Sleep(Duration),
+}
+
+/// The SplitMix64 Generator, which is used to derive the Seeds for the
+/// individual Threads
+fn splitmix(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+/// Advances the XorShift-Generator and returns the next random Value
+fn next_random(state: &mut u64) -> u64 {
+    let mut x = *state;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    *state = x;
+    x
+}
+
+/// Decides which Action to perform, based on the given random Value
+fn decide(random: u64) -> Action {
+    let hits = |one_in: &AtomicU32, shift: u32| {
+        let one_in = one_in.load(Ordering::Relaxed) as u64;
+        one_in != 0 && (random >> shift).is_multiple_of(one_in)
+    };
+
+    if hits(&SLEEP_ONE_IN, 0) {
+        let max = MAX_SLEEP_MICROS.load(Ordering::Relaxed);
+        Action::Sleep(Duration::from_micros((random >> 32) % max))
+    } else if hits(&YIELD_ONE_IN, 16) {
+        Action::Yield
+    } else if hits(&SPIN_ONE_IN, 24) {
+        Action::Spin(((random >> 40) % 64) as u32)
+    } else {
+        Action::Nothing
+    }
+}
+
+/// The Injection-Point that is run before every atomic Operation
+pub(crate) fn injection_point() {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    // The Thread-Local may already be destroyed, if this is called while the
+    // Thread is shutting down, in which case we simply don't inject anything
+    let random = GENERATOR.try_with(|generator| {
+        let (generation, mut state) = generator.get();
+
+        let current = GENERATION.load(Ordering::Relaxed);
+        if generation != current {
+            let index = THREADS.fetch_add(1, Ordering::Relaxed);
+            state = splitmix(SEED.load(Ordering::Relaxed) ^ splitmix(index)) | 1;
+        }
+
+        let random = next_random(&mut state);
+        generator.set((current, state));
+        random
+    });
+
+    match random.map(decide) {
+        Ok(Action::Spin(iterations)) => {
+            for _ in 0..iterations {
+                core::hint::spin_loop();
+            }
+        }
+        Ok(Action::Yield) => std::thread::yield_now(),
+        Ok(Action::Sleep(duration)) => std::thread::sleep(duration),
+        Ok(Action::Nothing) | Err(_) => {}
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_actions() {
+        let _guard = inject_yields(13);
+
+        let actions = |seed: u64| {
+            let mut state = splitmix(seed) | 1;
+            (0..256)
+                .map(|_| decide(next_random(&mut state)))
+                .collect::<std::vec::Vec<_>>()
+        };
+
+        assert_eq!(actions(13), actions(13));
+        assert_ne!(actions(13), actions(14));
+        assert!(actions(13).contains(&Action::Yield));
+    }
+}
@@ -0,0 +1,246 @@
+//! Wrappers around the Atomics from `core`, that run an Injection-Point
+//! before every Operation on them.
+//!
+//! All the Operations that are not explicitly wrapped, like `get_mut`, are
+//! still available through `Deref`/`DerefMut` and simply don't inject
+//! anything, which is fine as they can not race with other Threads anyway
+
+use core::{
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    sync::atomic as core_atomic,
+};
+
+pub use core::sync::atomic::{compiler_fence, Ordering};
+
+use super::injection_point;
+
+/// An atomic Fence, which is preceeded by an Injection-Point
+pub fn fence(order: Ordering) {
+    injection_point();
+    core_atomic::fence(order);
+}
+
+macro_rules! wrap_common {
+    ($name:ident, $value:ty) => {
+        impl $name {
+            /// Loads the Value
+            pub fn load(&self, order: Ordering) -> $value {
+                injection_point();
+                self.0.load(order)
+            }
+
+            /// Stores the Value
+            pub fn store(&self, value: $value, order: Ordering) {
+                injection_point();
+                self.0.store(value, order)
+            }
+
+            /// Swaps the Value and returns the previous one
+            pub fn swap(&self, value: $value, order: Ordering) -> $value {
+                injection_point();
+                self.0.swap(value, order)
+            }
+
+            /// Stores the `new` Value, if the current Value is `current`
+            pub fn compare_exchange(
+                &self,
+                current: $value,
+                new: $value,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$value, $value> {
+                injection_point();
+                self.0.compare_exchange(current, new, success, failure)
+            }
+
+            /// Stores the `new` Value, if the current Value is `current`, but
+            /// may fail spuriously
+            pub fn compare_exchange_weak(
+                &self,
+                current: $value,
+                new: $value,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$value, $value> {
+                injection_point();
+                self.0.compare_exchange_weak(current, new, success, failure)
+            }
+
+            /// Returns the inner Value
+            pub fn into_inner(self) -> $value {
+                self.0.into_inner()
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                Debug::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+macro_rules! wrap_fetch {
+    ($name:ident, $value:ty, $($method:ident),*) => {
+        impl $name {
+            $(
+                #[doc = concat!("Performs `", stringify!($method), "` on the Value")]
+                pub fn $method(&self, value: $value, order: Ordering) -> $value {
+                    injection_point();
+                    self.0.$method(value, order)
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! wrap_value {
+    ($name:ident, $value:ty, $($method:ident),*) => {
+        #[doc = concat!("A Wrapper around [`", stringify!($name), "`](core::sync::atomic::", stringify!($name), ")")]
+        #[derive(Default)]
+        #[repr(transparent)]
+        pub struct $name(core_atomic::$name);
+
+        impl $name {
+            /// Creates a new Atomic with the given Value
+            pub const fn new(value: $value) -> Self {
+                Self(core_atomic::$name::new(value))
+            }
+        }
+
+        impl Deref for $name {
+            type Target = core_atomic::$name;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+        impl DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl From<$value> for $name {
+            fn from(value: $value) -> Self {
+                Self::new(value)
+            }
+        }
+
+        wrap_common!($name, $value);
+        wrap_fetch!($name, $value, $($method),*);
+    };
+}
+
+wrap_value!(AtomicBool, bool, fetch_and, fetch_or, fetch_xor);
+wrap_value!(AtomicU8, u8, fetch_add, fetch_sub, fetch_and, fetch_or);
+wrap_value!(AtomicU32, u32, fetch_add, fetch_sub, fetch_and, fetch_or);
+wrap_value!(AtomicU64, u64, fetch_add, fetch_sub, fetch_and, fetch_or);
+wrap_value!(
+    AtomicUsize,
+    usize,
+    fetch_add,
+    fetch_sub,
+    fetch_and,
+    fetch_or
+);
+wrap_value!(AtomicI64, i64, fetch_add, fetch_sub, fetch_and, fetch_or);
+wrap_value!(
+    AtomicIsize,
+    isize,
+    fetch_add,
+    fetch_sub,
+    fetch_and,
+    fetch_or
+);
+
+/// A Wrapper around [`AtomicPtr`](core::sync::atomic::AtomicPtr)
+#[repr(transparent)]
+pub struct AtomicPtr<T>(core_atomic::AtomicPtr<T>);
+
+impl<T> AtomicPtr<T> {
+    /// Creates a new Atomic with the given Ptr
+    pub const fn new(ptr: *mut T) -> Self {
+        Self(core_atomic::AtomicPtr::new(ptr))
+    }
+
+    /// Loads the Ptr
+    pub fn load(&self, order: Ordering) -> *mut T {
+        injection_point();
+        self.0.load(order)
+    }
+
+    /// Stores the Ptr
+    pub fn store(&self, ptr: *mut T, order: Ordering) {
+        injection_point();
+        self.0.store(ptr, order)
+    }
+
+    /// Swaps the Ptr and returns the previous one
+    pub fn swap(&self, ptr: *mut T, order: Ordering) -> *mut T {
+        injection_point();
+        self.0.swap(ptr, order)
+    }
+
+    /// Stores the `new` Ptr, if the current Ptr is `current`
+    pub fn compare_exchange(
+        &self,
+        current: *mut T,
+        new: *mut T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, *mut T> {
+        injection_point();
+        self.0.compare_exchange(current, new, success, failure)
+    }
+
+    /// Stores the `new` Ptr, if the current Ptr is `current`, but may fail
+    /// spuriously
+    pub fn compare_exchange_weak(
+        &self,
+        current: *mut T,
+        new: *mut T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, *mut T> {
+        injection_point();
+        self.0.compare_exchange_weak(current, new, success, failure)
+    }
+
+    /// Returns the inner Ptr
+    pub fn into_inner(self) -> *mut T {
+        self.0.into_inner()
+    }
+}
+
+impl<T> Default for AtomicPtr<T> {
+    fn default() -> Self {
+        Self::new(core::ptr::null_mut())
+    }
+}
+
+impl<T> Deref for AtomicPtr<T> {
+    type Target = core_atomic::AtomicPtr<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<T> DerefMut for AtomicPtr<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<*mut T> for AtomicPtr<T> {
+    fn from(ptr: *mut T) -> Self {
+        Self::new(ptr)
+    }
+}
+
+impl<T> Debug for AtomicPtr<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
@@ -0,0 +1,453 @@
+//! A Harness for Soak-/Stress-Tests of the Queues, that checks the
+//! Invariants every Queue has to uphold.
+//!
+//! The Harness runs any Number of Producers and Consumers on their own
+//! Threads, while injecting Yields into all the atomic Operations (see
+//! [`inject_yields_with`](super::inject_yields_with)) and randomly yielding
+//! between the individual Operations. Afterwards it checks that
+//! * no Message was lost
+//! * no Message was received more than once
+//! * every Consumer received the Messages of every Producer in the Order
+//!   they were sent, unless [`StressConfig::fifo`] is disabled
+//!
+//! If any of these Checks fails or any of the Threads panics, the Harness
+//! panics with the Seed of the Run, which can be used to reproduce it by
+//! setting it in the [`SEED_ENV`](super::SEED_ENV) Environment-Variable.
+//!
+//! The Producers and Consumers are given as Closures, so any Queue can be
+//! tested, regardless of how its Senders and Receivers are shared between
+//! the Threads.
+//!
+//! # Example
+//! ```
+//! # use nolock::{queues::mpmc, test_util::{self, stress}};
+//! let (rx, tx) = mpmc::bounded::scq::queue::<stress::Message>(16);
+//!
+//! let producers: Vec<_> = (0..2)
+//!     .map(|_| {
+//!         let tx = tx.clone();
+//!         move |msg| tx.try_enqueue(msg).map_err(|(_, msg)| msg)
+//!     })
+//!     .collect();
+//! let consumers: Vec<_> = (0..2)
+//!     .map(|_| {
+//!         let rx = rx.clone();
+//!         move || rx.try_dequeue().ok()
+//!     })
+//!     .collect();
+//!
+//! let config = stress::StressConfig {
+//!     messages: 100,
+//!     ..Default::default()
+//! };
+//! stress::run(test_util::seed_from_env(), &config, producers, consumers);
+//! ```
+
+use std::{
+    any::Any,
+    boxed::Box,
+    format,
+    string::String,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+    vec,
+    vec::Vec,
+};
+
+use super::{inject_yields_with, next_random, splitmix, Config, SEED_ENV};
+
+/// A single Message sent through the Queue during a Stress-Test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Message {
+    /// The Index of the Producer that sent the Message
+    pub producer: usize,
+    /// The Position of the Message in the Messages sent by its Producer
+    pub seq: usize,
+}
+
+/// Configures a single Run of the Stress-Test Harness
+#[derive(Debug, Clone)]
+pub struct StressConfig {
+    /// The Number of Messages every Producer sends
+    pub messages: usize,
+    /// Whether the Queue is expected to preserve the Order of the Messages
+    /// sent by a single Producer
+    pub fifo: bool,
+    /// How often the Producers and Consumers yield between their Operations,
+    /// given as `1 in n` like in [`Config`], `0` disables it
+    pub yield_one_in: u32,
+    /// The Injection used for all the atomic Operations during the Run
+    pub injection: Config,
+    /// How long the Run may go on without any Message being received,
+    /// before all the Messages, that have not been received yet, are
+    /// considered lost
+    pub timeout: Duration,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        Self {
+            messages: 10_000,
+            fifo: true,
+            yield_one_in: 64,
+            injection: Config::default(),
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Tracks when the last Message was received, to detect when a Run has
+/// stalled
+struct Progress {
+    start: Instant,
+    /// The Time of the last received Message in Milliseconds since `start`
+    last: AtomicU64,
+    timeout: Duration,
+}
+
+impl Progress {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            last: AtomicU64::new(0),
+            timeout,
+        }
+    }
+
+    fn elapsed(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    fn record(&self) {
+        self.last.store(self.elapsed(), Ordering::Release);
+    }
+
+    fn is_stalled(&self) -> bool {
+        let idle = self.elapsed() - self.last.load(Ordering::Acquire);
+        idle > self.timeout.as_millis() as u64
+    }
+}
+
+/// Randomly yields the current Thread, based on the Generator
+struct Yielder {
+    state: u64,
+    one_in: u64,
+}
+
+impl Yielder {
+    fn new(seed: u64, index: u64, one_in: u32) -> Self {
+        Self {
+            state: splitmix(seed ^ splitmix(index)) | 1,
+            one_in: one_in as u64,
+        }
+    }
+
+    fn maybe_yield(&mut self) {
+        if self.one_in != 0 && next_random(&mut self.state).is_multiple_of(self.one_in) {
+            thread::yield_now();
+        }
+    }
+}
+
+/// Runs the Stress-Test with the given Producers and Consumers, each on
+/// their own Thread.
+///
+/// Every Producer is called with the Messages it should send and returns the
+/// Message again, if it could not be sent right now, in which case it is
+/// retried. Every Consumer is called repeatedly until all the Messages have
+/// been received and returns `None` if there is currently no Message
+/// available.
+///
+/// # Panics
+/// If any of the Invariants was violated or any of the Producers or
+/// Consumers panicked, together with the Seed of the Run
+pub fn run<P, C>(seed: u64, config: &StressConfig, producers: Vec<P>, consumers: Vec<C>)
+where
+    P: FnMut(Message) -> Result<(), Message> + Send + 'static,
+    C: FnMut() -> Option<Message> + Send + 'static,
+{
+    if let Err(reason) = try_run(seed, config, producers, consumers) {
+        panic!(
+            "Stress-Test failed, reproduce it with {}={}: {}",
+            SEED_ENV, seed, reason
+        );
+    }
+}
+
+fn try_run<P, C>(
+    seed: u64,
+    config: &StressConfig,
+    producers: Vec<P>,
+    consumers: Vec<C>,
+) -> Result<(), String>
+where
+    P: FnMut(Message) -> Result<(), Message> + Send + 'static,
+    C: FnMut() -> Option<Message> + Send + 'static,
+{
+    assert!(
+        !producers.is_empty() && !consumers.is_empty(),
+        "A Stress-Test needs at least one Producer and one Consumer"
+    );
+
+    let producer_count = producers.len();
+    let total = producer_count * config.messages;
+    let received = Arc::new(AtomicUsize::new(0));
+    let progress = Arc::new(Progress::new(config.timeout));
+
+    let injection = inject_yields_with(seed, config.injection.clone());
+
+    let producer_handles: Vec<_> = producers
+        .into_iter()
+        .enumerate()
+        .map(|(producer, mut send)| {
+            let mut yielder = Yielder::new(seed, producer as u64, config.yield_one_in);
+            let messages = config.messages;
+            let progress = progress.clone();
+            thread::spawn(move || {
+                for seq in 0..messages {
+                    let mut msg = Message { producer, seq };
+                    while let Err(m) = send(msg) {
+                        if progress.is_stalled() {
+                            return;
+                        }
+                        msg = m;
+                        thread::yield_now();
+                    }
+                    yielder.maybe_yield();
+                }
+            })
+        })
+        .collect();
+
+    let consumer_handles: Vec<_> = consumers
+        .into_iter()
+        .enumerate()
+        .map(|(consumer, mut recv)| {
+            let mut yielder = Yielder::new(
+                seed,
+                (producer_count + consumer) as u64,
+                config.yield_one_in,
+            );
+            let received = received.clone();
+            let progress = progress.clone();
+            thread::spawn(move || {
+                let mut messages = Vec::new();
+                while received.load(Ordering::Acquire) < total && !progress.is_stalled() {
+                    match recv() {
+                        Some(msg) => {
+                            messages.push(msg);
+                            received.fetch_add(1, Ordering::AcqRel);
+                            progress.record();
+                        }
+                        None => thread::yield_now(),
+                    }
+                    yielder.maybe_yield();
+                }
+                messages
+            })
+        })
+        .collect();
+
+    let mut failures = Vec::new();
+    for handle in producer_handles {
+        if let Err(e) = handle.join() {
+            failures.push(format!("A Producer panicked: {}", panic_message(&e)));
+        }
+    }
+    let mut received = Vec::new();
+    for handle in consumer_handles {
+        match handle.join() {
+            Ok(messages) => received.push(messages),
+            Err(e) => failures.push(format!("A Consumer panicked: {}", panic_message(&e))),
+        }
+    }
+    drop(injection);
+
+    if !failures.is_empty() {
+        return Err(failures.join(", "));
+    }
+
+    check(config, producer_count, &received)
+}
+
+/// Checks the Messages received by every Consumer against the Invariants
+fn check(
+    config: &StressConfig,
+    producer_count: usize,
+    received: &[Vec<Message>],
+) -> Result<(), String> {
+    let mut seen = vec![vec![false; config.messages]; producer_count];
+
+    for (consumer, messages) in received.iter().enumerate() {
+        let mut next = vec![0; producer_count];
+
+        for msg in messages {
+            let slot = seen
+                .get_mut(msg.producer)
+                .and_then(|s| s.get_mut(msg.seq))
+                .ok_or_else(|| format!("Received an invalid Message {:?}", msg))?;
+            if *slot {
+                return Err(format!("Received {:?} more than once", msg));
+            }
+            *slot = true;
+
+            if config.fifo {
+                if msg.seq < next[msg.producer] {
+                    return Err(format!(
+                        "Consumer {} received {:?} after Message {} of the same Producer",
+                        consumer,
+                        msg,
+                        next[msg.producer] - 1
+                    ));
+                }
+                next[msg.producer] = msg.seq + 1;
+            }
+        }
+    }
+
+    let lost: Vec<_> = seen
+        .iter()
+        .enumerate()
+        .flat_map(|(producer, seqs)| {
+            seqs.iter()
+                .enumerate()
+                .filter(|(_, seen)| !**seen)
+                .map(move |(seq, _)| Message { producer, seq })
+        })
+        .collect();
+    if !lost.is_empty() {
+        return Err(format!(
+            "Lost {} Messages, starting with {:?}",
+            lost.len(),
+            &lost[..lost.len().min(8)]
+        ));
+    }
+
+    Ok(())
+}
+
+fn panic_message(payload: &Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        return String::from(*msg);
+    }
+    if let Some(msg) = payload.downcast_ref::<String>() {
+        return msg.clone();
+    }
+    String::from("Unknown Panic")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{collections::VecDeque, sync::Mutex};
+
+    fn config() -> StressConfig {
+        StressConfig {
+            messages: 100,
+            timeout: Duration::from_secs(1),
+            ..Default::default()
+        }
+    }
+
+    type Producer = Box<dyn FnMut(Message) -> Result<(), Message> + Send>;
+    type Consumer = Box<dyn FnMut() -> Option<Message> + Send>;
+
+    /// A Queue that passes the Messages through the given Function, to
+    /// simulate broken Queues
+    fn faulty<F>(producers: usize, fault: F) -> (Vec<Producer>, Vec<Consumer>)
+    where
+        F: FnMut(&mut VecDeque<Message>, Message) + Send + 'static,
+    {
+        let queue = Arc::new(Mutex::new((VecDeque::new(), fault)));
+
+        let senders = (0..producers)
+            .map(|_| {
+                let queue = queue.clone();
+                Box::new(move |msg| {
+                    let mut guard = queue.lock().unwrap();
+                    let (queue, fault) = &mut *guard;
+                    fault(queue, msg);
+                    Ok(())
+                }) as Producer
+            })
+            .collect();
+        let receivers: Vec<Consumer> = vec![Box::new(move || queue.lock().unwrap().0.pop_front())];
+
+        (senders, receivers)
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn correct_queue() {
+        let (producers, consumers) = faulty(2, |queue, msg| queue.push_back(msg));
+        assert_eq!(Ok(()), try_run(13, &config(), producers, consumers));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn detects_loss() {
+        let (producers, consumers) = faulty(2, |queue, msg| {
+            if msg.seq != 50 {
+                queue.push_back(msg);
+            }
+        });
+        let result = try_run(13, &config(), producers, consumers);
+        assert!(result.unwrap_err().starts_with("Lost 2 Messages"));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn detects_duplicates() {
+        let (producers, consumers) = faulty(1, |queue, msg| {
+            queue.push_back(msg);
+            if msg.seq == 10 {
+                queue.push_back(msg);
+            }
+        });
+        let result = try_run(13, &config(), producers, consumers);
+        assert!(result.unwrap_err().contains("more than once"));
+    }
+
+    /// Swaps the Messages 10 and 11 of every Producer
+    fn swapping() -> impl FnMut(&mut VecDeque<Message>, Message) + Send + 'static {
+        let mut held = None;
+        move |queue, msg| match msg.seq {
+            10 => held = Some(msg),
+            11 => {
+                queue.push_back(msg);
+                queue.push_back(held.take().unwrap());
+            }
+            _ => queue.push_back(msg),
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn detects_reordering() {
+        let (producers, consumers) = faulty(1, swapping());
+        let result = try_run(13, &config(), producers, consumers);
+        assert!(result.unwrap_err().contains("after Message"));
+
+        // The same Queue is fine, if the Order does not matter
+        let (producers, consumers) = faulty(1, swapping());
+        let config = StressConfig {
+            fifo: false,
+            ..config()
+        };
+        assert_eq!(Ok(()), try_run(13, &config, producers, consumers));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    #[should_panic(expected = "NOLOCK_TEST_SEED=13")]
+    fn panics_with_seed() {
+        let producers = vec![|_| -> Result<(), Message> { panic!("broken") }];
+        let consumers = vec![|| None];
+        run(13, &config(), producers, consumers);
+    }
+}
@@ -36,7 +36,8 @@
 mod id;
 use core::fmt::Debug;
 
-use id::Id;
+pub mod key;
+use key::{Key, ThreadKey};
 
 pub mod storage;
 
@@ -57,12 +58,16 @@ pub trait StorageBackend<T> {
 }
 
 /// A Storage-Container for Thread Local Data
-pub struct ThreadDataStorage<S, T> {
+///
+/// The Data is stored per Thread by default, but the Key used to select the
+/// Data for the current Context can be customized using the `K` Parameter,
+/// see the [`key`] module for more Details
+pub struct ThreadDataStorage<S, T, K = ThreadKey> {
     storage: S,
-    _marker: core::marker::PhantomData<T>,
+    _marker: core::marker::PhantomData<(T, K)>,
 }
 
-impl<S, T> Debug for ThreadDataStorage<S, T>
+impl<S, T, K> Debug for ThreadDataStorage<S, T, K>
 where
     S: StorageBackend<T>,
 {
@@ -71,20 +76,62 @@ where
     }
 }
 
-impl<T> ThreadDataStorage<storage::Trie<T>, T> {
+impl<T, K> ThreadDataStorage<storage::Trie<T>, T, K> {
     /// Creates a new Instance using the [`Trie`](storage::Trie) StorageBackend
     pub fn new() -> Self {
         Self::new_storage(storage::Trie::new())
     }
 }
-impl<T> ThreadDataStorage<storage::List<T>, T> {
+impl<T, K> ThreadDataStorage<storage::Trie<T>, T, K> {
+    /// Creates a new Instance using the [`Trie`](storage::Trie)
+    /// StorageBackend, with the given Number of Key-Bits per Level and the
+    /// given maximum Chain-Length, see [`Trie::with_config`](storage::Trie::with_config)
+    ///
+    /// # Panics
+    /// If the `key_size` is 0 or larger than 16 or if the `max_chain` is 0
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::thread_data::ThreadData;
+    /// // Use 256 Buckets per Level and allow up to 4 Entries per Bucket
+    /// let local_data = ThreadData::with_trie_config(8, 4);
+    ///
+    /// assert_eq!(13, *local_data.get_or(|| 13));
+    /// assert_eq!(Some(&13), local_data.get());
+    /// ```
+    pub fn with_trie_config(key_size: usize, max_chain: usize) -> Self {
+        Self::new_storage(storage::Trie::with_config(key_size, max_chain))
+    }
+}
+impl<T, K> ThreadDataStorage<storage::List<T>, T, K> {
     /// Creates a new Instance using the [`List`](storage::List) StorageBackend
     pub const fn new() -> Self {
         Self::new_storage(storage::List::new())
     }
 }
 
-impl<S, T> ThreadDataStorage<S, T> {
+impl<T, K> ThreadDataStorage<storage::Array<T>, T, K> {
+    /// Creates a new Instance using the [`Array`](storage::Array)
+    /// StorageBackend, which can hold the Data for up to `capacity` Threads
+    ///
+    /// # Panics
+    /// If the `capacity` is 0
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::thread_data::{storage, ThreadDataStorage};
+    /// let local_data: ThreadDataStorage<storage::Array<usize>, usize> =
+    ///     ThreadDataStorage::with_capacity(8);
+    ///
+    /// assert_eq!(13, *local_data.get_or(|| 13));
+    /// assert_eq!(Some(&13), local_data.get());
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::new_storage(storage::Array::new(capacity))
+    }
+}
+
+impl<S, T, K> ThreadDataStorage<S, T, K> {
     /// Creates a new Instance which uses the given Storage-Backend for all the
     /// Data.
     ///
@@ -101,13 +148,14 @@ impl<S, T> ThreadDataStorage<S, T> {
     }
 }
 
-impl<S, T> ThreadDataStorage<S, T>
+impl<S, T, K> ThreadDataStorage<S, T, K>
 where
     S: StorageBackend<T>,
+    K: Key,
 {
     /// Attempts to load the stored Data for the current Thread
     pub fn get(&self) -> Option<&T> {
-        let id = Id::new().as_u64();
+        let id = K::current();
 
         self.storage.get(id)
     }
@@ -119,7 +167,7 @@ where
         F: FnOnce() -> T,
     {
         // First Attempt to load the Data
-        let id = Id::new().as_u64();
+        let id = K::current();
         match self.storage.get(id) {
             Some(d) => d,
             // If there is no Entry for the Data, create it with the given
@@ -133,23 +181,31 @@ where
     }
 }
 
-impl<T> Default for ThreadDataStorage<storage::Trie<T>, T> {
+impl<T, K> Default for ThreadDataStorage<storage::Trie<T>, T, K> {
     fn default() -> Self {
         Self::new()
     }
 }
-impl<T> Default for ThreadDataStorage<storage::List<T>, T> {
+impl<T, K> Default for ThreadDataStorage<storage::List<T>, T, K> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-unsafe impl<S, T> Sync for ThreadDataStorage<S, T> {}
-unsafe impl<S, T> Send for ThreadDataStorage<S, T> {}
+// Safety:
+// Every Thread only ever accesses its own Entry, as a Key is only ever used by
+// a single Thread at a Time, so T does not need to be Sync, but all the
+// Entries are dropped by the Thread dropping the Storage
+unsafe impl<S, T, K> Sync for ThreadDataStorage<S, T, K> where T: Send {}
+unsafe impl<S, T, K> Send for ThreadDataStorage<S, T, K> where T: Send {}
 
 /// The Default ThreadData Storage with the [`Trie`](storage::Trie) backend.
 /// This should be the right fit for basically all Use-Cases as it is the
-/// fastest Storage-Backend while also having low memory overhead
+/// fastest Storage-Backend while also having low memory overhead.
+///
+/// If the Number of Threads is known up front, the [`Array`](storage::Array)
+/// backend can be selected instead using [`ThreadDataStorage`] directly,
+/// which avoids walking the Trie on every Lookup.
 pub type ThreadData<T> = ThreadDataStorage<storage::Trie<T>, T>;
 
 #[cfg(test)]
@@ -200,4 +256,54 @@ mod tests {
             handle.join().unwrap();
         }
     }
+
+    #[test]
+    fn array_different_threads() {
+        let data = Arc::new(ThreadDataStorage::<storage::Array<usize>, usize>::with_capacity(4));
+
+        let handles: Vec<_> = (0..4)
+            .map(|number| {
+                let c_data = data.clone();
+                std::thread::spawn(move || {
+                    let result = c_data.get_or(|| number);
+                    assert_eq!(number, *result);
+                    assert_eq!(Some(&number), c_data.get());
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn custom_key() {
+        std::thread_local! {
+            static CONTEXT: core::cell::Cell<u64> = const { core::cell::Cell::new(0) };
+        }
+
+        struct ContextKey;
+        unsafe impl Key for ContextKey {
+            fn current() -> u64 {
+                CONTEXT.with(|c| c.get())
+            }
+        }
+
+        let data = Arc::new(ThreadDataStorage::<storage::Trie<u64>, u64, ContextKey>::new());
+
+        for context in 0..8 {
+            CONTEXT.with(|c| c.set(context));
+            assert_eq!(context, *data.get_or(|| context));
+        }
+
+        // The same Context on another Thread sees the same Data
+        let c_data = data.clone();
+        std::thread::spawn(move || {
+            CONTEXT.with(|c| c.set(3));
+            assert_eq!(Some(&3), c_data.get());
+        })
+        .join()
+        .unwrap();
+    }
 }
@@ -0,0 +1,66 @@
+//! The Keys under which the Data is stored in a [`ThreadDataStorage`].
+//!
+//! By default, the Data is stored per Thread using the [`ThreadKey`], but
+//! any other Context can be used as well, as long as it is only ever active
+//! on a single Thread at a Time, like the ID of an async Task.
+//!
+//! # Example
+//! ```rust
+//! # use nolock::thread_data::{key::Key, storage, ThreadDataStorage};
+//! # use std::cell::Cell;
+//! std::thread_local! {
+//!     static CURRENT_TASK: Cell<u64> = const { Cell::new(0) };
+//! }
+//!
+//! /// Keys the Data by the ID of the Task that is currently running on this
+//! /// Thread, which is set by the Executor before polling the Task
+//! struct TaskKey;
+//!
+//! // Safety:
+//! // A Task is only ever polled by a single Thread at a Time
+//! unsafe impl Key for TaskKey {
+//!     fn current() -> u64 {
+//!         CURRENT_TASK.with(|task| task.get())
+//!     }
+//! }
+//!
+//! let local_data = ThreadDataStorage::<storage::Trie<usize>, usize, TaskKey>::new();
+//!
+//! CURRENT_TASK.with(|task| task.set(1));
+//! assert_eq!(1, *local_data.get_or(|| 1));
+//!
+//! CURRENT_TASK.with(|task| task.set(2));
+//! assert_eq!(None, local_data.get());
+//! assert_eq!(2, *local_data.get_or(|| 2));
+//! ```
+//!
+//! [`ThreadDataStorage`]: super::ThreadDataStorage
+
+use super::id::Id;
+
+/// Provides the Key for the current Context, under which its Data is stored
+///
+/// # Safety
+/// A Key must only ever be returned on a single Thread at a Time, for as long
+/// as the Data stored for it is in use, as the Data is neither required to be
+/// Sync nor is it possible to insert the same Key concurrently.
+///
+/// This rules out Keys like the ID of the current CPU-Core, as a Thread can
+/// be preempted and another Thread scheduled on the same Core at any Point.
+pub unsafe trait Key {
+    /// Returns the Key for the current Context
+    fn current() -> u64;
+}
+
+/// The default [`Key`], which stores the Data for every Thread
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadKey;
+
+// Safety:
+// Every Thread has its own unique ID, which is never reused while the
+// Process is running
+unsafe impl Key for ThreadKey {
+    fn current() -> u64 {
+        Id::new().as_u64()
+    }
+}
@@ -1,6 +1,9 @@
 //! Contains a variety of backing Storage implementations for the Thread-Data
 //! Datastructure
 
+mod array;
+pub use array::Array;
+
 mod list;
 pub use list::List;
 
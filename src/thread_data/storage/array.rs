@@ -0,0 +1,208 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{cell::UnsafeCell, fmt::Debug, mem::MaybeUninit, sync::atomic};
+
+use crate::thread_data::StorageBackend;
+
+/// The Slot is not used by any Thread
+const EMPTY: u8 = 0;
+/// The Slot has been claimed by a Thread, but its Data is not yet written
+const WRITING: u8 = 1;
+/// The Slot contains the initialized Data for its ID
+const READY: u8 = 2;
+
+struct Slot<T> {
+    state: atomic::AtomicU8,
+    id: UnsafeCell<u64>,
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Slot<T> {
+    fn new() -> Self {
+        Self {
+            state: atomic::AtomicU8::new(EMPTY),
+            id: UnsafeCell::new(0),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+/// A Lock-Free fixed-capacity Array, that uses the ID of a Thread as the
+/// Index into it.
+///
+/// # Performance
+/// Lookups start at the Slot `id % capacity` and only probe the following
+/// Slots if that one is taken by another Thread. Thread-IDs are handed out
+/// sequentially, so as long as all Threads using the Storage are created up
+/// front and their Number does not exceed the Capacity, every Lookup will
+/// find its Entry in the first Slot, making it an O(1) Operation.
+///
+/// # Capacity
+/// The Array never grows, so it can only hold Data for at most `capacity`
+/// different Threads. Inserting the Data for any additional Thread panics.
+pub struct Array<T> {
+    slots: Box<[Slot<T>]>,
+}
+
+impl<T> Debug for Array<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Array (capacity: {})", self.slots.len())
+    }
+}
+
+impl<T> Array<T> {
+    /// Creates a new empty Instance, that can store the Data for up to
+    /// `capacity` Threads
+    ///
+    /// # Panics
+    /// If the `capacity` is 0
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "The Capacity needs to be at least 1");
+
+        let slots: Vec<Slot<T>> = (0..capacity).map(|_| Slot::new()).collect();
+        Self {
+            slots: slots.into_boxed_slice(),
+        }
+    }
+
+    /// The Number of Threads this Array can store Data for
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Iterates over the Slots in the Probe-Order for the given ID
+    fn probe(&self, id: u64) -> impl Iterator<Item = &Slot<T>> {
+        let start = (id % self.slots.len() as u64) as usize;
+        self.slots[start..].iter().chain(self.slots[..start].iter())
+    }
+}
+
+impl<T> StorageBackend<T> for Array<T> {
+    fn get(&self, id: u64) -> Option<&T> {
+        for slot in self.probe(id) {
+            match slot.state.load(atomic::Ordering::Acquire) {
+                // The Entry for an ID is always stored in the first Slot that
+                // was empty at the Time of insertion and Slots never become
+                // empty again, so there can be no Entry after an empty Slot
+                EMPTY => return None,
+                // A Slot that is currently being written to belongs to another
+                // Thread, as only the Thread itself inserts its own ID
+                WRITING => continue,
+                _ => {}
+            };
+
+            // Safety:
+            // The Slot is marked as READY, so its ID and Data have been fully
+            // written and will not be modified again until the Array is dropped
+            if unsafe { *slot.id.get() } == id {
+                return Some(unsafe { (*slot.data.get()).assume_init_ref() });
+            }
+        }
+
+        None
+    }
+
+    fn insert(&self, id: u64, data: T) -> &T {
+        for slot in self.probe(id) {
+            if slot
+                .state
+                .compare_exchange(
+                    EMPTY,
+                    WRITING,
+                    atomic::Ordering::AcqRel,
+                    atomic::Ordering::Relaxed,
+                )
+                .is_err()
+            {
+                continue;
+            }
+
+            // Safety:
+            // We successfully claimed the Slot, so no other Thread will
+            // access its ID or Data until it is marked as READY
+            let data = unsafe {
+                *slot.id.get() = id;
+                (*slot.data.get()).write(data)
+            };
+            slot.state.store(READY, atomic::Ordering::Release);
+
+            return data;
+        }
+
+        panic!(
+            "The Array-Storage can only hold Data for {} Threads",
+            self.slots.len()
+        );
+    }
+}
+
+impl<T> Drop for Array<T> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter_mut() {
+            if *slot.state.get_mut() == READY {
+                unsafe { slot.data.get_mut().assume_init_drop() };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_storage() {
+        let storage = Array::<usize>::new(4);
+        assert_eq!(4, storage.capacity());
+    }
+
+    #[test]
+    fn get_non_existent() {
+        let storage = Array::<usize>::new(4);
+        assert_eq!(None, storage.get(0));
+    }
+
+    #[test]
+    fn insert_get() {
+        let storage = Array::<usize>::new(4);
+
+        assert_eq!(123, *storage.insert(13, 123));
+        assert_eq!(Some(&123), storage.get(13));
+        assert_eq!(None, storage.get(14));
+    }
+
+    #[test]
+    fn insert_get_colliding() {
+        let storage = Array::<usize>::new(4);
+
+        storage.insert(1, 10);
+        storage.insert(5, 50);
+        storage.insert(9, 90);
+
+        assert_eq!(Some(&10), storage.get(1));
+        assert_eq!(Some(&50), storage.get(5));
+        assert_eq!(Some(&90), storage.get(9));
+        assert_eq!(None, storage.get(13));
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_full() {
+        let storage = Array::<usize>::new(2);
+
+        storage.insert(0, 0);
+        storage.insert(1, 1);
+        storage.insert(2, 2);
+    }
+
+    #[test]
+    fn drops_entries() {
+        let data = std::sync::Arc::new(());
+
+        let storage = Array::new(2);
+        storage.insert(0, data.clone());
+        assert_eq!(2, std::sync::Arc::strong_count(&data));
+
+        drop(storage);
+        assert_eq!(1, std::sync::Arc::strong_count(&data));
+    }
+}
@@ -12,7 +12,26 @@ use ptr::{CustomPtr, PtrTarget};
 
 use crate::thread_data::StorageBackend;
 
+/// The Number of Key-Bits used by every Level of the Trie by default
+const DEFAULT_KEY_SIZE: usize = 3;
+/// The default maximum Length of an Entry-Chain, before it is moved into its
+/// own Level
+const DEFAULT_MAX_CHAIN: usize = 2;
+/// The largest supported Number of Key-Bits per Level
+const MAX_KEY_SIZE: usize = 16;
+
 /// A Lock-Free Trie that can be used as the StorageBackend for Thread-Local-Data
+///
+/// # Layout
+/// Every Level of the Trie uses `key_size` Bits of the Thread-ID, starting
+/// with the lowest Bits, to select one of its `2^key_size` Buckets. Entries
+/// that end up in the same Bucket form a Chain, until the Chain reaches
+/// `max_chain` Entries, at which point the Bucket is replaced with a new
+/// Level that uses the next Bits of the Key.
+///
+/// The defaults work well for sequentially assigned Thread-IDs, but if the
+/// IDs only differ in their higher Bits, a larger `key_size` and `max_chain`
+/// can be configured using [`Trie::with_config`], to avoid deep Tries.
 pub struct Trie<T> {
     // The Pointer to the first Level
     initial_ptr: *mut Level<T>,
@@ -36,7 +55,34 @@ where
 impl<T> Trie<T> {
     /// Creates a new Trie instance
     pub fn new() -> Self {
-        let initial_level = Level::new(0, 3, core::ptr::null());
+        Self::with_config(DEFAULT_KEY_SIZE, DEFAULT_MAX_CHAIN)
+    }
+
+    /// Creates a new Trie instance, where every Level uses `key_size` Bits of
+    /// the Key and therefore has `2^key_size` Buckets, and Chains of up to
+    /// `max_chain` Entries are allowed in a single Bucket before they are
+    /// moved into a new Level
+    ///
+    /// # Panics
+    /// If the `key_size` is 0 or larger than 16 or if the `max_chain` is 0
+    ///
+    /// # Example
+    /// ```rust
+    /// # use nolock::thread_data::{storage, StorageBackend};
+    /// let trie = storage::Trie::with_config(8, 4);
+    ///
+    /// assert_eq!(13, *trie.insert(0x1234, 13));
+    /// assert_eq!(Some(&13), trie.get(0x1234));
+    /// ```
+    pub fn with_config(key_size: usize, max_chain: usize) -> Self {
+        assert!(
+            key_size > 0 && key_size <= MAX_KEY_SIZE,
+            "The Key-Size needs to be between 1 and {} Bits",
+            MAX_KEY_SIZE
+        );
+        assert!(max_chain > 0, "The maximum Chain-Length can not be 0");
+
+        let initial_level = Level::new(0, key_size, max_chain, core::ptr::null());
 
         Self {
             initial_ptr: Box::into_raw(initial_level),
@@ -132,4 +178,35 @@ mod tests {
         assert_eq!(Some(&14), trie.get(0x1334));
         assert_eq!(Some(&15), trie.get(0x1434));
     }
+
+    #[test]
+    fn insert_get_high_bits() {
+        let trie = Trie::<usize>::with_config(8, 4);
+
+        for i in 0..64u64 {
+            assert_eq!(i as usize, *trie.insert(i << 48, i as usize));
+        }
+        for i in 0..64u64 {
+            assert_eq!(Some(&(i as usize)), trie.get(i << 48));
+        }
+        assert_eq!(None, trie.get(64 << 48));
+    }
+
+    #[test]
+    fn insert_get_no_chains() {
+        let trie = Trie::<usize>::with_config(1, 1);
+
+        for i in 0..32 {
+            assert_eq!(i, *trie.insert(i as u64, i));
+        }
+        for i in 0..32 {
+            assert_eq!(Some(&i), trie.get(i as u64));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_key_size() {
+        Trie::<usize>::with_config(0, 2);
+    }
 }
@@ -11,11 +11,17 @@ pub struct Level<T> {
     previous: *const Self,
     entries: Vec<CustomPtr<T>>,
     key_size: usize,
+    max_chain: usize,
 }
 
 impl<T> Level<T> {
     /// Creates a new Trie-Level with the given Settings
-    pub fn new(level: usize, key_size: usize, previous: *const Self) -> Box<Self> {
+    pub fn new(
+        level: usize,
+        key_size: usize,
+        max_chain: usize,
+        previous: *const Self,
+    ) -> Box<Self> {
         // Calculate the Number of Buckets needed to fit all the possible
         // "Subkeys"
         let bucket_count = 2usize.pow(key_size as u32);
@@ -26,6 +32,7 @@ impl<T> Level<T> {
             previous,
             entries: Vec::with_capacity(bucket_count),
             key_size,
+            max_chain,
         });
 
         // Fill in the intermediate Result with all the missing Data
@@ -41,13 +48,18 @@ impl<T> Level<T> {
     /// This is just a simple helper function to make sure all the variables
     /// are updated and passed on correctly
     pub fn create_next(&self) -> Box<Self> {
-        Self::new(self.level + 1, self.key_size, self.get_own_ptr())
+        Self::new(
+            self.level + 1,
+            self.key_size,
+            self.max_chain,
+            self.get_own_ptr(),
+        )
     }
 
     /// The maximum Length for Entry-Chains before they are converted into
     /// their own Level
-    pub const fn max_chain(&self) -> usize {
-        2
+    pub fn max_chain(&self) -> usize {
+        self.max_chain
     }
     /// The current "Level" of the Trie-Level
     pub fn level(&self) -> usize {
@@ -69,16 +81,19 @@ impl<T> Level<T> {
     /// Calculates the Entry/Bucket Index for the given Key
     fn index(key: u64, level: usize, key_size: usize) -> usize {
         let start = key_size * level;
-        let end = key_size * (level + 1);
-        let mask = !(u64::MAX << end);
-        ((key & mask) >> start) as usize
+        // Levels past the End of the Key only ever use the first Bucket
+        if start >= 64 {
+            return 0;
+        }
+
+        let mask = !(u64::MAX << key_size);
+        ((key >> start) & mask) as usize
     }
 
     fn adjust_node_on_chain(&self, node: &Entry<T>, chain: &Entry<T>, chain_pos: usize) {
         if let PtrTarget::Level(sub_lvl_ptr) = chain.next.load(atomic::Ordering::Acquire) {
             if chain_pos == self.max_chain() {
-                let new_level = Level::new(self.level + 1, self.key_size, self.get_own_ptr());
-                let new_level_ptr = Box::into_raw(new_level);
+                let new_level_ptr = Box::into_raw(self.create_next());
 
                 if chain
                     .next
@@ -346,7 +361,7 @@ mod tests {
 
     #[test]
     fn new() {
-        Level::<usize>::new(0, 4, std::ptr::null());
+        Level::<usize>::new(0, 4, 2, std::ptr::null());
     }
 
     #[test]
@@ -359,4 +374,11 @@ mod tests {
         assert_eq!(0x2, Level::<usize>::index(0x1234, 2, 4));
         assert_eq!(0x1, Level::<usize>::index(0x1234, 3, 4));
     }
+
+    #[test]
+    fn level_past_key() {
+        // Only the last Bit of the Key is left for this Level
+        assert_eq!(0x1, Level::<usize>::index(u64::MAX, 21, 3));
+        assert_eq!(0, Level::<usize>::index(u64::MAX, 22, 3));
+    }
 }
@@ -0,0 +1,931 @@
+//! Low-level Utilities that are useful when building lock-free Algorithms
+//!
+//! # CachePadded
+//! [`CachePadded`] aligns a Value to the Size of a Cache-Line, so that Values
+//! that are modified by different Threads do not share a Cache-Line and
+//! therefore dont cause False-Sharing.
+//!
+//! # Backoff
+//! [`Backoff`] provides an exponential Backoff for Retry-Loops, which reduces
+//! the Contention on the shared Data and gives the CPU to Hyperthread-Siblings
+//! or other Threads, instead of just spinning as fast as possible.
+//!
+//! # EventCount
+//! [`EventCount`] allows Threads to block until some Condition, like a Queue
+//! no longer being empty, becomes true, without the Thread changing the
+//! Condition having to do any expensive Work if no one is waiting.
+//!
+//! # AtomicWaker
+//! [`AtomicWaker`] stores the Waker of a single Task, that is waiting for
+//! some Event, and can be woken up from any Thread. It is used by all the
+//! async Queues and only depends on `core`, so it also works in `no_std`
+//! Executors.
+//!
+//! # SegmentAlloc
+//! [`SegmentAlloc`] allocates the large Segments of the Data-Structures, like
+//! the Buffers of the unbounded Queues or the Superblocks of the Allocator,
+//! which allows placing them on a specific NUMA-Node.
+
+#[cfg(feature = "queues")]
+use alloc::{boxed::Box, sync::Arc};
+use core::alloc::Layout;
+#[cfg(feature = "queues")]
+use core::ptr::NonNull;
+use core::{
+    cell::{Cell, UnsafeCell},
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    sync::atomic,
+    task::Waker,
+};
+
+/// Pads and aligns a Value to the Size of a Cache-Line.
+///
+/// On x86_64, aarch64 and powerpc64 this uses 128 Bytes, because these either
+/// prefetch two Cache-Lines at once or have 128 Byte Cache-Lines, and 64
+/// Bytes on every other Architecture.
+///
+/// # Example
+/// ```
+/// # use nolock::utils::CachePadded;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// struct Indices {
+///     head: CachePadded<AtomicUsize>,
+///     tail: CachePadded<AtomicUsize>,
+/// }
+///
+/// let indices = Indices {
+///     head: CachePadded::new(AtomicUsize::new(0)),
+///     tail: CachePadded::new(AtomicUsize::new(0)),
+/// };
+///
+/// indices.head.store(13, Ordering::Relaxed);
+/// assert_eq!(13, indices.head.load(Ordering::Relaxed));
+/// assert!(core::mem::align_of::<CachePadded<AtomicUsize>>() >= 64);
+/// ```
+#[cfg_attr(
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64"
+    ),
+    repr(align(128))
+)]
+#[cfg_attr(
+    not(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64"
+    )),
+    repr(align(64))
+)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CachePadded<T> {
+    value: T,
+}
+
+impl<T> CachePadded<T> {
+    /// Pads and aligns the given Value
+    pub const fn new(value: T) -> Self {
+        Self { value }
+    }
+
+    /// Returns the inner Value
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+impl<T> DerefMut for CachePadded<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+impl<T> From<T> for CachePadded<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> Debug for CachePadded<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CachePadded")
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+/// An exponential Backoff for Retry-Loops
+///
+/// # Usage
+/// * [`spin`](Self::spin) should be used after a failed Attempt to modify
+///   shared Data, like a failed CAS, where another Thread made Progress
+/// * [`snooze`](Self::snooze) should be used while waiting for another Thread
+///   to make Progress, like waiting for an Element in an empty Queue
+/// * Once [`is_completed`](Self::is_completed) returns `true`, the Caller
+///   should block the Thread using some other Mechanism, like parking it,
+///   instead of continuing to wait
+///
+/// # Example
+/// ```
+/// # use nolock::utils::Backoff;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// fn fetch_double(value: &AtomicUsize) -> usize {
+///     let backoff = Backoff::new();
+///     let mut current = value.load(Ordering::Relaxed);
+///     loop {
+///         match value.compare_exchange_weak(
+///             current,
+///             current * 2,
+///             Ordering::AcqRel,
+///             Ordering::Relaxed,
+///         ) {
+///             Ok(previous) => return previous,
+///             Err(previous) => {
+///                 current = previous;
+///                 backoff.spin();
+///             }
+///         }
+///     }
+/// }
+///
+/// let value = AtomicUsize::new(13);
+/// assert_eq!(13, fetch_double(&value));
+/// assert_eq!(26, value.load(Ordering::Relaxed));
+/// ```
+pub struct Backoff {
+    step: Cell<u32>,
+}
+
+impl Backoff {
+    /// The Step after which the Backoff no longer increases the Number of
+    /// Spins, when spinning
+    const SPIN_LIMIT: u32 = 6;
+    /// The Step after which the Backoff is considered completed
+    const YIELD_LIMIT: u32 = 10;
+
+    /// Creates a new Backoff
+    pub const fn new() -> Self {
+        Self { step: Cell::new(0) }
+    }
+
+    /// Resets the Backoff, which should be done once an Operation made
+    /// Progress and the Backoff is going to be reused
+    pub fn reset(&self) {
+        self.step.set(0);
+    }
+
+    /// Backs off in a Retry-Loop, by spinning for an exponentially growing
+    /// Number of Iterations
+    pub fn spin(&self) {
+        for _ in 0..1 << self.step.get().min(Self::SPIN_LIMIT) {
+            core::hint::spin_loop();
+        }
+
+        if self.step.get() <= Self::SPIN_LIMIT {
+            self.step.set(self.step.get() + 1);
+        }
+    }
+
+    /// Backs off in a Waiting-Loop, by first spinning for an exponentially
+    /// growing Number of Iterations and then yielding the current Thread to
+    /// the OS-Scheduler.
+    ///
+    /// Without the `std` Feature, this can not yield the Thread and will
+    /// therefore keep spinning instead
+    pub fn snooze(&self) {
+        if self.step.get() <= Self::SPIN_LIMIT {
+            for _ in 0..1 << self.step.get() {
+                core::hint::spin_loop();
+            }
+        } else {
+            #[cfg(feature = "std")]
+            std::thread::yield_now();
+
+            #[cfg(not(feature = "std"))]
+            for _ in 0..1 << Self::SPIN_LIMIT {
+                core::hint::spin_loop();
+            }
+        }
+
+        if self.step.get() <= Self::YIELD_LIMIT {
+            self.step.set(self.step.get() + 1);
+        }
+    }
+
+    /// Checks if the Backoff has completed, meaning that the Caller should
+    /// block the Thread using another Mechanism instead of continuing to wait
+    pub fn is_completed(&self) -> bool {
+        self.step.get() > Self::YIELD_LIMIT
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for Backoff {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Backoff (step: {})", self.step.get())
+    }
+}
+
+/// The Key obtained from [`EventCount::prepare_wait`], which identifies the
+/// Point in Time up to which Notifications have already been observed
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventKey(usize);
+
+/// A Primitive to block Threads until a Condition becomes true, which only
+/// costs a Fence and a Load when notifying, as long as no Thread is blocked.
+///
+/// # Usage
+/// The waiting Thread needs to follow this Protocol:
+/// 1. Check the Condition and return if it is true
+/// 2. Call [`prepare_wait`](Self::prepare_wait) to obtain a Key
+/// 3. Check the Condition again and call [`cancel_wait`](Self::cancel_wait)
+///    and return if it is now true
+/// 4. Call [`wait`](Self::wait) with the Key, which blocks until a
+///    Notification happened after the Key was obtained
+///
+/// The notifying Thread first makes the Condition true and then calls
+/// [`notify_one`](Self::notify_one) or [`notify_all`](Self::notify_all).
+///
+/// # Example
+/// ```
+/// # use nolock::utils::EventCount;
+/// # use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+/// let event = Arc::new(EventCount::new());
+/// let ready = Arc::new(AtomicBool::new(false));
+///
+/// let handle = {
+///     let event = event.clone();
+///     let ready = ready.clone();
+///     std::thread::spawn(move || {
+///         while !ready.load(Ordering::SeqCst) {
+///             let key = event.prepare_wait();
+///             if ready.load(Ordering::SeqCst) {
+///                 event.cancel_wait();
+///                 break;
+///             }
+///             event.wait(key);
+///         }
+///     })
+/// };
+///
+/// ready.store(true, Ordering::SeqCst);
+/// event.notify_all();
+/// handle.join().unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub struct EventCount {
+    /// Incremented by every Notification, while there are Waiters
+    epoch: atomic::AtomicUsize,
+    /// The Number of Threads between `prepare_wait` and the End of their
+    /// `wait` or `cancel_wait`
+    waiters: atomic::AtomicUsize,
+    lock: std::sync::Mutex<()>,
+    cond: std::sync::Condvar,
+}
+
+#[cfg(feature = "std")]
+impl Debug for EventCount {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "EventCount ()")
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for EventCount {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl EventCount {
+    /// Creates a new EventCount without any Waiters
+    pub fn new() -> Self {
+        Self {
+            epoch: atomic::AtomicUsize::new(0),
+            waiters: atomic::AtomicUsize::new(0),
+            lock: std::sync::Mutex::new(()),
+            cond: std::sync::Condvar::new(),
+        }
+    }
+
+    /// Announces that the current Thread is about to wait, the Condition
+    /// needs to be checked again afterwards
+    pub fn prepare_wait(&self) -> EventKey {
+        self.waiters.fetch_add(1, atomic::Ordering::SeqCst);
+        // Pairs with the Fence in `advance`
+        atomic::fence(atomic::Ordering::SeqCst);
+        EventKey(self.epoch.load(atomic::Ordering::SeqCst))
+    }
+
+    /// Cancels a Wait started with [`prepare_wait`](Self::prepare_wait),
+    /// because the Condition became true in the mean time
+    pub fn cancel_wait(&self) {
+        self.waiters.fetch_sub(1, atomic::Ordering::SeqCst);
+    }
+
+    /// Blocks the current Thread, until a Notification happened after the
+    /// given Key was obtained. Like with any Condition-Variable, the Thread
+    /// may also be woken up spuriously
+    pub fn wait(&self, key: EventKey) {
+        let mut guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        while self.epoch.load(atomic::Ordering::SeqCst) == key.0 {
+            guard = self.cond.wait(guard).unwrap_or_else(|e| e.into_inner());
+        }
+        drop(guard);
+
+        self.waiters.fetch_sub(1, atomic::Ordering::SeqCst);
+    }
+
+    /// Wakes up a single waiting Thread, if there are any
+    pub fn notify_one(&self) {
+        if self.advance() {
+            let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.cond.notify_one();
+        }
+    }
+
+    /// Wakes up all the waiting Threads
+    pub fn notify_all(&self) {
+        if self.advance() {
+            let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.cond.notify_all();
+        }
+    }
+
+    /// Advances the Epoch if there are any Waiters and returns whether there
+    /// were any
+    fn advance(&self) -> bool {
+        // Pairs with the `fetch_add` in `prepare_wait`, so that either we see
+        // the Waiter or the Waiter sees the updated Condition
+        atomic::fence(atomic::Ordering::SeqCst);
+        if self.waiters.load(atomic::Ordering::SeqCst) == 0 {
+            return false;
+        }
+
+        self.epoch.fetch_add(1, atomic::Ordering::SeqCst);
+        true
+    }
+}
+
+/// No Waker is currently being registered or woken
+const WAKER_IDLE: u8 = 0;
+/// A new Waker is currently being registered
+const WAKER_REGISTERING: u8 = 0b01;
+/// The registered Waker is currently being woken
+const WAKER_WAKING: u8 = 0b10;
+
+/// A Slot for the Waker of a single waiting Task, which can be woken from
+/// any Thread.
+///
+/// Registering and waking can happen concurrently, in which case the Task is
+/// always woken either by [`wake`](Self::wake) or directly by
+/// [`register`](Self::register), so no Notification is ever lost. Only a
+/// single Task should register its Waker at a Time, concurrent Calls to
+/// `register` are not lost but one of the Wakers may be dropped.
+///
+/// # Example
+/// ```
+/// # use nolock::utils::AtomicWaker;
+/// # use std::task::{Context, Poll};
+/// # use std::sync::atomic::{AtomicBool, Ordering};
+/// struct Flag {
+///     waker: AtomicWaker,
+///     set: AtomicBool,
+/// }
+///
+/// impl Flag {
+///     fn poll_set(&self, cx: &mut Context<'_>) -> Poll<()> {
+///         // Register first, so a concurrent `set` can not be missed
+///         self.waker.register(cx.waker());
+///         if self.set.load(Ordering::Acquire) {
+///             Poll::Ready(())
+///         } else {
+///             Poll::Pending
+///         }
+///     }
+///
+///     fn set(&self) {
+///         self.set.store(true, Ordering::Release);
+///         self.waker.wake();
+///     }
+/// }
+/// # let flag = Flag { waker: AtomicWaker::new(), set: AtomicBool::new(false) };
+/// # flag.set();
+/// ```
+pub struct AtomicWaker {
+    state: atomic::AtomicU8,
+    /// Only accessed by the Thread that moved the State from `WAKER_IDLE`
+    /// to either `WAKER_REGISTERING` or `WAKER_WAKING`
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+impl AtomicWaker {
+    /// Creates a new empty Slot
+    pub const fn new() -> Self {
+        Self {
+            state: atomic::AtomicU8::new(WAKER_IDLE),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// Registers the Waker to be woken by the next Call to
+    /// [`wake`](Self::wake), replacing the previously registered one.
+    ///
+    /// If a Wakeup happens concurrently, the given Waker is woken right away
+    pub fn register(&self, waker: &Waker) {
+        match self
+            .state
+            .compare_exchange(
+                WAKER_IDLE,
+                WAKER_REGISTERING,
+                atomic::Ordering::Acquire,
+                atomic::Ordering::Acquire,
+            )
+            .unwrap_or_else(|state| state)
+        {
+            WAKER_IDLE => {
+                // # Safety:
+                // We moved the State to `WAKER_REGISTERING`, so we have
+                // exclusive Access to the Waker
+                let slot = unsafe { &mut *self.waker.get() };
+                match slot {
+                    Some(old) if old.will_wake(waker) => {}
+                    _ => *slot = Some(waker.clone()),
+                };
+
+                if self
+                    .state
+                    .compare_exchange(
+                        WAKER_REGISTERING,
+                        WAKER_IDLE,
+                        atomic::Ordering::AcqRel,
+                        atomic::Ordering::Acquire,
+                    )
+                    .is_err()
+                {
+                    // A Wakeup happened while we were registering, which
+                    // could not take the Waker, so we need to deliver it.
+                    //
+                    // # Safety:
+                    // The waking Thread leaves the Waker to us, as it saw the
+                    // `WAKER_REGISTERING` Flag
+                    let waker = unsafe { (*self.waker.get()).take() };
+                    self.state.store(WAKER_IDLE, atomic::Ordering::Release);
+
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+            // The previous Waker is currently being woken, so we wake the
+            // new one directly, as the Task should be polled again anyway
+            WAKER_WAKING => waker.wake_by_ref(),
+            // Another Thread is registering at the same Time, which is
+            // misuse, but we make sure that this Task is not lost
+            _ => waker.wake_by_ref(),
+        }
+    }
+
+    /// Wakes the currently registered Waker, if there is one
+    pub fn wake(&self) {
+        if let Some(waker) = self.take() {
+            waker.wake();
+        }
+    }
+
+    /// Removes the currently registered Waker and returns it, if there is
+    /// one and it is not concurrently being registered or woken
+    pub fn take(&self) -> Option<Waker> {
+        match self.state.fetch_or(WAKER_WAKING, atomic::Ordering::AcqRel) {
+            WAKER_IDLE => {
+                // # Safety:
+                // We moved the State from `WAKER_IDLE` to `WAKER_WAKING`, so
+                // we have exclusive Access to the Waker
+                let waker = unsafe { (*self.waker.get()).take() };
+                self.state
+                    .fetch_and(!WAKER_WAKING, atomic::Ordering::Release);
+                waker
+            }
+            // Either the registering Thread will wake its Waker, once it sees
+            // our Flag, or another Thread is already waking the Waker
+            _ => None,
+        }
+    }
+}
+
+impl Debug for AtomicWaker {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "AtomicWaker ()")
+    }
+}
+
+impl Default for AtomicWaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Safety:
+// The Waker is only ever accessed by the single Thread that moved the State
+// away from `WAKER_IDLE` and Wakers themselves are Send and Sync
+unsafe impl Send for AtomicWaker {}
+unsafe impl Sync for AtomicWaker {}
+
+/// Moves the Value onto the Heap, like `Box::new`, but returns the Value back
+/// instead of aborting if the Allocation fails
+#[cfg(feature = "queues")]
+pub(crate) fn try_box<T>(value: T) -> Result<Box<T>, T> {
+    let layout = Layout::new::<T>();
+    if layout.size() == 0 {
+        return Ok(Box::new(value));
+    }
+
+    let ptr = unsafe { alloc::alloc::alloc(layout) } as *mut T;
+    if ptr.is_null() {
+        return Err(value);
+    }
+
+    // Safety:
+    // The Memory was allocated by the global Allocator using the Layout of T,
+    // which is exactly what the Box expects
+    unsafe {
+        ptr.write(value);
+        Ok(Box::from_raw(ptr))
+    }
+}
+
+/// Allocates the Memory for the Segments of the Data-Structures, like the
+/// Buffers of the [`jiffy`](crate::queues::mpsc::jiffy) and unbounded
+/// MPMC-Queues or the Superblocks of the Allocator.
+///
+/// The Segments are usually much larger than any of the other Allocations
+/// and are accessed by all the Threads using the Data-Structure, so
+/// controlling their Placement, for example on a specific NUMA-Node using
+/// `numa_alloc_onnode`, avoids expensive Cross-Node Accesses.
+///
+/// # Safety
+/// A Ptr returned by [`alloc_segment`](Self::alloc_segment) must either be
+/// null or point to a new Allocation, that is valid for the given Layout
+/// until it is passed to [`dealloc_segment`](Self::dealloc_segment).
+///
+/// # Example
+/// ```
+/// # use nolock::utils::SegmentAlloc;
+/// # use std::alloc::{GlobalAlloc, Layout, System};
+/// /// Places all the Segments on the given NUMA-Node
+/// struct OnNode(usize);
+///
+/// unsafe impl SegmentAlloc for OnNode {
+///     unsafe fn alloc_segment(&self, layout: Layout) -> *mut u8 {
+///         // This would call `numa_alloc_onnode(layout.size(), self.0)`
+///         unsafe { System.alloc(layout) }
+///     }
+///
+///     unsafe fn dealloc_segment(&self, ptr: *mut u8, layout: Layout) {
+///         // This would call `numa_free(ptr, layout.size())`
+///         unsafe { System.dealloc(ptr, layout) }
+///     }
+/// }
+/// ```
+pub unsafe trait SegmentAlloc: Send + Sync {
+    /// Allocates the Memory for a single Segment with the given Layout and
+    /// returns a Null-Ptr if the Allocation failed
+    ///
+    /// # Safety
+    /// The Layout must have a non-zero Size
+    unsafe fn alloc_segment(&self, layout: Layout) -> *mut u8;
+
+    /// Frees the Memory of a Segment again
+    ///
+    /// # Safety
+    /// The Ptr must have been returned by
+    /// [`alloc_segment`](Self::alloc_segment) of the same SegmentAlloc for
+    /// the same Layout and must not be used afterwards
+    unsafe fn dealloc_segment(&self, ptr: *mut u8, layout: Layout);
+}
+
+// Safety:
+// The System-Allocator upholds the same Guarantees for all its Allocations
+#[cfg(feature = "std")]
+unsafe impl SegmentAlloc for std::alloc::System {
+    unsafe fn alloc_segment(&self, layout: Layout) -> *mut u8 {
+        unsafe { std::alloc::GlobalAlloc::alloc(self, layout) }
+    }
+
+    unsafe fn dealloc_segment(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { std::alloc::GlobalAlloc::dealloc(self, ptr, layout) }
+    }
+}
+
+/// The shared Handle to the [`SegmentAlloc`] of a Data-Structure, which uses
+/// the Global-Allocator if none was configured
+#[cfg(feature = "queues")]
+#[derive(Clone, Default)]
+pub(crate) struct Segments(Option<Arc<dyn SegmentAlloc>>);
+
+#[cfg(feature = "queues")]
+impl Segments {
+    /// Allocates all the Segments using the given SegmentAlloc
+    pub fn new(alloc: Arc<dyn SegmentAlloc>) -> Self {
+        Self(Some(alloc))
+    }
+
+    /// # Safety
+    /// Same as for [`SegmentAlloc::alloc_segment`]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        match self.0.as_ref() {
+            Some(alloc) => unsafe { alloc.alloc_segment(layout) },
+            None => unsafe { alloc::alloc::alloc(layout) },
+        }
+    }
+
+    /// # Safety
+    /// Same as for [`SegmentAlloc::dealloc_segment`]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        match self.0.as_ref() {
+            Some(alloc) => unsafe { alloc.dealloc_segment(ptr, layout) },
+            None => unsafe { alloc::alloc::dealloc(ptr, layout) },
+        }
+    }
+}
+
+#[cfg(feature = "queues")]
+impl Debug for Segments {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Segments ()")
+    }
+}
+
+/// A fixed-size Buffer, like a boxed Slice, whose Memory is allocated using
+/// the [`SegmentAlloc`] of the Data-Structure it belongs to. Every Segment
+/// keeps its own Handle to the SegmentAlloc, so it can be freed from
+/// anywhere, like the Free-Function of a Reclamation-Scheme
+#[cfg(feature = "queues")]
+pub(crate) struct Segment<T> {
+    ptr: NonNull<T>,
+    len: usize,
+    segments: Segments,
+}
+
+#[cfg(feature = "queues")]
+impl<T> Segment<T> {
+    /// Allocates a new Segment with `len` Elements, which are initialized
+    /// using the given Function, and aborts if the Allocation fails
+    pub fn new_with<F>(segments: &Segments, len: usize, init: F) -> Self
+    where
+        F: FnMut() -> T,
+    {
+        match Self::try_new_with(segments, len, init) {
+            Some(segment) => segment,
+            None => alloc::alloc::handle_alloc_error(Self::layout(len)),
+        }
+    }
+
+    /// Allocates a new Segment, like [`new_with`](Self::new_with), but
+    /// returns `None` instead of aborting if the Allocation fails
+    pub fn try_new_with<F>(segments: &Segments, len: usize, mut init: F) -> Option<Self>
+    where
+        F: FnMut() -> T,
+    {
+        let layout = Self::layout(len);
+        let ptr = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            NonNull::new(unsafe { segments.alloc(layout) } as *mut T)?
+        };
+
+        for index in 0..len {
+            // # Safety:
+            // The Memory was allocated for `len` Elements of T
+            unsafe { ptr.as_ptr().add(index).write(init()) };
+        }
+
+        Some(Self {
+            ptr,
+            len,
+            segments: segments.clone(),
+        })
+    }
+
+    fn layout(len: usize) -> Layout {
+        Layout::array::<T>(len).expect("The Segment is too large")
+    }
+}
+
+#[cfg(feature = "queues")]
+impl<T> Deref for Segment<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        // # Safety:
+        // All the Elements were initialized when creating the Segment
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+#[cfg(feature = "queues")]
+impl<T> DerefMut for Segment<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // # Safety:
+        // All the Elements were initialized when creating the Segment
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+#[cfg(feature = "queues")]
+impl<T> Drop for Segment<T> {
+    fn drop(&mut self) {
+        unsafe { core::ptr::drop_in_place(&mut **self as *mut [T]) };
+
+        let layout = Self::layout(self.len);
+        if layout.size() != 0 {
+            unsafe { self.segments.dealloc(self.ptr.as_ptr() as *mut u8, layout) };
+        }
+    }
+}
+
+#[cfg(feature = "queues")]
+impl<T> Debug for Segment<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+// Safety:
+// The Segment owns its Elements just like a boxed Slice
+#[cfg(feature = "queues")]
+unsafe impl<T> Send for Segment<T> where T: Send {}
+#[cfg(feature = "queues")]
+unsafe impl<T> Sync for Segment<T> where T: Sync {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_padded_alignment() {
+        assert!(core::mem::align_of::<CachePadded<u8>>() >= 64);
+        assert!(core::mem::size_of::<CachePadded<u8>>() >= 64);
+
+        let mut padded = CachePadded::new(13u8);
+        *padded += 1;
+        assert_eq!(14, padded.into_inner());
+    }
+
+    #[test]
+    #[cfg(feature = "queues")]
+    fn try_box_value() {
+        assert_eq!(Ok(Box::new(13u64)), try_box(13u64));
+        assert_eq!(Ok(Box::new(())), try_box(()));
+    }
+
+    /// Counts the Segments that are currently allocated
+    #[cfg(all(feature = "queues", feature = "std"))]
+    #[derive(Default)]
+    struct CountingSegments(core::sync::atomic::AtomicUsize);
+
+    #[cfg(all(feature = "queues", feature = "std"))]
+    unsafe impl SegmentAlloc for CountingSegments {
+        unsafe fn alloc_segment(&self, layout: Layout) -> *mut u8 {
+            self.0.fetch_add(1, atomic::Ordering::SeqCst);
+            unsafe { std::alloc::System.alloc_segment(layout) }
+        }
+
+        unsafe fn dealloc_segment(&self, ptr: *mut u8, layout: Layout) {
+            self.0.fetch_sub(1, atomic::Ordering::SeqCst);
+            unsafe { std::alloc::System.dealloc_segment(ptr, layout) }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "queues", feature = "std"))]
+    fn segment_uses_alloc() {
+        let counting = Arc::new(CountingSegments::default());
+        let segments = Segments::new(counting.clone());
+
+        let mut counter = 0;
+        let segment = Segment::new_with(&segments, 4, || {
+            counter += 1;
+            alloc::vec![counter]
+        });
+        assert_eq!(1, counting.0.load(atomic::Ordering::SeqCst));
+        assert_eq!(&[vec![1], vec![2], vec![3], vec![4]], &*segment);
+
+        drop(segment);
+        assert_eq!(0, counting.0.load(atomic::Ordering::SeqCst));
+
+        // Empty Segments dont need any Memory
+        let segment = Segment::<u64>::new_with(&segments, 0, || 0);
+        assert_eq!(0, counting.0.load(atomic::Ordering::SeqCst));
+        assert!(segment.is_empty());
+    }
+
+    #[test]
+    fn backoff_completes() {
+        let backoff = Backoff::new();
+        assert!(!backoff.is_completed());
+
+        for _ in 0..=Backoff::YIELD_LIMIT {
+            backoff.snooze();
+        }
+        assert!(backoff.is_completed());
+
+        backoff.reset();
+        assert!(!backoff.is_completed());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn event_count_cancel() {
+        let event = EventCount::new();
+
+        let key = event.prepare_wait();
+        event.notify_one();
+        // The Notification happened after the Key was obtained, so this
+        // returns immediately
+        event.wait(key);
+
+        event.prepare_wait();
+        event.cancel_wait();
+        assert_eq!(0, event.waiters.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn event_count_no_waiters() {
+        let event = EventCount::new();
+
+        event.notify_one();
+        event.notify_all();
+        assert_eq!(0, event.epoch.load(atomic::Ordering::SeqCst));
+    }
+
+    struct CountingWaker(atomic::AtomicUsize);
+
+    impl alloc::task::Wake for CountingWaker {
+        fn wake(self: alloc::sync::Arc<Self>) {
+            self.0.fetch_add(1, atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn atomic_waker_wakes_registered() {
+        let counter = alloc::sync::Arc::new(CountingWaker(atomic::AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+
+        let slot = AtomicWaker::new();
+        slot.wake();
+        assert_eq!(0, counter.0.load(atomic::Ordering::SeqCst));
+
+        slot.register(&waker);
+        slot.wake();
+        assert_eq!(1, counter.0.load(atomic::Ordering::SeqCst));
+
+        // The Waker was consumed by the first Wakeup
+        slot.wake();
+        assert_eq!(1, counter.0.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn atomic_waker_take() {
+        let counter = alloc::sync::Arc::new(CountingWaker(atomic::AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+
+        let slot = AtomicWaker::new();
+        slot.register(&waker);
+        assert!(slot.take().unwrap().will_wake(&waker));
+        assert!(slot.take().is_none());
+        assert_eq!(0, counter.0.load(atomic::Ordering::SeqCst));
+    }
+
+    #[test]
+    fn spin_never_completes() {
+        let backoff = Backoff::new();
+        for _ in 0..100 {
+            backoff.spin();
+        }
+        assert!(!backoff.is_completed());
+    }
+}
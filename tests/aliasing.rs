@@ -0,0 +1,116 @@
+//! Small Tests for the Access-Patterns of the Queues and Hazard-Pointers,
+//! which are meant to be run under Miri, using `cargo miri test --test aliasing`.
+//!
+//! Every Test only uses a small Number of Elements and Threads, so that Miri
+//! can run them in a reasonable Time, while still crossing the internal
+//! Buffer- and Node-Boundaries, where the Datastructures hand over Memory
+//! between the Producers and Consumers.
+#![cfg(all(feature = "queues", not(loom)))]
+
+use std::thread;
+
+use nolock::queues::{mpsc::jiffy, spsc, DequeueError};
+
+/// The Number of Elements in a single Buffer of the Jiffy-Queue
+const JIFFY_BUFFER_SIZE: usize = 1024;
+
+#[test]
+fn jiffy_reuses_buffers() {
+    let (mut rx, tx) = jiffy::queue::<Box<usize>>();
+
+    // Crosses multiple Buffer-Boundaries, so that consumed Buffers are
+    // returned to the Cache and reused by the Sender
+    for round in 0..3 {
+        for i in 0..(JIFFY_BUFFER_SIZE + 8) {
+            tx.enqueue(Box::new(round + i)).unwrap();
+        }
+        for i in 0..(JIFFY_BUFFER_SIZE + 8) {
+            assert_eq!(Ok(Box::new(round + i)), rx.try_dequeue());
+        }
+    }
+    assert_eq!(Err(DequeueError::Empty), rx.try_dequeue());
+}
+
+#[test]
+fn jiffy_skips_pending() {
+    let (mut rx, tx) = jiffy::queue::<usize>();
+
+    let handle = thread::spawn(move || {
+        for i in 0..64 {
+            tx.enqueue(i).unwrap();
+        }
+    });
+
+    let mut received = 0;
+    while received < 64 {
+        if let Ok(data) = rx.try_dequeue() {
+            assert_eq!(received, data);
+            received += 1;
+        }
+    }
+    handle.join().unwrap();
+}
+
+#[test]
+fn spsc_unbounded_recycles_nodes() {
+    let (mut rx, mut tx) = spsc::unbounded::queue::<Box<usize>>();
+
+    let handle = thread::spawn(move || {
+        for i in 0..256 {
+            tx.enqueue(Box::new(i)).unwrap();
+        }
+    });
+
+    let mut received = 0;
+    while received < 256 {
+        if let Ok(data) = rx.try_dequeue() {
+            assert_eq!(Box::new(received), data);
+            received += 1;
+        }
+    }
+    handle.join().unwrap();
+}
+
+#[test]
+fn spsc_bounded_wraps_around() {
+    let (mut rx, mut tx) = spsc::bounded::queue::<Box<usize>>(4);
+
+    let handle = thread::spawn(move || {
+        for i in 0..32 {
+            tx.enqueue(Box::new(i)).unwrap();
+        }
+    });
+
+    for i in 0..32 {
+        assert_eq!(Some(Box::new(i)), rx.dequeue());
+    }
+    handle.join().unwrap();
+}
+
+#[cfg(feature = "hazard_ptr")]
+#[test]
+fn hazard_ptr_protect_retire() {
+    use std::sync::atomic::{AtomicPtr, Ordering};
+
+    use nolock::hazard_ptr::Domain;
+
+    let domain = Domain::new(0);
+    let atom_ptr = AtomicPtr::new(Box::into_raw(Box::new(13u64)));
+
+    let guard = domain.protect(&atom_ptr, Ordering::SeqCst);
+    let old = atom_ptr.swap(Box::into_raw(Box::new(14)), Ordering::SeqCst);
+    unsafe {
+        domain.retire(old, |ptr| {
+            let _ = Box::from_raw(ptr);
+        })
+    };
+    domain.reclaim();
+
+    // The Guard still protects the retired Value
+    assert_eq!(13, *guard);
+    drop(guard);
+    domain.reclaim();
+
+    let current = atom_ptr.swap(std::ptr::null_mut(), Ordering::SeqCst);
+    let _ = unsafe { Box::from_raw(current) };
+}
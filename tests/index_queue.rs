@@ -0,0 +1,109 @@
+#![cfg(all(feature = "queues", not(loom)))]
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+
+use nolock::queues::{index_queue::IndexQueue, mpmc::bounded::scq};
+
+const THREADS: usize = 4;
+const ITERATIONS: usize = 2_000;
+
+/// Every Index is only ever owned by a single Thread at once and no Index is
+/// lost or duplicated while being passed around between the Threads
+#[test]
+fn index_queue_ownership() {
+    const CAPACITY: usize = 16;
+
+    let queue = Arc::new(IndexQueue::new_full(CAPACITY));
+    let owned: Arc<Vec<AtomicBool>> =
+        Arc::new((0..CAPACITY).map(|_| AtomicBool::new(false)).collect());
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let queue = queue.clone();
+            let owned = owned.clone();
+            std::thread::spawn(move || {
+                for _ in 0..ITERATIONS {
+                    let index = loop {
+                        if let Some(i) = queue.dequeue() {
+                            break i;
+                        }
+                        std::thread::yield_now();
+                    };
+
+                    assert!(
+                        !owned[index].swap(true, Ordering::SeqCst),
+                        "Index {} was dequeued twice",
+                        index
+                    );
+                    owned[index].store(false, Ordering::SeqCst);
+
+                    queue.enqueue(index).unwrap();
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let mut remaining: Vec<_> = std::iter::from_fn(|| queue.dequeue()).collect();
+    remaining.sort_unstable();
+    assert_eq!((0..CAPACITY).collect::<Vec<_>>(), remaining);
+}
+
+/// Every Element enqueued by any of the Producers is dequeued exactly once by
+/// one of the Consumers
+#[test]
+fn scq_multi_producer_multi_consumer() {
+    let (rx, tx) = scq::queue::<u64>(32);
+    let rx = Arc::new(rx);
+    let tx = Arc::new(tx);
+
+    let received_sum = Arc::new(AtomicU64::new(0));
+    let received_count = Arc::new(AtomicU64::new(0));
+    let total = (THREADS * ITERATIONS) as u64;
+
+    let producers: Vec<_> = (0..THREADS)
+        .map(|id| {
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                for i in 0..ITERATIONS {
+                    let mut data = (id * ITERATIONS + i) as u64;
+                    while let Err((_, d)) = tx.try_enqueue(data) {
+                        data = d;
+                        std::thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+    let consumers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let rx = rx.clone();
+            let received_sum = received_sum.clone();
+            let received_count = received_count.clone();
+            std::thread::spawn(move || {
+                while received_count.load(Ordering::SeqCst) < total {
+                    match rx.try_dequeue() {
+                        Ok(data) => {
+                            received_sum.fetch_add(data, Ordering::SeqCst);
+                            received_count.fetch_add(1, Ordering::SeqCst);
+                        }
+                        Err(_) => std::thread::yield_now(),
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for handle in producers.into_iter().chain(consumers) {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(total, received_count.load(Ordering::SeqCst));
+    assert_eq!(total * (total - 1) / 2, received_sum.load(Ordering::SeqCst));
+}
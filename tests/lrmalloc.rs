@@ -16,3 +16,132 @@ fn alloc_dealloc() {
     let ptr = unsafe { allocator.alloc(layout) };
     unsafe { allocator.dealloc(ptr, layout) };
 }
+
+#[test]
+fn over_aligned() {
+    let allocator = lrmalloc::Allocator::new();
+
+    for align in [64, 256, 512, 4096] {
+        let layout = Layout::from_size_align(100, align).unwrap();
+
+        let ptr = unsafe { allocator.alloc(layout) };
+        assert_eq!(0, ptr as usize % align, "Alignment {}", align);
+        unsafe { ptr.write_bytes(0xab, layout.size()) };
+        unsafe { allocator.dealloc(ptr, layout) };
+    }
+}
+
+#[test]
+fn large_alloc_dealloc() {
+    let allocator = lrmalloc::Allocator::new();
+
+    let layout = Layout::from_size_align(1 << 20, 64).unwrap();
+
+    for _ in 0..5 {
+        let ptr = unsafe { allocator.alloc(layout) };
+        assert_eq!(0, ptr as usize % 64);
+        unsafe { ptr.write_bytes(0xab, layout.size()) };
+        unsafe { allocator.dealloc(ptr, layout) };
+    }
+}
+
+#[test]
+fn usable_size_matches_allocated_size() {
+    let allocator = lrmalloc::Allocator::new();
+
+    for (size, align) in [
+        (0, 1),
+        (8, 8),
+        (1024, 8),
+        (5500, 16),
+        (100, 512),
+        (1 << 20, 64),
+    ] {
+        let layout = Layout::from_size_align(size, align).unwrap();
+
+        let ptr = unsafe { allocator.alloc(layout) };
+        let usable = unsafe { allocator.usable_size(ptr) };
+        assert!(usable >= size, "Size {} Align {}", size, align);
+        assert_eq!(allocator.allocated_size(layout), usable);
+
+        // The whole usable Size can be written to
+        unsafe { ptr.write_bytes(0xab, usable) };
+        unsafe { allocator.dealloc(ptr, layout) };
+    }
+}
+
+#[test]
+fn allocated_size_rounds_to_size_class() {
+    let allocator = lrmalloc::Allocator::new();
+
+    assert_eq!(1024, allocator.allocated_size(Layout::new::<u64>()));
+    assert_eq!(
+        6144,
+        allocator.allocated_size(Layout::from_size_align(5500, 8).unwrap())
+    );
+    assert_eq!(
+        20000,
+        allocator.allocated_size(Layout::from_size_align(20000, 8).unwrap())
+    );
+}
+
+#[test]
+fn trim_thread_releases_superblock() {
+    // A new Thread starts with an empty Cache, so no other Test interferes
+    std::thread::spawn(|| {
+        let allocator = lrmalloc::Allocator::new();
+        let layout = Layout::new::<usize>();
+
+        let ptr = unsafe { allocator.alloc(layout) };
+        unsafe { allocator.dealloc(ptr, layout) };
+
+        assert!(allocator.trim_thread() >= layout.size());
+        assert_eq!(0, allocator.trim_thread());
+
+        // The Allocator still works normally after trimming
+        let ptr = unsafe { allocator.alloc(layout) };
+        unsafe { allocator.dealloc(ptr, layout) };
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn remote_frees() {
+    let allocator = std::sync::Arc::new(lrmalloc::Allocator::new());
+    let layout = Layout::from_size_align(2000, 8).unwrap();
+
+    // Every Block is allocated on one Thread and freed on another one
+    let (tx, rx) = std::sync::mpsc::channel::<usize>();
+    let producer = {
+        let allocator = allocator.clone();
+        std::thread::spawn(move || {
+            for i in 0..500 {
+                let ptr = unsafe { allocator.alloc(layout) };
+                unsafe { ptr.write_bytes(i as u8, layout.size()) };
+                tx.send(ptr as usize).unwrap();
+            }
+        })
+    };
+    let consumer = {
+        let allocator = allocator.clone();
+        std::thread::spawn(move || {
+            for (i, ptr) in rx.into_iter().enumerate() {
+                let ptr = ptr as *mut u8;
+                assert_eq!(i as u8, unsafe { ptr.add(layout.size() - 1).read() });
+                unsafe { allocator.dealloc(ptr, layout) };
+            }
+        })
+    };
+    producer.join().unwrap();
+    consumer.join().unwrap();
+
+    // The remaining remotely freed Blocks are returned when trimming
+    std::thread::spawn(move || {
+        allocator.trim_thread();
+        let ptr = unsafe { allocator.alloc(layout) };
+        unsafe { allocator.dealloc(ptr, layout) };
+    })
+    .join()
+    .unwrap();
+}
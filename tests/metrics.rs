@@ -0,0 +1,93 @@
+//! Makes sure that the instrumented Queues report their Operations to the
+//! given Metrics
+#![cfg(all(feature = "metrics", not(loom)))]
+
+use std::sync::Arc;
+
+use nolock::queues::{metrics::Counters, mpmc, mpsc, spsc};
+
+#[test]
+fn spsc_bounded() {
+    let counters = Arc::new(Counters::new());
+    let (mut rx, mut tx) = spsc::bounded::queue_with_metrics::<usize>(2, counters.clone());
+
+    assert_eq!(2, tx.try_enqueue_slice(&[1, 2, 3]));
+    assert!(tx.try_enqueue(4).is_err());
+
+    let mut result = Vec::new();
+    assert_eq!(2, rx.try_dequeue_into(&mut result, 2));
+    assert!(rx.try_dequeue().is_err());
+
+    assert_eq!(2, counters.enqueued());
+    assert_eq!(2, counters.dequeued());
+    assert_eq!(1, counters.full());
+    assert_eq!(0, counters.segment_allocs());
+}
+
+#[test]
+fn spsc_unbounded() {
+    let counters = Arc::new(Counters::new());
+    let (mut rx, mut tx) = spsc::unbounded::queue_with_metrics::<usize>(counters.clone());
+
+    for i in 0..100 {
+        tx.enqueue(i).unwrap();
+    }
+    while rx.try_dequeue().is_ok() {}
+
+    assert_eq!(100, counters.enqueued());
+    assert_eq!(100, counters.dequeued());
+    assert_eq!(0, counters.full());
+    assert!(counters.segment_allocs() > 0);
+}
+
+#[test]
+fn jiffy() {
+    let counters = Arc::new(Counters::new());
+    let (mut rx, tx) = mpsc::jiffy::queue_with_metrics::<usize>(
+        mpsc::jiffy::OrderingMode::default(),
+        counters.clone(),
+    );
+
+    for i in 0..100 {
+        tx.enqueue(i).unwrap();
+    }
+    while rx.try_dequeue().is_ok() {}
+
+    assert_eq!(100, counters.enqueued());
+    assert_eq!(100, counters.dequeued());
+    assert!(counters.segment_allocs() > 0);
+}
+
+#[test]
+fn mpmc_bounded() {
+    let counters = Arc::new(Counters::new());
+    let (rx, tx) = mpmc::bounded::scq::queue_with_metrics::<usize>(1, counters.clone());
+
+    tx.try_enqueue(13).unwrap();
+    assert!(tx.try_enqueue(14).is_err());
+    assert_eq!(Ok(13), rx.try_dequeue());
+
+    let (ncq_rx, ncq_tx) = mpmc::bounded::ncq::queue_with_metrics::<usize>(1, counters.clone());
+    ncq_tx.try_enqueue(13).unwrap();
+    assert_eq!(Ok(13), ncq_rx.try_dequeue());
+
+    assert_eq!(2, counters.enqueued());
+    assert_eq!(2, counters.dequeued());
+    assert_eq!(1, counters.full());
+}
+
+#[test]
+#[cfg(feature = "hyaline")]
+fn mpmc_unbounded() {
+    let counters = Arc::new(Counters::new());
+    let (rx, tx) = mpmc::unbounded::queue_with_metrics::<usize>(counters.clone());
+
+    for i in 0..200 {
+        tx.enqueue(i).unwrap();
+    }
+    while rx.try_dequeue().is_ok() {}
+
+    assert_eq!(200, counters.enqueued());
+    assert_eq!(200, counters.dequeued());
+    assert!(counters.segment_allocs() > 0);
+}
@@ -0,0 +1,194 @@
+//! Makes sure that every Element inserted into a Queue is dropped exactly
+//! once, no matter if it was dequeued or still left in the Queue when both
+//! sides were dropped
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Counts the Number of times an Instance has been dropped
+#[derive(Debug)]
+struct DropCounter(Arc<AtomicUsize>);
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// The Orders in which the two Halves of a Queue can be dropped
+#[derive(Debug, Clone, Copy)]
+enum DropOrder {
+    ReceiverFirst,
+    SenderFirst,
+}
+
+const ORDERS: [DropOrder; 2] = [DropOrder::ReceiverFirst, DropOrder::SenderFirst];
+
+fn drop_halves<R, S>(rx: R, tx: S, order: DropOrder) {
+    match order {
+        DropOrder::ReceiverFirst => {
+            drop(rx);
+            drop(tx);
+        }
+        DropOrder::SenderFirst => {
+            drop(tx);
+            drop(rx);
+        }
+    }
+}
+
+#[cfg(feature = "queues")]
+#[test]
+fn spsc_bounded() {
+    use nolock::queues::spsc::bounded;
+
+    for order in ORDERS {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (mut rx, mut tx) = bounded::queue(16);
+
+        for _ in 0..10 {
+            tx.try_enqueue(DropCounter(counter.clone())).unwrap();
+        }
+        for _ in 0..4 {
+            drop(rx.try_dequeue().unwrap());
+        }
+        assert_eq!(4, counter.load(Ordering::SeqCst));
+
+        drop_halves(rx, tx, order);
+        assert_eq!(10, counter.load(Ordering::SeqCst), "{:?}", order);
+    }
+}
+
+#[cfg(feature = "queues")]
+#[test]
+fn spsc_bounded_const() {
+    use nolock::queues::spsc::bounded;
+
+    for order in ORDERS {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (mut rx, mut tx) = bounded::const_queue::<_, 16>();
+
+        for _ in 0..10 {
+            tx.try_enqueue(DropCounter(counter.clone())).unwrap();
+        }
+        for _ in 0..4 {
+            drop(rx.try_dequeue().unwrap());
+        }
+        assert_eq!(4, counter.load(Ordering::SeqCst));
+
+        drop_halves(rx, tx, order);
+        assert_eq!(10, counter.load(Ordering::SeqCst), "{:?}", order);
+    }
+}
+
+#[cfg(feature = "queues")]
+#[test]
+fn spsc_unbounded() {
+    use nolock::queues::spsc::unbounded;
+
+    for order in ORDERS {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (mut rx, mut tx) = unbounded::queue();
+
+        for _ in 0..500 {
+            tx.enqueue(DropCounter(counter.clone())).unwrap();
+        }
+        for _ in 0..100 {
+            drop(rx.try_dequeue().unwrap());
+        }
+        assert_eq!(100, counter.load(Ordering::SeqCst));
+
+        drop_halves(rx, tx, order);
+        assert_eq!(500, counter.load(Ordering::SeqCst), "{:?}", order);
+    }
+}
+
+#[cfg(feature = "queues")]
+#[test]
+fn mpsc_jiffy() {
+    use nolock::queues::mpsc::jiffy;
+
+    for order in ORDERS {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (mut rx, tx) = jiffy::queue();
+
+        for _ in 0..5000 {
+            tx.enqueue(DropCounter(counter.clone())).unwrap();
+        }
+        for _ in 0..1500 {
+            drop(rx.try_dequeue().unwrap());
+        }
+        assert_eq!(1500, counter.load(Ordering::SeqCst));
+
+        drop_halves(rx, tx, order);
+        assert_eq!(5000, counter.load(Ordering::SeqCst), "{:?}", order);
+    }
+}
+
+#[cfg(feature = "queues")]
+#[test]
+fn mpmc_bounded_ncq() {
+    use nolock::queues::mpmc::bounded::ncq;
+
+    for order in ORDERS {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (rx, tx) = ncq::queue(16);
+
+        for _ in 0..10 {
+            tx.try_enqueue(DropCounter(counter.clone())).unwrap();
+        }
+        for _ in 0..4 {
+            drop(rx.try_dequeue().unwrap());
+        }
+        assert_eq!(4, counter.load(Ordering::SeqCst));
+
+        drop_halves(rx, tx, order);
+        assert_eq!(10, counter.load(Ordering::SeqCst), "{:?}", order);
+    }
+}
+
+#[cfg(feature = "queues")]
+#[test]
+fn mpmc_bounded_scq() {
+    use nolock::queues::mpmc::bounded::scq;
+
+    for order in ORDERS {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (rx, tx) = scq::queue(16);
+
+        for _ in 0..10 {
+            tx.try_enqueue(DropCounter(counter.clone())).unwrap();
+        }
+        for _ in 0..4 {
+            drop(rx.try_dequeue().unwrap());
+        }
+        assert_eq!(4, counter.load(Ordering::SeqCst));
+
+        drop_halves(rx, tx, order);
+        assert_eq!(10, counter.load(Ordering::SeqCst), "{:?}", order);
+    }
+}
+
+#[cfg(all(feature = "queues", feature = "hyaline"))]
+#[test]
+fn mpmc_unbounded() {
+    use nolock::queues::mpmc::unbounded;
+
+    for order in ORDERS {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (rx, tx) = unbounded::queue();
+
+        for _ in 0..500 {
+            tx.enqueue(DropCounter(counter.clone())).unwrap();
+        }
+        for _ in 0..100 {
+            drop(rx.try_dequeue().unwrap());
+        }
+        assert_eq!(100, counter.load(Ordering::SeqCst));
+
+        drop_halves(rx, tx, order);
+        assert_eq!(500, counter.load(Ordering::SeqCst), "{:?}", order);
+    }
+}
@@ -0,0 +1,9 @@
+//! Compile-Fail Tests making sure, that the Queues and other Datastructures
+//! can not be used to move Types, that are not Send, across Threads
+#![cfg(all(feature = "full", feature = "hash_trie", not(loom)))]
+
+#[test]
+fn send_sync() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/send_sync/*.rs");
+}
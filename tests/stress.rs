@@ -0,0 +1,199 @@
+//! Long-running Soak-Tests for all the Queues, using the
+//! [`stress`](nolock::test_util::stress) Harness.
+//!
+//! These are ignored by default and should be run in Release-Mode:
+//! `cargo test --release --features test_util --test stress -- --ignored`
+//!
+//! A failed Run can be reproduced by setting the printed Seed in the
+//! `NOLOCK_TEST_SEED` Environment-Variable
+#![cfg(all(feature = "test_util", not(loom)))]
+
+use std::sync::Arc;
+
+use nolock::{
+    queues::{mpmc, mpsc, spsc},
+    test_util::{
+        self,
+        stress::{self, Message, StressConfig},
+    },
+};
+
+/// The Numbers of Producers every multi-producer Queue is tested with
+const PRODUCERS: [usize; 3] = [1, 2, 8];
+
+fn config() -> StressConfig {
+    StressConfig::default()
+}
+
+#[test]
+#[ignore]
+fn spsc_bounded() {
+    let (mut rx, mut tx) = spsc::bounded::queue::<Message>(64);
+    stress::run(
+        test_util::seed_from_env(),
+        &config(),
+        vec![move |msg| tx.try_enqueue(msg).map_err(|(msg, _)| msg)],
+        vec![move || rx.try_dequeue().ok()],
+    );
+}
+
+#[test]
+#[ignore]
+fn spsc_unbounded() {
+    let (mut rx, mut tx) = spsc::unbounded::queue::<Message>();
+    stress::run(
+        test_util::seed_from_env(),
+        &config(),
+        vec![move |msg| tx.enqueue(msg).map_err(|(msg, _)| msg)],
+        vec![move || rx.try_dequeue().ok()],
+    );
+}
+
+#[test]
+#[ignore]
+fn jiffy() {
+    for producers in PRODUCERS {
+        let (mut rx, tx) = mpsc::jiffy::queue::<Message>();
+        let tx = Arc::new(tx);
+
+        stress::run(
+            test_util::seed_from_env(),
+            &config(),
+            (0..producers)
+                .map(|_| {
+                    let tx = tx.clone();
+                    move |msg| tx.enqueue(msg).map_err(|(msg, _)| msg)
+                })
+                .collect(),
+            vec![move || rx.try_dequeue().ok()],
+        );
+    }
+}
+
+#[test]
+#[ignore]
+fn jiffy_bounded() {
+    for producers in PRODUCERS {
+        let (tx, mut rx) = mpsc::jiffy::bounded::channel::<Message>(64);
+        let tx = Arc::new(tx);
+
+        stress::run(
+            test_util::seed_from_env(),
+            &config(),
+            (0..producers)
+                .map(|_| {
+                    let tx = tx.clone();
+                    move |msg| tx.try_enqueue(msg).map_err(|(msg, _)| msg)
+                })
+                .collect(),
+            vec![move || rx.try_dequeue().ok()],
+        );
+    }
+}
+
+#[test]
+#[ignore]
+fn priority() {
+    for producers in PRODUCERS {
+        let (tx, mut rx) = mpsc::priority::channel::<Message>(4);
+
+        stress::run(
+            test_util::seed_from_env(),
+            // Only Messages with the same Priority are ordered
+            &StressConfig {
+                fifo: false,
+                ..config()
+            },
+            (0..producers)
+                .map(|_| {
+                    let tx = tx.clone();
+                    move |msg: Message| tx.enqueue(msg.seq % 4, msg).map_err(|(msg, _)| msg)
+                })
+                .collect(),
+            vec![move || rx.try_dequeue().ok()],
+        );
+    }
+}
+
+#[test]
+#[ignore]
+#[cfg(feature = "hyaline")]
+fn mpmc_unbounded() {
+    for producers in PRODUCERS {
+        for consumers in [1, 4] {
+            let (rx, tx) = mpmc::unbounded::queue::<Message>();
+            let (rx, tx) = (Arc::new(rx), Arc::new(tx));
+
+            stress::run(
+                test_util::seed_from_env(),
+                &config(),
+                (0..producers)
+                    .map(|_| {
+                        let tx = tx.clone();
+                        move |msg| tx.enqueue(msg).map_err(|(msg, _)| msg)
+                    })
+                    .collect(),
+                (0..consumers)
+                    .map(|_| {
+                        let rx = rx.clone();
+                        move || rx.try_dequeue().ok()
+                    })
+                    .collect(),
+            );
+        }
+    }
+}
+
+#[test]
+#[ignore]
+fn mpmc_bounded_ncq() {
+    for producers in PRODUCERS {
+        for consumers in [1, 4] {
+            let (rx, tx) = mpmc::bounded::ncq::queue::<Message>(64);
+
+            stress::run(
+                test_util::seed_from_env(),
+                &config(),
+                (0..producers)
+                    .map(|_| {
+                        let tx = tx.clone();
+                        move |msg| tx.try_enqueue(msg).map_err(|(_, msg)| msg)
+                    })
+                    .collect(),
+                (0..consumers)
+                    .map(|_| {
+                        let rx = rx.clone();
+                        move || rx.try_dequeue().ok()
+                    })
+                    .collect(),
+            );
+        }
+    }
+}
+
+#[test]
+#[ignore]
+fn mpmc_bounded_scq() {
+    for producers in PRODUCERS {
+        for consumers in [1, 4] {
+            let (rx, tx) = mpmc::bounded::scq::queue::<Message>(64);
+
+            stress::run(
+                test_util::seed_from_env(),
+                &config(),
+                (0..producers)
+                    .map(|_| {
+                        let tx = tx.clone();
+                        move |msg| tx.try_enqueue(msg).map_err(|(_, msg)| msg)
+                    })
+                    .collect(),
+                (0..consumers)
+                    .map(|_| {
+                        let rx = rx.clone();
+                        move || rx.try_dequeue().ok()
+                    })
+                    .collect(),
+            );
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use std::{collections::hash_map::RandomState, rc::Rc};
+
+use nolock::hash_trie::HashTrieMap;
+
+fn assert_sync<T: Sync>(_: &T) {}
+
+fn main() {
+    let map = HashTrieMap::<u64, Rc<u8>, RandomState>::new();
+    assert_sync(&map);
+}
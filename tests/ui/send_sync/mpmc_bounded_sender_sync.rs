@@ -0,0 +1,8 @@
+use std::rc::Rc;
+
+fn assert_sync<T: Sync>(_: &T) {}
+
+fn main() {
+    let (_rx, tx) = nolock::queues::mpmc::bounded::scq::queue::<Rc<u8>>(16);
+    assert_sync(&tx);
+}
@@ -0,0 +1,8 @@
+use std::rc::Rc;
+
+fn assert_send<T: Send>(_: &T) {}
+
+fn main() {
+    let (_rx, tx) = nolock::queues::mpmc::unbounded::queue::<Rc<u8>>();
+    assert_send(&tx);
+}
@@ -0,0 +1,8 @@
+use std::rc::Rc;
+
+fn assert_sync<T: Sync>(_: &T) {}
+
+fn main() {
+    let (rx, _tx) = nolock::queues::spsc::bounded::queue::<Rc<u8>>(16);
+    assert_sync(&rx);
+}
@@ -0,0 +1,8 @@
+use std::rc::Rc;
+
+fn assert_send<T: Send>(_: &T) {}
+
+fn main() {
+    let (_rx, tx) = nolock::queues::spsc::bounded::queue::<Rc<u8>>(16);
+    assert_send(&tx);
+}
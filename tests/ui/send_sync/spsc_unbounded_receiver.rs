@@ -0,0 +1,8 @@
+use std::rc::Rc;
+
+fn assert_send<T: Send>(_: &T) {}
+
+fn main() {
+    let (rx, _tx) = nolock::queues::spsc::unbounded::queue::<Rc<u8>>();
+    assert_send(&rx);
+}
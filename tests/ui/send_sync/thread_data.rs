@@ -0,0 +1,10 @@
+use std::rc::Rc;
+
+use nolock::thread_data::ThreadData;
+
+fn assert_sync<T: Sync>(_: &T) {}
+
+fn main() {
+    let data = ThreadData::<Rc<u8>>::new();
+    assert_sync(&data);
+}
@@ -0,0 +1,78 @@
+//! Stress-Tests for the Queues, which inject random Yields at every atomic
+//! Operation. A failed Run can be reproduced by setting the printed Seed in
+//! the `NOLOCK_TEST_SEED` Environment-Variable
+#![cfg(all(feature = "test_util", not(loom)))]
+
+use std::{sync::Arc, thread};
+
+use nolock::{
+    queues::{mpmc, mpsc},
+    test_util,
+};
+
+#[test]
+fn jiffy_producers() {
+    let _injection = test_util::inject_yields(test_util::seed_from_env());
+
+    let (mut rx, tx) = mpsc::jiffy::queue::<(usize, usize)>();
+    let tx = Arc::new(tx);
+
+    let producers: Vec<_> = (0..4)
+        .map(|producer| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..2000 {
+                    tx.enqueue((producer, i)).unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let mut expected = [0; 4];
+    for _ in 0..(4 * 2000) {
+        let (producer, i) = rx.dequeue().unwrap();
+        assert_eq!(expected[producer], i);
+        expected[producer] += 1;
+    }
+
+    for handle in producers {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn scq_producers_consumers() {
+    let _injection = test_util::inject_yields(test_util::seed_from_env());
+
+    let (rx, tx) = mpmc::bounded::scq::queue::<usize>(16);
+    let rx = Arc::new(rx);
+
+    let consumers: Vec<_> = (0..2)
+        .map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || {
+                let mut sum = 0;
+                let mut received = 0;
+                while received < 1000 {
+                    if let Ok(data) = rx.try_dequeue() {
+                        sum += data;
+                        received += 1;
+                    }
+                }
+                sum
+            })
+        })
+        .collect();
+
+    for i in 0..2000 {
+        let mut data = i;
+        while let Err((_, d)) = tx.try_enqueue(data) {
+            data = d;
+            thread::yield_now();
+        }
+    }
+
+    let sum: usize = consumers.into_iter().map(|h| h.join().unwrap()).sum();
+    assert_eq!((0..2000).sum::<usize>(), sum);
+}